@@ -38,8 +38,8 @@ fn make_keys(
 ) {
     let keygen = KeyGenerator::new(context).unwrap();
 
-    let public_key = keygen.create_public_key();
-    let private_key = keygen.secret_key();
+    let public_key = keygen.create_public_key().unwrap();
+    let private_key = keygen.secret_key().unwrap();
     let relin_keys = match keygen.create_relinearization_keys() {
         Ok(v) => Some(v),
         Err(_) => None,
@@ -6,6 +6,7 @@ use std::process::{Command, Stdio};
 pub struct Config {
     defines: HashMap<String, String>,
     emcc_args: Vec<String>,
+    out_dir: Option<PathBuf>,
     path: PathBuf,
 }
 
@@ -15,6 +16,7 @@ impl Config {
             path: path.as_ref().canonicalize().unwrap(),
             defines: HashMap::new(),
             emcc_args: vec![],
+            out_dir: None,
         }
     }
 
@@ -28,8 +30,18 @@ impl Config {
         self
     }
 
+    pub fn out_dir<P: AsRef<Path>>(mut self, out_dir: P) -> Self {
+        self.out_dir = Some(out_dir.as_ref().to_owned());
+        self
+    }
+
     pub fn build(self) -> PathBuf {
-        let output_directory = PathBuf::from(std::env::var("OUT_DIR").unwrap());
+        let output_directory = self
+            .out_dir
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(std::env::var("OUT_DIR").unwrap()));
+
+        std::fs::create_dir_all(&output_directory).unwrap();
 
         let script_path = output_directory.join("build.sh");
         let mut script = std::fs::File::create(&script_path).unwrap();
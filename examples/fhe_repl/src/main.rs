@@ -0,0 +1,430 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Editor, Helper};
+use seal_fhe::{
+    BFVEncoder, BFVEvaluator, Ciphertext, Context, Decryptor, Encryptor, Evaluator, GaloisKeys,
+    KeyGenerator, ParameterSelector, RelinearizationKeys, SecurityLevel,
+};
+
+/**
+ * The commands the REPL understands along with their arguments and a short
+ * description.
+ */
+const COMMANDS: &[(&str, &str, &str)] = &[
+    ("help", "", "Show this message"),
+    ("exit", "", "Quit"),
+    (
+        "params",
+        "<depth> <plain_bits>",
+        "Create a context and keys for the given multiplicative depth and plaintext modulus size",
+    ),
+    (
+        "encrypt",
+        "<name> <v0,v1,...>",
+        "Encrypt the given values into the first slots of a new ciphertext",
+    ),
+    (
+        "decrypt",
+        "<name> [count]",
+        "Decrypt and print the first `count` slots",
+    ),
+    ("add", "<dst> <a> <b>", "dst = a + b"),
+    ("sub", "<dst> <a> <b>", "dst = a - b"),
+    ("mul", "<dst> <a> <b>", "dst = a * b"),
+    ("square", "<dst> <a>", "dst = a * a"),
+    ("neg", "<dst> <a>", "dst = -a"),
+    ("relin", "<name>", "Relinearize a ciphertext in place"),
+    (
+        "rotate",
+        "<dst> <a> <steps>",
+        "Rotate the rows of a left (positive) or right (negative)",
+    ),
+    (
+        "modswitch",
+        "<name>",
+        "Switch a ciphertext to the next modulus in place",
+    ),
+    (
+        "noise",
+        "<name>",
+        "Show the noise budget, invariant noise, level and size of a ciphertext",
+    ),
+    ("vars", "", "List the ciphertexts and their noise budgets"),
+    ("drop", "<name>", "Forget a ciphertext"),
+];
+
+fn help() {
+    println!("This is an interactive BFV evaluator. Create a context with `params`, encrypt some values and apply operations to them while watching how each one consumes noise budget.");
+    println!("Press tab to complete command and ciphertext names.");
+    println!();
+
+    for (name, args, description) in COMMANDS {
+        println!("  {:<10} {:<22} {}", name, args, description);
+    }
+
+    println!();
+    println!("Example:");
+    println!(">> params 2 20");
+    println!(">> encrypt a 1,2,3");
+    println!(">> mul b a a");
+    println!(">> noise b");
+    println!(">> relin b");
+    println!(">> decrypt b 3");
+    println!("[1, 4, 9]");
+    println!();
+}
+
+/**
+ * Completes command names in the first word and ciphertext names
+ * afterwards.
+ */
+struct ReplHelper {
+    names: Rc<RefCell<Vec<String>>>,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let start = line[..pos].rfind(' ').map(|i| i + 1).unwrap_or(0);
+        let word = &line[start..pos];
+
+        let candidates = if start == 0 {
+            COMMANDS
+                .iter()
+                .map(|(name, _, _)| name.to_string())
+                .filter(|name| name.starts_with(word))
+                .collect()
+        } else {
+            self.names
+                .borrow()
+                .iter()
+                .filter(|name| name.starts_with(word))
+                .cloned()
+                .collect()
+        };
+
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+/**
+ * A context along with everything needed to encrypt, evaluate and decrypt
+ * under it.
+ */
+struct Session {
+    context: Context,
+    encoder: BFVEncoder,
+    encryptor: Encryptor,
+    decryptor: Decryptor,
+    evaluator: BFVEvaluator,
+    relin_keys: RelinearizationKeys,
+    galois_keys: GaloisKeys,
+}
+
+impl Session {
+    fn new(depth: u32, plain_modulus_bits: u32) -> Result<Self, String> {
+        let params = ParameterSelector::new()
+            .set_depth(depth)
+            .set_plain_modulus_bits(plain_modulus_bits)
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        println!(
+            "Polynomial degree {}, plaintext modulus {}, coefficient modulus {:?} bits",
+            params.get_poly_modulus_degree(),
+            params.get_plain_modulus().value(),
+            params
+                .get_coefficient_modulus()
+                .iter()
+                .map(|m| 64 - m.value().leading_zeros())
+                .collect::<Vec<_>>()
+        );
+
+        let context =
+            Context::new(&params, true, SecurityLevel::TC128).map_err(|e| e.to_string())?;
+
+        let keygen = KeyGenerator::new(&context).map_err(|e| e.to_string())?;
+        let public_key = keygen.create_public_key().map_err(|e| e.to_string())?;
+        let secret_key = keygen.secret_key().map_err(|e| e.to_string())?;
+        let relin_keys = keygen
+            .create_relinearization_keys()
+            .map_err(|e| e.to_string())?;
+        let galois_keys = keygen.create_galois_keys().map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            encoder: BFVEncoder::new(&context).map_err(|e| e.to_string())?,
+            encryptor: Encryptor::with_public_key(&context, &public_key)
+                .map_err(|e| e.to_string())?,
+            decryptor: Decryptor::new(&context, &secret_key).map_err(|e| e.to_string())?,
+            evaluator: BFVEvaluator::new(&context).map_err(|e| e.to_string())?,
+            context,
+            relin_keys,
+            galois_keys,
+        })
+    }
+}
+
+struct Repl {
+    session: Option<Session>,
+    vars: BTreeMap<String, Ciphertext>,
+    names: Rc<RefCell<Vec<String>>>,
+}
+
+fn parse<T: std::str::FromStr>(arg: &str) -> Result<T, String> {
+    arg.parse::<T>()
+        .map_err(|_| format!("Could not parse `{}`", arg))
+}
+
+impl Repl {
+    fn session(&self) -> Result<&Session, String> {
+        self.session
+            .as_ref()
+            .ok_or_else(|| "Create a context with `params` first".to_owned())
+    }
+
+    fn var(&self, name: &str) -> Result<&Ciphertext, String> {
+        self.vars
+            .get(name)
+            .ok_or_else(|| format!("No ciphertext named `{}`", name))
+    }
+
+    fn set_var(&mut self, name: &str, c: Ciphertext) -> Result<(), String> {
+        self.vars.insert(name.to_owned(), c);
+        *self.names.borrow_mut() = self.vars.keys().cloned().collect();
+
+        self.print_noise(name)
+    }
+
+    fn print_noise(&self, name: &str) -> Result<(), String> {
+        let session = self.session()?;
+        let c = self.var(name)?;
+
+        let budget = session
+            .decryptor
+            .invariant_noise_budget(c)
+            .map_err(|e| e.to_string())?;
+        let noise = session
+            .decryptor
+            .invariant_noise(c)
+            .map_err(|e| e.to_string())?;
+        let level = session
+            .context
+            .get_chain_index(&c.get_parms_id())
+            .map_err(|e| e.to_string())?;
+
+        println!(
+            "{}: noise budget {} bits, invariant noise {:.3e}, level {}, size {}",
+            name,
+            budget,
+            noise,
+            level,
+            c.num_polynomials()
+        );
+
+        if budget == 0 {
+            println!(
+                "Warning: `{}` has no noise budget left and won't decrypt correctly.",
+                name
+            );
+        }
+
+        Ok(())
+    }
+
+    fn binary<F>(&mut self, args: &[&str], op: F) -> Result<(), String>
+    where
+        F: Fn(&BFVEvaluator, &Ciphertext, &Ciphertext) -> seal_fhe::Result<Ciphertext>,
+    {
+        let [dst, a, b] = match args {
+            [dst, a, b] => [*dst, *a, *b],
+            _ => return Err("Expected <dst> <a> <b>".to_owned()),
+        };
+
+        let c = op(&self.session()?.evaluator, self.var(a)?, self.var(b)?)
+            .map_err(|e| e.to_string())?;
+
+        self.set_var(dst, c)
+    }
+
+    fn unary<F>(&mut self, args: &[&str], op: F) -> Result<(), String>
+    where
+        F: Fn(&Session, &Ciphertext) -> seal_fhe::Result<Ciphertext>,
+    {
+        let (dst, a) = match args {
+            [dst, a] => (*dst, *a),
+            [a] => (*a, *a),
+            _ => return Err("Expected [dst] <a>".to_owned()),
+        };
+
+        let c = op(self.session()?, self.var(a)?).map_err(|e| e.to_string())?;
+
+        self.set_var(dst, c)
+    }
+
+    /**
+     * Runs a single line of input. Returns false if the user asked to exit.
+     */
+    fn run(&mut self, line: &str) -> Result<bool, String> {
+        let mut words = line.split_whitespace();
+
+        let command = match words.next() {
+            Some(c) => c,
+            None => return Ok(true),
+        };
+
+        let args = words.collect::<Vec<_>>();
+
+        match (command, args.as_slice()) {
+            ("help", _) => help(),
+            ("exit", _) => return Ok(false),
+            ("params", [depth, bits]) => {
+                self.session = Some(Session::new(parse(depth)?, parse(bits)?)?);
+                self.vars.clear();
+                self.names.borrow_mut().clear();
+            }
+            ("encrypt", [name, values]) => {
+                let session = self.session()?;
+
+                let mut data = values
+                    .split(',')
+                    .map(parse::<i64>)
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                if data.len() > session.encoder.get_slot_count() {
+                    return Err(format!(
+                        "Too many values, the context has {} slots",
+                        session.encoder.get_slot_count()
+                    ));
+                }
+
+                data.resize(session.encoder.get_slot_count(), 0);
+
+                let p = session
+                    .encoder
+                    .encode_signed(&data)
+                    .map_err(|e| e.to_string())?;
+                let c = session.encryptor.encrypt(&p).map_err(|e| e.to_string())?;
+
+                self.set_var(name, c)?;
+            }
+            ("decrypt", [name, rest @ ..]) if rest.len() <= 1 => {
+                let session = self.session()?;
+                let count = match rest {
+                    [count] => parse(count)?,
+                    _ => 8,
+                };
+
+                let p = session
+                    .decryptor
+                    .decrypt(self.var(name)?)
+                    .map_err(|e| e.to_string())?;
+                let data = session
+                    .encoder
+                    .decode_signed(&p)
+                    .map_err(|e| e.to_string())?;
+
+                println!("{:?}", &data[..count.min(data.len())]);
+            }
+            ("add", args) => self.binary(args, |e, a, b| e.add(a, b))?,
+            ("sub", args) => self.binary(args, |e, a, b| e.sub(a, b))?,
+            ("mul", args) => self.binary(args, |e, a, b| e.multiply(a, b))?,
+            ("square", args) => self.unary(args, |s, a| s.evaluator.square(a))?,
+            ("neg", args) => self.unary(args, |s, a| s.evaluator.negate(a))?,
+            ("relin", args) => {
+                self.unary(args, |s, a| s.evaluator.relinearize(a, &s.relin_keys))?
+            }
+            ("modswitch", args) => self.unary(args, |s, a| s.evaluator.mod_switch_to_next(a))?,
+            ("rotate", [dst, a, steps]) => {
+                let steps = parse::<i32>(steps)?;
+                let session = self.session()?;
+
+                let c = session
+                    .evaluator
+                    .rotate_rows(self.var(a)?, steps, &session.galois_keys)
+                    .map_err(|e| e.to_string())?;
+
+                self.set_var(dst, c)?;
+            }
+            ("noise", [name]) => self.print_noise(name)?,
+            ("vars", []) => {
+                for name in self.vars.keys() {
+                    self.print_noise(name)?;
+                }
+            }
+            ("drop", [name]) => {
+                self.vars.remove(*name);
+                *self.names.borrow_mut() = self.vars.keys().cloned().collect();
+            }
+            (command, _) => match COMMANDS.iter().find(|(name, _, _)| *name == command) {
+                Some((name, args, _)) => return Err(format!("Usage: {} {}", name, args)),
+                None => {
+                    return Err(format!(
+                        "Unknown command `{}`. Type help for help.",
+                        command
+                    ))
+                }
+            },
+        };
+
+        Ok(true)
+    }
+}
+
+fn main() {
+    help();
+
+    let names = Rc::new(RefCell::new(vec![]));
+
+    let mut editor = Editor::<ReplHelper>::new();
+    editor.set_helper(Some(ReplHelper {
+        names: names.clone(),
+    }));
+
+    let mut repl = Repl {
+        session: None,
+        vars: BTreeMap::new(),
+        names,
+    };
+
+    loop {
+        let line = match editor.readline(">> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => break,
+            Err(e) => {
+                println!("{}", e);
+                break;
+            }
+        };
+
+        editor.add_history_entry(line.as_str());
+
+        match repl.run(&line) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) => println!("Error: {}", e),
+        }
+    }
+}
@@ -0,0 +1,513 @@
+use ark_ff::{FftField, Field};
+use ark_poly::univariate::DensePolynomial;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use merlin::Transcript;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    crypto::CryptoHash,
+    fields::FpRistretto,
+    inner_product,
+    linear_algebra::Matrix,
+    linear_relation::{LogProof, ProverKnowledge, VerifierKnowledge},
+    math::{FieldModulus, ModSwitch, SmartMul, Zero},
+    LogProofGenerators, ProofError,
+};
+
+type MatrixPoly<Q> = Matrix<DensePolynomial<Q>>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/**
+ * One segment of a chunked proof of `AS=T`, proving the relation for a
+ * contiguous range of the columns of `S` and `T` (e.g. one column per
+ * ciphertext).
+ */
+pub struct LogProofSegment {
+    /**
+     * The position of this segment in the stream, starting at 0.
+     */
+    pub index: u64,
+
+    /**
+     * The number of columns of `T` this segment covers.
+     */
+    pub columns: u64,
+
+    /**
+     * Whether this is the final segment.
+     */
+    pub last: bool,
+
+    /**
+     * The proof for this segment's columns.
+     */
+    pub proof: LogProof,
+}
+
+/**
+ * Appends a segment's header to the transcript shared by every segment.
+ *
+ * # Remarks
+ * The shared transcript is the accumulator binding the segments together:
+ * each segment's challenges depend on every prior segment's statement and
+ * commitments, so segments can't be reordered, dropped, spliced from
+ * another proof or verified out of context.
+ */
+fn append_segment_header(transcript: &mut Transcript, index: u64, columns: u64, last: bool) {
+    transcript.append_message(b"dom-sep", b"chunked-linear-relation-segment");
+    transcript.append_u64(b"segment-index", index);
+    transcript.append_u64(b"segment-columns", columns);
+    transcript.append_u64(b"segment-last", last as u64);
+}
+
+/**
+ * Horizontally concatenates single or multi-column matrices with the same
+ * number of rows.
+ */
+fn concat_columns<Q>(parts: &[MatrixPoly<Q>]) -> MatrixPoly<Q>
+where
+    Q: Field,
+{
+    let rows = parts[0].rows;
+    let cols = parts.iter().map(|p| p.cols).sum();
+
+    let mut out = MatrixPoly::<Q>::new(rows, cols);
+    let mut offset = 0;
+
+    for p in parts {
+        assert_eq!(p.rows, rows);
+
+        for i in 0..p.rows {
+            for j in 0..p.cols {
+                out[(i, offset + j)] = p[(i, j)].clone();
+            }
+        }
+
+        offset += p.cols;
+    }
+
+    out
+}
+
+/**
+ * Builds a proof of `AS=T` for many columns (e.g. hundreds of ciphertexts)
+ * as a stream of [`LogProofSegment`]s covering at most `max_columns`
+ * columns each.
+ *
+ * # Remarks
+ * A single [`LogProof`] over every column needs generators and witness
+ * vectors linear in the total number of columns. Here, the prover only
+ * holds one segment's columns and generators at a time, so memory stays
+ * bounded by `max_columns` regardless of the statement's size. Proofs are
+ * correspondingly larger, as each segment carries its own [`LogProof`].
+ *
+ * Push columns with [`push`](Self::push) and send every segment it
+ * returns, then send the one [`finish`](Self::finish) returns.
+ */
+pub struct ChunkedProver<Q>
+where
+    Q: Field + CryptoHash + ModSwitch<FpRistretto> + FieldModulus<4>,
+{
+    transcript: Transcript,
+    a: MatrixPoly<Q>,
+    f: DensePolynomial<Q>,
+    bound: u64,
+    max_columns: usize,
+    pending_s: Vec<MatrixPoly<Q>>,
+    pending_t: Vec<MatrixPoly<Q>>,
+    index: u64,
+    generators: LogProofGenerators,
+    u: RistrettoPoint,
+}
+
+impl<Q> ChunkedProver<Q>
+where
+    Q: Field
+        + ModSwitch<FpRistretto>
+        + FftField
+        + SmartMul<Q, Output = Q>
+        + CryptoHash
+        + Zero
+        + FieldModulus<4>,
+{
+    /**
+     * Creates a prover for statements `AS=T` in `Z_q[X]/f` where `bound`
+     * bounds every coefficient in `S`. The verifier must start from an
+     * identical transcript.
+     *
+     * # Panics
+     * If `max_columns` is zero.
+     */
+    pub fn new(
+        transcript: Transcript,
+        a: &MatrixPoly<Q>,
+        f: &DensePolynomial<Q>,
+        bound: u64,
+        max_columns: usize,
+    ) -> Self {
+        assert!(max_columns > 0);
+
+        let generators = segment_generators(a, f, bound, max_columns);
+
+        Self {
+            transcript,
+            a: a.clone(),
+            f: f.clone(),
+            bound,
+            max_columns,
+            pending_s: vec![],
+            pending_t: vec![],
+            index: 0,
+            generators,
+            u: inner_product::VerifierKnowledge::get_u(),
+        }
+    }
+
+    /**
+     * Adds the columns `s` and `t` where `As=t` to the statement. Returns
+     * the previous segment if it was full.
+     *
+     * # Panics
+     * If `s` and `t` don't have the same number of columns.
+     */
+    pub fn push(&mut self, s: &MatrixPoly<Q>, t: &MatrixPoly<Q>) -> Option<LogProofSegment> {
+        assert_eq!(s.cols, t.cols);
+
+        let pending = self.pending_t.iter().map(|t| t.cols).sum::<usize>();
+
+        // Segments are emitted lazily so the final one is always marked
+        // as such.
+        let segment = if pending > 0 && pending + t.cols > self.max_columns {
+            Some(self.prove_pending(false))
+        } else {
+            None
+        };
+
+        self.pending_s.push(s.clone());
+        self.pending_t.push(t.clone());
+
+        segment
+    }
+
+    /**
+     * Proves the remaining columns as the final segment.
+     *
+     * # Panics
+     * If no columns were pushed.
+     */
+    pub fn finish(mut self) -> LogProofSegment {
+        self.prove_pending(true)
+    }
+
+    fn prove_pending(&mut self, last: bool) -> LogProofSegment {
+        assert!(!self.pending_t.is_empty(), "No columns to prove");
+
+        let s = concat_columns(&self.pending_s);
+        let t = concat_columns(&self.pending_t);
+
+        self.pending_s.clear();
+        self.pending_t.clear();
+
+        let pk = ProverKnowledge::new(&self.a, &s, &t, self.bound, &self.f);
+        let l = pk.vk.l() as usize;
+
+        // A segment wider than max_columns needs more generators.
+        if l > self.generators.g.len() {
+            self.generators = LogProofGenerators::new(l);
+        }
+
+        append_segment_header(&mut self.transcript, self.index, t.cols as u64, last);
+
+        let proof = LogProof::create(
+            &mut self.transcript,
+            &pk,
+            &self.generators.g[..l],
+            &self.generators.h[..l],
+            &self.u,
+        );
+
+        let segment = LogProofSegment {
+            index: self.index,
+            columns: t.cols as u64,
+            last,
+            proof,
+        };
+
+        self.index += 1;
+
+        segment
+    }
+}
+
+/**
+ * Verifies a stream of [`LogProofSegment`]s created by a
+ * [`ChunkedProver`], one segment at a time.
+ *
+ * # Remarks
+ * Only [`finish`](Self::finish) returning `Ok` means the whole statement
+ * holds; it fails if the stream ended before its final segment.
+ */
+pub struct ChunkedVerifier<Q>
+where
+    Q: Field + CryptoHash + ModSwitch<FpRistretto> + FieldModulus<4>,
+{
+    transcript: Transcript,
+    a: MatrixPoly<Q>,
+    f: DensePolynomial<Q>,
+    bound: u64,
+    next_index: u64,
+    finished: bool,
+    complete: bool,
+    generators: LogProofGenerators,
+    u: RistrettoPoint,
+}
+
+impl<Q> ChunkedVerifier<Q>
+where
+    Q: Field + CryptoHash + ModSwitch<FpRistretto> + FieldModulus<4> + Zero,
+{
+    /**
+     * Creates a verifier for the statement the [`ChunkedProver`] with the
+     * same arguments proves.
+     */
+    pub fn new(
+        transcript: Transcript,
+        a: &MatrixPoly<Q>,
+        f: &DensePolynomial<Q>,
+        bound: u64,
+        max_columns: usize,
+    ) -> Self {
+        Self {
+            transcript,
+            a: a.clone(),
+            f: f.clone(),
+            bound,
+            next_index: 0,
+            finished: false,
+            complete: false,
+            generators: segment_generators(a, f, bound, max_columns.max(1)),
+            u: inner_product::VerifierKnowledge::get_u(),
+        }
+    }
+
+    /**
+     * Verifies the next segment against `t`, the columns of the public
+     * `T` it covers.
+     *
+     * # Remarks
+     * Returns [`ProofError::MalformedProof`] if the segment is out of
+     * order, follows the final segment or doesn't match `t`'s dimensions.
+     * Once a segment fails, the stream can't be verified further.
+     */
+    pub fn verify_segment(
+        &mut self,
+        segment: &LogProofSegment,
+        t: &MatrixPoly<Q>,
+    ) -> Result<(), ProofError> {
+        if self.finished
+            || segment.index != self.next_index
+            || segment.columns != t.cols as u64
+            || t.cols == 0
+            || t.rows != self.a.rows
+        {
+            self.finished = true;
+            return Err(ProofError::MalformedProof);
+        }
+
+        let vk = VerifierKnowledge::new(self.a.clone(), t.clone(), self.f.clone(), self.bound);
+        let l = vk.l() as usize;
+
+        if l > self.generators.g.len() {
+            self.generators = LogProofGenerators::new(l);
+        }
+
+        append_segment_header(
+            &mut self.transcript,
+            segment.index,
+            segment.columns,
+            segment.last,
+        );
+
+        let result = segment.proof.verify(
+            &mut self.transcript,
+            &vk,
+            &self.generators.g[..l],
+            &self.generators.h[..l],
+            &self.u,
+        );
+
+        self.next_index += 1;
+        self.finished = segment.last || result.is_err();
+        self.complete = segment.last && result.is_ok();
+
+        result
+    }
+
+    /**
+     * Completes verification. Returns [`ProofError::VerificationError`]
+     * unless the final segment verified.
+     */
+    pub fn finish(self) -> Result<(), ProofError> {
+        if self.complete {
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+}
+
+/**
+ * Creates the generators for a full segment of `max_columns` columns.
+ * Narrower segments use a prefix of them.
+ */
+fn segment_generators<Q>(
+    a: &MatrixPoly<Q>,
+    f: &DensePolynomial<Q>,
+    bound: u64,
+    max_columns: usize,
+) -> LogProofGenerators
+where
+    Q: Field + CryptoHash + ModSwitch<FpRistretto> + FieldModulus<4>,
+{
+    let t = MatrixPoly::<Q>::new(a.rows, max_columns);
+    let vk = VerifierKnowledge::new(a.clone(), t, f.clone(), bound);
+
+    LogProofGenerators::new(vk.l() as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fields::FqSeal128_8192, linear_algebra::ScalarRem, math::make_poly};
+
+    type Q = FqSeal128_8192;
+
+    fn statement(
+        columns: usize,
+    ) -> (
+        MatrixPoly<Q>,
+        Vec<(MatrixPoly<Q>, MatrixPoly<Q>)>,
+        DensePolynomial<Q>,
+    ) {
+        let a = MatrixPoly::from([
+            [
+                make_poly::<Q>(&[1, 2, 3, 4, 5, 6, 7, 8]),
+                make_poly::<Q>(&[1]),
+                make_poly::<Q>(&[2]),
+            ],
+            [
+                make_poly::<Q>(&[0]),
+                make_poly::<Q>(&[1]),
+                make_poly::<Q>(&[2]),
+            ],
+        ]);
+
+        // x^8 + 1
+        let f = make_poly::<Q>(&[1, 0, 0, 0, 0, 0, 0, 0, 1]);
+
+        let cols = (0..columns as u64)
+            .map(|i| {
+                let s = MatrixPoly::from([
+                    [make_poly::<Q>(&[i, 2, 3, 4, 5, 6, 7, 8])],
+                    [make_poly::<Q>(&[1, 0, i, 0, 1, 0, 1])],
+                    [make_poly::<Q>(&[0, 1, 0, 1, i, 0, 1])],
+                ]);
+
+                let t = (&a * &s).scalar_rem(&f);
+
+                (s, t)
+            })
+            .collect();
+
+        (a, cols, f)
+    }
+
+    fn prove(max_columns: usize, columns: usize) -> Vec<LogProofSegment> {
+        let (a, cols, f) = statement(columns);
+        let mut prover = ChunkedProver::new(Transcript::new(b"test"), &a, &f, 16, max_columns);
+
+        let mut segments = cols
+            .iter()
+            .filter_map(|(s, t)| prover.push(s, t))
+            .collect::<Vec<_>>();
+
+        segments.push(prover.finish());
+
+        segments
+    }
+
+    fn verify(
+        max_columns: usize,
+        columns: usize,
+        segments: &[LogProofSegment],
+    ) -> Result<(), ProofError> {
+        let (a, cols, f) = statement(columns);
+        let mut verifier = ChunkedVerifier::new(Transcript::new(b"test"), &a, &f, 16, max_columns);
+
+        let mut offset = 0;
+
+        for segment in segments {
+            let t = concat_columns(
+                &cols[offset..offset + segment.columns as usize]
+                    .iter()
+                    .map(|(_, t)| t.clone())
+                    .collect::<Vec<_>>(),
+            );
+
+            offset += segment.columns as usize;
+
+            verifier.verify_segment(segment, &t)?;
+        }
+
+        verifier.finish()
+    }
+
+    #[test]
+    fn can_prove_and_verify_segments() {
+        let segments = prove(2, 5);
+
+        assert_eq!(
+            segments.iter().map(|s| s.columns).collect::<Vec<_>>(),
+            vec![2, 2, 1]
+        );
+        assert_eq!(
+            segments.iter().map(|s| s.last).collect::<Vec<_>>(),
+            vec![false, false, true]
+        );
+
+        verify(2, 5, &segments).unwrap();
+    }
+
+    #[test]
+    fn exact_multiple_marks_final_segment() {
+        let segments = prove(2, 4);
+
+        assert_eq!(segments.len(), 2);
+        assert!(segments[1].last);
+
+        verify(2, 4, &segments).unwrap();
+    }
+
+    #[test]
+    fn truncated_stream_fails() {
+        let segments = prove(2, 5);
+
+        assert!(verify(2, 5, &segments[..2]).is_err());
+    }
+
+    #[test]
+    fn reordered_segments_fail() {
+        let mut segments = prove(2, 4);
+
+        segments.swap(0, 1);
+
+        assert!(verify(2, 4, &segments).is_err());
+
+        // Relabelling the indices doesn't help, as the transcript binds
+        // each segment to its predecessors.
+        segments[0].index = 0;
+        segments[1].index = 1;
+
+        assert!(verify(2, 4, &segments).is_err());
+    }
+}
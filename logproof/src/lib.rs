@@ -14,6 +14,9 @@
 
 mod assertions;
 
+mod chunked;
+pub use chunked::{ChunkedProver, ChunkedVerifier, LogProofSegment};
+
 /**
  * Contains traits relating to cryptographic operations.
  */
@@ -0,0 +1,88 @@
+//! Times individual SEAL operations at several polynomial degrees. Run with
+//! `cargo bench -p seal_fhe --features bench-suite`, adding features such as
+//! `hexl` to compare builds.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use seal_fhe::{
+    BFVEncoder, BFVEvaluator, BfvEncryptionParametersBuilder, CoefficientModulus, Context,
+    Encryptor, Evaluator, KeyGenerator, PlainModulus, SecurityLevel,
+};
+
+const DEGREES: [u64; 3] = [4096, 8192, 16384];
+
+struct Setup {
+    encoder: BFVEncoder,
+    encryptor: Encryptor,
+    evaluator: BFVEvaluator,
+    keygen: KeyGenerator,
+    data: Vec<u64>,
+}
+
+fn setup(degree: u64) -> Setup {
+    let params = BfvEncryptionParametersBuilder::new()
+        .set_poly_modulus_degree(degree)
+        .set_coefficient_modulus(
+            CoefficientModulus::bfv_default(degree, SecurityLevel::TC128).unwrap(),
+        )
+        .set_plain_modulus(PlainModulus::batching(degree, 20).unwrap())
+        .build()
+        .unwrap();
+
+    let context = Context::new(&params, true, SecurityLevel::TC128).unwrap();
+    let keygen = KeyGenerator::new(&context).unwrap();
+
+    Setup {
+        encoder: BFVEncoder::new(&context).unwrap(),
+        encryptor: Encryptor::with_public_key(&context, &keygen.create_public_key().unwrap())
+            .unwrap(),
+        evaluator: BFVEvaluator::new(&context).unwrap(),
+        keygen,
+        data: (0..degree).map(|x| x % 1024).collect(),
+    }
+}
+
+fn ops(c: &mut Criterion) {
+    let mut group = c.benchmark_group("seal_ops");
+
+    for degree in DEGREES {
+        let s = setup(degree);
+
+        let plaintext = s.encoder.encode_unsigned(&s.data).unwrap();
+        let a = s.encryptor.encrypt(&plaintext).unwrap();
+        let b = s.encryptor.encrypt(&plaintext).unwrap();
+        let product = s.evaluator.multiply(&a, &b).unwrap();
+        let relin_keys = s.keygen.create_relinearization_keys().unwrap();
+        let galois_keys = s.keygen.create_galois_keys().unwrap();
+
+        group.bench_with_input(BenchmarkId::new("encode", degree), &s.data, |bench, d| {
+            bench.iter(|| s.encoder.encode_unsigned(d).unwrap())
+        });
+
+        group.bench_with_input(
+            BenchmarkId::new("encrypt", degree),
+            &plaintext,
+            |bench, p| bench.iter(|| s.encryptor.encrypt(p).unwrap()),
+        );
+
+        group.bench_function(BenchmarkId::new("add", degree), |bench| {
+            bench.iter(|| s.evaluator.add(&a, &b).unwrap())
+        });
+
+        group.bench_function(BenchmarkId::new("multiply", degree), |bench| {
+            bench.iter(|| s.evaluator.multiply(&a, &b).unwrap())
+        });
+
+        group.bench_function(BenchmarkId::new("relinearize", degree), |bench| {
+            bench.iter(|| s.evaluator.relinearize(&product, &relin_keys).unwrap())
+        });
+
+        group.bench_function(BenchmarkId::new("rotate", degree), |bench| {
+            bench.iter(|| s.evaluator.rotate_rows(&a, 1, &galois_keys).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, ops);
+criterion_main!(benches);
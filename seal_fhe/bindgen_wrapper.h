@@ -23,3 +23,4 @@
 #include "seal/c/stdafx.h"
 //#include "seal/c/utilities.h"
 #include "seal/c/valcheck.h"
+#include "shim/shim.h"
@@ -3,12 +3,20 @@ use emsdk::Config as EmConfig;
 
 use std::path::{Path, PathBuf};
 
-fn compile_native(profile: &str, out_path: &Path) {
-    let hexl = if std::env::var("CARGO_FEATURE_HEXL").is_ok() {
+fn feature_flag(feature: &str) -> &'static str {
+    if std::env::var(format!("CARGO_FEATURE_{}", feature)).is_ok() {
         "ON"
     } else {
         "OFF"
-    };
+    }
+}
+
+fn seal_source_dir() -> PathBuf {
+    PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap()).join("SEAL/native/src")
+}
+
+fn compile_native(profile: &str, out_path: &Path) {
+    let hexl = feature_flag("HEXL");
 
     let dst = Config::new("SEAL")
         .define("CMAKE_BUILD_TYPE", profile)
@@ -25,8 +33,8 @@ fn compile_native(profile: &str, out_path: &Path) {
         .define("SEAL_USE_CXX17", "ON")
         .define("SEAL_USE_INTRIN", "ON")
         .define("SEAL_USE_MSGSL", "OFF")
-        .define("SEAL_USE_ZLIB", "ON")
-        .define("SEAL_USE_ZSTD", "ON")
+        .define("SEAL_USE_ZLIB", feature_flag("ZLIB"))
+        .define("SEAL_USE_ZSTD", feature_flag("ZSTD"))
         .build();
 
     let out_path_suffix = if std::env::var("CARGO_CFG_WINDOWS").is_ok() {
@@ -41,17 +49,37 @@ fn compile_native(profile: &str, out_path: &Path) {
         out_path_suffix
     );
 
+    let shim = Config::new("shim")
+        .out_dir(out_path.join("shim"))
+        .define("CMAKE_BUILD_TYPE", profile)
+        .define("SEAL_SOURCE_DIR", seal_source_dir())
+        .define("SEAL_BUILD_DIR", out_path.join("build"))
+        .build();
+
+    println!("cargo:rustc-link-search=native={}/lib", shim.display());
+
+    // The shim calls into SEAL, so it has to come first.
+    println!("cargo:rustc-link-lib=static=sealshim");
     println!("cargo:rustc-link-lib=static=sealc-4.0");
     println!("cargo:rustc-link-lib=static=seal-4.0");
 
     println!("-I{}", out_path.join("include").display());
 }
 
-fn compile_wasm(profile: &str, _out_path: &Path) {
+fn compile_wasm(profile: &str, out_path: &Path) {
     let dst = EmConfig::new("SEAL")
         .define("CMAKE_BUILD_TYPE", profile)
         .define("CMAKE_CXX_FLAGS_RELEASE", "-DNDEBUG -g -O3")
         .define("CMAKE_C_FLAGS_RELEASE", "-DNDEBUG -g -O3")
+        // Satisfy SEAL's find_package(Threads) without passing -pthread.
+        // Threaded WASM requires SharedArrayBuffer, which browsers only
+        // enable on cross-origin isolated pages. SEAL's memory pool mutex
+        // is a no-op without threads, which is fine as WASM runs on one
+        // thread.
+        .define("CMAKE_THREAD_LIBS_INIT", "")
+        .define("CMAKE_HAVE_THREADS_LIBRARY", "1")
+        .define("CMAKE_USE_PTHREADS_INIT", "1")
+        .define("THREADS_PREFER_PTHREAD_FLAG", "OFF")
         .define("SEAL_BUILD_STATIC_SEAL_C", "ON")
         .define("SEAL_BUILD_DEPS", "ON")
         .define("SEAL_BUILD_SEAL_C", "ON")
@@ -59,16 +87,30 @@ fn compile_wasm(profile: &str, _out_path: &Path) {
         .define("SEAL_BUILD_EXAMPLES", "OFF")
         .define("SEAL_BUILD_TESTS", "OFF")
         .define("SEAL_USE_CXX17", "ON")
-        .define("SEAL_USE_INTRIN", "ON")
+        .define("SEAL_USE_INTRIN", "OFF")
         .define("SEAL_USE_MSGSL", "OFF")
-        .define("SEAL_USE_ZLIB", "ON")
-        .define("SEAL_USE_ZSTD", "ON")
+        .define("SEAL_USE_ZLIB", feature_flag("ZLIB"))
+        .define("SEAL_USE_ZSTD", feature_flag("ZSTD"))
         .build();
 
     let lib_path = format!("{}/lib/{}", dst.display(), "");
 
     println!("cargo:rustc-link-search=native={}", lib_path);
 
+    let seal_source_dir = seal_source_dir();
+    let seal_build_dir = dst.display().to_string();
+
+    let shim = EmConfig::new("shim")
+        .out_dir(out_path.join("shim"))
+        .define("CMAKE_BUILD_TYPE", profile)
+        .define("SEAL_SOURCE_DIR", seal_source_dir.to_str().unwrap())
+        .define("SEAL_BUILD_DIR", &seal_build_dir)
+        .build();
+
+    println!("cargo:rustc-link-search=native={}", shim.display());
+
+    // The shim calls into SEAL, so it has to come first.
+    println!("cargo:rustc-link-lib=static=sealshim");
     println!("cargo:rustc-link-lib=static=sealc-4.0");
     println!("cargo:rustc-link-lib=static=seal-4.0");
 }
@@ -78,8 +120,11 @@ fn main() {
     let profile = std::env::var("PROFILE").expect("Failed to get build profile");
     let out_path = PathBuf::from(std::env::var("OUT_DIR").unwrap());
     let target = std::env::var("TARGET").expect("Failed to get target");
+    let is_wasm = std::env::var("CARGO_CFG_TARGET_ARCH").as_deref() == Ok("wasm32");
+    let is_emscripten = std::env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("emscripten");
 
     println!("cargo:rerun-if-changed=SEAL");
+    println!("cargo:rerun-if-changed=shim");
 
     let profile = if profile == "release" {
         "Release"
@@ -89,7 +134,18 @@ fn main() {
         panic!("Unknown profile type {}", profile);
     };
 
-    if target == "wasm32-unknown-emscripten" {
+    if is_wasm && !is_emscripten {
+        // SEAL is C++ and needs a libc, a C++ standard library and
+        // exception support at runtime. Of the WASM targets, only
+        // emscripten provides them; linking its archives into e.g.
+        // wasm32-unknown-unknown leaves unresolved JS imports.
+        panic!(
+            "seal_fhe doesn't support {}. Build for wasm32-unknown-emscripten instead, which runs in browsers and NodeJS.",
+            target
+        );
+    }
+
+    if is_emscripten {
         compile_wasm(profile, &out_path);
     } else {
         compile_native(profile, &out_path);
@@ -101,7 +157,7 @@ fn main() {
         .clang_arg("-xc++")
         .clang_arg("-std=c++17");
 
-    if target == "wasm32-unknown-emscripten" {
+    if is_emscripten {
         // Bindgen appears to be broken under wasm. Just generate bindings with
         // the host's target.
         builder = builder
@@ -136,11 +192,127 @@ fn main() {
         .allowlist_function("SEALContext_.*")
         .allowlist_function("SecretKey_.*")
         .allowlist_function("Serialization_.*")
-        .allowlist_function("ValCheck_.*");
+        .allowlist_function("Shim_.*")
+        .allowlist_function("ValCheck_.*")
+        .allowlist_var("SHIM_.*");
 
     let bindings = builder.generate().unwrap();
 
     bindings
         .write_to_file(out_path.join("bindings.rs"))
         .expect("Failed to write bindings");
+
+    if std::env::var("CARGO_FEATURE_FAILURE_INJECTION").is_ok() {
+        std::fs::write(
+            out_path.join("failing_bindings.rs"),
+            failing_bindings(&bindings.to_string()),
+        )
+        .expect("Failed to write failure injection bindings");
+    }
+}
+
+/**
+ * Returns bindings that wrap every SEAL function returning an HRESULT so
+ * it first counts towards an injected failure, which it reports without
+ * calling SEAL. Destroying objects and fetching error messages isn't
+ * counted, so destructors and error reporting can't fail.
+ */
+fn failing_bindings(bindings: &str) -> String {
+    const RETURNS_HRESULT: &str = ")->::std::os::raw::c_long;";
+
+    let mut out = String::from(
+        "mod raw {\n    include!(concat!(env!(\"OUT_DIR\"), \"/bindings.rs\"));\n}\n\npub use raw::*;\n",
+    );
+
+    let mut rest = bindings;
+
+    while let Some(start) = rest.find("pub fn ") {
+        rest = &rest[start + "pub fn ".len()..];
+
+        let name_end = match rest.find('(') {
+            Some(i) => i,
+            None => break,
+        };
+        let name = rest[..name_end].trim();
+
+        // Find the parenthesis closing the parameter list, skipping those
+        // of function pointer parameters.
+        let mut depth = 0;
+        let mut params_end = None;
+
+        for (i, c) in rest[name_end..].char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+
+                    if depth == 0 {
+                        params_end = Some(name_end + i);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let params_end = match params_end {
+            Some(i) => i,
+            None => break,
+        };
+
+        let signature_end: String = rest[params_end..]
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .take(RETURNS_HRESULT.len())
+            .collect();
+
+        let exempt = name.ends_with("_Destroy") || name.ends_with("LastErrorMessage");
+
+        if signature_end != RETURNS_HRESULT || exempt {
+            continue;
+        }
+
+        let params = &rest[name_end + 1..params_end];
+        let args = split_params(params)
+            .iter()
+            .map(|p| p.split(':').next().unwrap().trim().to_owned())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        out.push_str(&format!(
+            "\n#[allow(clippy::missing_safety_doc, clippy::too_many_arguments)]\npub unsafe fn {}({}) -> ::std::os::raw::c_long {{\n    match crate::failure_injection::next_call() {{\n        Some(err) => err,\n        None => raw::{}({}),\n    }}\n}}\n",
+            name, params, name, args
+        ));
+    }
+
+    out
+}
+
+/**
+ * Splits a parameter list at the commas between parameters, ignoring
+ * those inside the types of function pointer parameters.
+ */
+fn split_params(params: &str) -> Vec<&str> {
+    let mut depth = 0;
+    let mut start = 0;
+    let mut split = vec![];
+    let mut prev = ' ';
+
+    for (i, c) in params.char_indices() {
+        match c {
+            '(' | '<' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            '>' if prev != '-' => depth -= 1,
+            ',' if depth == 0 => {
+                split.push(&params[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+
+        prev = c;
+    }
+
+    split.push(&params[start..]);
+    split.into_iter().filter(|p| !p.trim().is_empty()).collect()
 }
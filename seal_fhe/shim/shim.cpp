@@ -0,0 +1,671 @@
+#include "shim.h"
+
+#include <algorithm>
+#include <istream>
+#include <memory>
+#include <new>
+#include <ostream>
+#include <stdexcept>
+#include <streambuf>
+#include <string>
+#include <vector>
+
+#include "seal/seal.h"
+#include "seal/util/ntt.h"
+
+using namespace seal;
+
+namespace
+{
+    thread_local std::string last_error_message;
+
+    // Maps the exception being handled to an HRESULT the way SEAL's C API
+    // does, keeping its message for Shim_GetLastErrorMessage.
+    HRESULT translate_exception()
+    {
+        try
+        {
+            throw;
+        }
+        catch (const std::invalid_argument &e)
+        {
+            last_error_message = e.what();
+            return E_INVALIDARG;
+        }
+        catch (const std::logic_error &e)
+        {
+            last_error_message = e.what();
+            return COR_E_INVALIDOPERATION;
+        }
+        catch (const std::runtime_error &e)
+        {
+            // SEAL reports stream failures as runtime errors.
+            last_error_message = e.what();
+            return COR_E_IO;
+        }
+        catch (const std::bad_alloc &e)
+        {
+            last_error_message = e.what();
+            return E_OUTOFMEMORY;
+        }
+        catch (const std::exception &e)
+        {
+            last_error_message = e.what();
+            return E_UNEXPECTED;
+        }
+        catch (...)
+        {
+            last_error_message.clear();
+            return E_UNEXPECTED;
+        }
+    }
+
+    // Hands out generators whose seeds are drawn from one generator seeded
+    // with the factory's seed. Successive keys and encryptions thus get
+    // independent randomness, while the sequence as a whole is
+    // reproducible.
+    class SeededPRNGFactory : public UniformRandomGeneratorFactory
+    {
+    public:
+        explicit SeededPRNGFactory(const prng_seed_type &seed)
+            : UniformRandomGeneratorFactory(seed), seeds_(std::make_shared<Blake2xbPRNG>(seed))
+        {}
+
+    protected:
+        std::shared_ptr<UniformRandomGenerator> create_impl(prng_seed_type seed) override
+        {
+            // create() passes the factory's seed, while create(seed) asks
+            // for a generator with a specific seed.
+            if (seed == default_seed())
+            {
+                seeds_->generate(prng_seed_byte_count, reinterpret_cast<seal_byte *>(seed.data()));
+            }
+
+            return std::make_shared<Blake2xbPRNG>(seed);
+        }
+
+    private:
+        std::shared_ptr<UniformRandomGenerator> seeds_;
+    };
+
+    // Passes everything written to it straight to a callback.
+    class CallbackWriteBuf : public std::streambuf
+    {
+    public:
+        CallbackWriteBuf(Shim_WriteCallback write, void *state) : write_(write), state_(state)
+        {}
+
+    protected:
+        std::streamsize xsputn(const char *data, std::streamsize count) override
+        {
+            if (write_(state_, reinterpret_cast<const uint8_t *>(data), static_cast<uint64_t>(count)) != 0)
+            {
+                return 0;
+            }
+
+            pos_ += count;
+
+            return count;
+        }
+
+        int_type overflow(int_type ch) override
+        {
+            if (traits_type::eq_int_type(ch, traits_type::eof()))
+            {
+                return traits_type::not_eof(ch);
+            }
+
+            char c = traits_type::to_char_type(ch);
+
+            return xsputn(&c, 1) == 1 ? ch : traits_type::eof();
+        }
+
+        // SEAL calls tellp to count the bytes it writes, which is all the
+        // seeking a callback can support.
+        pos_type seekoff(off_type off, std::ios_base::seekdir dir, std::ios_base::openmode which) override
+        {
+            if (off != 0 || dir != std::ios_base::cur || !(which & std::ios_base::out))
+            {
+                return pos_type(off_type(-1));
+            }
+
+            return pos_type(pos_);
+        }
+
+    private:
+        Shim_WriteCallback write_;
+        void *state_;
+        std::streamoff pos_ = 0;
+    };
+
+    // Reads from a callback through a small buffer.
+    class CallbackReadBuf : public std::streambuf
+    {
+    public:
+        CallbackReadBuf(Shim_ReadCallback read, void *state) : read_(read), state_(state)
+        {
+            setg(buffer_, buffer_, buffer_);
+        }
+
+    protected:
+        int_type underflow() override
+        {
+            if (gptr() < egptr())
+            {
+                return traits_type::to_int_type(*gptr());
+            }
+
+            int64_t count = read_(state_, reinterpret_cast<uint8_t *>(buffer_), sizeof(buffer_));
+
+            if (count <= 0)
+            {
+                return traits_type::eof();
+            }
+
+            pos_ += count;
+            setg(buffer_, buffer_, buffer_ + count);
+
+            return traits_type::to_int_type(*gptr());
+        }
+
+        // SEAL calls tellg to check how many bytes it read. Bytes still in
+        // the buffer haven't been read yet.
+        pos_type seekoff(off_type off, std::ios_base::seekdir dir, std::ios_base::openmode which) override
+        {
+            if (off != 0 || dir != std::ios_base::cur || !(which & std::ios_base::in))
+            {
+                return pos_type(off_type(-1));
+            }
+
+            return pos_type(pos_ - (egptr() - gptr()));
+        }
+
+    private:
+        Shim_ReadCallback read_;
+        void *state_;
+        char buffer_[4096];
+        std::streamoff pos_ = 0;
+    };
+
+    template <typename T>
+    std::streamoff save_to(void *object, std::ostream &stream, compr_mode_type compr_mode)
+    {
+        return static_cast<T *>(object)->save(stream, compr_mode);
+    }
+
+    template <typename T>
+    std::streamoff load_from(void *object, const SEALContext &context, std::istream &stream)
+    {
+        return static_cast<T *>(object)->load(context, stream);
+    }
+} // namespace
+
+SEAL_C_FUNC Shim_SEALContext_CreateSeeded(void *context, const uint64_t *seed, void **seeded_context)
+{
+    auto *ctx = static_cast<SEALContext *>(context);
+
+    if (!ctx || !seed || !seeded_context)
+    {
+        return E_POINTER;
+    }
+
+    auto key_context_data = ctx->key_context_data();
+
+    if (!key_context_data || !ctx->parameters_set())
+    {
+        return E_INVALIDARG;
+    }
+
+    try
+    {
+        EncryptionParameters parms = key_context_data->parms();
+
+        prng_seed_type prng_seed;
+        std::copy_n(seed, prng_seed.size(), prng_seed.begin());
+        parms.set_random_generator(std::make_shared<SeededPRNGFactory>(prng_seed));
+
+        // The chain only extends past the first data level if the
+        // original context expanded it.
+        bool expand_mod_chain = ctx->first_parms_id() != ctx->last_parms_id();
+
+        *seeded_context = new SEALContext(parms, expand_mod_chain, key_context_data->qualifiers().sec_level);
+
+        return S_OK;
+    }
+    catch (...)
+    {
+        return translate_exception();
+    }
+}
+
+SEAL_C_FUNC Shim_SEALContext_CreateAtLevel(void *context, const uint64_t *parms_id, void **level_context)
+{
+    auto *ctx = static_cast<SEALContext *>(context);
+
+    if (!ctx || !parms_id || !level_context)
+    {
+        return E_POINTER;
+    }
+
+    auto key_context_data = ctx->key_context_data();
+
+    if (!key_context_data || !ctx->parameters_set())
+    {
+        return E_INVALIDARG;
+    }
+
+    try
+    {
+        parms_id_type target;
+        std::copy_n(parms_id, target.size(), target.begin());
+
+        const EncryptionParameters &key_parms = key_context_data->parms();
+        const std::vector<Modulus> &moduli = key_parms.coeff_modulus();
+
+        // Each level of the chain drops the last prime of the one above,
+        // so the level with the first count primes has the parms_id of
+        // these parameters with just those primes.
+        for (std::size_t count = 1; count < moduli.size(); count++)
+        {
+            EncryptionParameters level_parms = key_parms;
+            level_parms.set_coeff_modulus(std::vector<Modulus>(moduli.begin(), moduli.begin() + count));
+
+            if (level_parms.parms_id() != target)
+            {
+                continue;
+            }
+
+            // Keeping the next prime as the special prime makes the
+            // level the context's first data level.
+            EncryptionParameters parms = key_parms;
+            parms.set_coeff_modulus(std::vector<Modulus>(moduli.begin(), moduli.begin() + count + 1));
+
+            *level_context = new SEALContext(parms, false, key_context_data->qualifiers().sec_level);
+
+            return S_OK;
+        }
+
+        return E_INVALIDARG;
+    }
+    catch (...)
+    {
+        return translate_exception();
+    }
+}
+
+SEAL_C_FUNC Shim_SecretKey_CreateAtLevel(void *context, void *secret_key, void *level_context, void **level_key)
+{
+    auto *ctx = static_cast<SEALContext *>(context);
+    auto *key = static_cast<SecretKey *>(secret_key);
+    auto *level_ctx = static_cast<SEALContext *>(level_context);
+
+    if (!ctx || !key || !level_ctx || !level_key)
+    {
+        return E_POINTER;
+    }
+
+    if (!ctx->parameters_set() || !level_ctx->parameters_set() || !is_valid_for(*key, *ctx))
+    {
+        return E_INVALIDARG;
+    }
+
+    const EncryptionParameters &parms = ctx->key_context_data()->parms();
+    const EncryptionParameters &level_parms = level_ctx->key_context_data()->parms();
+    const std::vector<Modulus> &moduli = parms.coeff_modulus();
+    const std::vector<Modulus> &level_moduli = level_parms.coeff_modulus();
+
+    if (level_parms.poly_modulus_degree() != parms.poly_modulus_degree() ||
+        level_moduli.size() > moduli.size() || !std::equal(level_moduli.begin(), level_moduli.end(), moduli.begin()))
+    {
+        return E_INVALIDARG;
+    }
+
+    try
+    {
+        // The key is in NTT form, which SEAL computes for each prime on
+        // its own, so the level's key is the leading primes' blocks.
+        std::size_t count = level_parms.poly_modulus_degree() * level_moduli.size();
+
+        auto result = std::make_unique<SecretKey>();
+        result->data().resize(count);
+        std::copy_n(key->data().data(), count, result->data().data());
+        result->data().parms_id() = level_ctx->key_parms_id();
+
+        *level_key = result.release();
+
+        return S_OK;
+    }
+    catch (...)
+    {
+        return translate_exception();
+    }
+}
+
+SEAL_C_FUNC Shim_Serialization_LoadParmsId(const uint8_t *in, uint64_t size, uint64_t *parms_id)
+{
+    if (!in || !parms_id)
+    {
+        return E_POINTER;
+    }
+
+    try
+    {
+        auto *bytes = reinterpret_cast<const seal_byte *>(in);
+
+        Serialization::SEALHeader header;
+        Serialization::LoadHeader(bytes, static_cast<std::size_t>(size), header);
+
+        std::size_t members_size = sizeof(Serialization::SEALHeader) + sizeof(parms_id_type);
+
+        if (!Serialization::IsValidHeader(header) || header.size > size || header.size < members_size)
+        {
+            return E_INVALIDARG;
+        }
+
+        parms_id_type id;
+
+        // Every SEAL object saves its parms_id first. Loading checks that
+        // uncompressed objects are read to the end, so skip the rest.
+        Serialization::Load(
+            [&](std::istream &stream, SEALVersion) {
+                stream.read(reinterpret_cast<char *>(id.data()), sizeof(parms_id_type));
+
+                if (header.compr_mode == compr_mode_type::none)
+                {
+                    stream.ignore(static_cast<std::streamsize>(header.size - members_size));
+                }
+            },
+            bytes, static_cast<std::size_t>(header.size), false);
+
+        std::copy_n(id.begin(), id.size(), parms_id);
+
+        return S_OK;
+    }
+    catch (...)
+    {
+        return translate_exception();
+    }
+}
+
+SEAL_C_FUNC Shim_Serialization_SaveToStream(
+    uint8_t object_type, void *object, uint8_t compr_mode, Shim_WriteCallback write, void *state,
+    int64_t *out_bytes)
+{
+    if (!object || !write || !out_bytes)
+    {
+        return E_POINTER;
+    }
+
+    auto mode = static_cast<compr_mode_type>(compr_mode);
+
+    if (!Serialization::IsSupportedComprMode(mode))
+    {
+        return E_INVALIDARG;
+    }
+
+    try
+    {
+        CallbackWriteBuf buffer(write, state);
+        std::ostream stream(&buffer);
+        std::streamoff written;
+
+        switch (object_type)
+        {
+        case SHIM_OBJECT_PLAINTEXT:
+            written = save_to<Plaintext>(object, stream, mode);
+            break;
+        case SHIM_OBJECT_CIPHERTEXT:
+            written = save_to<Ciphertext>(object, stream, mode);
+            break;
+        case SHIM_OBJECT_PUBLIC_KEY:
+            written = save_to<PublicKey>(object, stream, mode);
+            break;
+        case SHIM_OBJECT_SECRET_KEY:
+            written = save_to<SecretKey>(object, stream, mode);
+            break;
+        case SHIM_OBJECT_KSWITCH_KEYS:
+            written = save_to<KSwitchKeys>(object, stream, mode);
+            break;
+        default:
+            return E_INVALIDARG;
+        }
+
+        *out_bytes = static_cast<int64_t>(written);
+
+        return S_OK;
+    }
+    catch (...)
+    {
+        return translate_exception();
+    }
+}
+
+SEAL_C_FUNC Shim_Serialization_LoadFromStream(
+    uint8_t object_type, void *object, void *context, Shim_ReadCallback read, void *state,
+    int64_t *in_bytes)
+{
+    auto *ctx = static_cast<SEALContext *>(context);
+
+    if (!object || !ctx || !read || !in_bytes)
+    {
+        return E_POINTER;
+    }
+
+    try
+    {
+        CallbackReadBuf buffer(read, state);
+        std::istream stream(&buffer);
+        std::streamoff loaded;
+
+        switch (object_type)
+        {
+        case SHIM_OBJECT_PLAINTEXT:
+            loaded = load_from<Plaintext>(object, *ctx, stream);
+            break;
+        case SHIM_OBJECT_CIPHERTEXT:
+            loaded = load_from<Ciphertext>(object, *ctx, stream);
+            break;
+        case SHIM_OBJECT_PUBLIC_KEY:
+            loaded = load_from<PublicKey>(object, *ctx, stream);
+            break;
+        case SHIM_OBJECT_SECRET_KEY:
+            loaded = load_from<SecretKey>(object, *ctx, stream);
+            break;
+        case SHIM_OBJECT_KSWITCH_KEYS:
+            loaded = load_from<KSwitchKeys>(object, *ctx, stream);
+            break;
+        default:
+            return E_INVALIDARG;
+        }
+
+        *in_bytes = static_cast<int64_t>(loaded);
+
+        return S_OK;
+    }
+    catch (...)
+    {
+        return translate_exception();
+    }
+}
+
+SEAL_C_FUNC Shim_GetLastErrorMessage(const char **message)
+{
+    if (!message)
+    {
+        return E_POINTER;
+    }
+
+    *message = last_error_message.c_str();
+
+    return S_OK;
+}
+
+SEAL_C_FUNC Shim_ClearLastErrorMessage()
+{
+    last_error_message.clear();
+
+    return S_OK;
+}
+
+SEAL_C_FUNC Shim_Evaluator_Add(void *thisptr, void *encrypted1, void *encrypted2, void *destination)
+{
+    auto *evaluator = static_cast<Evaluator *>(thisptr);
+    auto *a = static_cast<Ciphertext *>(encrypted1);
+    auto *b = static_cast<Ciphertext *>(encrypted2);
+    auto *result = static_cast<Ciphertext *>(destination);
+
+    if (!evaluator || !a || !b || !result)
+    {
+        return E_POINTER;
+    }
+
+    try
+    {
+        evaluator->add(*a, *b, *result);
+
+        return S_OK;
+    }
+    catch (...)
+    {
+        return translate_exception();
+    }
+}
+
+SEAL_C_FUNC Shim_Evaluator_Sub(void *thisptr, void *encrypted1, void *encrypted2, void *destination)
+{
+    auto *evaluator = static_cast<Evaluator *>(thisptr);
+    auto *a = static_cast<Ciphertext *>(encrypted1);
+    auto *b = static_cast<Ciphertext *>(encrypted2);
+    auto *result = static_cast<Ciphertext *>(destination);
+
+    if (!evaluator || !a || !b || !result)
+    {
+        return E_POINTER;
+    }
+
+    try
+    {
+        evaluator->sub(*a, *b, *result);
+
+        return S_OK;
+    }
+    catch (...)
+    {
+        return translate_exception();
+    }
+}
+
+SEAL_C_FUNC Shim_Evaluator_Multiply(void *thisptr, void *encrypted1, void *encrypted2, void *destination)
+{
+    auto *evaluator = static_cast<Evaluator *>(thisptr);
+    auto *a = static_cast<Ciphertext *>(encrypted1);
+    auto *b = static_cast<Ciphertext *>(encrypted2);
+    auto *result = static_cast<Ciphertext *>(destination);
+
+    if (!evaluator || !a || !b || !result)
+    {
+        return E_POINTER;
+    }
+
+    try
+    {
+        evaluator->multiply(*a, *b, *result);
+
+        return S_OK;
+    }
+    catch (...)
+    {
+        return translate_exception();
+    }
+}
+
+SEAL_C_FUNC Shim_Ciphertext_GetPolyData(void *thisptr, uint64_t poly_index, uint64_t *data, uint64_t size)
+{
+    auto *ciphertext = static_cast<Ciphertext *>(thisptr);
+
+    if (!ciphertext || !data)
+    {
+        return E_POINTER;
+    }
+
+    std::size_t count = ciphertext->poly_modulus_degree() * ciphertext->coeff_modulus_size();
+
+    if (poly_index >= ciphertext->size() || size != count)
+    {
+        return E_INVALIDARG;
+    }
+
+    std::copy_n(ciphertext->data(static_cast<std::size_t>(poly_index)), count, data);
+
+    return S_OK;
+}
+
+SEAL_C_FUNC Shim_Ciphertext_SetPolyData(void *thisptr, uint64_t poly_index, const uint64_t *data, uint64_t size)
+{
+    auto *ciphertext = static_cast<Ciphertext *>(thisptr);
+
+    if (!ciphertext || !data)
+    {
+        return E_POINTER;
+    }
+
+    std::size_t count = ciphertext->poly_modulus_degree() * ciphertext->coeff_modulus_size();
+
+    if (poly_index >= ciphertext->size() || size != count)
+    {
+        return E_INVALIDARG;
+    }
+
+    std::copy_n(data, count, ciphertext->data(static_cast<std::size_t>(poly_index)));
+
+    return S_OK;
+}
+
+SEAL_C_FUNC Shim_SecretKey_GetCoefficients(void *context, void *secret_key, int64_t *coefficients, uint64_t size)
+{
+    auto *ctx = static_cast<SEALContext *>(context);
+    auto *key = static_cast<SecretKey *>(secret_key);
+
+    if (!ctx || !key || !coefficients)
+    {
+        return E_POINTER;
+    }
+
+    if (!ctx->parameters_set() || !is_valid_for(*key, *ctx))
+    {
+        return E_INVALIDARG;
+    }
+
+    auto key_context_data = ctx->key_context_data();
+    const EncryptionParameters &parms = key_context_data->parms();
+    std::size_t n = parms.poly_modulus_degree();
+
+    if (size != n)
+    {
+        return E_INVALIDARG;
+    }
+
+    try
+    {
+        // The key's coefficients are small, so the block for the first
+        // prime determines them.
+        std::vector<uint64_t> poly(key->data().data(), key->data().data() + n);
+        util::inverse_ntt_negacyclic_harvey(poly.data(), key_context_data->small_ntt_tables()[0]);
+
+        uint64_t q = parms.coeff_modulus()[0].value();
+
+        for (std::size_t i = 0; i < n; i++)
+        {
+            coefficients[i] = poly[i] > q / 2 ? -static_cast<int64_t>(q - poly[i]) : static_cast<int64_t>(poly[i]);
+        }
+
+        volatile uint64_t *secret = poly.data();
+        std::fill_n(secret, n, 0);
+
+        return S_OK;
+    }
+    catch (...)
+    {
+        return translate_exception();
+    }
+}
@@ -0,0 +1,88 @@
+#pragma once
+
+// Functions seal_fhe needs that SEAL's C API doesn't export. They follow
+// the C API's conventions: SEAL objects are passed as void pointers to
+// the objects the C API creates, and every function returns an HRESULT.
+
+#include <stdint.h>
+
+#include "seal/c/defines.h"
+
+// Creates a copy of a SEALContext whose pseudorandom generator is seeded
+// with the 8 words at seed. Keys and ciphertexts made with the copy are
+// valid for the original context.
+SEAL_C_FUNC Shim_SEALContext_CreateSeeded(void *context, const uint64_t *seed, void **seeded_context);
+
+// Creates a context whose first data level is the level of context's
+// modulus switching chain with the given parms_id, without the levels
+// above it. Returns E_INVALIDARG if no data level has that parms_id.
+SEAL_C_FUNC Shim_SEALContext_CreateAtLevel(void *context, const uint64_t *parms_id, void **level_context);
+
+// Creates a copy of a secret key for context that is valid for
+// level_context, a context Shim_SEALContext_CreateAtLevel made from it.
+SEAL_C_FUNC Shim_SecretKey_CreateAtLevel(void *context, void *secret_key, void *level_context, void **level_key);
+
+// Reads the parms_id of the SEAL object serialized in the size bytes at
+// in, e.g. a ciphertext or key, without loading the object.
+SEAL_C_FUNC Shim_Serialization_LoadParmsId(const uint8_t *in, uint64_t size, uint64_t *parms_id);
+
+// The types of SEAL object Shim_Serialization_SaveToStream and
+// Shim_Serialization_LoadFromStream accept. Relinearization and Galois
+// keys are both KSwitchKeys.
+#define SHIM_OBJECT_PLAINTEXT 0
+#define SHIM_OBJECT_CIPHERTEXT 1
+#define SHIM_OBJECT_PUBLIC_KEY 2
+#define SHIM_OBJECT_SECRET_KEY 3
+#define SHIM_OBJECT_KSWITCH_KEYS 4
+
+// Writes the size bytes at data to the stream behind state. Returns
+// nonzero if they couldn't all be written.
+typedef int32_t (*Shim_WriteCallback)(void *state, const uint8_t *data, uint64_t size);
+
+// Reads up to size bytes from the stream behind state into data. Returns
+// the number of bytes read, 0 at the end of the stream or -1 on error.
+typedef int64_t (*Shim_ReadCallback)(void *state, uint8_t *data, uint64_t size);
+
+// Saves the object of the given type to a stream that passes every write
+// to write, without first serializing it into a buffer. Stores the number
+// of bytes written in out_bytes.
+SEAL_C_FUNC Shim_Serialization_SaveToStream(
+    uint8_t object_type, void *object, uint8_t compr_mode, Shim_WriteCallback write, void *state,
+    int64_t *out_bytes);
+
+// Loads the object of the given type for context from a stream that
+// reads from read. Stores the number of bytes read in in_bytes. read may
+// be asked for more bytes than the object holds.
+SEAL_C_FUNC Shim_Serialization_LoadFromStream(
+    uint8_t object_type, void *object, void *context, Shim_ReadCallback read, void *state,
+    int64_t *in_bytes);
+
+// Points message at the what() of the last exception a shim function on
+// this thread caught, or at an empty string. SEAL's C API discards these
+// messages, so only failures in shim functions have one. The string lives
+// until the next shim call on this thread.
+SEAL_C_FUNC Shim_GetLastErrorMessage(const char **message);
+
+// Forgets the message Shim_GetLastErrorMessage returns.
+SEAL_C_FUNC Shim_ClearLastErrorMessage();
+
+// Evaluator_Add, Evaluator_Sub and Evaluator_Multiply from the C API,
+// except that failures keep SEAL's message, e.g. that the operands are at
+// different levels.
+SEAL_C_FUNC Shim_Evaluator_Add(void *thisptr, void *encrypted1, void *encrypted2, void *destination);
+SEAL_C_FUNC Shim_Evaluator_Sub(void *thisptr, void *encrypted1, void *encrypted2, void *destination);
+SEAL_C_FUNC Shim_Evaluator_Multiply(void *thisptr, void *encrypted1, void *encrypted2, void *destination);
+
+// Copies the poly_index-th polynomial of a ciphertext to the size words at
+// data, one block of poly_modulus_degree coefficients per prime. Returns
+// E_INVALIDARG if there's no such polynomial or size isn't its length.
+SEAL_C_FUNC Shim_Ciphertext_GetPolyData(void *thisptr, uint64_t poly_index, uint64_t *data, uint64_t size);
+
+// Overwrites the poly_index-th polynomial of a ciphertext with the size
+// words at data, laid out as Shim_Ciphertext_GetPolyData returns them.
+SEAL_C_FUNC Shim_Ciphertext_SetPolyData(void *thisptr, uint64_t poly_index, const uint64_t *data, uint64_t size);
+
+// Stores the size coefficients of a secret key for context, centered
+// around 0, at coefficients. SEAL keeps keys in NTT form, so this undoes
+// the transform. Returns E_INVALIDARG if size isn't the polynomial degree.
+SEAL_C_FUNC Shim_SecretKey_GetCoefficients(void *context, void *secret_key, int64_t *coefficients, uint64_t size);
@@ -1,4 +1,6 @@
+use std::collections::HashMap;
 use std::ptr::null_mut;
+use std::sync::{Arc, Mutex};
 
 use crate::evaluator_base::EvaluatorBase;
 use crate::{
@@ -6,10 +8,59 @@ use crate::{
     RelinearizationKeys, Result,
 };
 
+/**
+ * The number of scalar plaintexts a [`BFVEvaluator`] keeps encoded.
+ */
+const SCALAR_CACHE_CAPACITY: usize = 64;
+
 /**
  * An evaluator that contains additional operations specific to the BFV scheme.
  */
-pub struct BFVEvaluator(EvaluatorBase);
+pub struct BFVEvaluator(EvaluatorBase, Mutex<ScalarCache>);
+
+/**
+ * Encoded scalar plaintexts, evicting the least recently used once it
+ * holds [`SCALAR_CACHE_CAPACITY`] of them.
+ */
+#[derive(Default)]
+struct ScalarCache {
+    plaintexts: HashMap<u64, (Arc<Plaintext>, u64)>,
+    clock: u64,
+}
+
+impl ScalarCache {
+    fn get(&mut self, scalar: u64) -> Option<Arc<Plaintext>> {
+        self.clock += 1;
+
+        let clock = self.clock;
+
+        self.plaintexts.get_mut(&scalar).map(|(p, last_used)| {
+            *last_used = clock;
+            p.clone()
+        })
+    }
+
+    fn insert(&mut self, scalar: u64, plaintext: Arc<Plaintext>) {
+        if self.plaintexts.len() >= SCALAR_CACHE_CAPACITY {
+            let oldest = self
+                .plaintexts
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(s, _)| *s);
+
+            if let Some(oldest) = oldest {
+                self.plaintexts.remove(&oldest);
+            }
+        }
+
+        self.clock += 1;
+        self.plaintexts.insert(scalar, (plaintext, self.clock));
+    }
+
+    fn len(&self) -> usize {
+        self.plaintexts.len()
+    }
+}
 
 impl std::ops::Deref for BFVEvaluator {
     type Target = EvaluatorBase;
@@ -25,10 +76,168 @@ impl BFVEvaluator {
      * * `ctx` - The context.
      */
     pub fn new(ctx: &Context) -> Result<BFVEvaluator> {
-        Ok(BFVEvaluator(EvaluatorBase::new(ctx)?))
+        Ok(BFVEvaluator(
+            EvaluatorBase::new(ctx)?,
+            Mutex::new(ScalarCache::default()),
+        ))
+    }
+
+    /**
+     * Multiplies every slot of `a` by `scalar`.
+     *
+     * # Remarks
+     * The scalar is encoded as a constant plaintext, which the evaluator
+     * caches, so repeatedly scaling by the same constant encodes it once.
+     * The cache holds the 64 most recently used scalars.
+     * Multiplying by a constant grows the noise by a factor of `scalar`
+     * rather than the much larger factor of a general batched plaintext.
+     * `scalar` must be less than the plaintext modulus. Multiplying by 0
+     * returns an error, as the result would be a trivial encryption of 0.
+     */
+    pub fn multiply_scalar(&self, a: &Ciphertext, scalar: u64) -> Result<Ciphertext> {
+        self.multiply_plain(a, &self.scalar(scalar)?)
+    }
+
+    /**
+     * Adds `scalar` to every slot of `a`. As with
+     * [`multiply_scalar`](Self::multiply_scalar), the encoded scalar is
+     * cached and must be less than the plaintext modulus.
+     */
+    pub fn add_scalar(&self, a: &Ciphertext, scalar: u64) -> Result<Ciphertext> {
+        self.add_plain(a, &self.scalar(scalar)?)
+    }
+
+    /**
+     * Returns the cached constant plaintext for `scalar`, encoding it on
+     * first use. A constant polynomial decodes to the same value in every
+     * batching slot.
+     */
+    fn scalar(&self, scalar: u64) -> Result<Arc<Plaintext>> {
+        let mut cache = self.1.lock().unwrap_or_else(|e| e.into_inner());
+
+        if let Some(p) = cache.get(scalar) {
+            return Ok(p);
+        }
+
+        let p = Arc::new(Plaintext::from_hex_string(&format!("{:x}", scalar))?);
+        cache.insert(scalar, p.clone());
+
+        Ok(p)
+    }
+
+    /**
+     * Returns a ciphertext whose slots come from `a` where `mask` is 1 and
+     * from `b` where it's 0. Create masks with
+     * [`BFVEncoder::encode_mask`](crate::BFVEncoder::encode_mask).
+     *
+     * # Remarks
+     * This computes `b + mask * (a - b)`, which takes a single plaintext
+     * multiplication and consumes no multiplicative depth. The
+     * multiplication grows the noise of `a - b` by a factor proportional to
+     * the polynomial degree times the plaintext modulus, as for any batched
+     * plaintext multiplication. If `mask` is all zeros, this returns a copy of
+     * `b` without touching `a`.
+     */
+    pub fn select(&self, mask: &Plaintext, a: &Ciphertext, b: &Ciphertext) -> Result<Ciphertext> {
+        if is_zero(mask) {
+            return Ok(b.clone());
+        }
+
+        let mut c = self.sub(a, b)?;
+
+        self.multiply_plain_inplace(&mut c, mask)?;
+        self.add_inplace(&mut c, b)?;
+
+        Ok(c)
+    }
+
+    /**
+     * Like [`select`](Self::select), but takes the slots where `mask` is 0
+     * from the plaintext `b`.
+     *
+     * Returns an error if `mask` is all zeros, as the result would be a
+     * trivial encryption of `b`.
+     */
+    pub fn select_plain(
+        &self,
+        mask: &Plaintext,
+        a: &Ciphertext,
+        b: &Plaintext,
+    ) -> Result<Ciphertext> {
+        let mut c = self.sub_plain(a, b)?;
+
+        self.multiply_plain_inplace(&mut c, mask)?;
+        self.add_plain_inplace(&mut c, b)?;
+
+        Ok(c)
+    }
+
+    /**
+     * Like [`select`](Self::select), but with an encrypted mask whose slots
+     * must each decrypt to 0 or 1, e.g. the result of an encrypted
+     * comparison.
+     *
+     * # Remarks
+     * This consumes one multiplicative level and relinearizes the result.
+     */
+    pub fn select_encrypted(
+        &self,
+        mask: &Ciphertext,
+        a: &Ciphertext,
+        b: &Ciphertext,
+        relin_keys: &RelinearizationKeys,
+    ) -> Result<Ciphertext> {
+        let mut c = self.sub(a, b)?;
+
+        self.multiply_inplace(&mut c, mask)?;
+        self.relinearize_inplace(&mut c, relin_keys)?;
+        self.add_inplace(&mut c, b)?;
+
+        Ok(c)
+    }
+
+    /**
+     * Transforms `a` to the number theoretic transform (NTT) domain, where
+     * SEAL multiplies polynomials.
+     *
+     * # Remarks
+     * Multiplications transform their operands to the NTT domain and back,
+     * which dominates their cost. Every other BFV operation, including
+     * decryption, expects ciphertexts outside the NTT domain, so transform
+     * the result back with [`transform_from_ntt`](Self::transform_from_ntt)
+     * before using it.
+     */
+    pub fn transform_to_ntt(&self, a: &Ciphertext) -> Result<Ciphertext> {
+        let c = Ciphertext::new()?;
+
+        convert_seal_error(unsafe {
+            bindgen::Evaluator_TransformToNTT2(self.get_handle(), a.get_handle(), c.get_handle())
+        })
+        .map_err(|e| e.in_operation("transform_to_ntt", Some(a), None))?;
+
+        Ok(c)
+    }
+
+    /**
+     * Transforms `a` out of the NTT domain, undoing
+     * [`transform_to_ntt`](Self::transform_to_ntt).
+     */
+    pub fn transform_from_ntt(&self, a: &Ciphertext) -> Result<Ciphertext> {
+        let c = Ciphertext::new()?;
+
+        convert_seal_error(unsafe {
+            bindgen::Evaluator_TransformFromNTT(self.get_handle(), a.get_handle(), c.get_handle())
+        })
+        .map_err(|e| e.in_operation("transform_from_ntt", Some(a), None))?;
+
+        Ok(c)
     }
 }
 
+fn is_zero(p: &Plaintext) -> bool {
+    p.nonzero_coefficient_count() == 0
+}
+
 impl Evaluator for BFVEvaluator {
     fn negate_inplace(&self, a: &mut Ciphertext) -> Result<()> {
         self.0.negate_inplace(a)
@@ -86,7 +295,7 @@ impl Evaluator for BFVEvaluator {
         self.0.mod_switch_to_next(a)
     }
 
-    fn mod_switch_to_next_inplace(&self, a: &Ciphertext) -> Result<()> {
+    fn mod_switch_to_next_inplace(&self, a: &mut Ciphertext) -> Result<()> {
         self.0.mod_switch_to_next_inplace(a)
     }
 
@@ -94,7 +303,7 @@ impl Evaluator for BFVEvaluator {
         self.0.mod_switch_to_next_plaintext(a)
     }
 
-    fn mod_switch_to_next_inplace_plaintext(&self, a: &Plaintext) -> Result<()> {
+    fn mod_switch_to_next_inplace_plaintext(&self, a: &mut Plaintext) -> Result<()> {
         self.0.mod_switch_to_next_inplace_plaintext(a)
     }
 
@@ -109,7 +318,7 @@ impl Evaluator for BFVEvaluator {
 
     fn exponentiate_inplace(
         &self,
-        a: &Ciphertext,
+        a: &mut Ciphertext,
         exponent: u64,
         relin_keys: &RelinearizationKeys,
     ) -> Result<()> {
@@ -132,6 +341,14 @@ impl Evaluator for BFVEvaluator {
         self.0.sub_plain_inplace(a, b)
     }
 
+    fn plain_sub(&self, a: &Plaintext, b: &Ciphertext) -> Result<Ciphertext> {
+        self.0.plain_sub(a, b)
+    }
+
+    fn plain_sub_inplace(&self, a: &Plaintext, b: &mut Ciphertext) -> Result<()> {
+        self.0.plain_sub_inplace(a, b)
+    }
+
     fn multiply_plain(&self, a: &Ciphertext, b: &Plaintext) -> Result<Ciphertext> {
         self.0.multiply_plain(a, b)
     }
@@ -140,6 +357,10 @@ impl Evaluator for BFVEvaluator {
         self.0.multiply_plain_inplace(a, b)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(lhs_size = a.num_polynomials()))
+    )]
     fn relinearize_inplace(
         &self,
         a: &mut Ciphertext,
@@ -153,11 +374,16 @@ impl Evaluator for BFVEvaluator {
                 a.get_handle(),
                 null_mut(),
             )
-        })?;
+        })
+        .map_err(|e| e.in_operation("relinearize", Some(&*a), None))?;
 
         Ok(())
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(lhs_size = a.num_polynomials()))
+    )]
     fn relinearize(&self, a: &Ciphertext, relin_keys: &RelinearizationKeys) -> Result<Ciphertext> {
         let out = Ciphertext::new()?;
 
@@ -169,11 +395,20 @@ impl Evaluator for BFVEvaluator {
                 out.get_handle(),
                 null_mut(),
             )
-        })?;
+        })
+        .map_err(|e| e.in_operation("relinearize", Some(a), None))?;
 
         Ok(out)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(lhs_size = a.num_polynomials(), steps = steps)
+        )
+    )]
     fn rotate_rows(
         &self,
         a: &Ciphertext,
@@ -191,14 +426,23 @@ impl Evaluator for BFVEvaluator {
                 out.get_handle(),
                 null_mut(),
             )
-        })?;
+        })
+        .map_err(|e| e.in_operation("rotate_rows", Some(a), None))?;
 
         Ok(out)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(lhs_size = a.num_polynomials(), steps = steps)
+        )
+    )]
     fn rotate_rows_inplace(
         &self,
-        a: &Ciphertext,
+        a: &mut Ciphertext,
         steps: i32,
         galois_keys: &GaloisKeys,
     ) -> Result<()> {
@@ -211,11 +455,16 @@ impl Evaluator for BFVEvaluator {
                 a.get_handle(),
                 null_mut(),
             )
-        })?;
+        })
+        .map_err(|e| e.in_operation("rotate_rows", Some(&*a), None))?;
 
         Ok(())
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(lhs_size = a.num_polynomials()))
+    )]
     fn rotate_columns(&self, a: &Ciphertext, galois_keys: &GaloisKeys) -> Result<Ciphertext> {
         let out = Ciphertext::new()?;
 
@@ -227,12 +476,17 @@ impl Evaluator for BFVEvaluator {
                 out.get_handle(),
                 null_mut(),
             )
-        })?;
+        })
+        .map_err(|e| e.in_operation("rotate_columns", Some(a), None))?;
 
         Ok(out)
     }
 
-    fn rotate_columns_inplace(&self, a: &Ciphertext, galois_keys: &GaloisKeys) -> Result<()> {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(lhs_size = a.num_polynomials()))
+    )]
+    fn rotate_columns_inplace(&self, a: &mut Ciphertext, galois_keys: &GaloisKeys) -> Result<()> {
         convert_seal_error(unsafe {
             bindgen::Evaluator_RotateColumns(
                 self.get_handle(),
@@ -241,7 +495,8 @@ impl Evaluator for BFVEvaluator {
                 a.get_handle(),
                 null_mut(),
             )
-        })?;
+        })
+        .map_err(|e| e.in_operation("rotate_columns", Some(&*a), None))?;
 
         Ok(())
     }
@@ -270,8 +525,8 @@ mod tests {
 
         let encoder = BFVEncoder::new(&ctx).unwrap();
 
-        let public_key = gen.create_public_key();
-        let secret_key = gen.secret_key();
+        let public_key = gen.create_public_key().unwrap();
+        let secret_key = gen.secret_key().unwrap();
 
         let encryptor =
             Encryptor::with_public_and_secret_key(&ctx, &public_key, &secret_key).unwrap();
@@ -319,6 +574,155 @@ mod tests {
         std::mem::drop(evaluator);
     }
 
+    #[test]
+    fn can_multiply_and_add_scalars() {
+        run_bfv_test(|decryptor, encoder, encryptor, evaluator, _| {
+            let a = (0..encoder.get_slot_count() as u64)
+                .map(|x| x % 100)
+                .collect::<Vec<_>>();
+
+            let a_c = encryptor
+                .encrypt(&encoder.encode_unsigned(&a).unwrap())
+                .unwrap();
+
+            let b_c = evaluator.multiply_scalar(&a_c, 3).unwrap();
+            let b_c = evaluator.add_scalar(&b_c, 5).unwrap();
+            let b_c = evaluator.multiply_scalar(&b_c, 3).unwrap();
+
+            let b = encoder
+                .decode_unsigned(&decryptor.decrypt(&b_c).unwrap())
+                .unwrap();
+
+            assert_eq!(b, a.iter().map(|x| (x * 3 + 5) * 3).collect::<Vec<_>>());
+            assert_eq!(evaluator.1.lock().unwrap().len(), 2);
+        });
+    }
+
+    #[test]
+    fn scalar_cache_evicts_least_recently_used() {
+        run_bfv_test(|_, _, _, evaluator, _| {
+            for scalar in 0..SCALAR_CACHE_CAPACITY as u64 {
+                evaluator.scalar(scalar).unwrap();
+            }
+
+            // Touch 0 so 1 is now the least recently used.
+            let zero = evaluator.scalar(0).unwrap();
+            evaluator.scalar(1000).unwrap();
+
+            let mut cache = evaluator.1.lock().unwrap();
+
+            assert_eq!(cache.len(), SCALAR_CACHE_CAPACITY);
+            assert!(Arc::ptr_eq(&cache.get(0).unwrap(), &zero));
+            assert!(cache.get(1).is_none());
+            assert!(cache.get(1000).is_some());
+        });
+    }
+
+    #[test]
+    fn multiplying_by_one_is_a_copy() {
+        run_bfv_test(|decryptor, encoder, encryptor, evaluator, _| {
+            let a = make_vec(&encoder);
+            let a_c = encryptor
+                .encrypt(&encoder.encode_signed(&a).unwrap())
+                .unwrap();
+
+            let ones = encoder
+                .encode_mask_range(0..encoder.get_slot_count())
+                .unwrap();
+            let unit = encoder.encode_mask(&[3]).unwrap();
+
+            assert!(ones.is_one());
+            assert!(!unit.is_one());
+            assert!(unit.nonzero_coefficient_count() > 1);
+
+            let b_c = evaluator.multiply_plain(&a_c, &ones).unwrap();
+            assert!(b_c == a_c);
+
+            let mut c_c = a_c.clone();
+            evaluator.multiply_plain_inplace(&mut c_c, &unit).unwrap();
+
+            let c = encoder
+                .decode_signed(&decryptor.decrypt(&c_c).unwrap())
+                .unwrap();
+
+            for (i, x) in c.iter().enumerate() {
+                assert_eq!(*x, if i == 3 { a[i] } else { 0 });
+            }
+        });
+    }
+
+    #[test]
+    fn can_multiply_by_sparse_plaintexts() {
+        run_bfv_test(|decryptor, _, encryptor, evaluator, _| {
+            let a = Plaintext::from_hex_string("3x^2 + 2x^1 + 1").unwrap();
+            let b = Plaintext::from_hex_string("3x^5 + 2").unwrap();
+
+            assert_eq!(b.nonzero_coefficient_count(), 2);
+
+            let a_c = encryptor.encrypt(&a).unwrap();
+
+            let c_c = evaluator.multiply_plain(&a_c, &b).unwrap();
+
+            let mut d_c = a_c.clone();
+            evaluator.multiply_plain_inplace(&mut d_c, &b).unwrap();
+
+            let expected = [2, 4, 6, 0, 0, 3, 6, 9];
+
+            for c_c in [c_c, d_c] {
+                let c = decryptor.decrypt(&c_c).unwrap();
+
+                for i in 0..c.len() {
+                    assert_eq!(c.get_coefficient(i), *expected.get(i).unwrap_or(&0));
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn can_select() {
+        run_bfv_test(|decryptor, encoder, encryptor, evaluator, keygen| {
+            let relin_keys = keygen.create_relinearization_keys().unwrap();
+
+            let a = make_small_vec(&encoder);
+            let b = a.iter().map(|x| 100 + x).collect::<Vec<i64>>();
+            let slots = [0, 3, 4097];
+
+            let a_c = encryptor
+                .encrypt(&encoder.encode_signed(&a).unwrap())
+                .unwrap();
+            let b_p = encoder.encode_signed(&b).unwrap();
+            let b_c = encryptor.encrypt(&b_p).unwrap();
+            let mask = encoder.encode_mask(&slots).unwrap();
+
+            let expected = (0..a.len())
+                .map(|i| if slots.contains(&i) { a[i] } else { b[i] })
+                .collect::<Vec<i64>>();
+
+            let decrypt = |c: &Ciphertext| {
+                encoder
+                    .decode_signed(&decryptor.decrypt(c).unwrap())
+                    .unwrap()
+            };
+
+            let c = evaluator.select(&mask, &a_c, &b_c).unwrap();
+            assert_eq!(decrypt(&c), expected);
+
+            let c = evaluator.select_plain(&mask, &a_c, &b_p).unwrap();
+            assert_eq!(decrypt(&c), expected);
+
+            let encrypted_mask = encryptor.encrypt(&mask).unwrap();
+            let c = evaluator
+                .select_encrypted(&encrypted_mask, &a_c, &b_c, &relin_keys)
+                .unwrap();
+            assert_eq!(decrypt(&c), expected);
+
+            let c = evaluator
+                .select(&encoder.encode_mask(&[]).unwrap(), &a_c, &b_c)
+                .unwrap();
+            assert_eq!(decrypt(&c), b);
+        });
+    }
+
     #[test]
     fn can_negate() {
         run_bfv_test(|decryptor, encoder, encryptor, evaluator, _| {
@@ -708,10 +1112,10 @@ mod tests {
 
             let a = make_small_vec(&encoder);
             let a_p = encoder.encode_signed(&a).unwrap();
-            let a_c = encryptor.encrypt(&a_p).unwrap();
+            let mut a_c = encryptor.encrypt(&a_p).unwrap();
 
             evaluator
-                .exponentiate_inplace(&a_c, 4, &relin_keys)
+                .exponentiate_inplace(&mut a_c, 4, &relin_keys)
                 .unwrap();
 
             let a_p = decryptor.decrypt(&a_c).unwrap();
@@ -817,6 +1221,52 @@ mod tests {
         });
     }
 
+    #[test]
+    fn can_plain_sub() {
+        run_bfv_test(|decryptor, encoder, encryptor, evaluator, _| {
+            let a = make_vec(&encoder);
+            let b = make_vec(&encoder);
+            let a_p = encoder.encode_signed(&a).unwrap();
+            let b_p = encoder.encode_signed(&b).unwrap();
+            let b_c = encryptor.encrypt(&b_p).unwrap();
+
+            let c_c = evaluator.plain_sub(&a_p, &b_c).unwrap();
+
+            let c_p = decryptor.decrypt(&c_c).unwrap();
+            let c = encoder.decode_signed(&c_p).unwrap();
+
+            assert_eq!(a.len(), c.len());
+            assert_eq!(b.len(), c.len());
+
+            for i in 0..a.len() {
+                assert_eq!(c[i], a[i] - b[i]);
+            }
+        });
+    }
+
+    #[test]
+    fn can_plain_sub_inplace() {
+        run_bfv_test(|decryptor, encoder, encryptor, evaluator, _| {
+            let a = make_vec(&encoder);
+            let b = make_vec(&encoder);
+            let a_p = encoder.encode_signed(&a).unwrap();
+            let b_p = encoder.encode_signed(&b).unwrap();
+            let mut b_c = encryptor.encrypt(&b_p).unwrap();
+
+            evaluator.plain_sub_inplace(&a_p, &mut b_c).unwrap();
+
+            let b_p = decryptor.decrypt(&b_c).unwrap();
+            let c = encoder.decode_signed(&b_p).unwrap();
+
+            assert_eq!(a.len(), c.len());
+            assert_eq!(b.len(), c.len());
+
+            for i in 0..a.len() {
+                assert_eq!(c[i], a[i] - b[i]);
+            }
+        });
+    }
+
     #[test]
     fn can_multiply_plain() {
         run_bfv_test(|decryptor, encoder, encryptor, evaluator, _| {
@@ -840,6 +1290,31 @@ mod tests {
         });
     }
 
+    #[test]
+    fn can_transform_to_and_from_ntt() {
+        run_bfv_test(|decryptor, encoder, encryptor, evaluator, _| {
+            let a = make_vec(&encoder);
+            let a_c = encryptor
+                .encrypt(&encoder.encode_signed(&a).unwrap())
+                .unwrap();
+
+            let ntt = evaluator.transform_to_ntt(&a_c).unwrap();
+
+            assert!(ntt.is_ntt_form());
+            assert!(evaluator.transform_to_ntt(&ntt).is_err());
+
+            let b_c = evaluator.transform_from_ntt(&ntt).unwrap();
+
+            assert!(!b_c.is_ntt_form());
+
+            let b = encoder
+                .decode_signed(&decryptor.decrypt(&b_c).unwrap())
+                .unwrap();
+
+            assert_eq!(a, b);
+        });
+    }
+
     #[test]
     fn can_multiply_plain_inplace() {
         run_bfv_test(|decryptor, encoder, encryptor, evaluator, _| {
@@ -907,10 +1382,10 @@ mod tests {
 
             let a = make_matrix(&encoder);
             let a_p = encoder.encode_signed(&a).unwrap();
-            let a_c = encryptor.encrypt(&a_p).unwrap();
+            let mut a_c = encryptor.encrypt(&a_p).unwrap();
 
             evaluator
-                .rotate_rows_inplace(&a_c, -1, &galois_keys.unwrap())
+                .rotate_rows_inplace(&mut a_c, -1, &galois_keys.unwrap())
                 .unwrap();
 
             let a_p = decryptor.decrypt(&a_c).unwrap();
@@ -953,10 +1428,10 @@ mod tests {
 
             let a = make_matrix(&encoder);
             let a_p = encoder.encode_signed(&a).unwrap();
-            let a_c = encryptor.encrypt(&a_p).unwrap();
+            let mut a_c = encryptor.encrypt(&a_p).unwrap();
 
             evaluator
-                .rotate_columns_inplace(&a_c, &galois_keys.unwrap())
+                .rotate_columns_inplace(&mut a_c, &galois_keys.unwrap())
                 .unwrap();
 
             let a_p = decryptor.decrypt(&a_c).unwrap();
@@ -968,4 +1443,49 @@ mod tests {
             assert_eq!(a[4097], c[1]);
         });
     }
+
+    #[test]
+    fn operand_levels_are_reported_on_failure() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 20).unwrap())
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, true, SecurityLevel::TC128).unwrap();
+        let gen = KeyGenerator::new(&ctx).unwrap();
+        let encoder = BFVEncoder::new(&ctx).unwrap();
+        let encryptor =
+            Encryptor::with_public_key(&ctx, &gen.create_public_key().unwrap()).unwrap();
+        let evaluator = BFVEvaluator::new(&ctx).unwrap();
+
+        let p = encoder.encode_unsigned(&[1, 2, 3]).unwrap();
+        let a = encryptor.encrypt(&p).unwrap();
+        let b = evaluator
+            .mod_switch_to_next(&encryptor.encrypt(&p).unwrap())
+            .unwrap();
+
+        assert_eq!(a.coeff_modulus_size(), 4);
+        assert_eq!(b.coeff_modulus_size(), 3);
+
+        let err = evaluator.multiply(&a, &b).unwrap_err();
+
+        assert_eq!(err.code(), "invalid_argument");
+        assert_eq!(err.root_cause(), &Error::InvalidArgument);
+        assert_eq!(
+            err,
+            Error::OperationFailed(Box::new(OperationError {
+                operation: "multiply",
+                lhs_level: Some(4),
+                rhs_level: Some(3),
+                reason: Error::InvalidArgument,
+                message: Some("encrypted1 and encrypted2 parameter mismatch".to_owned()),
+            }))
+        );
+        assert!(err.to_string().contains("different levels 4 and 3"));
+        assert!(err.to_string().ends_with("parameter mismatch"));
+    }
 }
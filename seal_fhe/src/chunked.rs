@@ -0,0 +1,194 @@
+use crate::error::*;
+use crate::{BFVEncoder, Plaintext};
+
+/**
+ * Values split across as many plaintexts or ciphertexts as it takes to
+ * hold them, along with the number of values. Created by
+ * [`ChunkedEncoder`].
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunked<T> {
+    /**
+     * The chunks, in order. Each holds one slot's worth of values, except
+     * the last, which holds the remainder followed by zeros.
+     */
+    pub chunks: Vec<T>,
+
+    /**
+     * The number of values across all chunks, excluding the last chunk's
+     * padding.
+     */
+    pub len: usize,
+}
+
+impl<T> Chunked<T> {
+    /**
+     * Applies `f` to each chunk, e.g. to encrypt or decrypt them, keeping
+     * the length.
+     */
+    pub fn try_map<U, F>(&self, f: F) -> Result<Chunked<U>>
+    where
+        F: FnMut(&T) -> Result<U>,
+    {
+        Ok(Chunked {
+            chunks: self.chunks.iter().map(f).collect::<Result<Vec<_>>>()?,
+            len: self.len,
+        })
+    }
+}
+
+/**
+ * Encodes data of any length into as many batched plaintexts as it takes
+ * and reassembles it on decode.
+ *
+ * # Remarks
+ * Values fill each plaintext's slots in order, so value `i` lands in slot
+ * `i % slot_count` of chunk `i / slot_count`. The last chunk is padded
+ * with zeros, and decoding drops the padding using the recorded length.
+ * Slot-wise operations apply to chunks independently, but rotations
+ * don't carry values from one chunk into the next.
+ */
+pub struct ChunkedEncoder<'a> {
+    encoder: &'a BFVEncoder,
+}
+
+impl<'a> ChunkedEncoder<'a> {
+    /**
+     * Creates a chunked encoder on top of `encoder`.
+     */
+    pub fn new(encoder: &'a BFVEncoder) -> Self {
+        Self { encoder }
+    }
+
+    /**
+     * Returns the number of chunks `len` values take.
+     */
+    pub fn chunk_count(&self, len: usize) -> usize {
+        let slots = self.encoder.get_slot_count();
+
+        (len + slots - 1) / slots
+    }
+
+    /**
+     * Encodes `data` into [`chunk_count`](Self::chunk_count) plaintexts.
+     */
+    pub fn encode_unsigned(&self, data: &[u64]) -> Result<Chunked<Plaintext>> {
+        Ok(Chunked {
+            chunks: data
+                .chunks(self.encoder.get_slot_count())
+                .map(|c| self.encoder.encode_unsigned(c))
+                .collect::<Result<Vec<_>>>()?,
+            len: data.len(),
+        })
+    }
+
+    /**
+     * Encodes `data` into [`chunk_count`](Self::chunk_count) plaintexts.
+     */
+    pub fn encode_signed(&self, data: &[i64]) -> Result<Chunked<Plaintext>> {
+        Ok(Chunked {
+            chunks: data
+                .chunks(self.encoder.get_slot_count())
+                .map(|c| self.encoder.encode_signed(c))
+                .collect::<Result<Vec<_>>>()?,
+            len: data.len(),
+        })
+    }
+
+    /**
+     * Decodes the values encoded with
+     * [`encode_unsigned`](Self::encode_unsigned).
+     *
+     * Returns [`Error::InvalidArgument`] if the number of chunks doesn't
+     * match the length.
+     */
+    pub fn decode_unsigned(&self, plaintexts: &Chunked<Plaintext>) -> Result<Vec<u64>> {
+        self.decode(plaintexts, |p| self.encoder.decode_unsigned(p))
+    }
+
+    /**
+     * Decodes the values encoded with
+     * [`encode_signed`](Self::encode_signed).
+     *
+     * Returns [`Error::InvalidArgument`] if the number of chunks doesn't
+     * match the length.
+     */
+    pub fn decode_signed(&self, plaintexts: &Chunked<Plaintext>) -> Result<Vec<i64>> {
+        self.decode(plaintexts, |p| self.encoder.decode_signed(p))
+    }
+
+    fn decode<T, F>(&self, plaintexts: &Chunked<Plaintext>, decode: F) -> Result<Vec<T>>
+    where
+        F: Fn(&Plaintext) -> Result<Vec<T>>,
+    {
+        if plaintexts.chunks.len() != self.chunk_count(plaintexts.len) {
+            return Err(Error::InvalidArgument);
+        }
+
+        let mut data = Vec::with_capacity(plaintexts.chunks.len() * self.encoder.get_slot_count());
+
+        for p in plaintexts.chunks.iter() {
+            data.extend(decode(p)?);
+        }
+
+        data.truncate(plaintexts.len);
+
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn can_roundtrip_chunked_data() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(4096)
+            .set_coefficient_modulus(
+                CoefficientModulus::bfv_default(4096, SecurityLevel::TC128).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(4096, 20).unwrap())
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+        let gen = KeyGenerator::new(&ctx).unwrap();
+
+        let encoder = BFVEncoder::new(&ctx).unwrap();
+
+        let public_key = gen.create_public_key().unwrap();
+        let secret_key = gen.secret_key().unwrap();
+
+        let encryptor =
+            Encryptor::with_public_and_secret_key(&ctx, &public_key, &secret_key).unwrap();
+        let decryptor = Decryptor::new(&ctx, &secret_key).unwrap();
+
+        let chunked = ChunkedEncoder::new(&encoder);
+
+        for len in [0, 1, 4096, 4097, 10000] {
+            let data = (0..len as i64).map(|x| x % 201 - 100).collect::<Vec<_>>();
+
+            let plaintexts = chunked.encode_signed(&data).unwrap();
+            assert_eq!(plaintexts.chunks.len(), chunked.chunk_count(len));
+
+            let ciphertexts = plaintexts.try_map(|p| encryptor.encrypt(p)).unwrap();
+            let decrypted = ciphertexts.try_map(|c| decryptor.decrypt(c)).unwrap();
+
+            assert_eq!(chunked.decode_signed(&decrypted).unwrap(), data);
+        }
+
+        let data = (0..5000u64).collect::<Vec<_>>();
+        let mut plaintexts = chunked.encode_unsigned(&data).unwrap();
+
+        assert_eq!(chunked.decode_unsigned(&plaintexts).unwrap(), data);
+
+        plaintexts.chunks.pop();
+
+        assert_eq!(
+            chunked.decode_unsigned(&plaintexts),
+            Err(Error::InvalidArgument)
+        );
+    }
+}
@@ -1,10 +1,12 @@
 use std::ffi::c_void;
+use std::fmt::{Display, Formatter};
 use std::os::raw::c_int;
 use std::ptr::null_mut;
 
 use crate::bindgen;
 use crate::error::*;
 use crate::EncryptionParameters;
+use crate::Fingerprint;
 use crate::SecurityLevel;
 
 /**
@@ -46,6 +48,35 @@ pub struct Context {
     pub(crate) handle: *mut c_void,
 }
 
+/**
+ * A seed for SEAL's pseudorandom generator, which samples secret keys,
+ * encryption randomness and noise.
+ *
+ * # Remarks
+ * Key generators and encryptors created with a seed (see
+ * [`KeyGenerator::new_seeded`](crate::KeyGenerator::new_seeded) and
+ * [`Encryptor::with_public_key_seeded`](crate::Encryptor::with_public_key_seeded))
+ * are deterministic: the same seed and sequence of calls yield
+ * byte-identical keys and ciphertexts, which makes snapshot tests and
+ * reproducible benchmarks possible. Anyone who knows the seed can
+ * recompute the secret key or the randomness of a ciphertext, so only use
+ * seeds in tests.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrngSeed(pub [u64; 8]);
+
+impl PrngSeed {
+    /**
+     * Creates a seed from a single number, e.g. the index of a test case.
+     */
+    pub fn from_u64(seed: u64) -> Self {
+        let mut words = [0; 8];
+        words[0] = seed;
+
+        Self(words)
+    }
+}
+
 unsafe impl Sync for Context {}
 unsafe impl Send for Context {}
 
@@ -59,6 +90,10 @@ impl Context {
      * should be created.
      * * `security_level` - Determines whether a specific security level should be
      * enforced according to HomomorphicEncryption.org security standard.
+     *
+     * Returns [`Error::InvalidParameters`](crate::Error::InvalidParameters)
+     * describing the first problem found if SEAL rejects `params`. See
+     * [`EncryptionParameters::validate`].
      */
     pub fn new(
         params: &EncryptionParameters,
@@ -76,7 +111,22 @@ impl Context {
             )
         })?;
 
-        Ok(Context { handle })
+        let context = Context { handle };
+
+        let mut params_set = false;
+
+        convert_seal_error(unsafe {
+            bindgen::SEALContext_ParametersSet(context.handle, &mut params_set)
+        })?;
+
+        if !params_set {
+            return Err(params
+                .validate(security_level)
+                .err()
+                .unwrap_or(Error::InvalidArgument));
+        }
+
+        Ok(context)
     }
 
     /**
@@ -85,6 +135,341 @@ impl Context {
     pub fn get_handle(&self) -> *mut c_void {
         self.handle
     }
+
+    /**
+     * Returns a copy of this context whose pseudorandom generator is seeded
+     * with `seed`. Keys and ciphertexts created with the copy are valid for
+     * this context.
+     */
+    pub(crate) fn with_prng_seed(&self, seed: &PrngSeed) -> Result<Self> {
+        let mut handle: *mut c_void = null_mut();
+
+        convert_seal_error(unsafe {
+            bindgen::Shim_SEALContext_CreateSeeded(self.handle, seed.0.as_ptr(), &mut handle)
+        })?;
+
+        Ok(Context { handle })
+    }
+
+    /**
+     * Creates an auxiliary context for the level of this context's modulus
+     * switching chain with the given parms_id, without creating the levels
+     * above it.
+     *
+     * # Remarks
+     * Data at that level, e.g. a modulus switched ciphertext, loads and
+     * decrypts under the returned context as it would under this context
+     * with the chain expanded, which is much cheaper to create than the
+     * whole chain. The auxiliary context has its own key level, so get
+     * keys for it with [`SecretKey::at_level`](crate::SecretKey::at_level).
+     *
+     * Returns [`Error::InvalidArgument`] if no data level of this
+     * context's chain has the given parms_id.
+     */
+    pub fn at_level(&self, parms_id: &[u64; 4]) -> Result<Self> {
+        let mut handle: *mut c_void = null_mut();
+
+        convert_seal_error(unsafe {
+            bindgen::Shim_SEALContext_CreateAtLevel(self.handle, parms_id.as_ptr(), &mut handle)
+        })?;
+
+        Ok(Context { handle })
+    }
+
+    /**
+     * Returns the parms_id of the first parameter set in the modulus
+     * switching chain (i.e. the level fresh ciphertexts are encrypted at).
+     */
+    pub fn get_first_parms_id(&self) -> Result<[u64; 4]> {
+        let mut parms_id = [0u64; 4];
+
+        convert_seal_error(unsafe {
+            bindgen::SEALContext_FirstParmsId(self.handle, parms_id.as_mut_ptr())
+        })?;
+
+        Ok(parms_id)
+    }
+
+    /**
+     * Returns the parms_id of the key level, whose coefficient modulus
+     * includes the special prime. Keys live at this level.
+     */
+    pub fn get_key_parms_id(&self) -> Result<[u64; 4]> {
+        let mut parms_id = [0u64; 4];
+
+        convert_seal_error(unsafe {
+            bindgen::SEALContext_KeyParmsId(self.handle, parms_id.as_mut_ptr())
+        })?;
+
+        Ok(parms_id)
+    }
+
+    /**
+     * Returns the [`Fingerprint`] of the parameters this context was
+     * created with, which matches
+     * [`EncryptionParameters::fingerprint`].
+     */
+    pub fn fingerprint(&self) -> Result<Fingerprint> {
+        Ok(Fingerprint::from_parms_id(self.get_key_parms_id()?))
+    }
+
+    /**
+     * Returns the parms_id of the last parameter set in the modulus
+     * switching chain (i.e. the level ciphertexts reach after switching
+     * away every modulus but one).
+     */
+    pub fn get_last_parms_id(&self) -> Result<[u64; 4]> {
+        let mut parms_id = [0u64; 4];
+
+        convert_seal_error(unsafe {
+            bindgen::SEALContext_LastParmsId(self.handle, parms_id.as_mut_ptr())
+        })?;
+
+        Ok(parms_id)
+    }
+
+    /**
+     * Returns a copy of the encryption parameters at the first level of
+     * the modulus switching chain.
+     */
+    pub fn get_encryption_parameters(&self) -> Result<EncryptionParameters> {
+        let mut parms_id = self.get_first_parms_id()?;
+        let mut context_data: *mut c_void = null_mut();
+
+        convert_seal_error(unsafe {
+            bindgen::SEALContext_GetContextData(
+                self.handle,
+                parms_id.as_mut_ptr(),
+                &mut context_data,
+            )
+        })?;
+
+        if context_data.is_null() {
+            return Err(Error::InvalidArgument);
+        }
+
+        let mut parms: *mut c_void = null_mut();
+
+        // The context owns the context data, but SEAL returns a new copy of
+        // its parameters, which we then own.
+        convert_seal_error(unsafe { bindgen::ContextData_Parms(context_data, &mut parms) })?;
+
+        Ok(unsafe { EncryptionParameters::from_owned_handle(parms) })
+    }
+
+    /**
+     * Returns the index of the given parms_id in the modulus switching
+     * chain. Indices count down to 0 at the last level, so among ciphertexts
+     * under the same context, one with a lower index has been modulus
+     * switched further.
+     *
+     * Returns [`Error::InvalidArgument`] if the parms_id isn't in this
+     * context's chain, e.g. because the context was created without
+     * expanding the chain.
+     */
+    pub fn get_chain_index(&self, parms_id: &[u64; 4]) -> Result<u64> {
+        let mut parms_id = *parms_id;
+        let mut context_data: *mut c_void = null_mut();
+
+        convert_seal_error(unsafe {
+            bindgen::SEALContext_GetContextData(
+                self.handle,
+                parms_id.as_mut_ptr(),
+                &mut context_data,
+            )
+        })?;
+
+        if context_data.is_null() {
+            return Err(Error::InvalidArgument);
+        }
+
+        let mut index = 0;
+
+        // The context owns the context data, so we don't free it.
+        convert_seal_error(unsafe { bindgen::ContextData_ChainIndex(context_data, &mut index) })?;
+
+        Ok(index)
+    }
+
+    /**
+     * Returns the features SEAL disabled for this context's parameters.
+     * See [`ContextWarning`].
+     */
+    pub fn warnings(&self) -> Result<Vec<ContextWarning>> {
+        let mut warnings = vec![];
+
+        let mut context_data: *mut c_void = null_mut();
+
+        convert_seal_error(unsafe {
+            bindgen::SEALContext_FirstContextData(self.handle, &mut context_data)
+        })?;
+
+        if context_data.is_null() {
+            return Err(Error::InvalidArgument);
+        }
+
+        let mut qualifiers: *mut c_void = null_mut();
+
+        // The context owns the context data, but SEAL returns a new copy of
+        // its qualifiers, which we must free.
+        convert_seal_error(unsafe {
+            bindgen::ContextData_Qualifiers(context_data, &mut qualifiers)
+        })?;
+
+        let mut using_batching = false;
+        let mut using_fast_plain_lift = false;
+
+        let flags = convert_seal_error(unsafe {
+            bindgen::EPQ_UsingBatching(qualifiers, &mut using_batching)
+        })
+        .and_then(|_| {
+            convert_seal_error(unsafe {
+                bindgen::EPQ_UsingFastPlainLift(qualifiers, &mut using_fast_plain_lift)
+            })
+        });
+
+        convert_seal_error(unsafe { bindgen::EPQ_Destroy(qualifiers) })?;
+        flags?;
+
+        let mut using_keyswitching = false;
+
+        convert_seal_error(unsafe {
+            bindgen::SEALContext_UsingKeyswitching(self.handle, &mut using_keyswitching)
+        })?;
+
+        if !using_batching {
+            warnings.push(ContextWarning::BatchingDisabled);
+        }
+
+        if !using_keyswitching {
+            warnings.push(ContextWarning::KeyswitchingDisabled);
+        }
+
+        if !using_fast_plain_lift {
+            warnings.push(ContextWarning::FastPlainLiftDisabled);
+        }
+
+        Ok(warnings)
+    }
+}
+
+/**
+ * A feature SEAL disabled because of the encryption parameters a
+ * [`Context`] was created with. The context is still valid, but may not
+ * support what the application expects. Returned by
+ * [`ContextBuilder::build`] and [`Context::warnings`].
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextWarning {
+    /**
+     * The plain modulus isn't a prime congruent to 1 modulo
+     * `2 * poly_modulus_degree`, so [`BFVEncoder`](crate::BFVEncoder)
+     * can't be used. Create the plain modulus with
+     * [`PlainModulus::batching`](crate::PlainModulus::batching) to enable
+     * batching.
+     */
+    BatchingDisabled,
+
+    /**
+     * The coefficient modulus has a single prime, leaving none for the
+     * special prime key switching needs. Relinearization and rotations
+     * fail, and the modulus switching chain is empty.
+     */
+    KeyswitchingDisabled,
+
+    /**
+     * The plain modulus is at least as large as a coefficient modulus
+     * prime, so SEAL falls back to a slower way of lifting plaintexts
+     * during encryption and plaintext multiplication.
+     */
+    FastPlainLiftDisabled,
+}
+
+impl Display for ContextWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BatchingDisabled => write!(
+                f,
+                "batching disabled because the plain modulus isn't a prime congruent to 1 modulo 2 * poly_modulus_degree"
+            ),
+            Self::KeyswitchingDisabled => write!(
+                f,
+                "key switching disabled because the coefficient modulus has a single prime"
+            ),
+            Self::FastPlainLiftDisabled => write!(
+                f,
+                "fast plain lift disabled because the plain modulus isn't smaller than every coefficient modulus prime"
+            ),
+        }
+    }
+}
+
+/**
+ * Creates a [`Context`] with named options, reporting any
+ * [`ContextWarning`]s alongside it.
+ *
+ * ```ignore
+ * let (context, warnings) = ContextBuilder::new(&params)
+ *     .set_expand_mod_chain(true)
+ *     .set_security_level(SecurityLevel::TC192)
+ *     .build()?;
+ * ```
+ *
+ * # Remarks
+ * By default, the modulus switching chain is expanded and parameters
+ * must meet [`SecurityLevel::TC128`].
+ *
+ * SEAL doesn't tie a memory pool to a context; every operation in this
+ * crate allocates from SEAL's global, thread-safe pool.
+ */
+pub struct ContextBuilder<'a> {
+    params: &'a EncryptionParameters,
+    expand_mod_chain: bool,
+    security_level: SecurityLevel,
+}
+
+impl<'a> ContextBuilder<'a> {
+    /**
+     * Creates a builder for a context with the given parameters.
+     */
+    pub fn new(params: &'a EncryptionParameters) -> Self {
+        Self {
+            params,
+            expand_mod_chain: true,
+            security_level: SecurityLevel::TC128,
+        }
+    }
+
+    /**
+     * Sets whether to create the modulus switching chain, which is
+     * needed to [`mod_switch_to_next`](crate::Evaluator::mod_switch_to_next).
+     */
+    pub fn set_expand_mod_chain(mut self, expand_mod_chain: bool) -> Self {
+        self.expand_mod_chain = expand_mod_chain;
+        self
+    }
+
+    /**
+     * Sets the security level the parameters must meet according to the
+     * HomomorphicEncryption.org security standard.
+     */
+    pub fn set_security_level(mut self, security_level: SecurityLevel) -> Self {
+        self.security_level = security_level;
+        self
+    }
+
+    /**
+     * Creates the context and returns it along with any features SEAL
+     * disabled for the parameters.
+     *
+     * Fails as [`Context::new`] does if SEAL rejects the parameters.
+     */
+    pub fn build(self) -> Result<(Context, Vec<ContextWarning>)> {
+        let context = Context::new(self.params, self.expand_mod_chain, self.security_level)?;
+        let warnings = context.warnings()?;
+
+        Ok((context, warnings))
+    }
 }
 
 impl Drop for Context {
@@ -113,4 +498,143 @@ mod tests {
 
         std::mem::drop(ctx);
     }
+
+    #[test]
+    fn builder_reports_warnings() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 20).unwrap())
+            .build()
+            .unwrap();
+
+        let (ctx, warnings) = ContextBuilder::new(&params).build().unwrap();
+
+        assert_eq!(warnings, vec![]);
+        assert_eq!(
+            ctx.get_chain_index(&ctx.get_first_parms_id().unwrap())
+                .unwrap(),
+            3
+        );
+
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus_u64(1234)
+            .build()
+            .unwrap();
+
+        let (_, warnings) = ContextBuilder::new(&params)
+            .set_expand_mod_chain(false)
+            .build()
+            .unwrap();
+
+        assert_eq!(warnings, vec![ContextWarning::BatchingDisabled]);
+    }
+
+    #[test]
+    fn can_get_chain_index() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus_u64(1234)
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, true, SecurityLevel::TC128).unwrap();
+
+        let first = ctx.get_first_parms_id().unwrap();
+        let last = ctx.get_last_parms_id().unwrap();
+
+        assert_eq!(ctx.get_chain_index(&first).unwrap(), 3);
+        assert_eq!(ctx.get_chain_index(&last).unwrap(), 0);
+        assert_eq!(
+            ctx.get_chain_index(&[1, 2, 3, 4]),
+            Err(Error::InvalidArgument)
+        );
+
+        // Without the expanded chain, the first level is also the last.
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+
+        assert_eq!(ctx.get_chain_index(&first).unwrap(), 0);
+        assert_eq!(ctx.get_chain_index(&last), Err(Error::InvalidArgument));
+    }
+
+    #[test]
+    fn can_load_and_decrypt_at_level() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus_u64(1234)
+            .build()
+            .unwrap();
+
+        let expanded = Context::new(&params, true, SecurityLevel::TC128).unwrap();
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+
+        let gen = KeyGenerator::new(&ctx).unwrap();
+        let public_key = gen.create_public_key().unwrap();
+        let secret_key = gen.secret_key().unwrap();
+
+        let encoder = BFVScalarEncoder::new();
+        let encryptor = Encryptor::with_public_key(&ctx, &public_key).unwrap();
+        let evaluator = BFVEvaluator::new(&expanded).unwrap();
+
+        let ciphertext = encryptor
+            .encrypt(&encoder.encode_unsigned(42).unwrap())
+            .unwrap();
+        let ciphertext = evaluator.mod_switch_to_next(&ciphertext).unwrap();
+        let ciphertext = evaluator.mod_switch_to_next(&ciphertext).unwrap();
+
+        let bytes = ciphertext.as_bytes().unwrap();
+        let parms_id = serialized_parms_id(&bytes).unwrap();
+
+        assert_eq!(parms_id, ciphertext.get_parms_id());
+        assert!(Ciphertext::from_bytes(&ctx, &bytes).is_err());
+
+        let level_ctx = ctx.at_level(&parms_id).unwrap();
+        let level_key = secret_key.at_level(&ctx, &level_ctx).unwrap();
+
+        let loaded = Ciphertext::from_bytes(&level_ctx, &bytes).unwrap();
+        let decryptor = Decryptor::new(&level_ctx, &level_key).unwrap();
+
+        assert_eq!(
+            encoder
+                .decode_unsigned(&decryptor.decrypt(&loaded).unwrap())
+                .unwrap(),
+            42
+        );
+
+        assert_eq!(
+            ctx.at_level(&[1, 2, 3, 4]).err(),
+            Some(Error::InvalidArgument)
+        );
+        assert!(serialized_parms_id(&bytes[..8]).is_err());
+    }
+
+    #[test]
+    fn can_get_encryption_parameters() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus_u64(1234)
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, true, SecurityLevel::TC128).unwrap();
+        let copy = ctx.get_encryption_parameters().unwrap();
+
+        assert_eq!(copy.get_poly_modulus_degree(), 8192);
+        assert_eq!(copy.get_plain_modulus().value(), 1234);
+    }
 }
@@ -1,9 +1,111 @@
 use std::ffi::c_void;
+use std::ops::Range;
 use std::ptr::null_mut;
 
+use zeroize::Zeroizing;
+
 use crate::bindgen;
 use crate::error::*;
-use crate::{Context, Plaintext};
+use crate::rns;
+use crate::{Context, EncodingBuffer, Plaintext};
+
+/**
+ * A reduction over slots, used to pick a [`Padding`] value that doesn't
+ * change its result.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reduction {
+    /**
+     * Summing slots. The neutral element is 0.
+     */
+    Sum,
+
+    /**
+     * Multiplying slots. The neutral element is 1.
+     */
+    Product,
+}
+
+impl Reduction {
+    /**
+     * Returns the value that leaves this reduction's result unchanged.
+     */
+    pub fn neutral_element(&self) -> u8 {
+        match self {
+            Self::Sum => 0,
+            Self::Product => 1,
+        }
+    }
+}
+
+/**
+ * How to fill the slots past the end of data shorter than the slot count.
+ *
+ * # Remarks
+ * Padding slots take part in every slot-wise operation, so the padding
+ * value matters whenever a program combines slots, e.g. by rotating and
+ * summing. Zeros don't change a sum, but do zero a product and pull a
+ * minimum towards zero. [`RepeatLast`](Self::RepeatLast) pads with a value
+ * already in the data, which leaves minimums, maximums and other
+ * idempotent reductions unchanged.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Padding {
+    /**
+     * Fill with 0. This is what SEAL does for short data.
+     */
+    ZeroPad,
+
+    /**
+     * Fill with the last value of the data, or 0 if the data is empty.
+     */
+    RepeatLast,
+
+    /**
+     * Fill with the neutral element of the given reduction.
+     */
+    NeutralElement(Reduction),
+}
+
+impl Padding {
+    /**
+     * Returns `data` padded to `len` values.
+     *
+     * Returns [`Error::InvalidArgument`] if `data` has more than `len`
+     * values. Use [`fit`](Self::fit) to truncate instead.
+     */
+    pub fn pad<T>(&self, data: &[T], len: usize) -> Result<Vec<T>>
+    where
+        T: Copy + From<u8>,
+    {
+        if data.len() > len {
+            return Err(Error::InvalidArgument);
+        }
+
+        let fill = match self {
+            Self::ZeroPad => T::from(0),
+            Self::RepeatLast => data.last().copied().unwrap_or_else(|| T::from(0)),
+            Self::NeutralElement(r) => T::from(r.neutral_element()),
+        };
+
+        let mut padded = Vec::with_capacity(len);
+        padded.extend_from_slice(data);
+        padded.resize(len, fill);
+
+        Ok(padded)
+    }
+
+    /**
+     * Returns `data` padded or truncated to exactly `len` values.
+     */
+    pub fn fit<T>(&self, data: &[T], len: usize) -> Vec<T>
+    where
+        T: Copy + From<u8>,
+    {
+        self.pad(&data[..data.len().min(len)], len)
+            .expect("Internal error: truncated data is longer than len.")
+    }
+}
 
 /**
  * Provides functionality for CRT batching. If the polynomial modulus degree is N, and
@@ -38,6 +140,7 @@ use crate::{Context, Plaintext};
  */
 pub struct BFVEncoder {
     handle: *mut c_void,
+    plain_modulus: u64,
 }
 
 unsafe impl Sync for BFVEncoder {}
@@ -56,7 +159,12 @@ impl BFVEncoder {
 
         convert_seal_error(unsafe { bindgen::BatchEncoder_Create(ctx.get_handle(), &mut handle) })?;
 
-        Ok(Self { handle })
+        let plain_modulus = ctx.get_encryption_parameters()?.get_plain_modulus().value();
+
+        Ok(Self {
+            handle,
+            plain_modulus,
+        })
     }
 
     /**
@@ -72,6 +180,10 @@ impl BFVEncoder {
      *
      * `data` - The `2xN` matrix of integers modulo plaintext modulus to batch
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(len = data.len()))
+    )]
     pub fn encode_unsigned(&self, data: &[u64]) -> Result<Plaintext> {
         let plaintext = Plaintext::new()?;
 
@@ -102,6 +214,10 @@ impl BFVEncoder {
      *
      * `data` - The `2xN` matrix of integers modulo plaintext modulus to batch
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(len = data.len()))
+    )]
     pub fn encode_signed(&self, data: &[i64]) -> Result<Plaintext> {
         let plaintext = Plaintext::new()?;
 
@@ -131,6 +247,7 @@ impl BFVEncoder {
      *
      * * `plain` - The plaintext polynomial to unbatch
      */
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn decode_unsigned(&self, plaintext: &Plaintext) -> Result<Vec<u64>> {
         let mut data = Vec::with_capacity(self.get_slot_count());
         let data_ptr = data.as_mut_ptr();
@@ -168,8 +285,24 @@ impl BFVEncoder {
      * The input plaintext matrix should be known to contain `i64` elements.
      *
      * * `plain` - The plaintext polynomial to unbatch
+     *
+     * # Remarks
+     * With the `hardened` feature, this maps slots to signed values without
+     * branching on them and zeroes the intermediate unsigned slots, rather
+     * than using SEAL's decoder. Only the decoding is hardened; decrypting
+     * the plaintext in the first place isn't.
      */
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn decode_signed(&self, plaintext: &Plaintext) -> Result<Vec<i64>> {
+        if cfg!(feature = "hardened") {
+            let data = Zeroizing::new(self.decode_unsigned(plaintext)?);
+
+            return Ok(data
+                .iter()
+                .map(|x| center_constant_time(*x, self.plain_modulus))
+                .collect());
+        }
+
         let mut data = Vec::with_capacity(self.get_slot_count());
         let data_ptr = data.as_mut_ptr();
         let mut size: u64 = 0;
@@ -195,6 +328,87 @@ impl BFVEncoder {
         Ok(data)
     }
 
+    /**
+     * Decodes only the slots in `range` of the given plaintext into `out`.
+     * Use this rather than [`decode_signed`](Self::decode_signed) when you only
+     * need a handful of the plaintext's slots.
+     *
+     * The input plaintext matrix should be known to contain `i64` elements.
+     *
+     * * `plain` - The plaintext polynomial to unbatch
+     * * `range` - The slots to decode. Must lie within `0..get_slot_count()`.
+     * * `out` - The destination buffer. Its length must equal `range.len()`.
+     *
+     * # Remarks
+     * Rather than transforming the whole plaintext, this evaluates the
+     * plaintext polynomial at the root of unity behind each requested
+     * slot. That takes time linear in the polynomial degree per slot, so
+     * past roughly log2 of the slot count slots,
+     * [`decode_signed`](Self::decode_signed) is faster.
+     */
+    pub fn decode_signed_range(
+        &self,
+        plaintext: &Plaintext,
+        range: Range<usize>,
+        out: &mut [i64],
+    ) -> Result<()> {
+        let slot_count = self.get_slot_count();
+
+        if range.start > range.end || range.end > slot_count {
+            return Err(Error::InvalidArgument);
+        }
+
+        if out.len() != range.len() {
+            return Err(Error::InvalidArgument);
+        }
+
+        if plaintext.is_ntt_form() {
+            return Err(Error::InvalidArgument);
+        }
+
+        let t = self.plain_modulus;
+        let root = rns::minimal_primitive_root(slot_count, t)?;
+
+        let coefficients = Zeroizing::new(
+            (0..plaintext.len())
+                .map(|i| plaintext.get_coefficient(i) % t)
+                .collect::<Vec<_>>(),
+        );
+
+        // SEAL lays out the first row's slots at the roots root^(3^i) and
+        // the second row's at root^(-3^i), with exponents modulo 2N.
+        let row_size = slot_count / 2;
+        let m = 2 * slot_count as u64;
+
+        for (slot, out) in range.zip(out.iter_mut()) {
+            let exponent = rns::pow_mod(3, (slot % row_size) as u64, m);
+            let exponent = if slot < row_size {
+                exponent
+            } else {
+                m - exponent
+            };
+
+            let x = rns::pow_mod(root, exponent, t);
+
+            let value = coefficients
+                .iter()
+                .rev()
+                .fold(0, |acc, c| rns::add_mod(rns::mul_mod(acc, x, t), *c, t));
+
+            *out = center_constant_time(value, t);
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Returns the plaintext modulus of the context this encoder was
+     * created with.
+     */
+    pub fn get_plain_modulus(&self) -> u64 {
+        self.plain_modulus
+    }
+
     /**
      * Returns the number of "Batched" slots in this encoder produces.
      */
@@ -206,6 +420,175 @@ impl BFVEncoder {
 
         count as usize
     }
+
+    /**
+     * Like [`encode_unsigned`](Self::encode_unsigned), but fills the slots
+     * past the end of `data` according to `padding` rather than with 0.
+     *
+     * Returns [`Error::InvalidArgument`] if `data` has more values than
+     * there are slots.
+     */
+    pub fn encode_unsigned_padded(&self, data: &[u64], padding: Padding) -> Result<Plaintext> {
+        self.encode_unsigned(&padding.pad(data, self.get_slot_count())?)
+    }
+
+    /**
+     * Like [`encode_signed`](Self::encode_signed), but fills the slots
+     * past the end of `data` according to `padding` rather than with 0.
+     *
+     * Returns [`Error::InvalidArgument`] if `data` has more values than
+     * there are slots.
+     */
+    pub fn encode_signed_padded(&self, data: &[i64], padding: Padding) -> Result<Plaintext> {
+        self.encode_signed(&padding.pad(data, self.get_slot_count())?)
+    }
+
+    /**
+     * Encodes `data` into the slots starting at `offset`, with 0 in every
+     * other slot.
+     *
+     * Returns [`Error::InvalidArgument`] if `data` runs past the last slot.
+     */
+    pub fn encode_unsigned_at(&self, offset: usize, data: &[u64]) -> Result<Plaintext> {
+        self.encode_unsigned(&place_at(offset, data, self.get_slot_count())?)
+    }
+
+    /**
+     * Encodes `data` into the slots starting at `offset`, with 0 in every
+     * other slot.
+     *
+     * Returns [`Error::InvalidArgument`] if `data` runs past the last slot.
+     */
+    pub fn encode_signed_at(&self, offset: usize, data: &[i64]) -> Result<Plaintext> {
+        self.encode_signed(&place_at(offset, data, self.get_slot_count())?)
+    }
+
+    /**
+     * Encodes each `(slot, value)` pair into its slot, with 0 in every
+     * other slot. If a slot appears more than once, its last value wins.
+     *
+     * Returns [`Error::InvalidArgument`] if a slot is out of range.
+     */
+    pub fn encode_unsigned_sparse(&self, values: &[(usize, u64)]) -> Result<Plaintext> {
+        self.encode_unsigned(&place_sparse(values, self.get_slot_count())?)
+    }
+
+    /**
+     * Encodes each `(slot, value)` pair into its slot, with 0 in every
+     * other slot. If a slot appears more than once, its last value wins.
+     *
+     * Returns [`Error::InvalidArgument`] if a slot is out of range.
+     */
+    pub fn encode_signed_sparse(&self, values: &[(usize, i64)]) -> Result<Plaintext> {
+        self.encode_signed(&place_sparse(values, self.get_slot_count())?)
+    }
+
+    /**
+     * Encodes a mask with 1 in each of the given slots and 0 in every other
+     * slot. Multiplying a ciphertext by the mask zeroes the other slots, and
+     * [`BFVEvaluator::select`](crate::BFVEvaluator::select) uses it to
+     * choose slots from two ciphertexts.
+     *
+     * Returns [`Error::InvalidArgument`] if a slot is out of range.
+     */
+    pub fn encode_mask(&self, slots: &[usize]) -> Result<Plaintext> {
+        let mut data = vec![0u64; self.get_slot_count()];
+
+        for s in slots {
+            *data.get_mut(*s).ok_or(Error::InvalidArgument)? = 1;
+        }
+
+        self.encode_unsigned(&data)
+    }
+
+    /**
+     * Encodes a mask with 1 in the given range of slots and 0 in every other
+     * slot. See [`encode_mask`](Self::encode_mask).
+     *
+     * Returns [`Error::InvalidArgument`] if the range is out of bounds.
+     */
+    pub fn encode_mask_range(&self, range: Range<usize>) -> Result<Plaintext> {
+        let mut data = vec![0u64; self.get_slot_count()];
+
+        data.get_mut(range).ok_or(Error::InvalidArgument)?.fill(1);
+
+        self.encode_unsigned(&data)
+    }
+
+    /**
+     * Stages `data` in `buffer` (see [`EncodingBuffer::stage`]) and
+     * encodes the staged slots.
+     *
+     * # Remarks
+     * This skips the per-value conversion SEAL performs on signed data and
+     * reuses `buffer`'s slots, so encoding many plaintexts through one
+     * buffer avoids an allocation per plaintext.
+     *
+     * Returns [`Error::InvalidArgument`] if `data` has more values than
+     * there are slots.
+     */
+    pub fn encode_staged(&self, buffer: &mut EncodingBuffer, data: &[i64]) -> Result<Plaintext> {
+        self.encode_unsigned(buffer.stage(data)?)
+    }
+
+    /**
+     * Encodes each column of a dataset into its own plaintext, with row
+     * `i` of a column in slot `i` and 0 in the slots past its end.
+     *
+     * # Remarks
+     * Columns are staged through one [`EncodingBuffer`], so this performs
+     * one allocation for the whole dataset besides the plaintexts. Slot
+     * `i` of every plaintext holds the same row, so slot-wise operations
+     * between the plaintexts combine each row's columns.
+     *
+     * Returns [`Error::InvalidArgument`] if a column has more values than
+     * there are slots.
+     */
+    pub fn encode_columns(&self, columns: &[&[i64]]) -> Result<Vec<Plaintext>> {
+        let mut buffer = EncodingBuffer::for_encoder(self);
+
+        columns
+            .iter()
+            .map(|c| self.encode_staged(&mut buffer, c))
+            .collect()
+    }
+}
+
+/**
+ * Returns `len` zeros with `data` copied in at `offset`.
+ */
+fn place_at<T>(offset: usize, data: &[T], len: usize) -> Result<Vec<T>>
+where
+    T: Copy + From<u8>,
+{
+    let mut slots = vec![T::from(0); len];
+
+    let end = offset
+        .checked_add(data.len())
+        .ok_or(Error::InvalidArgument)?;
+
+    slots
+        .get_mut(offset..end)
+        .ok_or(Error::InvalidArgument)?
+        .copy_from_slice(data);
+
+    Ok(slots)
+}
+
+/**
+ * Returns `len` zeros with each value written to its slot.
+ */
+fn place_sparse<T>(values: &[(usize, T)], len: usize) -> Result<Vec<T>>
+where
+    T: Copy + From<u8>,
+{
+    let mut slots = vec![T::from(0); len];
+
+    for (slot, value) in values {
+        *slots.get_mut(*slot).ok_or(Error::InvalidArgument)? = *value;
+    }
+
+    Ok(slots)
 }
 
 impl Drop for BFVEncoder {
@@ -276,6 +659,18 @@ impl BFVScalarEncoder {
     }
 }
 
+/**
+ * Maps `x` in `[0, t)` to `x` if `x <= t / 2` and `x - t` otherwise, as
+ * SEAL's signed decoder does, but without branching on `x`.
+ */
+fn center_constant_time(x: u64, t: u64) -> i64 {
+    // Plain moduli have at most 60 bits, so the subtraction's top bit is
+    // set exactly when it wraps, i.e. when x > t / 2.
+    let mask = 0u64.wrapping_sub(((t >> 1).wrapping_sub(x)) >> 63);
+
+    (x as i64).wrapping_sub((t & mask) as i64)
+}
+
 impl Default for BFVScalarEncoder {
     fn default() -> Self {
         Self::new()
@@ -322,6 +717,263 @@ mod tests {
         assert_eq!(encoder.get_slot_count(), 8192);
     }
 
+    #[test]
+    fn constant_time_centering_matches_seal() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 20).unwrap())
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+
+        let encoder = BFVEncoder::new(&ctx).unwrap();
+        let t = encoder.get_plain_modulus();
+
+        assert_eq!(t, params.get_plain_modulus().value());
+
+        for x in [0, 1, t / 2 - 1, t / 2, t / 2 + 1, t - 1] {
+            let expected = if x > t / 2 {
+                x as i64 - t as i64
+            } else {
+                x as i64
+            };
+
+            assert_eq!(super::center_constant_time(x, t), expected);
+        }
+
+        let data = (0..8192).map(|x| x - 4096).collect::<Vec<i64>>();
+        let plaintext = encoder.encode_signed(&data).unwrap();
+
+        assert_eq!(encoder.decode_signed(&plaintext).unwrap(), data);
+    }
+
+    #[test]
+    fn decode_signed_range_matches_decode_signed() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 20).unwrap())
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+
+        let encoder = BFVEncoder::new(&ctx).unwrap();
+
+        let data = (0..8192)
+            .map(|x| (x * 37) % 1000 - 500)
+            .collect::<Vec<i64>>();
+        let plaintext = encoder.encode_signed(&data).unwrap();
+
+        // Cover the start of both rows and the boundary between them.
+        for range in [0..5, 4090..4100, 8187..8192] {
+            let mut out = vec![0; range.len()];
+
+            encoder
+                .decode_signed_range(&plaintext, range.clone(), &mut out)
+                .unwrap();
+
+            assert_eq!(out, data[range]);
+        }
+    }
+
+    #[test]
+    fn can_encode_columns() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 20).unwrap())
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+
+        let encoder = BFVEncoder::new(&ctx).unwrap();
+
+        let ages = (0..8192).map(|x| x % 100).collect::<Vec<i64>>();
+        let balances = (0..1000).map(|x| 500 - x).collect::<Vec<i64>>();
+
+        let plaintexts = encoder
+            .encode_columns(&[ages.as_slice(), balances.as_slice()])
+            .unwrap();
+
+        assert_eq!(plaintexts.len(), 2);
+        assert_eq!(encoder.decode_signed(&plaintexts[0]).unwrap(), ages);
+
+        let decoded = encoder.decode_signed(&plaintexts[1]).unwrap();
+
+        assert_eq!(&decoded[..1000], balances.as_slice());
+        assert!(decoded[1000..].iter().all(|x| *x == 0));
+
+        // Staging matches SEAL's own conversion.
+        assert!(plaintexts[1] == encoder.encode_signed(&balances).unwrap());
+
+        let too_long = vec![0; 8193];
+
+        assert_eq!(
+            encoder.encode_columns(&[too_long.as_slice()]).err(),
+            Some(Error::InvalidArgument)
+        );
+    }
+
+    #[test]
+    fn can_encode_masks() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 20).unwrap())
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+
+        let encoder = BFVEncoder::new(&ctx).unwrap();
+
+        let mask = encoder
+            .decode_unsigned(&encoder.encode_mask(&[1, 4096, 8191]).unwrap())
+            .unwrap();
+
+        for (i, m) in mask.iter().enumerate() {
+            assert_eq!(*m, u64::from([1, 4096, 8191].contains(&i)));
+        }
+
+        let mask = encoder
+            .decode_unsigned(&encoder.encode_mask_range(2..5).unwrap())
+            .unwrap();
+
+        for (i, m) in mask.iter().enumerate() {
+            assert_eq!(*m, u64::from((2..5).contains(&i)));
+        }
+
+        assert!(encoder.encode_mask(&[8192]).is_err());
+        assert!(encoder.encode_mask_range(8000..8193).is_err());
+    }
+
+    #[test]
+    fn can_pad_and_fit() {
+        let data = [3i64, -1, 4];
+
+        assert_eq!(Padding::ZeroPad.pad(&data, 5), Ok(vec![3, -1, 4, 0, 0]));
+        assert_eq!(Padding::RepeatLast.pad(&data, 5), Ok(vec![3, -1, 4, 4, 4]));
+        assert_eq!(
+            Padding::NeutralElement(Reduction::Product).pad(&data, 5),
+            Ok(vec![3, -1, 4, 1, 1])
+        );
+        assert_eq!(Padding::RepeatLast.pad::<u64>(&[], 2), Ok(vec![0, 0]));
+        assert_eq!(Padding::ZeroPad.pad(&data, 2), Err(Error::InvalidArgument));
+
+        assert_eq!(Padding::RepeatLast.fit(&data, 2), vec![3, -1]);
+        assert_eq!(Padding::RepeatLast.fit(&data, 4), vec![3, -1, 4, 4]);
+    }
+
+    #[test]
+    fn can_encode_padded() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 20).unwrap())
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+
+        let encoder = BFVEncoder::new(&ctx).unwrap();
+
+        let plaintext = encoder
+            .encode_signed_padded(&[-2, 7], Padding::RepeatLast)
+            .unwrap();
+        let data = encoder.decode_signed(&plaintext).unwrap();
+
+        assert_eq!(data.len(), 8192);
+        assert_eq!(&data[..2], &[-2, 7]);
+        assert!(data[2..].iter().all(|x| *x == 7));
+
+        let plaintext = encoder
+            .encode_unsigned_padded(&[5], Padding::NeutralElement(Reduction::Product))
+            .unwrap();
+        let data = encoder.decode_unsigned(&plaintext).unwrap();
+
+        assert_eq!(data[0], 5);
+        assert!(data[1..].iter().all(|x| *x == 1));
+
+        assert_eq!(
+            encoder.encode_unsigned_padded(&[0; 8193], Padding::ZeroPad),
+            Err(Error::InvalidArgument)
+        );
+    }
+
+    #[test]
+    fn can_encode_at_offsets_and_sparse_slots() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 20).unwrap())
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+
+        let encoder = BFVEncoder::new(&ctx).unwrap();
+
+        let data = encoder
+            .decode_signed(&encoder.encode_signed_at(4095, &[-1, 2, -3]).unwrap())
+            .unwrap();
+
+        assert_eq!(&data[4095..4098], &[-1, 2, -3]);
+        assert!(data[..4095].iter().chain(&data[4098..]).all(|x| *x == 0));
+
+        let data = encoder
+            .decode_unsigned(&encoder.encode_unsigned_at(8190, &[6, 9]).unwrap())
+            .unwrap();
+
+        assert_eq!(&data[8190..], &[6, 9]);
+
+        let data = encoder
+            .decode_signed(
+                &encoder
+                    .encode_signed_sparse(&[(7, -5), (4096, 3), (7, 8)])
+                    .unwrap(),
+            )
+            .unwrap();
+
+        for (i, x) in data.iter().enumerate() {
+            let expected = match i {
+                7 => 8,
+                4096 => 3,
+                _ => 0,
+            };
+
+            assert_eq!(*x, expected);
+        }
+
+        assert_eq!(
+            encoder.encode_signed_at(8191, &[1, 2]),
+            Err(Error::InvalidArgument)
+        );
+        assert_eq!(
+            encoder.encode_unsigned_at(usize::MAX, &[1]),
+            Err(Error::InvalidArgument)
+        );
+        assert_eq!(
+            encoder.encode_unsigned_sparse(&[(8192, 1)]),
+            Err(Error::InvalidArgument)
+        );
+    }
+
     #[test]
     fn can_get_encode_and_decode_unsigned() {
         let params = BfvEncryptionParametersBuilder::new()
@@ -1,11 +1,13 @@
 use std::ffi::c_void;
+use std::hash::{Hash, Hasher};
 use std::mem::forget;
 use std::ptr::null_mut;
 
 use crate::bindgen::{self};
-use crate::error::{convert_seal_error, Error};
+use crate::error::{convert_seal_error, Error, ParameterError};
 use crate::modulus::unchecked_from_handle;
-use crate::Modulus;
+use crate::rns::{mul_mod, pow_mod};
+use crate::{CoefficientModulus, Fingerprint, Modulus, SecurityLevel};
 
 use serde::{Deserialize, Serialize};
 
@@ -13,7 +15,7 @@ use serde::{Deserialize, Serialize};
  * The FHE scheme supported by SEAL.
  */
 #[repr(u8)]
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum SchemeType {
     /// None. Don't use this.
     None = 0x0,
@@ -54,10 +56,9 @@ impl SchemeType {
  * objects created for these encryption parameters. The ParmsId is not intended
  * to be directly modified by the user but is used internally for pre-computation
  * data lookup and input validity checks. In modulus switching the user can use
- * the ParmsId to keep track of the chain of encryption parameters. The ParmsId is
- * not exposed in the public API of EncryptionParameters, but can be accessed
- * through the SEALContext.ContextData" class once the SEALContext
- * has been created.
+ * the ParmsId to keep track of the chain of encryption parameters. You can read it
+ * with [`get_parms_id`](EncryptionParameters::get_parms_id). Two parameter sets are
+ * equal if and only if they have the same scheme and ParmsId.
  *
  * Choosing inappropriate encryption parameters may lead to an encryption scheme
  * that is not secure, does not perform well, and/or does not support the input
@@ -81,6 +82,18 @@ impl EncryptionParameters {
         Ok(Self { handle })
     }
 
+    /**
+     * Wraps a handle to SEAL encryption parameters this object takes
+     * ownership of.
+     *
+     * # Safety
+     * The handle must point to a valid SEAL `EncryptionParameters` object
+     * that nothing else frees.
+     */
+    pub(crate) unsafe fn from_owned_handle(handle: *mut c_void) -> Self {
+        Self { handle }
+    }
+
     /**
      * Returns the handle to the underlying SEAL object.
      */
@@ -184,6 +197,289 @@ impl EncryptionParameters {
             })
             .collect()
     }
+
+    /**
+     * Returns the ParmsId of these parameters. This is a hash of the
+     * scheme, polynomial degree, coefficient modulus and plain modulus.
+     *
+     * # Remarks
+     * This identifies the parameters at the top of the modulus switching chain,
+     * which includes the special prime used for key switching. Ciphertexts
+     * are encrypted at the next level down, so compare their ParmsId against
+     * [`Context::get_first_parms_id`](crate::Context::get_first_parms_id)
+     * instead.
+     */
+    pub fn get_parms_id(&self) -> [u64; 4] {
+        let mut parms_id = [0u64; 4];
+
+        unsafe {
+            convert_seal_error(bindgen::EncParams_GetParmsId(
+                self.handle,
+                parms_id.as_mut_ptr(),
+            ))
+            .expect("Internal error");
+        };
+
+        parms_id
+    }
+
+    /**
+     * Returns a stable hash of these parameters, derived from their
+     * ParmsId. See [`Fingerprint`].
+     */
+    pub fn fingerprint(&self) -> Fingerprint {
+        Fingerprint::from_parms_id(self.get_parms_id())
+    }
+}
+
+const POLY_MODULUS_DEGREE_MIN: u64 = 2;
+const POLY_MODULUS_DEGREE_MAX: u64 = 131072;
+const COEFF_MODULUS_COUNT_MAX: usize = 64;
+const USER_MODULUS_BITS_MIN: u32 = 2;
+const USER_MODULUS_BITS_MAX: u32 = 60;
+
+fn bit_count(x: u64) -> u32 {
+    u64::BITS - x.leading_zeros()
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+
+    a
+}
+
+/**
+ * Deterministic Miller-Rabin primality test for 64-bit values.
+ */
+fn is_prime(n: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+    if n < 2 {
+        return false;
+    }
+
+    for p in BASES {
+        if n % p == 0 {
+            return n == p;
+        }
+    }
+
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+
+    'witness: for a in BASES {
+        let mut x = pow_mod(a, d, n);
+
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+
+            if x == n - 1 {
+                continue 'witness;
+            }
+        }
+
+        return false;
+    }
+
+    true
+}
+
+/**
+ * Returns the number of bits in the product of the given moduli and whether
+ * that product fits in a u64.
+ */
+fn product_bit_count(moduli: &[u64]) -> (u32, Option<u64>) {
+    let mut limbs = vec![1u64];
+
+    for m in moduli {
+        let mut carry = 0u128;
+
+        for limb in limbs.iter_mut() {
+            let x = *limb as u128 * *m as u128 + carry;
+            *limb = x as u64;
+            carry = x >> 64;
+        }
+
+        if carry > 0 {
+            limbs.push(carry as u64);
+        }
+    }
+
+    let top = *limbs.last().unwrap();
+    let bits = u64::BITS * (limbs.len() as u32 - 1) + bit_count(top);
+    let value = if limbs.len() == 1 { Some(top) } else { None };
+
+    (bits, value)
+}
+
+impl EncryptionParameters {
+    /**
+     * Checks these parameters against the same rules SEAL applies when
+     * creating a [`Context`](crate::Context) and returns the first violation
+     * as an [`Error::InvalidParameters`] describing the offending values.
+     *
+     * # Remarks
+     * [`Context::new`](crate::Context::new) calls this to explain why SEAL
+     * rejected a parameter set, so you only need to call it directly to
+     * check parameters without creating a context.
+     *
+     * This doesn't check whether the parameters support batching. See
+     * [`validate_batching`](Self::validate_batching).
+     */
+    pub fn validate(&self, security_level: SecurityLevel) -> Result<(), Error> {
+        let degree = self.get_poly_modulus_degree();
+
+        if !degree.is_power_of_two()
+            || !(POLY_MODULUS_DEGREE_MIN..=POLY_MODULUS_DEGREE_MAX).contains(&degree)
+        {
+            return Err(ParameterError::InvalidPolyModulusDegree {
+                degree,
+                min: POLY_MODULUS_DEGREE_MIN,
+                max: POLY_MODULUS_DEGREE_MAX,
+            }
+            .into());
+        }
+
+        let coeff_modulus = self
+            .get_coefficient_modulus()
+            .iter()
+            .map(|m| m.value())
+            .collect::<Vec<u64>>();
+
+        if coeff_modulus.is_empty() || coeff_modulus.len() > COEFF_MODULUS_COUNT_MAX {
+            return Err(ParameterError::InvalidCoeffModulusCount {
+                count: coeff_modulus.len(),
+                max: COEFF_MODULUS_COUNT_MAX,
+            }
+            .into());
+        }
+
+        for (i, q) in coeff_modulus.iter().enumerate() {
+            if !(USER_MODULUS_BITS_MIN..=USER_MODULUS_BITS_MAX).contains(&bit_count(*q)) {
+                return Err(ParameterError::InvalidCoeffModulusBitCount {
+                    modulus: *q,
+                    min_bits: USER_MODULUS_BITS_MIN,
+                    max_bits: USER_MODULUS_BITS_MAX,
+                }
+                .into());
+            }
+
+            for r in coeff_modulus.iter().skip(i + 1) {
+                if gcd(*q, *r) != 1 {
+                    return Err(ParameterError::CoeffModulusNotCoprime { a: *q, b: *r }.into());
+                }
+            }
+
+            if q % (2 * degree) != 1 || !is_prime(*q) {
+                return Err(ParameterError::CoeffModulusNotNttFriendly {
+                    modulus: *q,
+                    degree,
+                }
+                .into());
+            }
+        }
+
+        let (bits, total_modulus) = product_bit_count(&coeff_modulus);
+
+        let max_bits = CoefficientModulus::try_max_bit_count(degree, security_level).ok_or(
+            ParameterError::DegreeUnsupportedForSecurity {
+                degree,
+                security_level,
+            },
+        )?;
+
+        if bits > max_bits {
+            return Err(ParameterError::CoeffModulusTooLargeForSecurity {
+                bits,
+                max_bits,
+                degree,
+                security_level,
+            }
+            .into());
+        }
+
+        if self.get_scheme() != SchemeType::Bfv {
+            return Ok(());
+        }
+
+        let plain_modulus = self.get_plain_modulus().value();
+
+        if !(USER_MODULUS_BITS_MIN..=USER_MODULUS_BITS_MAX).contains(&bit_count(plain_modulus)) {
+            return Err(ParameterError::InvalidPlainModulusBitCount {
+                plain_modulus,
+                min_bits: USER_MODULUS_BITS_MIN,
+                max_bits: USER_MODULUS_BITS_MAX,
+            }
+            .into());
+        }
+
+        for q in coeff_modulus.iter() {
+            if gcd(plain_modulus, *q) != 1 {
+                return Err(ParameterError::PlainModulusNotCoprime {
+                    plain_modulus,
+                    coeff_modulus: *q,
+                }
+                .into());
+            }
+        }
+
+        if matches!(total_modulus, Some(q) if plain_modulus >= q) {
+            return Err(ParameterError::PlainModulusTooLarge { plain_modulus }.into());
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Checks whether these BFV parameters support batching (i.e. encoding
+     * with a [`BFVEncoder`](crate::BFVEncoder)), which requires the plaintext
+     * modulus to be a prime congruent to 1 modulo `2 * degree`.
+     *
+     * Returns [`Error::InvalidParameters`] with
+     * [`ParameterError::PlainModulusNotPrime`] or
+     * [`ParameterError::BatchingUnsupported`] if not.
+     */
+    pub fn validate_batching(&self) -> Result<(), Error> {
+        let degree = self.get_poly_modulus_degree();
+        let plain_modulus = self.get_plain_modulus().value();
+
+        if !is_prime(plain_modulus) {
+            return Err(ParameterError::PlainModulusNotPrime { plain_modulus }.into());
+        }
+
+        if plain_modulus % (2 * degree) != 1 {
+            return Err(ParameterError::BatchingUnsupported {
+                plain_modulus,
+                degree,
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+impl PartialEq for EncryptionParameters {
+    fn eq(&self, other: &Self) -> bool {
+        self.get_scheme() == other.get_scheme() && self.get_parms_id() == other.get_parms_id()
+    }
+}
+
+impl Eq for EncryptionParameters {}
+
+impl Hash for EncryptionParameters {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.get_scheme().hash(state);
+        self.get_parms_id().hash(state);
+    }
 }
 
 enum CoefficientModulusType {
@@ -327,6 +623,63 @@ mod tests {
     use crate::CoefficientModulus;
     use crate::*;
 
+    fn make_params(plain_modulus: u64) -> EncryptionParameters {
+        BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::bfv_default(8192, SecurityLevel::default()).unwrap(),
+            )
+            .set_plain_modulus_u64(plain_modulus)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn can_compare_params() {
+        use std::collections::HashSet;
+
+        let a = make_params(1234);
+        let b = make_params(1234);
+        let c = make_params(4321);
+
+        assert_eq!(a.get_parms_id(), b.get_parms_id());
+        assert_ne!(a.get_parms_id(), c.get_parms_id());
+        assert!(a == b);
+        assert!(a != c);
+
+        let set: HashSet<_> = [a, b, c].into_iter().collect();
+
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn ciphertext_parms_id_matches_context() {
+        let params = make_params(1024);
+        let ctx = Context::new(&params, true, SecurityLevel::default()).unwrap();
+
+        assert_ne!(ctx.get_first_parms_id().unwrap(), params.get_parms_id());
+
+        let gen = KeyGenerator::new(&ctx).unwrap();
+        let encoder = BFVScalarEncoder::new();
+        let encryptor =
+            Encryptor::with_public_key(&ctx, &gen.create_public_key().unwrap()).unwrap();
+
+        let ciphertext = encryptor
+            .encrypt(&encoder.encode_unsigned(7).unwrap())
+            .unwrap();
+
+        assert_eq!(ciphertext.get_parms_id(), ctx.get_first_parms_id().unwrap());
+
+        let evaluator = BFVEvaluator::new(&ctx).unwrap();
+        let mut ciphertext = ciphertext;
+
+        while ciphertext.get_parms_id() != ctx.get_last_parms_id().unwrap() {
+            evaluator
+                .mod_switch_to_next_inplace(&mut ciphertext)
+                .unwrap();
+        }
+    }
+
     #[test]
     fn can_build_params() {
         let params = BfvEncryptionParametersBuilder::new()
@@ -365,4 +718,100 @@ mod tests {
         assert_eq!(modulus[3].value(), 1125899906629633);
         assert_eq!(modulus[4].value(), 1125899906826241);
     }
+
+    #[test]
+    fn can_test_primality() {
+        for p in [2, 3, 65537, 132120577, 1125899906629633] {
+            assert!(super::is_prime(p));
+        }
+
+        for n in [0, 1, 4, 561, 65537 * 65539, 1125899906629633 * 3] {
+            assert!(!super::is_prime(n));
+        }
+    }
+
+    #[test]
+    fn can_validate_params() {
+        let params = make_params(65537);
+
+        params.validate(SecurityLevel::default()).unwrap();
+        params.validate_batching().unwrap();
+
+        assert_eq!(
+            make_params(1024).validate_batching(),
+            Err(Error::InvalidParameters(Box::new(
+                ParameterError::PlainModulusNotPrime {
+                    plain_modulus: 1024
+                }
+            )))
+        );
+
+        assert_eq!(
+            make_params(12289).validate_batching(),
+            Err(Error::InvalidParameters(Box::new(
+                ParameterError::BatchingUnsupported {
+                    plain_modulus: 12289,
+                    degree: 8192
+                }
+            )))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_params() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(1000)
+            .set_coefficient_modulus(
+                CoefficientModulus::bfv_default(1024, SecurityLevel::default()).unwrap(),
+            )
+            .set_plain_modulus_u64(1234)
+            .build()
+            .unwrap();
+
+        assert!(matches!(
+            params.validate(SecurityLevel::default()),
+            Err(Error::InvalidParameters(e))
+                if matches!(*e, ParameterError::InvalidPolyModulusDegree { degree: 1000, .. })
+        ));
+
+        let coeff_modulus =
+            CoefficientModulus::bfv_default(8192, SecurityLevel::default()).unwrap();
+        let plain_modulus = coeff_modulus[0].value();
+
+        assert_eq!(
+            make_params(plain_modulus).validate(SecurityLevel::default()),
+            Err(Error::InvalidParameters(Box::new(
+                ParameterError::PlainModulusNotCoprime {
+                    plain_modulus,
+                    coeff_modulus: plain_modulus
+                }
+            )))
+        );
+    }
+
+    #[test]
+    fn context_reports_invalid_params() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(1024)
+            .set_coefficient_modulus(
+                CoefficientModulus::bfv_default(8192, SecurityLevel::default()).unwrap(),
+            )
+            .set_plain_modulus_u64(1234)
+            .build()
+            .unwrap();
+
+        let err = Context::new(&params, true, SecurityLevel::default())
+            .err()
+            .unwrap();
+
+        assert!(matches!(
+            err,
+            Error::InvalidParameters(e)
+                if matches!(*e, ParameterError::CoeffModulusTooLargeForSecurity {
+                    degree: 1024,
+                    max_bits: 27,
+                    ..
+                })
+        ));
+    }
 }
@@ -1,9 +1,10 @@
 use std::ffi::c_void;
+use std::ops::Range;
 use std::ptr::null_mut;
 
 use crate::bindgen;
 use crate::error::*;
-use crate::{Ciphertext, Context, Plaintext, PublicKey, SecretKey};
+use crate::{BFVEncoder, Ciphertext, Context, Plaintext, PrngSeed, PublicKey, SecretKey};
 
 /**
  *
@@ -88,6 +89,36 @@ impl Encryptor {
         Ok(Encryptor { handle })
     }
 
+    /**
+     * Creates an Encryptor like
+     * [`with_public_and_secret_key`](Self::with_public_and_secret_key)
+     * whose encryption randomness derives from `seed`. Encrypting the same
+     * plaintexts in the same order yields byte-identical ciphertexts. See
+     * [`PrngSeed`].
+     */
+    pub fn with_public_and_secret_key_seeded(
+        ctx: &Context,
+        public_key: &PublicKey,
+        secret_key: &SecretKey,
+        seed: &PrngSeed,
+    ) -> Result<Encryptor> {
+        Self::with_public_and_secret_key(&ctx.with_prng_seed(seed)?, public_key, secret_key)
+    }
+
+    /**
+     * Creates an Encryptor like [`with_public_key`](Self::with_public_key)
+     * whose encryption randomness derives from `seed`. Encrypting the same
+     * plaintexts in the same order yields byte-identical ciphertexts. See
+     * [`PrngSeed`].
+     */
+    pub fn with_public_key_seeded(
+        ctx: &Context,
+        public_key: &PublicKey,
+        seed: &PrngSeed,
+    ) -> Result<Encryptor> {
+        Self::with_public_key(&ctx.with_prng_seed(seed)?, public_key)
+    }
+
     /**
      *
      * Encrypts a plaintext with the public key and returns the ciphertext as
@@ -101,6 +132,7 @@ impl Encryptor {
      *
      * * `plainext` - The plaintext to encrypt.
      */
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn encrypt(&self, plaintext: &Plaintext) -> Result<Ciphertext> {
         let ciphertext = Ciphertext::new()?;
 
@@ -111,6 +143,103 @@ impl Encryptor {
                 ciphertext.get_handle(),
                 null_mut(),
             )
+        })
+        .map_err(|e| e.in_operation("encrypt", None, None))?;
+
+        Ok(ciphertext)
+    }
+
+    /**
+     * Encrypts a zero plaintext with the public key.
+     *
+     * # Remarks
+     * This is faster than encoding and encrypting a zero plaintext. Adding an
+     * encryption of zero to a ciphertext re-randomizes it without changing the
+     * value it encrypts, e.g. to blind a ciphertext before returning it to
+     * another party.
+     *
+     * The encryption parameters for the resulting ciphertext correspond to
+     * the highest (data) level in the modulus switching chain.
+     */
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn encrypt_zero(&self) -> Result<Ciphertext> {
+        let ciphertext = Ciphertext::new()?;
+
+        convert_seal_error(unsafe {
+            bindgen::Encryptor_EncryptZero2(self.handle, ciphertext.get_handle(), null_mut())
+        })?;
+
+        Ok(ciphertext)
+    }
+
+    /**
+     * Encrypts a zero plaintext with the public key at the level of the
+     * modulus switching chain given by `parms_id`. See
+     * [`encrypt_zero`](Self::encrypt_zero).
+     *
+     * # Remarks
+     * Use this to re-randomize ciphertexts that have been modulus switched,
+     * passing their [`Ciphertext::get_parms_id`].
+     */
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn encrypt_zero_with_parms_id(&self, parms_id: &[u64; 4]) -> Result<Ciphertext> {
+        let ciphertext = Ciphertext::new()?;
+        let mut parms_id = *parms_id;
+
+        convert_seal_error(unsafe {
+            bindgen::Encryptor_EncryptZero1(
+                self.handle,
+                parms_id.as_mut_ptr(),
+                ciphertext.get_handle(),
+                null_mut(),
+            )
+        })?;
+
+        Ok(ciphertext)
+    }
+
+    /**
+     * Encrypts a zero plaintext with the secret key. See
+     * [`encrypt_zero`](Self::encrypt_zero).
+     *
+     * # Remarks
+     * The encryptor must have been created with a secret key (e.g. with
+     * [`with_public_and_secret_key`](Self::with_public_and_secret_key)).
+     */
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn encrypt_zero_symmetric(&self) -> Result<Ciphertext> {
+        let ciphertext = Ciphertext::new()?;
+
+        convert_seal_error(unsafe {
+            bindgen::Encryptor_EncryptZeroSymmetric2(
+                self.handle,
+                false,
+                ciphertext.get_handle(),
+                null_mut(),
+            )
+        })?;
+
+        Ok(ciphertext)
+    }
+
+    /**
+     * Encrypts a zero plaintext with the secret key at the level of the
+     * modulus switching chain given by `parms_id`. See
+     * [`encrypt_zero_symmetric`](Self::encrypt_zero_symmetric).
+     */
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn encrypt_zero_symmetric_with_parms_id(&self, parms_id: &[u64; 4]) -> Result<Ciphertext> {
+        let ciphertext = Ciphertext::new()?;
+        let mut parms_id = *parms_id;
+
+        convert_seal_error(unsafe {
+            bindgen::Encryptor_EncryptZeroSymmetric1(
+                self.handle,
+                parms_id.as_mut_ptr(),
+                false,
+                ciphertext.get_handle(),
+                null_mut(),
+            )
         })?;
 
         Ok(ciphertext)
@@ -167,16 +296,44 @@ impl Decryptor {
      *
      * `encrypted` - The ciphertext to decrypt.
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = ciphertext.num_polynomials()))
+    )]
     pub fn decrypt(&self, ciphertext: &Ciphertext) -> Result<Plaintext> {
         let plaintext = Plaintext::new()?;
 
         convert_seal_error(unsafe {
             bindgen::Decryptor_Decrypt(self.handle, ciphertext.get_handle(), plaintext.get_handle())
-        })?;
+        })
+        .map_err(|e| e.in_operation("decrypt", Some(ciphertext), None))?;
 
         Ok(plaintext)
     }
 
+    /**
+     * Decrypts a ciphertext and decodes only the slots in `range` into `out`.
+     * This fuses [`decrypt`](Self::decrypt) and
+     * [`BFVEncoder::decode_signed_range`] for callers that only need a few
+     * result slots.
+     *
+     * * `encoder` - The batch encoder for this decryptor's context.
+     * * `ciphertext` - The ciphertext to decrypt.
+     * * `range` - The slots to decode. Must lie within the encoder's slot count.
+     * * `out` - The destination buffer. Its length must equal `range.len()`.
+     */
+    pub fn decrypt_decode_signed_range(
+        &self,
+        encoder: &BFVEncoder,
+        ciphertext: &Ciphertext,
+        range: Range<usize>,
+        out: &mut [i64],
+    ) -> Result<()> {
+        let plaintext = self.decrypt(ciphertext)?;
+
+        encoder.decode_signed_range(&plaintext, range, out)
+    }
+
     /**
      * Computes the invariant noise budget (in bits) of a ciphertext. The invariant noise
      * budget measures the amount of room there is for the noise to grow while ensuring
@@ -196,6 +353,10 @@ impl Decryptor {
      *
      * * `ciphertext` - The ciphertext for which to measure noise.
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = ciphertext.num_polynomials()))
+    )]
     pub fn invariant_noise_budget(&self, ciphertext: &Ciphertext) -> Result<u32> {
         let mut noise: i32 = 0;
 
@@ -205,7 +366,8 @@ impl Decryptor {
                 ciphertext.get_handle(),
                 &mut noise,
             )
-        })?;
+        })
+        .map_err(|e| e.in_operation("invariant_noise_budget", Some(ciphertext), None))?;
 
         Ok(noise as u32)
     }
@@ -223,6 +385,10 @@ impl Decryptor {
      * the invariant noise, and for correct decryption require it to be less than
      * 1/2.
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(size = ciphertext.num_polynomials()))
+    )]
     pub fn invariant_noise(&self, ciphertext: &Ciphertext) -> Result<f64> {
         let mut noise: f64 = 0f64;
 
@@ -245,6 +411,88 @@ impl Drop for Decryptor {
 mod tests {
     use crate::*;
 
+    fn make_context(expand_mod_chain: bool) -> Context {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 20).unwrap())
+            .build()
+            .unwrap();
+
+        Context::new(&params, expand_mod_chain, SecurityLevel::TC128).unwrap()
+    }
+
+    #[test]
+    fn can_encrypt_zero() {
+        let ctx = make_context(false);
+        let gen = KeyGenerator::new(&ctx).unwrap();
+
+        let public_key = gen.create_public_key().unwrap();
+        let secret_key = gen.secret_key().unwrap();
+
+        let encoder = BFVEncoder::new(&ctx).unwrap();
+        let encryptor =
+            Encryptor::with_public_and_secret_key(&ctx, &public_key, &secret_key).unwrap();
+        let decryptor = Decryptor::new(&ctx, &secret_key).unwrap();
+        let evaluator = BFVEvaluator::new(&ctx).unwrap();
+
+        let data = (0..encoder.get_slot_count() as i64).collect::<Vec<_>>();
+        let a = encryptor
+            .encrypt(&encoder.encode_signed(&data).unwrap())
+            .unwrap();
+
+        for zero in [
+            encryptor.encrypt_zero().unwrap(),
+            encryptor.encrypt_zero_symmetric().unwrap(),
+        ] {
+            let z = encoder
+                .decode_signed(&decryptor.decrypt(&zero).unwrap())
+                .unwrap();
+
+            assert!(z.iter().all(|x| *x == 0));
+
+            let b = evaluator.add(&a, &zero).unwrap();
+
+            assert_ne!(a.as_bytes().unwrap(), b.as_bytes().unwrap());
+
+            let b = encoder
+                .decode_signed(&decryptor.decrypt(&b).unwrap())
+                .unwrap();
+
+            assert_eq!(b, data);
+        }
+    }
+
+    #[test]
+    fn can_encrypt_zero_at_parms_id() {
+        let ctx = make_context(true);
+        let gen = KeyGenerator::new(&ctx).unwrap();
+
+        let public_key = gen.create_public_key().unwrap();
+        let secret_key = gen.secret_key().unwrap();
+
+        let encryptor =
+            Encryptor::with_public_and_secret_key(&ctx, &public_key, &secret_key).unwrap();
+        let decryptor = Decryptor::new(&ctx, &secret_key).unwrap();
+
+        let parms_id = ctx.get_last_parms_id().unwrap();
+
+        for zero in [
+            encryptor.encrypt_zero_with_parms_id(&parms_id).unwrap(),
+            encryptor
+                .encrypt_zero_symmetric_with_parms_id(&parms_id)
+                .unwrap(),
+        ] {
+            assert_eq!(zero.get_parms_id(), parms_id);
+
+            let p = decryptor.decrypt(&zero).unwrap();
+
+            assert!((0..p.len()).all(|i| p.get_coefficient(i) == 0));
+        }
+    }
+
     #[test]
     fn can_create_encryptor_from_public_key() {
         let params = BfvEncryptionParametersBuilder::new()
@@ -259,7 +507,7 @@ mod tests {
         let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
         let gen = KeyGenerator::new(&ctx).unwrap();
 
-        let public_key = gen.create_public_key();
+        let public_key = gen.create_public_key().unwrap();
 
         let encryptor = Encryptor::with_public_key(&ctx, &public_key).unwrap();
 
@@ -280,8 +528,8 @@ mod tests {
         let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
         let gen = KeyGenerator::new(&ctx).unwrap();
 
-        let public_key = gen.create_public_key();
-        let secret_key = gen.secret_key();
+        let public_key = gen.create_public_key().unwrap();
+        let secret_key = gen.secret_key().unwrap();
 
         let encryptor =
             Encryptor::with_public_and_secret_key(&ctx, &public_key, &secret_key).unwrap();
@@ -289,6 +537,52 @@ mod tests {
         std::mem::drop(encryptor);
     }
 
+    #[test]
+    fn seeded_encryptors_are_deterministic() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 20).unwrap())
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, true, SecurityLevel::TC128).unwrap();
+        let gen = KeyGenerator::new(&ctx).unwrap();
+
+        let public_key = gen.create_public_key().unwrap();
+        let secret_key = gen.secret_key().unwrap();
+
+        let encoder = BFVEncoder::new(&ctx).unwrap();
+        let data = (0..encoder.get_slot_count() as u64).collect::<Vec<_>>();
+        let plaintext = encoder.encode_unsigned(&data).unwrap();
+
+        let seed = PrngSeed::from_u64(7);
+
+        let encryptor = Encryptor::with_public_key_seeded(&ctx, &public_key, &seed).unwrap();
+        let encryptor_2 =
+            Encryptor::with_public_and_secret_key_seeded(&ctx, &public_key, &secret_key, &seed)
+                .unwrap();
+
+        let a = encryptor.encrypt(&plaintext).unwrap();
+        let b = encryptor.encrypt(&plaintext).unwrap();
+
+        assert_eq!(
+            a.as_bytes().unwrap(),
+            encryptor_2.encrypt(&plaintext).unwrap().as_bytes().unwrap()
+        );
+        assert_ne!(a.as_bytes().unwrap(), b.as_bytes().unwrap());
+
+        let decryptor = Decryptor::new(&ctx, &secret_key).unwrap();
+
+        for c in [a, b] {
+            let decrypted = decryptor.decrypt(&c).unwrap();
+
+            assert_eq!(encoder.decode_unsigned(&decrypted).unwrap(), data);
+        }
+    }
+
     #[test]
     fn can_create_and_destroy_decryptor() {
         let params = BfvEncryptionParametersBuilder::new()
@@ -303,7 +597,7 @@ mod tests {
         let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
         let gen = KeyGenerator::new(&ctx).unwrap();
 
-        let secret_key = gen.secret_key();
+        let secret_key = gen.secret_key().unwrap();
         let decryptor = Decryptor::new(&ctx, &secret_key);
 
         std::mem::drop(decryptor);
@@ -333,8 +627,8 @@ mod tests {
 
         let plaintext = encoder.encode_unsigned(&data).unwrap();
 
-        let public_key = gen.create_public_key();
-        let secret_key = gen.secret_key();
+        let public_key = gen.create_public_key().unwrap();
+        let secret_key = gen.secret_key().unwrap();
 
         let encryptor =
             Encryptor::with_public_and_secret_key(&ctx, &public_key, &secret_key).unwrap();
@@ -372,8 +666,8 @@ mod tests {
 
         let plaintext = encoder.encode_signed(&data).unwrap();
 
-        let public_key = gen.create_public_key();
-        let secret_key = gen.secret_key();
+        let public_key = gen.create_public_key().unwrap();
+        let secret_key = gen.secret_key().unwrap();
 
         let encryptor =
             Encryptor::with_public_and_secret_key(&ctx, &public_key, &secret_key).unwrap();
@@ -386,4 +680,60 @@ mod tests {
 
         assert_eq!(data, data_2);
     }
+
+    #[test]
+    fn can_decrypt_decode_signed_range() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 20).unwrap())
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+        let gen = KeyGenerator::new(&ctx).unwrap();
+
+        let encoder = BFVEncoder::new(&ctx).unwrap();
+
+        let mut data = vec![];
+
+        for i in 0..encoder.get_slot_count() {
+            data.push(encoder.get_slot_count() as i64 / 2i64 - i as i64)
+        }
+
+        let plaintext = encoder.encode_signed(&data).unwrap();
+
+        let public_key = gen.create_public_key().unwrap();
+        let secret_key = gen.secret_key().unwrap();
+
+        let encryptor =
+            Encryptor::with_public_and_secret_key(&ctx, &public_key, &secret_key).unwrap();
+        let decryptor = Decryptor::new(&ctx, &secret_key).unwrap();
+
+        let ciphertext = encryptor.encrypt(&plaintext).unwrap();
+
+        let mut out = [0i64; 4];
+
+        decryptor
+            .decrypt_decode_signed_range(&encoder, &ciphertext, 10..14, &mut out)
+            .unwrap();
+
+        assert_eq!(out, data[10..14]);
+
+        let mut too_small = [0i64; 3];
+
+        assert_eq!(
+            decryptor.decrypt_decode_signed_range(&encoder, &ciphertext, 10..14, &mut too_small),
+            Err(Error::InvalidArgument)
+        );
+
+        let mut out = [0i64; 1];
+
+        assert_eq!(
+            decryptor.decrypt_decode_signed_range(&encoder, &ciphertext, 8192..8193, &mut out),
+            Err(Error::InvalidArgument)
+        );
+    }
 }
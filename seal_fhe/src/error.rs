@@ -1,7 +1,13 @@
-use std::os::raw::c_long;
+use std::cell::RefCell;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_long};
+use std::panic::Location;
+use std::ptr::null;
 
 use static_assertions::const_assert;
 
+use crate::{bindgen, Ciphertext, SecurityLevel};
+
 use crate::bindgen::{
     COR_E_INVALIDOPERATION, COR_E_IO, E_INVALIDARG, E_OK, E_OUTOFMEMORY, E_POINTER, E_UNEXPECTED,
 };
@@ -54,10 +60,300 @@ pub enum Error {
     /// Serialization failed.
     #[error("Serialization failed {0}")]
     SerializationError(Box<String>),
+
+    /// The encryption parameters are invalid. See [`ParameterError`] for the reason.
+    #[error("Invalid encryption parameters: {0}")]
+    InvalidParameters(Box<ParameterError>),
+
+    /// An evaluator, encryptor or decryptor operation failed. See [`OperationError`] for the
+    /// operation and the levels of its operands.
+    #[error("{0}")]
+    OperationFailed(Box<OperationError>),
 }
 
 const_assert!(std::mem::size_of::<Error>() <= 16);
 
+impl Error {
+    /**
+     * Returns a stable, machine-readable identifier for the kind of error
+     * (e.g. `"invalid_argument"`).
+     *
+     * # Remarks
+     * Unlike the [`Display`](std::fmt::Display) message, codes don't
+     * change between releases, so hosts that can't match on the enum
+     * (e.g. JavaScript calling into a WASM build) can branch on them.
+     * Errors annotated with the operation that failed (see
+     * [`OperationFailed`](Self::OperationFailed)) have the code of the
+     * underlying error.
+     */
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::InvalidArgument => "invalid_argument",
+            Self::InvalidPointer => "invalid_pointer",
+            Self::OutOfMemory => "out_of_memory",
+            Self::Unexpected => "unexpected",
+            Self::InternalError(_) => "internal_error",
+            Self::Unknown(_) => "unknown",
+            Self::DegreeNotSet => "degree_not_set",
+            Self::CoefficientModulusNotSet => "coefficient_modulus_not_set",
+            Self::PlainModulusNotSet => "plain_modulus_not_set",
+            Self::SerializationError(_) => "serialization_error",
+            Self::InvalidParameters(_) => "invalid_parameters",
+            Self::OperationFailed(e) => e.reason.code(),
+        }
+    }
+
+    /**
+     * Returns the underlying error, looking through
+     * [`OperationFailed`](Self::OperationFailed).
+     */
+    pub fn root_cause(&self) -> &Error {
+        match self {
+            Self::OperationFailed(e) => e.reason.root_cause(),
+            _ => self,
+        }
+    }
+
+    /**
+     * Annotates this error with the operation that failed, the levels of
+     * its ciphertext operands and SEAL's message, if any.
+     */
+    pub(crate) fn in_operation(
+        self,
+        operation: &'static str,
+        lhs: Option<&Ciphertext>,
+        rhs: Option<&Ciphertext>,
+    ) -> Error {
+        Self::OperationFailed(Box::new(OperationError {
+            operation,
+            lhs_level: lhs.map(|c| c.coeff_modulus_size()),
+            rhs_level: rhs.map(|c| c.coeff_modulus_size()),
+            reason: self,
+            message: LAST_ERROR.with(|e| e.borrow().as_ref().and_then(|c| c.message.clone())),
+        }))
+    }
+}
+
+impl From<ParameterError> for Error {
+    fn from(err: ParameterError) -> Self {
+        Self::InvalidParameters(Box::new(err))
+    }
+}
+
+/**
+ * Describes why a set of encryption parameters is invalid. Each variant
+ * carries the offending values.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ParameterError {
+    /// The polynomial degree isn't a power of two in the range SEAL supports.
+    #[error("Polynomial degree {degree} must be a power of two between {min} and {max}")]
+    InvalidPolyModulusDegree {
+        /// The polynomial degree.
+        degree: u64,
+        /// The smallest supported degree.
+        min: u64,
+        /// The largest supported degree.
+        max: u64,
+    },
+
+    /// The coefficient modulus has too few or too many primes.
+    #[error("Coefficient modulus has {count} primes, but must have between 1 and {max}")]
+    InvalidCoeffModulusCount {
+        /// The number of primes in the coefficient modulus.
+        count: usize,
+        /// The maximum number of primes.
+        max: usize,
+    },
+
+    /// A coefficient modulus prime is too small or too large.
+    #[error("Coefficient modulus {modulus} must be between {min_bits} and {max_bits} bits")]
+    InvalidCoeffModulusBitCount {
+        /// The offending modulus.
+        modulus: u64,
+        /// The minimum number of bits.
+        min_bits: u32,
+        /// The maximum number of bits.
+        max_bits: u32,
+    },
+
+    /// Two coefficient moduli share a common factor.
+    #[error("Coefficient moduli {a} and {b} aren't coprime")]
+    CoeffModulusNotCoprime {
+        /// The first modulus.
+        a: u64,
+        /// The second modulus.
+        b: u64,
+    },
+
+    /// A coefficient modulus doesn't support the number theoretic transform
+    /// for the polynomial degree, i.e. it isn't a prime congruent to 1
+    /// modulo `2 * degree`.
+    #[error("Coefficient modulus {modulus} must be a prime congruent to 1 mod {}", 2 * degree)]
+    CoeffModulusNotNttFriendly {
+        /// The offending modulus.
+        modulus: u64,
+        /// The polynomial degree.
+        degree: u64,
+    },
+
+    /// The coefficient modulus is too large to meet the security level
+    /// for the polynomial degree.
+    #[error("Coefficient modulus has {bits} bits, but at most {max_bits} bits are allowed for degree {degree} at {security_level:?}")]
+    CoeffModulusTooLargeForSecurity {
+        /// The number of bits in the product of the coefficient moduli.
+        bits: u32,
+        /// The largest number of bits allowed.
+        max_bits: u32,
+        /// The polynomial degree.
+        degree: u64,
+        /// The requested security level.
+        security_level: SecurityLevel,
+    },
+
+    /// The security standard doesn't cover the polynomial degree.
+    #[error("No parameters with degree {degree} meet {security_level:?}")]
+    DegreeUnsupportedForSecurity {
+        /// The polynomial degree.
+        degree: u64,
+        /// The requested security level.
+        security_level: SecurityLevel,
+    },
+
+    /// The plaintext modulus is too small or too large.
+    #[error("Plain modulus {plain_modulus} must be between {min_bits} and {max_bits} bits")]
+    InvalidPlainModulusBitCount {
+        /// The plaintext modulus.
+        plain_modulus: u64,
+        /// The minimum number of bits.
+        min_bits: u32,
+        /// The maximum number of bits.
+        max_bits: u32,
+    },
+
+    /// The plaintext modulus shares a common factor with a coefficient
+    /// modulus.
+    #[error(
+        "Plain modulus {plain_modulus} isn't coprime with coefficient modulus {coeff_modulus}"
+    )]
+    PlainModulusNotCoprime {
+        /// The plaintext modulus.
+        plain_modulus: u64,
+        /// The offending coefficient modulus.
+        coeff_modulus: u64,
+    },
+
+    /// The plaintext modulus isn't smaller than the coefficient modulus.
+    #[error("Plain modulus {plain_modulus} must be smaller than the coefficient modulus")]
+    PlainModulusTooLarge {
+        /// The plaintext modulus.
+        plain_modulus: u64,
+    },
+
+    /// Batching requires a prime plaintext modulus.
+    #[error("Plain modulus {plain_modulus} isn't prime, so batching is unsupported")]
+    PlainModulusNotPrime {
+        /// The plaintext modulus.
+        plain_modulus: u64,
+    },
+
+    /// Batching requires the plaintext modulus to be congruent to 1 modulo
+    /// `2 * degree`.
+    #[error("Plain modulus {plain_modulus} isn't congruent to 1 mod {}, so batching is unsupported", 2 * degree)]
+    BatchingUnsupported {
+        /// The plaintext modulus.
+        plain_modulus: u64,
+        /// The polynomial degree.
+        degree: u64,
+    },
+
+    /// No supported polynomial degree yields a coefficient modulus large
+    /// enough for the requested computation at the security level. See
+    /// [`ParameterSelector`](crate::ParameterSelector).
+    #[error("No parameters support depth {depth} with a {plain_modulus_bits}-bit plain modulus at {security_level:?}")]
+    Unsatisfiable {
+        /// The requested multiplicative depth.
+        depth: u32,
+        /// The requested plaintext modulus bit count.
+        plain_modulus_bits: u32,
+        /// The requested security level.
+        security_level: SecurityLevel,
+    },
+}
+
+/**
+ * Describes a failed operation on ciphertexts, e.g. multiplying two
+ * ciphertexts at different levels.
+ *
+ * # Remarks
+ * An operand's level is the number of primes left in its coefficient
+ * modulus, which drops by one with each modulus switch. SEAL rejects
+ * binary operations on ciphertexts at different levels, so when the levels
+ * differ the message suggests switching the higher operand down first.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperationError {
+    /**
+     * The name of the operation, e.g. `"multiply"`.
+     */
+    pub operation: &'static str,
+
+    /**
+     * The level of the first ciphertext operand, or [`None`] if the
+     * operation has none.
+     */
+    pub lhs_level: Option<u64>,
+
+    /**
+     * The level of the second ciphertext operand, or [`None`] if the
+     * operation has none (e.g. `multiply_plain`).
+     */
+    pub rhs_level: Option<u64>,
+
+    /**
+     * Why the operation failed.
+     */
+    pub reason: Error,
+
+    /**
+     * The message of the exception SEAL threw, e.g. `"encrypted1 and
+     * encrypted2 parameter mismatch"`, if it was kept. See
+     * [`ErrorContext::message`].
+     */
+    pub message: Option<String>,
+}
+
+impl std::fmt::Display for OperationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} failed", self.operation)?;
+
+        match (self.lhs_level, self.rhs_level) {
+            (Some(lhs), Some(rhs)) if lhs != rhs => write!(
+                f,
+                " (operands are at different levels {} and {}; mod switch the higher one first)",
+                lhs, rhs
+            )?,
+            (Some(lhs), Some(rhs)) => write!(f, " (lhs level {}, rhs level {})", lhs, rhs)?,
+            (Some(level), None) | (None, Some(level)) => write!(f, " (level {})", level)?,
+            (None, None) => {}
+        };
+
+        write!(f, ": {}", self.reason)?;
+
+        match &self.message {
+            Some(message) => write!(f, ": {}", message),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for OperationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.reason)
+    }
+}
+
 impl From<c_long> for Error {
     fn from(err: c_long) -> Self {
         match err {
@@ -78,10 +374,157 @@ impl From<c_long> for Error {
  */
 pub type Result<T> = std::result::Result<T, Error>;
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+/**
+ * Details about the most recent SEAL call that failed on the current
+ * thread. See [`last_error`].
+ */
+pub struct ErrorContext {
+    /**
+     * The raw `HRESULT` SEAL returned.
+     */
+    pub hresult: c_long,
+
+    /**
+     * Where in this crate the failing SEAL function was called, which
+     * identifies the function.
+     */
+    pub location: &'static Location<'static>,
+
+    /**
+     * The message of the exception SEAL threw, if it was kept.
+     *
+     * # Remarks
+     * SEAL's C API catches the C++ exceptions SEAL throws and returns
+     * only an `HRESULT`, discarding the message. seal_fhe calls the
+     * operations whose messages matter most, e.g. adding and multiplying
+     * ciphertexts, through its own wrappers, which keep it.
+     */
+    pub message: Option<String>,
+}
+
+impl std::fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "SEAL returned {:#010x} at {}",
+            self.hresult as u32, self.location
+        )?;
+
+        match &self.message {
+            Some(message) => write!(f, ": {}", message),
+            None => Ok(()),
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<ErrorContext>> = RefCell::new(None);
+}
+
+/**
+ * Returns the [`ErrorContext`] of the most recent SEAL call on the current
+ * thread if it failed, or [`None`] if it succeeded.
+ *
+ * # Remarks
+ * Where SEAL's message was lost (see [`ErrorContext::message`]), the call
+ * site of the failing function is the most precise information left,
+ * e.g. to tell which operation of an FHE program rejected its arguments.
+ * The next SEAL call replaces the context, so call this right after a
+ * failure, on the same thread.
+ */
+pub fn last_error() -> Option<ErrorContext> {
+    LAST_ERROR.with(|e| e.borrow().clone())
+}
+
+/**
+ * Takes the message of the exception the last failing shim function on
+ * this thread caught, so a later failure elsewhere can't report it.
+ */
+fn take_shim_message() -> Option<String> {
+    let mut message: *const c_char = null();
+
+    if unsafe { bindgen::Shim_GetLastErrorMessage(&mut message) } != E_OK || message.is_null() {
+        return None;
+    }
+
+    let message = unsafe { CStr::from_ptr(message) }
+        .to_string_lossy()
+        .into_owned();
+
+    unsafe { bindgen::Shim_ClearLastErrorMessage() };
+
+    if message.is_empty() {
+        None
+    } else {
+        Some(message)
+    }
+}
+
+#[track_caller]
 pub fn convert_seal_error(err: c_long) -> Result<()> {
     if err == E_OK {
+        LAST_ERROR.with(|e| *e.borrow_mut() = None);
+
         Ok(())
     } else {
+        let context = ErrorContext {
+            hresult: err,
+            location: Location::caller(),
+            message: take_shim_message(),
+        };
+
+        LAST_ERROR.with(|e| *e.borrow_mut() = Some(context));
+
         Err(Error::from(err))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_codes_are_stable() {
+        assert_eq!(Error::from(E_INVALIDARG).code(), "invalid_argument");
+        assert_eq!(Error::from(E_OUTOFMEMORY).code(), "out_of_memory");
+        assert_eq!(Error::from(COR_E_IO).code(), "internal_error");
+        assert_eq!(Error::from(1).code(), "unknown");
+        assert_eq!(
+            Error::from(ParameterError::PlainModulusTooLarge { plain_modulus: 7 }).code(),
+            "invalid_parameters"
+        );
+        assert_eq!(
+            Error::InvalidArgument
+                .in_operation("multiply", None, None)
+                .code(),
+            "invalid_argument"
+        );
+    }
+
+    #[test]
+    fn records_last_error_context() {
+        assert_eq!(convert_seal_error(E_OK), Ok(()));
+
+        let line = line!() + 1;
+        let err = convert_seal_error(E_INVALIDARG);
+
+        assert_eq!(err, Err(Error::InvalidArgument));
+
+        let context = last_error().unwrap();
+
+        assert_eq!(context.hresult, E_INVALIDARG);
+        assert_eq!(context.location.file(), file!());
+        assert_eq!(context.location.line(), line);
+        assert!(context.to_string().starts_with("SEAL returned 0x80070057"));
+
+        // Successful calls clear it.
+        assert_eq!(convert_seal_error(E_OK), Ok(()));
+        assert!(last_error().is_none());
+
+        // Errors are per thread.
+        std::thread::spawn(|| assert!(last_error().is_none()))
+            .join()
+            .unwrap();
+    }
+}
@@ -34,6 +34,9 @@ pub trait Evaluator {
     /**
      * Performs an addition reduction of multiple ciphertexts packed into a slice.
      * * `a` - a slice of ciphertexts to sum.
+     *
+     * See [`ReductionTree::add`](crate::ReductionTree::add) to choose the
+     * order in which the ciphertexts are combined.
      */
     fn add_many(&self, a: &[Ciphertext]) -> Result<Ciphertext>;
 
@@ -42,6 +45,9 @@ pub trait Evaluator {
      * method creates a tree of multiplications with relinearization after each operation.
      * * `a` - a slice of ciphertexts to sum.
      * * `relin_keys` - the relinearization keys.
+     *
+     * See [`ReductionTree::multiply`](crate::ReductionTree::multiply) to
+     * choose the shape of the tree.
      */
     fn multiply_many(
         &self,
@@ -115,7 +121,7 @@ pub trait Evaluator {
      *
      * TODO: what does this mean for CKKS?
      */
-    fn mod_switch_to_next_inplace(&self, a: &Ciphertext) -> Result<()>;
+    fn mod_switch_to_next_inplace(&self, a: &mut Ciphertext) -> Result<()>;
 
     /**
      * Modulus switches an NTT transformed plaintext from modulo q_1...q_k down to modulo q_1...q_{k-1}.
@@ -126,7 +132,7 @@ pub trait Evaluator {
      * Modulus switches an NTT transformed plaintext from modulo q_1...q_k down to modulo q_1...q_{k-1}.
      * This variant does so in-place.
      */
-    fn mod_switch_to_next_inplace_plaintext(&self, a: &Plaintext) -> Result<()>;
+    fn mod_switch_to_next_inplace_plaintext(&self, a: &mut Plaintext) -> Result<()>;
 
     /**
      * This functions raises encrypted to a power and stores the result in the destination parameter. Dynamic
@@ -151,7 +157,7 @@ pub trait Evaluator {
      */
     fn exponentiate_inplace(
         &self,
-        a: &Ciphertext,
+        a: &mut Ciphertext,
         exponent: u64,
         relin_keys: &RelinearizationKeys,
     ) -> Result<()>;
@@ -184,10 +190,40 @@ pub trait Evaluator {
      */
     fn sub_plain_inplace(&self, a: &mut Ciphertext, b: &Plaintext) -> Result<()>;
 
+    /**
+     * Subtract a ciphertext from a plaintext, i.e. compute `a - b`.
+     * * `a` - the plaintext
+     * * `b` - the ciphertext
+     *
+     * # Remarks
+     * This is cheaper than negating `b` and adding `a` with
+     * [`add_plain`](Self::add_plain), as it allocates a single ciphertext.
+     */
+    fn plain_sub(&self, a: &Plaintext, b: &Ciphertext) -> Result<Ciphertext>;
+
+    /**
+     * Subtract a ciphertext from a plaintext and store the result in the
+     * ciphertext.
+     * * `a` - the plaintext
+     * * `b` - the ciphertext
+     */
+    fn plain_sub_inplace(&self, a: &Plaintext, b: &mut Ciphertext) -> Result<()>;
+
     /**
      * Multiply a ciphertext by a plaintext.
      * * `a` - the ciphertext
      * * `b` - the plaintext
+     *
+     * # Remarks
+     * The cost depends on the number of nonzero coefficients in `b` (see
+     * [`Plaintext::nonzero_coefficient_count`]), not its number of nonzero
+     * slots. Multiplying by the constant 1 is a copy, and by a plaintext
+     * with up to 8 nonzero coefficients sums one monomial multiplication
+     * per coefficient, each linear in the degree. Denser plaintexts take a
+     * multiplication through the NTT. Batch encoding spreads even a single
+     * nonzero slot over nearly every coefficient, so only plaintexts that
+     * are sparse as polynomials (e.g. small scalars and monomials) take
+     * the cheaper paths, not masks with few nonzero slots.
      */
     fn multiply_plain(&self, a: &Ciphertext, b: &Plaintext) -> Result<Ciphertext>;
 
@@ -247,7 +283,7 @@ pub trait Evaluator {
      */
     fn rotate_rows_inplace(
         &self,
-        a: &Ciphertext,
+        a: &mut Ciphertext,
         steps: i32,
         galois_keys: &GaloisKeys,
     ) -> Result<()>;
@@ -276,5 +312,5 @@ pub trait Evaluator {
      * * `encrypted` - The ciphertext to rotate
      * * `galoisKeys` - The Galois keys
      */
-    fn rotate_columns_inplace(&self, a: &Ciphertext, galois_keys: &GaloisKeys) -> Result<()>;
+    fn rotate_columns_inplace(&self, a: &mut Ciphertext, galois_keys: &GaloisKeys) -> Result<()>;
 }
@@ -5,6 +5,15 @@ use crate::bindgen;
 use crate::error::*;
 use crate::{Ciphertext, Context, Plaintext, RelinearizationKeys};
 
+/**
+ * The most nonzero coefficients a plaintext may have for
+ * [`EvaluatorBase::multiply_plain`] to multiply by each as a monomial
+ * rather than through the NTT. Each monomial multiplication and addition
+ * is linear in the degree, while the NTT path transforms the ciphertext
+ * there and back, so a handful of terms is still cheaper.
+ */
+const SPARSE_MULTIPLY_MAX_TERMS: usize = 8;
+
 /**
  * Provides operations on ciphertexts. Due to the properties of the encryption scheme, the arithmetic operations
  * pass through the encryption layer to the underlying plaintext, changing it according to the type of the
@@ -86,42 +95,74 @@ impl EvaluatorBase {
         self.handle
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(lhs_size = a.num_polynomials()))
+    )]
     pub(crate) fn negate_inplace(&self, a: &mut Ciphertext) -> Result<()> {
         convert_seal_error(unsafe {
             bindgen::Evaluator_Negate(self.handle, a.get_handle(), a.get_handle())
-        })?;
+        })
+        .map_err(|e| e.in_operation("negate", Some(&*a), None))?;
 
         Ok(())
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(lhs_size = a.num_polynomials()))
+    )]
     pub(crate) fn negate(&self, a: &Ciphertext) -> Result<Ciphertext> {
         let out = Ciphertext::new()?;
 
         convert_seal_error(unsafe {
             bindgen::Evaluator_Negate(self.handle, a.get_handle(), out.get_handle())
-        })?;
+        })
+        .map_err(|e| e.in_operation("negate", Some(a), None))?;
 
         Ok(out)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(lhs_size = a.num_polynomials(), rhs_size = b.num_polynomials())
+        )
+    )]
     pub(crate) fn add_inplace(&self, a: &mut Ciphertext, b: &Ciphertext) -> Result<()> {
         convert_seal_error(unsafe {
-            bindgen::Evaluator_Add(self.handle, a.get_handle(), b.get_handle(), a.get_handle())
-        })?;
+            bindgen::Shim_Evaluator_Add(self.handle, a.get_handle(), b.get_handle(), a.get_handle())
+        })
+        .map_err(|e| e.in_operation("add", Some(&*a), Some(b)))?;
 
         Ok(())
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(lhs_size = a.num_polynomials(), rhs_size = b.num_polynomials())
+        )
+    )]
     pub(crate) fn add(&self, a: &Ciphertext, b: &Ciphertext) -> Result<Ciphertext> {
         let c = Ciphertext::new()?;
 
         convert_seal_error(unsafe {
-            bindgen::Evaluator_Add(self.handle, a.get_handle(), b.get_handle(), c.get_handle())
-        })?;
+            bindgen::Shim_Evaluator_Add(self.handle, a.get_handle(), b.get_handle(), c.get_handle())
+        })
+        .map_err(|e| e.in_operation("add", Some(a), Some(b)))?;
 
         Ok(c)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(count = a.len()))
+    )]
     pub(crate) fn add_many(&self, a: &[Ciphertext]) -> Result<Ciphertext> {
         let c = Ciphertext::new()?;
 
@@ -132,11 +173,16 @@ impl EvaluatorBase {
 
         convert_seal_error(unsafe {
             bindgen::Evaluator_AddMany(self.handle, a.len() as u64, a.as_mut_ptr(), c.get_handle())
-        })?;
+        })
+        .map_err(|e| e.in_operation("add_many", None, None))?;
 
         Ok(c)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(count = a.len()))
+    )]
     pub(crate) fn multiply_many(
         &self,
         a: &[Ciphertext],
@@ -158,77 +204,126 @@ impl EvaluatorBase {
                 c.get_handle(),
                 null_mut(),
             )
-        })?;
+        })
+        .map_err(|e| e.in_operation("multiply_many", None, None))?;
 
         Ok(c)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(lhs_size = a.num_polynomials(), rhs_size = b.num_polynomials())
+        )
+    )]
     pub(crate) fn sub_inplace(&self, a: &mut Ciphertext, b: &Ciphertext) -> Result<()> {
         convert_seal_error(unsafe {
-            bindgen::Evaluator_Sub(self.handle, a.get_handle(), b.get_handle(), a.get_handle())
-        })?;
+            bindgen::Shim_Evaluator_Sub(self.handle, a.get_handle(), b.get_handle(), a.get_handle())
+        })
+        .map_err(|e| e.in_operation("sub", Some(&*a), Some(b)))?;
 
         Ok(())
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(lhs_size = a.num_polynomials(), rhs_size = b.num_polynomials())
+        )
+    )]
     pub(crate) fn sub(&self, a: &Ciphertext, b: &Ciphertext) -> Result<Ciphertext> {
         let c = Ciphertext::new()?;
 
         convert_seal_error(unsafe {
-            bindgen::Evaluator_Sub(self.handle, a.get_handle(), b.get_handle(), c.get_handle())
-        })?;
+            bindgen::Shim_Evaluator_Sub(self.handle, a.get_handle(), b.get_handle(), c.get_handle())
+        })
+        .map_err(|e| e.in_operation("sub", Some(a), Some(b)))?;
 
         Ok(c)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(lhs_size = a.num_polynomials(), rhs_size = b.num_polynomials())
+        )
+    )]
     pub(crate) fn multiply_inplace(&self, a: &mut Ciphertext, b: &Ciphertext) -> Result<()> {
         convert_seal_error(unsafe {
-            bindgen::Evaluator_Multiply(
+            bindgen::Shim_Evaluator_Multiply(
                 self.handle,
                 a.get_handle(),
                 b.get_handle(),
                 a.get_handle(),
-                null_mut(),
             )
-        })?;
+        })
+        .map_err(|e| e.in_operation("multiply", Some(&*a), Some(b)))?;
 
         Ok(())
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(lhs_size = a.num_polynomials(), rhs_size = b.num_polynomials())
+        )
+    )]
     pub(crate) fn multiply(&self, a: &Ciphertext, b: &Ciphertext) -> Result<Ciphertext> {
         let c = Ciphertext::new()?;
 
         convert_seal_error(unsafe {
-            bindgen::Evaluator_Multiply(
+            bindgen::Shim_Evaluator_Multiply(
                 self.handle,
                 a.get_handle(),
                 b.get_handle(),
                 c.get_handle(),
-                null_mut(),
             )
-        })?;
+        })
+        .map_err(|e| e.in_operation("multiply", Some(a), Some(b)))?;
 
         Ok(c)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(lhs_size = a.num_polynomials()))
+    )]
     pub(crate) fn square_inplace(&self, a: &mut Ciphertext) -> Result<()> {
         convert_seal_error(unsafe {
             bindgen::Evaluator_Square(self.handle, a.get_handle(), a.get_handle(), null_mut())
-        })?;
+        })
+        .map_err(|e| e.in_operation("square", Some(&*a), None))?;
 
         Ok(())
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(lhs_size = a.num_polynomials()))
+    )]
     pub(crate) fn square(&self, a: &Ciphertext) -> Result<Ciphertext> {
         let c = Ciphertext::new()?;
 
         convert_seal_error(unsafe {
             bindgen::Evaluator_Square(self.handle, a.get_handle(), c.get_handle(), null_mut())
-        })?;
+        })
+        .map_err(|e| e.in_operation("square", Some(a), None))?;
 
         Ok(c)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(lhs_size = a.num_polynomials()))
+    )]
     pub(crate) fn mod_switch_to_next(&self, a: &Ciphertext) -> Result<Ciphertext> {
         let c = Ciphertext::new()?;
 
@@ -239,12 +334,17 @@ impl EvaluatorBase {
                 c.get_handle(),
                 null_mut(),
             )
-        })?;
+        })
+        .map_err(|e| e.in_operation("mod_switch_to_next", Some(a), None))?;
 
         Ok(c)
     }
 
-    pub(crate) fn mod_switch_to_next_inplace(&self, a: &Ciphertext) -> Result<()> {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(lhs_size = a.num_polynomials()))
+    )]
+    pub(crate) fn mod_switch_to_next_inplace(&self, a: &mut Ciphertext) -> Result<()> {
         convert_seal_error(unsafe {
             bindgen::Evaluator_ModSwitchToNext1(
                 self.get_handle(),
@@ -252,29 +352,42 @@ impl EvaluatorBase {
                 a.get_handle(),
                 null_mut(),
             )
-        })?;
+        })
+        .map_err(|e| e.in_operation("mod_switch_to_next", Some(&*a), None))?;
 
         Ok(())
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub(crate) fn mod_switch_to_next_plaintext(&self, a: &Plaintext) -> Result<Plaintext> {
         let p = Plaintext::new()?;
 
         convert_seal_error(unsafe {
             bindgen::Evaluator_ModSwitchToNext2(self.get_handle(), a.get_handle(), p.get_handle())
-        })?;
+        })
+        .map_err(|e| e.in_operation("mod_switch_to_next_plaintext", None, None))?;
 
         Ok(p)
     }
 
-    pub(crate) fn mod_switch_to_next_inplace_plaintext(&self, a: &Plaintext) -> Result<()> {
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub(crate) fn mod_switch_to_next_inplace_plaintext(&self, a: &mut Plaintext) -> Result<()> {
         convert_seal_error(unsafe {
             bindgen::Evaluator_ModSwitchToNext2(self.get_handle(), a.get_handle(), a.get_handle())
-        })?;
+        })
+        .map_err(|e| e.in_operation("mod_switch_to_next_plaintext", None, None))?;
 
         Ok(())
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(lhs_size = a.num_polynomials(), exponent = exponent)
+        )
+    )]
     pub(crate) fn exponentiate(
         &self,
         a: &Ciphertext,
@@ -292,14 +405,23 @@ impl EvaluatorBase {
                 c.get_handle(),
                 null_mut(),
             )
-        })?;
+        })
+        .map_err(|e| e.in_operation("exponentiate", Some(a), None))?;
 
         Ok(c)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(lhs_size = a.num_polynomials(), exponent = exponent)
+        )
+    )]
     pub(crate) fn exponentiate_inplace(
         &self,
-        a: &Ciphertext,
+        a: &mut Ciphertext,
         exponent: u64,
         relin_keys: &RelinearizationKeys,
     ) -> Result<()> {
@@ -312,11 +434,16 @@ impl EvaluatorBase {
                 a.get_handle(),
                 null_mut(),
             )
-        })?;
+        })
+        .map_err(|e| e.in_operation("exponentiate", Some(&*a), None))?;
 
         Ok(())
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(lhs_size = a.num_polynomials()))
+    )]
     pub(crate) fn add_plain(&self, a: &Ciphertext, b: &Plaintext) -> Result<Ciphertext> {
         let c = Ciphertext::new()?;
 
@@ -327,11 +454,16 @@ impl EvaluatorBase {
                 b.get_handle(),
                 c.get_handle(),
             )
-        })?;
+        })
+        .map_err(|e| e.in_operation("add_plain", Some(a), None))?;
 
         Ok(c)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(lhs_size = a.num_polynomials()))
+    )]
     pub(crate) fn add_plain_inplace(&self, a: &mut Ciphertext, b: &Plaintext) -> Result<()> {
         convert_seal_error(unsafe {
             bindgen::Evaluator_AddPlain(
@@ -340,11 +472,16 @@ impl EvaluatorBase {
                 b.get_handle(),
                 a.get_handle(),
             )
-        })?;
+        })
+        .map_err(|e| e.in_operation("add_plain", Some(&*a), None))?;
 
         Ok(())
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(lhs_size = a.num_polynomials()))
+    )]
     pub(crate) fn sub_plain(&self, a: &Ciphertext, b: &Plaintext) -> Result<Ciphertext> {
         let c = Ciphertext::new()?;
 
@@ -355,11 +492,16 @@ impl EvaluatorBase {
                 b.get_handle(),
                 c.get_handle(),
             )
-        })?;
+        })
+        .map_err(|e| e.in_operation("sub_plain", Some(a), None))?;
 
         Ok(c)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(lhs_size = a.num_polynomials()))
+    )]
     pub(crate) fn sub_plain_inplace(&self, a: &mut Ciphertext, b: &Plaintext) -> Result<()> {
         convert_seal_error(unsafe {
             bindgen::Evaluator_SubPlain(
@@ -368,12 +510,57 @@ impl EvaluatorBase {
                 b.get_handle(),
                 a.get_handle(),
             )
-        })?;
+        })
+        .map_err(|e| e.in_operation("sub_plain", Some(&*a), None))?;
+
+        Ok(())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(rhs_size = b.num_polynomials()))
+    )]
+    pub(crate) fn plain_sub(&self, a: &Plaintext, b: &Ciphertext) -> Result<Ciphertext> {
+        let c = self.sub_plain(b, a)?;
+
+        // a - b = -(b - a), and negation adds no noise.
+        convert_seal_error(unsafe {
+            bindgen::Evaluator_Negate(self.get_handle(), c.get_handle(), c.get_handle())
+        })
+        .map_err(|e| e.in_operation("plain_sub", Some(b), None))?;
+
+        Ok(c)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(rhs_size = b.num_polynomials()))
+    )]
+    pub(crate) fn plain_sub_inplace(&self, a: &Plaintext, b: &mut Ciphertext) -> Result<()> {
+        self.sub_plain_inplace(b, a)?;
+
+        convert_seal_error(unsafe {
+            bindgen::Evaluator_Negate(self.get_handle(), b.get_handle(), b.get_handle())
+        })
+        .map_err(|e| e.in_operation("plain_sub", Some(&*b), None))?;
 
         Ok(())
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(lhs_size = a.num_polynomials()))
+    )]
     pub(crate) fn multiply_plain(&self, a: &Ciphertext, b: &Plaintext) -> Result<Ciphertext> {
+        // Multiplying by 1 (e.g. an all-ones mask) is a copy.
+        if b.is_one() {
+            return a.try_clone();
+        }
+
+        if is_sparse(b) {
+            return self.multiply_plain_sparse(a, b);
+        }
+
         let c = Ciphertext::new()?;
 
         convert_seal_error(unsafe {
@@ -384,12 +571,27 @@ impl EvaluatorBase {
                 c.get_handle(),
                 null_mut(),
             )
-        })?;
+        })
+        .map_err(|e| e.in_operation("multiply_plain", Some(a), None))?;
 
         Ok(c)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(lhs_size = a.num_polynomials()))
+    )]
     pub(crate) fn multiply_plain_inplace(&self, a: &mut Ciphertext, b: &Plaintext) -> Result<()> {
+        if b.is_one() {
+            return Ok(());
+        }
+
+        if is_sparse(b) {
+            *a = self.multiply_plain_sparse(a, b)?;
+
+            return Ok(());
+        }
+
         convert_seal_error(unsafe {
             bindgen::Evaluator_MultiplyPlain(
                 self.get_handle(),
@@ -398,10 +600,71 @@ impl EvaluatorBase {
                 a.get_handle(),
                 null_mut(),
             )
-        })?;
+        })
+        .map_err(|e| e.in_operation("multiply_plain", Some(&*a), None))?;
 
         Ok(())
     }
 
+    /**
+     * Multiplies `a` by each nonzero term of `b` and sums the products.
+     * SEAL multiplies by a plaintext with a single nonzero coefficient as a
+     * monomial, i.e. a negacyclic shift and scaling of each polynomial,
+     * without transforming to NTT form.
+     */
+    fn multiply_plain_sparse(&self, a: &Ciphertext, b: &Plaintext) -> Result<Ciphertext> {
+        let mut terms = b.nonzero_coefficient_count();
+        let mut product: Option<Ciphertext> = None;
+
+        for i in 0..b.len() {
+            if terms == 0 {
+                break;
+            }
+
+            let coefficient = b.get_coefficient(i);
+
+            if coefficient == 0 {
+                continue;
+            }
+
+            terms -= 1;
+
+            let mut monomial = Plaintext::new()?;
+            monomial.resize(i + 1);
+            monomial.set_coefficient(i, coefficient);
+
+            let c = Ciphertext::new()?;
+
+            convert_seal_error(unsafe {
+                bindgen::Evaluator_MultiplyPlain(
+                    self.get_handle(),
+                    a.get_handle(),
+                    monomial.get_handle(),
+                    c.get_handle(),
+                    null_mut(),
+                )
+            })
+            .map_err(|e| e.in_operation("multiply_plain", Some(a), None))?;
+
+            match product {
+                Some(ref mut product) => self.add_inplace(product, &c)?,
+                None => product = Some(c),
+            };
+        }
+
+        // Only plaintexts with nonzero coefficients are sparse.
+        product.ok_or(Error::Unexpected)
+    }
+
     // TODO: NTT transform.
 }
+
+/**
+ * Returns whether [`EvaluatorBase::multiply_plain_sparse`] multiplies by
+ * `b` faster than SEAL's NTT-based multiplication.
+ */
+fn is_sparse(b: &Plaintext) -> bool {
+    let terms = b.nonzero_coefficient_count();
+
+    !b.is_ntt_form() && terms > 1 && terms <= SPARSE_MULTIPLY_MAX_TERMS
+}
@@ -0,0 +1,91 @@
+//! Deterministic failure injection for testing error handling. Requires
+//! the `failure-injection` feature, which must never be enabled in
+//! production builds.
+//!
+//! Once armed with [`fail_nth_call`], the `n`th subsequent call into SEAL
+//! on any thread reports the chosen failure without calling SEAL. This
+//! exercises error paths that SEAL rarely takes, e.g. running out of
+//! memory halfway through an FHE program.
+//!
+//! # Remarks
+//! The failing call never reaches SEAL, so it creates nothing that could
+//! leak. Calls that destroy SEAL objects or fetch error messages aren't
+//! counted and never fail, so destructors and error reporting behave as
+//! in production.
+//!
+//! The countdown is global so it covers calls made on worker threads, e.g.
+//! by the runtime's parallel executor. Tests that inject failures should
+//! run in their own test binary or otherwise serially, lest concurrent
+//! tests consume the countdown.
+
+use std::os::raw::c_long;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+use crate::bindgen::{E_INVALIDARG, E_OUTOFMEMORY};
+
+/**
+ * The failure to inject.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InjectedFailure {
+    /**
+     * Fail with `E_OUTOFMEMORY`, i.e. [`Error::OutOfMemory`](crate::Error::OutOfMemory).
+     */
+    OutOfMemory,
+
+    /**
+     * Fail with `E_INVALIDARG`, i.e.
+     * [`Error::InvalidArgument`](crate::Error::InvalidArgument).
+     */
+    InvalidArgument,
+}
+
+/**
+ * The number of calls left until the injected failure, or 0 if disarmed.
+ */
+static COUNTDOWN: AtomicU64 = AtomicU64::new(0);
+static FAILURE: AtomicU8 = AtomicU8::new(0);
+
+/**
+ * Makes the `n`th call into SEAL from now on fail with `failure`, counting
+ * from 1. Later calls succeed as usual. Replaces any previously armed
+ * failure; `n = 0` disarms it.
+ */
+pub fn fail_nth_call(n: u64, failure: InjectedFailure) {
+    FAILURE.store(failure as u8, Ordering::SeqCst);
+    COUNTDOWN.store(n, Ordering::SeqCst);
+}
+
+/**
+ * Cancels a failure armed with [`fail_nth_call`] that hasn't fired yet.
+ */
+pub fn disarm() {
+    COUNTDOWN.store(0, Ordering::SeqCst);
+}
+
+/**
+ * Returns whether a failure is armed and hasn't fired yet.
+ */
+pub fn is_armed() -> bool {
+    COUNTDOWN.load(Ordering::SeqCst) > 0
+}
+
+/**
+ * Counts a call into SEAL, returning the error code to report instead of
+ * its result if this is the call that should fail.
+ */
+pub(crate) fn next_call() -> Option<c_long> {
+    let prev = COUNTDOWN
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+        .ok()?;
+
+    if prev != 1 {
+        return None;
+    }
+
+    if FAILURE.load(Ordering::SeqCst) == InjectedFailure::OutOfMemory as u8 {
+        Some(E_OUTOFMEMORY)
+    } else {
+        Some(E_INVALIDARG)
+    }
+}
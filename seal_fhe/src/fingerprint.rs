@@ -0,0 +1,269 @@
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::*;
+use crate::serialization::CompressionType;
+use crate::stream::SEAL_HEADER_SIZE;
+use crate::ToBytes;
+
+/**
+ * A stable 256-bit hash identifying encryption parameters or a key, e.g.
+ * so services can check they agree on them before exchanging
+ * ciphertexts. Returned by `fingerprint` on
+ * [`EncryptionParameters`](crate::EncryptionParameters),
+ * [`Context`](crate::Context), [`PublicKey`](crate::PublicKey),
+ * [`GaloisKeys`](crate::GaloisKeys) and
+ * [`RelinearizationKeys`](crate::RelinearizationKeys).
+ *
+ * # Remarks
+ * Fingerprints depend only on the hashed object's contents, so they're
+ * the same across processes, machines and builds. Parameter fingerprints
+ * are SEAL's own parms_id hash of the scheme, polynomial degree and
+ * moduli. Key fingerprints are SHA-256 hashes of the key's uncompressed
+ * serialization, which includes the parms_id of the key's parameters,
+ * excluding SEAL's version header.
+ *
+ * Fingerprints detect mismatches, not tampering: anyone can compute the
+ * fingerprint of a key they made up.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Fingerprint([u8; 32]);
+
+impl Fingerprint {
+    pub(crate) fn from_parms_id(parms_id: [u64; 4]) -> Self {
+        let mut bytes = [0u8; 32];
+
+        for (chunk, word) in bytes.chunks_mut(8).zip(parms_id.iter()) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+
+        Self(bytes)
+    }
+
+    /**
+     * Hashes `object`'s serialization, prefixed with `domain` so
+     * different kinds of objects never share a fingerprint.
+     */
+    pub(crate) fn of_object<T>(domain: &[u8], object: &T) -> Result<Self>
+    where
+        T: ToBytes,
+    {
+        let bytes = object.as_bytes_with_compression(CompressionType::None)?;
+
+        let mut data = domain.to_vec();
+        data.push(0);
+        data.extend_from_slice(&bytes[SEAL_HEADER_SIZE.min(bytes.len())..]);
+
+        Ok(Self(sha256(&data)))
+    }
+
+    /**
+     * Returns the fingerprint's bytes.
+     */
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl Display for Fingerprint {
+    /**
+     * Writes the fingerprint as 64 lowercase hexadecimal digits.
+     */
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for b in self.0.iter() {
+            write!(f, "{:02x}", b)?;
+        }
+
+        Ok(())
+    }
+}
+
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/**
+ * Returns the SHA-256 hash of `data`, as specified in FIPS 180-4.
+ */
+fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut h: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+
+    // Pad with a 1 bit, zeros and the length in bits to a multiple of 64
+    // bytes.
+    let mut message = data.to_vec();
+    message.push(0x80);
+
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut w = [0u32; 64];
+
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let mut v = h;
+
+        for (k, w) in K.iter().zip(w.iter()) {
+            let s1 = v[4].rotate_right(6) ^ v[4].rotate_right(11) ^ v[4].rotate_right(25);
+            let ch = (v[4] & v[5]) ^ (!v[4] & v[6]);
+            let t1 = v[7]
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(*k)
+                .wrapping_add(*w);
+            let s0 = v[0].rotate_right(2) ^ v[0].rotate_right(13) ^ v[0].rotate_right(22);
+            let maj = (v[0] & v[1]) ^ (v[0] & v[2]) ^ (v[1] & v[2]);
+            let t2 = s0.wrapping_add(maj);
+
+            v = [
+                t1.wrapping_add(t2),
+                v[0],
+                v[1],
+                v[2],
+                v[3].wrapping_add(t1),
+                v[4],
+                v[5],
+                v[6],
+            ];
+        }
+
+        for (h, v) in h.iter_mut().zip(v.iter()) {
+            *h = h.wrapping_add(*v);
+        }
+    }
+
+    let mut digest = [0u8; 32];
+
+    for (chunk, h) in digest.chunks_mut(4).zip(h.iter()) {
+        chunk.copy_from_slice(&h.to_be_bytes());
+    }
+
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    fn hex(bytes: &[u8; 32]) -> String {
+        Fingerprint(*bytes).to_string()
+    }
+
+    #[test]
+    fn sha256_matches_test_vectors() {
+        assert_eq!(
+            hex(&sha256(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hex(&sha256(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hex(&sha256(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn fingerprints_identify_parameters_and_keys() {
+        let make_params = |bits: &[i32]| {
+            BfvEncryptionParametersBuilder::new()
+                .set_poly_modulus_degree(8192)
+                .set_coefficient_modulus(CoefficientModulus::create(8192, bits).unwrap())
+                .set_plain_modulus(PlainModulus::batching(8192, 20).unwrap())
+                .build()
+                .unwrap()
+        };
+
+        let params = make_params(&[50, 30, 30, 50, 50]);
+        let other_params = make_params(&[50, 30, 50, 50]);
+
+        assert_eq!(
+            params.fingerprint(),
+            make_params(&[50, 30, 30, 50, 50]).fingerprint()
+        );
+        assert_ne!(params.fingerprint(), other_params.fingerprint());
+
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+        let other_ctx = Context::new(&other_params, false, SecurityLevel::TC128).unwrap();
+
+        assert_eq!(ctx.fingerprint().unwrap(), params.fingerprint());
+
+        let gen = KeyGenerator::new(&ctx).unwrap();
+        let public_key = gen.create_public_key().unwrap();
+        let galois_keys = gen.create_galois_keys().unwrap();
+
+        let copy = PublicKey::from_bytes(&ctx, &public_key.as_bytes().unwrap()).unwrap();
+
+        assert_eq!(
+            public_key.fingerprint().unwrap(),
+            copy.fingerprint().unwrap()
+        );
+        assert_ne!(
+            public_key.fingerprint().unwrap(),
+            KeyGenerator::new(&ctx)
+                .unwrap()
+                .create_public_key()
+                .unwrap()
+                .fingerprint()
+                .unwrap()
+        );
+        assert_ne!(
+            galois_keys.fingerprint().unwrap(),
+            galois_keys.subset(&[3]).unwrap().fingerprint().unwrap()
+        );
+
+        let relin_keys = gen.create_relinearization_keys().unwrap();
+
+        assert_eq!(
+            relin_keys.fingerprint().unwrap(),
+            relin_keys.clone().fingerprint().unwrap()
+        );
+        assert_ne!(
+            relin_keys.fingerprint().unwrap(),
+            gen.create_relinearization_keys()
+                .unwrap()
+                .fingerprint()
+                .unwrap()
+        );
+
+        assert_eq!(
+            public_key.get_parms_id().unwrap(),
+            ctx.get_key_parms_id().unwrap()
+        );
+        assert!(public_key.is_valid_for(&ctx).unwrap());
+        assert!(!public_key.is_valid_for(&other_ctx).unwrap());
+        assert!(galois_keys.is_valid_for(&ctx).unwrap());
+        assert!(!galois_keys.is_valid_for(&other_ctx).unwrap());
+    }
+}
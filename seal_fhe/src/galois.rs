@@ -0,0 +1,399 @@
+use crate::error::*;
+
+/**
+ * The generator of the cyclic subgroup of Galois automorphisms that
+ * rotate the rows of a batched plaintext.
+ */
+const GENERATOR: u64 = 3;
+
+fn validate_poly_degree(poly_degree: u64) -> Result<()> {
+    if !poly_degree.is_power_of_two() || poly_degree < 4 || poly_degree > (1 << 17) {
+        return Err(Error::InvalidArgument);
+    }
+
+    Ok(())
+}
+
+/**
+ * Normalizes a row rotation into an equivalent left rotation in `0..N/2`.
+ */
+fn normalize_step(step: i32, poly_degree: u64) -> Result<u64> {
+    let row_size = poly_degree / 2;
+
+    if step.unsigned_abs() as u64 >= row_size {
+        return Err(Error::InvalidArgument);
+    }
+
+    Ok(if step < 0 {
+        row_size - step.unsigned_abs() as u64
+    } else {
+        step as u64
+    })
+}
+
+/**
+ * Returns the Galois element that rotates the rows of a batched plaintext by
+ * `step` places for the given polynomial degree. This matches SEAL's own
+ * mapping, so the returned elements can be passed to
+ * [`KeyGenerator::create_galois_keys_from_elts`](crate::KeyGenerator::create_galois_keys_from_elts).
+ *
+ * # Remarks
+ * Positive steps rotate left and negative steps rotate right. As in SEAL, a
+ * `step` of 0 denotes a column rotation (i.e. swapping the two rows).
+ *
+ * Returns [`Error::InvalidArgument`] if `poly_degree` is not a supported power
+ * of two or `|step| >= poly_degree / 2`.
+ */
+pub fn galois_elt_from_step(step: i32, poly_degree: u64) -> Result<u32> {
+    validate_poly_degree(poly_degree)?;
+
+    let m = 2 * poly_degree;
+
+    if step == 0 {
+        return Ok((m - 1) as u32);
+    }
+
+    let step = normalize_step(step, poly_degree)?;
+
+    let mut elt = 1u64;
+
+    for _ in 0..step {
+        elt = (elt * GENERATOR) % m;
+    }
+
+    Ok(elt as u32)
+}
+
+/**
+ * Returns the Galois element for each of the given rotation steps. See
+ * [`galois_elt_from_step`].
+ */
+pub fn galois_elts_from_steps(steps: &[i32], poly_degree: u64) -> Result<Vec<u32>> {
+    steps
+        .iter()
+        .map(|s| galois_elt_from_step(*s, poly_degree))
+        .collect()
+}
+
+/**
+ * The inverse of [`galois_elt_from_step`]. Returns the left rotation step in
+ * `1..N/2` that the given Galois element performs, or 0 if the element
+ * performs a column rotation.
+ *
+ * Returns [`Error::InvalidArgument`] if the element doesn't correspond to a
+ * batched rotation under the given polynomial degree.
+ */
+pub fn step_from_galois_elt(galois_elt: u32, poly_degree: u64) -> Result<i32> {
+    validate_poly_degree(poly_degree)?;
+
+    let m = 2 * poly_degree;
+    let galois_elt = galois_elt as u64;
+
+    if galois_elt == m - 1 {
+        return Ok(0);
+    }
+
+    let mut elt = 1u64;
+
+    for step in 0..poly_degree / 2 {
+        if elt == galois_elt {
+            return Ok(step as i32);
+        }
+
+        elt = (elt * GENERATOR) % m;
+    }
+
+    Err(Error::InvalidArgument)
+}
+
+/**
+ * Returns the non-adjacent form of `value`, i.e. the signed powers of two
+ * summing to it with the fewest nonzero terms. SEAL uses this to compose
+ * rotations it has no dedicated Galois key for.
+ */
+pub(crate) fn naf(value: i32) -> Vec<i32> {
+    let negative = value < 0;
+    let mut value = value.unsigned_abs() as i64;
+    let mut terms = vec![];
+    let mut bit = 0;
+
+    while value != 0 {
+        let digit = if value & 1 == 1 { 2 - (value & 3) } else { 0 };
+
+        value = (value - digit) >> 1;
+
+        if digit != 0 {
+            let term = (digit << bit) as i32;
+
+            terms.push(if negative { -term } else { term });
+        }
+
+        bit += 1;
+    }
+
+    terms
+}
+
+/**
+ * Returns the steps of the power-of-two rotations SEAL performs in place of
+ * a rotation by `step` when there's no Galois key for `step` itself.
+ */
+pub(crate) fn naf_rotation_steps(step: i32, poly_degree: u64) -> Vec<i32> {
+    naf(step)
+        .into_iter()
+        // Rotating by half the degree is the identity on each row.
+        .filter(|s| s.unsigned_abs() as u64 != poly_degree / 2)
+        .collect()
+}
+
+/**
+ * Chooses the rotation steps to generate Galois keys for so that every
+ * rotation in `steps` can be performed.
+ *
+ * # Remarks
+ * Each requested rotation is first normalized to a left rotation. This
+ * function then picks the smaller of two key sets:
+ * * one key per distinct requested rotation, which lets each rotation run
+ * as a single key switch.
+ * * one key per power of two appearing in the non-adjacent form of any
+ * requested rotation, which SEAL performs as a sequence of power-of-two
+ * rotations in either direction.
+ *
+ * Ties favor the per-rotation set, since it requires fewer key switches at
+ * evaluation time. A `step` of 0 (column rotation) is always kept as is.
+ * The returned steps are sorted and contain no duplicates.
+ */
+pub fn minimal_rotation_key_steps(steps: &[i32], poly_degree: u64) -> Result<Vec<i32>> {
+    validate_poly_degree(poly_degree)?;
+
+    let mut exact = vec![];
+    let mut powers = vec![];
+
+    for s in steps {
+        if *s == 0 {
+            exact.push(0);
+            powers.push(0);
+            continue;
+        }
+
+        let step = normalize_step(*s, poly_degree)?;
+
+        exact.push(step as i32);
+
+        for s in naf_rotation_steps(*s, poly_degree) {
+            powers.push(normalize_step(s, poly_degree)? as i32);
+        }
+    }
+
+    exact.sort_unstable();
+    exact.dedup();
+    powers.sort_unstable();
+    powers.dedup();
+
+    if powers.len() < exact.len() {
+        Ok(powers)
+    } else {
+        Ok(exact)
+    }
+}
+
+/**
+ * Returns the steps of the Galois keys SEAL uses to rotate by `step` given
+ * keys for `key_steps`, or [`None`] if those keys can't perform the
+ * rotation. The result is sorted and contains no duplicates.
+ *
+ * # Remarks
+ * `key_steps` must be normalized left rotations, as returned by
+ * [`minimal_rotation_key_steps`]. This mirrors
+ * [`GaloisKeys::can_rotate_rows`](crate::GaloisKeys::can_rotate_rows), but
+ * without needing the keys themselves.
+ */
+pub fn rotation_key_steps(
+    step: i32,
+    key_steps: &[i32],
+    poly_degree: u64,
+) -> Result<Option<Vec<i32>>> {
+    validate_poly_degree(poly_degree)?;
+
+    let normalized = if step == 0 {
+        0
+    } else {
+        normalize_step(step, poly_degree)? as i32
+    };
+
+    if key_steps.contains(&normalized) {
+        return Ok(Some(vec![normalized]));
+    }
+
+    if step == 0 || naf(step).len() == 1 {
+        return Ok(None);
+    }
+
+    let mut used = vec![];
+
+    for s in naf_rotation_steps(step, poly_degree) {
+        match rotation_key_steps(s, key_steps, poly_degree)? {
+            Some(steps) => used.extend(steps),
+            None => return Ok(None),
+        }
+    }
+
+    used.sort_unstable();
+    used.dedup();
+
+    Ok(Some(used))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn can_compute_galois_elts() {
+        assert_eq!(galois_elt_from_step(0, 8192).unwrap(), 16383);
+        assert_eq!(galois_elt_from_step(1, 8192).unwrap(), 3);
+        assert_eq!(galois_elt_from_step(2, 8192).unwrap(), 9);
+        assert_eq!(
+            galois_elt_from_step(-1, 8192).unwrap(),
+            galois_elt_from_step(4095, 8192).unwrap()
+        );
+
+        assert_eq!(
+            galois_elt_from_step(4096, 8192),
+            Err(Error::InvalidArgument)
+        );
+        assert_eq!(galois_elt_from_step(1, 1000), Err(Error::InvalidArgument));
+    }
+
+    #[test]
+    fn can_roundtrip_galois_elts() {
+        for step in [0, 1, 2, 7, 100, 4095] {
+            let elt = galois_elt_from_step(step, 8192).unwrap();
+
+            assert_eq!(step_from_galois_elt(elt, 8192).unwrap(), step);
+        }
+
+        let elt = galois_elt_from_step(-3, 8192).unwrap();
+
+        assert_eq!(step_from_galois_elt(elt, 8192).unwrap(), 4093);
+
+        // Even elements aren't automorphisms.
+        assert_eq!(step_from_galois_elt(2, 8192), Err(Error::InvalidArgument));
+    }
+
+    #[test]
+    fn can_compute_naf() {
+        assert_eq!(naf(0), Vec::<i32>::new());
+        assert_eq!(naf(3), vec![-1, 4]);
+        assert_eq!(naf(5), vec![1, 4]);
+        assert_eq!(naf(-7), vec![1, -8]);
+
+        for value in -1000..1000 {
+            assert_eq!(naf(value).iter().sum::<i32>(), value);
+        }
+    }
+
+    #[test]
+    fn picks_smallest_rotation_key_set() {
+        assert_eq!(
+            minimal_rotation_key_steps(&[1, 2, 4, 5, 8, 9, 16], 8192).unwrap(),
+            vec![1, 2, 4, 8, 16]
+        );
+
+        // SEAL performs a rotation by 3 as rotations by 4 and -1.
+        assert_eq!(
+            minimal_rotation_key_steps(&[3, 4, 8, 11], 8192).unwrap(),
+            vec![3, 4, 8, 11]
+        );
+        assert_eq!(
+            minimal_rotation_key_steps(&[1, 3, 5, 7, 9, 15, 17], 8192).unwrap(),
+            vec![1, 4, 8, 16, 4095]
+        );
+
+        assert_eq!(
+            minimal_rotation_key_steps(&[5, 5, 0], 8192).unwrap(),
+            vec![0, 5]
+        );
+
+        assert_eq!(minimal_rotation_key_steps(&[-1], 8192).unwrap(), vec![4095]);
+    }
+
+    #[test]
+    fn can_find_rotation_key_steps() {
+        let keys = minimal_rotation_key_steps(&[1, 3, 5, 7, 9, 15, 17], 8192).unwrap();
+
+        assert_eq!(
+            rotation_key_steps(9, &keys, 8192).unwrap(),
+            Some(vec![1, 8])
+        );
+        assert_eq!(
+            rotation_key_steps(7, &keys, 8192).unwrap(),
+            Some(vec![8, 4095])
+        );
+        assert_eq!(rotation_key_steps(16, &keys, 8192).unwrap(), Some(vec![16]));
+        assert_eq!(rotation_key_steps(0, &keys, 8192).unwrap(), None);
+        assert_eq!(rotation_key_steps(2, &keys, 8192).unwrap(), None);
+
+        assert_eq!(
+            rotation_key_steps(-1, &[4095], 8192).unwrap(),
+            Some(vec![4095])
+        );
+        assert_eq!(rotation_key_steps(0, &[0, 3], 8192).unwrap(), Some(vec![0]));
+    }
+
+    #[test]
+    fn can_create_galois_keys_from_elts() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 20).unwrap())
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, true, SecurityLevel::TC128).unwrap();
+        let gen = KeyGenerator::new(&ctx).unwrap();
+
+        let steps = minimal_rotation_key_steps(&[1, 3], 8192).unwrap();
+        let elts = galois_elts_from_steps(&steps, 8192).unwrap();
+
+        let keys = gen.create_galois_keys_from_elts(&elts).unwrap();
+
+        assert!(keys.can_rotate_rows(1, 8192).unwrap());
+        assert!(keys.can_rotate_rows(3, 8192).unwrap());
+    }
+
+    #[test]
+    fn can_check_rotation_support() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 20).unwrap())
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, true, SecurityLevel::TC128).unwrap();
+        let gen = KeyGenerator::new(&ctx).unwrap();
+
+        let elts = galois_elts_from_steps(&[1, 2, 4], 8192).unwrap();
+        let keys = gen.create_galois_keys_from_elts(&elts).unwrap();
+
+        assert!(keys.has_key(elts[0]).unwrap());
+        assert!(keys.can_rotate_rows(5, 8192).unwrap());
+
+        // 3 = 4 - 1 and 6 = 8 - 2, but there are no keys to rotate right.
+        assert!(!keys.can_rotate_rows(3, 8192).unwrap());
+        assert!(!keys.can_rotate_rows(6, 8192).unwrap());
+        assert!(!keys.can_rotate_rows(8, 8192).unwrap());
+        assert!(!keys.can_rotate_rows(0, 8192).unwrap());
+
+        let keys = gen.create_galois_keys().unwrap();
+
+        for step in [-4095, -3, 0, 3, 7, 100, 4095] {
+            assert!(keys.can_rotate_rows(step, 8192).unwrap());
+        }
+    }
+}
@@ -0,0 +1,368 @@
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use crate::error::*;
+use crate::galois::{galois_elt_from_step, rotation_key_steps, step_from_galois_elt};
+use crate::serialization::CompressionType;
+use crate::stream::io_error;
+use crate::{Context, FromReader, GaloisKeys, ToBytes};
+
+/**
+ * The magic number at the start of an indexed Galois key archive.
+ */
+const MAGIC: [u8; 4] = *b"SGKI";
+
+/**
+ * The version of the archive format.
+ */
+const VERSION: u8 = 1;
+
+/**
+ * The size of each entry in the header: a `u32` Galois element and a
+ * `u64` size.
+ */
+const ENTRY_SIZE: usize = 12;
+
+/**
+ * The most entries a header may claim, one per odd Galois element for the
+ * largest polynomial degree SEAL supports.
+ */
+const MAX_ENTRIES: u32 = 1 << 17;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Entry {
+    galois_elt: u32,
+    offset: u64,
+    size: u64,
+}
+
+/**
+ * The header of a Galois key archive that stores each key separately, so
+ * a server can load just the keys a request needs rather than the whole
+ * set.
+ *
+ * # Remarks
+ * Write an archive with [`write`](Self::write). Reading it back with
+ * [`read`](Self::read) reads only the header, which lists each key's
+ * Galois element, offset and size. [`load`](Self::load) and
+ * [`load_for_steps`](Self::load_for_steps) then seek to and deserialize
+ * only the requested keys. A full set of keys for `N = 8192` runs to tens
+ * of megabytes, of which the few keys a typical program rotates with
+ * are a small fraction.
+ *
+ * The archive starts with the magic bytes `SGKI`, a version byte and the
+ * number of keys as a little-endian `u32`, followed by a little-endian
+ * `u32` Galois element and `u64` size per key. The keys follow in the
+ * same order, each in SEAL's format and holding the one key.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GaloisKeyIndex {
+    entries: Vec<Entry>,
+    // The stream position of the first key.
+    data_start: u64,
+}
+
+impl GaloisKeyIndex {
+    /**
+     * Writes `keys` to `writer` as an indexed archive and returns the
+     * number of bytes written.
+     */
+    pub fn write<W>(keys: &GaloisKeys, writer: &mut W) -> Result<u64>
+    where
+        W: Write,
+    {
+        Self::write_with_compression(keys, writer, CompressionType::default())
+    }
+
+    /**
+     * Writes `keys` to `writer` as an indexed archive, compressing each key
+     * with the given compression, and returns the number of bytes written.
+     */
+    pub fn write_with_compression<W>(
+        keys: &GaloisKeys,
+        writer: &mut W,
+        compression: CompressionType,
+    ) -> Result<u64>
+    where
+        W: Write,
+    {
+        let elts = keys.galois_elts()?;
+
+        let blobs = elts
+            .iter()
+            .map(|elt| keys.subset(&[*elt])?.as_bytes_with_compression(compression))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut header = MAGIC.to_vec();
+        header.push(VERSION);
+        header.extend_from_slice(&(elts.len() as u32).to_le_bytes());
+
+        for (elt, blob) in elts.iter().zip(blobs.iter()) {
+            header.extend_from_slice(&elt.to_le_bytes());
+            header.extend_from_slice(&(blob.len() as u64).to_le_bytes());
+        }
+
+        writer.write_all(&header).map_err(io_error)?;
+
+        let mut written = header.len() as u64;
+
+        for blob in blobs {
+            writer.write_all(&blob).map_err(io_error)?;
+            written += blob.len() as u64;
+        }
+
+        Ok(written)
+    }
+
+    /**
+     * Reads the header of an archive starting at `reader`'s current
+     * position, leaving the keys unread.
+     *
+     * Returns [`Error::SerializationError`] if the header is malformed.
+     */
+    pub fn read<R>(reader: &mut R) -> Result<Self>
+    where
+        R: Read + Seek,
+    {
+        let mut prefix = [0u8; 9];
+
+        reader.read_exact(&mut prefix).map_err(io_error)?;
+
+        if prefix[..4] != MAGIC || prefix[4] != VERSION {
+            return Err(Error::SerializationError(Box::new(
+                "Not an indexed Galois key archive".to_owned(),
+            )));
+        }
+
+        let mut count = [0u8; 4];
+        count.copy_from_slice(&prefix[5..9]);
+        let count = u32::from_le_bytes(count);
+
+        if count > MAX_ENTRIES {
+            return Err(Error::SerializationError(Box::new(format!(
+                "Archive claims {} keys",
+                count
+            ))));
+        }
+
+        let mut entries = Vec::with_capacity(count as usize);
+        let mut offset = 0u64;
+        let mut entry = [0u8; ENTRY_SIZE];
+
+        for _ in 0..count {
+            reader.read_exact(&mut entry).map_err(io_error)?;
+
+            let mut galois_elt = [0u8; 4];
+            galois_elt.copy_from_slice(&entry[..4]);
+            let mut size = [0u8; 8];
+            size.copy_from_slice(&entry[4..]);
+            let size = u64::from_le_bytes(size);
+
+            entries.push(Entry {
+                galois_elt: u32::from_le_bytes(galois_elt),
+                offset,
+                size,
+            });
+
+            offset = offset.checked_add(size).ok_or_else(|| {
+                Error::SerializationError(Box::new("Key sizes overflow".to_owned()))
+            })?;
+        }
+
+        Ok(Self {
+            entries,
+            data_start: reader.stream_position().map_err(io_error)?,
+        })
+    }
+
+    /**
+     * Returns the Galois elements the archive holds keys for, in the order
+     * they're stored.
+     */
+    pub fn galois_elts(&self) -> Vec<u32> {
+        self.entries.iter().map(|e| e.galois_elt).collect()
+    }
+
+    /**
+     * Returns whether the archive holds a key for the given Galois element.
+     */
+    pub fn has_key(&self, galois_elt: u32) -> bool {
+        self.entries.iter().any(|e| e.galois_elt == galois_elt)
+    }
+
+    /**
+     * Returns whether the archive's keys can rotate the rows of a batched
+     * ciphertext by `step` places, as in
+     * [`GaloisKeys::can_rotate_rows`].
+     */
+    pub fn can_rotate_rows(&self, step: i32, poly_degree: u64) -> Result<bool> {
+        Ok(rotation_key_steps(step, &self.key_steps(poly_degree)?, poly_degree)?.is_some())
+    }
+
+    /**
+     * Loads the keys for the given Galois elements from `reader`, which
+     * must be the stream [`read`](Self::read) read the header from.
+     *
+     * Returns [`Error::InvalidArgument`] if `galois_elts` is empty or the
+     * archive lacks any of them.
+     */
+    pub fn load<R>(
+        &self,
+        context: &Context,
+        reader: &mut R,
+        galois_elts: &[u32],
+    ) -> Result<GaloisKeys>
+    where
+        R: Read + Seek,
+    {
+        let mut galois_elts = galois_elts.to_owned();
+        galois_elts.sort_unstable();
+        galois_elts.dedup();
+
+        let keys = galois_elts
+            .iter()
+            .map(|elt| {
+                let entry = self
+                    .entries
+                    .iter()
+                    .find(|e| e.galois_elt == *elt)
+                    .ok_or(Error::InvalidArgument)?;
+
+                reader
+                    .seek(SeekFrom::Start(self.data_start + entry.offset))
+                    .map_err(io_error)?;
+
+                let mut blob = (&mut *reader).take(entry.size);
+                let keys = GaloisKeys::load_from_reader(context, &mut blob)?;
+
+                // Don't trust the header to say which key a blob holds.
+                if !keys.has_key(*elt)? || keys.galois_elts()?.len() != 1 {
+                    return Err(Error::SerializationError(Box::new(format!(
+                        "Archive entry for Galois element {} holds other keys",
+                        elt
+                    ))));
+                }
+
+                Ok(keys)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        GaloisKeys::merge(&keys)
+    }
+
+    /**
+     * Loads the keys needed to rotate the rows of batched ciphertexts by
+     * each of `steps`, following the same rules as
+     * [`GaloisKeys::can_rotate_rows`]: a step without its own key is
+     * performed with the keys for the power-of-two steps it decomposes
+     * into.
+     *
+     * Returns [`Error::InvalidArgument`] if `steps` is empty or the
+     * archive's keys can't perform one of them.
+     */
+    pub fn load_for_steps<R>(
+        &self,
+        context: &Context,
+        reader: &mut R,
+        steps: &[i32],
+        poly_degree: u64,
+    ) -> Result<GaloisKeys>
+    where
+        R: Read + Seek,
+    {
+        let key_steps = self.key_steps(poly_degree)?;
+        let mut galois_elts = vec![];
+
+        for step in steps {
+            let used = rotation_key_steps(*step, &key_steps, poly_degree)?
+                .ok_or(Error::InvalidArgument)?;
+
+            for s in used {
+                galois_elts.push(galois_elt_from_step(s, poly_degree)?);
+            }
+        }
+
+        self.load(context, reader, &galois_elts)
+    }
+
+    /**
+     * The normalized steps of the archive's keys, as
+     * [`rotation_key_steps`] takes them.
+     */
+    fn key_steps(&self, poly_degree: u64) -> Result<Vec<i32>> {
+        let row_size = poly_degree as i32 / 2;
+
+        self.entries
+            .iter()
+            .map(|e| {
+                let step = step_from_galois_elt(e.galois_elt, poly_degree)?;
+
+                Ok(if step < 0 { step + row_size } else { step })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    use std::io::Cursor;
+
+    #[test]
+    fn can_load_individual_keys() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 20).unwrap())
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+        let gen = KeyGenerator::new(&ctx).unwrap();
+        let galois_keys = gen.create_galois_keys().unwrap();
+
+        let mut archive = vec![0xffu8; 3];
+        GaloisKeyIndex::write(&galois_keys, &mut archive).unwrap();
+
+        let mut reader = Cursor::new(archive);
+        reader.set_position(3);
+
+        let index = GaloisKeyIndex::read(&mut reader).unwrap();
+
+        assert_eq!(index.galois_elts(), galois_keys.galois_elts().unwrap());
+        assert!(index.can_rotate_rows(3, 8192).unwrap());
+
+        let keys = index
+            .load_for_steps(&ctx, &mut reader, &[1, 0], 8192)
+            .unwrap();
+
+        assert_eq!(
+            keys.galois_elts().unwrap(),
+            vec![
+                galois_elt_from_step(1, 8192).unwrap(),
+                galois_elt_from_step(0, 8192).unwrap()
+            ]
+        );
+        assert_eq!(
+            keys,
+            galois_keys
+                .subset(&galois_elts_from_steps(&[1, 0], 8192).unwrap())
+                .unwrap()
+        );
+
+        // 3 = 4 - 1 has no key of its own.
+        let keys = index.load_for_steps(&ctx, &mut reader, &[3], 8192).unwrap();
+        let mut expected = galois_elts_from_steps(&[4, -1], 8192).unwrap();
+        expected.sort_unstable();
+
+        assert_eq!(keys.galois_elts().unwrap(), expected);
+
+        assert_eq!(
+            index.load(&ctx, &mut reader, &[2]).err(),
+            Some(Error::InvalidArgument)
+        );
+        assert!(GaloisKeyIndex::read(&mut Cursor::new(vec![0u8; 16])).is_err());
+    }
+}
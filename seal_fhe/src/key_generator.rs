@@ -3,11 +3,14 @@ use std::ptr::null_mut;
 
 use crate::bindgen;
 use crate::error::*;
+use crate::evaluator_base::EvaluatorBase;
+use crate::galois::{naf, naf_rotation_steps};
 use crate::serialization::CompressionType;
-use crate::{Context, FromBytes, ToBytes};
+use crate::{galois_elt_from_step, Context, Fingerprint, FromBytes, Plaintext, PrngSeed, ToBytes};
 
 use serde::ser::Error;
 use serde::{Serialize, Serializer};
+use zeroize::{Zeroize, Zeroizing};
 
 /**
  * Generates matching secret key and public key. An existing KeyGenerator can
@@ -59,22 +62,34 @@ impl KeyGenerator {
         Ok(KeyGenerator { handle })
     }
 
+    /**
+     * Creates a KeyGenerator whose secret key, and the randomness of every
+     * key it generates, derive from `seed`. The same seed and sequence of
+     * calls yield byte-identical keys. See [`PrngSeed`].
+     *
+     * * `context` - The context describing the encryption scheme.
+     * * `seed` - The seed. Only use fixed seeds in tests.
+     */
+    pub fn new_seeded(ctx: &Context, seed: &PrngSeed) -> Result<Self> {
+        Self::new(&ctx.with_prng_seed(seed)?)
+    }
+
     /**
      * Returns a copy of the secret key.
      */
-    pub fn secret_key(&self) -> SecretKey {
+    pub fn secret_key(&self) -> Result<SecretKey> {
         let mut handle = null_mut();
 
-        convert_seal_error(unsafe { bindgen::KeyGenerator_SecretKey(self.handle, &mut handle) })
-            .expect("Fatal error in KeyGenerator::secret_key");
+        convert_seal_error(unsafe { bindgen::KeyGenerator_SecretKey(self.handle, &mut handle) })?;
 
-        SecretKey { handle }
+        Ok(SecretKey { handle })
     }
 
     /**
      * Generates and returns a new public key.
      */
-    pub fn create_public_key(&self) -> PublicKey {
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn create_public_key(&self) -> Result<PublicKey> {
         self.create_public_key_internal(false)
     }
 
@@ -86,24 +101,25 @@ impl KeyGenerator {
      * directly and is meant to be serialized for the size reduction to have an
      * impact.
      */
-    pub fn create_compact_public_key(&self) -> CompactPublicKey {
-        CompactPublicKey(self.create_public_key_internal(true))
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn create_compact_public_key(&self) -> Result<CompactPublicKey> {
+        Ok(CompactPublicKey(self.create_public_key_internal(true)?))
     }
 
-    fn create_public_key_internal(&self, save_seed: bool) -> PublicKey {
+    fn create_public_key_internal(&self, save_seed: bool) -> Result<PublicKey> {
         let mut handle = null_mut();
 
         convert_seal_error(unsafe {
             bindgen::KeyGenerator_CreatePublicKey(self.handle, save_seed, &mut handle)
-        })
-        .expect("Fatal error in KeyGenerator::public_key");
+        })?;
 
-        PublicKey { handle }
+        Ok(PublicKey { handle })
     }
 
     /**
      * Creates relinearization keys
      */
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn create_relinearization_keys(&self) -> Result<RelinearizationKeys> {
         self.create_relinearization_keys_internal(false)
     }
@@ -119,6 +135,7 @@ impl KeyGenerator {
      * directly and is meant to be serialized for the size reduction to have an
      * impact.
      */
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn create_compact_relinearization_keys(&self) -> Result<CompactRelinearizationKeys> {
         Ok(CompactRelinearizationKeys(
             self.create_relinearization_keys_internal(true)?,
@@ -151,6 +168,7 @@ impl KeyGenerator {
      * (e.g. rotations) on encrypted data. Most users will want to use this
      * overload of the function.
      */
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn create_compact_galois_keys(&self) -> Result<CompactGaloisKeys> {
         Ok(CompactGaloisKeys(self.create_galois_keys_internal(true)?))
     }
@@ -167,10 +185,43 @@ impl KeyGenerator {
      * (e.g. rotations) on encrypted data. Most users will want to use this
      * overload of the function.
      */
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn create_galois_keys(&self) -> Result<GaloisKeys> {
         self.create_galois_keys_internal(false)
     }
 
+    /**
+     * Generates Galois keys for only the given Galois elements.
+     *
+     * # Remarks
+     * Generating keys for all automorphisms is expensive and produces large
+     * keys. When a program only performs a few rotations, use
+     * [`minimal_rotation_key_steps`](crate::minimal_rotation_key_steps) and
+     * [`galois_elts_from_steps`](crate::galois_elts_from_steps) to compute
+     * the elements it needs.
+     *
+     * * `galois_elts` - The Galois elements for which to generate keys.
+     */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(count = galois_elts.len()))
+    )]
+    pub fn create_galois_keys_from_elts(&self, galois_elts: &[u32]) -> Result<GaloisKeys> {
+        let mut handle = null_mut();
+
+        convert_seal_error(unsafe {
+            bindgen::KeyGenerator_CreateGaloisKeysFromElts(
+                self.handle,
+                galois_elts.len() as u64,
+                galois_elts.as_ptr() as *mut u32,
+                false,
+                &mut handle,
+            )
+        })?;
+
+        Ok(GaloisKeys { handle })
+    }
+
     fn create_galois_keys_internal(&self, save_seed: bool) -> Result<GaloisKeys> {
         let mut handle = null_mut();
 
@@ -200,13 +251,19 @@ unsafe impl Sync for PublicKey {}
 unsafe impl Send for PublicKey {}
 
 impl ToBytes for PublicKey {
-    fn as_bytes(&self) -> Result<Vec<u8>> {
+    fn save_size_with_compression(&self, compression: CompressionType) -> Result<u64> {
         let mut num_bytes: i64 = 0;
 
         convert_seal_error(unsafe {
-            bindgen::PublicKey_SaveSize(self.handle, CompressionType::ZStd as u8, &mut num_bytes)
+            bindgen::PublicKey_SaveSize(self.handle, compression as u8, &mut num_bytes)
         })?;
 
+        Ok(num_bytes as u64)
+    }
+
+    fn as_bytes_with_compression(&self, compression: CompressionType) -> Result<Vec<u8>> {
+        let num_bytes = self.save_size_with_compression(compression)?;
+
         let mut data: Vec<u8> = Vec::with_capacity(num_bytes as usize);
         let mut bytes_written: i64 = 0;
 
@@ -216,8 +273,8 @@ impl ToBytes for PublicKey {
             bindgen::PublicKey_Save(
                 self.handle,
                 data_ptr,
-                num_bytes as u64,
-                CompressionType::ZStd as u8,
+                num_bytes,
+                compression as u8,
                 &mut bytes_written,
             )
         })?;
@@ -254,7 +311,7 @@ impl FromBytes for PublicKey {
 }
 
 impl PublicKey {
-    fn new() -> Result<Self> {
+    pub(crate) fn new() -> Result<Self> {
         let mut handle: *mut c_void = null_mut();
 
         convert_seal_error(unsafe { bindgen::PublicKey_Create1(&mut handle) })?;
@@ -268,6 +325,44 @@ impl PublicKey {
     pub fn get_handle(&self) -> *mut c_void {
         self.handle
     }
+
+    /**
+     * Returns the parms_id of the parameters this key was created under,
+     * which is the context's key level (see
+     * [`Context::get_key_parms_id`]).
+     */
+    pub fn get_parms_id(&self) -> Result<[u64; 4]> {
+        let mut parms_id = [0u64; 4];
+
+        convert_seal_error(unsafe {
+            bindgen::PublicKey_ParmsId(self.handle, parms_id.as_mut_ptr())
+        })?;
+
+        Ok(parms_id)
+    }
+
+    /**
+     * Returns a stable hash of this key. See [`Fingerprint`].
+     */
+    pub fn fingerprint(&self) -> Result<Fingerprint> {
+        Fingerprint::of_object(b"PublicKey", self)
+    }
+
+    /**
+     * Returns whether this key is valid for the given context, i.e. it
+     * was created under the context's parameters and its data is well
+     * formed. Check keys from untrusted or external sources with this
+     * before encrypting with them.
+     */
+    pub fn is_valid_for(&self, context: &Context) -> Result<bool> {
+        let mut valid = false;
+
+        convert_seal_error(unsafe {
+            bindgen::ValCheck_PublicKey_IsValidFor(self.handle, context.get_handle(), &mut valid)
+        })?;
+
+        Ok(valid)
+    }
 }
 
 impl Drop for PublicKey {
@@ -305,6 +400,11 @@ impl CompactPublicKey {
 
 /**
  * Class to store a secret key.
+ *
+ * # Remarks
+ * Dropping a secret key (or calling [`destroy`](Self::destroy)) zeroes
+ * its coefficients before freeing it. SEAL's memory pool also clears
+ * secret key allocations on destruction, but we don't rely on it.
  */
 pub struct SecretKey {
     handle: *mut c_void,
@@ -314,7 +414,7 @@ unsafe impl Sync for SecretKey {}
 unsafe impl Send for SecretKey {}
 
 impl SecretKey {
-    fn new() -> Result<Self> {
+    pub(crate) fn new() -> Result<Self> {
         let mut handle: *mut c_void = null_mut();
 
         convert_seal_error(unsafe { bindgen::SecretKey_Create1(&mut handle) })?;
@@ -322,31 +422,171 @@ impl SecretKey {
         Ok(Self { handle })
     }
 
+    /**
+     * Returns this key, which is for `ctx`, as a key for `level_ctx`, an
+     * auxiliary context [`Context::at_level`] created from `ctx`.
+     *
+     * Returns [`Error::InvalidArgument`] if this key isn't valid for `ctx`
+     * or `level_ctx` isn't a level of `ctx`.
+     */
+    pub fn at_level(&self, ctx: &Context, level_ctx: &Context) -> Result<Self> {
+        let mut handle: *mut c_void = null_mut();
+
+        convert_seal_error(unsafe {
+            bindgen::Shim_SecretKey_CreateAtLevel(
+                ctx.get_handle(),
+                self.handle,
+                level_ctx.get_handle(),
+                &mut handle,
+            )
+        })?;
+
+        Ok(Self { handle })
+    }
+
+    /**
+     * Creates a secret key with the given coefficients, e.g. ones a dealer
+     * sampled and needs in the clear to split the key into shares.
+     *
+     * # Remarks
+     * `coefficients` must hold `poly_modulus_degree` values. SEAL samples
+     * them uniformly from {-1, 0, 1}, and keys from other distributions
+     * may be insecure or leave less noise budget. The key is stored in NTT
+     * form over every prime at the key level, as SEAL expects.
+     *
+     * Returns [`Error::InvalidArgument`] if `coefficients` has the wrong
+     * length.
+     */
+    pub fn from_coefficients(ctx: &Context, coefficients: &[i64]) -> Result<Self> {
+        let parms = ctx.get_encryption_parameters()?;
+
+        if coefficients.len() as u64 != parms.get_poly_modulus_degree() {
+            return Err(Error::InvalidArgument);
+        }
+
+        // Lifting a plaintext maps values above t / 2 to negative ones, so
+        // -1 becomes t - 1 and lifts to q_j - 1 modulo each prime.
+        let t = parms.get_plain_modulus().value() as i64;
+        let mut plain = Zeroizing::new(Plaintext::new()?);
+
+        plain.resize(coefficients.len());
+
+        for (i, c) in coefficients.iter().enumerate() {
+            plain.set_coefficient(i, c.rem_euclid(t) as u64);
+        }
+
+        let evaluator = EvaluatorBase::new(ctx)?;
+        let mut key_parms_id = ctx.get_key_parms_id()?;
+        let ntt = Zeroizing::new(Plaintext::new()?);
+
+        convert_seal_error(unsafe {
+            bindgen::Evaluator_TransformToNTT1(
+                evaluator.get_handle(),
+                plain.get_handle(),
+                key_parms_id.as_mut_ptr(),
+                ntt.get_handle(),
+                null_mut(),
+            )
+        })?;
+
+        let key = Self::new()?;
+
+        convert_seal_error(unsafe { bindgen::Plaintext_Set1(key.data()?, ntt.get_handle()) })?;
+
+        Ok(key)
+    }
+
+    /**
+     * Returns the key's coefficients, centered around 0, undoing
+     * [`from_coefficients`](Self::from_coefficients).
+     *
+     * # Remarks
+     * Anyone holding the coefficients can decrypt, so the result is
+     * zeroized when dropped.
+     *
+     * Returns [`Error::InvalidArgument`] if the key isn't valid for `ctx`.
+     */
+    pub fn coefficients(&self, ctx: &Context) -> Result<Zeroizing<Vec<i64>>> {
+        let parms = ctx.get_encryption_parameters()?;
+        let mut coefficients = Zeroizing::new(vec![0; parms.get_poly_modulus_degree() as usize]);
+
+        convert_seal_error(unsafe {
+            bindgen::Shim_SecretKey_GetCoefficients(
+                ctx.get_handle(),
+                self.handle,
+                coefficients.as_mut_ptr(),
+                coefficients.len() as u64,
+            )
+        })?;
+
+        Ok(coefficients)
+    }
+
     /**
      * Returns the handle to the underlying SEAL object.
      */
     pub fn get_handle(&self) -> *mut c_void {
         self.handle
     }
+
+    /**
+     * Zeroes and frees this secret key, returning any error SEAL reports
+     * rather than panicking as [`Drop`] would.
+     */
+    pub fn destroy(mut self) -> Result<()> {
+        let result = self.free();
+
+        // SEAL has freed the key, even if it reported an error.
+        std::mem::forget(self);
+
+        result
+    }
+
+    /**
+     * Returns a handle to the key's own plaintext. The key owns it, so
+     * callers mustn't free it.
+     */
+    fn data(&self) -> Result<*mut c_void> {
+        let mut data: *mut c_void = null_mut();
+
+        convert_seal_error(unsafe { bindgen::SecretKey_Data(self.handle, &mut data) })?;
+
+        Ok(data)
+    }
+
+    fn free(&mut self) -> Result<()> {
+        self.zeroize();
+
+        convert_seal_error(unsafe { bindgen::SecretKey_Destroy(self.handle) })
+    }
 }
 
 impl PartialEq for SecretKey {
     fn eq(&self, other: &Self) -> bool {
-        self.as_bytes() == other.as_bytes()
+        let a = self.as_bytes().map(Zeroizing::new);
+        let b = other.as_bytes().map(Zeroizing::new);
+
+        a == b
     }
 }
 
 impl ToBytes for SecretKey {
-    /**
-     * Returns the key as a byte array.
-     */
-    fn as_bytes(&self) -> Result<Vec<u8>> {
+    fn save_size_with_compression(&self, compression: CompressionType) -> Result<u64> {
         let mut num_bytes: i64 = 0;
 
         convert_seal_error(unsafe {
-            bindgen::SecretKey_SaveSize(self.handle, CompressionType::ZStd as u8, &mut num_bytes)
+            bindgen::SecretKey_SaveSize(self.handle, compression as u8, &mut num_bytes)
         })?;
 
+        Ok(num_bytes as u64)
+    }
+
+    /**
+     * Returns the key as a byte array.
+     */
+    fn as_bytes_with_compression(&self, compression: CompressionType) -> Result<Vec<u8>> {
+        let num_bytes = self.save_size_with_compression(compression)?;
+
         let mut data: Vec<u8> = Vec::with_capacity(num_bytes as usize);
         let mut bytes_written: i64 = 0;
 
@@ -356,8 +596,8 @@ impl ToBytes for SecretKey {
             bindgen::SecretKey_Save(
                 self.handle,
                 data_ptr,
-                num_bytes as u64,
-                CompressionType::ZStd as u8,
+                num_bytes,
+                compression as u8,
                 &mut bytes_written,
             )
         })?;
@@ -387,10 +627,21 @@ impl FromBytes for SecretKey {
     }
 }
 
+impl Zeroize for SecretKey {
+    /**
+     * Sets every coefficient of this key to zero. The key is unusable
+     * afterwards.
+     */
+    fn zeroize(&mut self) {
+        self.data()
+            .and_then(|data| convert_seal_error(unsafe { bindgen::Plaintext_SetZero1(data) }))
+            .expect("Internal error in SecretKey::zeroize.");
+    }
+}
+
 impl Drop for SecretKey {
     fn drop(&mut self) {
-        convert_seal_error(unsafe { bindgen::SecretKey_Destroy(self.handle) })
-            .expect("Fatal error in SecretKey::drop")
+        self.free().expect("Fatal error in SecretKey::drop")
     }
 }
 
@@ -401,6 +652,7 @@ impl Serialize for SecretKey {
     {
         let data = self
             .as_bytes()
+            .map(Zeroizing::new)
             .map_err(|e| S::Error::custom(format!("Failed to get secret key bytes: {}", e)))?;
 
         serializer.serialize_bytes(&data)
@@ -458,7 +710,7 @@ impl RelinearizationKeys {
         self.handle
     }
 
-    fn new() -> Result<RelinearizationKeys> {
+    pub(crate) fn new() -> Result<RelinearizationKeys> {
         let mut handle: *mut c_void = null_mut();
 
         convert_seal_error(unsafe { bindgen::KSwitchKeys_Create1(&mut handle) })?;
@@ -470,30 +722,14 @@ impl RelinearizationKeys {
      * Returns the key as a byte array.
      */
     pub fn as_bytes(&self) -> Result<Vec<u8>> {
-        let mut num_bytes: i64 = 0;
-
-        convert_seal_error(unsafe {
-            bindgen::KSwitchKeys_SaveSize(self.handle, CompressionType::ZStd as u8, &mut num_bytes)
-        })?;
-
-        let mut data: Vec<u8> = Vec::with_capacity(num_bytes as usize);
-        let mut bytes_written: i64 = 0;
-
-        convert_seal_error(unsafe {
-            let data_ptr = data.as_mut_ptr();
-
-            bindgen::KSwitchKeys_Save(
-                self.handle,
-                data_ptr,
-                num_bytes as u64,
-                CompressionType::ZStd as u8,
-                &mut bytes_written,
-            )
-        })?;
-
-        unsafe { data.set_len(bytes_written as usize) };
+        ToBytes::as_bytes(self)
+    }
 
-        Ok(data)
+    /**
+     * Returns a stable hash of these keys. See [`Fingerprint`].
+     */
+    pub fn fingerprint(&self) -> Result<Fingerprint> {
+        Fingerprint::of_object(b"RelinearizationKeys", self)
     }
 }
 
@@ -504,13 +740,19 @@ impl PartialEq for RelinearizationKeys {
 }
 
 impl ToBytes for RelinearizationKeys {
-    fn as_bytes(&self) -> Result<Vec<u8>> {
+    fn save_size_with_compression(&self, compression: CompressionType) -> Result<u64> {
         let mut num_bytes: i64 = 0;
 
         convert_seal_error(unsafe {
-            bindgen::KSwitchKeys_SaveSize(self.handle, CompressionType::ZStd as u8, &mut num_bytes)
+            bindgen::KSwitchKeys_SaveSize(self.handle, compression as u8, &mut num_bytes)
         })?;
 
+        Ok(num_bytes as u64)
+    }
+
+    fn as_bytes_with_compression(&self, compression: CompressionType) -> Result<Vec<u8>> {
+        let num_bytes = self.save_size_with_compression(compression)?;
+
         let mut data: Vec<u8> = Vec::with_capacity(num_bytes as usize);
         let mut bytes_written: i64 = 0;
 
@@ -520,8 +762,8 @@ impl ToBytes for RelinearizationKeys {
             bindgen::KSwitchKeys_Save(
                 self.handle,
                 data_ptr,
-                num_bytes as u64,
-                CompressionType::ZStd as u8,
+                num_bytes,
+                compression as u8,
                 &mut bytes_written,
             )
         })?;
@@ -622,13 +864,256 @@ impl GaloisKeys {
         self.handle
     }
 
-    fn new() -> Result<GaloisKeys> {
+    pub(crate) fn new() -> Result<GaloisKeys> {
         let mut handle: *mut c_void = null_mut();
 
         convert_seal_error(unsafe { bindgen::KSwitchKeys_Create1(&mut handle) })?;
 
         Ok(Self { handle })
     }
+
+    /**
+     * Returns whether these keys contain a key for the given Galois element.
+     */
+    pub fn has_key(&self, galois_elt: u32) -> Result<bool> {
+        let mut has_key = false;
+
+        convert_seal_error(unsafe {
+            bindgen::GaloisKeys_HasKey(self.handle, galois_elt, &mut has_key)
+        })?;
+
+        Ok(has_key)
+    }
+
+    /**
+     * Returns whether these keys can rotate the rows of a batched ciphertext
+     * by `step` places under the given polynomial degree. As in
+     * [`galois_elt_from_step`], a `step` of 0 denotes a column rotation.
+     *
+     * # Remarks
+     * When there's no key for `step` itself, SEAL performs the rotation as a
+     * sequence of power-of-two rotations in either direction, which requires
+     * a key for each of them.
+     */
+    pub fn can_rotate_rows(&self, step: i32, poly_degree: u64) -> Result<bool> {
+        if self.has_key(galois_elt_from_step(step, poly_degree)?)? {
+            return Ok(true);
+        }
+
+        // A single term means `step` is a power of two, which SEAL
+        // doesn't decompose further.
+        if step == 0 || naf(step).len() == 1 {
+            return Ok(false);
+        }
+
+        for s in naf_rotation_steps(step, poly_degree) {
+            if !self.can_rotate_rows(s, poly_degree)? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /**
+     * Combines several sets of Galois keys into one containing every key
+     * in any of them. This lets callers generate disjoint subsets of the
+     * keys in parallel.
+     *
+     * # Remarks
+     * All the sets must come from the same [`KeyGenerator`]; keys for the
+     * same Galois element are taken from the first set containing one.
+     * Returns [`Error::InvalidArgument`] if `keys` is empty or the sets
+     * were generated under different encryption parameters.
+     */
+    pub fn merge(keys: &[GaloisKeys]) -> Result<GaloisKeys> {
+        let first = keys.first().ok_or(Error::InvalidArgument)?;
+
+        let parms_id = Self::parms_id(first)?;
+        let mut size = 0u64;
+
+        for k in keys {
+            if Self::parms_id(k)? != parms_id {
+                return Err(Error::InvalidArgument);
+            }
+
+            size = size.max(k.raw_size()?);
+        }
+
+        let merged = Self::new()?;
+
+        convert_seal_error(unsafe {
+            bindgen::KSwitchKeys_ClearDataAndReserve(merged.handle, size)
+        })?;
+
+        // SEAL stores the key for each Galois element at a fixed index,
+        // leaving the indices of absent elements empty.
+        for index in 0..size {
+            let mut key_list: Vec<*mut c_void> = vec![];
+
+            for k in keys {
+                if index < k.raw_size()? {
+                    key_list = k.key_list(index)?;
+                }
+
+                if !key_list.is_empty() {
+                    break;
+                }
+            }
+
+            merged.add_key_list(&mut key_list)?;
+        }
+
+        merged.set_parms_id(parms_id)?;
+
+        Ok(merged)
+    }
+
+    /**
+     * Returns a stable hash of these keys. See [`Fingerprint`].
+     */
+    pub fn fingerprint(&self) -> Result<Fingerprint> {
+        Fingerprint::of_object(b"GaloisKeys", self)
+    }
+
+    /**
+     * Returns whether these keys are valid for the given context, i.e.
+     * they were created under the context's parameters and their data is
+     * well formed. Check keys from untrusted or external sources with this
+     * before rotating with them.
+     */
+    pub fn is_valid_for(&self, context: &Context) -> Result<bool> {
+        let mut valid = false;
+
+        convert_seal_error(unsafe {
+            bindgen::ValCheck_GaloisKeys_IsValidFor(self.handle, context.get_handle(), &mut valid)
+        })?;
+
+        Ok(valid)
+    }
+
+    /**
+     * Returns the Galois elements these keys contain a key for, in
+     * ascending order.
+     */
+    pub fn galois_elts(&self) -> Result<Vec<u32>> {
+        let mut elts = vec![];
+
+        for index in 0..self.raw_size()? {
+            if !self.key_list(index)?.is_empty() {
+                elts.push((2 * index + 1) as u32);
+            }
+        }
+
+        Ok(elts)
+    }
+
+    /**
+     * Returns a copy of just the keys for the given Galois elements, e.g.
+     * to store or send only the keys a computation needs.
+     *
+     * Returns [`Error::InvalidArgument`] if `galois_elts` is empty or
+     * these keys lack any of them.
+     */
+    pub fn subset(&self, galois_elts: &[u32]) -> Result<GaloisKeys> {
+        for elt in galois_elts {
+            if !self.has_key(*elt)? {
+                return Err(Error::InvalidArgument);
+            }
+        }
+
+        let size = galois_elts
+            .iter()
+            .map(|elt| (*elt as u64 >> 1) + 1)
+            .max()
+            .ok_or(Error::InvalidArgument)?;
+
+        let subset = Self::new()?;
+
+        convert_seal_error(unsafe {
+            bindgen::KSwitchKeys_ClearDataAndReserve(subset.handle, size)
+        })?;
+
+        for index in 0..size {
+            let mut key_list = if galois_elts.contains(&((2 * index + 1) as u32)) {
+                self.key_list(index)?
+            } else {
+                vec![]
+            };
+
+            subset.add_key_list(&mut key_list)?;
+        }
+
+        subset.set_parms_id(Self::parms_id(self)?)?;
+
+        Ok(subset)
+    }
+
+    /**
+     * The number of key list slots, one per odd Galois element up to the
+     * largest one these keys were created with.
+     */
+    fn raw_size(&self) -> Result<u64> {
+        let mut raw_size = 0u64;
+
+        convert_seal_error(unsafe { bindgen::KSwitchKeys_RawSize(self.handle, &mut raw_size) })?;
+
+        Ok(raw_size)
+    }
+
+    /**
+     * Returns the keys at the given slot, which is empty if there's no key
+     * for its Galois element. The pointers point into `self` and are
+     * copied by [`add_key_list`](Self::add_key_list).
+     */
+    fn key_list(&self, index: u64) -> Result<Vec<*mut c_void>> {
+        let mut count = 0u64;
+
+        convert_seal_error(unsafe {
+            bindgen::KSwitchKeys_GetKeyList(self.handle, index, &mut count, null_mut())
+        })?;
+
+        let mut key_list = vec![null_mut(); count as usize];
+
+        if count > 0 {
+            convert_seal_error(unsafe {
+                bindgen::KSwitchKeys_GetKeyList(
+                    self.handle,
+                    index,
+                    &mut count,
+                    key_list.as_mut_ptr(),
+                )
+            })?;
+        }
+
+        Ok(key_list)
+    }
+
+    fn add_key_list(&self, key_list: &mut [*mut c_void]) -> Result<()> {
+        convert_seal_error(unsafe {
+            bindgen::KSwitchKeys_AddKeyList(
+                self.handle,
+                key_list.len() as u64,
+                key_list.as_mut_ptr(),
+            )
+        })
+    }
+
+    fn set_parms_id(&self, mut parms_id: [u64; 4]) -> Result<()> {
+        convert_seal_error(unsafe {
+            bindgen::KSwitchKeys_SetParmsId(self.handle, parms_id.as_mut_ptr())
+        })
+    }
+
+    fn parms_id(keys: &GaloisKeys) -> Result<[u64; 4]> {
+        let mut parms_id = [0u64; 4];
+
+        convert_seal_error(unsafe {
+            bindgen::KSwitchKeys_GetParmsId(keys.handle, parms_id.as_mut_ptr())
+        })?;
+
+        Ok(parms_id)
+    }
 }
 
 impl PartialEq for GaloisKeys {
@@ -638,13 +1123,19 @@ impl PartialEq for GaloisKeys {
 }
 
 impl ToBytes for GaloisKeys {
-    fn as_bytes(&self) -> Result<Vec<u8>> {
+    fn save_size_with_compression(&self, compression: CompressionType) -> Result<u64> {
         let mut num_bytes: i64 = 0;
 
         convert_seal_error(unsafe {
-            bindgen::KSwitchKeys_SaveSize(self.handle, CompressionType::ZStd as u8, &mut num_bytes)
+            bindgen::KSwitchKeys_SaveSize(self.handle, compression as u8, &mut num_bytes)
         })?;
 
+        Ok(num_bytes as u64)
+    }
+
+    fn as_bytes_with_compression(&self, compression: CompressionType) -> Result<Vec<u8>> {
+        let num_bytes = self.save_size_with_compression(compression)?;
+
         let mut data: Vec<u8> = Vec::with_capacity(num_bytes as usize);
         let mut bytes_written: i64 = 0;
 
@@ -654,8 +1145,8 @@ impl ToBytes for GaloisKeys {
             bindgen::KSwitchKeys_Save(
                 self.handle,
                 data_ptr,
-                num_bytes as u64,
-                CompressionType::ZStd as u8,
+                num_bytes,
+                compression as u8,
                 &mut bytes_written,
             )
         })?;
@@ -731,6 +1222,7 @@ impl CompactGaloisKeys {
 #[cfg(test)]
 mod tests {
     use crate::*;
+    use zeroize::Zeroize;
 
     #[test]
     fn can_create_secret_key() {
@@ -746,11 +1238,11 @@ mod tests {
         let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
         let gen = KeyGenerator::new(&ctx).unwrap();
 
-        let secret_key = gen.secret_key();
+        let secret_key = gen.secret_key().unwrap();
 
         let gen = KeyGenerator::new(&ctx).unwrap();
 
-        let secret_key_2 = gen.secret_key();
+        let secret_key_2 = gen.secret_key().unwrap();
 
         // Different generators should give different keys.
         assert_ne!(
@@ -759,6 +1251,124 @@ mod tests {
         );
     }
 
+    #[test]
+    fn can_destroy_secret_key() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus_u64(1234)
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+        let gen = KeyGenerator::new(&ctx).unwrap();
+
+        let secret_key = gen.secret_key().unwrap();
+        let copy = secret_key.clone();
+
+        assert!(secret_key == copy);
+
+        secret_key.destroy().unwrap();
+
+        // Destroying one copy leaves the others intact.
+        assert!(copy == gen.secret_key().unwrap());
+
+        copy.destroy().unwrap();
+    }
+
+    #[test]
+    fn can_zeroize_secret_key() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus_u64(1234)
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+        let gen = KeyGenerator::new(&ctx).unwrap();
+
+        let mut secret_key = gen.secret_key().unwrap();
+        let mut zero_key = gen.secret_key().unwrap();
+
+        secret_key.zeroize();
+        zero_key.zeroize();
+
+        assert!(secret_key != gen.secret_key().unwrap());
+        assert!(secret_key == zero_key);
+    }
+
+    #[test]
+    fn secret_key_coefficients_round_trip() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus_u64(1234)
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+
+        let coefficients = (0..8192).map(|i| i % 3 - 1).collect::<Vec<i64>>();
+        let secret_key = SecretKey::from_coefficients(&ctx, &coefficients).unwrap();
+
+        assert_eq!(*secret_key.coefficients(&ctx).unwrap(), coefficients);
+
+        let gen = KeyGenerator::new(&ctx).unwrap();
+        let sampled = gen.secret_key().unwrap().coefficients(&ctx).unwrap();
+
+        assert!(sampled.iter().all(|c| (-1..=1).contains(c)));
+    }
+
+    #[test]
+    fn seeded_key_generators_are_deterministic() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus_u64(1234)
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, true, SecurityLevel::TC128).unwrap();
+
+        let gen = KeyGenerator::new_seeded(&ctx, &PrngSeed::from_u64(42)).unwrap();
+        let gen_2 = KeyGenerator::new_seeded(&ctx, &PrngSeed::from_u64(42)).unwrap();
+        let gen_3 = KeyGenerator::new_seeded(&ctx, &PrngSeed::from_u64(43)).unwrap();
+
+        assert_eq!(
+            gen.secret_key().unwrap().as_bytes().unwrap(),
+            gen_2.secret_key().unwrap().as_bytes().unwrap()
+        );
+        assert_ne!(
+            gen.secret_key().unwrap().as_bytes().unwrap(),
+            gen_3.secret_key().unwrap().as_bytes().unwrap()
+        );
+
+        let public_key = gen.create_public_key().unwrap();
+
+        assert_eq!(
+            public_key.as_bytes().unwrap(),
+            gen_2.create_public_key().unwrap().as_bytes().unwrap()
+        );
+
+        // Each key gets fresh randomness.
+        assert_ne!(
+            public_key.as_bytes().unwrap(),
+            gen.create_public_key().unwrap().as_bytes().unwrap()
+        );
+
+        // Seeded keys work with the original context.
+        Encryptor::with_public_key(&ctx, &public_key).unwrap();
+    }
+
     #[test]
     fn can_create_public_key() {
         let params = BfvEncryptionParametersBuilder::new()
@@ -773,7 +1383,7 @@ mod tests {
         let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
         let gen = KeyGenerator::new(&ctx).unwrap();
 
-        gen.create_public_key();
+        gen.create_public_key().unwrap();
     }
 
     #[test]
@@ -810,6 +1420,105 @@ mod tests {
         gen.create_galois_keys().unwrap();
     }
 
+    #[test]
+    fn can_merge_galois_keys() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::bfv_default(8192, SecurityLevel::TC128).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 32).unwrap())
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+        let gen = KeyGenerator::new(&ctx).unwrap();
+
+        let left = galois_elts_from_steps(&[1, 4], 8192).unwrap();
+        let right = galois_elts_from_steps(&[0, 4, 100], 8192).unwrap();
+
+        let keys = GaloisKeys::merge(&[
+            gen.create_galois_keys_from_elts(&left).unwrap(),
+            gen.create_galois_keys_from_elts(&right).unwrap(),
+        ])
+        .unwrap();
+
+        for elt in left.iter().chain(right.iter()) {
+            assert!(keys.has_key(*elt).unwrap());
+        }
+
+        assert!(!keys
+            .has_key(galois_elt_from_step(2, 8192).unwrap())
+            .unwrap());
+        assert!(keys.can_rotate_rows(5, 8192).unwrap());
+
+        // The merged keys must still rotate correctly.
+        let encoder = BFVEncoder::new(&ctx).unwrap();
+        let encryptor =
+            Encryptor::with_public_key(&ctx, &gen.create_public_key().unwrap()).unwrap();
+        let decryptor = Decryptor::new(&ctx, &gen.secret_key().unwrap()).unwrap();
+        let evaluator = BFVEvaluator::new(&ctx).unwrap();
+
+        let data = (0..8192).collect::<Vec<u64>>();
+        let ciphertext = encryptor
+            .encrypt(&encoder.encode_unsigned(&data).unwrap())
+            .unwrap();
+
+        let rotated = evaluator.rotate_rows(&ciphertext, 100, &keys).unwrap();
+        let rotated = encoder
+            .decode_unsigned(&decryptor.decrypt(&rotated).unwrap())
+            .unwrap();
+
+        assert_eq!(rotated[0], 100);
+        assert_eq!(rotated[4095], 99);
+
+        assert!(matches!(
+            GaloisKeys::merge(&[]),
+            Err(Error::InvalidArgument)
+        ));
+    }
+
+    #[test]
+    fn save_size_bounds_serialized_size() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::bfv_default(8192, SecurityLevel::TC128).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 32).unwrap())
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+        let gen = KeyGenerator::new(&ctx).unwrap();
+
+        fn check<T: ToBytes>(x: &T) -> u64 {
+            let size = x.save_size().unwrap();
+
+            assert!(size >= x.as_bytes().unwrap().len() as u64);
+
+            size
+        }
+
+        let public_key = gen.create_public_key().unwrap();
+
+        check(&gen.secret_key().unwrap());
+        check(&public_key);
+        check(&gen.create_relinearization_keys().unwrap());
+        let galois_size = check(&gen.create_galois_keys().unwrap());
+
+        let encryptor = Encryptor::with_public_key(&ctx, &public_key).unwrap();
+        let ciphertext = encryptor
+            .encrypt(&BFVScalarEncoder::new().encode_unsigned(7).unwrap())
+            .unwrap();
+
+        let ciphertext_size = check(&ciphertext);
+
+        // Galois keys hold many key switching keys, each far larger than a
+        // ciphertext.
+        assert!(galois_size > 10 * ciphertext_size);
+    }
+
     #[test]
     fn can_init_from_existing_secret_key() {
         let params = BfvEncryptionParametersBuilder::new()
@@ -824,11 +1533,11 @@ mod tests {
         let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
         let gen = KeyGenerator::new(&ctx).unwrap();
 
-        let secret_key = gen.secret_key();
+        let secret_key = gen.secret_key().unwrap();
 
         let gen = KeyGenerator::new_from_secret_key(&ctx, &secret_key).unwrap();
 
-        let secret_key_2 = gen.secret_key();
+        let secret_key_2 = gen.secret_key().unwrap();
 
         // Since we used the secret key from the first generator for the second,
         // we should get the same key.
@@ -8,6 +8,45 @@
 //! internal handle on any type after it has been dropped, these traits
 //! should safely hold. The internal handles should be of little use to you anyways.
 //!
+//! # Thread safety
+//! SEAL allocates from a global, thread-safe memory pool unless told otherwise and
+//! the operations this crate exposes on [`Context`], [`Encryptor`], [`Decryptor`],
+//! [`KeyGenerator`], the encoders and [`Evaluator`] implementations are `const` in
+//! SEAL. As such, you may share a single instance of each of these across threads
+//! (e.g. in an `Arc`) without wrapping it in a lock.
+//!
+//! Operations that modify a [`Ciphertext`] or [`Plaintext`] (i.e. the `*_inplace`
+//! evaluator methods) take a mutable reference, so the borrow checker prevents
+//! concurrent access to the operand.
+//!
+//! # Features
+//! * `hardened`: [`BFVEncoder::decode_signed`] maps slots to signed values without
+//!   branching on them and zeroes its intermediate buffers. Decryption and other
+//!   secret key operations run in SEAL unchanged. Secret keys and plaintexts are
+//!   zeroed on drop regardless.
+//! * `zlib`, `zstd` (default): enable the corresponding [`CompressionType`]s.
+//! * `hexl`: accelerates SEAL with Intel HEXL.
+//! * `tracing`: emits a debug-level [tracing](https://docs.rs/tracing) span
+//!   around each call into SEAL that encodes, encrypts, decrypts, evaluates or
+//!   generates keys. Spans are named after the method and record the sizes
+//!   (number of polynomials) of ciphertext operands, so a subscriber such as
+//!   `tracing-opentelemetry` can report per-operation durations.
+//! * `failure-injection`: enables [`failure_injection`], which makes a chosen
+//!   call into SEAL fail so tests can exercise error handling. Never enable
+//!   it outside of tests.
+//! * `bench-suite`: enables a [criterion](https://docs.rs/criterion) benchmark
+//!   timing encoding, encryption, addition, multiplication, relinearization
+//!   and rotation at several polynomial degrees. Run it with
+//!   `cargo bench -p seal_fhe --features bench-suite` on the machine and build
+//!   (e.g. with and without `hexl`) you want numbers for.
+//!
+//! # WebAssembly
+//! This crate builds for `wasm32-unknown-emscripten`, which runs in browsers and
+//! NodeJS. Emscripten supplies the C++ runtime SEAL needs, so other WASM targets
+//! (e.g. `wasm32-unknown-unknown`) fail to build with an explanatory error. SEAL
+//! is built without threads, so pages needn't be cross-origin isolated. Use
+//! [`Error::code`] to pass errors across the JavaScript boundary.
+//!
 //! This crate intentionally omits more esoteric use cases to streamline the API and
 //! is currently incomplete (e.g. CKKS is not currently supported). If any underlying
 //! SEAL API you care about is missing, please add it in a pull request or file
@@ -23,8 +62,14 @@ extern crate link_cplusplus;
 mod bindgen {
     use std::os::raw::c_long;
 
+    #[cfg(not(feature = "failure-injection"))]
     include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
 
+    // The same bindings, but checking for an injected failure before
+    // calling into SEAL.
+    #[cfg(feature = "failure-injection")]
+    include!(concat!(env!("OUT_DIR"), "/failing_bindings.rs"));
+
     pub const E_OK: c_long = 0x0;
     pub const E_POINTER: c_long = 0x80004003u32 as c_long;
     pub const E_INVALIDARG: c_long = 0x80070057u32 as c_long;
@@ -34,16 +79,8 @@ mod bindgen {
     pub const COR_E_INVALIDOPERATION: c_long = 0x80131509u32 as c_long;
 }
 
-mod serialization {
-    #[repr(u8)]
-    pub enum CompressionType {
-        // None = 0,
-        // ZLib = 1,
-        ZStd = 2,
-    }
-}
-
 mod bfv_evaluator;
+mod chunked;
 mod context;
 mod encoder;
 mod encryption_parameters;
@@ -51,29 +88,115 @@ mod encryptor_decryptor;
 mod error;
 mod evaluator;
 mod evaluator_base;
+#[cfg(feature = "failure-injection")]
+pub mod failure_injection;
+mod fingerprint;
+mod galois;
+mod galois_index;
 mod key_generator;
+mod linalg;
+mod memory;
 mod modulus;
+mod operators;
+mod parameter_selector;
 mod plaintext_ciphertext;
+mod pool;
+mod profiling;
+mod recording;
+mod reduction_tree;
+pub mod rns;
+mod serialization;
+mod staging;
+mod stream;
+
+use static_assertions::assert_impl_all;
 
 pub use bfv_evaluator::BFVEvaluator;
-pub use context::Context;
-pub use encoder::{BFVEncoder, BFVScalarEncoder};
+pub use chunked::{Chunked, ChunkedEncoder};
+pub use context::{Context, ContextBuilder, ContextWarning, PrngSeed};
+pub use encoder::{BFVEncoder, BFVScalarEncoder, Padding, Reduction};
 pub use encryption_parameters::*;
 pub use encryptor_decryptor::{Decryptor, Encryptor};
-pub use error::{Error, Result};
+pub use error::{last_error, Error, ErrorContext, OperationError, ParameterError, Result};
 pub use evaluator::Evaluator;
+pub use fingerprint::Fingerprint;
+pub use galois::*;
+pub use galois_index::GaloisKeyIndex;
 pub use key_generator::{GaloisKeys, KeyGenerator, PublicKey, RelinearizationKeys, SecretKey};
+pub use linalg::LinearAlgebra;
+pub use memory::{global_memory_pool_stats, MemoryPoolStats, MemoryUsage};
 pub use modulus::{CoefficientModulus, Modulus, PlainModulus, SecurityLevel};
-pub use plaintext_ciphertext::{Ciphertext, Plaintext};
+pub use operators::with_evaluator;
+pub use parameter_selector::ParameterSelector;
+pub use plaintext_ciphertext::{Ciphertext, CiphertextView, Plaintext};
+pub use pool::{CiphertextPool, PlaintextPool, Pool, Reusable};
+pub use profiling::{Operation, OperationProfile, ProfileReport, ProfilingEvaluator};
+pub use recording::{RecordedOp, RecordedValue, Recording, RecordingEvaluator, ValueId};
+pub use reduction_tree::ReductionTree;
+pub use serialization::{serialized_parms_id, CompressionType};
+pub use staging::EncodingBuffer;
+pub use stream::{FromReader, ToWriter};
+
+assert_impl_all!(BFVEvaluator: Send, Sync);
+assert_impl_all!(ProfilingEvaluator<BFVEvaluator>: Send, Sync);
+assert_impl_all!(RecordingEvaluator<BFVEvaluator>: Send, Sync);
+assert_impl_all!(Context: Send, Sync);
+assert_impl_all!(Pool<Ciphertext>: Send, Sync);
+assert_impl_all!(BFVEncoder: Send, Sync);
+assert_impl_all!(EncryptionParameters: Send, Sync);
+assert_impl_all!(Encryptor: Send, Sync);
+assert_impl_all!(Decryptor: Send, Sync);
+assert_impl_all!(KeyGenerator: Send, Sync);
+assert_impl_all!(PublicKey: Send, Sync);
+assert_impl_all!(SecretKey: Send, Sync);
+assert_impl_all!(RelinearizationKeys: Send, Sync);
+assert_impl_all!(GaloisKeys: Send, Sync);
+assert_impl_all!(Modulus: Send, Sync);
+assert_impl_all!(Ciphertext: Send, Sync);
+assert_impl_all!(Plaintext: Send, Sync);
 
 /**
  * A trait for converting objects into byte arrays.
  */
 pub trait ToBytes {
     /**
-     * Returns the object as a byte array.
+     * Returns an upper bound on the length of [`as_bytes`](Self::as_bytes)'s
+     * output without serializing the object.
+     *
+     * # Remarks
+     * SEAL computes this from the object's dimensions, so it's cheap even
+     * for large Galois keys. Use it to preallocate buffers or to reject
+     * payloads over a size limit before doing the serialization work.
+     * Compression usually makes the actual output smaller.
+     */
+    fn save_size(&self) -> Result<u64> {
+        self.save_size_with_compression(CompressionType::default())
+    }
+
+    /**
+     * Returns an upper bound on the length of
+     * [`as_bytes_with_compression`](Self::as_bytes_with_compression)'s
+     * output for the given compression. See [`save_size`](Self::save_size).
+     */
+    fn save_size_with_compression(&self, compression: CompressionType) -> Result<u64>;
+
+    /**
+     * Returns the object as a byte array, compressed with
+     * [`CompressionType::default`].
+     */
+    fn as_bytes(&self) -> Result<Vec<u8>> {
+        self.as_bytes_with_compression(CompressionType::default())
+    }
+
+    /**
+     * Returns the object as a byte array compressed with the given
+     * compression.
+     *
+     * # Remarks
+     * Fails if this build doesn't support the compression (see
+     * [`CompressionType::is_supported`]).
      */
-    fn as_bytes(&self) -> Result<Vec<u8>>;
+    fn as_bytes_with_compression(&self, compression: CompressionType) -> Result<Vec<u8>>;
 }
 
 /**
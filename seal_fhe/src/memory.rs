@@ -0,0 +1,162 @@
+use std::ffi::c_void;
+use std::ptr::null_mut;
+
+use crate::bindgen;
+use crate::error::*;
+use crate::{
+    Ciphertext, CompressionType, GaloisKeys, Plaintext, PublicKey, RelinearizationKeys, SecretKey,
+    ToBytes,
+};
+
+/**
+ * Reports the approximate number of heap bytes an object holds, e.g. to
+ * enforce per-tenant memory quotas or plan capacity.
+ *
+ * # Remarks
+ * Ciphertexts and plaintexts report the capacity of their coefficient
+ * buffers. Keys report the size of their uncompressed serialization,
+ * which exceeds the coefficients they hold by a small, constant header.
+ * Neither includes SEAL's bookkeeping, which is negligible in
+ * comparison.
+ */
+pub trait MemoryUsage {
+    /**
+     * Returns the approximate number of heap bytes this object holds.
+     */
+    fn heap_size(&self) -> Result<u64>;
+}
+
+impl MemoryUsage for Ciphertext {
+    fn heap_size(&self) -> Result<u64> {
+        let mut size_capacity: u64 = 0;
+        let mut degree: u64 = 0;
+
+        convert_seal_error(unsafe {
+            bindgen::Ciphertext_SizeCapacity(self.get_handle(), &mut size_capacity)
+        })?;
+        convert_seal_error(unsafe {
+            bindgen::Ciphertext_PolyModulusDegree(self.get_handle(), &mut degree)
+        })?;
+
+        let coefficients = size_capacity * degree * self.coeff_modulus_size();
+
+        Ok(coefficients * std::mem::size_of::<u64>() as u64)
+    }
+}
+
+impl MemoryUsage for Plaintext {
+    fn heap_size(&self) -> Result<u64> {
+        let mut capacity: u64 = 0;
+
+        convert_seal_error(unsafe {
+            bindgen::Plaintext_Capacity(self.get_handle(), &mut capacity)
+        })?;
+
+        Ok(capacity * std::mem::size_of::<u64>() as u64)
+    }
+}
+
+macro_rules! impl_key_memory_usage {
+    ($ty:ty) => {
+        impl MemoryUsage for $ty {
+            fn heap_size(&self) -> Result<u64> {
+                self.save_size_with_compression(CompressionType::None)
+            }
+        }
+    };
+}
+
+impl_key_memory_usage!(PublicKey);
+impl_key_memory_usage!(SecretKey);
+impl_key_memory_usage!(RelinearizationKeys);
+impl_key_memory_usage!(GaloisKeys);
+
+/**
+ * Statistics about SEAL's global memory pool, which every operation in
+ * this crate allocates temporaries and results from.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryPoolStats {
+    /**
+     * The number of bytes the pool has allocated from the system.
+     *
+     * # Remarks
+     * The pool keeps freed allocations to reuse them, so this is the
+     * peak amount the process has needed at once rather than what's in
+     * use right now. It only shrinks when the process exits.
+     */
+    pub allocated_bytes: u64,
+
+    /**
+     * The number of size classes the pool has allocated for.
+     */
+    pub pool_count: u64,
+}
+
+/**
+ * Returns statistics about SEAL's global memory pool.
+ */
+pub fn global_memory_pool_stats() -> Result<MemoryPoolStats> {
+    let mut handle: *mut c_void = null_mut();
+
+    convert_seal_error(unsafe { bindgen::MemoryPoolHandle_Global(&mut handle) })?;
+
+    let mut allocated_bytes: u64 = 0;
+    let mut pool_count: u64 = 0;
+
+    let stats = convert_seal_error(unsafe {
+        bindgen::MemoryPoolHandle_AllocByteCount(handle, &mut allocated_bytes)
+    })
+    .and_then(|_| {
+        convert_seal_error(unsafe { bindgen::MemoryPoolHandle_PoolCount(handle, &mut pool_count) })
+    });
+
+    // The handle is a new reference to the global pool, which outlives it.
+    convert_seal_error(unsafe { bindgen::MemoryPoolHandle_Destroy(handle) })?;
+    stats?;
+
+    Ok(MemoryPoolStats {
+        allocated_bytes,
+        pool_count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn can_measure_memory_usage() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 20).unwrap())
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+        let gen = KeyGenerator::new(&ctx).unwrap();
+        let encoder = BFVEncoder::new(&ctx).unwrap();
+        let public_key = gen.create_public_key().unwrap();
+        let encryptor = Encryptor::with_public_key(&ctx, &public_key).unwrap();
+
+        let plaintext = encoder.encode_unsigned(&[1, 2, 3]).unwrap();
+        let ciphertext = encryptor.encrypt(&plaintext).unwrap();
+
+        // Fresh ciphertexts are at the first level, which drops the
+        // special prime: 2 polynomials * 8192 coefficients * 4 primes.
+        assert_eq!(ciphertext.heap_size().unwrap(), 2 * 8192 * 4 * 8);
+        assert!(plaintext.heap_size().unwrap() >= 8192 * 8);
+
+        // Public keys hold a ciphertext at the key level, under all 5 primes.
+        assert!(public_key.heap_size().unwrap() >= 2 * 8192 * 5 * 8);
+
+        let stats = global_memory_pool_stats().unwrap();
+
+        assert!(stats.allocated_bytes > 0);
+        assert!(stats.pool_count > 0);
+    }
+}
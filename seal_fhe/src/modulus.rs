@@ -234,13 +234,25 @@ impl CoefficientModulus {
      * to the HomomorphicEncryption.org security standard.
      */
     pub fn max_bit_count(degree: u64, security_level: SecurityLevel) -> u32 {
+        Self::try_max_bit_count(degree, security_level)
+            .expect("Degree isn't supported at the given security level.")
+    }
+
+    /**
+     * Like [`max_bit_count`](Self::max_bit_count), but returns [`None`]
+     * rather than panicking if the HomomorphicEncryption.org standard
+     * doesn't define a bound for the given degree and security level.
+     */
+    pub fn try_max_bit_count(degree: u64, security_level: SecurityLevel) -> Option<u32> {
         let mut bits: i32 = 0;
 
         unsafe { bindgen::CoeffModulus_MaxBitCount(degree, security_level as i32, &mut bits) };
 
-        assert!(bits > 0);
-
-        bits as u32
+        if bits > 0 {
+            Some(bits as u32)
+        } else {
+            None
+        }
     }
 }
 
@@ -0,0 +1,224 @@
+//! Arithmetic operators on [`Ciphertext`], evaluated with the evaluator
+//! installed by [`with_evaluator`].
+
+use std::cell::RefCell;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use crate::{Ciphertext, Error, Evaluator, Plaintext, Result};
+
+struct Scope {
+    evaluator: *const (dyn Evaluator + 'static),
+    error: Option<Error>,
+}
+
+thread_local! {
+    static CURRENT_EVALUATOR: RefCell<Option<Scope>> = RefCell::new(None);
+}
+
+/**
+ * Restores the enclosing scope (if any) when a [`with_evaluator`] call
+ * returns or unwinds.
+ */
+struct ScopeGuard(Option<Scope>);
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        CURRENT_EVALUATOR.with(|s| *s.borrow_mut() = self.0.take());
+    }
+}
+
+/**
+ * Runs `f` with `evaluator` as this thread's evaluator for the `+`, `-`,
+ * `*` and unary `-` operators on [`Ciphertext`].
+ *
+ * ```ignore
+ * let d = with_evaluator(&evaluator, || &a + &b * &c)?;
+ * ```
+ *
+ * # Remarks
+ * Operators can't return a [`Result`], so the first failing operation
+ * is remembered and returned by `with_evaluator` once `f` finishes. Any
+ * operation after the failure is skipped and yields a placeholder copy
+ * of its left operand; discard whatever `f` computed in that case.
+ *
+ * Multiplying ciphertexts doesn't relinearize. Call
+ * [`Evaluator::relinearize`] on the result as usual.
+ *
+ * Calls may be nested; the innermost evaluator is used. The operators
+ * panic when used outside of any `with_evaluator` call.
+ */
+pub fn with_evaluator<E, F, R>(evaluator: &E, f: F) -> Result<R>
+where
+    E: Evaluator,
+    F: FnOnce() -> R,
+{
+    let evaluator: *const (dyn Evaluator + '_) = evaluator;
+
+    // Transmute away the lifetime to 'static. This is sound because the
+    // guard below uninstalls the evaluator before this function returns
+    // or unwinds, so it's never dereferenced after the borrow ends.
+    let evaluator = unsafe {
+        std::mem::transmute::<*const (dyn Evaluator + '_), *const (dyn Evaluator + 'static)>(
+            evaluator,
+        )
+    };
+
+    let previous = CURRENT_EVALUATOR.with(|s| {
+        s.replace(Some(Scope {
+            evaluator,
+            error: None,
+        }))
+    });
+
+    let guard = ScopeGuard(previous);
+
+    let result = f();
+
+    let error = CURRENT_EVALUATOR.with(|s| s.borrow_mut().as_mut().and_then(|s| s.error.take()));
+
+    drop(guard);
+
+    match error {
+        Some(e) => Err(e),
+        None => Ok(result),
+    }
+}
+
+fn apply<F>(lhs: &Ciphertext, op: F) -> Ciphertext
+where
+    F: FnOnce(&dyn Evaluator) -> Result<Ciphertext>,
+{
+    CURRENT_EVALUATOR.with(|s| {
+        let mut scope = s.borrow_mut();
+        let scope = scope
+            .as_mut()
+            .expect("Ciphertext operators can only be used inside with_evaluator");
+
+        if scope.error.is_some() {
+            return lhs.clone();
+        }
+
+        // with_evaluator guarantees the evaluator outlives the scope.
+        let evaluator = unsafe { &*scope.evaluator };
+
+        match op(evaluator) {
+            Ok(c) => c,
+            Err(e) => {
+                scope.error = Some(e);
+                lhs.clone()
+            }
+        }
+    })
+}
+
+macro_rules! impl_binary_op {
+    ($trait:ident, $fn:ident, $method:ident, $rhs:ty) => {
+        impl $trait<&$rhs> for &Ciphertext {
+            type Output = Ciphertext;
+
+            fn $fn(self, rhs: &$rhs) -> Ciphertext {
+                apply(self, |e| e.$method(self, rhs))
+            }
+        }
+
+        impl $trait<&$rhs> for Ciphertext {
+            type Output = Ciphertext;
+
+            fn $fn(self, rhs: &$rhs) -> Ciphertext {
+                (&self).$fn(rhs)
+            }
+        }
+
+        impl $trait<$rhs> for &Ciphertext {
+            type Output = Ciphertext;
+
+            fn $fn(self, rhs: $rhs) -> Ciphertext {
+                self.$fn(&rhs)
+            }
+        }
+
+        impl $trait<$rhs> for Ciphertext {
+            type Output = Ciphertext;
+
+            fn $fn(self, rhs: $rhs) -> Ciphertext {
+                (&self).$fn(&rhs)
+            }
+        }
+    };
+}
+
+impl_binary_op!(Add, add, add, Ciphertext);
+impl_binary_op!(Sub, sub, sub, Ciphertext);
+impl_binary_op!(Mul, mul, multiply, Ciphertext);
+impl_binary_op!(Add, add, add_plain, Plaintext);
+impl_binary_op!(Sub, sub, sub_plain, Plaintext);
+impl_binary_op!(Mul, mul, multiply_plain, Plaintext);
+
+impl Neg for &Ciphertext {
+    type Output = Ciphertext;
+
+    fn neg(self) -> Ciphertext {
+        apply(self, |e| e.negate(self))
+    }
+}
+
+impl Neg for Ciphertext {
+    type Output = Ciphertext;
+
+    fn neg(self) -> Ciphertext {
+        -&self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn can_use_operators() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 20).unwrap())
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, true, SecurityLevel::TC128).unwrap();
+        let gen = KeyGenerator::new(&ctx).unwrap();
+        let encoder = BFVEncoder::new(&ctx).unwrap();
+        let encryptor =
+            Encryptor::with_public_key(&ctx, &gen.create_public_key().unwrap()).unwrap();
+        let decryptor = Decryptor::new(&ctx, &gen.secret_key().unwrap()).unwrap();
+        let evaluator = BFVEvaluator::new(&ctx).unwrap();
+
+        let encrypt = |x: &[i64]| {
+            encryptor
+                .encrypt(&encoder.encode_signed(x).unwrap())
+                .unwrap()
+        };
+
+        let a = encrypt(&[2, 3]);
+        let b = encrypt(&[5, 7]);
+        let c = encrypt(&[1, -1]);
+        let p = encoder.encode_signed(&[10, 20]).unwrap();
+
+        let d = with_evaluator(&evaluator, || -(&a + &b * &c) - &a * &p).unwrap();
+
+        let d = encoder
+            .decode_signed(&decryptor.decrypt(&d).unwrap())
+            .unwrap();
+
+        assert_eq!(d[0..2], [-27, -56]);
+
+        // Operands at different levels fail, and the failure is reported.
+        let b = evaluator.mod_switch_to_next(&b).unwrap();
+
+        assert!(with_evaluator(&evaluator, || &a + &b - &c).is_err());
+
+        // Scopes don't leak errors into later calls.
+        assert!(with_evaluator(&evaluator, || &a - &c).is_ok());
+    }
+}
@@ -0,0 +1,303 @@
+use crate::error::{Error, ParameterError, Result};
+use crate::{
+    BfvEncryptionParametersBuilder, CoefficientModulus, EncryptionParameters, Modulus,
+    PlainModulus, SecurityLevel,
+};
+
+const MIN_LOG_DEGREE: u32 = 10;
+const MAX_LOG_DEGREE: u32 = 15;
+const MAX_PRIME_BITS: u32 = 60;
+
+/**
+ * Bits of noise budget a fresh ciphertext lacks beyond the plaintext
+ * modulus and polynomial degree, and the budget left after the last
+ * multiplication.
+ */
+const NOISE_MARGIN_BITS: u32 = 10;
+
+/**
+ * Chooses BFV encryption parameters that support a given multiplicative
+ * depth and plaintext modulus size at a given security level.
+ *
+ * # Remarks
+ * The selector tries polynomial degrees from 1024 to 32768 and returns the
+ * smallest one whose coefficient modulus can hold the estimated noise while
+ * staying within the HomomorphicEncryption.org bound for the security
+ * level.
+ *
+ * The noise estimate assumes each multiplication (followed by
+ * relinearization) consumes roughly `plain_modulus_bits + log2(degree) + 10`
+ * bits of noise budget, which holds for multiplying fresh ciphertexts in a
+ * balanced tree. Long chains of multiplications by the same operand,
+ * rotations and many additions consume more. Check the remaining noise
+ * budget with [`Decryptor::invariant_noise_budget`](crate::Decryptor::invariant_noise_budget)
+ * when in doubt.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParameterSelector {
+    depth: u32,
+    plain_modulus_bits: u32,
+    security_level: SecurityLevel,
+    batching: bool,
+}
+
+impl Default for ParameterSelector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ParameterSelector {
+    /**
+     * Creates a selector for depth 1 computations on a 20-bit plaintext
+     * modulus that supports batching at the default security level.
+     */
+    pub fn new() -> Self {
+        Self {
+            depth: 1,
+            plain_modulus_bits: 20,
+            security_level: SecurityLevel::default(),
+            batching: true,
+        }
+    }
+
+    /**
+     * Sets the number of sequential multiplications the parameters must
+     * support.
+     */
+    pub fn set_depth(mut self, depth: u32) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /**
+     * Sets the number of bits in the plaintext modulus. Signed values must
+     * stay within `[-2^(bits - 2), 2^(bits - 2))` to avoid wrapping.
+     */
+    pub fn set_plain_modulus_bits(mut self, bits: u32) -> Self {
+        self.plain_modulus_bits = bits;
+        self
+    }
+
+    /**
+     * Sets the security level the parameters must meet.
+     */
+    pub fn set_security_level(mut self, security_level: SecurityLevel) -> Self {
+        self.security_level = security_level;
+        self
+    }
+
+    /**
+     * Sets whether the plaintext modulus must support batching. If so, the
+     * plaintext modulus is a prime congruent to 1 modulo `2 * degree`,
+     * otherwise it's `2^(bits - 1) + 1`. Defaults to true.
+     */
+    pub fn set_batching(mut self, batching: bool) -> Self {
+        self.batching = batching;
+        self
+    }
+
+    /**
+     * Selects the parameters.
+     *
+     * Returns [`ParameterError::Unsatisfiable`] if no degree up to 32768
+     * supports the requested computation and [`Error::InvalidArgument`] if
+     * the plaintext modulus size is outside `2..=60` bits.
+     */
+    pub fn build(&self) -> Result<EncryptionParameters> {
+        if !(2..=MAX_PRIME_BITS).contains(&self.plain_modulus_bits) {
+            return Err(Error::InvalidArgument);
+        }
+
+        for log_degree in MIN_LOG_DEGREE..=MAX_LOG_DEGREE {
+            if let Some(params) = self.try_degree(log_degree)? {
+                return Ok(params);
+            }
+        }
+
+        Err(ParameterError::Unsatisfiable {
+            depth: self.depth,
+            plain_modulus_bits: self.plain_modulus_bits,
+            security_level: self.security_level,
+        }
+        .into())
+    }
+
+    /**
+     * Returns the bit sizes of the primes in the coefficient modulus for the
+     * given degree.
+     */
+    fn coeff_modulus_bits(&self, log_degree: u32) -> Vec<u32> {
+        let noise_per_level = self.plain_modulus_bits + log_degree + NOISE_MARGIN_BITS;
+        let data_bits = (self.depth + 1) * noise_per_level + NOISE_MARGIN_BITS;
+
+        let count = (data_bits + MAX_PRIME_BITS - 1) / MAX_PRIME_BITS;
+
+        // NTT-friendly primes must exceed 2 * degree.
+        let min_bits = log_degree + 2;
+
+        let mut bits = (0..count)
+            .map(|i| {
+                let b = data_bits / count + u32::from(i < data_bits % count);
+
+                // A prime with the same size as the plain modulus would
+                // be the same prime.
+                let b = if b == self.plain_modulus_bits {
+                    b + 1
+                } else {
+                    b
+                };
+
+                b.clamp(min_bits, MAX_PRIME_BITS)
+            })
+            .collect::<Vec<u32>>();
+
+        // Relinearization requires a special prime at least as large as the
+        // others.
+        if self.depth > 0 {
+            bits.push(*bits.iter().max().unwrap());
+        }
+
+        bits
+    }
+
+    fn plain_modulus(&self, degree: u64) -> Result<Option<Modulus>> {
+        if self.batching {
+            // Batching primes are congruent to 1 mod 2 * degree.
+            if self.plain_modulus_bits <= degree.trailing_zeros() + 1 {
+                return Ok(None);
+            }
+
+            Ok(Some(PlainModulus::batching(
+                degree,
+                self.plain_modulus_bits,
+            )?))
+        } else {
+            Ok(Some(PlainModulus::raw(
+                (1 << (self.plain_modulus_bits - 1)) + 1,
+            )?))
+        }
+    }
+
+    fn try_degree(&self, log_degree: u32) -> Result<Option<EncryptionParameters>> {
+        let degree = 1u64 << log_degree;
+
+        let max_bits = match CoefficientModulus::try_max_bit_count(degree, self.security_level) {
+            Some(b) => b,
+            None => return Ok(None),
+        };
+
+        let bits = self.coeff_modulus_bits(log_degree);
+
+        if bits.iter().sum::<u32>() > max_bits {
+            return Ok(None);
+        }
+
+        let plain_modulus = match self.plain_modulus(degree)? {
+            Some(p) => p,
+            None => return Ok(None),
+        };
+
+        let bits = bits.iter().map(|b| *b as i32).collect::<Vec<i32>>();
+
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(degree)
+            .set_coefficient_modulus(CoefficientModulus::create(degree, &bits)?)
+            .set_plain_modulus(plain_modulus)
+            .build()?;
+
+        match params.validate(self.security_level) {
+            Ok(()) => Ok(Some(params)),
+            Err(Error::InvalidParameters(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn selects_valid_params() {
+        let mut last_degree = 0;
+
+        for depth in 0..4 {
+            let params = ParameterSelector::new()
+                .set_depth(depth)
+                .set_plain_modulus_bits(20)
+                .build()
+                .unwrap();
+
+            params.validate(SecurityLevel::TC128).unwrap();
+            params.validate_batching().unwrap();
+
+            assert!(params.get_poly_modulus_degree() >= last_degree);
+            last_degree = params.get_poly_modulus_degree();
+        }
+
+        let params = ParameterSelector::new()
+            .set_depth(1)
+            .set_plain_modulus_bits(16)
+            .set_batching(false)
+            .set_security_level(SecurityLevel::TC192)
+            .build()
+            .unwrap();
+
+        params.validate(SecurityLevel::TC192).unwrap();
+        assert_eq!(params.get_plain_modulus().value(), (1 << 15) + 1);
+    }
+
+    #[test]
+    fn selected_params_support_depth() {
+        let depth = 3;
+
+        let params = ParameterSelector::new()
+            .set_depth(depth)
+            .set_plain_modulus_bits(20)
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, true, SecurityLevel::TC128).unwrap();
+        let gen = KeyGenerator::new(&ctx).unwrap();
+        let public_key = gen.create_public_key().unwrap();
+        let secret_key = gen.secret_key().unwrap();
+        let relin_keys = gen.create_relinearization_keys().unwrap();
+
+        let encoder = BFVEncoder::new(&ctx).unwrap();
+        let encryptor = Encryptor::with_public_key(&ctx, &public_key).unwrap();
+        let decryptor = Decryptor::new(&ctx, &secret_key).unwrap();
+        let evaluator = BFVEvaluator::new(&ctx).unwrap();
+
+        let data = vec![2; encoder.get_slot_count()];
+        let mut c = encryptor
+            .encrypt(&encoder.encode_signed(&data).unwrap())
+            .unwrap();
+
+        for _ in 0..depth {
+            c = evaluator.square(&c).unwrap();
+            c = evaluator.relinearize(&c, &relin_keys).unwrap();
+        }
+
+        assert!(decryptor.invariant_noise_budget(&c).unwrap() > 0);
+
+        let result = encoder
+            .decode_signed(&decryptor.decrypt(&c).unwrap())
+            .unwrap();
+
+        assert_eq!(result, vec![256; encoder.get_slot_count()]);
+    }
+
+    #[test]
+    fn rejects_unsatisfiable_depth() {
+        let result = ParameterSelector::new()
+            .set_depth(100)
+            .set_plain_modulus_bits(20)
+            .build();
+
+        assert!(matches!(
+            result,
+            Err(Error::InvalidParameters(e)) if matches!(*e, ParameterError::Unsatisfiable { depth: 100, .. })
+        ));
+    }
+}
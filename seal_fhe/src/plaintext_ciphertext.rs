@@ -3,10 +3,12 @@ use std::ffi::{c_void, CString};
 use std::ptr::null_mut;
 
 use crate::error::*;
+use crate::stream::serialized_size;
 use crate::{bindgen, serialization::CompressionType, Context, FromBytes, ToBytes};
 
 use serde::ser::Error;
 use serde::{Serialize, Serializer};
+use zeroize::Zeroize;
 
 #[derive(Debug, Eq)]
 /**
@@ -42,12 +44,17 @@ unsafe impl Send for Plaintext {}
 
 impl Clone for Plaintext {
     fn clone(&self) -> Self {
-        let mut copy = null_mut();
-
-        convert_seal_error(unsafe { bindgen::Plaintext_Create5(self.handle, &mut copy) })
-            .expect("Internal error: Failed to copy plaintext.");
+        self.try_clone()
+            .expect("Internal error: Failed to copy plaintext.")
+    }
 
-        Self { handle: copy }
+    /**
+     * Copies `source` into this plaintext, reusing its allocation if it's
+     * large enough.
+     */
+    fn clone_from(&mut self, source: &Self) {
+        self.assign(source)
+            .expect("Internal error: Failed to copy plaintext.")
     }
 }
 
@@ -84,7 +91,11 @@ impl Serialize for Plaintext {
         let mut num_bytes: i64 = 0;
 
         convert_seal_error(unsafe {
-            bindgen::Plaintext_SaveSize(self.handle, CompressionType::ZStd as u8, &mut num_bytes)
+            bindgen::Plaintext_SaveSize(
+                self.handle,
+                CompressionType::default() as u8,
+                &mut num_bytes,
+            )
         })
         .map_err(|e| {
             S::Error::custom(format!("Failed to get private key serialized size: {}", e))
@@ -124,13 +135,19 @@ impl FromBytes for Plaintext {
 }
 
 impl ToBytes for Plaintext {
-    fn as_bytes(&self) -> Result<Vec<u8>> {
+    fn save_size_with_compression(&self, compression: CompressionType) -> Result<u64> {
         let mut num_bytes: i64 = 0;
 
         convert_seal_error(unsafe {
-            bindgen::Plaintext_SaveSize(self.handle, CompressionType::ZStd as u8, &mut num_bytes)
+            bindgen::Plaintext_SaveSize(self.handle, compression as u8, &mut num_bytes)
         })?;
 
+        Ok(num_bytes as u64)
+    }
+
+    fn as_bytes_with_compression(&self, compression: CompressionType) -> Result<Vec<u8>> {
+        let num_bytes = self.save_size_with_compression(compression)?;
+
         let mut data: Vec<u8> = Vec::with_capacity(num_bytes as usize);
         let mut bytes_written: i64 = 0;
 
@@ -140,8 +157,8 @@ impl ToBytes for Plaintext {
             bindgen::Plaintext_Save(
                 self.handle,
                 data_ptr,
-                num_bytes as u64,
-                CompressionType::ZStd as u8,
+                num_bytes,
+                compression as u8,
                 &mut bytes_written,
             )
         })?;
@@ -171,6 +188,27 @@ impl Plaintext {
         Ok(Self { handle })
     }
 
+    /**
+     * Returns a deep copy of this plaintext. Unlike [`Clone::clone`], this
+     * returns rather than panics if SEAL fails to allocate the copy.
+     */
+    pub fn try_clone(&self) -> Result<Self> {
+        let mut copy = null_mut();
+
+        convert_seal_error(unsafe { bindgen::Plaintext_Create5(self.handle, &mut copy) })?;
+
+        Ok(Self { handle: copy })
+    }
+
+    /**
+     * Overwrites this plaintext with a copy of `source`, reusing this
+     * plaintext's allocation if it's large enough. See
+     * [`Pool`](crate::Pool).
+     */
+    pub fn assign(&mut self, source: &Self) -> Result<()> {
+        convert_seal_error(unsafe { bindgen::Plaintext_Set1(self.handle, source.handle) })
+    }
+
     /**
      * Constructs a plaintext from a given hexadecimal string describing the
      * plaintext polynomial.
@@ -272,10 +310,87 @@ impl Plaintext {
 
         size as usize
     }
+
+    /**
+     * Returns the number of nonzero coefficients in this plaintext.
+     *
+     * # Remarks
+     * This counts polynomial coefficients, not batching slots. A batched
+     * plaintext with a single nonzero slot generally has almost every
+     * coefficient nonzero, while one with the same value in every slot is
+     * a constant polynomial with at most one.
+     */
+    pub fn nonzero_coefficient_count(&self) -> usize {
+        let mut count: u64 = 0;
+
+        convert_seal_error(unsafe {
+            bindgen::Plaintext_NonZeroCoeffCount(self.handle, &mut count)
+        })
+        .expect("Fatal error in Plaintext::nonzero_coefficient_count().");
+
+        count as usize
+    }
+
+    /**
+     * Returns whether this plaintext is in NTT form, as CKKS plaintexts
+     * are. BFV plaintexts aren't.
+     */
+    pub fn is_ntt_form(&self) -> bool {
+        let mut result = false;
+
+        convert_seal_error(unsafe { bindgen::Plaintext_IsNTTForm(self.handle, &mut result) })
+            .expect("Fatal error in Plaintext::is_ntt_form().");
+
+        result
+    }
+
+    /**
+     * Returns whether this plaintext is the constant polynomial 1, e.g. an
+     * all-ones mask, which leaves ciphertexts unchanged when multiplied.
+     */
+    pub fn is_one(&self) -> bool {
+        !self.is_ntt_form()
+            && self.nonzero_coefficient_count() == 1
+            && self.len() > 0
+            && self.get_coefficient(0) == 1
+    }
+}
+
+impl Plaintext {
+    /**
+     * Zeroes this plaintext's coefficients and frees it, returning any
+     * error SEAL reports rather than panicking as [`Drop`] would.
+     */
+    pub fn destroy(mut self) -> Result<()> {
+        self.zeroize();
+
+        let result = convert_seal_error(unsafe { bindgen::Plaintext_Destroy(self.handle) });
+
+        // SEAL has freed the plaintext, even if it reported an error.
+        std::mem::forget(self);
+
+        result
+    }
+}
+
+impl Zeroize for Plaintext {
+    /**
+     * Sets every coefficient of this plaintext to zero.
+     */
+    fn zeroize(&mut self) {
+        convert_seal_error(unsafe { bindgen::Plaintext_SetZero1(self.handle) })
+            .expect("Internal error in Plaintext::zeroize.");
+    }
 }
 
 impl Drop for Plaintext {
+    /**
+     * Plaintexts may hold encoded secrets (e.g. decrypted values), so
+     * their coefficients are zeroed before they're freed.
+     */
     fn drop(&mut self) {
+        self.zeroize();
+
         convert_seal_error(unsafe { bindgen::Plaintext_Destroy(self.handle) })
             .expect("Internal error in Plaintext::drop.");
     }
@@ -313,12 +428,17 @@ unsafe impl Send for Ciphertext {}
 
 impl Clone for Ciphertext {
     fn clone(&self) -> Self {
-        let mut handle = null_mut();
-
-        convert_seal_error(unsafe { bindgen::Ciphertext_Create2(self.handle, &mut handle) })
-            .expect("Fatal error: Failed to clone ciphertext");
+        self.try_clone()
+            .expect("Fatal error: Failed to clone ciphertext")
+    }
 
-        Self { handle }
+    /**
+     * Copies `source` into this ciphertext, reusing its allocation if it's
+     * large enough.
+     */
+    fn clone_from(&mut self, source: &Self) {
+        self.assign(source)
+            .expect("Fatal error: Failed to clone ciphertext")
     }
 }
 
@@ -341,6 +461,27 @@ impl Ciphertext {
         Ok(Self { handle })
     }
 
+    /**
+     * Returns a deep copy of this ciphertext. Unlike [`Clone::clone`],
+     * this returns rather than panics if SEAL fails to allocate the copy.
+     */
+    pub fn try_clone(&self) -> Result<Self> {
+        let mut handle = null_mut();
+
+        convert_seal_error(unsafe { bindgen::Ciphertext_Create2(self.handle, &mut handle) })?;
+
+        Ok(Self { handle })
+    }
+
+    /**
+     * Overwrites this ciphertext with a copy of `source`, reusing this
+     * ciphertext's allocation if it's large enough. See
+     * [`Pool`](crate::Pool).
+     */
+    pub fn assign(&mut self, source: &Self) -> Result<()> {
+        convert_seal_error(unsafe { bindgen::Ciphertext_Set(self.handle, source.handle) })
+    }
+
     /**
      * Returns the number of polynomials in this ciphertext.
      */
@@ -351,6 +492,120 @@ impl Ciphertext {
 
         size
     }
+
+    /**
+     * Returns the number of primes in the coefficient modulus at this
+     * ciphertext's level, which drops by one with each modulus switch.
+     */
+    pub fn coeff_modulus_size(&self) -> u64 {
+        let mut size: u64 = 0;
+
+        convert_seal_error(unsafe { bindgen::Ciphertext_CoeffModulusSize(self.handle, &mut size) })
+            .unwrap();
+
+        size
+    }
+
+    /**
+     * Returns the parms_id of the parameter set this ciphertext is
+     * currently at in the modulus switching chain.
+     */
+    pub fn get_parms_id(&self) -> [u64; 4] {
+        let mut parms_id = [0u64; 4];
+
+        convert_seal_error(unsafe {
+            bindgen::Ciphertext_ParmsId(self.handle, parms_id.as_mut_ptr())
+        })
+        .unwrap();
+
+        parms_id
+    }
+
+    /**
+     * Returns whether this ciphertext is in NTT form.
+     */
+    pub fn is_ntt_form(&self) -> bool {
+        let mut result = false;
+
+        convert_seal_error(unsafe { bindgen::Ciphertext_IsNTTForm(self.handle, &mut result) })
+            .unwrap();
+
+        result
+    }
+
+    /**
+     * Returns the coefficients of the `index`-th polynomial in this
+     * ciphertext in RNS form: [`coeff_modulus_size`](Self::coeff_modulus_size)
+     * blocks of `poly_modulus_degree` coefficients, each block reduced
+     * modulo the corresponding prime.
+     *
+     * Returns [`Error::InvalidArgument`] if the ciphertext has no such
+     * polynomial.
+     */
+    pub fn polynomial(&self, index: u64) -> Result<Vec<u64>> {
+        let mut degree: u64 = 0;
+
+        convert_seal_error(unsafe {
+            bindgen::Ciphertext_PolyModulusDegree(self.handle, &mut degree)
+        })?;
+
+        let mut coefficients = vec![0; (degree * self.coeff_modulus_size()) as usize];
+
+        convert_seal_error(unsafe {
+            bindgen::Shim_Ciphertext_GetPolyData(
+                self.handle,
+                index,
+                coefficients.as_mut_ptr(),
+                coefficients.len() as u64,
+            )
+        })?;
+
+        Ok(coefficients)
+    }
+
+    /**
+     * Overwrites the `index`-th polynomial in this ciphertext with
+     * `coefficients`, given in the RNS form
+     * [`polynomial`](Self::polynomial) returns.
+     *
+     * # Remarks
+     * The ciphertext keeps its parameters and level, so `coefficients`
+     * must be reduced modulo the primes at that level. Check untrusted
+     * results with [`is_valid_for`](Self::is_valid_for).
+     *
+     * Returns [`Error::InvalidArgument`] if the ciphertext has no such
+     * polynomial or `coefficients` has the wrong length.
+     */
+    pub fn set_polynomial(&mut self, index: u64, coefficients: &[u64]) -> Result<()> {
+        convert_seal_error(unsafe {
+            bindgen::Shim_Ciphertext_SetPolyData(
+                self.handle,
+                index,
+                coefficients.as_ptr(),
+                coefficients.len() as u64,
+            )
+        })
+    }
+
+    /**
+     * Returns whether this ciphertext's data and metadata are valid for the
+     * given context, i.e. it was created under the context's parameters,
+     * sits at a level in its modulus switching chain and every coefficient
+     * is reduced modulo its prime.
+     *
+     * # Remarks
+     * Use this to check ciphertexts that arrive from untrusted or external
+     * sources before evaluating on them.
+     */
+    pub fn is_valid_for(&self, context: &Context) -> Result<bool> {
+        let mut valid = false;
+
+        convert_seal_error(unsafe {
+            bindgen::ValCheck_Ciphertext_IsValidFor(self.handle, context.get_handle(), &mut valid)
+        })?;
+
+        Ok(valid)
+    }
 }
 
 impl PartialEq for Ciphertext {
@@ -360,13 +615,19 @@ impl PartialEq for Ciphertext {
 }
 
 impl ToBytes for Ciphertext {
-    fn as_bytes(&self) -> Result<Vec<u8>> {
+    fn save_size_with_compression(&self, compression: CompressionType) -> Result<u64> {
         let mut num_bytes: i64 = 0;
 
         convert_seal_error(unsafe {
-            bindgen::Ciphertext_SaveSize(self.handle, CompressionType::ZStd as u8, &mut num_bytes)
+            bindgen::Ciphertext_SaveSize(self.handle, compression as u8, &mut num_bytes)
         })?;
 
+        Ok(num_bytes as u64)
+    }
+
+    fn as_bytes_with_compression(&self, compression: CompressionType) -> Result<Vec<u8>> {
+        let num_bytes = self.save_size_with_compression(compression)?;
+
         let mut data: Vec<u8> = Vec::with_capacity(num_bytes as usize);
         let mut bytes_written: i64 = 0;
 
@@ -376,8 +637,8 @@ impl ToBytes for Ciphertext {
             bindgen::Ciphertext_Save(
                 self.handle,
                 data_ptr,
-                num_bytes as u64,
-                CompressionType::ZStd as u8,
+                num_bytes,
+                compression as u8,
                 &mut bytes_written,
             )
         })?;
@@ -407,6 +668,79 @@ impl FromBytes for Ciphertext {
     }
 }
 
+/**
+ * A validated, borrowed view over a serialized [`Ciphertext`] living in an
+ * external buffer (e.g. a memory-mapped file).
+ *
+ * # Remarks
+ * Creating a view only validates the SEAL serialization header and records
+ * where the ciphertext ends; it neither copies nor decompresses the payload.
+ * This lets services index large buffers of ciphertexts cheaply and only pay
+ * for deserialization when they [`load`](Self::load) a ciphertext they
+ * actually use. SEAL stores ciphertexts compressed and in its own memory, so
+ * loading necessarily copies the data out of the buffer.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CiphertextView<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> CiphertextView<'a> {
+    /**
+     * Validates the header of the serialized ciphertext at the start of
+     * `bytes` and returns a view over it along with the remainder of
+     * `bytes`. Use this to walk a buffer of back-to-back ciphertexts.
+     */
+    pub fn split_first(bytes: &'a [u8]) -> Result<(Self, &'a [u8])> {
+        let size = serialized_size(bytes)?;
+
+        if size > bytes.len() {
+            return Err(crate::Error::SerializationError(Box::new(format!(
+                "Serialized ciphertext claims {} bytes, but buffer has {}",
+                size,
+                bytes.len()
+            ))));
+        }
+
+        let (view, rest) = bytes.split_at(size);
+
+        Ok((Self { bytes: view }, rest))
+    }
+
+    /**
+     * Returns the underlying serialized bytes of this ciphertext.
+     */
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /**
+     * Deserializes the viewed ciphertext under the given context.
+     */
+    pub fn load(&self, context: &Context) -> Result<Ciphertext> {
+        Ciphertext::from_bytes(context, self.bytes)
+    }
+}
+
+impl Ciphertext {
+    /**
+     * Creates a [`CiphertextView`] over the serialized ciphertext in `bytes`
+     * without copying it. Fails if `bytes` doesn't begin with a valid
+     * SEAL header or contains data beyond the serialized ciphertext.
+     */
+    pub fn from_bytes_in_place(bytes: &[u8]) -> Result<CiphertextView<'_>> {
+        let (view, rest) = CiphertextView::split_first(bytes)?;
+
+        if !rest.is_empty() {
+            return Err(crate::Error::SerializationError(Box::new(
+                "Trailing bytes after serialized ciphertext".to_owned(),
+            )));
+        }
+
+        Ok(view)
+    }
+}
+
 impl Drop for Ciphertext {
     fn drop(&mut self) {
         convert_seal_error(unsafe { bindgen::Ciphertext_Destroy(self.handle) })
@@ -440,4 +774,99 @@ mod tests {
         assert_eq!(plaintext.get_coefficient(1), 0);
         assert_eq!(plaintext.get_coefficient(2), 0x1234);
     }
+
+    #[test]
+    fn can_zeroize_plaintext() {
+        let mut plaintext = Plaintext::from_hex_string("1234x^2 + 4321").unwrap();
+
+        plaintext.zeroize();
+
+        assert_eq!(plaintext.len(), 3);
+
+        for i in 0..plaintext.len() {
+            assert_eq!(plaintext.get_coefficient(i), 0);
+        }
+
+        plaintext.destroy().unwrap();
+    }
+
+    #[test]
+    fn can_view_ciphertexts_in_place() {
+        use crate::*;
+
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 20).unwrap())
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+        let gen = KeyGenerator::new(&ctx).unwrap();
+        let encoder = BFVEncoder::new(&ctx).unwrap();
+        let encryptor =
+            Encryptor::with_public_key(&ctx, &gen.create_public_key().unwrap()).unwrap();
+
+        let a = encryptor
+            .encrypt(&encoder.encode_unsigned(&[1, 2, 3]).unwrap())
+            .unwrap();
+        let b = encryptor
+            .encrypt(&encoder.encode_unsigned(&[4, 5, 6]).unwrap())
+            .unwrap();
+
+        let a_bytes = a.as_bytes().unwrap();
+        let buffer = [a_bytes.clone(), b.as_bytes().unwrap()].concat();
+
+        let (view_a, rest) = CiphertextView::split_first(&buffer).unwrap();
+        let view_b = Ciphertext::from_bytes_in_place(rest).unwrap();
+
+        assert_eq!(view_a.as_bytes(), &a_bytes[..]);
+        assert_eq!(view_a.load(&ctx).unwrap(), a);
+        assert_eq!(view_b.load(&ctx).unwrap(), b);
+
+        assert!(Ciphertext::from_bytes_in_place(&buffer).is_err());
+        assert!(Ciphertext::from_bytes_in_place(&buffer[1..]).is_err());
+    }
+
+    #[test]
+    fn can_check_ciphertext_validity() {
+        use crate::*;
+
+        let make_context = |plain_bits| {
+            let params = BfvEncryptionParametersBuilder::new()
+                .set_poly_modulus_degree(8192)
+                .set_coefficient_modulus(
+                    CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+                )
+                .set_plain_modulus(PlainModulus::batching(8192, plain_bits).unwrap())
+                .build()
+                .unwrap();
+
+            Context::new(&params, true, SecurityLevel::TC128).unwrap()
+        };
+
+        let ctx = make_context(20);
+        let other_ctx = make_context(21);
+
+        let gen = KeyGenerator::new(&ctx).unwrap();
+        let encoder = BFVEncoder::new(&ctx).unwrap();
+        let encryptor =
+            Encryptor::with_public_key(&ctx, &gen.create_public_key().unwrap()).unwrap();
+        let evaluator = BFVEvaluator::new(&ctx).unwrap();
+
+        let a = encryptor
+            .encrypt(&encoder.encode_unsigned(&[1, 2, 3]).unwrap())
+            .unwrap();
+
+        assert!(a.is_valid_for(&ctx).unwrap());
+        assert!(evaluator
+            .mod_switch_to_next(&a)
+            .unwrap()
+            .is_valid_for(&ctx)
+            .unwrap());
+        assert!(!a.is_valid_for(&other_ctx).unwrap());
+        assert!(!Ciphertext::new().unwrap().is_valid_for(&ctx).unwrap());
+    }
 }
@@ -0,0 +1,190 @@
+use std::sync::Mutex;
+
+use crate::error::*;
+use crate::{Ciphertext, Plaintext};
+
+/**
+ * A SEAL object that a [`Pool`] can construct empty and later overwrite
+ * in place.
+ */
+pub trait Reusable: Sized {
+    /**
+     * Constructs an empty object allocating no memory.
+     */
+    fn empty() -> Result<Self>;
+
+    /**
+     * Overwrites this object with a copy of `source`, reusing its
+     * allocation if it's large enough.
+     */
+    fn assign(&mut self, source: &Self) -> Result<()>;
+}
+
+impl Reusable for Ciphertext {
+    fn empty() -> Result<Self> {
+        Ciphertext::new()
+    }
+
+    fn assign(&mut self, source: &Self) -> Result<()> {
+        Ciphertext::assign(self, source)
+    }
+}
+
+impl Reusable for Plaintext {
+    fn empty() -> Result<Self> {
+        Plaintext::new()
+    }
+
+    fn assign(&mut self, source: &Self) -> Result<()> {
+        Plaintext::assign(self, source)
+    }
+}
+
+/**
+ * Recycles ciphertexts or plaintexts so hot loops can reuse their
+ * allocations rather than constructing a fresh object per operation.
+ *
+ * # Remarks
+ * Objects handed to [`recycle`](Self::recycle) keep their coefficient
+ * buffers. [`copy_of`](Self::copy_of) copies into a recycled object, so
+ * copying a ciphertext no larger than one previously recycled doesn't
+ * allocate. The pool keeps at most `max_size` idle objects and drops any
+ * beyond that.
+ *
+ * Pools are `Send` and `Sync`, so worker threads may share one.
+ */
+pub struct Pool<T> {
+    free: Mutex<Vec<T>>,
+    max_size: usize,
+}
+
+/**
+ * A [`Pool`] of ciphertexts.
+ */
+pub type CiphertextPool = Pool<Ciphertext>;
+
+/**
+ * A [`Pool`] of plaintexts.
+ */
+pub type PlaintextPool = Pool<Plaintext>;
+
+impl<T: Reusable> Pool<T> {
+    /**
+     * Creates an empty pool that holds at most `max_size` idle objects.
+     */
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            free: Mutex::new(Vec::new()),
+            max_size,
+        }
+    }
+
+    /**
+     * Returns a recycled object if one is idle, otherwise a new empty one.
+     * A recycled object still holds its previous contents.
+     */
+    pub fn take(&self) -> Result<T> {
+        match self.free.lock().unwrap().pop() {
+            Some(x) => Ok(x),
+            None => T::empty(),
+        }
+    }
+
+    /**
+     * Returns a deep copy of `source`, reusing an idle object's allocation
+     * if one is available.
+     */
+    pub fn copy_of(&self, source: &T) -> Result<T> {
+        let mut copy = self.take()?;
+        copy.assign(source)?;
+
+        Ok(copy)
+    }
+
+    /**
+     * Returns an object to the pool for reuse. The object is dropped if
+     * the pool is full.
+     */
+    pub fn recycle(&self, value: T) {
+        let mut free = self.free.lock().unwrap();
+
+        if free.len() < self.max_size {
+            free.push(value);
+        }
+    }
+
+    /**
+     * The number of idle objects in the pool.
+     */
+    pub fn len(&self) -> usize {
+        self.free.lock().unwrap().len()
+    }
+
+    /**
+     * Whether the pool holds no idle objects.
+     */
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn can_reuse_pooled_ciphertexts() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::bfv_default(8192, SecurityLevel::TC128).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 20).unwrap())
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+        let gen = KeyGenerator::new(&ctx).unwrap();
+        let encoder = BFVEncoder::new(&ctx).unwrap();
+
+        let public_key = gen.create_public_key().unwrap();
+        let secret_key = gen.secret_key().unwrap();
+
+        let encryptor =
+            Encryptor::with_public_and_secret_key(&ctx, &public_key, &secret_key).unwrap();
+        let decryptor = Decryptor::new(&ctx, &secret_key).unwrap();
+
+        let data = (0..8192).map(|x| x % 100).collect::<Vec<_>>();
+        let ciphertext = encryptor
+            .encrypt(&encoder.encode_unsigned(&data).unwrap())
+            .unwrap();
+
+        let pool = CiphertextPool::new(1);
+
+        let a = pool.copy_of(&ciphertext).unwrap();
+        let b = pool.copy_of(&ciphertext).unwrap();
+
+        pool.recycle(a);
+        pool.recycle(b);
+        assert_eq!(pool.len(), 1);
+
+        let c = pool.copy_of(&ciphertext).unwrap();
+        assert!(pool.is_empty());
+
+        let decrypted = encoder
+            .decode_unsigned(&decryptor.decrypt(&c).unwrap())
+            .unwrap();
+
+        assert_eq!(decrypted, data);
+
+        let mut d = Ciphertext::new().unwrap();
+        d.clone_from(&c);
+
+        let decrypted = encoder
+            .decode_unsigned(&decryptor.decrypt(&d).unwrap())
+            .unwrap();
+
+        assert_eq!(decrypted, data);
+    }
+}
@@ -0,0 +1,436 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::*;
+use crate::{Ciphertext, Evaluator, GaloisKeys, Plaintext, RelinearizationKeys};
+
+/**
+ * The kinds of operation a [`ProfilingEvaluator`] counts. In-place and
+ * out-of-place variants of an operation count as the same kind.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Operation {
+    /// [`Evaluator::negate`].
+    Negate,
+
+    /// [`Evaluator::add`].
+    Add,
+
+    /// [`Evaluator::add_many`].
+    AddMany,
+
+    /// [`Evaluator::sub`].
+    Sub,
+
+    /// [`Evaluator::multiply`].
+    Multiply,
+
+    /// [`Evaluator::multiply_many`].
+    MultiplyMany,
+
+    /// [`Evaluator::square`].
+    Square,
+
+    /// [`Evaluator::exponentiate`].
+    Exponentiate,
+
+    /// [`Evaluator::add_plain`].
+    AddPlain,
+
+    /// [`Evaluator::sub_plain`].
+    SubPlain,
+
+    /// [`Evaluator::plain_sub`].
+    PlainSub,
+
+    /// [`Evaluator::multiply_plain`].
+    MultiplyPlain,
+
+    /// [`Evaluator::relinearize`].
+    Relinearize,
+
+    /// [`Evaluator::rotate_rows`].
+    RotateRows,
+
+    /// [`Evaluator::rotate_columns`].
+    RotateColumns,
+
+    /// [`Evaluator::mod_switch_to_next`] on a ciphertext.
+    ModSwitch,
+
+    /// [`Evaluator::mod_switch_to_next_plaintext`].
+    ModSwitchPlaintext,
+}
+
+/**
+ * How many times a [`ProfilingEvaluator`] ran an operation, and for how
+ * long in total.
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationProfile {
+    /**
+     * The number of calls.
+     */
+    pub count: u64,
+
+    /**
+     * The wall time spent in those calls.
+     */
+    pub total_time: Duration,
+}
+
+/**
+ * The operations a [`ProfilingEvaluator`] has run. See
+ * [`ProfilingEvaluator::report`].
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileReport {
+    /**
+     * The profile of each operation that ran at least once.
+     */
+    pub operations: BTreeMap<Operation, OperationProfile>,
+}
+
+impl ProfileReport {
+    /**
+     * Returns the number of times `operation` ran.
+     */
+    pub fn count(&self, operation: Operation) -> u64 {
+        self.operations
+            .get(&operation)
+            .map(|p| p.count)
+            .unwrap_or_default()
+    }
+
+    /**
+     * Returns the wall time spent in all operations.
+     */
+    pub fn total_time(&self) -> Duration {
+        self.operations.values().map(|p| p.total_time).sum()
+    }
+}
+
+/**
+ * An [`Evaluator`] that forwards each operation to another evaluator and
+ * counts the operations it runs and the time they take, e.g. to compare
+ * formulations of a circuit.
+ *
+ * # Remarks
+ * Counts and times include operations that fail. The evaluator may be
+ * shared across threads, in which case the report covers all of them and
+ * times are summed across threads rather than measuring elapsed time.
+ */
+pub struct ProfilingEvaluator<E> {
+    inner: E,
+    report: Mutex<ProfileReport>,
+}
+
+impl<E> ProfilingEvaluator<E>
+where
+    E: Evaluator,
+{
+    /**
+     * Creates a profiling evaluator that forwards operations to `inner`.
+     */
+    pub fn new(inner: E) -> Self {
+        Self {
+            inner,
+            report: Mutex::new(ProfileReport::default()),
+        }
+    }
+
+    /**
+     * Returns the wrapped evaluator. Operations run on it directly aren't
+     * counted.
+     */
+    pub fn inner(&self) -> &E {
+        &self.inner
+    }
+
+    /**
+     * Returns the wrapped evaluator, discarding the report.
+     */
+    pub fn into_inner(self) -> E {
+        self.inner
+    }
+
+    /**
+     * Returns a snapshot of the operations run so far.
+     */
+    pub fn report(&self) -> ProfileReport {
+        self.lock().clone()
+    }
+
+    /**
+     * Clears the report, e.g. between the formulations being compared.
+     */
+    pub fn reset(&self) {
+        *self.lock() = ProfileReport::default();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ProfileReport> {
+        // The report stays consistent even if a thread panicked while
+        // holding the lock, so ignore poisoning.
+        self.report.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn record<T>(&self, operation: Operation, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        let elapsed = start.elapsed();
+
+        let mut report = self.lock();
+        let profile = report.operations.entry(operation).or_default();
+
+        profile.count += 1;
+        profile.total_time += elapsed;
+
+        result
+    }
+}
+
+impl<E> Evaluator for ProfilingEvaluator<E>
+where
+    E: Evaluator,
+{
+    fn negate_inplace(&self, a: &mut Ciphertext) -> Result<()> {
+        self.record(Operation::Negate, || self.inner.negate_inplace(a))
+    }
+
+    fn negate(&self, a: &Ciphertext) -> Result<Ciphertext> {
+        self.record(Operation::Negate, || self.inner.negate(a))
+    }
+
+    fn add_inplace(&self, a: &mut Ciphertext, b: &Ciphertext) -> Result<()> {
+        self.record(Operation::Add, || self.inner.add_inplace(a, b))
+    }
+
+    fn add(&self, a: &Ciphertext, b: &Ciphertext) -> Result<Ciphertext> {
+        self.record(Operation::Add, || self.inner.add(a, b))
+    }
+
+    fn add_many(&self, a: &[Ciphertext]) -> Result<Ciphertext> {
+        self.record(Operation::AddMany, || self.inner.add_many(a))
+    }
+
+    fn multiply_many(
+        &self,
+        a: &[Ciphertext],
+        relin_keys: &RelinearizationKeys,
+    ) -> Result<Ciphertext> {
+        self.record(Operation::MultiplyMany, || {
+            self.inner.multiply_many(a, relin_keys)
+        })
+    }
+
+    fn sub_inplace(&self, a: &mut Ciphertext, b: &Ciphertext) -> Result<()> {
+        self.record(Operation::Sub, || self.inner.sub_inplace(a, b))
+    }
+
+    fn sub(&self, a: &Ciphertext, b: &Ciphertext) -> Result<Ciphertext> {
+        self.record(Operation::Sub, || self.inner.sub(a, b))
+    }
+
+    fn multiply_inplace(&self, a: &mut Ciphertext, b: &Ciphertext) -> Result<()> {
+        self.record(Operation::Multiply, || self.inner.multiply_inplace(a, b))
+    }
+
+    fn multiply(&self, a: &Ciphertext, b: &Ciphertext) -> Result<Ciphertext> {
+        self.record(Operation::Multiply, || self.inner.multiply(a, b))
+    }
+
+    fn square_inplace(&self, a: &mut Ciphertext) -> Result<()> {
+        self.record(Operation::Square, || self.inner.square_inplace(a))
+    }
+
+    fn square(&self, a: &Ciphertext) -> Result<Ciphertext> {
+        self.record(Operation::Square, || self.inner.square(a))
+    }
+
+    fn mod_switch_to_next(&self, a: &Ciphertext) -> Result<Ciphertext> {
+        self.record(Operation::ModSwitch, || self.inner.mod_switch_to_next(a))
+    }
+
+    fn mod_switch_to_next_inplace(&self, a: &mut Ciphertext) -> Result<()> {
+        self.record(Operation::ModSwitch, || {
+            self.inner.mod_switch_to_next_inplace(a)
+        })
+    }
+
+    fn mod_switch_to_next_plaintext(&self, a: &Plaintext) -> Result<Plaintext> {
+        self.record(Operation::ModSwitchPlaintext, || {
+            self.inner.mod_switch_to_next_plaintext(a)
+        })
+    }
+
+    fn mod_switch_to_next_inplace_plaintext(&self, a: &mut Plaintext) -> Result<()> {
+        self.record(Operation::ModSwitchPlaintext, || {
+            self.inner.mod_switch_to_next_inplace_plaintext(a)
+        })
+    }
+
+    fn exponentiate(
+        &self,
+        a: &Ciphertext,
+        exponent: u64,
+        relin_keys: &RelinearizationKeys,
+    ) -> Result<Ciphertext> {
+        self.record(Operation::Exponentiate, || {
+            self.inner.exponentiate(a, exponent, relin_keys)
+        })
+    }
+
+    fn exponentiate_inplace(
+        &self,
+        a: &mut Ciphertext,
+        exponent: u64,
+        relin_keys: &RelinearizationKeys,
+    ) -> Result<()> {
+        self.record(Operation::Exponentiate, || {
+            self.inner.exponentiate_inplace(a, exponent, relin_keys)
+        })
+    }
+
+    fn add_plain(&self, a: &Ciphertext, b: &Plaintext) -> Result<Ciphertext> {
+        self.record(Operation::AddPlain, || self.inner.add_plain(a, b))
+    }
+
+    fn add_plain_inplace(&self, a: &mut Ciphertext, b: &Plaintext) -> Result<()> {
+        self.record(Operation::AddPlain, || self.inner.add_plain_inplace(a, b))
+    }
+
+    fn sub_plain(&self, a: &Ciphertext, b: &Plaintext) -> Result<Ciphertext> {
+        self.record(Operation::SubPlain, || self.inner.sub_plain(a, b))
+    }
+
+    fn sub_plain_inplace(&self, a: &mut Ciphertext, b: &Plaintext) -> Result<()> {
+        self.record(Operation::SubPlain, || self.inner.sub_plain_inplace(a, b))
+    }
+
+    fn plain_sub(&self, a: &Plaintext, b: &Ciphertext) -> Result<Ciphertext> {
+        self.record(Operation::PlainSub, || self.inner.plain_sub(a, b))
+    }
+
+    fn plain_sub_inplace(&self, a: &Plaintext, b: &mut Ciphertext) -> Result<()> {
+        self.record(Operation::PlainSub, || self.inner.plain_sub_inplace(a, b))
+    }
+
+    fn multiply_plain(&self, a: &Ciphertext, b: &Plaintext) -> Result<Ciphertext> {
+        self.record(Operation::MultiplyPlain, || self.inner.multiply_plain(a, b))
+    }
+
+    fn multiply_plain_inplace(&self, a: &mut Ciphertext, b: &Plaintext) -> Result<()> {
+        self.record(Operation::MultiplyPlain, || {
+            self.inner.multiply_plain_inplace(a, b)
+        })
+    }
+
+    fn relinearize_inplace(
+        &self,
+        a: &mut Ciphertext,
+        relin_keys: &RelinearizationKeys,
+    ) -> Result<()> {
+        self.record(Operation::Relinearize, || {
+            self.inner.relinearize_inplace(a, relin_keys)
+        })
+    }
+
+    fn relinearize(&self, a: &Ciphertext, relin_keys: &RelinearizationKeys) -> Result<Ciphertext> {
+        self.record(Operation::Relinearize, || {
+            self.inner.relinearize(a, relin_keys)
+        })
+    }
+
+    fn rotate_rows(
+        &self,
+        a: &Ciphertext,
+        steps: i32,
+        galois_keys: &GaloisKeys,
+    ) -> Result<Ciphertext> {
+        self.record(Operation::RotateRows, || {
+            self.inner.rotate_rows(a, steps, galois_keys)
+        })
+    }
+
+    fn rotate_rows_inplace(
+        &self,
+        a: &mut Ciphertext,
+        steps: i32,
+        galois_keys: &GaloisKeys,
+    ) -> Result<()> {
+        self.record(Operation::RotateRows, || {
+            self.inner.rotate_rows_inplace(a, steps, galois_keys)
+        })
+    }
+
+    fn rotate_columns(&self, a: &Ciphertext, galois_keys: &GaloisKeys) -> Result<Ciphertext> {
+        self.record(Operation::RotateColumns, || {
+            self.inner.rotate_columns(a, galois_keys)
+        })
+    }
+
+    fn rotate_columns_inplace(&self, a: &mut Ciphertext, galois_keys: &GaloisKeys) -> Result<()> {
+        self.record(Operation::RotateColumns, || {
+            self.inner.rotate_columns_inplace(a, galois_keys)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn can_profile_operations() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 20).unwrap())
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+        let gen = KeyGenerator::new(&ctx).unwrap();
+        let encoder = BFVEncoder::new(&ctx).unwrap();
+        let encryptor =
+            Encryptor::with_public_key(&ctx, &gen.create_public_key().unwrap()).unwrap();
+        let relin_keys = gen.create_relinearization_keys().unwrap();
+
+        let evaluator = ProfilingEvaluator::new(BFVEvaluator::new(&ctx).unwrap());
+
+        let p = encoder.encode_unsigned(&[1, 2, 3]).unwrap();
+        let a = encryptor.encrypt(&p).unwrap();
+
+        let mut b = evaluator.multiply(&a, &a).unwrap();
+        evaluator.relinearize_inplace(&mut b, &relin_keys).unwrap();
+        evaluator.add_inplace(&mut b, &a).unwrap();
+        evaluator.add(&b, &a).unwrap();
+
+        // Not counted.
+        evaluator.inner().add(&b, &a).unwrap();
+
+        let report = evaluator.report();
+
+        assert_eq!(report.count(Operation::Multiply), 1);
+        assert_eq!(report.count(Operation::Relinearize), 1);
+        assert_eq!(report.count(Operation::Add), 2);
+        assert_eq!(report.count(Operation::RotateRows), 0);
+        assert_eq!(report.operations.len(), 3);
+        assert!(report.total_time() >= report.operations[&Operation::Multiply].total_time);
+
+        let json = serde_json::to_string(&report).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ProfileReport>(&json).unwrap(),
+            report
+        );
+
+        evaluator.reset();
+        assert_eq!(evaluator.report(), ProfileReport::default());
+    }
+}
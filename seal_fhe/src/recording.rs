@@ -0,0 +1,754 @@
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::sync::{Mutex, MutexGuard};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::*;
+use crate::{Ciphertext, Evaluator, GaloisKeys, Plaintext, RelinearizationKeys};
+
+/**
+ * Identifies a value in a [`Recording`]: the index of the
+ * [`RecordedOp`] that produced it.
+ */
+pub type ValueId = usize;
+
+/**
+ * An operation in a [`Recording`]. Each operation produces one value,
+ * whose [`ValueId`] is the operation's index in
+ * [`Recording::ops`], and refers to its operands by their IDs.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecordedOp {
+    /// The next ciphertext input.
+    CiphertextInput,
+
+    /// The next plaintext input.
+    PlaintextInput,
+
+    /// [`Evaluator::negate`].
+    Negate(ValueId),
+
+    /// [`Evaluator::add`].
+    Add(ValueId, ValueId),
+
+    /// [`Evaluator::add_many`].
+    AddMany(Vec<ValueId>),
+
+    /// [`Evaluator::sub`].
+    Sub(ValueId, ValueId),
+
+    /// [`Evaluator::multiply`].
+    Multiply(ValueId, ValueId),
+
+    /// [`Evaluator::multiply_many`].
+    MultiplyMany(Vec<ValueId>),
+
+    /// [`Evaluator::square`].
+    Square(ValueId),
+
+    /// [`Evaluator::exponentiate`] by the given exponent.
+    Exponentiate(ValueId, u64),
+
+    /// [`Evaluator::add_plain`].
+    AddPlain(ValueId, ValueId),
+
+    /// [`Evaluator::sub_plain`].
+    SubPlain(ValueId, ValueId),
+
+    /// [`Evaluator::plain_sub`], with the plaintext first.
+    PlainSub(ValueId, ValueId),
+
+    /// [`Evaluator::multiply_plain`].
+    MultiplyPlain(ValueId, ValueId),
+
+    /// [`Evaluator::relinearize`].
+    Relinearize(ValueId),
+
+    /// [`Evaluator::rotate_rows`] by the given number of steps.
+    RotateRows(ValueId, i32),
+
+    /// [`Evaluator::rotate_columns`].
+    RotateColumns(ValueId),
+
+    /// [`Evaluator::mod_switch_to_next`].
+    ModSwitch(ValueId),
+
+    /// [`Evaluator::mod_switch_to_next_plaintext`].
+    ModSwitchPlaintext(ValueId),
+}
+
+/**
+ * A value passed to or produced by [`Recording::replay`].
+ */
+#[derive(Clone, PartialEq)]
+pub enum RecordedValue {
+    /// A ciphertext.
+    Ciphertext(Ciphertext),
+
+    /// A plaintext.
+    Plaintext(Plaintext),
+}
+
+/**
+ * The operations a [`RecordingEvaluator`] ran, in order, as a portable
+ * list that can be serialized (e.g. as a golden file) and replayed
+ * against other inputs or evaluators.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Recording {
+    /**
+     * The operations. Inputs appear as [`RecordedOp::CiphertextInput`] and
+     * [`RecordedOp::PlaintextInput`] in the order they were first used.
+     */
+    pub ops: Vec<RecordedOp>,
+}
+
+impl Recording {
+    /**
+     * Runs the recorded operations on `evaluator` and returns every value,
+     * indexed by [`ValueId`].
+     *
+     * `inputs` replace the recorded inputs in order. Pass the keys the
+     * recording needs: relinearization keys for relinearizing,
+     * multiplying many ciphertexts and exponentiating, and Galois keys for
+     * rotating.
+     *
+     * Returns [`Error::InvalidArgument`] if the inputs don't match the
+     * recorded ones in number or kind, a needed key is missing or the
+     * recording is malformed.
+     */
+    pub fn replay<E>(
+        &self,
+        evaluator: &E,
+        inputs: Vec<RecordedValue>,
+        relin_keys: Option<&RelinearizationKeys>,
+        galois_keys: Option<&GaloisKeys>,
+    ) -> Result<Vec<RecordedValue>>
+    where
+        E: Evaluator,
+    {
+        let mut inputs = inputs.into_iter();
+        let mut values: Vec<RecordedValue> = Vec::with_capacity(self.ops.len());
+
+        fn ct(values: &[RecordedValue], id: ValueId) -> Result<&Ciphertext> {
+            match values.get(id) {
+                Some(RecordedValue::Ciphertext(c)) => Ok(c),
+                _ => Err(Error::InvalidArgument),
+            }
+        }
+
+        fn pt(values: &[RecordedValue], id: ValueId) -> Result<&Plaintext> {
+            match values.get(id) {
+                Some(RecordedValue::Plaintext(p)) => Ok(p),
+                _ => Err(Error::InvalidArgument),
+            }
+        }
+
+        fn cts(values: &[RecordedValue], ids: &[ValueId]) -> Result<Vec<Ciphertext>> {
+            ids.iter().map(|id| ct(values, *id).cloned()).collect()
+        }
+
+        let relin = || relin_keys.ok_or(Error::InvalidArgument);
+        let galois = || galois_keys.ok_or(Error::InvalidArgument);
+
+        for op in &self.ops {
+            let v = &values;
+
+            let value = match op {
+                RecordedOp::CiphertextInput => match inputs.next() {
+                    Some(c @ RecordedValue::Ciphertext(_)) => c,
+                    _ => return Err(Error::InvalidArgument),
+                },
+                RecordedOp::PlaintextInput => match inputs.next() {
+                    Some(p @ RecordedValue::Plaintext(_)) => p,
+                    _ => return Err(Error::InvalidArgument),
+                },
+                RecordedOp::Negate(a) => RecordedValue::Ciphertext(evaluator.negate(ct(v, *a)?)?),
+                RecordedOp::Add(a, b) => {
+                    RecordedValue::Ciphertext(evaluator.add(ct(v, *a)?, ct(v, *b)?)?)
+                }
+                RecordedOp::AddMany(a) => {
+                    RecordedValue::Ciphertext(evaluator.add_many(&cts(v, a)?)?)
+                }
+                RecordedOp::Sub(a, b) => {
+                    RecordedValue::Ciphertext(evaluator.sub(ct(v, *a)?, ct(v, *b)?)?)
+                }
+                RecordedOp::Multiply(a, b) => {
+                    RecordedValue::Ciphertext(evaluator.multiply(ct(v, *a)?, ct(v, *b)?)?)
+                }
+                RecordedOp::MultiplyMany(a) => {
+                    RecordedValue::Ciphertext(evaluator.multiply_many(&cts(v, a)?, relin()?)?)
+                }
+                RecordedOp::Square(a) => RecordedValue::Ciphertext(evaluator.square(ct(v, *a)?)?),
+                RecordedOp::Exponentiate(a, e) => {
+                    RecordedValue::Ciphertext(evaluator.exponentiate(ct(v, *a)?, *e, relin()?)?)
+                }
+                RecordedOp::AddPlain(a, b) => {
+                    RecordedValue::Ciphertext(evaluator.add_plain(ct(v, *a)?, pt(v, *b)?)?)
+                }
+                RecordedOp::SubPlain(a, b) => {
+                    RecordedValue::Ciphertext(evaluator.sub_plain(ct(v, *a)?, pt(v, *b)?)?)
+                }
+                RecordedOp::PlainSub(a, b) => {
+                    RecordedValue::Ciphertext(evaluator.plain_sub(pt(v, *a)?, ct(v, *b)?)?)
+                }
+                RecordedOp::MultiplyPlain(a, b) => {
+                    RecordedValue::Ciphertext(evaluator.multiply_plain(ct(v, *a)?, pt(v, *b)?)?)
+                }
+                RecordedOp::Relinearize(a) => {
+                    RecordedValue::Ciphertext(evaluator.relinearize(ct(v, *a)?, relin()?)?)
+                }
+                RecordedOp::RotateRows(a, steps) => RecordedValue::Ciphertext(
+                    evaluator.rotate_rows(ct(v, *a)?, *steps, galois()?)?,
+                ),
+                RecordedOp::RotateColumns(a) => {
+                    RecordedValue::Ciphertext(evaluator.rotate_columns(ct(v, *a)?, galois()?)?)
+                }
+                RecordedOp::ModSwitch(a) => {
+                    RecordedValue::Ciphertext(evaluator.mod_switch_to_next(ct(v, *a)?)?)
+                }
+                RecordedOp::ModSwitchPlaintext(a) => {
+                    RecordedValue::Plaintext(evaluator.mod_switch_to_next_plaintext(pt(v, *a)?)?)
+                }
+            };
+
+            values.push(value);
+        }
+
+        if inputs.next().is_some() {
+            return Err(Error::InvalidArgument);
+        }
+
+        Ok(values)
+    }
+}
+
+#[derive(Default)]
+struct RecorderState {
+    recording: Recording,
+    // Maps the handle of each ciphertext and plaintext the evaluator has
+    // seen to the ID of the value it currently holds.
+    ids: HashMap<usize, ValueId>,
+}
+
+impl RecorderState {
+    fn push(&mut self, op: RecordedOp, handle: *mut c_void) -> ValueId {
+        let id = self.recording.ops.len();
+
+        self.recording.ops.push(op);
+        self.ids.insert(handle as usize, id);
+
+        id
+    }
+
+    fn id(&mut self, handle: *mut c_void, input: RecordedOp) -> ValueId {
+        match self.ids.get(&(handle as usize)) {
+            Some(id) => *id,
+            None => self.push(input, handle),
+        }
+    }
+
+    fn ct(&mut self, c: &Ciphertext) -> ValueId {
+        self.id(c.get_handle(), RecordedOp::CiphertextInput)
+    }
+
+    fn pt(&mut self, p: &Plaintext) -> ValueId {
+        self.id(p.get_handle(), RecordedOp::PlaintextInput)
+    }
+}
+
+/**
+ * An [`Evaluator`] that forwards each operation to another evaluator and
+ * records it in a [`Recording`], e.g. to save a pipeline as a golden file
+ * for regression tests or to attach to a bug report.
+ *
+ * # Remarks
+ * Values are tracked by the address of the underlying SEAL object, and
+ * ciphertexts and plaintexts the evaluator hasn't seen become inputs.
+ * SEAL may reuse the address of a dropped object for a new one, so
+ * register values created outside the evaluator (e.g. fresh encryptions)
+ * with [`input`](Self::input) or [`plain_input`](Self::plain_input) before
+ * their first use. Failed operations aren't recorded, and keys aren't
+ * recorded at all.
+ */
+pub struct RecordingEvaluator<E> {
+    inner: E,
+    state: Mutex<RecorderState>,
+}
+
+impl<E> RecordingEvaluator<E>
+where
+    E: Evaluator,
+{
+    /**
+     * Creates a recording evaluator that forwards operations to `inner`.
+     */
+    pub fn new(inner: E) -> Self {
+        Self {
+            inner,
+            state: Mutex::new(RecorderState::default()),
+        }
+    }
+
+    /**
+     * Returns the wrapped evaluator. Operations run on it directly aren't
+     * recorded.
+     */
+    pub fn inner(&self) -> &E {
+        &self.inner
+    }
+
+    /**
+     * Records `c` as the next ciphertext input and returns its ID.
+     */
+    pub fn input(&self, c: &Ciphertext) -> ValueId {
+        self.lock()
+            .push(RecordedOp::CiphertextInput, c.get_handle())
+    }
+
+    /**
+     * Records `p` as the next plaintext input and returns its ID.
+     */
+    pub fn plain_input(&self, p: &Plaintext) -> ValueId {
+        self.lock().push(RecordedOp::PlaintextInput, p.get_handle())
+    }
+
+    /**
+     * Returns the ID of the value `c` currently holds, or [`None`] if the
+     * evaluator hasn't seen it.
+     */
+    pub fn id_of(&self, c: &Ciphertext) -> Option<ValueId> {
+        self.lock().ids.get(&(c.get_handle() as usize)).copied()
+    }
+
+    /**
+     * Returns a copy of the operations recorded so far.
+     */
+    pub fn recording(&self) -> Recording {
+        self.lock().recording.clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, RecorderState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /**
+     * Runs an out-of-place operation and records it as producing `out`.
+     */
+    fn record<T, F>(&self, op: impl FnOnce(&mut RecorderState) -> RecordedOp, f: F) -> Result<T>
+    where
+        F: FnOnce() -> Result<T>,
+        T: Handle,
+    {
+        let out = f()?;
+
+        let mut state = self.lock();
+        let op = op(&mut *state);
+        state.push(op, out.handle());
+
+        Ok(out)
+    }
+
+    /**
+     * Runs an in-place operation on `a` and records it as producing a new
+     * value in `a`.
+     */
+    fn record_inplace<T>(
+        &self,
+        a: &mut T,
+        op: impl FnOnce(&mut RecorderState, &T) -> RecordedOp,
+        f: impl FnOnce(&mut T) -> Result<()>,
+    ) -> Result<()>
+    where
+        T: Handle,
+    {
+        // Operands must be resolved before `a` is overwritten.
+        let op = op(&mut *self.lock(), a);
+
+        f(a)?;
+
+        self.lock().push(op, a.handle());
+
+        Ok(())
+    }
+}
+
+trait Handle {
+    fn handle(&self) -> *mut c_void;
+}
+
+impl Handle for Ciphertext {
+    fn handle(&self) -> *mut c_void {
+        self.get_handle()
+    }
+}
+
+impl Handle for Plaintext {
+    fn handle(&self) -> *mut c_void {
+        self.get_handle()
+    }
+}
+
+impl<E> Evaluator for RecordingEvaluator<E>
+where
+    E: Evaluator,
+{
+    fn negate_inplace(&self, a: &mut Ciphertext) -> Result<()> {
+        self.record_inplace(
+            a,
+            |s, a| RecordedOp::Negate(s.ct(a)),
+            |a| self.inner.negate_inplace(a),
+        )
+    }
+
+    fn negate(&self, a: &Ciphertext) -> Result<Ciphertext> {
+        self.record(|s| RecordedOp::Negate(s.ct(a)), || self.inner.negate(a))
+    }
+
+    fn add_inplace(&self, a: &mut Ciphertext, b: &Ciphertext) -> Result<()> {
+        self.record_inplace(
+            a,
+            |s, a| RecordedOp::Add(s.ct(a), s.ct(b)),
+            |a| self.inner.add_inplace(a, b),
+        )
+    }
+
+    fn add(&self, a: &Ciphertext, b: &Ciphertext) -> Result<Ciphertext> {
+        self.record(
+            |s| RecordedOp::Add(s.ct(a), s.ct(b)),
+            || self.inner.add(a, b),
+        )
+    }
+
+    fn add_many(&self, a: &[Ciphertext]) -> Result<Ciphertext> {
+        self.record(
+            |s| RecordedOp::AddMany(a.iter().map(|c| s.ct(c)).collect()),
+            || self.inner.add_many(a),
+        )
+    }
+
+    fn multiply_many(
+        &self,
+        a: &[Ciphertext],
+        relin_keys: &RelinearizationKeys,
+    ) -> Result<Ciphertext> {
+        self.record(
+            |s| RecordedOp::MultiplyMany(a.iter().map(|c| s.ct(c)).collect()),
+            || self.inner.multiply_many(a, relin_keys),
+        )
+    }
+
+    fn sub_inplace(&self, a: &mut Ciphertext, b: &Ciphertext) -> Result<()> {
+        self.record_inplace(
+            a,
+            |s, a| RecordedOp::Sub(s.ct(a), s.ct(b)),
+            |a| self.inner.sub_inplace(a, b),
+        )
+    }
+
+    fn sub(&self, a: &Ciphertext, b: &Ciphertext) -> Result<Ciphertext> {
+        self.record(
+            |s| RecordedOp::Sub(s.ct(a), s.ct(b)),
+            || self.inner.sub(a, b),
+        )
+    }
+
+    fn multiply_inplace(&self, a: &mut Ciphertext, b: &Ciphertext) -> Result<()> {
+        self.record_inplace(
+            a,
+            |s, a| RecordedOp::Multiply(s.ct(a), s.ct(b)),
+            |a| self.inner.multiply_inplace(a, b),
+        )
+    }
+
+    fn multiply(&self, a: &Ciphertext, b: &Ciphertext) -> Result<Ciphertext> {
+        self.record(
+            |s| RecordedOp::Multiply(s.ct(a), s.ct(b)),
+            || self.inner.multiply(a, b),
+        )
+    }
+
+    fn square_inplace(&self, a: &mut Ciphertext) -> Result<()> {
+        self.record_inplace(
+            a,
+            |s, a| RecordedOp::Square(s.ct(a)),
+            |a| self.inner.square_inplace(a),
+        )
+    }
+
+    fn square(&self, a: &Ciphertext) -> Result<Ciphertext> {
+        self.record(|s| RecordedOp::Square(s.ct(a)), || self.inner.square(a))
+    }
+
+    fn mod_switch_to_next(&self, a: &Ciphertext) -> Result<Ciphertext> {
+        self.record(
+            |s| RecordedOp::ModSwitch(s.ct(a)),
+            || self.inner.mod_switch_to_next(a),
+        )
+    }
+
+    fn mod_switch_to_next_inplace(&self, a: &mut Ciphertext) -> Result<()> {
+        self.record_inplace(
+            a,
+            |s, a| RecordedOp::ModSwitch(s.ct(a)),
+            |a| self.inner.mod_switch_to_next_inplace(a),
+        )
+    }
+
+    fn mod_switch_to_next_plaintext(&self, a: &Plaintext) -> Result<Plaintext> {
+        self.record(
+            |s| RecordedOp::ModSwitchPlaintext(s.pt(a)),
+            || self.inner.mod_switch_to_next_plaintext(a),
+        )
+    }
+
+    fn mod_switch_to_next_inplace_plaintext(&self, a: &mut Plaintext) -> Result<()> {
+        self.record_inplace(
+            a,
+            |s, a| RecordedOp::ModSwitchPlaintext(s.pt(a)),
+            |a| self.inner.mod_switch_to_next_inplace_plaintext(a),
+        )
+    }
+
+    fn exponentiate(
+        &self,
+        a: &Ciphertext,
+        exponent: u64,
+        relin_keys: &RelinearizationKeys,
+    ) -> Result<Ciphertext> {
+        self.record(
+            |s| RecordedOp::Exponentiate(s.ct(a), exponent),
+            || self.inner.exponentiate(a, exponent, relin_keys),
+        )
+    }
+
+    fn exponentiate_inplace(
+        &self,
+        a: &mut Ciphertext,
+        exponent: u64,
+        relin_keys: &RelinearizationKeys,
+    ) -> Result<()> {
+        self.record_inplace(
+            a,
+            |s, a| RecordedOp::Exponentiate(s.ct(a), exponent),
+            |a| self.inner.exponentiate_inplace(a, exponent, relin_keys),
+        )
+    }
+
+    fn add_plain(&self, a: &Ciphertext, b: &Plaintext) -> Result<Ciphertext> {
+        self.record(
+            |s| RecordedOp::AddPlain(s.ct(a), s.pt(b)),
+            || self.inner.add_plain(a, b),
+        )
+    }
+
+    fn add_plain_inplace(&self, a: &mut Ciphertext, b: &Plaintext) -> Result<()> {
+        self.record_inplace(
+            a,
+            |s, a| RecordedOp::AddPlain(s.ct(a), s.pt(b)),
+            |a| self.inner.add_plain_inplace(a, b),
+        )
+    }
+
+    fn sub_plain(&self, a: &Ciphertext, b: &Plaintext) -> Result<Ciphertext> {
+        self.record(
+            |s| RecordedOp::SubPlain(s.ct(a), s.pt(b)),
+            || self.inner.sub_plain(a, b),
+        )
+    }
+
+    fn sub_plain_inplace(&self, a: &mut Ciphertext, b: &Plaintext) -> Result<()> {
+        self.record_inplace(
+            a,
+            |s, a| RecordedOp::SubPlain(s.ct(a), s.pt(b)),
+            |a| self.inner.sub_plain_inplace(a, b),
+        )
+    }
+
+    fn plain_sub(&self, a: &Plaintext, b: &Ciphertext) -> Result<Ciphertext> {
+        self.record(
+            |s| RecordedOp::PlainSub(s.pt(a), s.ct(b)),
+            || self.inner.plain_sub(a, b),
+        )
+    }
+
+    fn plain_sub_inplace(&self, a: &Plaintext, b: &mut Ciphertext) -> Result<()> {
+        self.record_inplace(
+            b,
+            |s, b| RecordedOp::PlainSub(s.pt(a), s.ct(b)),
+            |b| self.inner.plain_sub_inplace(a, b),
+        )
+    }
+
+    fn multiply_plain(&self, a: &Ciphertext, b: &Plaintext) -> Result<Ciphertext> {
+        self.record(
+            |s| RecordedOp::MultiplyPlain(s.ct(a), s.pt(b)),
+            || self.inner.multiply_plain(a, b),
+        )
+    }
+
+    fn multiply_plain_inplace(&self, a: &mut Ciphertext, b: &Plaintext) -> Result<()> {
+        self.record_inplace(
+            a,
+            |s, a| RecordedOp::MultiplyPlain(s.ct(a), s.pt(b)),
+            |a| self.inner.multiply_plain_inplace(a, b),
+        )
+    }
+
+    fn relinearize_inplace(
+        &self,
+        a: &mut Ciphertext,
+        relin_keys: &RelinearizationKeys,
+    ) -> Result<()> {
+        self.record_inplace(
+            a,
+            |s, a| RecordedOp::Relinearize(s.ct(a)),
+            |a| self.inner.relinearize_inplace(a, relin_keys),
+        )
+    }
+
+    fn relinearize(&self, a: &Ciphertext, relin_keys: &RelinearizationKeys) -> Result<Ciphertext> {
+        self.record(
+            |s| RecordedOp::Relinearize(s.ct(a)),
+            || self.inner.relinearize(a, relin_keys),
+        )
+    }
+
+    fn rotate_rows(
+        &self,
+        a: &Ciphertext,
+        steps: i32,
+        galois_keys: &GaloisKeys,
+    ) -> Result<Ciphertext> {
+        self.record(
+            |s| RecordedOp::RotateRows(s.ct(a), steps),
+            || self.inner.rotate_rows(a, steps, galois_keys),
+        )
+    }
+
+    fn rotate_rows_inplace(
+        &self,
+        a: &mut Ciphertext,
+        steps: i32,
+        galois_keys: &GaloisKeys,
+    ) -> Result<()> {
+        self.record_inplace(
+            a,
+            |s, a| RecordedOp::RotateRows(s.ct(a), steps),
+            |a| self.inner.rotate_rows_inplace(a, steps, galois_keys),
+        )
+    }
+
+    fn rotate_columns(&self, a: &Ciphertext, galois_keys: &GaloisKeys) -> Result<Ciphertext> {
+        self.record(
+            |s| RecordedOp::RotateColumns(s.ct(a)),
+            || self.inner.rotate_columns(a, galois_keys),
+        )
+    }
+
+    fn rotate_columns_inplace(&self, a: &mut Ciphertext, galois_keys: &GaloisKeys) -> Result<()> {
+        self.record_inplace(
+            a,
+            |s, a| RecordedOp::RotateColumns(s.ct(a)),
+            |a| self.inner.rotate_columns_inplace(a, galois_keys),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn can_record_and_replay() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 20).unwrap())
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+        let gen = KeyGenerator::new(&ctx).unwrap();
+        let encoder = BFVEncoder::new(&ctx).unwrap();
+        let encryptor =
+            Encryptor::with_public_key(&ctx, &gen.create_public_key().unwrap()).unwrap();
+        let decryptor = Decryptor::new(&ctx, &gen.secret_key().unwrap()).unwrap();
+        let relin_keys = gen.create_relinearization_keys().unwrap();
+
+        let evaluator = RecordingEvaluator::new(BFVEvaluator::new(&ctx).unwrap());
+
+        let a = encryptor
+            .encrypt(&encoder.encode_unsigned(&[2, 3]).unwrap())
+            .unwrap();
+        let b = encoder.encode_unsigned(&[5, 7]).unwrap();
+
+        assert_eq!(evaluator.input(&a), 0);
+        assert_eq!(evaluator.plain_input(&b), 1);
+
+        // (a * a + b) * a
+        let mut c = evaluator.multiply(&a, &a).unwrap();
+        evaluator.relinearize_inplace(&mut c, &relin_keys).unwrap();
+        evaluator.add_plain_inplace(&mut c, &b).unwrap();
+        let d = evaluator.multiply(&c, &a).unwrap();
+
+        let recording = evaluator.recording();
+
+        assert_eq!(
+            recording.ops,
+            vec![
+                RecordedOp::CiphertextInput,
+                RecordedOp::PlaintextInput,
+                RecordedOp::Multiply(0, 0),
+                RecordedOp::Relinearize(2),
+                RecordedOp::AddPlain(3, 1),
+                RecordedOp::Multiply(4, 0),
+            ]
+        );
+        assert_eq!(evaluator.id_of(&c), Some(4));
+        assert_eq!(evaluator.id_of(&d), Some(5));
+
+        let json = serde_json::to_string(&recording).unwrap();
+        let recording: Recording = serde_json::from_str(&json).unwrap();
+
+        // Replay on new inputs.
+        let x = encryptor
+            .encrypt(&encoder.encode_unsigned(&[4, 1]).unwrap())
+            .unwrap();
+        let y = encoder.encode_unsigned(&[1, 1]).unwrap();
+
+        let replay_evaluator = BFVEvaluator::new(&ctx).unwrap();
+
+        let values = recording
+            .replay(
+                &replay_evaluator,
+                vec![RecordedValue::Ciphertext(x), RecordedValue::Plaintext(y)],
+                Some(&relin_keys),
+                None,
+            )
+            .unwrap();
+
+        let out = match &values[5] {
+            RecordedValue::Ciphertext(c) => c,
+            _ => panic!("Expected a ciphertext"),
+        };
+
+        let out = encoder
+            .decode_unsigned(&decryptor.decrypt(out).unwrap())
+            .unwrap();
+
+        assert_eq!(&out[..2], &[68, 2]);
+
+        // Missing keys and inputs are rejected.
+        assert_eq!(
+            recording
+                .replay(&replay_evaluator, vec![], Some(&relin_keys), None)
+                .err(),
+            Some(Error::InvalidArgument)
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use std::borrow::Cow;
+use std::convert::Infallible;
+
+use crate::error::*;
+use crate::{Ciphertext, Evaluator, RelinearizationKeys};
+
+/**
+ * The shape of the tree used to combine many values with an associative
+ * operation, e.g. summing or multiplying a list of ciphertexts.
+ *
+ * # Remarks
+ * The result is the same either way, but the cost isn't. Combining `n`
+ * values always takes `n - 1` operations, while the tree's shape decides
+ * how deep they're nested:
+ *
+ * * [`Balanced`](Self::Balanced) combines neighbouring pairs level by
+ *   level, nesting `ceil(log2(n))` deep. Products consume that many
+ *   multiplicative levels instead of `n - 1`, and noise grows much more
+ *   slowly. Each level's operations are independent of each other.
+ * * [`LeftFold`](Self::LeftFold) combines values one at a time into a
+ *   running result, nesting `n - 1` deep. It holds a single intermediate
+ *   value, so it suits streaming inputs. For sums, where noise grows
+ *   additively, it's no noisier than a balanced tree.
+ *
+ * Besides [`add`](Self::add) and [`multiply`](Self::multiply) on
+ * ciphertexts, [`fold`](Self::fold) applies the same shape to any values,
+ * e.g. to nodes inside an FHE program so the compiled program has the
+ * chosen depth.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReductionTree {
+    /**
+     * Combine neighbouring pairs, halving the number of values each round.
+     */
+    Balanced,
+
+    /**
+     * Combine each value into a running result, left to right.
+     */
+    LeftFold,
+}
+
+impl ReductionTree {
+    /**
+     * Combines `items` with `op` in this tree's shape, stopping at the
+     * first error. Returns `None` if `items` is empty.
+     */
+    pub fn try_fold<T, F, E>(self, items: Vec<T>, mut op: F) -> std::result::Result<Option<T>, E>
+    where
+        F: FnMut(T, T) -> std::result::Result<T, E>,
+    {
+        match self {
+            Self::Balanced => {
+                let mut level = items;
+
+                while level.len() > 1 {
+                    let mut next = Vec::with_capacity((level.len() + 1) / 2);
+                    let mut values = level.into_iter();
+
+                    while let Some(a) = values.next() {
+                        match values.next() {
+                            Some(b) => next.push(op(a, b)?),
+                            None => next.push(a),
+                        }
+                    }
+
+                    level = next;
+                }
+
+                Ok(level.pop())
+            }
+            Self::LeftFold => {
+                let mut values = items.into_iter();
+
+                match values.next() {
+                    Some(first) => values.try_fold(first, op).map(Some),
+                    None => Ok(None),
+                }
+            }
+        }
+    }
+
+    /**
+     * Combines `items` with `op` in this tree's shape. Returns `None` if
+     * `items` is empty.
+     */
+    pub fn fold<T, F>(self, items: Vec<T>, mut op: F) -> Option<T>
+    where
+        F: FnMut(T, T) -> T,
+    {
+        match self.try_fold(items, |a, b| Ok::<_, Infallible>(op(a, b))) {
+            Ok(x) => x,
+            Err(e) => match e {},
+        }
+    }
+
+    /**
+     * Sums the given ciphertexts.
+     *
+     * Returns [`Error::InvalidArgument`] if `a` is empty.
+     */
+    pub fn add<E>(self, evaluator: &E, a: &[Ciphertext]) -> Result<Ciphertext>
+    where
+        E: Evaluator,
+    {
+        let items = a.iter().map(Cow::Borrowed).collect();
+
+        self.try_fold(items, |x, y| evaluator.add(&x, &y).map(Cow::Owned))?
+            .map(Cow::into_owned)
+            .ok_or(Error::InvalidArgument)
+    }
+
+    /**
+     * Multiplies the given ciphertexts, relinearizing after each
+     * multiplication.
+     *
+     * Returns [`Error::InvalidArgument`] if `a` is empty.
+     */
+    pub fn multiply<E>(
+        self,
+        evaluator: &E,
+        a: &[Ciphertext],
+        relin_keys: &RelinearizationKeys,
+    ) -> Result<Ciphertext>
+    where
+        E: Evaluator,
+    {
+        let items = a.iter().map(Cow::Borrowed).collect();
+
+        self.try_fold(items, |x, y| {
+            let mut product = evaluator.multiply(&x, &y)?;
+            evaluator.relinearize_inplace(&mut product, relin_keys)?;
+
+            Ok(Cow::Owned(product))
+        })?
+        .map(Cow::into_owned)
+        .ok_or(Error::InvalidArgument)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn trees_have_expected_shape() {
+        let items = || {
+            ["a", "b", "c", "d", "e"]
+                .iter()
+                .map(|x| x.to_string())
+                .collect()
+        };
+        let op = |a: String, b: String| format!("({}{})", a, b);
+
+        assert_eq!(
+            ReductionTree::Balanced.fold(items(), op).unwrap(),
+            "(((ab)(cd))e)"
+        );
+        assert_eq!(
+            ReductionTree::LeftFold.fold(items(), op).unwrap(),
+            "((((ab)c)d)e)"
+        );
+        assert_eq!(
+            ReductionTree::Balanced.fold(Vec::<u32>::new(), |a, b| a + b),
+            None
+        );
+    }
+
+    #[test]
+    fn can_reduce_ciphertexts() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 20).unwrap())
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+        let gen = KeyGenerator::new(&ctx).unwrap();
+        let encoder = BFVEncoder::new(&ctx).unwrap();
+        let encryptor =
+            Encryptor::with_public_key(&ctx, &gen.create_public_key().unwrap()).unwrap();
+        let decryptor = Decryptor::new(&ctx, &gen.secret_key().unwrap()).unwrap();
+        let evaluator = BFVEvaluator::new(&ctx).unwrap();
+        let relin_keys = gen.create_relinearization_keys().unwrap();
+
+        let values = (1..=4)
+            .map(|x| {
+                encryptor
+                    .encrypt(&encoder.encode_unsigned(&[x]).unwrap())
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let decrypt = |c: &Ciphertext| {
+            encoder
+                .decode_unsigned(&decryptor.decrypt(c).unwrap())
+                .unwrap()[0]
+        };
+
+        for tree in [ReductionTree::Balanced, ReductionTree::LeftFold] {
+            assert_eq!(decrypt(&tree.add(&evaluator, &values).unwrap()), 10);
+            assert_eq!(
+                decrypt(&tree.multiply(&evaluator, &values, &relin_keys).unwrap()),
+                24
+            );
+            assert!(tree.add(&evaluator, &[]).is_err());
+        }
+    }
+}
@@ -0,0 +1,352 @@
+//! Arithmetic on polynomials in RNS form, for protocols that work on the
+//! coefficients of ciphertexts directly (see [`Ciphertext::polynomial`](crate::Ciphertext::polynomial)).
+//!
+//! A polynomial in `Z_q[X] / (X^n + 1)` is held as one block of `n`
+//! coefficients per prime `q_j` of the coefficient modulus. Everything
+//! here works on a single block at a time and assumes the moduli are the
+//! NTT-friendly primes SEAL uses, i.e. `q_j = 1 mod 2n` and below 2^62.
+
+use crate::error::*;
+
+/**
+ * Returns `(a + b) mod q` for `a, b < q`.
+ */
+pub fn add_mod(a: u64, b: u64, q: u64) -> u64 {
+    let sum = a + b;
+
+    if sum >= q {
+        sum - q
+    } else {
+        sum
+    }
+}
+
+/**
+ * Returns `(a - b) mod q` for `a, b < q`.
+ */
+pub fn sub_mod(a: u64, b: u64, q: u64) -> u64 {
+    if a >= b {
+        a - b
+    } else {
+        a + q - b
+    }
+}
+
+/**
+ * Returns `a * b mod q`.
+ */
+pub fn mul_mod(a: u64, b: u64, q: u64) -> u64 {
+    ((a as u128 * b as u128) % q as u128) as u64
+}
+
+/**
+ * Returns `base^exponent mod q`.
+ */
+pub fn pow_mod(mut base: u64, mut exponent: u64, q: u64) -> u64 {
+    let mut result = 1 % q;
+
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base, q);
+        }
+
+        base = mul_mod(base, base, q);
+        exponent >>= 1;
+    }
+
+    result
+}
+
+/**
+ * Returns the inverse of `a` modulo the prime `q`.
+ */
+pub fn inv_mod(a: u64, q: u64) -> u64 {
+    pow_mod(a, q - 2, q)
+}
+
+/**
+ * Returns `x mod q` in `[0, q)`.
+ */
+pub fn reduce_signed(x: i64, q: u64) -> u64 {
+    (x as i128).rem_euclid(q as i128) as u64
+}
+
+/**
+ * Returns a primitive `2n`-th root of unity modulo the prime `q`.
+ *
+ * Returns [`Error::InvalidArgument`] if `n` isn't a power of two or `q`
+ * isn't a prime with `q = 1 mod 2n`.
+ */
+pub fn primitive_root(n: usize, q: u64) -> Result<u64> {
+    if !n.is_power_of_two() || q < 3 || (q - 1) % (2 * n as u64) != 0 {
+        return Err(Error::InvalidArgument);
+    }
+
+    let exponent = (q - 1) / (2 * n as u64);
+
+    // Since 2n is a power of two, psi has order 2n iff psi^n = -1. A
+    // composite q fails this for small candidates, so give up rather than
+    // search forever.
+    (2..q.min(1 << 16))
+        .map(|g| pow_mod(g, exponent, q))
+        .find(|psi| pow_mod(*psi, n as u64, q) == q - 1)
+        .ok_or(Error::InvalidArgument)
+}
+
+/**
+ * Returns the smallest primitive `2n`-th root of unity modulo the prime
+ * `q`, which is the root SEAL builds its NTT tables, and thus its batch
+ * encoder, from.
+ *
+ * Returns [`Error::InvalidArgument`] under the same conditions as
+ * [`primitive_root`].
+ */
+pub fn minimal_primitive_root(n: usize, q: u64) -> Result<u64> {
+    let psi = primitive_root(n, q)?;
+    let psi_squared = mul_mod(psi, psi, q);
+
+    // The primitive roots are exactly the odd powers of any one of them.
+    let mut root = psi;
+    let mut minimal = psi;
+
+    for _ in 1..n {
+        root = mul_mod(root, psi_squared, q);
+        minimal = minimal.min(root);
+    }
+
+    Ok(minimal)
+}
+
+/**
+ * Precomputed roots of unity for multiplying polynomials of degree `n`
+ * modulo a prime `q` with the number theoretic transform.
+ *
+ * # Remarks
+ * The forward transform twists its input by powers of a primitive `2n`-th
+ * root of unity, so pointwise products of transformed polynomials are
+ * negacyclic products in `Z_q[X] / (X^n + 1)`. The transformed
+ * coefficients are in no particular order and don't match SEAL's NTT
+ * form, so only pass them to [`inverse`](Self::inverse).
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NttTables {
+    q: u64,
+    omega: u64,
+    omega_inv: u64,
+    psi_powers: Vec<u64>,
+    // psi^-i / n, which the inverse transform untwists by.
+    psi_inv_powers: Vec<u64>,
+}
+
+impl NttTables {
+    /**
+     * Creates tables for polynomials with `n` coefficients modulo `q`.
+     *
+     * Returns [`Error::InvalidArgument`] if `n` isn't a power of two or
+     * `q` isn't a prime with `q = 1 mod 2n`.
+     */
+    pub fn new(n: usize, q: u64) -> Result<Self> {
+        let psi = primitive_root(n, q)?;
+        let psi_inv = inv_mod(psi, q);
+        let n_inv = inv_mod(n as u64 % q, q);
+
+        let powers = |base: u64, start: u64| {
+            let mut power = start;
+
+            (0..n)
+                .map(|_| {
+                    let p = power;
+                    power = mul_mod(power, base, q);
+                    p
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let omega = mul_mod(psi, psi, q);
+
+        Ok(Self {
+            q,
+            omega,
+            omega_inv: inv_mod(omega, q),
+            psi_powers: powers(psi, 1),
+            psi_inv_powers: powers(psi_inv, n_inv),
+        })
+    }
+
+    /**
+     * The number of coefficients in the polynomials these tables
+     * transform.
+     */
+    pub fn degree(&self) -> usize {
+        self.psi_powers.len()
+    }
+
+    /**
+     * The modulus.
+     */
+    pub fn modulus(&self) -> u64 {
+        self.q
+    }
+
+    /**
+     * Transforms the polynomial `a` in place.
+     *
+     * # Panics
+     * If `a` doesn't have [`degree`](Self::degree) coefficients.
+     */
+    pub fn forward(&self, a: &mut [u64]) {
+        assert_eq!(a.len(), self.degree(), "Polynomial has the wrong degree");
+
+        for (x, p) in a.iter_mut().zip(self.psi_powers.iter()) {
+            *x = mul_mod(*x, *p, self.q);
+        }
+
+        cyclic_ntt(a, self.omega, self.q);
+    }
+
+    /**
+     * Undoes [`forward`](Self::forward) in place.
+     *
+     * # Panics
+     * If `a` doesn't have [`degree`](Self::degree) coefficients.
+     */
+    pub fn inverse(&self, a: &mut [u64]) {
+        assert_eq!(a.len(), self.degree(), "Polynomial has the wrong degree");
+
+        cyclic_ntt(a, self.omega_inv, self.q);
+
+        for (x, p) in a.iter_mut().zip(self.psi_inv_powers.iter()) {
+            *x = mul_mod(*x, *p, self.q);
+        }
+    }
+
+    /**
+     * Returns the negacyclic product of `a` and `b`.
+     *
+     * # Panics
+     * If `a` or `b` doesn't have [`degree`](Self::degree) coefficients.
+     */
+    pub fn multiply(&self, a: &[u64], b: &[u64]) -> Vec<u64> {
+        let mut a = a.to_owned();
+        let mut b = b.to_owned();
+
+        self.forward(&mut a);
+        self.forward(&mut b);
+
+        for (a, b) in a.iter_mut().zip(b.iter()) {
+            *a = mul_mod(*a, *b, self.q);
+        }
+
+        self.inverse(&mut a);
+
+        a
+    }
+}
+
+/**
+ * Transforms `a`, whose length is a power of two, in place with the
+ * cyclic number theoretic transform for the root of unity `omega`.
+ */
+fn cyclic_ntt(a: &mut [u64], omega: u64, q: u64) {
+    let n = a.len();
+    let mut j = 0;
+
+    for i in 1..n {
+        let mut bit = n >> 1;
+
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+
+        j |= bit;
+
+        if i < j {
+            a.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+
+    while len <= n {
+        let step = pow_mod(omega, (n / len) as u64, q);
+
+        for block in a.chunks_mut(len) {
+            let (lo, hi) = block.split_at_mut(len / 2);
+            let mut w = 1;
+
+            for (u, v) in lo.iter_mut().zip(hi.iter_mut()) {
+                let x = *u;
+                let y = mul_mod(*v, w, q);
+
+                *u = add_mod(x, y, q);
+                *v = sub_mod(x, y, q);
+                w = mul_mod(w, step, q);
+            }
+        }
+
+        len <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // An NTT-friendly prime for n up to 2^14.
+    const Q: u64 = 1_152_921_504_606_584_833;
+
+    #[test]
+    fn multiply_matches_schoolbook() {
+        let n = 16;
+        let a = (0..n as u64).map(|i| (i * 7919) % Q).collect::<Vec<_>>();
+        let b = (0..n as u64)
+            .map(|i| Q - 1 - i * 104_729)
+            .collect::<Vec<_>>();
+
+        let mut expected = vec![0; n];
+
+        for i in 0..n {
+            for j in 0..n {
+                let p = mul_mod(a[i], b[j], Q);
+
+                if i + j < n {
+                    expected[i + j] = add_mod(expected[i + j], p, Q);
+                } else {
+                    expected[i + j - n] = sub_mod(expected[i + j - n], p, Q);
+                }
+            }
+        }
+
+        let tables = NttTables::new(n, Q).unwrap();
+
+        assert_eq!(tables.multiply(&a, &b), expected);
+
+        let mut x = a.clone();
+        tables.forward(&mut x);
+        tables.inverse(&mut x);
+
+        assert_eq!(x, a);
+    }
+
+    #[test]
+    fn finds_minimal_primitive_root() {
+        // 3 generates Z_17^*, so the primitive 8th roots are its powers
+        // 2, 6, 10 and 14, i.e. 9, 15, 8 and 2.
+        assert_eq!(minimal_primitive_root(4, 17), Ok(2));
+
+        let psi = minimal_primitive_root(16, Q).unwrap();
+
+        assert_eq!(pow_mod(psi, 16, Q), Q - 1);
+        assert!((0..16)
+            .map(|i| pow_mod(psi, 2 * i + 1, Q))
+            .all(|root| root >= psi));
+    }
+
+    #[test]
+    fn rejects_unsupported_moduli() {
+        assert_eq!(NttTables::new(12, Q), Err(Error::InvalidArgument));
+        assert_eq!(NttTables::new(16, 65537 * 3), Err(Error::InvalidArgument));
+        assert_eq!(NttTables::new(1 << 16, 65537), Err(Error::InvalidArgument));
+    }
+}
@@ -0,0 +1,156 @@
+use crate::bindgen;
+use crate::error::*;
+
+use serde::{Deserialize, Serialize};
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/**
+ * The compression SEAL applies when serializing an object.
+ *
+ * # Remarks
+ * Loading detects the compression from the serialized header, so only
+ * saving needs to choose one. Compressed objects can only be loaded by a
+ * build of SEAL that supports their compression.
+ */
+pub enum CompressionType {
+    /**
+     * No compression. Always supported.
+     */
+    None = 0,
+
+    /**
+     * zlib compression. Requires the `zlib` feature.
+     */
+    ZLib = 1,
+
+    /**
+     * Zstandard compression, which is faster than zlib and compresses
+     * about as well. Requires the `zstd` feature.
+     */
+    ZStd = 2,
+}
+
+impl Default for CompressionType {
+    /**
+     * Returns the best compression this build supports.
+     */
+    fn default() -> Self {
+        if cfg!(feature = "zstd") {
+            Self::ZStd
+        } else if cfg!(feature = "zlib") {
+            Self::ZLib
+        } else {
+            Self::None
+        }
+    }
+}
+
+impl CompressionType {
+    /**
+     * Returns whether the linked build of SEAL supports this compression.
+     */
+    pub fn is_supported(&self) -> Result<bool> {
+        let mut supported = false;
+
+        convert_seal_error(unsafe {
+            bindgen::Serialization_IsSupportedComprMode(*self as u8, &mut supported)
+        })?;
+
+        Ok(supported)
+    }
+}
+
+/**
+ * Returns the parms_id of the SEAL object serialized in `bytes`, e.g. a
+ * ciphertext or key, without loading it. Use this to find the level of
+ * the modulus switching chain the object needs a context for (see
+ * [`Context::at_level`](crate::Context::at_level)).
+ *
+ * Returns an error if `bytes` doesn't start with a SEAL object.
+ */
+pub fn serialized_parms_id(bytes: &[u8]) -> Result<[u64; 4]> {
+    let mut parms_id = [0u64; 4];
+
+    convert_seal_error(unsafe {
+        bindgen::Shim_Serialization_LoadParmsId(
+            bytes.as_ptr(),
+            bytes.len() as u64,
+            parms_id.as_mut_ptr(),
+        )
+    })?;
+
+    Ok(parms_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn default_compression_is_supported() {
+        assert!(CompressionType::None.is_supported().unwrap());
+        assert!(CompressionType::default().is_supported().unwrap());
+        assert_eq!(
+            CompressionType::ZStd.is_supported().unwrap(),
+            cfg!(feature = "zstd")
+        );
+        assert_eq!(
+            CompressionType::ZLib.is_supported().unwrap(),
+            cfg!(feature = "zlib")
+        );
+    }
+
+    #[test]
+    fn can_save_with_each_compression() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus_u64(1234)
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+        let gen = KeyGenerator::new(&ctx).unwrap();
+
+        let public_key = gen.create_public_key().unwrap();
+        let encryptor = Encryptor::with_public_key(&ctx, &public_key).unwrap();
+        let ciphertext = encryptor
+            .encrypt(&BFVScalarEncoder::new().encode_unsigned(7).unwrap())
+            .unwrap();
+
+        let uncompressed = ciphertext
+            .as_bytes_with_compression(CompressionType::None)
+            .unwrap();
+
+        assert_eq!(
+            ciphertext
+                .save_size_with_compression(CompressionType::None)
+                .unwrap(),
+            uncompressed.len() as u64
+        );
+
+        for compression in [
+            CompressionType::None,
+            CompressionType::ZLib,
+            CompressionType::ZStd,
+        ] {
+            if !compression.is_supported().unwrap() {
+                assert!(ciphertext.as_bytes_with_compression(compression).is_err());
+                continue;
+            }
+
+            let bytes = ciphertext.as_bytes_with_compression(compression).unwrap();
+
+            // The header records the compression.
+            assert_eq!(bytes[5], compression as u8);
+            assert!(Ciphertext::from_bytes(&ctx, &bytes).unwrap() == ciphertext);
+
+            let key_bytes = public_key.as_bytes_with_compression(compression).unwrap();
+
+            assert!(PublicKey::from_bytes(&ctx, &key_bytes).unwrap() == public_key);
+        }
+    }
+}
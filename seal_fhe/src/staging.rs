@@ -0,0 +1,223 @@
+use crate::error::*;
+use crate::BFVEncoder;
+
+/**
+ * A reusable buffer that stages signed integers as batching slots, i.e.
+ * reduces them modulo the plaintext modulus and pads them with zeros, so
+ * [`BFVEncoder::encode_staged`] can pass them to SEAL without further
+ * conversion.
+ *
+ * # Remarks
+ * Encoding many plaintexts through one buffer allocates its slots once,
+ * rather than once per plaintext. Reduction uses AVX2 on x86-64 CPUs that
+ * support it, detected at runtime. Elsewhere, including on AArch64 where
+ * NEON is always available, the compiler vectorizes the same branchless
+ * loop for the target's baseline SIMD extension.
+ */
+pub struct EncodingBuffer {
+    slots: Vec<u64>,
+    plain_modulus: u64,
+}
+
+impl EncodingBuffer {
+    /**
+     * Creates a buffer with `slot_count` slots for the plaintext modulus
+     * `plain_modulus`.
+     *
+     * # Panics
+     * If `plain_modulus` is 0 or doesn't fit in an [`i64`].
+     */
+    pub fn new(slot_count: usize, plain_modulus: u64) -> Self {
+        assert!(
+            plain_modulus > 0 && plain_modulus <= i64::MAX as u64,
+            "Invalid plaintext modulus."
+        );
+
+        Self {
+            slots: vec![0; slot_count],
+            plain_modulus,
+        }
+    }
+
+    /**
+     * Creates a buffer matching `encoder`'s slot count and plaintext
+     * modulus.
+     */
+    pub fn for_encoder(encoder: &BFVEncoder) -> Self {
+        Self::new(encoder.get_slot_count(), encoder.get_plain_modulus())
+    }
+
+    /**
+     * The number of slots in the buffer.
+     */
+    pub fn slot_count(&self) -> usize {
+        self.slots.len()
+    }
+
+    /**
+     * Reduces `data` modulo the plaintext modulus into the first slots
+     * and zeros the rest, returning every slot.
+     *
+     * # Remarks
+     * Negative values map to the plaintext modulus plus the value, as in
+     * [`BFVEncoder::encode_signed`], so values in
+     * `(-plain_modulus / 2, plain_modulus / 2]` round trip through
+     * [`BFVEncoder::decode_signed`]. Larger values wrap around.
+     *
+     * Returns [`Error::InvalidArgument`] if `data` has more values than
+     * there are slots.
+     */
+    pub fn stage(&mut self, data: &[i64]) -> Result<&[u64]> {
+        if data.len() > self.slots.len() {
+            return Err(Error::InvalidArgument);
+        }
+
+        let (head, tail) = self.slots.split_at_mut(data.len());
+
+        reduce_signed(data, head, self.plain_modulus);
+        tail.fill(0);
+
+        Ok(&self.slots)
+    }
+}
+
+/**
+ * Writes each value in `src` modulo `t` to the same position in `dst`.
+ */
+fn reduce_signed(src: &[i64], dst: &mut [u64], t: u64) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") {
+            // Safe because the CPU supports AVX2.
+            let in_range = unsafe { avx2::reduce_signed(src, dst, t) };
+
+            if !in_range {
+                reduce_signed_scalar(src, dst, t);
+            }
+
+            return;
+        }
+    }
+
+    reduce_signed_scalar(src, dst, t);
+}
+
+/**
+ * Values in `(-t, t)` only need `t` added when negative, which compiles
+ * to a compare, a mask and an add per lane. Values outside that range
+ * take the slower exact reduction.
+ */
+fn reduce_signed_scalar(src: &[i64], dst: &mut [u64], t: u64) {
+    let bound = t as i64;
+
+    if src.iter().all(|x| *x > -bound && *x < bound) {
+        for (d, s) in dst.iter_mut().zip(src) {
+            *d = (*s as u64).wrapping_add(t & ((*s >> 63) as u64));
+        }
+    } else {
+        for (d, s) in dst.iter_mut().zip(src) {
+            *d = s.rem_euclid(bound) as u64;
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use std::arch::x86_64::*;
+
+    /**
+     * Like [`reduce_signed_scalar`](super::reduce_signed_scalar) for values
+     * in `(-t, t)`, 4 lanes at a time. Returns whether every value was in
+     * range; if not, `dst` holds garbage and the caller must reduce again.
+     *
+     * # Safety
+     * The CPU must support AVX2.
+     */
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn reduce_signed(src: &[i64], dst: &mut [u64], t: u64) -> bool {
+        let len = src.len().min(dst.len());
+        let vectors = len / 4;
+
+        let zero = _mm256_setzero_si256();
+        let modulus = _mm256_set1_epi64x(t as i64);
+        let max = _mm256_set1_epi64x(t as i64 - 1);
+
+        let mut out_of_range = zero;
+
+        for i in 0..vectors {
+            let x = _mm256_loadu_si256(src.as_ptr().add(4 * i) as *const __m256i);
+
+            let negative = _mm256_cmpgt_epi64(zero, x);
+            let r = _mm256_add_epi64(x, _mm256_and_si256(negative, modulus));
+
+            // In range iff 0 <= r <= t - 1.
+            out_of_range = _mm256_or_si256(
+                out_of_range,
+                _mm256_or_si256(_mm256_cmpgt_epi64(zero, r), _mm256_cmpgt_epi64(r, max)),
+            );
+
+            _mm256_storeu_si256(dst.as_mut_ptr().add(4 * i) as *mut __m256i, r);
+        }
+
+        if _mm256_testz_si256(out_of_range, out_of_range) == 0 {
+            return false;
+        }
+
+        let bound = t as i64;
+
+        for (d, x) in dst[4 * vectors..len].iter_mut().zip(&src[4 * vectors..len]) {
+            if *x <= -bound || *x >= bound {
+                return false;
+            }
+
+            *d = (*x as u64).wrapping_add(t & ((*x >> 63) as u64));
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reduces_values_in_range() {
+        let t = 65537;
+        let src = (-20..20).map(|x| x * 1000).collect::<Vec<i64>>();
+
+        let mut dst = vec![0; src.len()];
+        reduce_signed(&src, &mut dst, t);
+
+        for (d, s) in dst.iter().zip(&src) {
+            assert_eq!(*d, s.rem_euclid(t as i64) as u64);
+        }
+    }
+
+    #[test]
+    fn reduces_values_out_of_range() {
+        let t = 17;
+        let src = [i64::MIN, -35, -17, -1, 0, 16, 17, 100, i64::MAX];
+
+        let mut dst = vec![0; src.len()];
+        reduce_signed(&src, &mut dst, t);
+
+        for (d, s) in dst.iter().zip(&src) {
+            assert_eq!(*d, s.rem_euclid(17) as u64);
+        }
+    }
+
+    #[test]
+    fn stage_pads_with_zeros() {
+        let mut buffer = EncodingBuffer::new(8, 17);
+
+        assert_eq!(
+            buffer.stage(&[1, -1, 5]).unwrap(),
+            &[1, 16, 5, 0, 0, 0, 0, 0]
+        );
+
+        // Slots from earlier stages don't leak into later ones.
+        assert_eq!(buffer.stage(&[-2]).unwrap(), &[15, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(buffer.stage(&[0; 9]), Err(Error::InvalidArgument));
+    }
+}
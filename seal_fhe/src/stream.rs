@@ -0,0 +1,466 @@
+use std::any::Any;
+use std::ffi::c_void;
+use std::io::{Chain, ErrorKind, Read, Take, Write};
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+
+use crate::bindgen;
+use crate::error::*;
+use crate::{
+    serialization::CompressionType, Ciphertext, Context, GaloisKeys, Plaintext, PublicKey,
+    RelinearizationKeys, SecretKey,
+};
+
+/**
+ * The size in bytes of the header SEAL writes at the start of every
+ * serialized object.
+ */
+pub(crate) const SEAL_HEADER_SIZE: usize = 16;
+
+/**
+ * The magic number at the start of every SEAL serialization header.
+ */
+const SEAL_MAGIC: u16 = 0xA15E;
+
+pub(crate) fn io_error(e: std::io::Error) -> Error {
+    Error::SerializationError(Box::new(e.to_string()))
+}
+
+/**
+ * Validates the SEAL serialization header at the start of `bytes` and
+ * returns the total size of the serialized object, header included.
+ */
+pub(crate) fn serialized_size(bytes: &[u8]) -> Result<usize> {
+    if bytes.len() < SEAL_HEADER_SIZE {
+        return Err(Error::SerializationError(Box::new(
+            "Buffer too small to contain a SEAL header".to_owned(),
+        )));
+    }
+
+    let magic = u16::from_le_bytes([bytes[0], bytes[1]]);
+    let header_size = bytes[2] as usize;
+    let compression_mode = bytes[5];
+
+    let mut size = [0u8; 8];
+    size.copy_from_slice(&bytes[8..16]);
+    let size = u64::from_le_bytes(size) as usize;
+
+    if magic != SEAL_MAGIC || header_size != SEAL_HEADER_SIZE {
+        return Err(Error::SerializationError(Box::new(
+            "Invalid SEAL header".to_owned(),
+        )));
+    }
+
+    if compression_mode > CompressionType::ZStd as u8 {
+        return Err(Error::SerializationError(Box::new(format!(
+            "Unknown compression mode {}",
+            compression_mode
+        ))));
+    }
+
+    if size < SEAL_HEADER_SIZE {
+        return Err(Error::SerializationError(Box::new(format!(
+            "Serialized object claims {} bytes, which is smaller than its header",
+            size
+        ))));
+    }
+
+    Ok(size)
+}
+
+/**
+ * The state a stream callback works on: the Rust stream and whatever went
+ * wrong while SEAL was calling into it.
+ */
+struct CallbackState<'a, S> {
+    stream: &'a mut S,
+    error: Option<std::io::Error>,
+    panic: Option<Box<dyn Any + Send + 'static>>,
+}
+
+impl<'a, S> CallbackState<'a, S> {
+    fn new(stream: &'a mut S) -> Self {
+        Self {
+            stream,
+            error: None,
+            panic: None,
+        }
+    }
+
+    fn failed(&self) -> bool {
+        self.error.is_some() || self.panic.is_some()
+    }
+
+    /**
+     * Resumes a panic raised in a callback, or returns the I/O error that
+     * made SEAL fail in place of SEAL's less specific error.
+     */
+    fn finish(self, result: Result<()>) -> Result<()> {
+        if let Some(panic) = self.panic {
+            resume_unwind(panic);
+        }
+
+        match self.error {
+            Some(e) => Err(io_error(e)),
+            None => result,
+        }
+    }
+}
+
+/**
+ * Writes the bytes SEAL serializes to a [`Write`]. Panics can't unwind
+ * into C++, so they're caught and resumed once SEAL returns.
+ */
+unsafe extern "C" fn write_callback<W>(state: *mut c_void, data: *const u8, size: u64) -> i32
+where
+    W: Write,
+{
+    let state = &mut *(state as *mut CallbackState<W>);
+
+    if state.failed() {
+        return 1;
+    }
+
+    if size == 0 {
+        return 0;
+    }
+
+    let data = std::slice::from_raw_parts(data, size as usize);
+
+    match catch_unwind(AssertUnwindSafe(|| state.stream.write_all(data))) {
+        Ok(Ok(())) => 0,
+        Ok(Err(e)) => {
+            state.error = Some(e);
+            1
+        }
+        Err(panic) => {
+            state.panic = Some(panic);
+            1
+        }
+    }
+}
+
+/**
+ * Reads the bytes SEAL deserializes from a [`Read`], catching panics as
+ * [`write_callback`] does.
+ */
+unsafe extern "C" fn read_callback<R>(state: *mut c_void, data: *mut u8, size: u64) -> i64
+where
+    R: Read,
+{
+    let state = &mut *(state as *mut CallbackState<R>);
+
+    if state.failed() {
+        return -1;
+    }
+
+    if size == 0 {
+        return 0;
+    }
+
+    let data = std::slice::from_raw_parts_mut(data, size as usize);
+
+    let read = catch_unwind(AssertUnwindSafe(|| loop {
+        match state.stream.read(data) {
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            result => return result,
+        }
+    }));
+
+    match read {
+        Ok(Ok(count)) => count as i64,
+        Ok(Err(e)) => {
+            state.error = Some(e);
+            -1
+        }
+        Err(panic) => {
+            state.panic = Some(panic);
+            -1
+        }
+    }
+}
+
+fn save_to_stream<W>(
+    object_type: u32,
+    handle: *mut c_void,
+    writer: &mut W,
+    compression: CompressionType,
+) -> Result<u64>
+where
+    W: Write,
+{
+    let mut state = CallbackState::new(writer);
+    let mut bytes_written: i64 = 0;
+
+    let result = convert_seal_error(unsafe {
+        bindgen::Shim_Serialization_SaveToStream(
+            object_type as u8,
+            handle,
+            compression as u8,
+            Some(write_callback::<W>),
+            &mut state as *mut CallbackState<W> as *mut c_void,
+            &mut bytes_written,
+        )
+    });
+
+    state.finish(result)?;
+
+    Ok(bytes_written as u64)
+}
+
+fn load_from_stream<R>(
+    object_type: u32,
+    handle: *mut c_void,
+    context: &Context,
+    reader: &mut R,
+) -> Result<()>
+where
+    R: Read,
+{
+    let mut header = [0u8; SEAL_HEADER_SIZE];
+
+    reader.read_exact(&mut header).map_err(io_error)?;
+
+    let size = serialized_size(&header)?;
+
+    // SEAL reads the stream through a buffer, so stop it at the end of the
+    // object to leave whatever follows in `reader`.
+    let mut object = (&header[..]).chain(reader.take((size - SEAL_HEADER_SIZE) as u64));
+    let mut state = CallbackState::new(&mut object);
+    let mut bytes_read: i64 = 0;
+
+    let result = convert_seal_error(unsafe {
+        bindgen::Shim_Serialization_LoadFromStream(
+            object_type as u8,
+            handle,
+            context.handle,
+            Some(read_callback::<Chain<&[u8], Take<&mut R>>>),
+            &mut state as *mut CallbackState<Chain<&[u8], Take<&mut R>>> as *mut c_void,
+            &mut bytes_read,
+        )
+    });
+
+    state.finish(result)
+}
+
+/**
+ * A trait for writing objects to a [`Write`] stream, such as a file or a
+ * socket.
+ */
+pub trait ToWriter {
+    /**
+     * Writes the object to `writer` and returns the number of bytes
+     * written.
+     *
+     * # Remarks
+     * The output is identical to [`ToBytes::as_bytes`], so either side of
+     * a connection can use either API. SEAL writes straight to `writer`,
+     * so only compression buffers the object in memory. Objects written
+     * back-to-back can be read back one at a time with
+     * [`FromReader::load_from_reader`].
+     *
+     * A panic in `writer` is resumed once SEAL has cleaned up.
+     */
+    fn save_to_writer<W>(&self, writer: &mut W) -> Result<u64>
+    where
+        W: Write,
+    {
+        self.save_to_writer_with_compression(writer, CompressionType::default())
+    }
+
+    /**
+     * Writes the object to `writer` compressed with the given compression
+     * and returns the number of bytes written.
+     */
+    fn save_to_writer_with_compression<W>(
+        &self,
+        writer: &mut W,
+        compression: CompressionType,
+    ) -> Result<u64>
+    where
+        W: Write;
+}
+
+/**
+ * A trait for reading objects from a [`Read`] stream, such as a file or a
+ * socket, under a given SEAL context.
+ */
+pub trait FromReader {
+    /**
+     * Reads one object from `reader`.
+     *
+     * # Remarks
+     * This reads the SEAL header to learn the object's size and lets SEAL
+     * read no further than that, leaving any data that follows in the
+     * stream. SEAL reads the object straight from `reader` and sizes its
+     * allocations from the context, so a corrupt or malicious header
+     * can't force a large allocation.
+     *
+     * A panic in `reader` is resumed once SEAL has cleaned up.
+     */
+    fn load_from_reader<R>(context: &Context, reader: &mut R) -> Result<Self>
+    where
+        Self: Sized,
+        R: Read;
+}
+
+macro_rules! impl_stream {
+    ($type:ty, $object_type:expr) => {
+        impl ToWriter for $type {
+            fn save_to_writer_with_compression<W>(
+                &self,
+                writer: &mut W,
+                compression: CompressionType,
+            ) -> Result<u64>
+            where
+                W: Write,
+            {
+                save_to_stream($object_type, self.get_handle(), writer, compression)
+            }
+        }
+
+        impl FromReader for $type {
+            fn load_from_reader<R>(context: &Context, reader: &mut R) -> Result<Self>
+            where
+                R: Read,
+            {
+                let object = Self::new()?;
+
+                load_from_stream($object_type, object.get_handle(), context, reader)?;
+
+                Ok(object)
+            }
+        }
+    };
+}
+
+impl_stream!(Plaintext, bindgen::SHIM_OBJECT_PLAINTEXT);
+impl_stream!(Ciphertext, bindgen::SHIM_OBJECT_CIPHERTEXT);
+impl_stream!(PublicKey, bindgen::SHIM_OBJECT_PUBLIC_KEY);
+impl_stream!(SecretKey, bindgen::SHIM_OBJECT_SECRET_KEY);
+impl_stream!(RelinearizationKeys, bindgen::SHIM_OBJECT_KSWITCH_KEYS);
+impl_stream!(GaloisKeys, bindgen::SHIM_OBJECT_KSWITCH_KEYS);
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    use std::io::{Cursor, Read, Write};
+    use std::panic::AssertUnwindSafe;
+
+    #[test]
+    fn can_stream_ciphertexts_and_keys() {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 20).unwrap())
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+        let gen = KeyGenerator::new(&ctx).unwrap();
+
+        let public_key = gen.create_public_key().unwrap();
+        let galois_keys = gen.create_galois_keys().unwrap();
+
+        let encryptor = Encryptor::with_public_key(&ctx, &public_key).unwrap();
+        let encoder = BFVScalarEncoder::new();
+
+        let a = encryptor
+            .encrypt(&encoder.encode_unsigned(3).unwrap())
+            .unwrap();
+        let b = encryptor
+            .encrypt(&encoder.encode_unsigned(4).unwrap())
+            .unwrap();
+
+        let mut stream = vec![];
+
+        let mut written = a.save_to_writer(&mut stream).unwrap();
+        written += galois_keys.save_to_writer(&mut stream).unwrap();
+        written += b.save_to_writer(&mut stream).unwrap();
+
+        assert_eq!(written, stream.len() as u64);
+        assert_eq!(
+            &stream[..a.as_bytes().unwrap().len()],
+            &a.as_bytes().unwrap()[..]
+        );
+
+        let mut reader = Cursor::new(&stream);
+
+        let a_2 = Ciphertext::load_from_reader(&ctx, &mut reader).unwrap();
+        let galois_keys_2 = GaloisKeys::load_from_reader(&ctx, &mut reader).unwrap();
+        let b_2 = Ciphertext::load_from_reader(&ctx, &mut reader).unwrap();
+
+        assert!(a_2 == a);
+        assert!(b_2 == b);
+        assert_eq!(
+            galois_keys_2.as_bytes().unwrap().len(),
+            galois_keys.as_bytes().unwrap().len()
+        );
+        assert_eq!(reader.position(), stream.len() as u64);
+
+        // The stream is exhausted.
+        assert!(Ciphertext::load_from_reader(&ctx, &mut reader).is_err());
+
+        // A truncated stream fails rather than loading a partial object.
+        let mut truncated = Cursor::new(&stream[..stream.len() - 1]);
+
+        Ciphertext::load_from_reader(&ctx, &mut truncated).unwrap();
+        GaloisKeys::load_from_reader(&ctx, &mut truncated).unwrap();
+        assert!(Ciphertext::load_from_reader(&ctx, &mut truncated).is_err());
+    }
+
+    #[test]
+    fn stream_errors_and_panics_propagate() {
+        struct Failing;
+
+        impl Write for Failing {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::new(std::io::ErrorKind::Other, "disk full"))
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        struct Panicking;
+
+        impl Read for Panicking {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                panic!("reader panicked");
+            }
+        }
+
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_poly_modulus_degree(8192)
+            .set_coefficient_modulus(
+                CoefficientModulus::create(8192, &[50, 30, 30, 50, 50]).unwrap(),
+            )
+            .set_plain_modulus(PlainModulus::batching(8192, 20).unwrap())
+            .build()
+            .unwrap();
+
+        let ctx = Context::new(&params, false, SecurityLevel::TC128).unwrap();
+        let gen = KeyGenerator::new(&ctx).unwrap();
+
+        let public_key = gen.create_public_key().unwrap();
+
+        match public_key.save_to_writer(&mut Failing) {
+            Err(Error::SerializationError(e)) => assert!(e.contains("disk full")),
+            _ => panic!("Expected a serialization error"),
+        }
+
+        // A valid header, so the panic happens while SEAL reads the body.
+        let bytes = public_key.as_bytes().unwrap();
+        let mut reader = (&bytes[..16]).chain(Panicking);
+
+        let panic = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            let _ = PublicKey::load_from_reader(&ctx, &mut reader);
+        }))
+        .unwrap_err();
+
+        assert_eq!(panic.downcast_ref::<&str>(), Some(&"reader panicked"));
+    }
+}
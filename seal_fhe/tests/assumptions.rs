@@ -156,8 +156,8 @@ fn relinearization_consumes_no_noise_budget() {
 
     let encoder = BFVEncoder::new(&ctx).unwrap();
 
-    let public_key = gen.create_public_key();
-    let private_key = gen.secret_key();
+    let public_key = gen.create_public_key().unwrap();
+    let private_key = gen.secret_key().unwrap();
     let relin_key = gen.create_relinearization_keys().unwrap();
 
     let encryptor = Encryptor::with_public_and_secret_key(&ctx, &public_key, &private_key).unwrap();
@@ -214,8 +214,8 @@ fn addition_noise_less_equal_operands() {
 
     let encoder = BFVEncoder::new(&ctx).unwrap();
 
-    let public_key = gen.create_public_key();
-    let private_key = gen.secret_key();
+    let public_key = gen.create_public_key().unwrap();
+    let private_key = gen.secret_key().unwrap();
 
     let encryptor = Encryptor::with_public_and_secret_key(&ctx, &public_key, &private_key).unwrap();
     let decryptor = Decryptor::new(&ctx, &private_key).unwrap();
@@ -0,0 +1,45 @@
+#![cfg(feature = "failure-injection")]
+
+mod test_common;
+
+use seal_fhe::failure_injection::{disarm, fail_nth_call, is_armed, InjectedFailure};
+use seal_fhe::{Error, Evaluator};
+
+// Failure injection is global, so everything runs in a single test.
+#[test]
+fn injected_failures_are_reported() {
+    test_common::run_bfv_test(17, 8192, |decryptor, encoder, encryptor, eval, _| {
+        let p = encoder.encode_unsigned(&[1, 2, 3]).unwrap();
+        let a = encryptor.encrypt(&p).unwrap();
+
+        // Creating the result ciphertext is the first call.
+        fail_nth_call(1, InjectedFailure::OutOfMemory);
+
+        assert_eq!(eval.add(&a, &a).err(), Some(Error::OutOfMemory));
+        assert!(!is_armed());
+
+        // The addition itself is the second, and is annotated as usual.
+        fail_nth_call(2, InjectedFailure::InvalidArgument);
+
+        let err = eval.add(&a, &a).unwrap_err();
+
+        assert!(matches!(err, Error::OperationFailed(_)));
+        assert_eq!(err.root_cause(), &Error::InvalidArgument);
+
+        // Failures fire once.
+        let sum = eval.add(&a, &a).unwrap();
+
+        assert_eq!(
+            encoder
+                .decode_unsigned(&decryptor.decrypt(&sum).unwrap())
+                .unwrap()[0..3],
+            [2, 4, 6]
+        );
+
+        fail_nth_call(1_000_000, InjectedFailure::OutOfMemory);
+        disarm();
+
+        assert!(!is_armed());
+        assert!(eval.add(&a, &a).is_ok());
+    });
+}
@@ -0,0 +1,302 @@
+//! Approximate set membership tests of encrypted elements against a
+//! plaintext Bloom filter.
+//!
+//! The server builds a [`BloomFilter`] over its set and packs the filter's
+//! bits into the lanes of a [`Batched`] vector. To query an element, the
+//! client computes the `K` positions the element hashes to and encrypts one
+//! one-hot [`Batched`] vector per position (see [`BloomFilter::query`]).
+//! Inside an [`fhe_program`](crate::fhe_program), [`membership`] masks the
+//! filter with each query vector, sums the lanes to extract the bit at each
+//! position and multiplies the `K` bits together. The result decrypts to 1
+//! in every lane if all of the element's bits are set and to 0 otherwise.
+//!
+//! The server learns nothing about the element, while the client learns
+//! only whether it's (probably) in the set. As with any Bloom filter,
+//! elements outside the set test positive with probability
+//! [`BloomFilter::false_positive_rate`]. This makes membership much cheaper
+//! than full private set intersection when occasional false positives are
+//! acceptable.
+//!
+//! # Example
+//! ```
+//! # use sunscreen::{
+//! #    applications::bloom::{membership, BloomFilter},
+//! #    fhe_program,
+//! #    types::{bfv::Batched, Cipher},
+//! #    Compiler, FheProgramInput, PlainModulusConstraint, Runtime,
+//! # };
+//! #[fhe_program(scheme = "bfv")]
+//! fn contains(filter: Batched<64>, query: [Cipher<Batched<64>>; 3]) -> Cipher<Batched<64>> {
+//!     membership(filter, query)
+//! }
+//!
+//! let app = Compiler::new()
+//!     .fhe_program(contains)
+//!     .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(16))
+//!     .compile()
+//!     .unwrap();
+//!
+//! let runtime = Runtime::new_fhe(app.params()).unwrap();
+//! let (public_key, private_key) = runtime.generate_keys().unwrap();
+//!
+//! // The server's set.
+//! let mut filter = BloomFilter::<64, 3>::new();
+//! filter.insert(b"alice");
+//! filter.insert(b"bob");
+//!
+//! // The client's encrypted query.
+//! let query = runtime.encrypt(filter.query(b"alice"), &public_key).unwrap();
+//!
+//! let args: Vec<FheProgramInput> = vec![filter.to_batched().into(), query.into()];
+//!
+//! let result = runtime
+//!     .run(app.get_fhe_program(contains).unwrap(), args, &public_key)
+//!     .unwrap();
+//!
+//! let found: Batched<64> = runtime.decrypt(&result[0], &private_key).unwrap();
+//!
+//! assert_eq!(found[(0, 0)], 1);
+//! ```
+
+use crate::types::{bfv::Batched, LaneCount, SwapRows};
+
+use sha3::{Digest, Sha3_256};
+use std::ops::{Add, Mul, Shl};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/**
+ * A Bloom filter with `2 * LANES` bits and `K` hash functions whose bits
+ * pack into a [`Batched<LANES>`] vector.
+ *
+ * # Remarks
+ * Bit `i` lives in row `i / LANES` and lane `i % LANES`. The hash
+ * functions are derived from SHA3-256, so clients and servers that agree
+ * on `LANES` and `K` compute the same positions for an element.
+ */
+pub struct BloomFilter<const LANES: usize, const K: usize> {
+    bits: [[i64; LANES]; 2],
+}
+
+impl<const LANES: usize, const K: usize> Default for BloomFilter<LANES, K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const LANES: usize, const K: usize> BloomFilter<LANES, K> {
+    /**
+     * Creates an empty filter.
+     */
+    pub fn new() -> Self {
+        Self {
+            bits: [[0; LANES]; 2],
+        }
+    }
+
+    /**
+     * Returns the `K` bit positions `item` hashes to. Positions may repeat.
+     *
+     * # Remarks
+     * This uses double hashing, i.e. position `i` is `h1 + i * h2` modulo
+     * the filter size, where `h1` and `h2` come from the SHA3-256 digest
+     * of `item`.
+     */
+    pub fn positions(item: &[u8]) -> [usize; K] {
+        let digest = Sha3_256::digest(item);
+
+        let h1 = u64::from_le_bytes(digest[0..8].try_into().unwrap());
+        // An odd stride visits distinct positions when the filter size is
+        // a power of two.
+        let h2 = u64::from_le_bytes(digest[8..16].try_into().unwrap()) | 1;
+
+        let m = 2 * LANES as u64;
+
+        let mut positions = [0; K];
+
+        for (i, p) in positions.iter_mut().enumerate() {
+            *p = (h1.wrapping_add((i as u64).wrapping_mul(h2)) % m) as usize;
+        }
+
+        positions
+    }
+
+    /**
+     * Adds `item` to the filter.
+     */
+    pub fn insert(&mut self, item: &[u8]) {
+        for p in Self::positions(item) {
+            self.bits[p / LANES][p % LANES] = 1;
+        }
+    }
+
+    /**
+     * Returns whether `item` is (probably) in the filter, without
+     * encryption.
+     */
+    pub fn contains(&self, item: &[u8]) -> bool {
+        Self::positions(item)
+            .iter()
+            .all(|p| self.bits[p / LANES][p % LANES] == 1)
+    }
+
+    /**
+     * Returns the probability that an element not in the filter tests
+     * positive given the bits currently set.
+     */
+    pub fn false_positive_rate(&self) -> f64 {
+        let set = self.bits.iter().flatten().filter(|b| **b == 1).count();
+
+        (set as f64 / (2 * LANES) as f64).powi(K as i32)
+    }
+
+    /**
+     * Returns the filter's bits packed into a [`Batched`] vector, which the
+     * server passes to [`membership`] as a plaintext.
+     */
+    pub fn to_batched(&self) -> Batched<LANES> {
+        self.bits.into()
+    }
+
+    /**
+     * Returns the query vectors for `item`, which the client encrypts and
+     * passes to [`membership`]. Vector `i` contains a 1 at the `i`th
+     * position `item` hashes to and 0 everywhere else.
+     */
+    pub fn query(&self, item: &[u8]) -> [Batched<LANES>; K] {
+        Self::positions(item).map(|p| {
+            let mut bits = [[0; LANES]; 2];
+            bits[p / LANES][p % LANES] = 1;
+
+            bits.into()
+        })
+    }
+}
+
+/**
+ * Sums all lanes of both rows of `x` into every lane using
+ * `log2(LANES)` rotations and a row swap.
+ */
+pub(crate) fn lane_sum<T>(x: T) -> T
+where
+    T: Copy + LaneCount + Add<Output = T> + Shl<u64, Output = T> + SwapRows<Output = T>,
+{
+    let mut x = x;
+    let mut shift = 1;
+
+    while shift < T::lane_count() {
+        x = x + (x << shift as u64);
+        shift *= 2;
+    }
+
+    x + x.swap_rows()
+}
+
+/**
+ * Tests whether the element the `query` vectors were created for (see
+ * [`BloomFilter::query`]) is in `filter`. Every lane of the result is 1 if
+ * so and 0 otherwise.
+ *
+ * # Remarks
+ * This masks the filter with each query vector in a plaintext
+ * multiplication, sums the lanes of each result to extract one bit per
+ * hash function, and multiplies the `K` bits in a balanced tree. As such,
+ * it has a multiplicative depth of `ceil(log2(K))` and performs
+ * `K * (log2(LANES) + 1)` rotations.
+ *
+ * This function accepts both [`Batched`] vectors and, inside an
+ * [`fhe_program`](crate::fhe_program), an encrypted query with a
+ * plaintext filter.
+ *
+ * # Panics
+ * If `K` is 0.
+ */
+pub fn membership<F, T, const K: usize>(filter: F, query: [T; K]) -> T
+where
+    F: Copy,
+    T: Copy
+        + LaneCount
+        + Add<Output = T>
+        + Mul<Output = T>
+        + Mul<F, Output = T>
+        + Shl<u64, Output = T>
+        + SwapRows<Output = T>,
+{
+    assert!(K > 0, "Membership requires at least one hash function");
+
+    let mut bits = query
+        .iter()
+        .map(|q| lane_sum(*q * filter))
+        .collect::<Vec<T>>();
+
+    while bits.len() > 1 {
+        bits = bits
+            .chunks(2)
+            .map(|c| match c {
+                [a, b] => *a * *b,
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+
+    bits[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_test_membership_non_fhe() {
+        let mut filter = BloomFilter::<32, 3>::new();
+
+        let members: [&[u8]; 4] = [b"alice", b"bob", b"carol", b"dave"];
+
+        for m in members {
+            filter.insert(m);
+        }
+
+        for m in members {
+            assert!(filter.contains(m));
+
+            let found = membership(filter.to_batched(), filter.query(m));
+
+            assert_eq!(found, Batched::from(1));
+        }
+
+        for i in 0..100u32 {
+            let item = i.to_le_bytes();
+            let expected = i64::from(filter.contains(&item));
+
+            let found = membership(filter.to_batched(), filter.query(&item));
+
+            assert_eq!(found, Batched::from(expected));
+        }
+    }
+
+    #[test]
+    fn positions_are_in_range() {
+        for i in 0..100u32 {
+            for p in BloomFilter::<8, 5>::positions(&i.to_le_bytes()) {
+                assert!(p < 16);
+            }
+        }
+    }
+
+    #[test]
+    fn false_positive_rate_grows_with_inserts() {
+        let mut filter = BloomFilter::<16, 2>::new();
+
+        assert_eq!(filter.false_positive_rate(), 0.0);
+
+        filter.insert(b"alice");
+        let rate = filter.false_positive_rate();
+
+        assert!(rate > 0.0);
+
+        filter.insert(b"bob");
+        filter.insert(b"carol");
+
+        assert!(filter.false_positive_rate() >= rate);
+    }
+}
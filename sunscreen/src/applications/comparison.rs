@@ -0,0 +1,448 @@
+//! Equality, less-than and min/max circuits over encrypted integers.
+//!
+//! BFV only offers addition and multiplication modulo the plaintext
+//! modulus, so comparisons must be built from polynomials that happen to
+//! evaluate to 0 or 1. This module provides two constructions:
+//!
+//! * Bit-decomposed circuits ([`equal`], [`less_than`], [`greater_than`],
+//!   [`min`] and [`max`]) operate on integers the client splits into `N`
+//!   bits with [`to_bits`] and encrypts one bit per ciphertext, most
+//!   significant bit first. They have a multiplicative depth of
+//!   `1 + ceil(log2(N))` (one more for [`min`] and [`max`]) and work
+//!   with any plaintext modulus.
+//! * Fermat circuits ([`is_nonzero_fermat`] and [`equal_fermat`]) operate
+//!   on whole encrypted values. By Fermat's little theorem, `x^(p - 1)` is
+//!   1 for every nonzero `x` modulo a prime `p` and 0 for `x = 0`. This
+//!   avoids decomposing values, but has a multiplicative depth of
+//!   `ceil(log2(p - 1))` and requires the program to know its plaintext
+//!   modulus, e.g. by compiling it with
+//!   [`PlainModulusConstraint::Raw`](crate::PlainModulusConstraint::Raw).
+//!
+//! Every function is generic over its operand type, so it works on
+//! [`Batched`] vectors, where it compares each lane independently, both
+//! in the clear and inside an [`fhe_program`](crate::fhe_program).
+//!
+//! # Example
+//! ```
+//! # use sunscreen::{
+//! #    applications::comparison::{from_bits, less_than, min, to_bits},
+//! #    fhe_program,
+//! #    types::{bfv::Batched, Cipher},
+//! #    Compiler, FheProgramInput, PlainModulusConstraint, Runtime,
+//! # };
+//! #[fhe_program(scheme = "bfv")]
+//! fn smaller(
+//!     a: [Cipher<Batched<4>>; 4],
+//!     b: [Cipher<Batched<4>>; 4],
+//! ) -> (Cipher<Batched<4>>, [Cipher<Batched<4>>; 4]) {
+//!     (less_than(a, b), min(a, b))
+//! }
+//!
+//! let app = Compiler::new()
+//!     .fhe_program(smaller)
+//!     .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(16))
+//!     .compile()
+//!     .unwrap();
+//!
+//! let runtime = Runtime::new_fhe(app.params()).unwrap();
+//! let (public_key, private_key) = runtime.generate_keys().unwrap();
+//!
+//! let a = Batched::<4>::from([[3, 9, 12, 0], [15, 7, 7, 1]]);
+//! let b = Batched::<4>::from([[5, 9, 2, 0], [14, 8, 6, 3]]);
+//!
+//! let args: Vec<FheProgramInput> = vec![
+//!     runtime.encrypt(to_bits::<4, 4>(a), &public_key).unwrap().into(),
+//!     runtime.encrypt(to_bits::<4, 4>(b), &public_key).unwrap().into(),
+//! ];
+//!
+//! let result = runtime
+//!     .run(app.get_fhe_program(smaller).unwrap(), args, &public_key)
+//!     .unwrap();
+//!
+//! let lt: Batched<4> = runtime.decrypt(&result[0], &private_key).unwrap();
+//! let m: [Batched<4>; 4] = runtime.decrypt(&result[1], &private_key).unwrap();
+//!
+//! assert_eq!(lt, Batched::from([[1, 0, 0, 0], [0, 1, 0, 1]]));
+//! assert_eq!(from_bits(m), Batched::from([[3, 9, 2, 0], [14, 7, 6, 1]]));
+//! ```
+
+use crate::types::bfv::Batched;
+
+use std::ops::{Add, Mul, Sub};
+
+/**
+ * Splits each lane of `x` into `N` bits, most significant bit first, for
+ * use with the bit-decomposed circuits in this module.
+ *
+ * # Panics
+ * If any lane lies outside `[0, 2^N)`.
+ */
+pub fn to_bits<const LANES: usize, const N: usize>(x: Batched<LANES>) -> [Batched<LANES>; N] {
+    let rows = <[[i64; LANES]; 2]>::from(x);
+
+    assert!(
+        rows.iter()
+            .flatten()
+            .all(|v| *v >= 0 && (N >= 63 || *v < 1 << N)),
+        "Every lane must lie in [0, 2^{})",
+        N
+    );
+
+    let mut bits = [Batched::from(0); N];
+
+    for (i, b) in bits.iter_mut().enumerate() {
+        let shift = N - 1 - i;
+
+        *b = rows.map(|row| row.map(|v| (v >> shift) & 1)).into();
+    }
+
+    bits
+}
+
+/**
+ * Reassembles the integers [`to_bits`] split, e.g. after decrypting the
+ * result of [`min`] or [`max`].
+ */
+pub fn from_bits<const LANES: usize, const N: usize>(bits: [Batched<LANES>; N]) -> Batched<LANES> {
+    bits.iter().fold(Batched::from(0), |acc, b| acc * 2i64 + *b)
+}
+
+/**
+ * Multiplies the given values in a balanced tree, so the product's depth
+ * grows logarithmically in their number.
+ */
+pub(crate) fn product<T>(values: Vec<T>) -> T
+where
+    T: Copy + Mul<Output = T>,
+{
+    let mut values = values;
+
+    while values.len() > 1 {
+        values = values
+            .chunks(2)
+            .map(|c| match c {
+                [a, b] => *a * *b,
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+
+    values[0]
+}
+
+/**
+ * Returns whether `a < b` and whether `a == b` for the integers whose
+ * bits, most significant first, are in `a` and `b`.
+ */
+fn compare<T>(a: &[T], b: &[T]) -> (T, T)
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Add<i64, Output = T>
+        + Mul<i64, Output = T>,
+{
+    if a.len() == 1 {
+        let ab = a[0] * b[0];
+
+        // b(1 - a) and 1 - (a xor b)
+        return (b[0] - ab, ab * 2i64 - a[0] - b[0] + 1i64);
+    }
+
+    let mid = a.len() / 2;
+
+    let (lt_high, eq_high) = compare(&a[..mid], &b[..mid]);
+    let (lt_low, eq_low) = compare(&a[mid..], &b[mid..]);
+
+    // The high bits decide unless they're equal.
+    (lt_high + eq_high * lt_low, eq_high * eq_low)
+}
+
+/**
+ * Returns 1 if the `N`-bit integers `a` and `b` are equal and 0
+ * otherwise.
+ *
+ * # Remarks
+ * Both integers are bit-decomposed as with [`to_bits`]. This has a
+ * multiplicative depth of `1 + ceil(log2(N))`.
+ *
+ * # Panics
+ * If `N` is 0.
+ */
+pub fn equal<T, const N: usize>(a: [T; N], b: [T; N]) -> T
+where
+    T: Copy + Sub<Output = T> + Mul<Output = T> + Add<i64, Output = T> + Mul<i64, Output = T>,
+{
+    assert!(N > 0, "Comparisons require at least one bit");
+
+    let bits = a
+        .iter()
+        .zip(b.iter())
+        .map(|(a, b)| {
+            let d = *a - *b;
+
+            d * d * -1i64 + 1i64
+        })
+        .collect();
+
+    product(bits)
+}
+
+/**
+ * Returns 1 if the `N`-bit integer `a` is less than `b` and 0 otherwise.
+ *
+ * # Remarks
+ * Both integers are bit-decomposed as with [`to_bits`]. This compares the
+ * high and low halves of the bits recursively, so it has a multiplicative
+ * depth of `1 + ceil(log2(N))`.
+ *
+ * # Panics
+ * If `N` is 0.
+ */
+pub fn less_than<T, const N: usize>(a: [T; N], b: [T; N]) -> T
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Add<i64, Output = T>
+        + Mul<i64, Output = T>,
+{
+    assert!(N > 0, "Comparisons require at least one bit");
+
+    compare(&a, &b).0
+}
+
+/**
+ * Returns 1 if the `N`-bit integer `a` is greater than `b` and 0
+ * otherwise. See [`less_than`].
+ *
+ * # Panics
+ * If `N` is 0.
+ */
+pub fn greater_than<T, const N: usize>(a: [T; N], b: [T; N]) -> T
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Add<i64, Output = T>
+        + Mul<i64, Output = T>,
+{
+    less_than(b, a)
+}
+
+/**
+ * Returns the bits of the smaller of the `N`-bit integers `a` and `b`.
+ *
+ * # Remarks
+ * This selects each bit with the result of [`less_than`], so it has a
+ * multiplicative depth of `2 + ceil(log2(N))`.
+ *
+ * # Panics
+ * If `N` is 0.
+ */
+pub fn min<T, const N: usize>(a: [T; N], b: [T; N]) -> [T; N]
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Add<i64, Output = T>
+        + Mul<i64, Output = T>,
+{
+    let lt = less_than(a, b);
+
+    let mut result = b;
+
+    for (r, (a, b)) in result.iter_mut().zip(a.iter().zip(b.iter())) {
+        *r = *b + lt * (*a - *b);
+    }
+
+    result
+}
+
+/**
+ * Returns the bits of the larger of the `N`-bit integers `a` and `b`. See
+ * [`min`].
+ *
+ * # Panics
+ * If `N` is 0.
+ */
+pub fn max<T, const N: usize>(a: [T; N], b: [T; N]) -> [T; N]
+where
+    T: Copy
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Add<i64, Output = T>
+        + Mul<i64, Output = T>,
+{
+    let lt = less_than(a, b);
+
+    let mut result = a;
+
+    for (r, (a, b)) in result.iter_mut().zip(a.iter().zip(b.iter())) {
+        *r = *a + lt * (*b - *a);
+    }
+
+    result
+}
+
+/**
+ * Returns 1 if `x` isn't zero modulo the prime `plain_modulus` and 0
+ * otherwise, by raising `x` to the power `plain_modulus - 1`.
+ *
+ * # Remarks
+ * This squares `x` repeatedly and multiplies the needed powers in a
+ * balanced tree, so it has a multiplicative depth of
+ * `ceil(log2(plain_modulus - 1))`. For example, the commonly used
+ * batching modulus 65537 requires a depth of 16. The result is only
+ * meaningful when evaluated modulo `plain_modulus`, i.e. inside an
+ * [`fhe_program`](crate::fhe_program) whose plaintext modulus is exactly
+ * `plain_modulus`.
+ *
+ * # Panics
+ * If `plain_modulus` is less than 2.
+ */
+pub fn is_nonzero_fermat<T>(x: T, plain_modulus: u64) -> T
+where
+    T: Copy + Mul<Output = T>,
+{
+    assert!(plain_modulus >= 2, "The plaintext modulus must be prime");
+
+    let mut exponent = plain_modulus - 1;
+    let mut power = x;
+    let mut factors = vec![];
+
+    loop {
+        if exponent & 1 == 1 {
+            factors.push(power);
+        }
+
+        exponent >>= 1;
+
+        if exponent == 0 {
+            break;
+        }
+
+        power = power * power;
+    }
+
+    product(factors)
+}
+
+/**
+ * Returns 1 if `a` and `b` are equal modulo the prime `plain_modulus` and
+ * 0 otherwise. See [`is_nonzero_fermat`].
+ *
+ * # Panics
+ * If `plain_modulus` is less than 2.
+ */
+pub fn equal_fermat<T>(a: T, b: T, plain_modulus: u64) -> T
+where
+    T: Copy + Sub<Output = T> + Mul<Output = T> + Add<i64, Output = T> + Mul<i64, Output = T>,
+{
+    is_nonzero_fermat(a - b, plain_modulus) * -1i64 + 1i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_roundtrip_bits() {
+        let x = Batched::<4>::from([[0, 1, 6, 15], [8, 9, 3, 12]]);
+
+        let bits = to_bits::<4, 4>(x);
+
+        assert_eq!(bits[0], Batched::from([[0, 0, 0, 1], [1, 1, 0, 1]]));
+        assert_eq!(bits[3], Batched::from([[0, 1, 0, 1], [0, 1, 1, 0]]));
+        assert_eq!(from_bits(bits), x);
+    }
+
+    #[test]
+    #[should_panic]
+    fn to_bits_rejects_out_of_range() {
+        to_bits::<4, 3>(Batched::from(8));
+    }
+
+    #[test]
+    fn can_compare_non_fhe() {
+        for x in 0..16 {
+            for y in 0..16 {
+                let a = to_bits::<4, 4>(Batched::from(x));
+                let b = to_bits::<4, 4>(Batched::from(y));
+
+                assert_eq!(equal(a, b), Batched::from(i64::from(x == y)));
+                assert_eq!(less_than(a, b), Batched::from(i64::from(x < y)));
+                assert_eq!(greater_than(a, b), Batched::from(i64::from(x > y)));
+                assert_eq!(from_bits(min(a, b)), Batched::from(x.min(y)));
+                assert_eq!(from_bits(max(a, b)), Batched::from(x.max(y)));
+            }
+        }
+    }
+
+    #[test]
+    fn can_compare_odd_widths_non_fhe() {
+        for x in 0..8 {
+            for y in 0..8 {
+                let a = to_bits::<4, 3>(Batched::from(x));
+                let b = to_bits::<4, 3>(Batched::from(y));
+
+                assert_eq!(less_than(a, b), Batched::from(i64::from(x < y)));
+            }
+        }
+    }
+
+    /**
+     * Integers modulo 17, standing in for the plaintext space.
+     */
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Z17(i64);
+
+    impl Sub for Z17 {
+        type Output = Self;
+
+        fn sub(self, rhs: Self) -> Self {
+            Self((self.0 - rhs.0).rem_euclid(17))
+        }
+    }
+
+    impl Mul for Z17 {
+        type Output = Self;
+
+        fn mul(self, rhs: Self) -> Self {
+            Self((self.0 * rhs.0).rem_euclid(17))
+        }
+    }
+
+    impl Add<i64> for Z17 {
+        type Output = Self;
+
+        fn add(self, rhs: i64) -> Self {
+            Self((self.0 + rhs).rem_euclid(17))
+        }
+    }
+
+    impl Mul<i64> for Z17 {
+        type Output = Self;
+
+        fn mul(self, rhs: i64) -> Self {
+            Self((self.0 * rhs).rem_euclid(17))
+        }
+    }
+
+    #[test]
+    fn can_compare_fermat() {
+        for x in 0..17 {
+            assert_eq!(is_nonzero_fermat(Z17(x), 17), Z17(i64::from(x != 0)));
+
+            for y in 0..17 {
+                assert_eq!(equal_fermat(Z17(x), Z17(y), 17), Z17(i64::from(x == y)));
+            }
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! Oblivious selection of an element from a packed vector at an encrypted
+//! index, as in private information retrieval.
+//!
+//! The table's `2 * LANES` elements pack into a [`Batched`] vector, with
+//! element `i` in row `i / LANES` and lane `i % LANES`. The client splits
+//! the index it wants into `N` bits with [`index_bits`] and encrypts them,
+//! while the server passes the bits of every position, from
+//! [`positions`], as plaintexts. Inside an
+//! [`fhe_program`](crate::fhe_program), [`one_hot`] compares the index
+//! with every position at once, yielding a mask that is 1 in the lane the
+//! index points at and 0 elsewhere. [`select`] multiplies the table by the
+//! mask and sums the lanes with rotations, so every lane of the result
+//! holds the selected element.
+//!
+//! The server learns nothing about the index, and the table may be either
+//! a plaintext, as in private information retrieval, or encrypted.
+//!
+//! # Example
+//! ```
+//! # use sunscreen::{
+//! #    applications::lookup::{index_bits, positions, select},
+//! #    fhe_program,
+//! #    types::{bfv::Batched, Cipher},
+//! #    Compiler, FheProgramInput, PlainModulusConstraint, Runtime,
+//! # };
+//! #[fhe_program(scheme = "bfv")]
+//! fn lookup(
+//!     table: Batched<8>,
+//!     index: [Cipher<Batched<8>>; 4],
+//!     positions: [Batched<8>; 4],
+//! ) -> Cipher<Batched<8>> {
+//!     select(table, index, positions)
+//! }
+//!
+//! let app = Compiler::new()
+//!     .fhe_program(lookup)
+//!     .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(16))
+//!     .compile()
+//!     .unwrap();
+//!
+//! let runtime = Runtime::new_fhe(app.params()).unwrap();
+//! let (public_key, private_key) = runtime.generate_keys().unwrap();
+//!
+//! // The server's table.
+//! let table = Batched::<8>::from([
+//!     [10, 11, 12, 13, 14, 15, 16, 17],
+//!     [20, 21, 22, 23, 24, 25, 26, 27],
+//! ]);
+//!
+//! // The client's encrypted index.
+//! let index = runtime.encrypt(index_bits::<8, 4>(11), &public_key).unwrap();
+//!
+//! let args: Vec<FheProgramInput> = vec![
+//!     table.into(),
+//!     index.into(),
+//!     positions::<8, 4>().into(),
+//! ];
+//!
+//! let result = runtime
+//!     .run(app.get_fhe_program(lookup).unwrap(), args, &public_key)
+//!     .unwrap();
+//!
+//! let element: Batched<8> = runtime.decrypt(&result[0], &private_key).unwrap();
+//!
+//! assert_eq!(element[(0, 0)], 23);
+//! ```
+
+use crate::types::{bfv::Batched, LaneCount, SwapRows};
+
+use super::{
+    bloom::lane_sum,
+    comparison::{product, to_bits},
+};
+
+use std::ops::{Add, Mul, Shl, Sub};
+
+/**
+ * Returns the bits of every element's position in a table packed into a
+ * [`Batched<LANES>`] vector, most significant bit first, for the server to
+ * pass to [`one_hot`] or [`select`] as plaintexts.
+ *
+ * # Panics
+ * If `N` bits can't represent every position, i.e. if `2^N < 2 * LANES`.
+ */
+pub fn positions<const LANES: usize, const N: usize>() -> [Batched<LANES>; N] {
+    assert!(
+        N >= 63 || 2 * LANES <= 1 << N,
+        "{} bits can't represent {} positions",
+        N,
+        2 * LANES
+    );
+
+    let mut rows = [[0; LANES]; 2];
+
+    for (r, row) in rows.iter_mut().enumerate() {
+        for (l, x) in row.iter_mut().enumerate() {
+            *x = (r * LANES + l) as i64;
+        }
+    }
+
+    to_bits(rows.into())
+}
+
+/**
+ * Returns the bits of `index`, most significant bit first and repeated in
+ * every lane, for the client to encrypt and pass to [`one_hot`] or
+ * [`select`].
+ *
+ * # Panics
+ * If `index` isn't less than `2 * LANES` or `N` bits can't represent it.
+ */
+pub fn index_bits<const LANES: usize, const N: usize>(index: usize) -> [Batched<LANES>; N] {
+    assert!(
+        index < 2 * LANES,
+        "Index {} is out of bounds for {} elements",
+        index,
+        2 * LANES
+    );
+
+    to_bits(Batched::from(index as i64))
+}
+
+/**
+ * Returns a vector that is 1 at the position the `index` bits (see
+ * [`index_bits`]) point at and 0 in every other lane.
+ *
+ * # Remarks
+ * This compares the index with every position (see [`positions`]) at
+ * once. Each bit's comparison takes one multiplication and the `N`
+ * results multiply in a balanced tree, so this has a multiplicative depth
+ * of `1 + ceil(log2(N))`.
+ *
+ * This function accepts both [`Batched`] vectors and, inside an
+ * [`fhe_program`](crate::fhe_program), encrypted index bits with plaintext
+ * positions.
+ *
+ * # Panics
+ * If `N` is 0.
+ */
+pub fn one_hot<F, T, const N: usize>(index: [T; N], positions: [F; N]) -> T
+where
+    F: Copy,
+    T: Copy + Sub<F, Output = T> + Mul<Output = T> + Add<i64, Output = T> + Mul<i64, Output = T>,
+{
+    assert!(N > 0, "Indices require at least one bit");
+
+    let bits = index
+        .iter()
+        .zip(positions.iter())
+        .map(|(i, p)| {
+            let d = *i - *p;
+
+            d * d * -1i64 + 1i64
+        })
+        .collect();
+
+    product(bits)
+}
+
+/**
+ * Returns the element of `table` at the position the `index` bits (see
+ * [`index_bits`]) point at, in every lane.
+ *
+ * # Remarks
+ * This masks `table` with [`one_hot`] and sums the lanes of the result
+ * with `log2(LANES)` rotations and a row swap. As such, it has a
+ * multiplicative depth of `1 + ceil(log2(N))` for plaintext tables and
+ * one more for encrypted tables.
+ *
+ * This function accepts both [`Batched`] vectors and, inside an
+ * [`fhe_program`](crate::fhe_program), encrypted index bits with
+ * plaintext positions and a plaintext or encrypted table.
+ *
+ * # Panics
+ * If `N` is 0.
+ */
+pub fn select<D, F, T, const N: usize>(table: D, index: [T; N], positions: [F; N]) -> T
+where
+    D: Copy,
+    F: Copy,
+    T: Copy
+        + LaneCount
+        + Add<Output = T>
+        + Sub<F, Output = T>
+        + Mul<Output = T>
+        + Mul<D, Output = T>
+        + Add<i64, Output = T>
+        + Mul<i64, Output = T>
+        + Shl<u64, Output = T>
+        + SwapRows<Output = T>,
+{
+    lane_sum(one_hot(index, positions) * table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_select_non_fhe() {
+        let table = Batched::<4>::from([[3, 1, 4, 1], [5, 9, 2, 6]]);
+        let elements: [[i64; 4]; 2] = table.into();
+
+        for i in 0..8 {
+            let mask = one_hot(index_bits::<4, 3>(i), positions::<4, 3>());
+
+            let mut expected = [[0; 4]; 2];
+            expected[i / 4][i % 4] = 1;
+
+            assert_eq!(mask, Batched::from(expected));
+
+            let element = select(table, index_bits::<4, 3>(i), positions::<4, 3>());
+
+            assert_eq!(element, Batched::from(elements[i / 4][i % 4]));
+        }
+    }
+
+    #[test]
+    fn can_select_with_extra_bits_non_fhe() {
+        let table = Batched::<2>::from([[7, 8], [9, 10]]);
+
+        let element = select(table, index_bits::<2, 5>(2), positions::<2, 5>());
+
+        assert_eq!(element, Batched::from(9));
+    }
+
+    #[test]
+    #[should_panic]
+    fn positions_rejects_too_few_bits() {
+        positions::<4, 2>();
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_bits_rejects_out_of_bounds() {
+        index_bits::<4, 3>(8);
+    }
+}
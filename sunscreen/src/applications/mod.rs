@@ -0,0 +1,33 @@
+/**
+ * Approximate membership tests of encrypted elements against a plaintext
+ * Bloom filter.
+ */
+pub mod bloom;
+
+/**
+ * Equality, less-than and min/max circuits over encrypted integers.
+ */
+pub mod comparison;
+
+/**
+ * Oblivious selection of an element from a packed vector at an encrypted
+ * index.
+ */
+pub mod lookup;
+
+/**
+ * Fixed-point quantization of floating point values matched to a
+ * plaintext modulus.
+ */
+pub mod quantization;
+
+/**
+ * Helpers for aggregating masked client updates, as in federated learning.
+ */
+pub mod secure_aggregation;
+
+/**
+ * Windowed aggregation over time series packed into
+ * [`Batched`](crate::types::bfv::Batched) vectors.
+ */
+pub mod timeseries;
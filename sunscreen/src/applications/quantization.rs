@@ -0,0 +1,198 @@
+//! Quantization of floating point values (e.g. model weights and
+//! activations) into the integer range a parameter set's plaintext modulus
+//! supports.
+//!
+//! BFV plaintexts hold integers modulo the plaintext modulus `t`, and any
+//! intermediate or final value whose magnitude exceeds `t / 2` silently
+//! wraps. A [`Quantizer`] chooses the largest fixed-point scale for which
+//! the computation you describe can't wrap, maps values onto integers with
+//! that scale, and maps decrypted results back.
+//!
+//! # Example
+//! ```
+//! # use sunscreen::applications::quantization::Quantizer;
+//! // Dot products of 16 terms between values in [-1, 1].
+//! let q = Quantizer::new(65537, 1.0, 2, 16).unwrap();
+//!
+//! let x = [0.5f32, -0.25];
+//! let w = [0.75f32, 0.5];
+//!
+//! let dot: i64 = x
+//!     .iter()
+//!     .zip(w.iter())
+//!     .map(|(x, w)| q.quantize(*x) * q.quantize(*w))
+//!     .sum();
+//!
+//! // Rounding each value to the nearest of the 91 levels in [-1, 1] introduces
+//! // some error.
+//! assert!((q.dequantize(dot, 2) - 0.25).abs() < 0.02);
+//! ```
+
+use crate::types::bfv::Batched;
+use crate::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/**
+ * Maps floating point values onto integers using a fixed scale chosen so a
+ * computation's results fit in the plaintext modulus.
+ */
+pub struct Quantizer {
+    scale: f64,
+    max_abs: f32,
+}
+
+impl Quantizer {
+    /**
+     * Creates a quantizer for values in `[-max_abs, max_abs]` that are
+     * multiplied together `degree` at a time and then summed over at most
+     * `terms` products (e.g. `degree = 2` and `terms = n` for an
+     * `n`-element dot product).
+     *
+     * # Remarks
+     * The scale is the largest one for which `terms` products of `degree`
+     * quantized values can't exceed `(plain_modulus - 1) / 2` in magnitude.
+     * A larger plaintext modulus thus yields more precision.
+     *
+     * Returns [`Error::UnsatisfiableConstraint`] if `max_abs` isn't positive
+     * and finite, `degree` or `terms` is 0, or the plaintext modulus is too
+     * small to represent even a single nonzero quantized value.
+     */
+    pub fn new(plain_modulus: u64, max_abs: f32, degree: u32, terms: u64) -> Result<Self> {
+        if !max_abs.is_finite() || max_abs <= 0.0 || degree == 0 || terms == 0 {
+            return Err(Error::UnsatisfiableConstraint);
+        }
+
+        let budget = (plain_modulus.saturating_sub(1) / 2 / terms) as u128;
+
+        let fits = |q: u128| matches!(q.checked_pow(degree), Some(p) if p <= budget);
+
+        // Floating point roots may be off by one in either direction.
+        let mut q_max = (budget as f64).powf(1.0 / degree as f64).floor() as u128 + 1;
+
+        while q_max > 0 && !fits(q_max) {
+            q_max -= 1;
+        }
+
+        if q_max == 0 {
+            return Err(Error::UnsatisfiableConstraint);
+        }
+
+        Ok(Self {
+            scale: q_max as f64 / max_abs as f64,
+            max_abs,
+        })
+    }
+
+    /**
+     * The factor by which this quantizer multiplies values before rounding.
+     */
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /**
+     * The scale of a product of `degree` quantized values.
+     */
+    pub fn output_scale(&self, degree: u32) -> f64 {
+        self.scale.powi(degree as i32)
+    }
+
+    /**
+     * Quantizes `x`, clamping it into `[-max_abs, max_abs]` first.
+     */
+    pub fn quantize(&self, x: f32) -> i64 {
+        let x = x.clamp(-self.max_abs, self.max_abs) as f64;
+
+        (x * self.scale).round() as i64
+    }
+
+    /**
+     * Quantizes each value in `rows` into the lanes of a [`Batched`] vector.
+     *
+     * Returns [`Error::RuntimeError`] if either row doesn't contain exactly
+     * `LANES` values.
+     */
+    pub fn quantize_batched<const LANES: usize>(
+        &self,
+        rows: [&[f32]; 2],
+    ) -> Result<Batched<LANES>> {
+        let rows = rows.map(|r| r.iter().map(|x| self.quantize(*x)).collect::<Vec<i64>>());
+
+        Ok(Batched::try_from(rows)?)
+    }
+
+    /**
+     * Maps a decrypted value that is the sum of products of `degree`
+     * quantized values back to a floating point value.
+     */
+    pub fn dequantize(&self, x: i64, degree: u32) -> f32 {
+        (x as f64 / self.output_scale(degree)) as f32
+    }
+
+    /**
+     * Dequantizes each lane of a decrypted [`Batched`] vector. See
+     * [`dequantize`](Self::dequantize).
+     */
+    pub fn dequantize_batched<const LANES: usize>(
+        &self,
+        x: Batched<LANES>,
+        degree: u32,
+    ) -> [Vec<f32>; 2] {
+        let rows: [Vec<i64>; 2] = x.into();
+
+        rows.map(|r| r.iter().map(|x| self.dequantize(*x, degree)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scale_fits_plain_modulus() {
+        let t = 65537;
+
+        for degree in 1..4 {
+            for terms in [1, 7, 100] {
+                let q = Quantizer::new(t, 2.0, degree, terms).unwrap();
+                let max = q.quantize(2.0) as i128;
+
+                assert!(max > 0);
+                assert!(terms as i128 * max.pow(degree) <= (t as i128 - 1) / 2);
+                assert!(terms as i128 * (max + 1).pow(degree) > (t as i128 - 1) / 2);
+            }
+        }
+    }
+
+    #[test]
+    fn can_roundtrip_values() {
+        let q = Quantizer::new(1 << 40, 10.0, 1, 1).unwrap();
+
+        for x in [-10.0, -3.5, 0.0, 0.001, 7.25, 10.0] {
+            assert!((q.dequantize(q.quantize(x), 1) - x).abs() < 1e-3);
+        }
+
+        assert_eq!(q.quantize(100.0), q.quantize(10.0));
+    }
+
+    #[test]
+    fn can_quantize_batched() {
+        let q = Quantizer::new(65537, 1.0, 1, 1).unwrap();
+
+        let x: Batched<2> = q.quantize_batched([&[1.0, -1.0], &[0.5, 0.0]]).unwrap();
+
+        assert_eq!(
+            q.dequantize_batched(x, 1),
+            [vec![1.0, -1.0], vec![0.5, 0.0]]
+        );
+
+        assert!(q.quantize_batched::<2>([&[1.0], &[0.5, 0.0]]).is_err());
+    }
+
+    #[test]
+    fn rejects_unsatisfiable_constraints() {
+        assert!(Quantizer::new(3, 1.0, 2, 2).is_err());
+        assert!(Quantizer::new(65537, 0.0, 1, 1).is_err());
+        assert!(Quantizer::new(65537, 1.0, 0, 1).is_err());
+    }
+}
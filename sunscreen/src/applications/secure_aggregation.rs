@@ -0,0 +1,252 @@
+//! Secure sum aggregation over [`Batched`] vectors using pairwise affine
+//! masks.
+//!
+//! In federated learning, a server aggregates model updates from many
+//! clients. Encrypting each update under the aggregation key already hides it
+//! from the server evaluating the sum, but the holder of the private key
+//! could still decrypt any individual update. To prevent this, every pair
+//! of clients `(i, j)` shares a [`MaskSeed`] (e.g. derived from a key
+//! agreement) from which both derive the same random mask `m_ij`. Client
+//! `i` adds `m_ij` to its update if `i < j` and subtracts it otherwise, so
+//! every mask cancels in the sum while each individual update looks
+//! uniformly random.
+//!
+//! The protocol runs as follows:
+//! 1. Each client masks its update with [`mask_update`] and encrypts the
+//! result.
+//! 2. The server homomorphically adds the ciphertexts it receives.
+//! 3. If a client drops out, the masks it shares with the remaining
+//! clients no longer cancel. Each surviving client reveals the seed it
+//! shared with the dropped client and the server computes a
+//! [`dropout_correction`] to add to the sum, e.g. as a plaintext input
+//! of the aggregation program.
+//!
+//! All arithmetic happens modulo the plaintext modulus, which must be the
+//! same one the aggregation program was compiled with.
+//!
+//! # Example
+//! ```
+//! # use sunscreen::{
+//! #    applications::secure_aggregation::{dropout_correction, mask_update},
+//! #    fhe_program,
+//! #    types::{bfv::Batched, Cipher},
+//! #    Compiler, FheProgramInput, PlainModulusConstraint, Runtime,
+//! # };
+//! #[fhe_program(scheme = "bfv")]
+//! fn aggregate(
+//!     a: Cipher<Batched<4>>,
+//!     b: Cipher<Batched<4>>,
+//!     correction: Batched<4>,
+//! ) -> Cipher<Batched<4>> {
+//!     a + b + correction
+//! }
+//!
+//! let app = Compiler::new()
+//!     .fhe_program(aggregate)
+//!     .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(20))
+//!     .compile()
+//!     .unwrap();
+//!
+//! let runtime = Runtime::new_fhe(app.params()).unwrap();
+//! let (public_key, private_key) = runtime.generate_keys().unwrap();
+//! let t = app.params().plain_modulus;
+//!
+//! // Pairwise seeds between clients 0, 1 and 2.
+//! let (s01, s02, s12) = ([1; 32], [2; 32], [3; 32]);
+//!
+//! let update_0 = Batched::<4>::try_from([vec![1, 2, 3, 4], vec![0; 4]]).unwrap();
+//! let update_1 = Batched::<4>::try_from([vec![10, 20, 30, 40], vec![0; 4]]).unwrap();
+//!
+//! let masked_0 = mask_update(update_0, 0, &[(1, s01), (2, s02)], t);
+//! let masked_1 = mask_update(update_1, 1, &[(0, s01), (2, s12)], t);
+//!
+//! // Client 2 drops out. Clients 0 and 1 reveal the seeds they share with it.
+//! let correction: Batched<4> = dropout_correction(2, &[(0, s02), (1, s12)], t);
+//!
+//! let args: Vec<FheProgramInput> = vec![
+//!     runtime.encrypt(masked_0, &public_key).unwrap().into(),
+//!     runtime.encrypt(masked_1, &public_key).unwrap().into(),
+//!     correction.into(),
+//! ];
+//!
+//! let result = runtime
+//!     .run(app.get_fhe_program(aggregate).unwrap(), args, &public_key)
+//!     .unwrap();
+//!
+//! let sum: Batched<4> = runtime.decrypt(&result[0], &private_key).unwrap();
+//!
+//! assert_eq!(sum, update_0 + update_1);
+//! ```
+
+use crate::types::bfv::Batched;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+/**
+ * A secret shared between two clients from which both derive the same mask.
+ */
+pub type MaskSeed = [u8; 32];
+
+/**
+ * Reduces `x` modulo `plain_modulus` into the centered range
+ * `(-plain_modulus / 2, plain_modulus / 2]` expected by the batch encoder.
+ */
+fn center(x: i128, plain_modulus: u64) -> i64 {
+    let t = plain_modulus as i128;
+    let x = x.rem_euclid(t);
+
+    if x > t / 2 {
+        (x - t) as i64
+    } else {
+        x as i64
+    }
+}
+
+/**
+ * Derives the mask two clients share from their seed.
+ */
+fn pair_mask<const LANES: usize>(seed: &MaskSeed, plain_modulus: u64) -> [[i64; LANES]; 2] {
+    let mut rng = ChaCha20Rng::from_seed(*seed);
+    let mut mask = [[0; LANES]; 2];
+
+    for row in mask.iter_mut() {
+        for x in row.iter_mut() {
+            *x = center(rng.gen_range(0..plain_modulus) as i128, plain_modulus);
+        }
+    }
+
+    mask
+}
+
+/**
+ * Computes the combined mask client `client_id` adds to its update given
+ * the seeds it shares with each of its `peers` as `(peer_id, seed)` pairs.
+ *
+ * # Panics
+ * If a peer has the same id as `client_id` or `plain_modulus` is 0.
+ */
+pub fn client_mask<const LANES: usize>(
+    client_id: u64,
+    peers: &[(u64, MaskSeed)],
+    plain_modulus: u64,
+) -> Batched<LANES> {
+    let mut total = [[0; LANES]; 2];
+
+    for (peer_id, seed) in peers {
+        assert_ne!(
+            *peer_id, client_id,
+            "A client can't share a mask with itself"
+        );
+
+        let mask = pair_mask::<LANES>(seed, plain_modulus);
+
+        for (total, mask) in total.iter_mut().zip(mask.iter()) {
+            for (t, m) in total.iter_mut().zip(mask.iter()) {
+                let m = if client_id < *peer_id { *m } else { -*m };
+
+                *t = center(*t as i128 + m as i128, plain_modulus);
+            }
+        }
+    }
+
+    total.into()
+}
+
+/**
+ * Adds client `client_id`'s mask (see [`client_mask`]) to `update`. The
+ * client should encrypt the returned value and send it to the server.
+ */
+pub fn mask_update<const LANES: usize>(
+    update: Batched<LANES>,
+    client_id: u64,
+    peers: &[(u64, MaskSeed)],
+    plain_modulus: u64,
+) -> Batched<LANES> {
+    let mask: [[i64; LANES]; 2] = client_mask(client_id, peers, plain_modulus).into();
+    let mut update: [[i64; LANES]; 2] = update.into();
+
+    for (update, mask) in update.iter_mut().zip(mask.iter()) {
+        for (u, m) in update.iter_mut().zip(mask.iter()) {
+            *u = center(*u as i128 + *m as i128, plain_modulus);
+        }
+    }
+
+    update.into()
+}
+
+/**
+ * Computes the value to add to an aggregate to cancel the masks surviving
+ * clients share with client `dropped_id`, which never submitted its
+ * update. `survivors` contains each surviving client's id and the seed it
+ * shares with the dropped client.
+ *
+ * # Remarks
+ * The correction is exactly the mask the dropped client would have added.
+ * When several clients drop out, add one correction per dropped client,
+ * each listing only the surviving clients.
+ *
+ * Revealing these seeds exposes nothing about surviving clients' updates,
+ * as each of their masks still contains terms shared with other
+ * survivors. However, the server must never obtain a correction for a
+ * client whose masked update it has received, as that would unmask it.
+ */
+pub fn dropout_correction<const LANES: usize>(
+    dropped_id: u64,
+    survivors: &[(u64, MaskSeed)],
+    plain_modulus: u64,
+) -> Batched<LANES> {
+    client_mask(dropped_id, survivors, plain_modulus)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const T: u64 = 65537;
+
+    fn add_mod<const LANES: usize>(a: Batched<LANES>, b: Batched<LANES>) -> Batched<LANES> {
+        let mut a: [[i64; LANES]; 2] = a.into();
+        let b: [[i64; LANES]; 2] = b.into();
+
+        for (a, b) in a.iter_mut().zip(b.iter()) {
+            for (x, y) in a.iter_mut().zip(b.iter()) {
+                *x = center(*x as i128 + *y as i128, T);
+            }
+        }
+
+        a.into()
+    }
+
+    #[test]
+    fn masks_cancel_in_sum() {
+        let seeds = [[1; 32], [2; 32], [3; 32]];
+
+        let m_0: Batched<8> = client_mask(0, &[(1, seeds[0]), (2, seeds[1])], T);
+        let m_1: Batched<8> = client_mask(1, &[(0, seeds[0]), (2, seeds[2])], T);
+        let m_2: Batched<8> = client_mask(2, &[(0, seeds[1]), (1, seeds[2])], T);
+
+        assert_ne!(m_0, Batched::from(0));
+        assert_eq!(add_mod(add_mod(m_0, m_1), m_2), Batched::from(0));
+    }
+
+    #[test]
+    fn can_correct_dropouts() {
+        let seeds = [[1; 32], [2; 32], [3; 32]];
+
+        let a = Batched::<8>::from([[1, -2, 3, -4, 5, -6, 7, -8], [100; 8]]);
+        let b = Batched::<8>::from([[-1000; 8], [7; 8]]);
+
+        let masked_a = mask_update(a, 0, &[(1, seeds[0]), (2, seeds[1])], T);
+        let masked_b = mask_update(b, 1, &[(0, seeds[0]), (2, seeds[2])], T);
+
+        assert_ne!(masked_a, a);
+
+        let correction = dropout_correction(2, &[(0, seeds[1]), (1, seeds[2])], T);
+
+        assert_eq!(
+            add_mod(add_mod(masked_a, masked_b), correction),
+            add_mod(a, b)
+        );
+    }
+}
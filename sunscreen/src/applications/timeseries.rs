@@ -0,0 +1,173 @@
+//! Sliding window operators over time series packed into
+//! [`Batched`](crate::types::bfv::Batched) vectors.
+//!
+//! Each row of a [`Batched`](crate::types::bfv::Batched) vector holds one
+//! series, with lane `i` containing the sample at time step `i`. The
+//! operators in this module combine neighboring lanes using rotations, so
+//! they work identically on plaintext vectors and on encrypted vectors
+//! inside an [`fhe_program`](crate::fhe_program).
+//!
+//! # Wrap-around
+//! Rotations are cyclic, so the windows ending at the first `window - 1`
+//! lanes wrap around and include samples from the end of the row. Either
+//! ignore these lanes or pad the end of each row with `window - 1` zeros.
+//!
+//! # Example
+//! ```
+//! # use sunscreen::{
+//! #    applications::timeseries::{moving_average, windowed_sum},
+//! #    fhe_program,
+//! #    types::{bfv::Batched, Cipher},
+//! #    Compiler, PlainModulusConstraint, Runtime,
+//! # };
+//! #[fhe_program(scheme = "bfv")]
+//! fn sum_of_pairs(x: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+//!     windowed_sum(x, 2)
+//! }
+//!
+//! let app = Compiler::new()
+//!     .fhe_program(sum_of_pairs)
+//!     .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+//!     .compile()
+//!     .unwrap();
+//!
+//! let runtime = Runtime::new_fhe(app.params()).unwrap();
+//! let (public_key, private_key) = runtime.generate_keys().unwrap();
+//!
+//! let readings = Batched::<4>::try_from([vec![1, 3, 5, 7], vec![2, 2, 4, 4]]).unwrap();
+//! let readings = runtime.encrypt(readings, &public_key).unwrap();
+//!
+//! let result = runtime
+//!     .run(app.get_fhe_program(sum_of_pairs).unwrap(), vec![readings], &public_key)
+//!     .unwrap();
+//!
+//! let sums: Batched<4> = runtime.decrypt(&result[0], &private_key).unwrap();
+//!
+//! // Lane 0 wraps around to include the last sample.
+//! assert_eq!(
+//!     moving_average(sums, 2),
+//!     [vec![4.0, 2.0, 4.0, 6.0], vec![3.0, 2.0, 3.0, 4.0]]
+//! );
+//! ```
+
+use crate::types::{bfv::Batched, LaneCount};
+
+use std::ops::{Add, Shr};
+
+/**
+ * Computes the sum of each trailing window of `window` consecutive lanes.
+ * Lane `i` of the result contains `x[i - window + 1] + ... + x[i]`.
+ *
+ * # Remarks
+ * This builds sums over power-of-two windows by repeated doubling and then
+ * combines the ones appearing in the binary representation of `window`.
+ * As such, it performs fewer than `2 * log2(window)` rotations and
+ * additions and no multiplications.
+ *
+ * This function accepts both [`Batched`] vectors and encrypted [`Batched`]
+ * vectors inside an [`fhe_program`](crate::fhe_program). See the
+ * [module documentation](self) for how the first `window - 1` lanes wrap.
+ *
+ * # Panics
+ * If `window` is 0 or exceeds the number of lanes.
+ */
+pub fn windowed_sum<T>(x: T, window: usize) -> T
+where
+    T: Copy + LaneCount + Add<Output = T> + Shr<u64, Output = T>,
+{
+    assert!(
+        window > 0 && window <= T::lane_count(),
+        "Window must be between 1 and the lane count"
+    );
+
+    // Invariant: lane i of `block` contains the sum of the `block_len`
+    // samples ending at lane i.
+    let mut block = x;
+    let mut block_len = 1;
+
+    let mut acc: Option<T> = None;
+    let mut offset = 0;
+    let mut remaining = window;
+
+    loop {
+        if remaining & 1 == 1 {
+            let shifted = if offset == 0 {
+                block
+            } else {
+                block >> offset as u64
+            };
+
+            acc = Some(match acc {
+                Some(acc) => acc + shifted,
+                None => shifted,
+            });
+
+            offset += block_len;
+        }
+
+        remaining >>= 1;
+
+        if remaining == 0 {
+            break;
+        }
+
+        block = block + (block >> block_len as u64);
+        block_len *= 2;
+    }
+
+    acc.unwrap()
+}
+
+/**
+ * Divides the windowed sums computed by [`windowed_sum`] by the window size,
+ * yielding the moving average of each row.
+ *
+ * # Remarks
+ * The BFV scheme can't divide ciphertexts, so clients call this after
+ * decrypting the sums.
+ */
+pub fn moving_average<const LANES: usize>(sums: Batched<LANES>, window: usize) -> [Vec<f64>; 2] {
+    let rows: [Vec<i64>; 2] = sums.into();
+
+    rows.map(|row| row.iter().map(|x| *x as f64 / window as f64).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_compute_windowed_sums_non_fhe() {
+        let x =
+            Batched::<8>::try_from([vec![1, 2, 3, 4, 5, 6, 7, 8], vec![1, 1, 1, 1, 1, 1, 1, 1]])
+                .unwrap();
+
+        for window in 1..=8 {
+            let actual: [Vec<i64>; 2] = windowed_sum(x, window).into();
+            let rows: [Vec<i64>; 2] = x.into();
+
+            for (actual, row) in actual.iter().zip(rows.iter()) {
+                for (i, actual) in actual.iter().enumerate() {
+                    let expected: i64 = (0..window).map(|j| row[(i + 8 - j) % 8]).sum();
+
+                    assert_eq!(*actual, expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn can_compute_moving_average() {
+        let sums = Batched::<2>::try_from([vec![4, 6], vec![-2, 0]]).unwrap();
+
+        assert_eq!(moving_average(sums, 2), [vec![2.0, 3.0], vec![-1.0, 0.0]]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn windowed_sum_rejects_empty_window() {
+        let x = Batched::<2>::try_from([vec![1, 2], vec![3, 4]]).unwrap();
+
+        windowed_sum(x, 0);
+    }
+}
@@ -0,0 +1,80 @@
+//! Helpers for calling Sunscreen's long-running operations from async code.
+//!
+//! Compiling, generating keys, running FHE programs, proving and verifying
+//! are CPU-bound calls that take anywhere from milliseconds to minutes.
+//! Calling them directly from a tokio task blocks the worker thread it
+//! runs on, starving every other task scheduled there. The helpers in this
+//! module run them on tokio's blocking thread pool instead:
+//!
+//! * [`compile`] compiles an [`Application`].
+//! * [`AsyncRuntime`] wraps a runtime to generate keys, encrypt, decrypt,
+//!   run FHE programs and, with the `zkp` feature, prove and verify.
+//! * [`spawn_blocking`] runs any other blocking work.
+//!
+//! Dropping the future from [`AsyncRuntime::run`] or
+//! [`AsyncRuntime::run_cancellable`] stops evaluation before the next FHE
+//! operation, as does cancelling the [`CancellationToken`] passed to the
+//! latter. All of these must be called from within a tokio runtime.
+//!
+//! # Example
+//! ```no_run
+//! # use sunscreen::{
+//! #     asynch::{self, AsyncRuntime},
+//! #     fhe_program,
+//! #     types::{bfv::Signed, Cipher},
+//! #     Compiler, Runtime,
+//! # };
+//! # use std::sync::Arc;
+//! #[fhe_program(scheme = "bfv")]
+//! fn square(a: Cipher<Signed>) -> Cipher<Signed> {
+//!     a * a
+//! }
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let app = asynch::compile(|| Compiler::new().fhe_program(square).compile()).await?;
+//!
+//! let runtime = AsyncRuntime::new(Runtime::new_fhe(app.params())?);
+//! let (public_key, private_key) = runtime.generate_keys().await?;
+//! let public_key = Arc::new(public_key);
+//!
+//! let a = runtime.encrypt(Signed::from(7), public_key.clone()).await?;
+//!
+//! let program = Arc::new(app.get_fhe_program(square).unwrap().clone());
+//! let result = runtime.run(program, vec![a], public_key).await?;
+//!
+//! let c: Signed = runtime
+//!     .decrypt(result[0].clone(), Arc::new(private_key))
+//!     .await?;
+//!
+//! assert_eq!(c, Signed::from(49));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{Application, Result};
+
+#[cfg(feature = "fhe")]
+pub use sunscreen_runtime::AsyncFheRuntime;
+#[cfg(all(feature = "fhe", feature = "zkp"))]
+pub use sunscreen_runtime::AsyncFheZkpRuntime;
+#[cfg(feature = "zkp")]
+pub use sunscreen_runtime::AsyncZkpRuntime;
+pub use sunscreen_runtime::{spawn_blocking, AsyncRuntime, CancellationToken};
+
+/**
+ * Runs `build`, which should construct and compile an [`Application`], on
+ * tokio's blocking thread pool.
+ *
+ * # Remarks
+ * This takes a closure rather than a compiler, as compilers hold FHE
+ * program definitions that can't move between threads. Build the compiler
+ * inside the closure, e.g.
+ * `asynch::compile(|| Compiler::new().fhe_program(my_program).compile())`.
+ */
+pub async fn compile<F, T>(build: F) -> Result<Application<T>>
+where
+    F: FnOnce() -> Result<Application<T>> + Send + 'static,
+    T: Send + 'static,
+{
+    spawn_blocking(build).await
+}
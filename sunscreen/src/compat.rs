@@ -0,0 +1,281 @@
+use std::fmt::{Display, Formatter};
+
+use sunscreen_runtime::{CompiledFheProgram, RequiredKeys};
+
+use crate::{SchemeType, SecurityLevel};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/**
+ * A difference between two [`CompiledFheProgram`]s that prevents or
+ * complicates running them side by side. See
+ * [`Application::check_compat`](crate::Application::check_compat).
+ */
+pub enum Mismatch {
+    /**
+     * The programs use different FHE schemes.
+     */
+    SchemeType {
+        /**
+         * The first program's scheme.
+         */
+        a: SchemeType,
+
+        /**
+         * The second program's scheme.
+         */
+        b: SchemeType,
+    },
+
+    /**
+     * The programs target different security levels.
+     */
+    SecurityLevel {
+        /**
+         * The first program's security level.
+         */
+        a: SecurityLevel,
+
+        /**
+         * The second program's security level.
+         */
+        b: SecurityLevel,
+    },
+
+    /**
+     * The programs use different polynomial degrees.
+     */
+    LatticeDimension {
+        /**
+         * The first program's lattice dimension.
+         */
+        a: u64,
+
+        /**
+         * The second program's lattice dimension.
+         */
+        b: u64,
+    },
+
+    /**
+     * The programs use different ciphertext moduli.
+     */
+    CoeffModulus {
+        /**
+         * The first program's coefficient modulus.
+         */
+        a: Vec<u64>,
+
+        /**
+         * The second program's coefficient modulus.
+         */
+        b: Vec<u64>,
+    },
+
+    /**
+     * The programs use different plaintext moduli, so the same ciphertext
+     * decrypts to different values in each.
+     */
+    PlainModulus {
+        /**
+         * The first program's plaintext modulus.
+         */
+        a: u64,
+
+        /**
+         * The second program's plaintext modulus.
+         */
+        b: u64,
+    },
+
+    /**
+     * Each program requires key types the other doesn't.
+     */
+    RequiredKeys {
+        /**
+         * The key types only the first program requires.
+         */
+        only_a: Vec<RequiredKeys>,
+
+        /**
+         * The key types only the second program requires.
+         */
+        only_b: Vec<RequiredKeys>,
+    },
+
+    /**
+     * Each program performs row rotations the other doesn't, as in
+     * [`CompiledFheProgram::rotation_steps`].
+     */
+    Rotations {
+        /**
+         * The steps only the first program rotates by.
+         */
+        only_a: Vec<i32>,
+
+        /**
+         * The steps only the second program rotates by.
+         */
+        only_b: Vec<i32>,
+    },
+}
+
+impl Mismatch {
+    /**
+     * Whether the mismatch is in the programs' scheme parameters, as
+     * opposed to the keys they need.
+     */
+    pub fn is_params(&self) -> bool {
+        !matches!(self, Self::RequiredKeys { .. } | Self::Rotations { .. })
+    }
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SchemeType { a, b } => write!(f, "scheme types differ: {:?} vs {:?}", a, b),
+            Self::SecurityLevel { a, b } => {
+                write!(f, "security levels differ: {:?} vs {:?}", a, b)
+            }
+            Self::LatticeDimension { a, b } => {
+                write!(f, "lattice dimensions differ: {} vs {}", a, b)
+            }
+            Self::CoeffModulus { a, b } => {
+                write!(f, "coefficient moduli differ: {:?} vs {:?}", a, b)
+            }
+            Self::PlainModulus { a, b } => write!(f, "plain moduli differ: {} vs {}", a, b),
+            Self::RequiredKeys { only_a, only_b } => write!(
+                f,
+                "required keys differ: {:?} only in a, {:?} only in b",
+                only_a, only_b
+            ),
+            Self::Rotations { only_a, only_b } => write!(
+                f,
+                "rotations differ: {:?} only in a, {:?} only in b",
+                only_a, only_b
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/**
+ * How two [`CompiledFheProgram`]s differ in their parameters and key
+ * requirements. See
+ * [`Application::check_compat`](crate::Application::check_compat).
+ */
+pub struct CompatibilityReport {
+    /**
+     * Every difference found, parameters first.
+     */
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl CompatibilityReport {
+    pub(crate) fn new(a: &CompiledFheProgram, b: &CompiledFheProgram) -> Self {
+        let mut mismatches = vec![];
+
+        let a_params = &a.metadata.params;
+        let b_params = &b.metadata.params;
+
+        if a_params.scheme_type != b_params.scheme_type {
+            mismatches.push(Mismatch::SchemeType {
+                a: a_params.scheme_type,
+                b: b_params.scheme_type,
+            });
+        }
+
+        if a_params.security_level != b_params.security_level {
+            mismatches.push(Mismatch::SecurityLevel {
+                a: a_params.security_level,
+                b: b_params.security_level,
+            });
+        }
+
+        if a_params.lattice_dimension != b_params.lattice_dimension {
+            mismatches.push(Mismatch::LatticeDimension {
+                a: a_params.lattice_dimension,
+                b: b_params.lattice_dimension,
+            });
+        }
+
+        if a_params.coeff_modulus != b_params.coeff_modulus {
+            mismatches.push(Mismatch::CoeffModulus {
+                a: a_params.coeff_modulus.clone(),
+                b: b_params.coeff_modulus.clone(),
+            });
+        }
+
+        if a_params.plain_modulus != b_params.plain_modulus {
+            mismatches.push(Mismatch::PlainModulus {
+                a: a_params.plain_modulus,
+                b: b_params.plain_modulus,
+            });
+        }
+
+        let a_keys = &a.metadata.required_keys;
+        let b_keys = &b.metadata.required_keys;
+
+        let only_a = difference(a_keys, b_keys);
+        let only_b = difference(b_keys, a_keys);
+
+        if !only_a.is_empty() || !only_b.is_empty() {
+            mismatches.push(Mismatch::RequiredKeys { only_a, only_b });
+        }
+
+        let a_steps = a.rotation_steps();
+        let b_steps = b.rotation_steps();
+
+        let only_a = difference(&a_steps, &b_steps);
+        let only_b = difference(&b_steps, &a_steps);
+
+        if !only_a.is_empty() || !only_b.is_empty() {
+            mismatches.push(Mismatch::Rotations { only_a, only_b });
+        }
+
+        Self { mismatches }
+    }
+
+    /**
+     * Whether the programs share their parameters and key requirements
+     * exactly.
+     */
+    pub fn is_compatible(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /**
+     * Whether the programs share their parameters, so ciphertexts flow
+     * between them and one key pair serves both.
+     *
+     * # Remarks
+     * The programs may still need different evaluation keys. Generate
+     * keys covering both, e.g. with
+     * [`Application::generate_minimal_galois_keys`](crate::Application::generate_minimal_galois_keys)
+     * on an application containing both programs.
+     */
+    pub fn params_compatible(&self) -> bool {
+        self.mismatches.iter().all(|m| !m.is_params())
+    }
+}
+
+impl Display for CompatibilityReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_compatible() {
+            return write!(f, "programs are compatible");
+        }
+
+        for (i, m) in self.mismatches.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+
+            write!(f, "{}", m)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn difference<T: Clone + PartialEq>(a: &[T], b: &[T]) -> Vec<T> {
+    a.iter().filter(|x| !b.contains(x)).cloned().collect()
+}
@@ -1,21 +1,61 @@
+#[cfg(feature = "fhe")]
 use crate::fhe::{FheCompile, FheFrontendCompilation};
-use crate::params::{determine_params, PlainModulusConstraint};
+#[cfg(feature = "fhe")]
+use crate::params::{determine_params_with_options, PlainModulusConstraint};
+#[cfg(feature = "zkp")]
 use crate::{
-    zkp, Application, CallSignature, Error, FheProgramMetadata, Params, RequiredKeys, Result,
-    SchemeType, SecurityLevel, ZkpProgramFn,
+    zkp::{self, CircuitSize, ZkpLimits},
+    ZkpProgramFn,
 };
-use std::collections::{HashMap, HashSet};
+use crate::{Application, Error, Result};
+#[cfg(feature = "fhe")]
+use crate::{
+    CallSignature, DepthReport, FheProgramMetadata, Params, ProgramGraph, RelinearizationPolicy,
+    RequiredKeys, SchemeType, SecurityLevel,
+};
+#[cfg(feature = "fhe")]
+use log::warn;
+use std::collections::HashMap;
+#[cfg(feature = "fhe")]
+use std::collections::HashSet;
 use std::marker::PhantomData;
+#[cfg(feature = "fhe")]
 use sunscreen_fhe_program::FheProgramTrait;
-use sunscreen_runtime::{marker, CompiledFheProgram, Fhe, FheZkp, Zkp};
+use sunscreen_runtime::marker;
+#[cfg(all(feature = "fhe", feature = "zkp"))]
+use sunscreen_runtime::FheZkp;
+#[cfg(feature = "zkp")]
+use sunscreen_runtime::Zkp;
+#[cfg(feature = "fhe")]
+use sunscreen_runtime::{CompiledFheProgram, Fhe};
+#[cfg(feature = "zkp")]
 use sunscreen_zkp_backend::{BackendField, CompiledZkpProgram, ZkpBackend};
 
+#[cfg(feature = "fhe")]
 #[derive(Debug, Clone)]
 enum ParamsMode {
     Search,
     Manual(Params),
 }
 
+#[cfg(feature = "fhe")]
+/**
+ * Overrides the parameters a single FHE program is compiled under.
+ */
+#[derive(Debug, Clone)]
+enum ProgramParams {
+    /**
+     * Search for parameters shared by every program in the named group.
+     */
+    Group(String),
+
+    /**
+     * Use exactly the given parameters.
+     */
+    Manual(Params),
+}
+
+#[cfg(feature = "fhe")]
 /**
  * The operations supported by an `#[fhe_program]` function.
  */
@@ -44,52 +84,92 @@ pub trait FheProgramFn {
      * The number of times to chain this FHE program.
      */
     fn chain_count(&self) -> usize;
+
+    /**
+     * The authorization requirements declared with
+     * `#[fhe_program(requires = "...")]`. See
+     * [`PolicyProvider`](crate::PolicyProvider).
+     */
+    fn requirements(&self) -> Vec<String> {
+        vec![]
+    }
+
+    /**
+     * The names of the FHE program's arguments, in order. See
+     * [`GenericRuntime::encrypt_for`](crate::GenericRuntime::encrypt_for).
+     */
+    fn argument_names(&self) -> Vec<String> {
+        vec![]
+    }
 }
 
+#[cfg(feature = "fhe")]
 struct FheCompilerData {
     fhe_program_fns: Vec<Box<dyn FheProgramFn>>,
+    program_params: HashMap<String, ProgramParams>,
     params_mode: ParamsMode,
     plain_modulus_constraint: PlainModulusConstraint,
     security_level: SecurityLevel,
     noise_margin: u32,
+    deny_unused_inputs: bool,
+    deterministic_params: bool,
+    verify_passes: bool,
+    relinearization: RelinearizationPolicy,
 }
 
+#[cfg(feature = "fhe")]
 impl Default for FheCompilerData {
     fn default() -> Self {
         Self {
             fhe_program_fns: vec![],
+            program_params: HashMap::new(),
             params_mode: ParamsMode::Search,
             // This default value is sufficient for doing 3 levels of 64-bit
             // multiplications
             plain_modulus_constraint: PlainModulusConstraint::Raw(262_144),
             security_level: SecurityLevel::TC128,
             noise_margin: 20,
+            deny_unused_inputs: false,
+            deterministic_params: false,
+            verify_passes: false,
+            relinearization: RelinearizationPolicy::default(),
         }
     }
 }
 
+#[cfg_attr(not(feature = "zkp"), allow(dead_code))]
 impl<B> Default for ZkpCompilerData<B> {
     fn default() -> Self {
         Self {
             zkp_program_fns: vec![],
+            #[cfg(feature = "zkp")]
+            limits: ZkpLimits::default(),
         }
     }
 }
 
+#[cfg_attr(not(feature = "zkp"), allow(dead_code))]
 struct ZkpCompilerData<B> {
     // In practice, B should always be BoxZkpFn<Field = F>> where
     // F: BackendField.
     zkp_program_fns: Vec<B>,
+    #[cfg(feature = "zkp")]
+    limits: ZkpLimits,
 }
 
+#[cfg_attr(not(feature = "zkp"), allow(dead_code))]
 enum CompilerData<B> {
     None,
+    #[cfg(feature = "fhe")]
     Fhe(FheCompilerData),
     Zkp(ZkpCompilerData<B>),
+    #[cfg(feature = "fhe")]
     FheZkp(FheCompilerData, ZkpCompilerData<B>),
 }
 
+#[cfg_attr(not(feature = "zkp"), allow(dead_code))]
 impl<B> CompilerData<B> {
+    #[cfg(feature = "fhe")]
     fn new_fhe(data: FheCompilerData) -> Self {
         Self::Fhe(data)
     }
@@ -98,6 +178,7 @@ impl<B> CompilerData<B> {
         Self::Zkp(data)
     }
 
+    #[cfg(feature = "fhe")]
     fn new_fhe_zkp(fhe_data: FheCompilerData, zkp_data: ZkpCompilerData<B>) -> Self {
         Self::FheZkp(fhe_data, zkp_data)
     }
@@ -105,6 +186,7 @@ impl<B> CompilerData<B> {
     fn zkp_data_mut(&mut self) -> &mut ZkpCompilerData<B> {
         match self {
             Self::Zkp(d) => d,
+            #[cfg(feature = "fhe")]
             Self::FheZkp(_, d) => d,
             _ => unreachable!(),
         }
@@ -113,11 +195,13 @@ impl<B> CompilerData<B> {
     fn zkp_data(&self) -> &ZkpCompilerData<B> {
         match self {
             Self::Zkp(d) => d,
+            #[cfg(feature = "fhe")]
             Self::FheZkp(_, d) => d,
             _ => unreachable!(),
         }
     }
 
+    #[cfg(feature = "fhe")]
     fn fhe_data_mut(&mut self) -> &mut FheCompilerData {
         match self {
             Self::Fhe(d) => d,
@@ -126,6 +210,7 @@ impl<B> CompilerData<B> {
         }
     }
 
+    #[cfg(feature = "fhe")]
     fn fhe_data(&self) -> &FheCompilerData {
         match self {
             Self::Fhe(d) => d,
@@ -137,11 +222,13 @@ impl<B> CompilerData<B> {
     fn unwrap_zkp(self) -> ZkpCompilerData<B> {
         match self {
             Self::Zkp(d) => d,
+            #[cfg(feature = "fhe")]
             Self::FheZkp(_, d) => d,
             _ => unreachable!(),
         }
     }
 
+    #[cfg(feature = "fhe")]
     fn unwrap_fhe(self) -> FheCompilerData {
         match self {
             Self::Fhe(d) => d,
@@ -151,6 +238,7 @@ impl<B> CompilerData<B> {
     }
 }
 
+#[cfg(feature = "zkp")]
 type BoxZkpFn<F> = Box<dyn ZkpProgramFn<F>>;
 
 /**
@@ -178,6 +266,7 @@ impl Compiler {
         }
     }
 
+    #[cfg(feature = "fhe")]
     /**
      * Add the given FHE program for compilation.
      */
@@ -196,6 +285,41 @@ impl Compiler {
         }
     }
 
+    #[cfg(feature = "fhe")]
+    /**
+     * Add the given FHE program for compilation under exactly the given
+     * parameters rather than those shared by the other programs.
+     */
+    pub fn fhe_program_with_params<F>(self, fhe_program_fn: F, params: &Params) -> FheCompiler
+    where
+        F: FheProgramFn + 'static,
+    {
+        let compiler: FheCompiler = GenericCompiler {
+            data: CompilerData::new_fhe(FheCompilerData::default()),
+            _phantom: PhantomData,
+        };
+
+        compiler.fhe_program_with_params(fhe_program_fn, params)
+    }
+
+    #[cfg(feature = "fhe")]
+    /**
+     * Add the given FHE program for compilation as part of the named
+     * group. The compiler searches for parameters separately for each group.
+     */
+    pub fn fhe_program_in_group<F>(self, fhe_program_fn: F, group: &str) -> FheCompiler
+    where
+        F: FheProgramFn + 'static,
+    {
+        let compiler: FheCompiler = GenericCompiler {
+            data: CompilerData::new_fhe(FheCompilerData::default()),
+            _phantom: PhantomData,
+        };
+
+        compiler.fhe_program_in_group(fhe_program_fn, group)
+    }
+
+    #[cfg(feature = "zkp")]
     /**
      * Sets the ZKP backend target.
      */
@@ -209,12 +333,17 @@ impl Compiler {
     }
 }
 
+#[cfg(feature = "fhe")]
 impl<T, B> GenericCompiler<T, B> {
-    fn compile_fhe(&self) -> Result<HashMap<String, CompiledFheProgram>> {
+    /**
+     * Compiles the FHE programs and returns them along with the parameters
+     * shared by programs without a per-program override, if any.
+     */
+    fn compile_fhe(&self) -> Result<(HashMap<String, CompiledFheProgram>, Option<Params>)> {
         let fhe_data: &FheCompilerData = self.data.fhe_data();
 
         if fhe_data.fhe_program_fns.is_empty() {
-            return Ok(HashMap::new());
+            return Ok((HashMap::new(), None));
         }
 
         // Check that all programs use the same scheme type.
@@ -265,24 +394,75 @@ impl<T, B> GenericCompiler<T, B> {
 
         let scheme = fhe_data.fhe_program_fns.first().unwrap().scheme_type();
 
-        let params = match &fhe_data.params_mode {
-            ParamsMode::Manual(p) => p.clone(),
-            ParamsMode::Search => determine_params(
-                &fhe_data.fhe_program_fns,
+        let search = |programs: &[&dyn FheProgramFn]| {
+            determine_params_with_options(
+                programs,
                 fhe_data.plain_modulus_constraint,
                 fhe_data.security_level,
                 fhe_data.noise_margin,
                 scheme,
-            )?,
+                fhe_data.deterministic_params,
+                fhe_data.relinearization,
+            )
         };
 
+        let default_programs = fhe_data
+            .fhe_program_fns
+            .iter()
+            .filter(|p| !fhe_data.program_params.contains_key(p.name()))
+            .map(|p| p.as_ref())
+            .collect::<Vec<_>>();
+
+        let default_params = if default_programs.is_empty() {
+            None
+        } else {
+            Some(match &fhe_data.params_mode {
+                ParamsMode::Manual(p) => p.clone(),
+                ParamsMode::Search => search(default_programs.as_slice())?,
+            })
+        };
+
+        let mut groups: HashMap<&str, Vec<&dyn FheProgramFn>> = HashMap::new();
+
+        for prog in fhe_data.fhe_program_fns.iter() {
+            if let Some(ProgramParams::Group(group)) = fhe_data.program_params.get(prog.name()) {
+                groups.entry(group).or_default().push(prog.as_ref());
+            }
+        }
+
+        let group_params = groups
+            .iter()
+            .map(|(group, programs)| Ok((*group, search(programs.as_slice())?)))
+            .collect::<Result<HashMap<_, _>>>()?;
+
         let fhe_programs = fhe_data
             .fhe_program_fns
             .iter()
             .map(|prog| {
-                let execution_graph = prog.build(&params);
+                let params = match fhe_data.program_params.get(prog.name()) {
+                    // Programs without an override are in default_programs.
+                    None => default_params.as_ref().unwrap(),
+                    Some(ProgramParams::Group(group)) => &group_params[group.as_str()],
+                    Some(ProgramParams::Manual(p)) => p,
+                };
+
+                let execution_graph = prog.build(params)?;
+                let depth_before = execution_graph.multiplicative_depth();
                 let mut required_keys = vec![];
-                let fhe_program_fn = execution_graph?.compile();
+
+                let fhe_program_fn = if fhe_data.verify_passes {
+                    execution_graph.compile_verified_with_policy(fhe_data.relinearization)?
+                } else {
+                    execution_graph.compile_with_policy(fhe_data.relinearization)
+                };
+
+                if !fhe_program_fn.unused_inputs().is_empty() {
+                    if fhe_data.deny_unused_inputs {
+                        return Err(Error::unused_inputs(prog.name()));
+                    }
+
+                    warn!("FHE program {} has unused inputs", prog.name());
+                }
 
                 if fhe_program_fn.requires_relin_keys() {
                     required_keys.push(RequiredKeys::Relin);
@@ -292,10 +472,21 @@ impl<T, B> GenericCompiler<T, B> {
                     required_keys.push(RequiredKeys::Galois);
                 }
 
+                // Programs only draw randomness when encrypting, which
+                // requires the public key.
+                let deterministic = !required_keys.contains(&RequiredKeys::PublicKey);
+
                 let metadata = FheProgramMetadata {
                     params: params.clone(),
+                    deterministic,
                     required_keys,
                     signature: prog.signature(),
+                    requirements: prog.requirements(),
+                    argument_names: prog.argument_names(),
+                    multiplicative_depth: DepthReport {
+                        before: depth_before,
+                        after: ProgramGraph::new(&fhe_program_fn).multiplicative_depth(),
+                    },
                 };
 
                 let compiled_program = CompiledFheProgram {
@@ -307,10 +498,11 @@ impl<T, B> GenericCompiler<T, B> {
             })
             .collect::<Result<HashMap<_, _>>>()?;
 
-        Ok(fhe_programs)
+        Ok((fhe_programs, default_params))
     }
 }
 
+#[cfg(feature = "zkp")]
 impl<T, B> GenericCompiler<T, BoxZkpFn<B>>
 where
     B: BackendField,
@@ -323,6 +515,12 @@ where
             .iter()
             .map(|prog| {
                 let result = prog.build()?;
+
+                zkp_data
+                    .limits
+                    .check(prog.name(), &CircuitSize::of(&result))
+                    .map_err(|e| Error::ZkpBudgetExceeded(Box::new(e)))?;
+
                 let result = zkp::compile(&result);
 
                 Ok((prog.name().to_owned(), result))
@@ -333,10 +531,11 @@ where
     }
 
     fn compile_internal(self) -> Result<Application<T>> {
-        Application::new(HashMap::new(), self.compile_zkp()?)
+        Application::new_zkp(self.compile_zkp()?)
     }
 }
 
+#[cfg(feature = "fhe")]
 impl FheCompiler {
     /**
      * Add the given FHE program for compilation.
@@ -352,6 +551,57 @@ impl FheCompiler {
         self
     }
 
+    /**
+     * Add the given FHE program for compilation under exactly the given
+     * parameters rather than those shared by the other programs.
+     *
+     * # Remarks
+     * Ciphertexts can only be passed between programs compiled under the
+     * same parameters, and running this program requires a runtime and
+     * keys created from its [`FheProgramMetadata::params`].
+     */
+    pub fn fhe_program_with_params<F>(mut self, fhe_program_fn: F, params: &Params) -> FheCompiler
+    where
+        F: FheProgramFn + 'static,
+    {
+        let name = fhe_program_fn.name().to_owned();
+
+        self = self.fhe_program(fhe_program_fn);
+        self.data
+            .fhe_data_mut()
+            .program_params
+            .insert(name, ProgramParams::Manual(params.clone()));
+        self
+    }
+
+    /**
+     * Add the given FHE program for compilation as part of the named
+     * group. The compiler searches for parameters separately for each group,
+     * so programs with very different multiplicative depths can each get
+     * appropriately sized (and thus faster) parameters.
+     *
+     * # Remarks
+     * Programs added with [`fhe_program`](Self::fhe_program) form their own
+     * group. Every program in a group is compiled under the same parameters,
+     * so ciphertexts can be passed between them, but not between groups.
+     * Running a program requires a runtime and keys created from its
+     * [`FheProgramMetadata::params`].
+     */
+    pub fn fhe_program_in_group<F>(mut self, fhe_program_fn: F, group: &str) -> FheCompiler
+    where
+        F: FheProgramFn + 'static,
+    {
+        let name = fhe_program_fn.name().to_owned();
+
+        self = self.fhe_program(fhe_program_fn);
+        self.data
+            .fhe_data_mut()
+            .program_params
+            .insert(name, ProgramParams::Group(group.to_owned()));
+        self
+    }
+
+    #[cfg(feature = "zkp")]
     /**
      * Set a ZKP backend for compiling ZKP programs.
      */
@@ -372,7 +622,9 @@ impl FheCompiler {
      * # Remarks
      * Each compiled FHE program in the returned [`Application`]
      * is compiled under the same [`Params`] so ciphertexts can be
-     * used interchangeably between programs.
+     * used interchangeably between programs, except for those added with
+     * [`fhe_program_with_params`](Self::fhe_program_with_params) or
+     * [`fhe_program_in_group`](Self::fhe_program_in_group).
      *
      * Each specified FHE program must have a unique name,
      * regardless of its parent module or crate. `compile` returns
@@ -383,14 +635,18 @@ impl FheCompiler {
      * will return a [`Error::NameCollision`] error.
      */
     pub fn compile(self) -> Result<Application<Fhe>> {
-        Application::new(self.compile_fhe()?, HashMap::new())
+        let (fhe_programs, params) = self.compile_fhe()?;
+
+        Application::new_fhe(fhe_programs, params)
     }
 }
 
+#[cfg(feature = "zkp")]
 impl<B> ZkpCompiler<B>
 where
     B: BackendField,
 {
+    #[cfg(feature = "fhe")]
     /**
      * Add the given FHE program for compilation.
      */
@@ -437,6 +693,7 @@ where
     }
 }
 
+#[cfg(all(feature = "fhe", feature = "zkp"))]
 impl<B> FheZkpCompiler<B>
 where
     B: BackendField,
@@ -492,6 +749,39 @@ where
     }
 }
 
+#[cfg(feature = "zkp")]
+impl<T, B> GenericCompiler<T, B>
+where
+    T: marker::Zkp,
+{
+    /**
+     * Fail compilation with [`Error::ZkpBudgetExceeded`] if any ZKP
+     * program has more than `max` multiplication gates and equality
+     * constraints.
+     *
+     * # Remarks
+     * Use this to catch circuits that would exhaust the prover's memory,
+     * e.g. a gadget accidentally invoked inside a large loop, before they
+     * reach production. The error names the gadget contributing the most
+     * hidden inputs.
+     */
+    pub fn max_constraints(mut self, max: usize) -> Self {
+        self.data.zkp_data_mut().limits.max_constraints = Some(max);
+        self
+    }
+
+    /**
+     * Fail compilation with [`Error::ZkpBudgetExceeded`] if any ZKP
+     * program's witness, i.e. its private inputs plus the hidden inputs
+     * of every gadget invocation, has more than `max` values.
+     */
+    pub fn max_witness_size(mut self, max: usize) -> Self {
+        self.data.zkp_data_mut().limits.max_witness_size = Some(max);
+        self
+    }
+}
+
+#[cfg(feature = "fhe")]
 impl<T, B> GenericCompiler<T, B>
 where
     T: marker::Fhe,
@@ -538,6 +828,71 @@ where
         self.data.fhe_data_mut().noise_margin = noise_margin;
         self
     }
+
+    /**
+     * Fail compilation with [`Error::UnusedInputs`] if any FHE program
+     * has an argument on which none of its outputs depend. By default,
+     * the compiler only logs a warning.
+     */
+    pub fn deny_unused_inputs(mut self) -> Self {
+        self.data.fhe_data_mut().deny_unused_inputs = true;
+        self
+    }
+
+    /**
+     * Check each FHE program's invariants (operand types, acyclicity and
+     * relinearization) before and after every backend pass, failing with
+     * [`Error::Miscompilation`] instead of producing a program that
+     * decrypts to garbage.
+     *
+     * # Remarks
+     * Verification walks every program once per pass, so it's off by
+     * default.
+     */
+    pub fn verify_passes(mut self) -> Self {
+        self.data.fhe_data_mut().verify_passes = true;
+        self
+    }
+
+    /**
+     * Where to relinearize the results of ciphertext multiplications.
+     * Defaults to [`RelinearizationPolicy::Always`].
+     *
+     * # Remarks
+     * [`Lazy`](RelinearizationPolicy::Lazy) and
+     * [`SizeExceeds`](RelinearizationPolicy::SizeExceeds) skip
+     * relinearizations where later operations don't need them, e.g. when
+     * summing products. This saves time, but larger ciphertexts make the
+     * operations between multiplications and relinearizations slower and
+     * noisier, so the parameter search may pick larger parameters.
+     * Programs that end up never relinearizing don't need relinearization
+     * keys.
+     */
+    pub fn relinearization_policy(mut self, policy: RelinearizationPolicy) -> Self {
+        self.data.fhe_data_mut().relinearization = policy;
+        self
+    }
+
+    /**
+     * Make the parameter search select the same parameters for the same
+     * FHE programs on every build machine, e.g. across x86_64 and aarch64.
+     *
+     * # Remarks
+     * The search measures noise by running each candidate program on
+     * ciphertexts encrypted with fresh randomness, so measurements vary
+     * slightly between runs. Without this flag, a program whose noise lands
+     * right at the target can select different parameters from one build
+     * to the next. With it, the search encrypts from a fixed seed, compares
+     * noise in whole bits of budget and requires
+     * [`DETERMINISTIC_GUARD_BITS`](crate::DETERMINISTIC_GUARD_BITS) of
+     * budget beyond [`additional_noise_budget`](Self::additional_noise_budget),
+     * so the same programs select the same parameters on every build. This
+     * may select larger parameters than the default search.
+     */
+    pub fn deterministic_params(mut self) -> Self {
+        self.data.fhe_data_mut().deterministic_params = true;
+        self
+    }
 }
 
 /**
@@ -546,21 +901,30 @@ where
  * and new configurations become valid.
  */
 pub type Compiler = GenericCompiler<(), ()>;
+#[cfg(feature = "fhe")]
 pub type FheCompiler = GenericCompiler<Fhe, ()>;
+#[cfg(feature = "zkp")]
 pub type ZkpCompiler<F> = GenericCompiler<Zkp, BoxZkpFn<F>>;
+#[cfg(all(feature = "fhe", feature = "zkp"))]
 pub type FheZkpCompiler<F> = GenericCompiler<FheZkp, BoxZkpFn<F>>;
 
 #[cfg(test)]
 mod tests {
     use std::any::{Any, TypeId};
 
-    use sunscreen_compiler_macros::{fhe_program, zkp_program};
+    #[cfg(feature = "fhe")]
+    use sunscreen_compiler_macros::fhe_program;
+    #[cfg(feature = "zkp")]
+    use sunscreen_compiler_macros::zkp_program;
+    #[cfg(feature = "zkp")]
     use sunscreen_zkp_backend::bulletproofs::BulletproofsBackend;
 
     use super::*;
 
     // Needed to make the fhe_program macro work.
-    use crate::{self as sunscreen, types::zkp::NativeField};
+    #[cfg(all(feature = "fhe", feature = "zkp"))]
+    use crate::types::zkp::NativeField;
+    use crate::{self as sunscreen};
 
     #[test]
     fn raw_compiler_has_correct_type() {
@@ -570,6 +934,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "fhe")]
     fn fhe_program_yields_fhe_compiler() {
         #[fhe_program(scheme = "bfv")]
         fn kitty() {}
@@ -580,6 +945,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "zkp")]
     fn zkp_program_yields_zkp_compiler() {
         let c = Compiler::new().zkp_backend::<BulletproofsBackend>();
 
@@ -590,6 +956,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(all(feature = "fhe", feature = "zkp"))]
     fn fhe_zkp_program_yields_fhezkp_compiler() {
         #[zkp_program(backend = "bulletproofs")]
         fn kitty<F: BackendField>() {}
@@ -608,6 +975,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "fhe")]
     fn compiling_fhe_program_yields_fhe_application() {
         #[fhe_program(scheme = "bfv")]
         fn kitty() {}
@@ -618,6 +986,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "zkp")]
     fn compiling_zkp_program_yields_zkp_application() {
         #[zkp_program(backend = "bulletproofs")]
         fn kitty<F: BackendField>() {}
@@ -632,6 +1001,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(all(feature = "fhe", feature = "zkp"))]
     fn compiling_fhe_and_zkp_program_yields_fhezkp_application() {
         #[zkp_program(backend = "bulletproofs")]
         fn kitty<F: BackendField>(_a: NativeField<F>) {}
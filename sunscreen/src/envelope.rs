@@ -0,0 +1,334 @@
+use std::collections::{HashSet, VecDeque};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use sunscreen_runtime::{Ciphertext, CompiledFheProgram};
+use sunscreen_zkp_backend::Proof;
+
+use crate::{fhe_program_hash, Error, FheProgramHash, Result, RuntimeError};
+
+/**
+ * A SHA3-256 hash identifying an [`Envelope`] or its contents.
+ */
+pub type EnvelopeDigest = [u8; 32];
+
+/**
+ * How far into the future a client's clock may run ahead of the
+ * server's before [`ReplayGuard`] rejects its envelopes.
+ */
+const MAX_CLOCK_SKEW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+/**
+ * Why a [`ReplayGuard`] rejected an [`Envelope`].
+ */
+pub enum EnvelopeError {
+    /**
+     * The envelope targets a different FHE program than the one the
+     * server is about to run.
+     */
+    #[error("Envelope targets a different FHE program")]
+    ProgramMismatch,
+
+    /**
+     * The envelope is older than the guard's maximum age.
+     */
+    #[error("Envelope expired")]
+    Expired,
+
+    /**
+     * The envelope's timestamp is too far in the future.
+     */
+    #[error("Envelope timestamp is in the future")]
+    FromFuture,
+
+    /**
+     * The envelope's nonce or its ciphertexts and proofs were already
+     * accepted.
+     */
+    #[error("Envelope was replayed")]
+    Replayed,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+/**
+ * Binds ciphertexts and proofs submitted together to the FHE program
+ * they're for, a client-chosen nonce and the time they were sent.
+ *
+ * # Remarks
+ * A server that accepts ciphertexts with proofs of their well-formedness
+ * should receive them in an envelope and pass it to a [`ReplayGuard`]
+ * before running anything. Otherwise, an eavesdropper can resubmit
+ * another client's valid ciphertexts and proofs, either to the same
+ * program or to a different one that accepts the same input types.
+ *
+ * Clients should create the envelope first and prove with
+ * [`prove_with_context`](crate::GenericRuntime::prove_with_context), passing
+ * the envelope's [`proof_context`](Self::proof_context), and servers
+ * should verify with the same context. The proofs then only verify in
+ * this envelope, so they can't be moved into another with a fresh nonce
+ * or timestamp.
+ */
+pub struct Envelope {
+    /**
+     * The hash of the FHE program the ciphertexts are inputs to.
+     */
+    pub program: FheProgramHash,
+
+    /**
+     * The ciphertexts, in the program's argument order.
+     */
+    pub ciphertexts: Vec<Ciphertext>,
+
+    /**
+     * The proofs about the ciphertexts.
+     */
+    pub proofs: Vec<Proof>,
+
+    /**
+     * A random value the client picks for each submission.
+     */
+    pub nonce: [u8; 32],
+
+    /**
+     * When the client created the envelope, in seconds since the Unix
+     * epoch.
+     */
+    pub timestamp: u64,
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl Envelope {
+    /**
+     * Creates an envelope for the given program's inputs with a random
+     * nonce and the current time.
+     */
+    pub fn new(
+        program: &CompiledFheProgram,
+        ciphertexts: Vec<Ciphertext>,
+        proofs: Vec<Proof>,
+    ) -> Self {
+        Self {
+            program: fhe_program_hash(program),
+            ciphertexts,
+            proofs,
+            nonce: rand::random(),
+            timestamp: unix_time(),
+        }
+    }
+
+    /**
+     * Hashes the ciphertexts and proofs, but not the program, nonce or
+     * timestamp.
+     *
+     * # Remarks
+     * Fresh ciphertexts are randomized, so two honest submissions never
+     * share a content digest. [`ReplayGuard`] uses this to catch
+     * ciphertexts and proofs rewrapped in a new envelope.
+     *
+     * Returns [`RuntimeError::BincodeError`] if a ciphertext or proof
+     * fails to serialize.
+     */
+    pub fn content_digest(&self) -> Result<EnvelopeDigest> {
+        let mut hasher = Sha3_256::new();
+
+        hasher.update(b"sunscreen-envelope-content");
+        hasher.update((self.ciphertexts.len() as u64).to_le_bytes());
+
+        for c in self.ciphertexts.iter() {
+            update_serialized(&mut hasher, c)?;
+        }
+
+        hasher.update((self.proofs.len() as u64).to_le_bytes());
+
+        for p in self.proofs.iter() {
+            update_serialized(&mut hasher, p)?;
+        }
+
+        Ok(hasher.finalize().into())
+    }
+
+    /**
+     * Hashes the program, nonce and timestamp, which proofs about the
+     * ciphertexts should be bound to (see [`Envelope`]).
+     */
+    pub fn proof_context(&self) -> EnvelopeDigest {
+        let mut hasher = Sha3_256::new();
+
+        hasher.update(b"sunscreen-envelope-proof-context");
+        hasher.update(self.program);
+        hasher.update(self.nonce);
+        hasher.update(self.timestamp.to_le_bytes());
+
+        hasher.finalize().into()
+    }
+
+    /**
+     * Hashes every field of the envelope. Servers can log this digest as
+     * a receipt for the submission.
+     *
+     * Returns [`RuntimeError::BincodeError`] if a ciphertext or proof
+     * fails to serialize.
+     */
+    pub fn digest(&self) -> Result<EnvelopeDigest> {
+        Ok(self.digest_with_content(&self.content_digest()?))
+    }
+
+    fn digest_with_content(&self, content: &EnvelopeDigest) -> EnvelopeDigest {
+        let mut hasher = Sha3_256::new();
+
+        hasher.update(b"sunscreen-envelope");
+        hasher.update(self.program);
+        hasher.update(self.nonce);
+        hasher.update(self.timestamp.to_le_bytes());
+        hasher.update(content);
+
+        hasher.finalize().into()
+    }
+}
+
+/**
+ * Feeds the length-prefixed bincode serialization of `value` to `hasher`.
+ */
+fn update_serialized<T: Serialize>(hasher: &mut Sha3_256, value: &T) -> Result<()> {
+    let bytes = bincode::serialize(value).map_err(RuntimeError::from)?;
+
+    hasher.update((bytes.len() as u64).to_le_bytes());
+    hasher.update(bytes);
+
+    Ok(())
+}
+
+/**
+ * Checks [`Envelope`]s a server receives and records the accepted ones so
+ * none can be submitted twice.
+ *
+ * # Remarks
+ * The guard rejects an envelope if it targets another program, is older
+ * than the maximum age, or repeats an accepted envelope's nonce or
+ * contents. Nonces and content digests only need remembering for the
+ * maximum age, after which [`check`](Self::check) rejects their envelopes
+ * as expired, so the guard forgets them. Rewrapping old contents in an
+ * envelope with a fresh timestamp fails proof verification, as the
+ * proofs are bound to the original envelope's
+ * [`proof_context`](Envelope::proof_context). Ciphertexts submitted
+ * without proofs are only protected for the maximum age.
+ *
+ * The guard only protects a single server. Servers behind a load balancer
+ * must share it (e.g. behind a lock in a service all of them call).
+ */
+pub struct ReplayGuard {
+    max_age: Duration,
+    accepted: VecDeque<AcceptedEnvelope>,
+    seen_nonces: HashSet<[u8; 32]>,
+    seen_contents: HashSet<EnvelopeDigest>,
+}
+
+/**
+ * What a [`ReplayGuard`] remembers about an envelope it accepted.
+ */
+struct AcceptedEnvelope {
+    nonce: [u8; 32],
+    content: EnvelopeDigest,
+    timestamp: u64,
+}
+
+impl ReplayGuard {
+    /**
+     * Creates a guard accepting envelopes at most `max_age` old.
+     */
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            max_age,
+            accepted: VecDeque::new(),
+            seen_nonces: HashSet::new(),
+            seen_contents: HashSet::new(),
+        }
+    }
+
+    /**
+     * Checks and records an envelope submitted for `program`. On success,
+     * returns the envelope's [`digest`](Envelope::digest).
+     *
+     * # Remarks
+     * Verify the envelope's proofs against its
+     * [`proof_context`](Envelope::proof_context) before calling this, so
+     * a client whose proofs fail can't claim a nonce. Returns
+     * [`Error::Envelope`] if the envelope is rejected and
+     * [`RuntimeError::BincodeError`] if its contents fail to serialize, in
+     * which case nothing is recorded.
+     */
+    pub fn check(
+        &mut self,
+        envelope: &Envelope,
+        program: &CompiledFheProgram,
+    ) -> Result<EnvelopeDigest> {
+        self.check_at(envelope, program, unix_time())
+    }
+
+    /**
+     * Like [`check`](Self::check), but at the given time in seconds since
+     * the Unix epoch rather than the current time.
+     */
+    pub fn check_at(
+        &mut self,
+        envelope: &Envelope,
+        program: &CompiledFheProgram,
+        now: u64,
+    ) -> Result<EnvelopeDigest> {
+        if envelope.program != fhe_program_hash(program) {
+            return Err(Error::Envelope(EnvelopeError::ProgramMismatch));
+        }
+
+        if envelope.timestamp > now.saturating_add(MAX_CLOCK_SKEW.as_secs()) {
+            return Err(Error::Envelope(EnvelopeError::FromFuture));
+        }
+
+        let oldest = now.saturating_sub(self.max_age.as_secs());
+
+        if envelope.timestamp < oldest {
+            return Err(Error::Envelope(EnvelopeError::Expired));
+        }
+
+        self.forget_before(oldest);
+
+        let content = envelope.content_digest()?;
+
+        if self.seen_nonces.contains(&envelope.nonce) || self.seen_contents.contains(&content) {
+            return Err(Error::Envelope(EnvelopeError::Replayed));
+        }
+
+        self.seen_nonces.insert(envelope.nonce);
+        self.seen_contents.insert(content);
+        self.accepted.push_back(AcceptedEnvelope {
+            nonce: envelope.nonce,
+            content,
+            timestamp: envelope.timestamp,
+        });
+
+        Ok(envelope.digest_with_content(&content))
+    }
+
+    fn forget_before(&mut self, oldest: u64) {
+        let seen_nonces = &mut self.seen_nonces;
+        let seen_contents = &mut self.seen_contents;
+
+        self.accepted.retain(|e| {
+            let keep = e.timestamp >= oldest;
+
+            if !keep {
+                seen_nonces.remove(&e.nonce);
+                seen_contents.remove(&e.content);
+            }
+
+            keep
+        });
+    }
+}
@@ -57,6 +57,7 @@ pub enum Error {
     /**
      * An internal error occurred in the SEAL library.
      */
+    #[cfg(feature = "fhe")]
     #[error("SEAL error: {0}")]
     SealError(#[from] seal_fhe::Error),
 
@@ -69,6 +70,7 @@ pub enum Error {
     /**
      * The compiled Sunscreen FHE program is malformed.
      */
+    #[cfg(feature = "fhe")]
     #[error("FHE program error: {0}")]
     FheProgramError(sunscreen_fhe_program::Error),
 
@@ -77,6 +79,69 @@ pub enum Error {
      */
     #[error("Unsupported: {0}")]
     Unsupported(Box<String>),
+
+    /**
+     * An FHE program has arguments on which none of its outputs depend.
+     * Only returned when the compiler is configured with
+     * [`deny_unused_inputs`](crate::GenericCompiler::deny_unused_inputs).
+     */
+    #[error("FHE program {0} has unused inputs")]
+    UnusedInputs(Box<String>),
+
+    /**
+     * A backend compiler pass broke an FHE program invariant. Only
+     * returned when the compiler is configured with
+     * [`verify_passes`](crate::GenericCompiler::verify_passes).
+     */
+    #[cfg(feature = "fhe")]
+    #[error("{0}")]
+    Miscompilation(Box<sunscreen_backend::VerificationFailure>),
+
+    /**
+     * A [`PsiReceiver`](crate::protocols::psi::PsiReceiver)'s items didn't
+     * fit into the available bins. Use fewer items or parameters with more
+     * batching slots.
+     */
+    #[error("PSI set doesn't fit into the available bins")]
+    PsiSetTooLarge,
+
+    /**
+     * A [`CiphertextStore`](crate::CiphertextStore) failed to list, load or
+     * write back a ciphertext.
+     */
+    #[error("Ciphertext store error: {0}")]
+    CiphertextStore(Box<String>),
+
+    /**
+     * A [`Reencrypt`](crate::Reencrypt) strategy returned a different
+     * number of ciphertexts than it was given.
+     */
+    #[error("Re-encryption strategy returned the wrong number of ciphertexts")]
+    ReencryptionBatchSize,
+
+    /**
+     * A [threshold decryption](crate::protocols::threshold) step received
+     * invalid arguments, e.g. too few participants or mismatched shares.
+     */
+    #[error("Threshold decryption error: {0}")]
+    ThresholdDecryption(Box<String>),
+
+    /**
+     * A [`ReplayGuard`](crate::ReplayGuard) rejected an
+     * [`Envelope`](crate::Envelope).
+     */
+    #[cfg(feature = "linkedproofs")]
+    #[error("Envelope rejected: {0}")]
+    Envelope(crate::EnvelopeError),
+
+    /**
+     * A ZKP program's circuit exceeded a limit set with
+     * [`max_constraints`](crate::GenericCompiler::max_constraints) or
+     * [`max_witness_size`](crate::GenericCompiler::max_witness_size).
+     */
+    #[cfg(feature = "zkp")]
+    #[error("{0}")]
+    ZkpBudgetExceeded(Box<crate::ZkpBudgetError>),
 }
 
 const_assert!(std::mem::size_of::<Error>() <= 24);
@@ -88,6 +153,20 @@ impl Error {
     pub fn unsupported(msg: &str) -> Self {
         Self::Unsupported(Box::new(msg.to_owned()))
     }
+
+    /**
+     * Create an [`Error::UnusedInputs`]
+     */
+    pub fn unused_inputs(program_name: &str) -> Self {
+        Self::UnusedInputs(Box::new(program_name.to_owned()))
+    }
+
+    /**
+     * Create an [`Error::ThresholdDecryption`]
+     */
+    pub fn threshold_decryption(msg: &str) -> Self {
+        Self::ThresholdDecryption(Box::new(msg.to_owned()))
+    }
 }
 
 /**
@@ -1,11 +1,15 @@
 use petgraph::stable_graph::NodeIndex;
 use serde::{Deserialize, Serialize};
-use sunscreen_backend::compile_inplace;
+use sunscreen_backend::{
+    compile_inplace_verified_with_policy, compile_inplace_with_policy, Error as BackendError,
+    RelinearizationPolicy,
+};
 use sunscreen_compiler_common::{
     CompilationResult, Context, EdgeInfo, NodeInfo, Operation as OperationTrait,
 };
 use sunscreen_fhe_program::{
-    FheProgram, Literal as FheProgramLiteral, Operation as FheProgramOperation, SchemeType,
+    FheProgram, Literal as FheProgramLiteral, Operation as FheProgramOperation, ProgramGraph,
+    SchemeType,
 };
 use sunscreen_runtime::{InnerPlaintext, Params};
 
@@ -344,60 +348,115 @@ pub trait FheCompile {
      * Performs frontend compilation of this intermediate representation into a backend [`FheProgram`],
      * then perform backend compilation and return the result.
      */
-    fn compile(&self) -> FheProgram;
+    fn compile(&self) -> FheProgram {
+        self.compile_with_policy(RelinearizationPolicy::default())
+    }
+
+    /**
+     * Like [`compile`](Self::compile), but verifies the program's
+     * invariants after every backend pass.
+     *
+     * Returns [`Error::Miscompilation`](crate::Error::Miscompilation) if a
+     * pass breaks one.
+     */
+    fn compile_verified(&self) -> crate::Result<FheProgram> {
+        self.compile_verified_with_policy(RelinearizationPolicy::default())
+    }
+
+    /**
+     * Like [`compile`](Self::compile), but places relinearizations
+     * according to `policy`.
+     */
+    fn compile_with_policy(&self, policy: RelinearizationPolicy) -> FheProgram;
+
+    /**
+     * Like [`compile_verified`](Self::compile_verified), but places
+     * relinearizations according to `policy`.
+     */
+    fn compile_verified_with_policy(
+        &self,
+        policy: RelinearizationPolicy,
+    ) -> crate::Result<FheProgram>;
+
+    /**
+     * Returns the multiplicative depth of the program as written, before
+     * any backend pass runs.
+     */
+    fn multiplicative_depth(&self) -> usize;
 }
 
 impl FheCompile for FheFrontendCompilation {
-    fn compile(&self) -> FheProgram {
-        let mut fhe_program = FheProgram::new(SchemeType::Bfv);
-
-        let mapped_graph = self.0.map(
-            |id, n| match &n.operation {
-                FheOperation::Add => NodeInfo::new(FheProgramOperation::Add),
-                FheOperation::InputCiphertext => {
-                    // HACKHACK: Input nodes are always added first to the graph in the order
-                    // they're specified as function arguments. We should not depend on this.
-                    NodeInfo::new(FheProgramOperation::InputCiphertext(id.index()))
-                }
-                FheOperation::InputPlaintext => {
-                    // HACKHACK: Input nodes are always added first to the graph in the order
-                    // they're specified as function arguments. We should not depend on this.
-                    NodeInfo::new(FheProgramOperation::InputPlaintext(id.index()))
-                }
-                FheOperation::Literal(Literal::U64(x)) => {
-                    NodeInfo::new(FheProgramOperation::Literal(FheProgramLiteral::U64(*x)))
-                }
-                FheOperation::Literal(Literal::Plaintext(x)) => {
-                    // It's okay to unwrap here because fhe_program compilation will
-                    // catch the panic and return a compilation error.
-                    NodeInfo::new(FheProgramOperation::Literal(FheProgramLiteral::Plaintext(
-                        x.to_bytes().expect("Failed to serialize plaintext."),
-                    )))
-                }
-                FheOperation::Sub => NodeInfo::new(FheProgramOperation::Sub),
-                FheOperation::SubPlaintext => NodeInfo::new(FheProgramOperation::SubPlaintext),
-                FheOperation::Negate => NodeInfo::new(FheProgramOperation::Negate),
-                FheOperation::Multiply => NodeInfo::new(FheProgramOperation::Multiply),
-                FheOperation::MultiplyPlaintext => {
-                    NodeInfo::new(FheProgramOperation::MultiplyPlaintext)
-                }
-                FheOperation::Output => NodeInfo::new(FheProgramOperation::OutputCiphertext),
-                FheOperation::RotateLeft => NodeInfo::new(FheProgramOperation::ShiftLeft),
-                FheOperation::RotateRight => NodeInfo::new(FheProgramOperation::ShiftRight),
-                FheOperation::SwapRows => NodeInfo::new(FheProgramOperation::SwapRows),
-                FheOperation::AddPlaintext => NodeInfo::new(FheProgramOperation::AddPlaintext),
-            },
-            |_, e| match e {
-                EdgeInfo::Left => EdgeInfo::Left,
-                EdgeInfo::Right => EdgeInfo::Right,
-                EdgeInfo::Unary => EdgeInfo::Unary,
-                EdgeInfo::Unordered => unreachable!("FHE programs have no unordered edges."),
-                EdgeInfo::Ordered(_) => unreachable!("FHE programs have no ordered edges."),
-            },
-        );
-
-        fhe_program.graph = CompilationResult(mapped_graph);
-
-        compile_inplace(fhe_program)
+    fn compile_with_policy(&self, policy: RelinearizationPolicy) -> FheProgram {
+        compile_inplace_with_policy(lower(self), policy)
+    }
+
+    fn compile_verified_with_policy(
+        &self,
+        policy: RelinearizationPolicy,
+    ) -> crate::Result<FheProgram> {
+        compile_inplace_verified_with_policy(lower(self), policy).map_err(|e| match e {
+            BackendError::VerificationFailed(f) => crate::Error::Miscompilation(f),
+            e => crate::Error::unsupported(&format!("{:?}", e)),
+        })
     }
+
+    fn multiplicative_depth(&self) -> usize {
+        ProgramGraph::new(&lower(self)).multiplicative_depth()
+    }
+}
+
+/**
+ * Maps a frontend compilation onto a backend [`FheProgram`].
+ */
+fn lower(compilation: &FheFrontendCompilation) -> FheProgram {
+    let mut fhe_program = FheProgram::new(SchemeType::Bfv);
+
+    let mapped_graph = compilation.0.map(
+        |id, n| match &n.operation {
+            FheOperation::Add => NodeInfo::new(FheProgramOperation::Add),
+            FheOperation::InputCiphertext => {
+                // HACKHACK: Input nodes are always added first to the graph in the order
+                // they're specified as function arguments. We should not depend on this.
+                NodeInfo::new(FheProgramOperation::InputCiphertext(id.index()))
+            }
+            FheOperation::InputPlaintext => {
+                // HACKHACK: Input nodes are always added first to the graph in the order
+                // they're specified as function arguments. We should not depend on this.
+                NodeInfo::new(FheProgramOperation::InputPlaintext(id.index()))
+            }
+            FheOperation::Literal(Literal::U64(x)) => {
+                NodeInfo::new(FheProgramOperation::Literal(FheProgramLiteral::U64(*x)))
+            }
+            FheOperation::Literal(Literal::Plaintext(x)) => {
+                // It's okay to unwrap here because fhe_program compilation will
+                // catch the panic and return a compilation error.
+                NodeInfo::new(FheProgramOperation::Literal(FheProgramLiteral::Plaintext(
+                    x.to_bytes().expect("Failed to serialize plaintext."),
+                )))
+            }
+            FheOperation::Sub => NodeInfo::new(FheProgramOperation::Sub),
+            FheOperation::SubPlaintext => NodeInfo::new(FheProgramOperation::SubPlaintext),
+            FheOperation::Negate => NodeInfo::new(FheProgramOperation::Negate),
+            FheOperation::Multiply => NodeInfo::new(FheProgramOperation::Multiply),
+            FheOperation::MultiplyPlaintext => {
+                NodeInfo::new(FheProgramOperation::MultiplyPlaintext)
+            }
+            FheOperation::Output => NodeInfo::new(FheProgramOperation::OutputCiphertext),
+            FheOperation::RotateLeft => NodeInfo::new(FheProgramOperation::ShiftLeft),
+            FheOperation::RotateRight => NodeInfo::new(FheProgramOperation::ShiftRight),
+            FheOperation::SwapRows => NodeInfo::new(FheProgramOperation::SwapRows),
+            FheOperation::AddPlaintext => NodeInfo::new(FheProgramOperation::AddPlaintext),
+        },
+        |_, e| match e {
+            EdgeInfo::Left => EdgeInfo::Left,
+            EdgeInfo::Right => EdgeInfo::Right,
+            EdgeInfo::Unary => EdgeInfo::Unary,
+            EdgeInfo::Unordered => unreachable!("FHE programs have no unordered edges."),
+            EdgeInfo::Ordered(_) => unreachable!("FHE programs have no ordered edges."),
+        },
+    );
+
+    fhe_program.graph = CompilationResult(mapped_graph);
+
+    fhe_program
 }
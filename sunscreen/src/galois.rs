@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use rayon::prelude::*;
+use seal_fhe::{
+    galois_elts_from_steps, minimal_rotation_key_steps, rotation_key_steps, GaloisKeys,
+    KeyGenerator,
+};
+use sunscreen_runtime::marker;
+
+use crate::{Application, Result};
+
+/**
+ * Galois keys covering every rotation in an [`Application`], generated by
+ * [`Application::generate_minimal_galois_keys`].
+ */
+pub struct MinimalGaloisKeys {
+    /**
+     * The keys, or [`None`] if no program rotates.
+     */
+    pub keys: Option<GaloisKeys>,
+
+    /**
+     * The Galois elements [`keys`](Self::keys) contain, sorted.
+     */
+    pub elements: Vec<u32>,
+
+    /**
+     * The subset of [`elements`](Self::elements) each program uses, keyed
+     * by program name. Programs that don't rotate map to an empty list.
+     */
+    pub program_elements: HashMap<String, Vec<u32>>,
+}
+
+impl MinimalGaloisKeys {
+    pub(crate) fn generate<T>(app: &Application<T>, keygen: &KeyGenerator) -> Result<Self>
+    where
+        T: marker::Fhe,
+    {
+        let params = app.params();
+        let degree = params.lattice_dimension;
+
+        let programs = app
+            .get_fhe_programs()
+            .filter(|(_, p)| p.metadata.params == *params)
+            .map(|(name, p)| (name.clone(), p.rotation_steps()))
+            .collect::<Vec<_>>();
+
+        let all_steps = programs
+            .iter()
+            .flat_map(|(_, steps)| steps.iter().copied())
+            .collect::<Vec<_>>();
+
+        // Normalizing the steps merges rotations that map to the same
+        // Galois element, e.g. right by 1 and left by N/2 - 1.
+        let key_steps = minimal_rotation_key_steps(&all_steps, degree)?;
+
+        let mut program_elements = HashMap::new();
+
+        for (name, steps) in programs {
+            let mut used = vec![];
+
+            for step in steps {
+                used.extend(rotation_key_steps(step, &key_steps, degree)?.expect(
+                    "Internal error: minimal rotation keys don't cover a program's rotation.",
+                ));
+            }
+
+            used.sort_unstable();
+            used.dedup();
+
+            program_elements.insert(name, galois_elts_from_steps(&used, degree)?);
+        }
+
+        let mut elements = galois_elts_from_steps(&key_steps, degree)?;
+        elements.sort_unstable();
+
+        let keys = if elements.is_empty() {
+            None
+        } else {
+            // Each key takes the same time to generate, so split the
+            // elements evenly across threads and merge the results.
+            let threads = rayon::current_num_threads().max(1);
+            let chunk_size = (elements.len() + threads - 1) / threads;
+
+            let mut parts = elements
+                .par_chunks(chunk_size)
+                .map(|c| keygen.create_galois_keys_from_elts(c))
+                .collect::<seal_fhe::Result<Vec<_>>>()?;
+
+            Some(if parts.len() == 1 {
+                parts.remove(0)
+            } else {
+                GaloisKeys::merge(&parts)?
+            })
+        };
+
+        Ok(Self {
+            keys,
+            elements,
+            program_elements,
+        })
+    }
+}
@@ -0,0 +1,146 @@
+use std::marker::PhantomData;
+
+use sunscreen_runtime::{marker, CompiledFheProgram, FheProgramInput, GenericRuntime};
+
+use crate::{Ciphertext, FheProgramFn, PublicKey, Result};
+
+/**
+ * An `#[fhe_program]` whose argument types are known at compile time. The
+ * `#[fhe_program]` macro implements this for every program.
+ */
+pub trait TypedFheProgramFn: FheProgramFn {
+    /**
+     * The tuple of values [`FheProgramHandle::run`] takes, one per
+     * argument in order: a [`Ciphertext`] for each argument that contains
+     * a `Cipher` and the argument's own type for each plaintext argument.
+     *
+     * # Example
+     * A program declared as
+     * `fn f(a: Cipher<Signed>, b: [Cipher<Signed>; 3], c: Signed)` takes
+     * `(Ciphertext, Ciphertext, Signed)`.
+     */
+    type Arguments: FheProgramArguments;
+}
+
+/**
+ * A tuple of FHE program arguments, each convertible into an
+ * [`FheProgramInput`]. Implemented for tuples of up to 12 values.
+ */
+pub trait FheProgramArguments {
+    /**
+     * Converts the arguments into the inputs
+     * [`GenericRuntime::run`] takes.
+     */
+    fn into_inputs(self) -> Vec<FheProgramInput>;
+}
+
+macro_rules! impl_fhe_program_arguments {
+    ($($t:ident),*) => {
+        impl<$($t),*> FheProgramArguments for ($($t,)*)
+        where
+            $($t: Into<FheProgramInput>,)*
+        {
+            #[allow(non_snake_case)]
+            fn into_inputs(self) -> Vec<FheProgramInput> {
+                let ($($t,)*) = self;
+
+                vec![$($t.into()),*]
+            }
+        }
+    };
+}
+
+impl_fhe_program_arguments!();
+impl_fhe_program_arguments!(A);
+impl_fhe_program_arguments!(A, B);
+impl_fhe_program_arguments!(A, B, C);
+impl_fhe_program_arguments!(A, B, C, D);
+impl_fhe_program_arguments!(A, B, C, D, E);
+impl_fhe_program_arguments!(A, B, C, D, E, F);
+impl_fhe_program_arguments!(A, B, C, D, E, F, G);
+impl_fhe_program_arguments!(A, B, C, D, E, F, G, H);
+impl_fhe_program_arguments!(A, B, C, D, E, F, G, H, I);
+impl_fhe_program_arguments!(A, B, C, D, E, F, G, H, I, J);
+impl_fhe_program_arguments!(A, B, C, D, E, F, G, H, I, J, K);
+impl_fhe_program_arguments!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+/**
+ * A [`CompiledFheProgram`] that knows its argument types, from
+ * [`Application::get_fhe_program_handle`](crate::Application::get_fhe_program_handle).
+ *
+ * # Remarks
+ * Running a program through its handle checks the number of arguments
+ * and whether each is a ciphertext or a plaintext at compile time, rather
+ * than failing with [`RuntimeError::ArgumentMismatch`](crate::RuntimeError::ArgumentMismatch)
+ * when the program runs. The runtime still checks the type inside each
+ * [`Ciphertext`], which the compiler can't see.
+ *
+ * ```compile_fail
+ * # use sunscreen::{
+ * #    fhe_program,
+ * #    types::{bfv::Signed, Cipher},
+ * #    Compiler, Runtime,
+ * # };
+ * #[fhe_program(scheme = "bfv")]
+ * fn add(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+ *     a + b
+ * }
+ *
+ * let app = Compiler::new().fhe_program(add).compile().unwrap();
+ * let runtime = Runtime::new_fhe(app.params()).unwrap();
+ * let (public_key, _) = runtime.generate_keys().unwrap();
+ *
+ * let a = runtime.encrypt(Signed::from(1), &public_key).unwrap();
+ * let handle = app.get_fhe_program_handle(add).unwrap();
+ *
+ * // Error: add takes 2 arguments.
+ * handle.run(&runtime, (a,), &public_key).unwrap();
+ * ```
+ */
+pub struct FheProgramHandle<'a, A> {
+    program: &'a CompiledFheProgram,
+    _arguments: PhantomData<fn(A)>,
+}
+
+impl<'a, A> Clone for FheProgramHandle<'a, A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, A> Copy for FheProgramHandle<'a, A> {}
+
+impl<'a, A> FheProgramHandle<'a, A>
+where
+    A: FheProgramArguments,
+{
+    pub(crate) fn new(program: &'a CompiledFheProgram) -> Self {
+        Self {
+            program,
+            _arguments: PhantomData,
+        }
+    }
+
+    /**
+     * Returns the underlying [`CompiledFheProgram`].
+     */
+    pub fn program(&self) -> &'a CompiledFheProgram {
+        self.program
+    }
+
+    /**
+     * Runs the program with the given arguments, as
+     * [`GenericRuntime::run`] does.
+     */
+    pub fn run<T, B>(
+        &self,
+        runtime: &GenericRuntime<T, B>,
+        arguments: A,
+        public_key: &PublicKey,
+    ) -> Result<Vec<Ciphertext>>
+    where
+        T: marker::Fhe,
+    {
+        Ok(runtime.run(self.program, arguments.into_inputs(), public_key)?)
+    }
+}
@@ -37,15 +37,69 @@
 //! }
 //! ```
 //!
+//! # Features
+//! * `fhe` (default): enables [`fhe_program`]s, FHE runtimes, keys and
+//!   ciphertexts, and the protocols built on them. Disable it if you only
+//!   need ZKPs to avoid compiling SEAL.
+//! * `bfv-types` (default): enables the BFV data types in `types::bfv` and
+//!   the `applications` built on them. Implies `fhe`.
+//! * `zkp` (default): enables [`zkp_program`]s, ZKP runtimes and the
+//!   types in `types::zkp`. Disable it if you only need FHE to avoid
+//!   compiling the ZKP backend and its curve arithmetic.
+//! * `linkedproofs` (default): enables `Envelope`s, which bind ciphertexts
+//!   and the proofs about them to an FHE program. Implies `fhe` and `zkp`.
+//! * `bulletproofs`: enables the Bulletproofs ZKP backend. Implies `zkp`.
+//! * `hardened`: decodes decrypted `Batched` and `Matrix` values without
+//!   branching on them and zeroes intermediate buffers, at a small cost in
+//!   decryption speed. Decryption itself and other operations on the private
+//!   key aren't hardened.
+//! * `hexl`: accelerates SEAL with Intel HEXL.
+//! * `failure-injection`: re-exports `seal_fhe`'s [`failure_injection`]
+//!   module, which makes a chosen call into SEAL fail so tests can exercise
+//!   error handling. Never enable it outside of tests.
+//! * `metrics`: enables [`Metrics`], which aggregates counters and histograms
+//!   about program runs and encodes them in Prometheus' text format.
+//! * `tokio`: enables the [`asynch`] module for calling long-running operations
+//!   (compiling, running, proving, etc.) from async code.
+//!
 
+#[cfg(feature = "bfv-types")]
+/**
+ * This module contains reusable building blocks for common FHE workloads,
+ * written in terms of Sunscreen's [`types`].
+ */
+pub mod applications;
+#[cfg(feature = "tokio")]
+pub mod asynch;
+#[cfg(feature = "fhe")]
+mod compat;
 mod compiler;
+#[cfg(feature = "linkedproofs")]
+mod envelope;
 mod error;
+#[cfg(feature = "fhe")]
 /**
  * This module contains types used internally when compiling
  * [`fhe_program`]s.
  */
 pub mod fhe;
+#[cfg(feature = "fhe")]
+mod galois;
+#[cfg(feature = "fhe")]
+mod handle;
+#[cfg(feature = "fhe")]
+mod migration;
+#[cfg(feature = "fhe")]
 mod params;
+#[cfg(feature = "fhe")]
+/**
+ * This module contains cryptographic protocols between parties, built
+ * directly on SEAL rather than compiled [`fhe_program`]s.
+ */
+pub mod protocols;
+#[cfg(feature = "fhe")]
+mod reencryption;
+#[cfg(feature = "zkp")]
 mod zkp;
 
 /**
@@ -63,33 +117,96 @@ mod zkp;
  */
 pub mod types;
 
+#[cfg(feature = "fhe")]
 use fhe::{FheOperation, Literal};
+#[cfg(feature = "fhe")]
 use petgraph::stable_graph::StableGraph;
 use serde::{Deserialize, Serialize};
-use sunscreen_runtime::{marker, Fhe, FheZkp, Zkp};
+#[cfg(feature = "fhe")]
+use sunscreen_runtime::Fhe;
+#[cfg(all(feature = "fhe", feature = "zkp"))]
+use sunscreen_runtime::FheZkp;
+#[cfg(feature = "zkp")]
+use sunscreen_runtime::Zkp;
+#[cfg(feature = "zkp")]
 use sunscreen_zkp_backend::CompiledZkpProgram;
 
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::marker::PhantomData;
 
-pub use compiler::{Compiler, FheProgramFn, GenericCompiler};
+#[cfg(feature = "fhe")]
+pub use compat::{CompatibilityReport, Mismatch};
+#[cfg(feature = "fhe")]
+pub use compiler::FheProgramFn;
+pub use compiler::{Compiler, GenericCompiler};
+#[cfg(feature = "linkedproofs")]
+pub use envelope::{Envelope, EnvelopeDigest, EnvelopeError, ReplayGuard};
 pub use error::{Error, Result};
-pub use params::PlainModulusConstraint;
+#[cfg(feature = "fhe")]
+pub use galois::MinimalGaloisKeys;
+#[cfg(feature = "fhe")]
+pub use handle::{FheProgramArguments, FheProgramHandle, TypedFheProgramFn};
+#[cfg(feature = "fhe")]
+pub use migration::{
+    fhe_program_hash, ApplicationDiff, FheProgramDiff, FheProgramHash, MigrationAction,
+};
+#[cfg(feature = "fhe")]
+pub use params::{PlainModulusConstraint, DETERMINISTIC_GUARD_BITS};
+#[cfg(feature = "fhe")]
+pub use reencryption::{
+    CiphertextStore, ClientAssisted, Reencrypt, ReencryptionPlanner, ReencryptionProgress,
+};
+#[cfg(feature = "failure-injection")]
+pub use seal_fhe::failure_injection;
+#[cfg(feature = "fhe")]
 pub use seal_fhe::Plaintext as SealPlaintext;
+#[cfg(feature = "fhe")]
+pub use seal_fhe::{
+    global_memory_pool_stats, GaloisKeyIndex, MemoryPoolStats, MemoryUsage, Padding, Reduction,
+    ReductionTree,
+};
+#[cfg(feature = "fhe")]
+pub use sunscreen_backend::noise_model;
+#[cfg(feature = "fhe")]
+pub use sunscreen_backend::RelinearizationPolicy;
 pub use sunscreen_compiler_macros::*;
-pub use sunscreen_fhe_program::{SchemeType, SecurityLevel};
+#[cfg(feature = "fhe")]
+pub use sunscreen_fhe_program::{
+    EdgeInfo, Operation as FheProgramOperation, ProgramEdge, ProgramGraph, ProgramNode, SchemeType,
+    SecurityLevel,
+};
+pub use sunscreen_runtime::marker;
+#[cfg(all(feature = "tokio", feature = "fhe"))]
+pub use sunscreen_runtime::AsyncFheRuntime;
+#[cfg(feature = "tokio")]
+pub use sunscreen_runtime::AsyncRuntime;
+#[cfg(all(feature = "fhe", feature = "zkp"))]
+pub use sunscreen_runtime::FheZkpRuntime;
+pub use sunscreen_runtime::{
+    CallSignature, CancellationToken, Error as RuntimeError, GenericRuntime, Runtime, Secret,
+};
+#[cfg(feature = "fhe")]
 pub use sunscreen_runtime::{
-    CallSignature, Ciphertext, CompiledFheProgram, Error as RuntimeError, FheProgramInput,
-    FheProgramInputTrait, FheProgramMetadata, FheRuntime, FheZkpRuntime, InnerCiphertext,
-    InnerPlaintext, Params, Plaintext, PrivateKey, PublicKey, RequiredKeys, Runtime, WithContext,
-    ZkpProgramInput, ZkpRuntime,
+    Ciphertext, CompiledFheProgram, CrtBasis, CrtEncoder, DecryptionGuard, DepthReport,
+    FairScheduler, Fairness, FheProgramInput, FheProgramInputTrait, FheProgramMetadata, FheRuntime,
+    InnerCiphertext, InnerPlaintext, JsonInput, JsonTypes, KeyRotation, KeySetFingerprint, OpCosts,
+    Params, Plaintext, PolicyProvider, PrivateKey, PublicKey, ReEncryptionKey, ReEncryptor,
+    RequiredKeys, RunOptions, RuntimeState, SealCiphertexts, WithContext,
+    DEFAULT_DECOMPOSITION_BITS,
 };
+#[cfg(feature = "metrics")]
+pub use sunscreen_runtime::{Metrics, DEFAULT_DURATION_BUCKETS};
+#[cfg(feature = "zkp")]
+pub use sunscreen_runtime::{ZkpProgramInput, ZkpRuntime};
+#[cfg(feature = "zkp")]
 pub use sunscreen_zkp_backend::{BackendField, Error as ZkpError, Result as ZkpResult, ZkpBackend};
+#[cfg(feature = "zkp")]
 pub use zkp::ZkpProgramFn;
+#[cfg(feature = "zkp")]
 pub use zkp::{
-    invoke_gadget, with_zkp_ctx, ZkpContext, ZkpContextOps, ZkpData, ZkpFrontendCompilation,
-    CURRENT_ZKP_CTX,
+    invoke_gadget, with_zkp_ctx, CircuitSize, GadgetUsage, ZkpBudgetError, ZkpContext,
+    ZkpContextOps, ZkpData, ZkpFrontendCompilation, ZkpLimit, CURRENT_ZKP_CTX,
 };
 
 #[derive(Clone)]
@@ -97,16 +214,20 @@ pub use zkp::{
  * The outcome of successful compilation. Contains one or more [`CompiledFheProgram`].
  */
 pub struct Application<T> {
+    #[cfg(feature = "fhe")]
     fhe_programs: HashMap<String, CompiledFheProgram>,
+    #[cfg(feature = "zkp")]
     zkp_programs: HashMap<String, CompiledZkpProgram>,
+    #[cfg(feature = "fhe")]
+    params: Option<Params>,
     _phantom: PhantomData<T>,
 }
 
 impl<T> Application<T> {
     /**
-     * Constructs a new Application from the given HashMap of programs. The
-     * keys of this contain FHE program names and the values are the
-     * compiled FHE programs.
+     * Constructs a new Application containing only the given ZKP programs.
+     * The keys of the [`HashMap`] contain ZKP program names and the values
+     * are the compiled ZKP programs.
      *
      * # Remarks
      * The programs [`HashMap`] must contain at least 1 program or this
@@ -115,22 +236,51 @@ impl<T> Application<T> {
      * You should generally not call this function
      * It is an implementation detail of compilation.
      */
-    pub(crate) fn new(
+    #[cfg(feature = "zkp")]
+    pub(crate) fn new_zkp(zkp_programs: HashMap<String, CompiledZkpProgram>) -> Result<Self> {
+        if zkp_programs.is_empty() {
+            return Err(Error::NoPrograms);
+        }
+
+        Ok(Self {
+            #[cfg(feature = "fhe")]
+            fhe_programs: HashMap::new(),
+            zkp_programs,
+            #[cfg(feature = "fhe")]
+            params: None,
+            _phantom: PhantomData,
+        })
+    }
+
+    /**
+     * Constructs a new Application containing only the given FHE programs.
+     * `params` are the parameters shared by programs compiled without a
+     * per-program override, if any.
+     *
+     * # Remarks
+     * The programs [`HashMap`] must contain at least 1 program or this
+     * function will return [`Error::NoPrograms`].
+     */
+    #[cfg(feature = "fhe")]
+    pub(crate) fn new_fhe(
         fhe_programs: HashMap<String, CompiledFheProgram>,
-        zkp_programs: HashMap<String, CompiledZkpProgram>,
+        params: Option<Params>,
     ) -> Result<Self> {
-        if fhe_programs.is_empty() && zkp_programs.is_empty() {
+        if fhe_programs.is_empty() {
             return Err(Error::NoPrograms);
         }
 
         Ok(Self {
             fhe_programs,
-            zkp_programs,
+            #[cfg(feature = "zkp")]
+            zkp_programs: HashMap::new(),
+            params,
             _phantom: PhantomData,
         })
     }
 }
 
+#[cfg(feature = "fhe")]
 impl<T> Application<T>
 where
     T: marker::Fhe,
@@ -140,10 +290,25 @@ where
      * These parameters were chosen during compilation.
      *
      * # Remarks
-     * If no [`fhe_program`] was specified, this function returns [`None`].
+     * Programs compiled with a per-program override (i.e. added with
+     * `fhe_program_with_params` or `fhe_program_in_group`) may use different
+     * parameters, found in their [`FheProgramMetadata::params`]. If every program has
+     * an override, this returns the parameters of the program whose name sorts first.
      */
     pub fn params(&self) -> &Params {
-        &self.fhe_programs.values().next().unwrap().metadata.params
+        match &self.params {
+            Some(p) => p,
+            None => {
+                &self
+                    .fhe_programs
+                    .iter()
+                    .min_by_key(|(name, _)| *name)
+                    .unwrap()
+                    .1
+                    .metadata
+                    .params
+            }
+        }
     }
 
     #[deprecated]
@@ -167,6 +332,22 @@ where
         self.fhe_programs.get(name.as_ref())
     }
 
+    /**
+     * Gets a [`FheProgramHandle`] for the given `#[fhe_program]` or
+     * [`None`] if not present. Running a program through its handle checks
+     * the number and kinds of its arguments at compile time.
+     */
+    pub fn get_fhe_program_handle<F>(
+        &self,
+        fhe_program: F,
+    ) -> Option<FheProgramHandle<'_, F::Arguments>>
+    where
+        F: TypedFheProgramFn,
+    {
+        self.get_fhe_program(fhe_program.name())
+            .map(FheProgramHandle::new)
+    }
+
     #[deprecated]
     /**
      * Returns an iterator over all the compiled programs.
@@ -184,8 +365,69 @@ where
     pub fn get_fhe_programs(&self) -> impl Iterator<Item = (&String, &CompiledFheProgram)> {
         self.fhe_programs.iter()
     }
+
+    /**
+     * Reports how this application differs from an `old` compilation of it,
+     * including changes to the scheme parameters, each program's
+     * [`FheProgramHash`], and the keys and rotations programs require.
+     *
+     * # Remarks
+     * Use the returned [`ApplicationDiff`] to find which stored ciphertexts
+     * and keys must be migrated before deploying this application.
+     */
+    pub fn diff(&self, old: &Self) -> ApplicationDiff {
+        ApplicationDiff::new(old, self)
+    }
+
+    /**
+     * Generates the Galois keys needed by every program in this
+     * application, and no others, in parallel.
+     *
+     * # Remarks
+     * This takes the union of the rotations all programs perform, merges
+     * rotations with the same Galois element and picks the smallest key
+     * set able to perform them, as in
+     * [`minimal_rotation_key_steps`](seal_fhe::minimal_rotation_key_steps).
+     * The result records which keys each program uses.
+     *
+     * `keygen` must be for [`params`](Self::params); programs compiled
+     * with other parameters are skipped. Pass this to
+     * [`Runtime::generate_keys_with_galois`](GenericRuntime::generate_keys_with_galois)
+     * to create a full set of keys.
+     */
+    pub fn generate_minimal_galois_keys(
+        &self,
+        keygen: &seal_fhe::KeyGenerator,
+    ) -> Result<MinimalGaloisKeys> {
+        MinimalGaloisKeys::generate(self, keygen)
+    }
+}
+
+#[cfg(feature = "fhe")]
+impl Application<Fhe> {
+    /**
+     * Checks whether two compiled FHE programs, possibly from different
+     * applications, agree on their scheme parameters and key
+     * requirements.
+     *
+     * # Remarks
+     * Programs must share their parameters for one's outputs to be passed
+     * to the other, or to serve both from one service with a single key
+     * pair. Use this before merging applications compiled at different
+     * times, e.g. with different compiler versions or constraints.
+     * [`CompatibilityReport::params_compatible`] tells whether the
+     * programs can share ciphertexts and keys at all, and the report's
+     * [`Mismatch`]es what differs.
+     */
+    pub fn check_compat(
+        prog_a: &CompiledFheProgram,
+        prog_b: &CompiledFheProgram,
+    ) -> CompatibilityReport {
+        CompatibilityReport::new(prog_a, prog_b)
+    }
 }
 
+#[cfg(feature = "zkp")]
 impl<T> Application<T>
 where
     T: marker::Zkp,
@@ -245,6 +487,7 @@ pub trait Value {
     fn output(&self) -> Self;
 }
 
+#[cfg(feature = "fhe")]
 #[derive(Clone, Debug, Deserialize, Serialize)]
 /**
  * Contains the frontend compilation graph.
@@ -264,16 +507,19 @@ thread_local! {
     pub static INDEX_ARENA: RefCell<bumpalo::Bump> = RefCell::new(bumpalo::Bump::new());
 }
 
+#[cfg(feature = "fhe")]
 /**
  * An application with FHE programs.
  */
 pub type FheApplication = Application<Fhe>;
 
+#[cfg(feature = "zkp")]
 /**
  * An application with ZKP programs.
  */
 pub type ZkpApplication = Application<Zkp>;
 
+#[cfg(all(feature = "fhe", feature = "zkp"))]
 /**
  * An application with FHE and ZKP programs.
  */
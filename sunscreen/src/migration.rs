@@ -0,0 +1,325 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use sha3::{Digest, Sha3_256};
+use sunscreen_compiler_common::GraphQuery;
+use sunscreen_fhe_program::{Literal, Operation};
+use sunscreen_runtime::{
+    marker, Ciphertext, CompiledFheProgram, InnerCiphertext, Params, PublicKey, RequiredKeys,
+};
+
+use crate::Application;
+
+/**
+ * A SHA3-256 hash of a [`CompiledFheProgram`]'s operations and metadata.
+ */
+pub type FheProgramHash = [u8; 32];
+
+/**
+ * Computes the [`FheProgramHash`] of the given program.
+ */
+pub fn fhe_program_hash(program: &CompiledFheProgram) -> FheProgramHash {
+    let bytes = bincode::serialize(program)
+        .expect("Internal error: failed to serialize compiled FHE program.");
+
+    Sha3_256::digest(bytes).into()
+}
+
+/**
+ * Returns the rotation amounts used by the given program, where positive
+ * amounts rotate left and negative amounts rotate right.
+ */
+fn rotations(program: &CompiledFheProgram) -> BTreeSet<i64> {
+    let graph = &program.fhe_program_fn.graph.0;
+    let query = GraphQuery::new(graph);
+
+    graph
+        .node_indices()
+        .filter_map(|index| {
+            let sign = match graph[index].operation {
+                Operation::ShiftLeft => 1,
+                Operation::ShiftRight => -1,
+                _ => return None,
+            };
+
+            let (_, right) = query.get_binary_operands(index).ok()?;
+
+            match graph[right].operation {
+                Operation::Literal(Literal::U64(v)) => Some(sign * v as i64),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/**
+ * Describes how an FHE program with a given name differs between two
+ * [`Application`]s.
+ */
+pub struct FheProgramDiff {
+    /**
+     * The name of the FHE program.
+     */
+    pub name: String,
+
+    /**
+     * The hash of the program in the old application or [`None`] if the
+     * program was added.
+     */
+    pub old_hash: Option<FheProgramHash>,
+
+    /**
+     * The hash of the program in the new application or [`None`] if the
+     * program was removed.
+     */
+    pub new_hash: Option<FheProgramHash>,
+
+    /**
+     * Whether the program's arguments or return values changed. Callers
+     * must update how they construct arguments and interpret results.
+     */
+    pub signature_changed: bool,
+
+    /**
+     * Key types the new program requires that the old one didn't.
+     */
+    pub added_keys: Vec<RequiredKeys>,
+
+    /**
+     * Rotation amounts the new program performs that the old one didn't.
+     * Positive amounts rotate left and negative amounts rotate right.
+     */
+    pub added_rotations: Vec<i64>,
+
+    /**
+     * Rotation amounts the old program performed that the new one doesn't.
+     */
+    pub removed_rotations: Vec<i64>,
+}
+
+impl FheProgramDiff {
+    /**
+     * Whether the program exists in the new application but not the old one.
+     */
+    pub fn is_added(&self) -> bool {
+        self.old_hash.is_none()
+    }
+
+    /**
+     * Whether the program exists in the old application but not the new one.
+     */
+    pub fn is_removed(&self) -> bool {
+        self.new_hash.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/**
+ * What to do with a stored ciphertext or key before using it with a
+ * recompiled [`Application`].
+ */
+pub enum MigrationAction {
+    /**
+     * The value works with the new application as is.
+     */
+    Keep,
+
+    /**
+     * The ciphertext was encrypted under parameters the new application
+     * doesn't use. Decrypt it with the old private key and encrypt the result
+     * under a key generated for the new parameters.
+     */
+    Reencrypt,
+
+    /**
+     * The public key was generated for different parameters or lacks a key
+     * type a program in the new application requires. Generate a new key pair
+     * with a runtime created from the new application's parameters. If the
+     * parameters changed, the corresponding private key must be replaced as
+     * well.
+     */
+    RegenerateKeys,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/**
+ * The differences between two compilations of an [`Application`]. See
+ * [`Application::diff`].
+ *
+ * # Remarks
+ * Use this to plan a deployment before replacing an application, e.g. to
+ * find which stored ciphertexts need re-encryption
+ * ([`ciphertext_action`](Self::ciphertext_action)) and whether clients must
+ * generate new keys ([`public_key_action`](Self::public_key_action)).
+ * [`ReencryptionPlanner`](crate::ReencryptionPlanner) carries out the
+ * re-encryption across a store of ciphertexts.
+ */
+pub struct ApplicationDiff {
+    /**
+     * The parameters of the old application.
+     */
+    pub old_params: Params,
+
+    /**
+     * The parameters of the new application.
+     */
+    pub new_params: Params,
+
+    /**
+     * The FHE programs that were added, removed or changed, sorted by name.
+     * Unchanged programs aren't listed.
+     */
+    pub programs: Vec<FheProgramDiff>,
+
+    /**
+     * The key types any program in the new application requires.
+     */
+    pub required_keys: Vec<RequiredKeys>,
+}
+
+impl ApplicationDiff {
+    pub(crate) fn new<T>(old: &Application<T>, new: &Application<T>) -> Self
+    where
+        T: marker::Fhe,
+    {
+        let old_programs = old.get_fhe_programs().collect::<BTreeMap<_, _>>();
+        let new_programs = new.get_fhe_programs().collect::<BTreeMap<_, _>>();
+
+        let names = old_programs
+            .keys()
+            .chain(new_programs.keys())
+            .collect::<BTreeSet<_>>();
+
+        let mut programs = vec![];
+
+        for name in names {
+            let old = old_programs.get(name).copied();
+            let new = new_programs.get(name).copied();
+
+            let old_hash = old.map(fhe_program_hash);
+            let new_hash = new.map(fhe_program_hash);
+
+            if old_hash == new_hash {
+                continue;
+            }
+
+            let old_rotations = old.map(rotations).unwrap_or_default();
+            let new_rotations = new.map(rotations).unwrap_or_default();
+
+            let (signature_changed, added_keys) = match (old, new) {
+                (Some(old), Some(new)) => (
+                    old.metadata.signature != new.metadata.signature,
+                    new.metadata
+                        .required_keys
+                        .iter()
+                        .filter(|k| !old.metadata.required_keys.contains(k))
+                        .cloned()
+                        .collect(),
+                ),
+                (None, Some(new)) => (false, new.metadata.required_keys.clone()),
+                _ => (false, vec![]),
+            };
+
+            programs.push(FheProgramDiff {
+                name: name.to_string(),
+                old_hash,
+                new_hash,
+                signature_changed,
+                added_keys,
+                added_rotations: new_rotations.difference(&old_rotations).copied().collect(),
+                removed_rotations: old_rotations.difference(&new_rotations).copied().collect(),
+            });
+        }
+
+        let mut required_keys: Vec<RequiredKeys> = vec![];
+
+        for key in new_programs
+            .values()
+            .flat_map(|p| p.metadata.required_keys.iter())
+        {
+            if !required_keys.contains(key) {
+                required_keys.push(key.clone());
+            }
+        }
+
+        Self {
+            old_params: old.params().clone(),
+            new_params: new.params().clone(),
+            programs,
+            required_keys,
+        }
+    }
+
+    /**
+     * Whether the scheme parameters changed. If so, every stored ciphertext
+     * and key must be migrated.
+     */
+    pub fn params_changed(&self) -> bool {
+        self.old_params != self.new_params
+    }
+
+    /**
+     * Whether the two applications are identical.
+     */
+    pub fn is_empty(&self) -> bool {
+        !self.params_changed() && self.programs.is_empty()
+    }
+
+    /**
+     * Returns what to do with the given ciphertext before passing it to a
+     * program in the new application.
+     */
+    pub fn ciphertext_action(&self, ciphertext: &Ciphertext) -> MigrationAction {
+        let compatible = match &ciphertext.inner {
+            InnerCiphertext::Seal(c) => c.iter().all(|c| c.params == self.new_params),
+        };
+
+        if compatible {
+            MigrationAction::Keep
+        } else {
+            MigrationAction::Reencrypt
+        }
+    }
+
+    /**
+     * Returns the indices of the given ciphertexts that require
+     * [`MigrationAction::Reencrypt`].
+     */
+    pub fn incompatible_ciphertexts<'a, I>(&self, ciphertexts: I) -> Vec<usize>
+    where
+        I: IntoIterator<Item = &'a Ciphertext>,
+    {
+        ciphertexts
+            .into_iter()
+            .enumerate()
+            .filter(|(_, c)| self.ciphertext_action(c) != MigrationAction::Keep)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /**
+     * Returns what to do with the given public key before using it with the
+     * new application.
+     */
+    pub fn public_key_action(&self, public_key: &PublicKey) -> MigrationAction {
+        if public_key.public_key.params != self.new_params {
+            return MigrationAction::RegenerateKeys;
+        }
+
+        let has_key = |key: &RequiredKeys| match key {
+            RequiredKeys::Galois => {
+                matches!(&public_key.galois_key, Some(k) if k.params == self.new_params)
+            }
+            RequiredKeys::Relin => {
+                matches!(&public_key.relin_key, Some(k) if k.params == self.new_params)
+            }
+            RequiredKeys::PublicKey => true,
+        };
+
+        if self.required_keys.iter().all(has_key) {
+            MigrationAction::Keep
+        } else {
+            MigrationAction::RegenerateKeys
+        }
+    }
+}
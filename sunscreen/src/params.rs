@@ -4,10 +4,14 @@ use log::{debug, trace};
 
 use seal_fhe::{
     BfvEncryptionParametersBuilder, CoefficientModulus, Context, KeyGenerator, Modulus,
-    PlainModulus,
+    PlainModulus, PrngSeed,
 };
-use sunscreen_backend::noise_model::{
-    noise_budget_to_noise, predict_noise, MeasuredModel, TargetNoiseLevel,
+use sunscreen_backend::{
+    noise_model::{
+        noise_budget_bits_to_noise, noise_to_noise_budget_bits, predict_noise, MeasuredModel,
+        TargetNoiseLevel,
+    },
+    RelinearizationPolicy,
 };
 use sunscreen_fhe_program::{FheProgram, FheProgramTrait, Operation, SchemeType};
 pub use sunscreen_runtime::Params;
@@ -35,6 +39,19 @@ pub enum PlainModulusConstraint {
 }
 
 const LATTICE_DIMENSIONS: &[u64] = &[1024, 2048, 4096, 8192, 16384, 32768];
+
+/**
+ * The extra bits of noise budget deterministic parameter search requires
+ * of every output, absorbing small differences in the noise SEAL
+ * computes for the same seeded ciphertexts, e.g. between SEAL versions.
+ */
+pub const DETERMINISTIC_GUARD_BITS: u32 = 2;
+
+/**
+ * The seed deterministic parameter search generates the keys and
+ * ciphertexts it measures noise on from.
+ */
+const DETERMINISTIC_SEED: PrngSeed = PrngSeed([0; 8]);
 const BATCHING_MIN_BITS: &[u32] = &[14, 14, 16, 17, 17, 17];
 
 /**
@@ -115,7 +132,12 @@ fn can_make_required_keys(fhe_program: &FheProgram, params: &Params) -> Result<b
 /**
  * Determines the minimal parameters required to satisfy the noise constraint for
  * the given FHE program and plaintext modulo and security level.
+ *
+ * # Remarks
+ * This searches as [`determine_params_with_options`] does without
+ * deterministic mode and with the default [`RelinearizationPolicy`].
  */
+#[allow(dead_code)]
 pub fn determine_params(
     fhe_program_fns: &[Box<dyn FheProgramFn>],
     plaintext_constraint: PlainModulusConstraint,
@@ -123,6 +145,89 @@ pub fn determine_params(
     noise_margin_bits: u32,
     scheme_type: SchemeType,
 ) -> Result<Params> {
+    let fhe_program_fns = fhe_program_fns
+        .iter()
+        .map(|p| p.as_ref())
+        .collect::<Vec<_>>();
+
+    determine_params_with_options(
+        &fhe_program_fns,
+        plaintext_constraint,
+        security_level,
+        noise_margin_bits,
+        scheme_type,
+        false,
+        RelinearizationPolicy::default(),
+    )
+}
+
+/**
+ * Determines the minimal parameters required to satisfy the noise constraint for
+ * the given FHE programs and plaintext modulo and security level.
+ *
+ * # Remarks
+ * The search measures noise by running the programs on real ciphertexts.
+ * When `deterministic` is set, it generates those from a fixed seed, so
+ * the same programs always measure the same noise. It also compares
+ * noise in whole bits of budget and requires [`DETERMINISTIC_GUARD_BITS`]
+ * beyond `noise_margin_bits` of every output, so the parameters stay the
+ * same unless SEAL computes noise differently, e.g. after an upgrade,
+ * by more than that margin.
+ *
+ * Programs compile with the given `relinearization` policy, as the
+ * noise it leaves depends on where relinearizations go.
+ */
+#[allow(clippy::too_many_arguments)]
+pub fn determine_params_with_options(
+    fhe_program_fns: &[&dyn FheProgramFn],
+    plaintext_constraint: PlainModulusConstraint,
+    security_level: SecurityLevel,
+    noise_margin_bits: u32,
+    scheme_type: SchemeType,
+    deterministic: bool,
+    relinearization: RelinearizationPolicy,
+) -> Result<Params> {
+    let seed = if deterministic {
+        Some(&DETERMINISTIC_SEED)
+    } else {
+        None
+    };
+
+    search_params(
+        fhe_program_fns,
+        plaintext_constraint,
+        security_level,
+        noise_margin_bits,
+        scheme_type,
+        seed,
+        relinearization,
+    )
+}
+
+/**
+ * Searches for parameters as [`determine_params_with_options`] does, in
+ * deterministic mode when given a `seed` to measure noise with.
+ */
+#[allow(clippy::too_many_arguments)]
+fn search_params(
+    fhe_program_fns: &[&dyn FheProgramFn],
+    plaintext_constraint: PlainModulusConstraint,
+    security_level: SecurityLevel,
+    noise_margin_bits: u32,
+    scheme_type: SchemeType,
+    seed: Option<&PrngSeed>,
+    relinearization: RelinearizationPolicy,
+) -> Result<Params> {
+    let deterministic = seed.is_some();
+
+    let noise_margin_bits = if deterministic {
+        noise_margin_bits + DETERMINISTIC_GUARD_BITS
+    } else {
+        noise_margin_bits
+    };
+
+    let target_noise = noise_budget_bits_to_noise(noise_margin_bits);
+
     'params_loop: for (i, n) in LATTICE_DIMENSIONS.iter().enumerate() {
         // Select a plain modulus that meets needs of the passed
         // constraint.
@@ -155,7 +260,7 @@ pub fn determine_params(
         for program in fhe_program_fns {
             trace!("Successfully created parameters.");
             trace!("Running backend compilation for {}", program.name());
-            let ir = program.build(&params)?.compile();
+            let ir = program.build(&params)?.compile_with_policy(relinearization);
 
             ir.validate().map_err(Error::FheProgramError)?;
             trace!("Built and validated {}", program.name());
@@ -187,6 +292,10 @@ pub fn determine_params(
                         Operation::InputCiphertext(_) => {
                             if chain_noise_level == 0f64 {
                                 TargetNoiseLevel::Fresh
+                            } else if deterministic {
+                                TargetNoiseLevel::InvariantNoiseBudget(noise_to_noise_budget_bits(
+                                    chain_noise_level,
+                                ))
                             } else {
                                 TargetNoiseLevel::InvariantNoise(chain_noise_level)
                             }
@@ -196,7 +305,12 @@ pub fn determine_params(
                     })
                     .collect::<Vec<TargetNoiseLevel>>();
 
-                let model = match MeasuredModel::new(&ir, &params, &noise_targets) {
+                let model = match seed {
+                    Some(seed) => MeasuredModel::new_seeded(&ir, &params, &noise_targets, seed),
+                    None => MeasuredModel::new(&ir, &params, &noise_targets),
+                };
+
+                let model = match model {
                     Ok(v) => v,
                     Err(_) => {
                         trace!(
@@ -210,10 +324,14 @@ pub fn determine_params(
 
                 let output_noises = predict_noise(&model, &ir);
 
-                let target_noise = noise_budget_to_noise(noise_margin_bits as f64);
-
                 for output_noise in output_noises {
-                    if output_noise > target_noise {
+                    let exceeds_target = if deterministic {
+                        noise_to_noise_budget_bits(output_noise) < noise_margin_bits
+                    } else {
+                        output_noise > target_noise
+                    };
+
+                    if exceeds_target {
                         trace!(
                             "Failed to meet noise constraints with lattice dimension {} for program {}",
                             n,
@@ -234,3 +352,39 @@ pub fn determine_params(
 
     Err(Error::NoParams)
 }
+
+#[cfg(all(test, feature = "bfv-types"))]
+mod tests {
+    use sunscreen_compiler_macros::fhe_program;
+
+    use super::*;
+    use crate::types::{bfv::Signed, Cipher};
+    use crate::{self as sunscreen};
+
+    #[test]
+    fn deterministic_search_ignores_measurement_seed() {
+        #[fhe_program(scheme = "bfv")]
+        fn mul(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+            a * b * a
+        }
+
+        let search = |seed: u64| {
+            search_params(
+                &[&mul],
+                PlainModulusConstraint::Raw(64),
+                SecurityLevel::TC128,
+                0,
+                SchemeType::Bfv,
+                Some(&PrngSeed::from_u64(seed)),
+                RelinearizationPolicy::default(),
+            )
+            .unwrap()
+        };
+
+        let params = search(0);
+
+        for seed in 1..8 {
+            assert_eq!(search(seed), params);
+        }
+    }
+}
@@ -0,0 +1,11 @@
+/**
+ * Private set intersection between a receiver, who learns which of its
+ * items the sender also holds, and a sender, who learns nothing.
+ */
+pub mod psi;
+
+/**
+ * Threshold decryption, where any `threshold` of `parties` key holders
+ * decrypt together and no single party holds the secret key.
+ */
+pub mod threshold;
@@ -0,0 +1,572 @@
+//! Private set intersection (PSI) in the style of Chen, Laine and Rindal,
+//! built directly on [`seal_fhe`]'s BFV scheme.
+//!
+//! The flow has three steps:
+//! 1. **Hash to bins.** The [`PsiReceiver`] places each of its items in
+//!    one of the batching slots (bins) with cuckoo hashing over
+//!    [`HASH_FUNCTIONS`] hash functions, so every bin holds at most one
+//!    item. The [`PsiSender`] places each of its items in *every* bin any
+//!    hash function maps it to, since it can't know which one the receiver
+//!    chose.
+//! 2. **Batched polynomial evaluation.** The receiver encrypts its bins as
+//!    a single [`PsiQuery`]. For each bin, the sender interpolates the
+//!    polynomial whose roots are the values in that bin, and evaluates
+//!    every bin's polynomial at the receiver's value at once, using the
+//!    encrypted powers of the query and plaintext coefficients. A slot is
+//!    zero exactly when the receiver's item in that bin is a root, i.e.
+//!    the sender holds it too.
+//! 3. **Response randomization.** Before returning the [`PsiResponse`],
+//!    the sender multiplies every slot by a random nonzero mask, so
+//!    nonzero slots reveal nothing about the sender's items, and adds a
+//!    fresh encryption of zero to re-randomize the ciphertext.
+//!
+//! The receiver then decrypts the response and reads the intersection off
+//! the zero slots with [`PsiReceiver::intersection`].
+//!
+//! # Remarks
+//! Items hash to values modulo the plaintext modulus `t`, so a receiver's
+//! item collides with an unrelated sender item in its bin with
+//! probability about `HASH_FUNCTIONS * |sender| / (bins * t)`, for a
+//! false positive rate of roughly `HASH_FUNCTIONS * |sender| *
+//! |receiver| / (bins * t)` over the whole set. Choose a large batching
+//! plaintext modulus to keep it small.
+//!
+//! Bins hold more sender values than the polynomial degree the parameters
+//! can afford to evaluate, so the sender splits them into partitions of
+//! at most `max_degree` values, each with its own polynomial, and returns
+//! one ciphertext per partition. Evaluating a partition takes a
+//! multiplicative depth of `ceil(log2(max_degree)) + 1`.
+//!
+//! The protocol is secure against a semi-honest sender and receiver. The
+//! encryption of zero re-randomizes the response but doesn't flood its
+//! noise, which may leak information about the sender's polynomials to a
+//! receiver with the secret key.
+//!
+//! # Example
+//! ```no_run
+//! # use seal_fhe::{
+//! #    BfvEncryptionParametersBuilder, CoefficientModulus, Context, PlainModulus,
+//! #    SecurityLevel,
+//! # };
+//! # use sunscreen::protocols::psi::{PsiReceiver, PsiSender};
+//! let params = BfvEncryptionParametersBuilder::new()
+//!     .set_poly_modulus_degree(8192)
+//!     .set_coefficient_modulus(
+//!         CoefficientModulus::bfv_default(8192, SecurityLevel::TC128).unwrap(),
+//!     )
+//!     .set_plain_modulus(PlainModulus::batching(8192, 30).unwrap())
+//!     .build()
+//!     .unwrap();
+//!
+//! let ctx = Context::new(&params, true, SecurityLevel::TC128).unwrap();
+//!
+//! let receiver = PsiReceiver::new(&ctx, &["alice", "bob", "carol"]).unwrap();
+//! let sender = PsiSender::new(&ctx, &["bob", "dave", "carol"], 4).unwrap();
+//!
+//! let response = sender.respond(&receiver.query().unwrap()).unwrap();
+//!
+//! // "bob" and "carol" are at indices 1 and 2 in the receiver's items.
+//! assert_eq!(receiver.intersection(&response).unwrap(), vec![1, 2]);
+//! ```
+
+use std::collections::HashMap;
+
+use rand::Rng;
+use seal_fhe::{
+    BFVEncoder, BFVEvaluator, Ciphertext, Context, Decryptor, Encryptor, Evaluator, KeyGenerator,
+    Plaintext, PublicKey, RelinearizationKeys,
+};
+use sha3::{Digest, Sha3_256};
+
+use crate::{Error, Result};
+
+/**
+ * The number of hash functions that map an item to its candidate bins.
+ */
+pub const HASH_FUNCTIONS: usize = 3;
+
+/**
+ * How many times cuckoo hashing evicts an item before giving up on
+ * placing the receiver's set.
+ */
+const MAX_EVICTIONS: usize = 500;
+
+/**
+ * The receiver's encrypted bins, along with the keys the sender needs to
+ * evaluate on them.
+ */
+pub struct PsiQuery {
+    /**
+     * The receiver's item values, one per bin.
+     */
+    pub ciphertext: Ciphertext,
+
+    /**
+     * The receiver's public key, under which the sender re-randomizes its
+     * response.
+     */
+    pub public_key: PublicKey,
+
+    /**
+     * The receiver's relinearization keys, with which the sender computes
+     * powers of the query.
+     */
+    pub relin_keys: RelinearizationKeys,
+}
+
+/**
+ * The sender's randomized evaluation of its polynomials on a
+ * [`PsiQuery`].
+ */
+pub struct PsiResponse {
+    /**
+     * One ciphertext per partition of the sender's bins. A slot in any of
+     * them decrypts to zero when the receiver's item in that bin is in
+     * the intersection.
+     */
+    pub ciphertexts: Vec<Ciphertext>,
+}
+
+/**
+ * The party that learns the intersection. See the
+ * [module documentation](self).
+ */
+pub struct PsiReceiver {
+    encoder: BFVEncoder,
+    encryptor: Encryptor,
+    decryptor: Decryptor,
+    public_key: PublicKey,
+    relin_keys: RelinearizationKeys,
+
+    /**
+     * The indices into the receiver's items of each distinct item.
+     */
+    members: Vec<Vec<usize>>,
+
+    /**
+     * The distinct item in each bin, if any.
+     */
+    bins: Vec<Option<usize>>,
+
+    /**
+     * The value of the item in each bin, or 0 for empty bins.
+     */
+    values: Vec<u64>,
+}
+
+impl PsiReceiver {
+    /**
+     * Generates fresh keys under `ctx` and hashes `items` into bins.
+     *
+     * # Remarks
+     * `ctx` must support batching. Returns [`Error::PsiSetTooLarge`] if
+     * the distinct items don't fit into `ctx`'s batching slots.
+     */
+    pub fn new<I: AsRef<[u8]>>(ctx: &Context, items: &[I]) -> Result<Self> {
+        let encoder = BFVEncoder::new(ctx)?;
+        let keygen = KeyGenerator::new(ctx)?;
+
+        let public_key = keygen.create_public_key()?;
+        let relin_keys = keygen.create_relinearization_keys()?;
+        let encryptor = Encryptor::with_public_key(ctx, &public_key)?;
+        let decryptor = Decryptor::new(ctx, &keygen.secret_key()?)?;
+
+        let mut ids = HashMap::new();
+        let mut distinct = vec![];
+        let mut members: Vec<Vec<usize>> = vec![];
+
+        for (i, item) in items.iter().enumerate() {
+            let item = item.as_ref();
+
+            let id = *ids.entry(item).or_insert_with(|| {
+                distinct.push(item);
+                members.push(vec![]);
+
+                distinct.len() - 1
+            });
+
+            members[id].push(i);
+        }
+
+        let bins = cuckoo_hash(&distinct, encoder.get_slot_count())?;
+        let plain_modulus = encoder.get_plain_modulus();
+
+        let values = bins
+            .iter()
+            .map(|b| match b {
+                Some(id) => item_value(distinct[*id], plain_modulus),
+                None => 0,
+            })
+            .collect();
+
+        Ok(Self {
+            encoder,
+            encryptor,
+            decryptor,
+            public_key,
+            relin_keys,
+            members,
+            bins,
+            values,
+        })
+    }
+
+    /**
+     * Encrypts the receiver's bins for the sender.
+     */
+    pub fn query(&self) -> Result<PsiQuery> {
+        let plaintext = self.encoder.encode_unsigned(&self.values)?;
+
+        Ok(PsiQuery {
+            ciphertext: self.encryptor.encrypt(&plaintext)?,
+            public_key: self.public_key.clone(),
+            relin_keys: self.relin_keys.clone(),
+        })
+    }
+
+    /**
+     * Decrypts the sender's response and returns the indices of the
+     * receiver's items the sender also holds, in ascending order.
+     *
+     * # Remarks
+     * Duplicate items are either all in the intersection or none are.
+     */
+    pub fn intersection(&self, response: &PsiResponse) -> Result<Vec<usize>> {
+        let mut found = vec![false; self.bins.len()];
+
+        for c in &response.ciphertexts {
+            let slots = self.encoder.decode_unsigned(&self.decryptor.decrypt(c)?)?;
+
+            for (f, s) in found.iter_mut().zip(slots) {
+                *f |= s == 0;
+            }
+        }
+
+        let mut indices = self
+            .bins
+            .iter()
+            .zip(found)
+            .filter_map(|(b, f)| if f { *b } else { None })
+            .flat_map(|id| self.members[id].iter().copied())
+            .collect::<Vec<usize>>();
+
+        indices.sort_unstable();
+
+        Ok(indices)
+    }
+}
+
+/**
+ * The party that holds the set the receiver intersects with. See the
+ * [module documentation](self).
+ */
+pub struct PsiSender<'a> {
+    ctx: &'a Context,
+    encoder: BFVEncoder,
+
+    /**
+     * The highest power of the query any partition's polynomial uses.
+     */
+    degree: usize,
+
+    /**
+     * For each partition, the polynomial's coefficients across every bin,
+     * lowest degree first. Coefficients that are zero in every bin are
+     * [`None`], except the constant term.
+     */
+    partitions: Vec<(Plaintext, Vec<Option<Plaintext>>)>,
+}
+
+impl<'a> PsiSender<'a> {
+    /**
+     * Hashes `items` into every candidate bin and interpolates each bin's
+     * polynomials of degree at most `max_degree`.
+     *
+     * # Remarks
+     * `ctx` must be the receiver's context.
+     *
+     * # Panics
+     * If `max_degree` is 0.
+     */
+    pub fn new<I: AsRef<[u8]>>(ctx: &'a Context, items: &[I], max_degree: usize) -> Result<Self> {
+        assert!(
+            max_degree > 0,
+            "PSI polynomials need a degree of at least 1"
+        );
+
+        let encoder = BFVEncoder::new(ctx)?;
+        let slot_count = encoder.get_slot_count();
+        let plain_modulus = encoder.get_plain_modulus();
+
+        let mut bins = vec![vec![]; slot_count];
+
+        for item in items {
+            let item = item.as_ref();
+            let value = item_value(item, plain_modulus);
+
+            for h in 0..HASH_FUNCTIONS {
+                let bin = &mut bins[bin_index(item, h, slot_count)];
+
+                if !bin.contains(&value) {
+                    bin.push(value);
+                }
+            }
+        }
+
+        let max_load = bins.iter().map(|b| b.len()).max().unwrap_or(0);
+        let partition_count = ((max_load + max_degree - 1) / max_degree).max(1);
+        let degree = max_load.min(max_degree);
+
+        let mut partitions = Vec::with_capacity(partition_count);
+
+        for p in 0..partition_count {
+            let mut coefficients = vec![vec![0; slot_count]; degree + 1];
+
+            for (b, bin) in bins.iter().enumerate() {
+                let start = (p * max_degree).min(bin.len());
+                let end = ((p + 1) * max_degree).min(bin.len());
+
+                for (k, c) in roots_to_coefficients(&bin[start..end], plain_modulus)
+                    .into_iter()
+                    .enumerate()
+                {
+                    coefficients[k][b] = c;
+                }
+            }
+
+            let constant = encoder.encode_unsigned(&coefficients[0])?;
+
+            let terms = coefficients[1..]
+                .iter()
+                .map(|c| {
+                    if c.iter().all(|x| *x == 0) {
+                        Ok(None)
+                    } else {
+                        Ok(Some(encoder.encode_unsigned(c)?))
+                    }
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            partitions.push((constant, terms));
+        }
+
+        Ok(Self {
+            ctx,
+            encoder,
+            degree,
+            partitions,
+        })
+    }
+
+    /**
+     * Evaluates every partition's polynomials on `query` and randomizes
+     * the results.
+     */
+    pub fn respond(&self, query: &PsiQuery) -> Result<PsiResponse> {
+        let evaluator = BFVEvaluator::new(self.ctx)?;
+        let encryptor = Encryptor::with_public_key(self.ctx, &query.public_key)?;
+
+        // powers[k - 1] holds the query to the k-th power. Splitting k in
+        // halves keeps the depth of every power logarithmic.
+        let mut powers = vec![query.ciphertext.clone()];
+
+        for k in 2..=self.degree {
+            let mut power = evaluator.multiply(&powers[k / 2 - 1], &powers[k - k / 2 - 1])?;
+            evaluator.relinearize_inplace(&mut power, &query.relin_keys)?;
+
+            powers.push(power);
+        }
+
+        let plain_modulus = self.encoder.get_plain_modulus();
+        let mut rng = rand::thread_rng();
+
+        let ciphertexts = self
+            .partitions
+            .iter()
+            .map(|(constant, terms)| {
+                let mut sum: Option<Ciphertext> = None;
+
+                for (power, term) in powers.iter().zip(terms) {
+                    if let Some(term) = term {
+                        let product = evaluator.multiply_plain(power, term)?;
+
+                        sum = Some(match sum {
+                            Some(mut s) => {
+                                evaluator.add_inplace(&mut s, &product)?;
+                                s
+                            }
+                            None => product,
+                        });
+                    }
+                }
+
+                let mut result = match sum {
+                    Some(s) => evaluator.add_plain(&s, constant)?,
+                    None => encryptor.encrypt(constant)?,
+                };
+
+                let mask = (0..self.encoder.get_slot_count())
+                    .map(|_| rng.gen_range(1..plain_modulus))
+                    .collect::<Vec<u64>>();
+
+                evaluator
+                    .multiply_plain_inplace(&mut result, &self.encoder.encode_unsigned(&mask)?)?;
+                evaluator.add_inplace(&mut result, &encryptor.encrypt_zero()?)?;
+
+                Ok(result)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(PsiResponse { ciphertexts })
+    }
+}
+
+/**
+ * Hashes `item` under the hash function `domain`.
+ */
+fn digest(domain: usize, item: &[u8]) -> u64 {
+    let mut hasher = Sha3_256::new();
+    hasher.update([domain as u8]);
+    hasher.update(item);
+
+    let hash = hasher.finalize();
+
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&hash[..8]);
+
+    u64::from_le_bytes(bytes)
+}
+
+/**
+ * The bin the `h`-th hash function maps `item` to.
+ */
+fn bin_index(item: &[u8], h: usize, bins: usize) -> usize {
+    (digest(h, item) % bins as u64) as usize
+}
+
+/**
+ * The value that represents `item` in its bin, independent of the hash
+ * function that placed it there.
+ */
+fn item_value(item: &[u8], plain_modulus: u64) -> u64 {
+    digest(HASH_FUNCTIONS, item) % plain_modulus
+}
+
+/**
+ * Places each of `items` in one of its candidate bins, so no bin holds
+ * more than one item. Returns the index of the item in each bin.
+ */
+fn cuckoo_hash(items: &[&[u8]], bins: usize) -> Result<Vec<Option<usize>>> {
+    if items.len() > bins {
+        return Err(Error::PsiSetTooLarge);
+    }
+
+    let mut table = vec![None; bins];
+    let mut rng = rand::thread_rng();
+
+    'items: for i in 0..items.len() {
+        let mut current = i;
+
+        for _ in 0..MAX_EVICTIONS {
+            let mut candidates = [0; HASH_FUNCTIONS];
+
+            for (h, c) in candidates.iter_mut().enumerate() {
+                *c = bin_index(items[current], h, bins);
+            }
+
+            if let Some(b) = candidates.iter().find(|b| table[**b].is_none()) {
+                table[*b] = Some(current);
+                continue 'items;
+            }
+
+            let b = candidates[rng.gen_range(0..HASH_FUNCTIONS)];
+
+            // The bin is occupied, or we'd have placed the item above.
+            current = table[b].replace(current).unwrap();
+        }
+
+        return Err(Error::PsiSetTooLarge);
+    }
+
+    Ok(table)
+}
+
+/**
+ * Returns the coefficients, lowest degree first, of the monic polynomial
+ * with the given `roots` modulo `plain_modulus`.
+ */
+fn roots_to_coefficients(roots: &[u64], plain_modulus: u64) -> Vec<u64> {
+    let t = plain_modulus as u128;
+    let mut coefficients = vec![1];
+
+    for r in roots {
+        let neg = (t - *r as u128 % t) % t;
+        let mut next = vec![0; coefficients.len() + 1];
+
+        // Multiply by (y - r).
+        for (i, c) in coefficients.iter().enumerate() {
+            next[i + 1] = ((next[i + 1] as u128 + *c as u128) % t) as u64;
+            next[i] = ((next[i] as u128 + *c as u128 * neg) % t) as u64;
+        }
+
+        coefficients = next;
+    }
+
+    coefficients
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(coefficients: &[u64], y: u64, t: u64) -> u64 {
+        coefficients.iter().rev().fold(0, |acc, c| {
+            ((acc as u128 * y as u128 + *c as u128) % t as u128) as u64
+        })
+    }
+
+    #[test]
+    fn polynomial_vanishes_at_roots() {
+        let t = 65537;
+        let roots = [3, 17, 65536, 40000];
+
+        let coefficients = roots_to_coefficients(&roots, t);
+
+        assert_eq!(coefficients.len(), roots.len() + 1);
+        assert_eq!(coefficients[roots.len()], 1);
+
+        for r in &roots {
+            assert_eq!(evaluate(&coefficients, *r, t), 0);
+        }
+
+        assert_ne!(evaluate(&coefficients, 4, t), 0);
+        assert_eq!(roots_to_coefficients(&[], t), vec![1]);
+    }
+
+    #[test]
+    fn cuckoo_hashing_places_every_item_once() {
+        let items = (0..200u32).map(|i| i.to_le_bytes()).collect::<Vec<_>>();
+        let items = items.iter().map(|i| &i[..]).collect::<Vec<_>>();
+
+        let table = cuckoo_hash(&items, 256).unwrap();
+
+        let mut placed = table.iter().flatten().copied().collect::<Vec<usize>>();
+        placed.sort_unstable();
+
+        assert_eq!(placed, (0..200).collect::<Vec<_>>());
+
+        for (b, i) in table.iter().enumerate() {
+            if let Some(i) = i {
+                assert!((0..HASH_FUNCTIONS).any(|h| bin_index(items[*i], h, 256) == b));
+            }
+        }
+    }
+
+    #[test]
+    fn cuckoo_hashing_rejects_too_many_items() {
+        let items = (0..9u32).map(|i| i.to_le_bytes()).collect::<Vec<_>>();
+        let items = items.iter().map(|i| &i[..]).collect::<Vec<_>>();
+
+        assert!(matches!(cuckoo_hash(&items, 8), Err(Error::PsiSetTooLarge)));
+    }
+}
@@ -0,0 +1,698 @@
+//! Threshold decryption, where any `threshold` of `parties` key holders
+//! can decrypt a [`Ciphertext`] together but fewer learn nothing about the
+//! plaintext or the secret key.
+//!
+//! The flow has three steps:
+//! 1. **Dealing.** A trusted dealer calls [`deal`], which samples a secret
+//!    key, returns the matching [`PublicKey`] and splits the secret key
+//!    into one [`KeyShare`] per party with Shamir secret sharing. The
+//!    dealer then hands each party its share and forgets the key.
+//! 2. **Partial decryption.** To decrypt a ciphertext, the participating
+//!    parties agree on who takes part, and each computes a
+//!    [`DecryptionShare`] with [`KeyShare::partial_decrypt`]. Only
+//!    decrypt ciphertexts you trust to be honestly generated; see the
+//!    remarks below for what a share reveals.
+//! 3. **Combination.** Anyone holding the ciphertext and a share from
+//!    every participant calls [`combine`] to recover the [`Plaintext`],
+//!    which [`Runtime::decode`](crate::GenericRuntime::decode) turns into
+//!    a value.
+//!
+//! Every share type implements [`Serialize`] and [`Deserialize`], so
+//! parties can exchange them over any transport.
+//!
+//! # Remarks
+//! Shares are points on a random polynomial of degree `threshold - 1`
+//! whose value at 0 is the secret key, evaluated at each party's index
+//! (counting from 1), independently for every coefficient and every prime
+//! in the coefficient modulus. A decryption share holds the participant's
+//! Lagrange coefficient times `c_1 * s_i`, where `s_i` is its key share,
+//! plus fresh noise. The shares sum to `c_1 * s` plus noise, so adding
+//! `c_0` and scaling by `t / q` as in ordinary BFV decryption recovers the
+//! plaintext.
+//!
+//! The noise hides `c_1 * s_i` from whoever combines the shares only
+//! statistically, and only for honestly generated ciphertexts with enough
+//! smudging bits: the noise must dwarf the ciphertext's own noise, which
+//! the share also carries. A ciphertext whose `c_1` an attacker chose
+//! (e.g. a small, known polynomial) makes the share reveal its Lagrange
+//! coefficient times `c_1 * s_i`, and thus the key share, up to the
+//! smudging noise.
+//! Parties should never partially decrypt ciphertexts from untrusted
+//! sources.
+//!
+//! The noise eats into the ciphertext's noise budget: each participant
+//! adds noise of up to `2^smudging_bits`. Decryption fails silently,
+//! returning garbage, if a ciphertext has less than roughly
+//! `smudging_bits + log2(participants) + 1` bits of noise budget left.
+//! Compile programs with enough
+//! [`additional_noise_budget`](crate::GenericCompiler::additional_noise_budget)
+//! to cover it.
+//!
+//! The protocol is secure against semi-honest parties. Nothing checks
+//! that a decryption share was computed correctly, so a single malicious
+//! participant can corrupt the result.
+//!
+//! # Example
+//! ```
+//! # use sunscreen::{
+//! #    fhe_program,
+//! #    protocols::threshold::{combine, deal},
+//! #    types::{bfv::Signed, Cipher},
+//! #    Compiler, Runtime,
+//! # };
+//! #[fhe_program(scheme = "bfv")]
+//! fn add(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+//!     a + b
+//! }
+//!
+//! let app = Compiler::new()
+//!     .fhe_program(add)
+//!     .additional_noise_budget(64)
+//!     .compile()
+//!     .unwrap();
+//!
+//! let runtime = Runtime::new_fhe(app.params()).unwrap();
+//!
+//! // Any 2 of 3 parties can decrypt.
+//! let (public_key, key_shares) = deal(app.params(), 2, 3).unwrap();
+//!
+//! let a = runtime.encrypt(Signed::from(15), &public_key).unwrap();
+//! let b = runtime.encrypt(Signed::from(5), &public_key).unwrap();
+//!
+//! let result = runtime
+//!     .run(app.get_fhe_program(add).unwrap(), vec![a, b], &public_key)
+//!     .unwrap();
+//!
+//! // Parties 1 and 3 decrypt.
+//! let participants = [1, 3];
+//!
+//! let decryption_shares = [&key_shares[0], &key_shares[2]]
+//!     .iter()
+//!     .map(|k| k.partial_decrypt(&result[0], &participants).unwrap())
+//!     .collect::<Vec<_>>();
+//!
+//! let plaintext = combine(&result[0], &decryption_shares).unwrap();
+//! let sum: Signed = runtime.decode(&plaintext).unwrap();
+//!
+//! assert_eq!(sum, 20.into());
+//! ```
+
+use rand::Rng;
+use seal_fhe::rns::{add_mod, inv_mod, mul_mod, reduce_signed, sub_mod, NttTables};
+use seal_fhe::{
+    BfvEncryptionParametersBuilder, Ciphertext as SealCiphertext, Context as SealContext,
+    KeyGenerator, Modulus, SecretKey as SealSecretKey,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::types::Type;
+use crate::{
+    Ciphertext, Error, InnerCiphertext, InnerPlaintext, Params, Plaintext, PublicKey, Result,
+    RuntimeError, SealPlaintext, Secret, WithContext,
+};
+
+/**
+ * The number of bits of noise each participant adds to its
+ * [`DecryptionShare`] in [`KeyShare::partial_decrypt`].
+ */
+pub const DEFAULT_SMUDGING_BITS: u32 = 40;
+
+#[derive(Debug, Serialize, Deserialize)]
+/**
+ * One party's share of a secret key, from [`deal`].
+ *
+ * # Remarks
+ * Like a [`PrivateKey`](crate::PrivateKey), a key share must stay with its
+ * party. Its [`Debug`] output is redacted, and the share is zeroized when
+ * it's dropped.
+ */
+pub struct KeyShare {
+    party: u32,
+    threshold: u32,
+    params: Params,
+    share: Secret<Vec<u64>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/**
+ * One party's contribution to decrypting a ciphertext, from
+ * [`KeyShare::partial_decrypt`]. Pass a share from every participant to
+ * [`combine`].
+ */
+pub struct DecryptionShare {
+    party: u32,
+    participants: Vec<u32>,
+    params: Params,
+    polynomials: Vec<Vec<u64>>,
+}
+
+/**
+ * Samples a secret key for `params` and splits it into `parties` shares,
+ * any `threshold` of which can decrypt. Returns the public key, with
+ * relinearization and Galois keys as
+ * [`Runtime::generate_keys`](crate::GenericRuntime::generate_keys) creates
+ * them, and the shares of parties `1..=parties` in order.
+ *
+ * # Remarks
+ * The caller sees the whole secret key while dealing, so this must run
+ * somewhere every party trusts, which then discards everything but the
+ * public key.
+ *
+ * Returns [`Error::ThresholdDecryption`] if `threshold` is 0 or exceeds
+ * `parties`.
+ */
+pub fn deal(params: &Params, threshold: u32, parties: u32) -> Result<(PublicKey, Vec<KeyShare>)> {
+    if threshold == 0 || threshold > parties {
+        return Err(Error::threshold_decryption(&format!(
+            "can't share a key {} of {} ways",
+            threshold, parties
+        )));
+    }
+
+    let context = seal_context(params)?;
+    let n = params.lattice_dimension as usize;
+    let mut rng = rand::thread_rng();
+
+    // SEAL samples secret keys with ternary coefficients.
+    let mut secret = (0..n).map(|_| rng.gen_range(-1..=1)).collect::<Vec<i64>>();
+
+    let secret_key = SealSecretKey::from_coefficients(&context, &secret);
+    let keygen = secret_key.and_then(|k| KeyGenerator::new_from_secret_key(&context, &k));
+
+    let keygen = match keygen {
+        Ok(k) => k,
+        Err(e) => {
+            clear(&mut secret);
+            return Err(RuntimeError::from(e).into());
+        }
+    };
+
+    let mut shares = vec![vec![0u64; n * params.coeff_modulus.len()]; parties as usize];
+    let mut polynomial = vec![0u64; threshold as usize];
+
+    for (j, q) in params.coeff_modulus.iter().enumerate() {
+        for (i, s) in secret.iter().enumerate() {
+            polynomial[0] = reduce_signed(*s, *q);
+
+            for c in polynomial[1..].iter_mut() {
+                *c = rng.gen_range(0..*q);
+            }
+
+            for (x, share) in shares.iter_mut().enumerate() {
+                share[j * n + i] = evaluate(&polynomial, x as u64 + 1, *q);
+            }
+        }
+    }
+
+    clear(&mut secret);
+    clear(&mut polynomial);
+
+    let public_key = PublicKey {
+        public_key: WithContext {
+            params: params.clone(),
+            data: keygen.create_public_key()?,
+        },
+        galois_key: keygen.create_galois_keys().ok().map(|k| WithContext {
+            params: params.clone(),
+            data: k,
+        }),
+        relin_key: keygen
+            .create_relinearization_keys()
+            .ok()
+            .map(|k| WithContext {
+                params: params.clone(),
+                data: k,
+            }),
+    };
+
+    let shares = shares
+        .into_iter()
+        .enumerate()
+        .map(|(x, share)| KeyShare {
+            party: x as u32 + 1,
+            threshold,
+            params: params.clone(),
+            share: Secret::new(share),
+        })
+        .collect();
+
+    Ok((public_key, shares))
+}
+
+impl KeyShare {
+    /**
+     * This share's party index, counting from 1.
+     */
+    pub fn party(&self) -> u32 {
+        self.party
+    }
+
+    /**
+     * The number of parties needed to decrypt.
+     */
+    pub fn threshold(&self) -> u32 {
+        self.threshold
+    }
+
+    /**
+     * The parameters of the shared key.
+     */
+    pub fn params(&self) -> &Params {
+        &self.params
+    }
+
+    /**
+     * Computes this party's share of decrypting `ciphertext` together with
+     * the `participants`, which must include this party, adding noise of
+     * up to [`DEFAULT_SMUDGING_BITS`] bits.
+     *
+     * # Remarks
+     * Every participant must pass the same `participants`, in any order.
+     *
+     * Returns [`RuntimeError::ParameterMismatch`] if `ciphertext` uses
+     * different parameters than the key, and
+     * [`Error::ThresholdDecryption`] if the participants are fewer than
+     * the threshold, don't include this party, repeat a party or include
+     * party 0, if the share doesn't match its parameters (e.g. after
+     * deserializing a corrupt one) or if `ciphertext` isn't relinearized.
+     */
+    pub fn partial_decrypt(
+        &self,
+        ciphertext: &Ciphertext,
+        participants: &[u32],
+    ) -> Result<DecryptionShare> {
+        self.partial_decrypt_with_smudging(ciphertext, participants, DEFAULT_SMUDGING_BITS)
+    }
+
+    /**
+     * Like [`partial_decrypt`](Self::partial_decrypt), but adds noise of up
+     * to `smudging_bits` bits. More noise hides the key share better but
+     * takes more noise budget.
+     *
+     * Returns [`Error::ThresholdDecryption`] if `smudging_bits` exceeds 62,
+     * and otherwise fails as [`partial_decrypt`](Self::partial_decrypt)
+     * does.
+     */
+    pub fn partial_decrypt_with_smudging(
+        &self,
+        ciphertext: &Ciphertext,
+        participants: &[u32],
+        smudging_bits: u32,
+    ) -> Result<DecryptionShare> {
+        if smudging_bits > 62 {
+            return Err(Error::threshold_decryption(
+                "smudging_bits must be at most 62",
+            ));
+        }
+
+        let mut participants = participants.to_owned();
+        participants.sort_unstable();
+
+        if !are_valid_parties(&participants) || participants.binary_search(&self.party).is_err() {
+            return Err(Error::threshold_decryption(&format!(
+                "participants must be distinct, nonzero and include party {}",
+                self.party
+            )));
+        }
+
+        if participants.len() < self.threshold as usize {
+            return Err(Error::threshold_decryption(&format!(
+                "{} participants can't meet the threshold of {}",
+                participants.len(),
+                self.threshold
+            )));
+        }
+
+        let n = self.params.lattice_dimension as usize;
+        let bound = 1i64 << smudging_bits;
+        let share = self.share.expose_secret();
+
+        if share.len() != n * self.params.coeff_modulus.len() {
+            return Err(Error::threshold_decryption(
+                "key share doesn't match its parameters",
+            ));
+        }
+
+        let mut rng = rand::thread_rng();
+
+        let polynomials =
+            seal_ciphertexts(ciphertext, &self.params)?
+                .iter()
+                .map(|c| {
+                    let c1 = c.polynomial(1).map_err(RuntimeError::from)?;
+                    let noise = (0..n)
+                        .map(|_| rng.gen_range(-bound..=bound))
+                        .collect::<Vec<_>>();
+
+                    let mut out = Vec::with_capacity(c1.len());
+
+                    for (j, c1) in c1.chunks(n).enumerate() {
+                        let q = self.params.coeff_modulus[j];
+                        let lambda = lagrange_at_zero(self.party, &participants, q);
+                        let tables = NttTables::new(n, q).map_err(RuntimeError::from)?;
+                        let mut product = tables.multiply(c1, &share[j * n..(j + 1) * n]);
+
+                        out.extend(product.iter().zip(noise.iter()).map(|(p, e)| {
+                            add_mod(mul_mod(*p, lambda, q), reduce_signed(*e, q), q)
+                        }));
+
+                        // Without the noise, c_1 * s_i reveals the key share.
+                        clear(&mut product);
+                    }
+
+                    Ok(out)
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+        Ok(DecryptionShare {
+            party: self.party,
+            participants,
+            params: self.params.clone(),
+            polynomials,
+        })
+    }
+}
+
+impl DecryptionShare {
+    /**
+     * The index of the party that computed this share.
+     */
+    pub fn party(&self) -> u32 {
+        self.party
+    }
+
+    /**
+     * The parties taking part in the decryption, in ascending order.
+     */
+    pub fn participants(&self) -> &[u32] {
+        &self.participants
+    }
+}
+
+/**
+ * Combines a [`DecryptionShare`] of `ciphertext` from every participant
+ * into the decrypted [`Plaintext`].
+ *
+ * # Remarks
+ * The result is garbage if the shares belong to a different ciphertext,
+ * or if the ciphertext has too little noise budget left (see the
+ * [module documentation](self)).
+ *
+ * Returns [`RuntimeError::ParameterMismatch`] if the shares and
+ * `ciphertext` use different parameters, and
+ * [`Error::ThresholdDecryption`] if the shares disagree on the
+ * participants, some participant's share is missing or repeated, a party
+ * index is 0, or the shares don't match the ciphertext's shape or hold
+ * coefficients that aren't reduced modulo their primes.
+ */
+pub fn combine(ciphertext: &Ciphertext, shares: &[DecryptionShare]) -> Result<Plaintext> {
+    let first = shares
+        .first()
+        .ok_or_else(|| Error::threshold_decryption("no decryption shares"))?;
+
+    let mut parties = shares.iter().map(|s| s.party).collect::<Vec<_>>();
+    parties.sort_unstable();
+
+    if !are_valid_parties(&first.participants)
+        || shares.iter().any(|s| s.participants != first.participants)
+        || parties != first.participants
+    {
+        return Err(Error::threshold_decryption(
+            "need exactly one share from every participant",
+        ));
+    }
+
+    if shares.iter().any(|s| s.params != first.params) {
+        return Err(RuntimeError::ParameterMismatch.into());
+    }
+
+    let params = &first.params;
+    let n = params.lattice_dimension as usize;
+    let t = params.plain_modulus;
+    let ciphertexts = seal_ciphertexts(ciphertext, params)?;
+
+    let shape_mismatch = || Error::threshold_decryption("shares don't match the ciphertext");
+
+    if shares
+        .iter()
+        .any(|s| s.polynomials.len() != ciphertexts.len())
+    {
+        return Err(shape_mismatch());
+    }
+
+    let plaintexts = ciphertexts
+        .iter()
+        .enumerate()
+        .map(|(c_index, c)| {
+            let c0 = c.polynomial(0).map_err(RuntimeError::from)?;
+
+            if shares
+                .iter()
+                .any(|s| s.polynomials[c_index].len() != c0.len())
+            {
+                return Err(shape_mismatch());
+            }
+
+            let unreduced = shares.iter().any(|s| {
+                s.polynomials[c_index]
+                    .chunks(n)
+                    .zip(params.coeff_modulus.iter())
+                    .any(|(d, q)| d.iter().any(|d| *d >= *q))
+            });
+
+            if unreduced {
+                return Err(Error::threshold_decryption(
+                    "decryption share coefficients must be reduced modulo their primes",
+                ));
+            }
+
+            // c_0 + c_1 * s in RNS form.
+            let mut x = c0;
+
+            for share in shares {
+                for (j, (x, d)) in x
+                    .chunks_mut(n)
+                    .zip(share.polynomials[c_index].chunks(n))
+                    .enumerate()
+                {
+                    let q = params.coeff_modulus[j];
+
+                    for (x, d) in x.iter_mut().zip(d) {
+                        *x = add_mod(*x, *d, q);
+                    }
+                }
+            }
+
+            let moduli = &params.coeff_modulus[..x.len() / n];
+            let coefficients = scale_and_round(&x, moduli, t);
+
+            let mut plaintext = SealPlaintext::new().map_err(RuntimeError::from)?;
+            plaintext.resize(n);
+
+            for (i, m) in coefficients.iter().enumerate() {
+                plaintext.set_coefficient(i, *m);
+            }
+
+            Ok(WithContext {
+                params: params.clone(),
+                data: plaintext,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Plaintext {
+        data_type: Type {
+            is_encrypted: false,
+            ..ciphertext.data_type.clone()
+        },
+        inner: InnerPlaintext::Seal(plaintexts),
+    })
+}
+
+fn seal_context(params: &Params) -> Result<SealContext> {
+    let coeff_modulus = params
+        .coeff_modulus
+        .iter()
+        .map(|q| Modulus::new(*q))
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(RuntimeError::from)?;
+
+    let encryption_params = BfvEncryptionParametersBuilder::new()
+        .set_poly_modulus_degree(params.lattice_dimension)
+        .set_coefficient_modulus(coeff_modulus)
+        .set_plain_modulus_u64(params.plain_modulus)
+        .build()
+        .map_err(RuntimeError::from)?;
+
+    SealContext::new(&encryption_params, true, params.security_level)
+        .map_err(|e| RuntimeError::from(e).into())
+}
+
+/**
+ * Returns the SEAL ciphertexts in `ciphertext`, checking they use `params`
+ * and are relinearized and in coefficient form.
+ */
+fn seal_ciphertexts<'a>(
+    ciphertext: &'a Ciphertext,
+    params: &Params,
+) -> Result<&'a [WithContext<SealCiphertext>]> {
+    let ciphertexts = match &ciphertext.inner {
+        InnerCiphertext::Seal(c) => c,
+    };
+
+    if ciphertexts.iter().any(|c| c.params != *params) {
+        return Err(RuntimeError::ParameterMismatch.into());
+    }
+
+    if ciphertexts
+        .iter()
+        .any(|c| c.data.num_polynomials() != 2 || c.data.is_ntt_form())
+    {
+        return Err(Error::threshold_decryption(
+            "only relinearized ciphertexts in coefficient form can be decrypted",
+        ));
+    }
+
+    Ok(ciphertexts)
+}
+
+/**
+ * Overwrites secret values in a way the compiler won't optimize away.
+ */
+fn clear<T: Default>(values: &mut [T]) {
+    for x in values.iter_mut() {
+        // Safe because x is a valid, aligned reference.
+        unsafe { std::ptr::write_volatile(x, T::default()) };
+    }
+}
+
+/**
+ * Returns whether the sorted party indices `parties` are distinct and
+ * nonzero. Shares are evaluations of the sharing polynomial at the party
+ * indices, so index 0 would be the secret itself.
+ */
+fn are_valid_parties(parties: &[u32]) -> bool {
+    parties.first() != Some(&0) && parties.windows(2).all(|p| p[0] < p[1])
+}
+
+/**
+ * Evaluates the polynomial with the given coefficients, constant term
+ * first, at `x` modulo `q`.
+ */
+fn evaluate(coefficients: &[u64], x: u64, q: u64) -> u64 {
+    let x = x % q;
+
+    coefficients
+        .iter()
+        .rev()
+        .fold(0, |acc, c| add_mod(mul_mod(acc, x, q), *c, q))
+}
+
+/**
+ * The Lagrange coefficient that weights `party`'s share when
+ * interpolating the sharing polynomial at 0 from the `participants`'
+ * shares, modulo the prime `q`.
+ */
+fn lagrange_at_zero(party: u32, participants: &[u32], q: u64) -> u64 {
+    let x = party as u64 % q;
+
+    let (numerator, denominator) =
+        participants
+            .iter()
+            .filter(|p| **p != party)
+            .fold((1, 1), |(num, den), p| {
+                let p = *p as u64 % q;
+
+                (mul_mod(num, p, q), mul_mod(den, sub_mod(p, x, q), q))
+            });
+
+    mul_mod(numerator, inv_mod(denominator, q), q)
+}
+
+/**
+ * Computes `round(t * x / q) mod t` for every coefficient of `x`, given in
+ * RNS form over `moduli`, whose product is `q`.
+ */
+fn scale_and_round(x: &[u64], moduli: &[u64], t: u64) -> Vec<u64> {
+    let n = x.len() / moduli.len();
+
+    // x = sum_j v_j * (q / q_j) - k * q, where
+    // v_j = x_j * (q / q_j)^-1 mod q_j, so t * x / q is sum_j t * v_j / q_j
+    // minus a multiple of t.
+    let q_hat_inv = moduli
+        .iter()
+        .enumerate()
+        .map(|(j, q)| {
+            let q_hat = moduli
+                .iter()
+                .enumerate()
+                .filter(|(l, _)| *l != j)
+                .fold(1, |acc, (_, p)| mul_mod(acc, *p % q, *q));
+
+            inv_mod(q_hat, *q)
+        })
+        .collect::<Vec<_>>();
+
+    (0..n)
+        .map(|i| {
+            let mut whole = 0u128;
+            let mut fraction = 0f64;
+
+            for (j, q) in moduli.iter().enumerate() {
+                let v = mul_mod(x[j * n + i], q_hat_inv[j], *q);
+                let scaled = v as u128 * t as u128;
+
+                whole += scaled / *q as u128;
+                fraction += (scaled % *q as u128) as f64 / *q as f64;
+            }
+
+            ((whole + fraction.round() as u128) % t as u128) as u64
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const Q: u64 = 1_152_921_504_606_584_833;
+
+    #[test]
+    fn lagrange_coefficients_reconstruct_secret() {
+        let secret = reduce_signed(-1, Q);
+        let polynomial = [secret, 12345, 67890];
+
+        for participants in [[1, 2, 3], [1, 3, 5], [2, 4, 5]].iter() {
+            let reconstructed = participants.iter().fold(0, |acc, p| {
+                let share = evaluate(&polynomial, *p as u64, Q);
+
+                add_mod(
+                    acc,
+                    mul_mod(share, lagrange_at_zero(*p, participants, Q), Q),
+                    Q,
+                )
+            });
+
+            assert_eq!(reconstructed, secret);
+        }
+    }
+
+    #[test]
+    fn scale_and_round_matches_big_integer_division() {
+        let moduli = [1_099_511_922_689, 1_099_512_004_609];
+        let q = moduli[0] as u128 * moduli[1] as u128;
+        let t = 1024;
+
+        for x in [0, 1, q / 2, q / 3 + 17, q - 1].iter() {
+            let rns = moduli
+                .iter()
+                .map(|m| (x % *m as u128) as u64)
+                .collect::<Vec<_>>();
+
+            // round(t * x / q), computed without overflow since t * x < 2^128.
+            let expected = ((*x * t as u128 + q / 2) / q % t as u128) as u64;
+
+            assert_eq!(scale_and_round(&rns, &moduli, t), vec![expected]);
+        }
+    }
+}
@@ -0,0 +1,394 @@
+use std::collections::BTreeMap;
+
+use rayon::prelude::*;
+use sunscreen_runtime::{marker, Ciphertext, KeyRotation, ReEncryptor};
+
+use crate::{ApplicationDiff, Error, MigrationAction, Result, RuntimeError};
+
+/**
+ * Persistent storage for ciphertexts a [`ReencryptionPlanner`] migrates,
+ * e.g. a database table or an object store.
+ *
+ * # Remarks
+ * Implementations should map their own failures to
+ * [`Error::CiphertextStore`]. A [`BTreeMap`] serves as an in-memory store.
+ */
+pub trait CiphertextStore {
+    /**
+     * Identifies a ciphertext in the store.
+     */
+    type Id: Clone + Ord;
+
+    /**
+     * Lists the identifiers of every ciphertext in the store.
+     */
+    fn ids(&self) -> Result<Vec<Self::Id>>;
+
+    /**
+     * Loads the ciphertext with the given identifier.
+     */
+    fn load(&self, id: &Self::Id) -> Result<Ciphertext>;
+
+    /**
+     * Replaces the ciphertext with the given identifier.
+     */
+    fn store(&mut self, id: &Self::Id, ciphertext: Ciphertext) -> Result<()>;
+}
+
+impl<K> CiphertextStore for BTreeMap<K, Ciphertext>
+where
+    K: Clone + Ord + std::fmt::Debug,
+{
+    type Id = K;
+
+    fn ids(&self) -> Result<Vec<K>> {
+        Ok(self.keys().cloned().collect())
+    }
+
+    fn load(&self, id: &K) -> Result<Ciphertext> {
+        self.get(id)
+            .cloned()
+            .ok_or_else(|| Error::CiphertextStore(Box::new(format!("No ciphertext {:?}", id))))
+    }
+
+    fn store(&mut self, id: &K, ciphertext: Ciphertext) -> Result<()> {
+        self.insert(id.clone(), ciphertext);
+
+        Ok(())
+    }
+}
+
+/**
+ * A strategy for moving a batch of ciphertexts to new parameters or keys,
+ * driven by a [`ReencryptionPlanner`].
+ */
+pub trait Reencrypt {
+    /**
+     * Re-encrypts `ciphertexts`, returning one result per ciphertext in
+     * the same order. A failure only affects the corresponding
+     * ciphertext.
+     */
+    fn reencrypt_batch(&mut self, ciphertexts: &[Ciphertext]) -> Vec<Result<Ciphertext>>;
+}
+
+/**
+ * Re-encrypts under a new key pair with the same parameters, wherever the
+ * old private key is held. See [`KeyRotation`].
+ */
+impl<'a, T, B> Reencrypt for KeyRotation<'a, T, B>
+where
+    T: marker::Fhe + Sync,
+    B: Sync,
+{
+    fn reencrypt_batch(&mut self, ciphertexts: &[Ciphertext]) -> Vec<Result<Ciphertext>> {
+        let rotation = *self;
+
+        ciphertexts
+            .par_iter()
+            .map(|c| Ok(rotation.reencrypt(c)?))
+            .collect()
+    }
+}
+
+/**
+ * Switches ciphertexts to the delegatee's key pair without the old
+ * private key. See [`ReEncryptor`].
+ */
+impl Reencrypt for ReEncryptor {
+    fn reencrypt_batch(&mut self, ciphertexts: &[Ciphertext]) -> Vec<Result<Ciphertext>> {
+        let reencryptor = &*self;
+
+        ciphertexts
+            .par_iter()
+            .map(|c| Ok(reencryptor.reencrypt(c)?))
+            .collect()
+    }
+}
+
+/**
+ * Re-encrypts by handing each batch to the key holder, e.g. a client
+ * that decrypts with its old private key and encrypts the values under
+ * its new public key.
+ *
+ * # Remarks
+ * Unlike [`KeyRotation`], this works across parameter changes, since the
+ * client decodes each value before encrypting it under the new
+ * parameters. The wrapped function receives a batch and returns the
+ * re-encrypted ciphertexts in the same order. If it fails, every
+ * ciphertext in the batch fails.
+ */
+pub struct ClientAssisted<F>(pub F);
+
+impl<F> Reencrypt for ClientAssisted<F>
+where
+    F: FnMut(&[Ciphertext]) -> Result<Vec<Ciphertext>>,
+{
+    fn reencrypt_batch(&mut self, ciphertexts: &[Ciphertext]) -> Vec<Result<Ciphertext>> {
+        match (self.0)(ciphertexts) {
+            Ok(results) if results.len() == ciphertexts.len() => {
+                results.into_iter().map(Ok).collect()
+            }
+            Ok(_) => ciphertexts
+                .iter()
+                .map(|_| Err(Error::ReencryptionBatchSize))
+                .collect(),
+            Err(e) => ciphertexts.iter().map(|_| Err(e.clone())).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/**
+ * How far a [`ReencryptionPlanner`] has progressed.
+ */
+pub struct ReencryptionProgress {
+    /**
+     * The number of ciphertexts the plan covers.
+     */
+    pub total: usize,
+
+    /**
+     * The number of ciphertexts re-encrypted and written back.
+     */
+    pub completed: usize,
+
+    /**
+     * The number of ciphertexts whose re-encryption failed.
+     */
+    pub failed: usize,
+}
+
+impl ReencryptionProgress {
+    /**
+     * The number of ciphertexts not yet attempted.
+     */
+    pub fn remaining(&self) -> usize {
+        self.total - self.completed - self.failed
+    }
+
+    /**
+     * Whether every ciphertext has been attempted.
+     */
+    pub fn is_done(&self) -> bool {
+        self.remaining() == 0
+    }
+}
+
+/**
+ * Drives a re-encryption campaign over a [`CiphertextStore`], e.g. after
+ * recompiling an application with new parameters or rotating keys.
+ *
+ * # Remarks
+ * The planner lists the stored ciphertexts that need re-encryption up
+ * front, then re-encrypts them `batch_size` at a time with a
+ * [`Reencrypt`] strategy, writing each result back to the store as its
+ * batch completes. It records which ciphertexts completed and which
+ * failed, so a campaign interrupted part way can resume with
+ * [`skip`](Self::skip) and failures can be retried with
+ * [`retry_failed`](Self::retry_failed).
+ *
+ * Errors from the store abort the campaign, while errors re-encrypting
+ * individual ciphertexts are recorded in [`failures`](Self::failures).
+ */
+pub struct ReencryptionPlanner<'a, S: CiphertextStore> {
+    store: &'a mut S,
+    target: Option<ApplicationDiff>,
+    batch_size: usize,
+    pending: Vec<S::Id>,
+    completed: Vec<S::Id>,
+    failures: Vec<(S::Id, Error)>,
+}
+
+impl<'a, S: CiphertextStore> ReencryptionPlanner<'a, S> {
+    /**
+     * Plans re-encrypting the ciphertexts in `store` that `diff` (see
+     * [`Application::diff`](crate::Application::diff)) marks
+     * [`MigrationAction::Reencrypt`].
+     *
+     * # Remarks
+     * This loads every stored ciphertext once to check its parameters.
+     * Re-encrypted ciphertexts that still don't match the new
+     * application's parameters are recorded as failures with
+     * [`RuntimeError::ParameterMismatch`].
+     */
+    pub fn new(diff: &ApplicationDiff, store: &'a mut S) -> Result<Self> {
+        let mut pending = vec![];
+
+        for id in store.ids()? {
+            if diff.ciphertext_action(&store.load(&id)?) == MigrationAction::Reencrypt {
+                pending.push(id);
+            }
+        }
+
+        Ok(Self::with_pending(store, Some(diff.clone()), pending))
+    }
+
+    /**
+     * Plans re-encrypting every ciphertext in `store`, e.g. to rotate keys
+     * without changing parameters, where ciphertexts under the old key
+     * are indistinguishable from those under the new one.
+     */
+    pub fn all(store: &'a mut S) -> Result<Self> {
+        let pending = store.ids()?;
+
+        Ok(Self::with_pending(store, None, pending))
+    }
+
+    fn with_pending(
+        store: &'a mut S,
+        target: Option<ApplicationDiff>,
+        pending: Vec<S::Id>,
+    ) -> Self {
+        Self {
+            store,
+            target,
+            batch_size: 64,
+            pending,
+            completed: vec![],
+            failures: vec![],
+        }
+    }
+
+    /**
+     * Sets how many ciphertexts to hand the [`Reencrypt`] strategy at a
+     * time. Defaults to 64.
+     *
+     * # Panics
+     * If `batch_size` is 0.
+     */
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        assert!(batch_size > 0, "Batches must hold at least one ciphertext");
+
+        self.batch_size = batch_size;
+        self
+    }
+
+    /**
+     * Removes `ids`, e.g. the [`completed`](Self::completed) ciphertexts of
+     * an earlier, interrupted campaign, from the plan.
+     */
+    pub fn skip<I>(mut self, ids: I) -> Self
+    where
+        I: IntoIterator<Item = S::Id>,
+    {
+        let mut skipped = ids.into_iter().collect::<Vec<_>>();
+        skipped.sort();
+
+        self.pending.retain(|id| skipped.binary_search(id).is_err());
+        self
+    }
+
+    /**
+     * The ciphertexts not yet attempted, in the order they'll be
+     * re-encrypted.
+     */
+    pub fn pending(&self) -> &[S::Id] {
+        &self.pending
+    }
+
+    /**
+     * The ciphertexts re-encrypted and written back so far.
+     */
+    pub fn completed(&self) -> &[S::Id] {
+        &self.completed
+    }
+
+    /**
+     * The ciphertexts whose re-encryption failed and why.
+     */
+    pub fn failures(&self) -> &[(S::Id, Error)] {
+        &self.failures
+    }
+
+    /**
+     * Returns how far the campaign has progressed.
+     */
+    pub fn progress(&self) -> ReencryptionProgress {
+        ReencryptionProgress {
+            total: self.pending.len() + self.completed.len() + self.failures.len(),
+            completed: self.completed.len(),
+            failed: self.failures.len(),
+        }
+    }
+
+    /**
+     * Moves every failed ciphertext back into the plan, to be attempted
+     * after the remaining ones.
+     */
+    pub fn retry_failed(&mut self) {
+        self.pending
+            .extend(self.failures.drain(..).map(|(id, _)| id));
+    }
+
+    /**
+     * Re-encrypts the next batch of ciphertexts with `strategy` and writes
+     * the results back to the store. Returns the progress afterwards, or
+     * [`None`] if no ciphertexts were pending.
+     */
+    pub fn run_batch<R: Reencrypt>(
+        &mut self,
+        strategy: &mut R,
+    ) -> Result<Option<ReencryptionProgress>> {
+        if self.pending.is_empty() {
+            return Ok(None);
+        }
+
+        let count = self.batch_size.min(self.pending.len());
+
+        let ciphertexts = self.pending[..count]
+            .iter()
+            .map(|id| self.store.load(id))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut results = strategy.reencrypt_batch(&ciphertexts).into_iter();
+
+        for i in 0..count {
+            let id = self.pending[i].clone();
+            let result = results.next().unwrap_or(Err(Error::ReencryptionBatchSize));
+
+            let result = result.and_then(|c| match &self.target {
+                Some(diff) if diff.ciphertext_action(&c) != MigrationAction::Keep => {
+                    Err(Error::RuntimeError(RuntimeError::ParameterMismatch))
+                }
+                _ => Ok(c),
+            });
+
+            match result {
+                Ok(c) => {
+                    if let Err(e) = self.store.store(&id, c) {
+                        // Keep the ciphertexts not yet written back pending.
+                        self.pending.drain(..i);
+                        return Err(e);
+                    }
+
+                    self.completed.push(id);
+                }
+                Err(e) => self.failures.push((id, e)),
+            }
+        }
+
+        self.pending.drain(..count);
+
+        Ok(Some(self.progress()))
+    }
+
+    /**
+     * Runs batches until no ciphertexts are pending, calling `on_progress`
+     * after each, and returns the final progress.
+     */
+    pub fn run<R, P>(
+        &mut self,
+        strategy: &mut R,
+        mut on_progress: P,
+    ) -> Result<ReencryptionProgress>
+    where
+        R: Reencrypt,
+        P: FnMut(&ReencryptionProgress),
+    {
+        while let Some(progress) = self.run_batch(strategy)? {
+            on_progress(&progress);
+        }
+
+        Ok(self.progress())
+    }
+}
@@ -1,15 +1,15 @@
 use crate::{
     fhe::{with_fhe_ctx, FheContextOps, Literal},
     types::{
-        intern::{Cipher, FheProgramNode},
+        intern::{Cipher, FheProgramNode, IntegerLiteral},
         ops::*,
-        BfvType, FheType, LaneCount, NumCiphertexts, SwapRows, TryFromPlaintext, TryIntoPlaintext,
-        Type, TypeName, TypeNameInstance, Version,
+        BfvType, FheType, FoldColumns, LaneCount, NumCiphertexts, Rotate, SwapRows, TryFromJson,
+        TryFromPlaintext, TryIntoPlaintext, Type, TypeName, TypeNameInstance, Version,
     },
     FheProgramInputTrait, InnerPlaintext, Params, Plaintext, WithContext,
 };
 use seal_fhe::{
-    BFVEncoder, BfvEncryptionParametersBuilder, Context as SealContext, Modulus,
+    BFVEncoder, BfvEncryptionParametersBuilder, Context as SealContext, Modulus, Padding,
     Result as SealResult,
 };
 use std::ops::*;
@@ -44,7 +44,9 @@ use sunscreen_runtime::{Error as RuntimeError, Result as RuntimeResult};
  * To combine values across multiple lanes, one can use rotation
  * operations. Unlike a shift, rotation operations cause elements to
  * wrap around rather than truncate. The Batched type exposes these as the
- * `<<`, `>>`, and `swap_rows` operators:
+ * `<<`, `>>`, and `swap_rows` operators, and as the
+ * [`rotate_left`](Rotate::rotate_left) and [`rotate_right`](Rotate::rotate_right)
+ * methods:
  * * `x << n`, where n is a u64 rotates each row n places to the left.
  * For example, `[0, 1, 2, 3; 4, 5, 6, 7] << 3` yields
  * `[3, 0, 1, 2; 7, 4, 5, 6]` (note that real vectors have many more
@@ -52,6 +54,7 @@ use sunscreen_runtime::{Error as RuntimeError, Result as RuntimeResult};
  * * `x << n`, where n is a u64 rotates each lane n places to the left.
  * For example, `[0, 1, 2, 3; 4, 5, 6, 7] >> 1` yields `[3, 0, 1, 2; 7, 4, 5, 6]`.
  * * `x.swap_rows()` swaps the rows. For example, `[0, 1, 2, 3; 4, 5, 6, 7].swap_rows()` yields `[4, 5, 6, 7; 0, 1, 2, 3]`.
+ * * `x.fold_columns()` sums the rows into both rows. For example, `[0, 1, 2, 3; 4, 5, 6, 7].fold_columns()` yields `[4, 6, 8, 10; 4, 6, 8, 10]`.
  *
  * # Performance
  * The BFV scheme is parameterized by a number of values. Generally,
@@ -181,9 +184,15 @@ impl<const LANES: usize> TryIntoPlaintext for Batched<LANES> {
 
         let reps = params.lattice_dimension as usize / (2 * LANES);
 
-        let data = [self.data[0].repeat(reps), self.data[1].repeat(reps)].concat();
+        // Map negative values into Z_t ourselves, as SEAL doesn't check
+        // that values are in range and silently wraps those that aren't.
+        let data = [self.data[0].repeat(reps), self.data[1].repeat(reps)]
+            .concat()
+            .iter()
+            .map(|x| x.to_plain_modulus(params.plain_modulus))
+            .collect::<RuntimeResult<Vec<u64>>>()?;
 
-        let plaintext = encoder.encode_signed(&data)?;
+        let plaintext = encoder.encode_unsigned(&data)?;
 
         Ok(Plaintext {
             data_type: Self::type_name(),
@@ -262,6 +271,20 @@ impl<const LANES: usize> TryFromPlaintext for Batched<LANES> {
     }
 }
 
+impl<const LANES: usize> TryFromJson for Batched<LANES> {
+    /**
+     * Reads a batched vector from a JSON array of its two rows, each an
+     * array of exactly `LANES` integers.
+     */
+    fn try_from_json(value: &serde_json::Value) -> RuntimeResult<Self> {
+        let rows: [Vec<i64>; 2] = serde_json::from_value(value.clone()).map_err(|_| {
+            RuntimeError::fhe_type_error("Expected a JSON array of two integer arrays")
+        })?;
+
+        Self::try_from(rows)
+    }
+}
+
 impl<const LANES: usize> TryFrom<[Vec<i64>; 2]> for Batched<LANES> {
     type Error = RuntimeError;
 
@@ -285,6 +308,30 @@ impl<const LANES: usize> TryFrom<[Vec<i64>; 2]> for Batched<LANES> {
     }
 }
 
+impl<const LANES: usize> Batched<LANES> {
+    /**
+     * Creates a Batched vector from rows with at most `LANES` values each,
+     * filling the remaining lanes of each row according to `padding`.
+     *
+     * # Remarks
+     * Choose the padding to suit how the program combines lanes. For
+     * example, pad with [`Padding::NeutralElement`] of
+     * [`Reduction::Product`](seal_fhe::Reduction::Product) if the program
+     * multiplies lanes together, or [`Padding::RepeatLast`] if it takes a
+     * minimum. Use [`Padding::fit`] to truncate longer rows first.
+     */
+    pub fn try_from_padded(data: [Vec<i64>; 2], padding: Padding) -> RuntimeResult<Self> {
+        if data.iter().any(|row| row.len() > LANES) {
+            return Err(RuntimeError::fhe_type_error(&format!(
+                "Rows must have at most {} values",
+                LANES
+            )));
+        }
+
+        Self::try_from([padding.pad(&data[0], LANES)?, padding.pad(&data[1], LANES)?])
+    }
+}
+
 impl<const LANES: usize> From<Batched<LANES>> for [Vec<i64>; 2] {
     fn from(val: Batched<LANES>) -> Self {
         [val.data[0].into(), val.data[1].into()]
@@ -381,6 +428,47 @@ impl<const LANES: usize> Mul for Batched<LANES> {
     }
 }
 
+impl<const LANES: usize> TryFrom<Batched<LANES>> for i64 {
+    type Error = RuntimeError;
+
+    /**
+     * Returns the value in every lane, if they're all equal.
+     */
+    fn try_from(val: Batched<LANES>) -> RuntimeResult<Self> {
+        let first = val.data[0].first().copied().unwrap_or_default();
+
+        if val.data.iter().flatten().all(|x| *x == first) {
+            Ok(first)
+        } else {
+            Err(RuntimeError::fhe_type_error("Batched lanes aren't equal"))
+        }
+    }
+}
+
+impl<const LANES: usize> Add<i64> for Batched<LANES> {
+    type Output = Self;
+
+    fn add(self, rhs: i64) -> Self::Output {
+        self + Self::from(rhs)
+    }
+}
+
+impl<const LANES: usize> Sub<i64> for Batched<LANES> {
+    type Output = Self;
+
+    fn sub(self, rhs: i64) -> Self::Output {
+        self - Self::from(rhs)
+    }
+}
+
+impl<const LANES: usize> Mul<i64> for Batched<LANES> {
+    type Output = Self;
+
+    fn mul(self, rhs: i64) -> Self::Output {
+        self * Self::from(rhs)
+    }
+}
+
 impl<const LANES: usize> Neg for Batched<LANES> {
     type Output = Self;
 
@@ -482,6 +570,18 @@ impl<const LANES: usize> Shr<u64> for Batched<LANES> {
     }
 }
 
+impl<const LANES: usize> Rotate for Batched<LANES> {
+    type Output = Self;
+
+    fn rotate_left(self, n: u64) -> Self::Output {
+        self << (n % LANES as u64)
+    }
+
+    fn rotate_right(self, n: u64) -> Self::Output {
+        self >> (n % LANES as u64)
+    }
+}
+
 impl<const LANES: usize> SwapRows for Batched<LANES> {
     type Output = Self;
 
@@ -492,6 +592,14 @@ impl<const LANES: usize> SwapRows for Batched<LANES> {
     }
 }
 
+impl<const LANES: usize> FoldColumns for Batched<LANES> {
+    type Output = Self;
+
+    fn fold_columns(self) -> Self::Output {
+        self + self.swap_rows()
+    }
+}
+
 impl<const LANES: usize> Index<(usize, usize)> for Batched<LANES> {
     type Output = i64;
 
@@ -522,6 +630,40 @@ impl<const LANES: usize> GraphCipherAdd for Batched<LANES> {
     }
 }
 
+impl<const LANES: usize> GraphCipherPlainAdd for Batched<LANES> {
+    type Left = Self;
+    type Right = Self;
+
+    fn graph_cipher_plain_add(
+        a: FheProgramNode<Cipher<Self::Left>>,
+        b: FheProgramNode<Self::Right>,
+    ) -> FheProgramNode<Cipher<Self::Left>> {
+        with_fhe_ctx(|ctx| {
+            let n = ctx.add_addition_plaintext(a.ids[0], b.ids[0]);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
+impl<const LANES: usize> GraphCipherConstAdd for Batched<LANES> {
+    type Left = Self;
+    type Right = i64;
+
+    fn graph_cipher_const_add(
+        a: FheProgramNode<Cipher<Self::Left>>,
+        b: Self::Right,
+    ) -> FheProgramNode<Cipher<Self::Left>> {
+        with_fhe_ctx(|ctx| {
+            let b = Self::from(b).try_into_plaintext(&ctx.data).unwrap();
+            let l = ctx.add_plaintext_literal(b.inner);
+            let n = ctx.add_addition_plaintext(a.ids[0], l);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
 impl<const LANES: usize> GraphCipherSub for Batched<LANES> {
     type Left = Self;
     type Right = Self;
@@ -538,6 +680,76 @@ impl<const LANES: usize> GraphCipherSub for Batched<LANES> {
     }
 }
 
+impl<const LANES: usize> GraphCipherConstSub for Batched<LANES> {
+    type Left = Self;
+    type Right = i64;
+
+    fn graph_cipher_const_sub(
+        a: FheProgramNode<Cipher<Self::Left>>,
+        b: Self::Right,
+    ) -> FheProgramNode<Cipher<Self::Left>> {
+        with_fhe_ctx(|ctx| {
+            let b = Self::from(b).try_into_plaintext(&ctx.data).unwrap();
+            let l = ctx.add_plaintext_literal(b.inner);
+            let n = ctx.add_subtraction_plaintext(a.ids[0], l);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
+impl<const LANES: usize> GraphCipherPlainSub for Batched<LANES> {
+    type Left = Self;
+    type Right = Self;
+
+    fn graph_cipher_plain_sub(
+        a: FheProgramNode<Cipher<Self::Left>>,
+        b: FheProgramNode<Self::Right>,
+    ) -> FheProgramNode<Cipher<Self::Left>> {
+        with_fhe_ctx(|ctx| {
+            let n = ctx.add_subtraction_plaintext(a.ids[0], b.ids[0]);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
+impl<const LANES: usize> GraphPlainCipherSub for Batched<LANES> {
+    type Left = Self;
+    type Right = Self;
+
+    fn graph_plain_cipher_sub(
+        a: FheProgramNode<Self::Left>,
+        b: FheProgramNode<Cipher<Self::Right>>,
+    ) -> FheProgramNode<Cipher<Self::Left>> {
+        with_fhe_ctx(|ctx| {
+            let n = ctx.add_subtraction_plaintext(b.ids[0], a.ids[0]);
+            let n = ctx.add_negate(n);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
+impl<const LANES: usize> GraphConstCipherSub for Batched<LANES> {
+    type Left = i64;
+    type Right = Self;
+
+    fn graph_const_cipher_sub(
+        a: Self::Left,
+        b: FheProgramNode<Cipher<Self::Right>>,
+    ) -> FheProgramNode<Cipher<Self::Right>> {
+        with_fhe_ctx(|ctx| {
+            let a = Self::from(a).try_into_plaintext(&ctx.data).unwrap();
+            let l = ctx.add_plaintext_literal(a.inner);
+            let n = ctx.add_subtraction_plaintext(b.ids[0], l);
+            let n = ctx.add_negate(n);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
 impl<const LANES: usize> GraphCipherMul for Batched<LANES> {
     type Left = Self;
     type Right = Self;
@@ -554,6 +766,22 @@ impl<const LANES: usize> GraphCipherMul for Batched<LANES> {
     }
 }
 
+impl<const LANES: usize> GraphCipherPlainMul for Batched<LANES> {
+    type Left = Self;
+    type Right = Self;
+
+    fn graph_cipher_plain_mul(
+        a: FheProgramNode<Cipher<Self::Left>>,
+        b: FheProgramNode<Self::Right>,
+    ) -> FheProgramNode<Cipher<Self::Left>> {
+        with_fhe_ctx(|ctx| {
+            let n = ctx.add_multiplication_plaintext(a.ids[0], b.ids[0]);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
 impl<const LANES: usize> GraphCipherConstMul for Batched<LANES> {
     type Left = Self;
     type Right = i64;
@@ -658,6 +886,23 @@ mod tests {
         assert_eq!(x, y);
     }
 
+    #[test]
+    fn can_pad_batched() {
+        use seal_fhe::Reduction;
+
+        let x = Batched::<4>::try_from_padded([vec![1, 2], vec![5]], Padding::RepeatLast).unwrap();
+        assert_eq!(x, [[1, 2, 2, 2], [5, 5, 5, 5]].into());
+
+        let x = Batched::<4>::try_from_padded(
+            [vec![1, 2], vec![]],
+            Padding::NeutralElement(Reduction::Product),
+        )
+        .unwrap();
+        assert_eq!(x, [[1, 2, 1, 1], [1, 1, 1, 1]].into());
+
+        assert!(Batched::<4>::try_from_padded([vec![0; 5], vec![]], Padding::ZeroPad).is_err());
+    }
+
     const A_VEC: [[i64; 4]; 2] = [[1, 2, 3, 4], [5, 6, 7, 8]];
     const B_VEC: [[i64; 4]; 2] = [[5, 6, 7, 8], [1, 2, 3, 4]];
 
@@ -706,10 +951,27 @@ mod tests {
         assert_eq!(a >> 3, [[2, 3, 4, 1], [6, 7, 8, 5]].into());
     }
 
+    #[test]
+    fn can_rotate_non_fhe() {
+        let a = Batched::<4>::try_from(A_VEC).unwrap();
+
+        assert_eq!(a.rotate_left(3), a << 3);
+        assert_eq!(a.rotate_right(3), a >> 3);
+        assert_eq!(a.rotate_left(5), a << 1);
+        assert_eq!(a.rotate_right(4), a);
+    }
+
     #[test]
     fn can_swap_rows_non_fhe() {
         let a = Batched::<4>::try_from(A_VEC).unwrap();
 
         assert_eq!(a.swap_rows(), [[5, 6, 7, 8], [1, 2, 3, 4]].into());
     }
+
+    #[test]
+    fn can_fold_columns_non_fhe() {
+        let a = Batched::<4>::try_from(A_VEC).unwrap();
+
+        assert_eq!(a.fold_columns(), [[6, 8, 10, 12], [6, 8, 10, 12]].into());
+    }
 }
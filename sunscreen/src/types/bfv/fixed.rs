@@ -0,0 +1,704 @@
+use seal_fhe::Plaintext as SealPlaintext;
+
+use crate::{
+    fhe::{with_fhe_ctx, FheContextOps},
+    types::{
+        bfv::Fractional,
+        ops::{
+            AtLeast, GraphCipherAdd, GraphCipherCast, GraphCipherConstAdd, GraphCipherConstDiv,
+            GraphCipherConstMul, GraphCipherConstSub, GraphCipherMul, GraphCipherNeg,
+            GraphCipherPlainAdd, GraphCipherPlainMul, GraphCipherPlainSub, GraphCipherSub,
+            GraphConstCipherSub, GraphPlainCipherSub,
+        },
+        Cipher,
+    },
+};
+use crate::{
+    types::{intern::FheProgramNode, BfvType, FheType, Type, Version},
+    FheProgramInputTrait, Params, WithContext,
+};
+
+use sunscreen_runtime::{
+    InnerPlaintext, NumCiphertexts, Plaintext, TryFromJson, TryFromPlaintext, TryIntoPlaintext,
+    TypeName, TypeNameInstance,
+};
+
+use std::ops::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/**
+ * A signed fixed-point value with `FRAC_BITS` fractional bits, stored as
+ * the integer `raw = value * 2^FRAC_BITS`.
+ *
+ * # Remarks
+ * Unlike [`Fractional`](crate::types::bfv::Fractional), values are exact
+ * multiples of `2^-FRAC_BITS` rather than [`f64`]s, so sums and products
+ * of amounts such as prices round the same way on every machine. Like
+ * [`Fractional`](crate::types::bfv::Fractional), addition, subtraction and
+ * multiplication each take exactly one operation.
+ *
+ * ## Representation
+ * Each binary digit of `raw` maps to one plaintext coefficient, with digit
+ * `k` standing for `2^(k - FRAC_BITS)`. Digits with non-negative powers go
+ * in the low coefficients. Fractional digits use the ring's negative powers
+ * `x^-p = -x^(N-p)`, i.e. they go in the top coefficients with their sign
+ * inverted, where `N` is the `poly_degree`.
+ *
+ * Multiplying two values multiplies these polynomials, so the product's
+ * fractional digits land `2 * FRAC_BITS` places down without any explicit
+ * rescaling in the FHE program. Decoding reads coefficients in the lower
+ * half of the polynomial as integer digits and those in the upper half as
+ * fractional digits, then rounds the result to the nearest multiple of
+ * `2^-FRAC_BITS`, ties away from zero. This is the rounding
+ * [`Mul`] applies to unencrypted values.
+ *
+ * ## Limitations
+ * * Each multiplication adds `FRAC_BITS` fractional digits. Once the
+ * deepest reaches `N / 2` places, it wraps into the integer digits and
+ * garbles the value.
+ * * As with [`Signed`](crate::types::bfv::Signed), digits don't carry, so
+ * any coefficient exceeding the `plain_modulus` garbles the value.
+ * * An encrypted product rounds once, on decryption, while chained
+ * unencrypted multiplications round after each one, so the two can differ
+ * in the last bit.
+ * * Decryption fails if the value exceeds the range of `raw`.
+ */
+pub struct Fixed<const FRAC_BITS: usize> {
+    raw: i64,
+}
+
+impl<const FRAC_BITS: usize> Fixed<FRAC_BITS> {
+    /**
+     * Creates a value from its scaled representation, `value *
+     * 2^FRAC_BITS`.
+     */
+    pub fn from_raw(raw: i64) -> Self {
+        Self { raw }
+    }
+
+    /**
+     * Returns the scaled representation, `value * 2^FRAC_BITS`.
+     */
+    pub fn raw(&self) -> i64 {
+        self.raw
+    }
+
+    fn scale() -> f64 {
+        (FRAC_BITS as f64).exp2()
+    }
+}
+
+/**
+ * Divides `x` by `2^bits`, rounding ties away from zero.
+ */
+fn round_shift(x: i128, bits: usize) -> i128 {
+    if bits == 0 {
+        return x;
+    }
+
+    let half = 1i128 << (bits - 1);
+
+    if x >= 0 {
+        (x + half) >> bits
+    } else {
+        -((half - x) >> bits)
+    }
+}
+
+impl<const FRAC_BITS: usize> NumCiphertexts for Fixed<FRAC_BITS> {
+    const NUM_CIPHERTEXTS: usize = 1;
+}
+
+impl<const FRAC_BITS: usize> FheProgramInputTrait for Fixed<FRAC_BITS> {}
+
+impl<const FRAC_BITS: usize> Default for Fixed<FRAC_BITS> {
+    fn default() -> Self {
+        Self::from_raw(0)
+    }
+}
+
+impl<const FRAC_BITS: usize> TypeName for Fixed<FRAC_BITS> {
+    fn type_name() -> Type {
+        let version = env!("CARGO_PKG_VERSION");
+
+        Type {
+            name: format!("sunscreen::types::Fixed<{}>", FRAC_BITS),
+            version: Version::parse(version).expect("Crate version is not a valid semver"),
+            is_encrypted: false,
+        }
+    }
+}
+
+impl<const FRAC_BITS: usize> TypeNameInstance for Fixed<FRAC_BITS> {
+    fn type_name_instance(&self) -> Type {
+        Self::type_name()
+    }
+}
+
+impl<const FRAC_BITS: usize> FheType for Fixed<FRAC_BITS> {}
+impl<const FRAC_BITS: usize> BfvType for Fixed<FRAC_BITS> {}
+
+impl<const FRAC_BITS: usize> GraphCipherAdd for Fixed<FRAC_BITS> {
+    type Left = Fixed<FRAC_BITS>;
+    type Right = Fixed<FRAC_BITS>;
+
+    fn graph_cipher_add(
+        a: FheProgramNode<Cipher<Self::Left>>,
+        b: FheProgramNode<Cipher<Self::Right>>,
+    ) -> FheProgramNode<Cipher<Self::Left>> {
+        with_fhe_ctx(|ctx| {
+            let n = ctx.add_addition(a.ids[0], b.ids[0]);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
+impl<const FRAC_BITS: usize> GraphCipherPlainAdd for Fixed<FRAC_BITS> {
+    type Left = Fixed<FRAC_BITS>;
+    type Right = Fixed<FRAC_BITS>;
+
+    fn graph_cipher_plain_add(
+        a: FheProgramNode<Cipher<Self::Left>>,
+        b: FheProgramNode<Self::Right>,
+    ) -> FheProgramNode<Cipher<Self::Left>> {
+        with_fhe_ctx(|ctx| {
+            let n = ctx.add_addition_plaintext(a.ids[0], b.ids[0]);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
+impl<const FRAC_BITS: usize> GraphCipherConstAdd for Fixed<FRAC_BITS> {
+    type Left = Fixed<FRAC_BITS>;
+    type Right = f64;
+
+    fn graph_cipher_const_add(
+        a: FheProgramNode<Cipher<Self::Left>>,
+        b: Self::Right,
+    ) -> FheProgramNode<Cipher<Self::Left>> {
+        with_fhe_ctx(|ctx| {
+            let b = Self::from(b).try_into_plaintext(&ctx.data).unwrap();
+
+            let lit = ctx.add_plaintext_literal(b.inner);
+            let n = ctx.add_addition_plaintext(a.ids[0], lit);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
+impl<const FRAC_BITS: usize> GraphCipherSub for Fixed<FRAC_BITS> {
+    type Left = Fixed<FRAC_BITS>;
+    type Right = Fixed<FRAC_BITS>;
+
+    fn graph_cipher_sub(
+        a: FheProgramNode<Cipher<Self::Left>>,
+        b: FheProgramNode<Cipher<Self::Right>>,
+    ) -> FheProgramNode<Cipher<Self::Left>> {
+        with_fhe_ctx(|ctx| {
+            let n = ctx.add_subtraction(a.ids[0], b.ids[0]);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
+impl<const FRAC_BITS: usize> GraphCipherPlainSub for Fixed<FRAC_BITS> {
+    type Left = Fixed<FRAC_BITS>;
+    type Right = Fixed<FRAC_BITS>;
+
+    fn graph_cipher_plain_sub(
+        a: FheProgramNode<Cipher<Self::Left>>,
+        b: FheProgramNode<Self::Right>,
+    ) -> FheProgramNode<Cipher<Self::Left>> {
+        with_fhe_ctx(|ctx| {
+            let n = ctx.add_subtraction_plaintext(a.ids[0], b.ids[0]);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
+impl<const FRAC_BITS: usize> GraphPlainCipherSub for Fixed<FRAC_BITS> {
+    type Left = Fixed<FRAC_BITS>;
+    type Right = Fixed<FRAC_BITS>;
+
+    fn graph_plain_cipher_sub(
+        a: FheProgramNode<Self::Left>,
+        b: FheProgramNode<Cipher<Self::Right>>,
+    ) -> FheProgramNode<Cipher<Self::Left>> {
+        with_fhe_ctx(|ctx| {
+            let n = ctx.add_subtraction_plaintext(b.ids[0], a.ids[0]);
+            let n = ctx.add_negate(n);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
+impl<const FRAC_BITS: usize> GraphCipherConstSub for Fixed<FRAC_BITS> {
+    type Left = Fixed<FRAC_BITS>;
+    type Right = f64;
+
+    fn graph_cipher_const_sub(
+        a: FheProgramNode<Cipher<Self::Left>>,
+        b: Self::Right,
+    ) -> FheProgramNode<Cipher<Self::Left>> {
+        with_fhe_ctx(|ctx| {
+            let b = Self::from(b).try_into_plaintext(&ctx.data).unwrap();
+
+            let lit = ctx.add_plaintext_literal(b.inner);
+            let n = ctx.add_subtraction_plaintext(a.ids[0], lit);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
+impl<const FRAC_BITS: usize> GraphConstCipherSub for Fixed<FRAC_BITS> {
+    type Left = f64;
+    type Right = Fixed<FRAC_BITS>;
+
+    fn graph_const_cipher_sub(
+        a: Self::Left,
+        b: FheProgramNode<Cipher<Self::Right>>,
+    ) -> FheProgramNode<Cipher<Self::Right>> {
+        with_fhe_ctx(|ctx| {
+            let a = Self::from(a).try_into_plaintext(&ctx.data).unwrap();
+
+            let lit = ctx.add_plaintext_literal(a.inner);
+            let n = ctx.add_subtraction_plaintext(b.ids[0], lit);
+            let n = ctx.add_negate(n);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
+impl<const FRAC_BITS: usize> GraphCipherMul for Fixed<FRAC_BITS> {
+    type Left = Fixed<FRAC_BITS>;
+    type Right = Fixed<FRAC_BITS>;
+
+    fn graph_cipher_mul(
+        a: FheProgramNode<Cipher<Self::Left>>,
+        b: FheProgramNode<Cipher<Self::Right>>,
+    ) -> FheProgramNode<Cipher<Self::Left>> {
+        with_fhe_ctx(|ctx| {
+            let n = ctx.add_multiplication(a.ids[0], b.ids[0]);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
+impl<const FRAC_BITS: usize> GraphCipherPlainMul for Fixed<FRAC_BITS> {
+    type Left = Fixed<FRAC_BITS>;
+    type Right = Fixed<FRAC_BITS>;
+
+    fn graph_cipher_plain_mul(
+        a: FheProgramNode<Cipher<Self::Left>>,
+        b: FheProgramNode<Self::Right>,
+    ) -> FheProgramNode<Cipher<Self::Left>> {
+        with_fhe_ctx(|ctx| {
+            let n = ctx.add_multiplication_plaintext(a.ids[0], b.ids[0]);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
+impl<const FRAC_BITS: usize> GraphCipherConstMul for Fixed<FRAC_BITS> {
+    type Left = Fixed<FRAC_BITS>;
+    type Right = f64;
+
+    fn graph_cipher_const_mul(
+        a: FheProgramNode<Cipher<Self::Left>>,
+        b: Self::Right,
+    ) -> FheProgramNode<Cipher<Self::Left>> {
+        with_fhe_ctx(|ctx| {
+            let b = Self::from(b).try_into_plaintext(&ctx.data).unwrap();
+            let lit = ctx.add_plaintext_literal(b.inner);
+
+            let n = ctx.add_multiplication_plaintext(a.ids[0], lit);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
+impl<const FRAC_BITS: usize> GraphCipherConstDiv for Fixed<FRAC_BITS> {
+    type Left = Fixed<FRAC_BITS>;
+    type Right = f64;
+
+    /**
+     * Multiplies by `1 / b` rounded to `FRAC_BITS` fractional bits, so the
+     * quotient is exact only when `1 / b` is representable.
+     */
+    fn graph_cipher_const_div(
+        a: FheProgramNode<Cipher<Self::Left>>,
+        b: f64,
+    ) -> FheProgramNode<Cipher<Self::Left>> {
+        with_fhe_ctx(|ctx| {
+            let b = Self::from(1. / b).try_into_plaintext(&ctx.data).unwrap();
+
+            let lit = ctx.add_plaintext_literal(b.inner);
+
+            let n = ctx.add_multiplication_plaintext(a.ids[0], lit);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
+impl<const FRAC_BITS: usize> GraphCipherNeg for Fixed<FRAC_BITS> {
+    type Val = Fixed<FRAC_BITS>;
+
+    fn graph_cipher_neg(a: FheProgramNode<Cipher<Self>>) -> FheProgramNode<Cipher<Self::Val>> {
+        with_fhe_ctx(|ctx| {
+            let n = ctx.add_negate(a.ids[0]);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
+impl<const FRAC_BITS: usize> TryIntoPlaintext for Fixed<FRAC_BITS> {
+    fn try_into_plaintext(
+        &self,
+        params: &Params,
+    ) -> std::result::Result<Plaintext, sunscreen_runtime::Error> {
+        let n = params.lattice_dimension as usize;
+
+        // Digits run from 2^-FRAC_BITS up to 2^(63 - FRAC_BITS).
+        if FRAC_BITS >= n / 2 || 63 - FRAC_BITS as i64 >= (n / 2) as i64 {
+            return Err(sunscreen_runtime::Error::fhe_type_error(
+                "FRAC_BITS doesn't fit the lattice dimension.",
+            ));
+        }
+
+        let mut seal_plaintext = SealPlaintext::new()?;
+        seal_plaintext.resize(n);
+
+        let negative = self.raw < 0;
+        let magnitude = self.raw.unsigned_abs();
+
+        for k in 0..64 {
+            if (magnitude >> k) & 0x1 == 0 {
+                continue;
+            }
+
+            let power = k as i64 - FRAC_BITS as i64;
+
+            let coeff_index = if power >= 0 {
+                power as usize
+            } else {
+                (n as i64 + power) as usize
+            };
+
+            // For powers less than 0, we invert the sign.
+            let coeff = if negative == (power >= 0) {
+                params.plain_modulus - 1
+            } else {
+                1
+            };
+
+            seal_plaintext.set_coefficient(coeff_index, coeff);
+        }
+
+        Ok(Plaintext {
+            data_type: self.type_name_instance(),
+            inner: InnerPlaintext::Seal(vec![WithContext {
+                params: params.clone(),
+                data: seal_plaintext,
+            }]),
+        })
+    }
+}
+
+impl<const FRAC_BITS: usize> TryFromPlaintext for Fixed<FRAC_BITS> {
+    fn try_from_plaintext(
+        plaintext: &Plaintext,
+        params: &Params,
+    ) -> std::result::Result<Self, sunscreen_runtime::Error> {
+        let out_of_range = || sunscreen_runtime::Error::fhe_type_error("Out of range");
+
+        let val = match &plaintext.inner {
+            InnerPlaintext::Seal(p) => {
+                if p.len() != 1 {
+                    return Err(sunscreen_runtime::Error::IncorrectCiphertextCount);
+                }
+
+                let n = params.lattice_dimension as usize;
+                let len = p[0].len();
+                let negative_cutoff = (params.plain_modulus + 1) / 2;
+
+                // Digits at or above 2^-FRAC_BITS sum exactly into the scaled
+                // value. Deeper digits only matter for rounding.
+                let mut raw = 0i128;
+                let mut remainder = 0f64;
+
+                for i in 0..usize::min(n, len) {
+                    let coeff = p[0].get_coefficient(i);
+
+                    if coeff == 0 {
+                        continue;
+                    }
+
+                    let coeff = if coeff < negative_cutoff {
+                        coeff as i128
+                    } else {
+                        -((params.plain_modulus - coeff) as i128)
+                    };
+
+                    // Reverse the sign of negative powers.
+                    let (power, coeff) = if i < n / 2 {
+                        (i as i64, coeff)
+                    } else {
+                        (i as i64 - n as i64, -coeff)
+                    };
+
+                    let shift = power + FRAC_BITS as i64;
+
+                    if shift >= 64 {
+                        return Err(out_of_range());
+                    } else if shift >= 0 {
+                        raw = raw.checked_add(coeff << shift).ok_or_else(out_of_range)?;
+                    } else {
+                        remainder += coeff as f64 * (shift as f64).exp2();
+                    }
+                }
+
+                let whole = raw
+                    .checked_add(remainder.floor() as i128)
+                    .ok_or_else(out_of_range)?;
+                let frac = remainder - remainder.floor();
+
+                // Round to nearest, ties away from zero, to match Mul.
+                let raw = if frac > 0.5 || (frac == 0.5 && whole >= 0) {
+                    whole + 1
+                } else {
+                    whole
+                };
+
+                Self {
+                    raw: i64::try_from(raw).map_err(|_| out_of_range())?,
+                }
+            }
+        };
+
+        Ok(val)
+    }
+}
+
+impl<const FRAC_BITS: usize> TryFromJson for Fixed<FRAC_BITS> {
+    fn try_from_json(
+        value: &serde_json::Value,
+    ) -> std::result::Result<Self, sunscreen_runtime::Error> {
+        value
+            .as_f64()
+            .map(Self::from)
+            .ok_or_else(|| sunscreen_runtime::Error::fhe_type_error("Expected a JSON number"))
+    }
+}
+
+impl<const FRAC_BITS: usize> From<f64> for Fixed<FRAC_BITS> {
+    /**
+     * Rounds `val` to the nearest multiple of `2^-FRAC_BITS`, saturating
+     * at the bounds of the scaled representation.
+     */
+    fn from(val: f64) -> Self {
+        Self {
+            raw: (val * Self::scale()).round() as i64,
+        }
+    }
+}
+
+impl<const FRAC_BITS: usize> From<Fixed<FRAC_BITS>> for f64 {
+    fn from(val: Fixed<FRAC_BITS>) -> Self {
+        val.raw as f64 / Fixed::<FRAC_BITS>::scale()
+    }
+}
+
+impl<const FRAC_BITS: usize> Add for Fixed<FRAC_BITS> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            raw: self.raw + rhs.raw,
+        }
+    }
+}
+
+impl<const FRAC_BITS: usize> Sub for Fixed<FRAC_BITS> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self {
+            raw: self.raw - rhs.raw,
+        }
+    }
+}
+
+impl<const FRAC_BITS: usize> Mul for Fixed<FRAC_BITS> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let product = self.raw as i128 * rhs.raw as i128;
+
+        Self {
+            raw: round_shift(product, FRAC_BITS) as i64,
+        }
+    }
+}
+
+impl<const FRAC_BITS: usize> Neg for Fixed<FRAC_BITS> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self { raw: -self.raw }
+    }
+}
+
+impl<const FRAC_BITS: usize, const TO_FRAC_BITS: usize> GraphCipherCast<Fixed<TO_FRAC_BITS>>
+    for Fixed<FRAC_BITS>
+{
+    fn graph_cipher_cast(
+        x: FheProgramNode<Cipher<Self>>,
+    ) -> FheProgramNode<Cipher<Fixed<TO_FRAC_BITS>>> {
+        // Decoding with fewer fractional bits would round digits away.
+        #[allow(clippy::let_unit_value)]
+        let () = AtLeast::<TO_FRAC_BITS, 0, FRAC_BITS>::OK;
+
+        FheProgramNode::new(x.ids)
+    }
+}
+
+impl<const FRAC_BITS: usize, const INT_BITS: usize> GraphCipherCast<Fractional<INT_BITS>>
+    for Fixed<FRAC_BITS>
+{
+    fn graph_cipher_cast(
+        x: FheProgramNode<Cipher<Self>>,
+    ) -> FheProgramNode<Cipher<Fractional<INT_BITS>>> {
+        // Our integer part has at most 64 - FRAC_BITS digits.
+        #[allow(clippy::let_unit_value)]
+        let () = AtLeast::<INT_BITS, FRAC_BITS, 64>::OK;
+
+        FheProgramNode::new(x.ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SchemeType, SecurityLevel};
+
+    fn params() -> Params {
+        Params {
+            lattice_dimension: 4096,
+            plain_modulus: 1_000_000,
+            coeff_modulus: vec![],
+            scheme_type: SchemeType::Bfv,
+            security_level: SecurityLevel::TC128,
+        }
+    }
+
+    #[test]
+    fn can_encode_decode_fixed() {
+        let round_trip = |x: f64| {
+            let f_1 = Fixed::<16>::from(x);
+            let pt = f_1.try_into_plaintext(&params()).unwrap();
+            let f_2 = Fixed::<16>::try_from_plaintext(&pt, &params()).unwrap();
+
+            assert_eq!(f_1, f_2);
+        };
+
+        round_trip(0.0);
+        round_trip(1.0);
+        round_trip(5.8125);
+        round_trip(19.99);
+        round_trip(1e9);
+        round_trip(-1.0);
+        round_trip(-5.8125);
+        round_trip(-19.99);
+        round_trip(-1e9);
+    }
+
+    #[test]
+    fn polynomial_product_matches_rescaled_mul() {
+        let a = Fixed::<16>::from(19.99);
+        let b = Fixed::<16>::from(-3.75);
+
+        let a_p = a.try_into_plaintext(&params()).unwrap();
+        let b_p = b.try_into_plaintext(&params()).unwrap();
+
+        let n = 4096;
+        let t = 1_000_000u64;
+
+        let nonzero = |p: &Plaintext| match &p.inner {
+            InnerPlaintext::Seal(p) => (0..n)
+                .map(|i| (i, p[0].data.get_coefficient(i)))
+                .filter(|(_, c)| *c != 0)
+                .collect::<Vec<_>>(),
+        };
+
+        // Multiply the polynomials mod (x^n + 1, plain_modulus) as the
+        // evaluator would.
+        let mut product = vec![0u64; n];
+
+        for (i, x) in nonzero(&a_p) {
+            for (j, y) in nonzero(&b_p) {
+                let c = x * y % t;
+                let k = (i + j) % n;
+
+                product[k] = if i + j < n {
+                    (product[k] + c) % t
+                } else {
+                    (product[k] + t - c) % t
+                };
+            }
+        }
+
+        let mut seal_plaintext = SealPlaintext::new().unwrap();
+        seal_plaintext.resize(n);
+
+        for (i, c) in product.into_iter().enumerate() {
+            seal_plaintext.set_coefficient(i, c);
+        }
+
+        let product = Plaintext {
+            data_type: Fixed::<16>::type_name(),
+            inner: InnerPlaintext::Seal(vec![WithContext {
+                params: params(),
+                data: seal_plaintext,
+            }]),
+        };
+
+        assert_eq!(
+            Fixed::<16>::try_from_plaintext(&product, &params()).unwrap(),
+            a * b
+        );
+    }
+
+    #[test]
+    fn can_mul_non_fhe() {
+        let a = Fixed::<8>::from_raw(3);
+        let b = Fixed::<8>::from_raw(128);
+
+        // 3/256 * 1/2 = 1.5/256 rounds away from zero.
+        assert_eq!((a * b).raw(), 2);
+        assert_eq!((-a * b).raw(), -2);
+        assert_eq!(f64::from(Fixed::<8>::from(2.5) * Fixed::from(4.0)), 10.0);
+    }
+
+    #[test]
+    fn can_add_sub_neg_non_fhe() {
+        let a = Fixed::<16>::from(19.99);
+        let b = Fixed::<16>::from(0.01);
+
+        assert_eq!((a + b).raw(), a.raw() + b.raw());
+        assert_eq!((a - b).raw(), a.raw() - b.raw());
+        assert_eq!((-a).raw(), -a.raw());
+    }
+}
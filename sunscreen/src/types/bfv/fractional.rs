@@ -4,10 +4,10 @@ use crate::{
     fhe::{with_fhe_ctx, FheContextOps},
     types::{
         ops::{
-            GraphCipherAdd, GraphCipherConstAdd, GraphCipherConstDiv, GraphCipherConstMul,
-            GraphCipherConstSub, GraphCipherMul, GraphCipherNeg, GraphCipherPlainAdd,
-            GraphCipherPlainMul, GraphCipherPlainSub, GraphCipherSub, GraphConstCipherSub,
-            GraphPlainCipherSub,
+            AtLeast, GraphCipherAdd, GraphCipherCast, GraphCipherConstAdd, GraphCipherConstDiv,
+            GraphCipherConstMul, GraphCipherConstSub, GraphCipherMul, GraphCipherNeg,
+            GraphCipherPlainAdd, GraphCipherPlainMul, GraphCipherPlainSub, GraphCipherSub,
+            GraphConstCipherSub, GraphPlainCipherSub,
         },
         Cipher,
     },
@@ -18,8 +18,8 @@ use crate::{
 };
 
 use sunscreen_runtime::{
-    InnerPlaintext, NumCiphertexts, Plaintext, TryFromPlaintext, TryIntoPlaintext, TypeName,
-    TypeNameInstance,
+    InnerPlaintext, NumCiphertexts, Plaintext, TryFromJson, TryFromPlaintext, TryIntoPlaintext,
+    TypeName, TypeNameInstance,
 };
 
 use std::ops::*;
@@ -561,6 +561,17 @@ impl<const INT_BITS: usize> TryFromPlaintext for Fractional<INT_BITS> {
     }
 }
 
+impl<const INT_BITS: usize> TryFromJson for Fractional<INT_BITS> {
+    fn try_from_json(
+        value: &serde_json::Value,
+    ) -> std::result::Result<Self, sunscreen_runtime::Error> {
+        value
+            .as_f64()
+            .map(Self::from)
+            .ok_or_else(|| sunscreen_runtime::Error::fhe_type_error("Expected a JSON number"))
+    }
+}
+
 impl<const INT_BITS: usize> From<f64> for Fractional<INT_BITS> {
     fn from(val: f64) -> Self {
         Self { val }
@@ -681,6 +692,21 @@ impl<const INT_BITS: usize> Neg for Fractional<INT_BITS> {
     }
 }
 
+impl<const INT_BITS: usize, const TO_INT_BITS: usize> GraphCipherCast<Fractional<TO_INT_BITS>>
+    for Fractional<INT_BITS>
+{
+    fn graph_cipher_cast(
+        x: FheProgramNode<Cipher<Self>>,
+    ) -> FheProgramNode<Cipher<Fractional<TO_INT_BITS>>> {
+        // Both types share an encoding, but fewer integer bits would read
+        // our top integer digits as fractional ones.
+        #[allow(clippy::let_unit_value)]
+        let () = AtLeast::<TO_INT_BITS, 0, INT_BITS>::OK;
+
+        FheProgramNode::new(x.ids)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -0,0 +1,564 @@
+use crate::{
+    fhe::{with_fhe_ctx, FheContext, FheContextOps, Literal},
+    types::{
+        bfv::Batched,
+        intern::{Cipher, FheProgramNode, IntegerLiteral},
+        BfvType, FheType, NumCiphertexts, TryFromPlaintext, TryIntoPlaintext, Type, TypeName,
+        TypeNameInstance, Version,
+    },
+    FheProgramInputTrait, InnerPlaintext, Params, Plaintext, WithContext,
+};
+use petgraph::stable_graph::NodeIndex;
+use seal_fhe::{
+    BFVEncoder, BfvEncryptionParametersBuilder, Context as SealContext, Modulus,
+    Result as SealResult,
+};
+use std::ops::*;
+use sunscreen_runtime::{Error as RuntimeError, Result as RuntimeResult};
+
+/**
+ * Returns the side of the square matrix an `r x c` matrix pads to.
+ */
+const fn padded_dim(r: usize, c: usize) -> usize {
+    let d = if r > c { r } else { c };
+
+    d.next_power_of_two()
+}
+
+/**
+ * An `R x C` matrix of signed integers that multiplies vectors packed into
+ * [`Batched`] values.
+ *
+ * # Remarks
+ * A vector of length `C` is a [`Batched`] value holding its entries in the
+ * first `C` lanes of a row and zero in the rest. Each row holds a separate
+ * vector, so one multiplication computes 2 products. The result holds the
+ * `R` entries of each product in the first `R` lanes and zero in the rest,
+ * so it can feed another multiplication directly.
+ *
+ * Multiplying a matrix by a [`Cipher<Batched>`](Cipher) vector uses the
+ * diagonal method of Halevi and Shoup. The matrix pads to a `D x D` square,
+ * where `D` is `max(R, C)` rounded up to a power of 2, which must not
+ * exceed the vector's `LANES`. The FHE program first tiles the vector
+ * across all `LANES` with `log2(LANES / D)` rotations, then multiplies
+ * each rotation `x << i` by the matrix's `i`th diagonal and sums the
+ * products. The result takes at most `D - 1 + log2(LANES / D)` rotations,
+ * `D` multiplications and `D - 1` additions. Multiplying a matrix by an
+ * array of vectors multiplies it by each, so storing a matrix's columns in
+ * the vectors computes a matrix-matrix product.
+ *
+ * The compiler records every rotation amount, so
+ * [`Application::generate_minimal_galois_keys`](crate::Application::generate_minimal_galois_keys)
+ * creates exactly the Galois keys these products need.
+ *
+ * Matrices come in 3 forms:
+ * * A `Matrix` value in an FHE program is a constant, such as model
+ * weights. Its diagonals become plaintext literals and all-zero diagonals
+ * are skipped.
+ * * A `Matrix` argument is a plaintext input.
+ * * A `Cipher<Matrix>` argument is an encrypted input. Each diagonal is a
+ * ciphertext, so the product multiplies ciphertexts and its noise grows
+ * accordingly.
+ *
+ * Inputs encode as `D` plaintexts (or ciphertexts), one per diagonal, and
+ * their products take 1 more plaintext multiplication to clear the lanes
+ * after the first `R`.
+ *
+ * # Limitations
+ * * The vector's lanes after the first `C` must be zero. Otherwise, the
+ * product is garbage.
+ * * As with [`Batched`], entries and intermediate sums wrap around the
+ * `plain_modulus`.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Matrix<const R: usize, const C: usize> {
+    data: [[i64; C]; R],
+}
+
+impl<const R: usize, const C: usize> Matrix<R, C> {
+    const DIM: usize = padded_dim(R, C);
+
+    /**
+     * Returns the `i`th diagonal of the matrix padded to `DIM x DIM`, i.e.
+     * the entries `(j, (j + i) % DIM)`.
+     */
+    fn diagonal(&self, i: usize) -> Vec<i64> {
+        (0..Self::DIM)
+            .map(|j| {
+                let k = (j + i) % Self::DIM;
+
+                if j < R && k < C {
+                    self.data[j][k]
+                } else {
+                    0
+                }
+            })
+            .collect()
+    }
+
+    /**
+     * Returns the transpose of this matrix.
+     */
+    pub fn transpose(&self) -> Matrix<C, R> {
+        let mut data = [[0; R]; C];
+
+        for (i, row) in self.data.iter().enumerate() {
+            for (j, x) in row.iter().enumerate() {
+                data[j][i] = *x;
+            }
+        }
+
+        Matrix { data }
+    }
+}
+
+impl<const R: usize, const C: usize> Default for Matrix<R, C> {
+    fn default() -> Self {
+        Self { data: [[0; C]; R] }
+    }
+}
+
+impl<const R: usize, const C: usize> From<[[i64; C]; R]> for Matrix<R, C> {
+    fn from(data: [[i64; C]; R]) -> Self {
+        Self { data }
+    }
+}
+
+impl<const R: usize, const C: usize> From<Matrix<R, C>> for [[i64; C]; R] {
+    fn from(m: Matrix<R, C>) -> Self {
+        m.data
+    }
+}
+
+impl<const R: usize, const C: usize> Index<(usize, usize)> for Matrix<R, C> {
+    type Output = i64;
+
+    fn index(&self, (row, col): (usize, usize)) -> &Self::Output {
+        &self.data[row][col]
+    }
+}
+
+impl<const R: usize, const C: usize> NumCiphertexts for Matrix<R, C> {
+    const NUM_CIPHERTEXTS: usize = padded_dim(R, C);
+}
+
+impl<const R: usize, const C: usize> TypeName for Matrix<R, C> {
+    fn type_name() -> Type {
+        let version = env!("CARGO_PKG_VERSION");
+
+        Type {
+            name: format!("sunscreen::types::Matrix<{}, {}>", R, C),
+            version: Version::parse(version).expect("Crate version is not a valid semver"),
+            is_encrypted: false,
+        }
+    }
+}
+
+impl<const R: usize, const C: usize> TypeNameInstance for Matrix<R, C> {
+    fn type_name_instance(&self) -> Type {
+        Self::type_name()
+    }
+}
+
+impl<const R: usize, const C: usize> FheProgramInputTrait for Matrix<R, C> {}
+impl<const R: usize, const C: usize> FheType for Matrix<R, C> {}
+impl<const R: usize, const C: usize> BfvType for Matrix<R, C> {}
+
+fn encoder(params: &Params) -> RuntimeResult<BFVEncoder> {
+    let encryption_params = BfvEncryptionParametersBuilder::new()
+        .set_poly_modulus_degree(params.lattice_dimension)
+        .set_plain_modulus(Modulus::new(params.plain_modulus)?)
+        .set_coefficient_modulus(
+            params
+                .coeff_modulus
+                .iter()
+                .map(|x| Modulus::new(*x))
+                .collect::<SealResult<Vec<Modulus>>>()?,
+        )
+        .build()?;
+
+    let context = SealContext::new(&encryption_params, false, params.security_level)?;
+
+    Ok(BFVEncoder::new(&context)?)
+}
+
+impl<const R: usize, const C: usize> TryIntoPlaintext for Matrix<R, C> {
+    fn try_into_plaintext(
+        &self,
+        params: &Params,
+    ) -> std::result::Result<Plaintext, sunscreen_runtime::Error> {
+        if 2 * Self::DIM > params.lattice_dimension as usize {
+            return Err(RuntimeError::fhe_type_error(
+                "Matrix dimensions must be <= polynomial degree / 2",
+            ));
+        }
+
+        let encoder = encoder(params)?;
+        let reps = params.lattice_dimension as usize / (2 * Self::DIM);
+
+        // Repeat each diagonal with period DIM, so rotations of any vector
+        // whose LANES is a multiple of DIM line up with it.
+        let plaintexts = (0..Self::DIM)
+            .map(|i| {
+                let row = self.diagonal(i).repeat(reps);
+
+                let data = [row.clone(), row]
+                    .concat()
+                    .iter()
+                    .map(|x| x.to_plain_modulus(params.plain_modulus))
+                    .collect::<RuntimeResult<Vec<u64>>>()?;
+
+                Ok(WithContext {
+                    params: params.clone(),
+                    data: encoder.encode_unsigned(&data)?,
+                })
+            })
+            .collect::<RuntimeResult<Vec<_>>>()?;
+
+        Ok(Plaintext {
+            data_type: Self::type_name(),
+            inner: InnerPlaintext::Seal(plaintexts),
+        })
+    }
+}
+
+impl<const R: usize, const C: usize> TryFromPlaintext for Matrix<R, C> {
+    fn try_from_plaintext(
+        plaintext: &Plaintext,
+        params: &Params,
+    ) -> std::result::Result<Self, sunscreen_runtime::Error> {
+        let plaintext = plaintext.inner_as_seal_plaintext()?;
+
+        if plaintext.len() != Self::DIM {
+            return Err(RuntimeError::IncorrectCiphertextCount);
+        }
+
+        let encoder = encoder(params)?;
+        let mut data = [[0; C]; R];
+
+        for (i, p) in plaintext.iter().enumerate() {
+            if p.params != *params {
+                return Err(RuntimeError::ParameterMismatch);
+            }
+
+            let diagonal = encoder.decode_signed(&p.data)?;
+
+            for (j, row) in data.iter_mut().enumerate() {
+                let k = (j + i) % Self::DIM;
+
+                if k < C {
+                    row[k] = diagonal[j];
+                }
+            }
+        }
+
+        Ok(Self { data })
+    }
+}
+
+impl<const R: usize, const C: usize, const LANES: usize> Mul<Batched<LANES>> for Matrix<R, C> {
+    type Output = Batched<LANES>;
+
+    fn mul(self, x: Batched<LANES>) -> Self::Output {
+        assert!(
+            Self::DIM <= LANES,
+            "Matrix dimensions exceed the vector's LANES"
+        );
+
+        let x: [[i64; LANES]; 2] = x.into();
+        let mut y = [[0; LANES]; 2];
+
+        for (x_row, y_row) in x.iter().zip(y.iter_mut()) {
+            for (j, m_row) in self.data.iter().enumerate() {
+                y_row[j] = m_row
+                    .iter()
+                    .zip(x_row.iter())
+                    .map(|(a, b)| a.wrapping_mul(*b))
+                    .fold(0i64, |s, p| s.wrapping_add(p));
+            }
+        }
+
+        y.into()
+    }
+}
+
+impl<const R: usize, const C: usize, const K: usize> Mul<Matrix<C, K>> for Matrix<R, C> {
+    type Output = Matrix<R, K>;
+
+    fn mul(self, rhs: Matrix<C, K>) -> Self::Output {
+        let mut data = [[0; K]; R];
+
+        for (i, row) in data.iter_mut().enumerate() {
+            for (j, x) in row.iter_mut().enumerate() {
+                *x = (0..C)
+                    .map(|k| self.data[i][k].wrapping_mul(rhs.data[k][j]))
+                    .fold(0i64, |s, p| s.wrapping_add(p));
+            }
+        }
+
+        Matrix { data }
+    }
+}
+
+/**
+ * A diagonal of a matrix in an FHE program.
+ */
+#[derive(Clone, Copy)]
+enum Diagonal {
+    Plain(NodeIndex),
+    Cipher(NodeIndex),
+}
+
+/**
+ * Computes `sum_i diagonal_i * (x << i)` over `x` tiled with period `dim`,
+ * where `diagonals` lists the nonzero `(i, diagonal_i)`.
+ */
+fn rotate_multiply_accumulate(
+    ctx: &mut FheContext,
+    x: NodeIndex,
+    lanes: usize,
+    dim: usize,
+    diagonals: &[(usize, Diagonal)],
+) -> NodeIndex {
+    assert!(dim <= lanes, "Matrix dimensions exceed the vector's LANES");
+
+    // Lanes dim..LANES of x are zero, so each doubling step fills the next
+    // block with a copy of the vector.
+    let mut tiled = x;
+    let mut period = dim;
+
+    while period < lanes {
+        let amount = ctx.add_literal(Literal::U64(period as u64));
+        let rotated = ctx.add_rotate_right(tiled, amount);
+
+        tiled = ctx.add_addition(tiled, rotated);
+        period *= 2;
+    }
+
+    let mut sum = None;
+
+    for (i, diagonal) in diagonals {
+        let rotated = if *i == 0 {
+            tiled
+        } else {
+            let amount = ctx.add_literal(Literal::U64(*i as u64));
+
+            ctx.add_rotate_left(tiled, amount)
+        };
+
+        let product = match diagonal {
+            Diagonal::Plain(d) => ctx.add_multiplication_plaintext(rotated, *d),
+            Diagonal::Cipher(d) => ctx.add_multiplication(rotated, *d),
+        };
+
+        sum = Some(match sum {
+            Some(s) => ctx.add_addition(s, product),
+            None => product,
+        });
+    }
+
+    // A zero matrix yields a zero vector.
+    sum.unwrap_or_else(|| ctx.add_subtraction(x, x))
+}
+
+/**
+ * Multiplies `x` by a matrix input with `rows` rows, whose diagonals repeat
+ * with period `dim`, and clears the lanes the products fill beyond `rows`.
+ */
+fn mul_input<const LANES: usize>(
+    x: FheProgramNode<Cipher<Batched<LANES>>>,
+    rows: usize,
+    diagonals: &[Diagonal],
+) -> FheProgramNode<Cipher<Batched<LANES>>> {
+    with_fhe_ctx(|ctx| {
+        let dim = diagonals.len();
+        let diagonals = diagonals.iter().copied().enumerate().collect::<Vec<_>>();
+
+        let n = rotate_multiply_accumulate(ctx, x.ids[0], LANES, dim, &diagonals);
+
+        let n = if rows < LANES {
+            let mut mask = [0; LANES];
+
+            for x in mask.iter_mut().take(rows) {
+                *x = 1;
+            }
+
+            // It's okay to unwrap here because fhe_program compilation will
+            // catch the panic and return a compilation error.
+            let mask = Batched::<LANES>::from([mask; 2])
+                .try_into_plaintext(&ctx.data)
+                .unwrap();
+            let mask = ctx.add_plaintext_literal(mask.inner);
+
+            ctx.add_multiplication_plaintext(n, mask)
+        } else {
+            n
+        };
+
+        FheProgramNode::new(&[n])
+    })
+}
+
+impl<const R: usize, const C: usize, const LANES: usize> Mul<FheProgramNode<Cipher<Batched<LANES>>>>
+    for Matrix<R, C>
+{
+    type Output = FheProgramNode<Cipher<Batched<LANES>>>;
+
+    fn mul(self, x: FheProgramNode<Cipher<Batched<LANES>>>) -> Self::Output {
+        with_fhe_ctx(|ctx| {
+            let mut diagonals = vec![];
+
+            for i in 0..Self::DIM {
+                let diagonal = self.diagonal(i);
+
+                if diagonal.iter().all(|x| *x == 0) {
+                    continue;
+                }
+
+                // Zero the lanes after DIM so the products leave them zero.
+                let mut row = [0; LANES];
+                row.iter_mut().zip(diagonal).for_each(|(r, d)| *r = d);
+
+                // It's okay to unwrap here because fhe_program compilation
+                // will catch the panic and return a compilation error.
+                let d = Batched::<LANES>::from([row; 2])
+                    .try_into_plaintext(&ctx.data)
+                    .unwrap();
+
+                diagonals.push((i, Diagonal::Plain(ctx.add_plaintext_literal(d.inner))));
+            }
+
+            let n = rotate_multiply_accumulate(ctx, x.ids[0], LANES, Self::DIM, &diagonals);
+
+            FheProgramNode::new(&[n])
+        })
+    }
+}
+
+impl<const R: usize, const C: usize, const LANES: usize> Mul<FheProgramNode<Cipher<Batched<LANES>>>>
+    for FheProgramNode<Matrix<R, C>>
+{
+    type Output = FheProgramNode<Cipher<Batched<LANES>>>;
+
+    fn mul(self, x: FheProgramNode<Cipher<Batched<LANES>>>) -> Self::Output {
+        let diagonals = self
+            .ids
+            .iter()
+            .map(|d| Diagonal::Plain(*d))
+            .collect::<Vec<_>>();
+
+        mul_input(x, R, &diagonals)
+    }
+}
+
+impl<const R: usize, const C: usize, const LANES: usize> Mul<FheProgramNode<Cipher<Batched<LANES>>>>
+    for FheProgramNode<Cipher<Matrix<R, C>>>
+{
+    type Output = FheProgramNode<Cipher<Batched<LANES>>>;
+
+    fn mul(self, x: FheProgramNode<Cipher<Batched<LANES>>>) -> Self::Output {
+        let diagonals = self
+            .ids
+            .iter()
+            .map(|d| Diagonal::Cipher(*d))
+            .collect::<Vec<_>>();
+
+        mul_input(x, R, &diagonals)
+    }
+}
+
+impl<const R: usize, const C: usize, const LANES: usize, const K: usize>
+    Mul<[FheProgramNode<Cipher<Batched<LANES>>>; K]> for Matrix<R, C>
+{
+    type Output = [FheProgramNode<Cipher<Batched<LANES>>>; K];
+
+    fn mul(self, columns: [FheProgramNode<Cipher<Batched<LANES>>>; K]) -> Self::Output {
+        columns.map(|x| self * x)
+    }
+}
+
+impl<const R: usize, const C: usize, const LANES: usize, const K: usize>
+    Mul<[FheProgramNode<Cipher<Batched<LANES>>>; K]> for FheProgramNode<Matrix<R, C>>
+{
+    type Output = [FheProgramNode<Cipher<Batched<LANES>>>; K];
+
+    fn mul(self, columns: [FheProgramNode<Cipher<Batched<LANES>>>; K]) -> Self::Output {
+        columns.map(|x| self * x)
+    }
+}
+
+impl<const R: usize, const C: usize, const LANES: usize, const K: usize>
+    Mul<[FheProgramNode<Cipher<Batched<LANES>>>; K]> for FheProgramNode<Cipher<Matrix<R, C>>>
+{
+    type Output = [FheProgramNode<Cipher<Batched<LANES>>>; K];
+
+    fn mul(self, columns: [FheProgramNode<Cipher<Batched<LANES>>>; K]) -> Self::Output {
+        columns.map(|x| self * x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SchemeType;
+    use seal_fhe::{CoefficientModulus, PlainModulus, SecurityLevel};
+
+    fn params() -> Params {
+        Params {
+            lattice_dimension: 4096,
+            plain_modulus: PlainModulus::batching(4096, 16).unwrap().value(),
+            coeff_modulus: CoefficientModulus::bfv_default(4096, SecurityLevel::TC128)
+                .unwrap()
+                .iter()
+                .map(|x| x.value())
+                .collect::<Vec<u64>>(),
+            scheme_type: SchemeType::Bfv,
+            security_level: SecurityLevel::TC128,
+        }
+    }
+
+    const M: [[i64; 3]; 2] = [[1, 2, 3], [4, 5, 6]];
+
+    #[test]
+    fn can_mul_vec_non_fhe() {
+        let x = Batched::<4>::from([[1, 0, -1, 0], [2, 1, 0, 0]]);
+
+        let y = Matrix::from(M) * x;
+
+        assert_eq!(y, Batched::from([[-2, -2, 0, 0], [4, 13, 0, 0]]));
+    }
+
+    #[test]
+    fn can_mul_matrices_non_fhe() {
+        let a = Matrix::from(M);
+
+        assert_eq!(a * a.transpose(), Matrix::from([[14, 32], [32, 77]]));
+        assert_eq!(
+            a.transpose() * a,
+            Matrix::from([[17, 22, 27], [22, 29, 36], [27, 36, 45]])
+        );
+    }
+
+    #[test]
+    fn diagonals_pad_to_power_of_two() {
+        let m = Matrix::from(M);
+
+        assert_eq!(Matrix::<2, 3>::NUM_CIPHERTEXTS, 4);
+        assert_eq!(m.diagonal(0), vec![1, 5, 0, 0]);
+        assert_eq!(m.diagonal(1), vec![2, 6, 0, 0]);
+        assert_eq!(m.diagonal(2), vec![3, 0, 0, 0]);
+        assert_eq!(m.diagonal(3), vec![0, 4, 0, 0]);
+    }
+
+    #[test]
+    fn can_encode_decode_matrix() {
+        let m = Matrix::from(M);
+
+        let pt = m.try_into_plaintext(&params()).unwrap();
+
+        assert_eq!(
+            Matrix::<2, 3>::try_from_plaintext(&pt, &params()).unwrap(),
+            m
+        );
+    }
+}
@@ -1,9 +1,13 @@
 mod batched;
+mod fixed;
 mod fractional;
+mod matrix;
 mod rational;
 mod signed;
 
 pub use batched::*;
+pub use fixed::*;
 pub use fractional::*;
+pub use matrix::*;
 pub use rational::*;
 pub use signed::*;
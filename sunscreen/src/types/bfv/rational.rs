@@ -8,7 +8,7 @@ use crate::types::{
 use crate::{FheProgramInputTrait, InnerPlaintext, Params, Plaintext, TypeName};
 use std::cmp::Eq;
 use std::ops::*;
-use sunscreen_runtime::Error;
+use sunscreen_runtime::{Error, TryFromJson};
 
 use num::Rational64;
 
@@ -86,6 +86,16 @@ impl FheProgramInputTrait for Rational {}
 impl FheType for Rational {}
 impl BfvType for Rational {}
 
+impl TryFromJson for Rational {
+    fn try_from_json(value: &serde_json::Value) -> Result<Self, Error> {
+        let val = value
+            .as_f64()
+            .ok_or_else(|| Error::fhe_type_error("Expected a JSON number"))?;
+
+        Self::try_from(val)
+    }
+}
+
 impl TryFrom<f64> for Rational {
     type Error = Error;
 
@@ -4,10 +4,12 @@ use crate as sunscreen;
 use crate::{
     fhe::{with_fhe_ctx, FheContextOps},
     types::{
+        bfv::{Fixed, Fractional, Rational},
         ops::{
-            GraphCipherAdd, GraphCipherConstAdd, GraphCipherConstMul, GraphCipherConstSub,
-            GraphCipherMul, GraphCipherNeg, GraphCipherPlainAdd, GraphCipherPlainMul,
-            GraphCipherPlainSub, GraphCipherSub, GraphConstCipherSub, GraphPlainCipherSub,
+            AtLeast, GraphCipherAdd, GraphCipherCast, GraphCipherConstAdd, GraphCipherConstMul,
+            GraphCipherConstSub, GraphCipherMul, GraphCipherNeg, GraphCipherPlainAdd,
+            GraphCipherPlainMul, GraphCipherPlainSub, GraphCipherSub, GraphConstCipherSub,
+            GraphPlainCipherSub,
         },
         Cipher,
     },
@@ -18,7 +20,7 @@ use crate::{
 };
 
 use sunscreen_runtime::{
-    InnerPlaintext, NumCiphertexts, Plaintext, TryFromPlaintext, TryIntoPlaintext,
+    InnerPlaintext, NumCiphertexts, Plaintext, TryFromJson, TryFromPlaintext, TryIntoPlaintext,
 };
 
 use std::ops::*;
@@ -70,7 +72,7 @@ impl TryIntoPlaintext for Signed {
     ) -> std::result::Result<Plaintext, sunscreen_runtime::Error> {
         let mut seal_plaintext = SealPlaintext::new()?;
 
-        let signed_val = if self.val < 0 { -self.val } else { self.val } as u64;
+        let signed_val = self.val.unsigned_abs();
 
         let sig_bits = significant_bits(signed_val);
         seal_plaintext.resize(sig_bits);
@@ -135,6 +137,17 @@ impl TryFromPlaintext for Signed {
     }
 }
 
+impl TryFromJson for Signed {
+    fn try_from_json(
+        value: &serde_json::Value,
+    ) -> std::result::Result<Self, sunscreen_runtime::Error> {
+        value
+            .as_i64()
+            .map(Self::from)
+            .ok_or_else(|| sunscreen_runtime::Error::fhe_type_error("Expected a JSON integer"))
+    }
+}
+
 impl From<i64> for Signed {
     fn from(val: i64) -> Self {
         Self { val }
@@ -447,6 +460,43 @@ impl GraphCipherPlainMul for Signed {
     }
 }
 
+impl<const INT_BITS: usize> GraphCipherCast<Fractional<INT_BITS>> for Signed {
+    fn graph_cipher_cast(
+        x: FheProgramNode<Cipher<Self>>,
+    ) -> FheProgramNode<Cipher<Fractional<INT_BITS>>> {
+        // Fractional reads digits at or above INT_BITS as fractional ones,
+        // so it needs room for all 64 of ours.
+        #[allow(clippy::let_unit_value)]
+        let () = AtLeast::<INT_BITS, 0, 64>::OK;
+
+        FheProgramNode::new(x.ids)
+    }
+}
+
+impl<const FRAC_BITS: usize> GraphCipherCast<Fixed<FRAC_BITS>> for Signed {
+    fn graph_cipher_cast(
+        x: FheProgramNode<Cipher<Self>>,
+    ) -> FheProgramNode<Cipher<Fixed<FRAC_BITS>>> {
+        FheProgramNode::new(x.ids)
+    }
+}
+
+impl GraphCipherCast<Rational> for Signed {
+    fn graph_cipher_cast(x: FheProgramNode<Cipher<Self>>) -> FheProgramNode<Cipher<Rational>> {
+        with_fhe_ctx(|ctx| {
+            let one = Self::from(1).try_into_plaintext(&ctx.data).unwrap();
+            let one = ctx.add_plaintext_literal(one.inner);
+
+            // The denominator must be a ciphertext, so derive an encrypted 1
+            // from x.
+            let zero = ctx.add_subtraction(x.ids[0], x.ids[0]);
+            let den = ctx.add_addition_plaintext(zero, one);
+
+            FheProgramNode::new(&[x.ids[0], den])
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -6,3 +6,93 @@ pub trait FheLiteral {}
 impl FheLiteral for f64 {}
 impl FheLiteral for u64 {}
 impl FheLiteral for i64 {}
+impl FheLiteral for u128 {}
+impl FheLiteral for i128 {}
+
+/**
+ * Integer literals that can be mapped into `Z_t`, the integers modulo the
+ * plaintext modulus `t`.
+ */
+pub trait IntegerLiteral: FheLiteral + Copy {
+    /**
+     * Maps this value into `Z_t`. Nonnegative values map to themselves
+     * and negative values `v` map to `t + v`, so `-1` becomes `t - 1`.
+     *
+     * # Remarks
+     * Returns [`Error::FheTypeError`](sunscreen_runtime::Error::FheTypeError)
+     * unless `-t < v < t`, rather than silently wrapping values that don't
+     * fit.
+     */
+    fn to_plain_modulus(self, plain_modulus: u64) -> Result<u64, sunscreen_runtime::Error>;
+}
+
+fn i128_to_plain_modulus(
+    val: Option<i128>,
+    plain_modulus: u64,
+) -> Result<u64, sunscreen_runtime::Error> {
+    let t = plain_modulus as i128;
+
+    match val {
+        Some(v) if 0 <= v && v < t => Ok(v as u64),
+        Some(v) if -t < v && v < 0 => Ok((t + v) as u64),
+        _ => Err(sunscreen_runtime::Error::fhe_type_error(
+            "Literal is outside the plaintext modulus range",
+        )),
+    }
+}
+
+impl IntegerLiteral for u64 {
+    fn to_plain_modulus(self, plain_modulus: u64) -> Result<u64, sunscreen_runtime::Error> {
+        i128_to_plain_modulus(Some(self as i128), plain_modulus)
+    }
+}
+
+impl IntegerLiteral for i64 {
+    fn to_plain_modulus(self, plain_modulus: u64) -> Result<u64, sunscreen_runtime::Error> {
+        i128_to_plain_modulus(Some(self as i128), plain_modulus)
+    }
+}
+
+impl IntegerLiteral for u128 {
+    fn to_plain_modulus(self, plain_modulus: u64) -> Result<u64, sunscreen_runtime::Error> {
+        i128_to_plain_modulus(i128::try_from(self).ok(), plain_modulus)
+    }
+}
+
+impl IntegerLiteral for i128 {
+    fn to_plain_modulus(self, plain_modulus: u64) -> Result<u64, sunscreen_runtime::Error> {
+        i128_to_plain_modulus(Some(self), plain_modulus)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_integers_into_plain_modulus() {
+        let t = 65537;
+
+        assert_eq!(0i64.to_plain_modulus(t).unwrap(), 0);
+        assert_eq!(5u64.to_plain_modulus(t).unwrap(), 5);
+        assert_eq!((-1i64).to_plain_modulus(t).unwrap(), t - 1);
+        assert_eq!((-5i128).to_plain_modulus(t).unwrap(), t - 5);
+        assert_eq!((t - 1).to_plain_modulus(t).unwrap(), t - 1);
+        assert_eq!((1 - t as i64).to_plain_modulus(t).unwrap(), 1);
+        assert_eq!(((t - 1) as u128).to_plain_modulus(t).unwrap(), t - 1);
+
+        assert!(t.to_plain_modulus(t).is_err());
+        assert!((-(t as i64)).to_plain_modulus(t).is_err());
+        assert!(i64::MIN.to_plain_modulus(t).is_err());
+        assert!(u64::MAX.to_plain_modulus(t).is_err());
+        assert!(i128::MIN.to_plain_modulus(t).is_err());
+        assert!(u128::MAX.to_plain_modulus(t).is_err());
+
+        // Values near the largest plain modulus SEAL supports don't
+        // overflow.
+        let t = (1 << 60) - 93;
+
+        assert_eq!((-1i64).to_plain_modulus(t).unwrap(), t - 1);
+        assert_eq!((t as i128 - 1).to_plain_modulus(t).unwrap(), t - 1);
+    }
+}
@@ -1,8 +1,8 @@
 use crate::{
     fhe::with_fhe_ctx,
     types::{
-        intern::FheLiteral, ops::*, Cipher, FheType, LaneCount, NumCiphertexts, SwapRows, Type,
-        TypeName,
+        intern::FheLiteral, ops::*, Cipher, FheType, FoldColumns, LaneCount, NumCiphertexts,
+        Rotate, SwapRows, Type, TypeName,
     },
     INDEX_ARENA,
 };
@@ -98,6 +98,81 @@ impl<T: NumCiphertexts> FheProgramNode<T> {
     }
 }
 
+impl<T> FheProgramNode<Cipher<T>>
+where
+    T: FheType
+        + GraphCipherAdd<Left = T, Right = T>
+        + GraphCipherSub<Left = T, Right = T>
+        + GraphCipherMul<Left = T, Right = T>,
+{
+    /**
+     * Treating `self` as an encrypted condition, returns `if_true` where
+     * `self` encrypts 1 and `if_false` where `self` encrypts 0.
+     *
+     * # Remarks
+     * This computes `if_false + self * (if_true - if_false)`, so both
+     * alternatives are always evaluated and the program's shape doesn't
+     * depend on the condition. For [`Batched`](crate::types::bfv::Batched)
+     * types, the selection happens independently in each lane.
+     *
+     * The result is only meaningful when `self` encrypts 0 or 1. This costs
+     * one ciphertext-ciphertext multiplication.
+     */
+    pub fn select(self, if_true: Self, if_false: Self) -> Self {
+        if_false + self * (if_true - if_false)
+    }
+}
+
+impl<T> FheProgramNode<Cipher<T>>
+where
+    T: FheType,
+{
+    /**
+     * Converts `self` to an encrypted `U`, e.g.
+     * `x.cast::<Fractional<64>>()`, without decrypting it.
+     *
+     * # Remarks
+     * Only conversions that can represent every value of `T` are
+     * available, and those depending on generic arguments fail to build
+     * when the arguments don't allow it:
+     * * [`Signed`](crate::types::bfv::Signed) to
+     * [`Fractional<INT_BITS>`](crate::types::bfv::Fractional) for
+     * `INT_BITS >= 64`.
+     * * [`Fractional<A>`](crate::types::bfv::Fractional) to
+     * [`Fractional<B>`](crate::types::bfv::Fractional) for `B >= A`.
+     * * [`Signed`](crate::types::bfv::Signed) and
+     * [`Fixed<F>`](crate::types::bfv::Fixed) to
+     * [`Fixed<G>`](crate::types::bfv::Fixed) for `G >= F`, where `Signed`
+     * counts as `Fixed<0>`.
+     * * [`Fixed<F>`](crate::types::bfv::Fixed) to
+     * [`Fractional<INT_BITS>`](crate::types::bfv::Fractional) for
+     * `INT_BITS + F >= 64`.
+     * * [`Signed`](crate::types::bfv::Signed) to
+     * [`Rational`](crate::types::bfv::Rational).
+     *
+     * [`Signed`](crate::types::bfv::Signed),
+     * [`Fractional`](crate::types::bfv::Fractional) and
+     * [`Fixed`](crate::types::bfv::Fixed) all encode a value's binary
+     * digits in the same polynomial coefficients, so converting between
+     * them costs no operations. Converting to
+     * [`Rational`](crate::types::bfv::Rational) computes an encrypted
+     * denominator of 1 from `self`, costing a subtraction and a plaintext
+     * addition.
+     *
+     * Adding fractional bits shrinks the range of a
+     * [`Fixed`](crate::types::bfv::Fixed) value, so decrypting a cast
+     * value fails if it no longer fits, as it does for any other
+     * [`Fixed`](crate::types::bfv::Fixed) value out of range.
+     */
+    pub fn cast<U>(self) -> FheProgramNode<Cipher<U>>
+    where
+        T: GraphCipherCast<U>,
+        U: FheType,
+    {
+        T::graph_cipher_cast(self)
+    }
+}
+
 // cipher + cipher
 impl<T> Add for FheProgramNode<Cipher<T>>
 where
@@ -462,6 +537,32 @@ where
     }
 }
 
+impl<T> FoldColumns for FheProgramNode<Cipher<T>>
+where
+    T: FheType + GraphCipherSwapRows + GraphCipherAdd<Left = T, Right = T>,
+{
+    type Output = Self;
+
+    fn fold_columns(self) -> Self::Output {
+        self + self.swap_rows()
+    }
+}
+
+impl<T> Rotate for FheProgramNode<Cipher<T>>
+where
+    T: FheType + LaneCount + GraphCipherRotateLeft + GraphCipherRotateRight,
+{
+    type Output = Self;
+
+    fn rotate_left(self, n: u64) -> Self::Output {
+        T::graph_cipher_rotate_left(self, n % T::lane_count() as u64)
+    }
+
+    fn rotate_right(self, n: u64) -> Self::Output {
+        T::graph_cipher_rotate_right(self, n % T::lane_count() as u64)
+    }
+}
+
 impl<T> LaneCount for FheProgramNode<Cipher<T>>
 where
     T: FheType + LaneCount,
@@ -66,6 +66,7 @@ where
 }
 
 #[test]
+#[cfg(feature = "bfv-types")]
 fn can_create_inputs() {
     use crate::{
         fhe::{FheContext, FheOperation, CURRENT_FHE_CTX},
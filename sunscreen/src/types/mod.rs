@@ -1,3 +1,4 @@
+#[cfg(feature = "bfv-types")]
 /**
  * This module contains build-in types you can use as inputs and outputs
  * from FHE programs using the BFV scheme.
@@ -26,6 +27,10 @@
  * conditions. This type intrinsically supports homomorphic addition
  * multiplication, and negation. Dividing by an [`f64`] constant is supported.
  * Dividing by ciphertext is not possible.
+ * * The [`Fixed`](crate::types::bfv::Fixed) type is a fixed-point value with a
+ * configurable number of fractional bits, stored exactly as a scaled integer.
+ * It uses the same encoding trick as [`Fractional`](crate::types::bfv::Fractional),
+ * so products need no explicit rescaling and cost a single multiplication.
  * * The [`Rational`](crate::types::bfv::Rational) type allows quasi fixed-point
  * representation. This type interally uses 2 ciphertexts, and is thus requires
  * twice as much space as other types. Its overflow semantics are effectively
@@ -38,17 +43,28 @@
  * into lanes by exploiting the Chinese remainder theorem for cyclotomic polynomials.
  * Arithmetic operations semantically execute per-lane, enabling high-throughput;
  * e.g. a single addition operation `a + b` will element-wise add the many lanes of a to the
- * many lanes in b.
+ * many lanes in b. Rotations across lanes are available as the `<<` and `>>`
+ * operators or the [`Rotate`](crate::types::Rotate) methods.
+ * * The [`Matrix`](crate::types::bfv::Matrix) type multiplies
+ * [`Batched`](crate::types::bfv::Batched) vectors, or arrays of them holding a
+ * matrix's columns. The compiler lowers each product to rotations,
+ * multiplications and additions over the matrix's diagonals.
  * Type comparison:
  *
  * | Type       | # ciphertexts | overflow conditions | values            | ops/add        | ops/mul | ops/sub        | ops/neg | ops/div |
  * |------------|---------------|---------------------|-------------------|----------------|---------|----------------|---------|---------|
  * | Signed     | 1             | moderate            | signed integral   | 1 add          | 1 mul   | 1 sub          | 1 neg   | -       |
  * | Fractional | 1             | complex             | signed decimal    | 1 add          | 1 mul   | 1 sub          | 1 neg   | 1 mul*  |
+ * | Fixed      | 1             | complex             | signed fixed-point| 1 add          | 1 mul   | 1 sub          | 1 neg   | 1 mul*  |
  * | Rational   | 2             | moderate            | signed decimal    | 2 muls + 1 sub | 2 muls  | 2 muls + 1 sub | 1 neg   | 2 muls  |
+ * | Batched    | 1             | moderate            | signed int lanes  | 1 add          | 1 mul   | 1 sub          | 1 neg   | -       |
  *
  * `* Division by constant only.`
  *
+ * Inside an FHE program, [`cast`](crate::types::intern::FheProgramNode::cast)
+ * converts ciphertexts between these scalar types where the target can
+ * represent every value of the source.
+ *
  * The set of feasible computations under FHE with BFV is fairly limited. For
  * example, comparisons, modulus, transcendentals, are generally very difficult
  * and are often infeasible depending on scheme parameters and noise budget.
@@ -56,6 +72,7 @@
  */
 pub mod bfv;
 
+#[cfg(feature = "fhe")]
 /**
  * This module contains implementation details used to support
  * Sunscreen's domain specific language under the
@@ -63,23 +80,27 @@ pub mod bfv;
  */
 pub mod intern;
 
+#[cfg(feature = "fhe")]
 /**
  * Contains the set of ops traits that dictate legal operations
  * for FHE data types.
  */
 mod ops;
 
+#[cfg(feature = "zkp")]
 /**
  * Contains types used in creating zero-knowledge proof R1CS circuits.
  */
 pub mod zkp;
 
+#[cfg(feature = "fhe")]
 use crate::types::ops::*;
 
+#[cfg(feature = "fhe")]
 pub use sunscreen_runtime::{
-    BfvType, FheType, NumCiphertexts, TryFromPlaintext, TryIntoPlaintext, Type, TypeName,
-    TypeNameInstance, Version,
+    BfvType, FheType, NumCiphertexts, TryFromJson, TryFromPlaintext, TryIntoPlaintext,
 };
+pub use sunscreen_runtime::{Type, TypeName, TypeNameInstance, Version};
 
 /**
  * A trait that allows data types to swap_rows. E.g. [`Batched`](crate::types::bfv::Batched)
@@ -96,6 +117,58 @@ pub trait SwapRows {
     fn swap_rows(self) -> Self::Output;
 }
 
+/**
+ * A trait that allows data types to sum their rows. E.g.
+ * [`Batched`](crate::types::bfv::Batched)
+ *
+ * # Remarks
+ * Folding adds the rows lane-wise and places the sum in both rows, as
+ * `x + x.swap_rows()` does. On ciphertexts, this takes a single column
+ * rotation, so a program whose only rotations are folds needs just one
+ * Galois key (see
+ * [`Application::generate_minimal_galois_keys`](crate::Application::generate_minimal_galois_keys)).
+ * Since a fold's rows are equal, the compiler removes row swaps of
+ * folded values.
+ */
+pub trait FoldColumns {
+    /**
+     * The result type. Typically, this should just be `Self`.
+     */
+    type Output;
+
+    /**
+     * Sums each column, i.e. the rows lane-wise, into both rows.
+     */
+    fn fold_columns(self) -> Self::Output;
+}
+
+/**
+ * A trait that allows data types to rotate their lanes. E.g.
+ * [`Batched`](crate::types::bfv::Batched)
+ *
+ * # Remarks
+ * These are the named equivalents of the `<<` and `>>` operators. Amounts
+ * wrap around the lane count, so rotating by the lane count is a no-op.
+ */
+pub trait Rotate {
+    /**
+     * The result type. Typically, this should just be `Self`.
+     */
+    type Output;
+
+    /**
+     * Rotates each row `n` places to the left, so lane `i` takes the value
+     * in lane `i + n`.
+     */
+    fn rotate_left(self, n: u64) -> Self::Output;
+
+    /**
+     * Rotates each row `n` places to the right, so lane `i + n` takes the
+     * value in lane `i`.
+     */
+    fn rotate_right(self, n: u64) -> Self::Output;
+}
+
 /**
  * On Batched types, returns the number of Batched lanes.
  */
@@ -106,6 +179,7 @@ pub trait LaneCount {
     fn lane_count() -> usize;
 }
 
+#[cfg(feature = "fhe")]
 #[derive(Copy, Clone, Debug)]
 /**
  * Declares a type T as being encrypted in an [`fhe_program`](crate::fhe_program).
@@ -117,6 +191,7 @@ where
     _val: T,
 }
 
+#[cfg(feature = "fhe")]
 impl<T> NumCiphertexts for Cipher<T>
 where
     T: FheType,
@@ -124,6 +199,7 @@ where
     const NUM_CIPHERTEXTS: usize = T::NUM_CIPHERTEXTS;
 }
 
+#[cfg(feature = "fhe")]
 impl<T> TypeName for Cipher<T>
 where
     T: FheType + TypeName,
@@ -0,0 +1,35 @@
+use crate::types::{
+    intern::{FheProgramNode, FheType},
+    Cipher,
+};
+
+/**
+ * Called when the user casts a ciphertext to another type with
+ * [`FheProgramNode::cast`].
+ *
+ * This trait is an implementation detail of FHE program compilation;
+ * you should not directly call methods on this trait.
+ */
+pub trait GraphCipherCast<T>
+where
+    Self: FheType,
+    T: FheType,
+{
+    /**
+     * Converts the given ciphertext to type `T`.
+     */
+    fn graph_cipher_cast(x: FheProgramNode<Cipher<Self>>) -> FheProgramNode<Cipher<T>>;
+}
+
+/**
+ * Rejects casts to types with too few digits for the value. Evaluating
+ * [`AtLeast::OK`] fails to build the calling program unless
+ * `A + B >= MIN`.
+ */
+pub(crate) struct AtLeast<const A: usize, const B: usize, const MIN: usize>;
+
+impl<const A: usize, const B: usize, const MIN: usize> AtLeast<A, B, MIN> {
+    // Indexing out of bounds fails constant evaluation. Const panics
+    // would give a clearer message, but need a newer compiler.
+    pub(crate) const OK: () = [()][(A + B < MIN) as usize];
+}
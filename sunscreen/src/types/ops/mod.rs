@@ -1,4 +1,5 @@
 mod add;
+mod cast;
 mod div;
 mod mul;
 mod neg;
@@ -6,6 +7,7 @@ mod rotate;
 mod sub;
 
 pub use add::*;
+pub use cast::*;
 pub use div::*;
 pub use mul::*;
 pub use neg::*;
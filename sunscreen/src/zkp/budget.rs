@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+use super::{Operation, ZkpFrontendCompilation};
+
+/**
+ * A resource a [`#[zkp_program]`](crate::zkp_program) consumes, which the
+ * compiler can cap with
+ * [`max_constraints`](crate::GenericCompiler::max_constraints) and
+ * [`max_witness_size`](crate::GenericCompiler::max_witness_size).
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZkpLimit {
+    /**
+     * The number of multiplication gates and equality constraints in the
+     * circuit.
+     */
+    Constraints,
+
+    /**
+     * The number of values the prover supplies: private inputs plus the
+     * hidden inputs of every gadget invocation.
+     */
+    WitnessSize,
+}
+
+impl std::fmt::Display for ZkpLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Constraints => write!(f, "constraints"),
+            Self::WitnessSize => write!(f, "witness values"),
+        }
+    }
+}
+
+/**
+ * How often a gadget was invoked while building a ZKP program.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GadgetUsage {
+    /**
+     * The gadget's [`debug_name`](sunscreen_zkp_backend::Gadget::debug_name).
+     */
+    pub name: &'static str,
+
+    /**
+     * The number of times the gadget was invoked.
+     */
+    pub invocations: usize,
+
+    /**
+     * The total number of hidden inputs these invocations added to the
+     * witness.
+     */
+    pub hidden_inputs: usize,
+}
+
+/**
+ * The size of a ZKP program's circuit before it's handed to a backend.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CircuitSize {
+    /**
+     * See [`ZkpLimit::Constraints`].
+     */
+    pub constraints: usize,
+
+    /**
+     * See [`ZkpLimit::WitnessSize`].
+     */
+    pub witness_size: usize,
+
+    /**
+     * The gadgets the program invoked, most hidden inputs first.
+     */
+    pub gadgets: Vec<GadgetUsage>,
+}
+
+impl CircuitSize {
+    /**
+     * Measures the given program.
+     */
+    pub(crate) fn of(program: &ZkpFrontendCompilation) -> Self {
+        let mut size = Self::default();
+        let mut gadgets = HashMap::<&'static str, GadgetUsage>::new();
+
+        for node in program.0.node_weights() {
+            match &node.operation {
+                Operation::Mul | Operation::Constraint(_) => size.constraints += 1,
+                Operation::PrivateInput(_) | Operation::HiddenInput(_) => size.witness_size += 1,
+                Operation::InvokeGadget(g) => {
+                    let usage = gadgets.entry(g.debug_name()).or_insert(GadgetUsage {
+                        name: g.debug_name(),
+                        invocations: 0,
+                        hidden_inputs: 0,
+                    });
+
+                    usage.invocations += 1;
+                    usage.hidden_inputs += g.hidden_input_count();
+                }
+                _ => {}
+            }
+        }
+
+        size.gadgets = gadgets.into_values().collect();
+        size.gadgets.sort_by(|a, b| {
+            b.hidden_inputs
+                .cmp(&a.hidden_inputs)
+                .then(b.invocations.cmp(&a.invocations))
+                .then(a.name.cmp(b.name))
+        });
+
+        size
+    }
+
+    /**
+     * Returns how much of the given resource the program uses.
+     */
+    pub fn get(&self, limit: ZkpLimit) -> usize {
+        match limit {
+            ZkpLimit::Constraints => self.constraints,
+            ZkpLimit::WitnessSize => self.witness_size,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+/**
+ * A ZKP program exceeded one of the compiler's circuit limits.
+ */
+#[error("ZKP program {program} has {actual} {limit}, exceeding the limit of {max}{}", culprit(.gadget))]
+pub struct ZkpBudgetError {
+    /**
+     * The name of the offending program.
+     */
+    pub program: String,
+
+    /**
+     * The limit that was exceeded.
+     */
+    pub limit: ZkpLimit,
+
+    /**
+     * How much of the resource the program uses.
+     */
+    pub actual: usize,
+
+    /**
+     * The configured limit.
+     */
+    pub max: usize,
+
+    /**
+     * The gadget adding the most hidden inputs, if the program invoked
+     * any. A gadget invoked inside a loop typically shows up here with a
+     * large invocation count.
+     */
+    pub gadget: Option<GadgetUsage>,
+}
+
+fn culprit(gadget: &Option<GadgetUsage>) -> String {
+    match gadget {
+        Some(g) => format!(
+            "; gadget {} was invoked {} times, adding {} hidden inputs",
+            g.name, g.invocations, g.hidden_inputs
+        ),
+        None => "; no gadgets were invoked, so the circuit comes from the program body".to_owned(),
+    }
+}
+
+/**
+ * Optional caps on the size of each compiled ZKP program.
+ */
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct ZkpLimits {
+    pub max_constraints: Option<usize>,
+    pub max_witness_size: Option<usize>,
+}
+
+impl ZkpLimits {
+    /**
+     * Fails if the named program's circuit exceeds any of these limits.
+     */
+    pub fn check(&self, program: &str, size: &CircuitSize) -> Result<(), ZkpBudgetError> {
+        let limits = [
+            (ZkpLimit::Constraints, self.max_constraints),
+            (ZkpLimit::WitnessSize, self.max_witness_size),
+        ];
+
+        for (limit, max) in limits {
+            let max = match max {
+                Some(max) => max,
+                None => continue,
+            };
+
+            if size.get(limit) > max {
+                return Err(ZkpBudgetError {
+                    program: program.to_owned(),
+                    limit,
+                    actual: size.get(limit),
+                    max,
+                    gadget: size.gadgets.first().cloned(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -6,6 +6,11 @@ use sunscreen_zkp_backend::{
 
 use crate::Result;
 
+mod budget;
+
+pub(crate) use budget::ZkpLimits;
+pub use budget::{CircuitSize, GadgetUsage, ZkpBudgetError, ZkpLimit};
+
 use std::collections::HashMap;
 use std::hash::Hash;
 use std::sync::Arc;
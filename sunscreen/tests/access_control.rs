@@ -0,0 +1,111 @@
+#![cfg(feature = "bfv-types")]
+
+use sunscreen::{
+    types::{bfv::Signed, Cipher},
+    *,
+};
+
+#[fhe_program(scheme = "bfv", requires = "role:analyst")]
+fn double(a: Cipher<Signed>) -> Cipher<Signed> {
+    a + a
+}
+
+#[test]
+fn compiler_records_requirements() {
+    let app = Compiler::new().fhe_program(double).compile().unwrap();
+
+    assert_eq!(
+        app.get_fhe_program(double).unwrap().metadata.requirements,
+        vec!["role:analyst".to_owned()]
+    );
+}
+
+#[test]
+fn runtime_enforces_requirements() {
+    let app = Compiler::new().fhe_program(double).compile().unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let program = app.get_fhe_program(double).unwrap();
+    let a = runtime.encrypt(Signed::from(21), &public_key).unwrap();
+
+    assert!(matches!(
+        runtime.run(program, vec![a.clone()], &public_key),
+        Err(RuntimeError::Unauthorized(_))
+    ));
+
+    let deny = |_: &str| false;
+    let options = RunOptions {
+        policy: Some(&deny),
+        ..Default::default()
+    };
+
+    assert!(matches!(
+        runtime.run_with_options(program, vec![a.clone()], &public_key, &options),
+        Err(RuntimeError::Unauthorized(_))
+    ));
+
+    let analyst = |r: &str| r == "role:analyst";
+    let options = RunOptions {
+        policy: Some(&analyst),
+        ..Default::default()
+    };
+
+    let result = runtime
+        .run_with_options(program, vec![a], &public_key, &options)
+        .unwrap();
+
+    let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, 42.into());
+}
+
+#[fhe_program(scheme = "bfv")]
+fn triple(a: Cipher<Signed>) -> Cipher<Signed> {
+    a + a + a
+}
+
+#[test]
+fn guard_refuses_untrusted_ciphertexts() {
+    let app = Compiler::new().fhe_program(triple).compile().unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let program = app.get_fhe_program(triple).unwrap();
+
+    let guard = DecryptionGuard::new(&runtime);
+
+    // Encrypted by a third party.
+    let external = runtime.encrypt(Signed::from(7), &public_key).unwrap();
+
+    assert!(matches!(
+        guard.decrypt::<Signed>(&external, &private_key),
+        Err(RuntimeError::UntrustedCiphertext)
+    ));
+
+    assert!(matches!(
+        guard.run(program, vec![external.clone()], &public_key),
+        Err(RuntimeError::UnregisteredProgram)
+    ));
+
+    guard.register(program).unwrap();
+
+    let result = guard
+        .run(program, vec![external.clone()], &public_key)
+        .unwrap();
+
+    let c: Signed = guard.decrypt(&result[0], &private_key).unwrap();
+    assert_eq!(c, 21.into());
+
+    let local = guard.encrypt(Signed::from(5), &public_key).unwrap();
+
+    let c: Signed = guard.decrypt(&local, &private_key).unwrap();
+    assert_eq!(c, 5.into());
+
+    guard.trust(&external).unwrap();
+
+    let c: Signed = guard.decrypt(&external, &private_key).unwrap();
+    assert_eq!(c, 7.into());
+}
@@ -1,9 +1,10 @@
+#![cfg(feature = "bfv-types")]
 #![allow(clippy::needless_range_loop)]
 
 use sunscreen::{
     fhe_program,
     types::{bfv::Signed, Cipher},
-    Compiler, FheProgramInput, PlainModulusConstraint, Runtime,
+    Compiler, FheProgramInput, PlainModulusConstraint, ReductionTree, Runtime,
 };
 
 #[test]
@@ -46,6 +47,41 @@ fn can_add_array_elements() {
     assert_eq!(c, a + b);
 }
 
+#[test]
+fn can_reduce_array_with_balanced_tree() {
+    #[fhe_program(scheme = "bfv")]
+    fn product(x: [Cipher<Signed>; 4]) -> Cipher<Signed> {
+        ReductionTree::Balanced
+            .fold(x.to_vec(), |a, b| a * b)
+            .unwrap()
+    }
+
+    let app = Compiler::new()
+        .fhe_program(product)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let x = [1, 2, 3, 4].map(|x| Signed::try_from(x).unwrap());
+    let x_c = runtime.encrypt(x, &public_key).unwrap();
+
+    let result = runtime
+        .run(
+            app.get_fhe_program(product).unwrap(),
+            vec![x_c],
+            &public_key,
+        )
+        .unwrap();
+
+    let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, Signed::from(24));
+}
+
 #[test]
 fn multidimensional_arrays() {
     fn determinant_impl<T, U, V>(x: T) -> V
@@ -0,0 +1,45 @@
+#![cfg(feature = "bfv-types")]
+
+use sunscreen::{
+    types::{bfv::Signed, Cipher},
+    *,
+};
+
+#[fhe_program(scheme = "bfv")]
+fn cube(a: Cipher<Signed>) -> Cipher<Signed> {
+    a * a * a
+}
+
+#[test]
+fn can_cancel_run() {
+    let app = Compiler::new().fhe_program(cube).compile().unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let program = app.get_fhe_program(cube).unwrap();
+    let a = runtime.encrypt(Signed::from(3), &public_key).unwrap();
+
+    let cancel = CancellationToken::new();
+    let options = RunOptions {
+        cancel: Some(&cancel),
+        ..Default::default()
+    };
+
+    let result = runtime
+        .run_with_options(program, vec![a.clone()], &public_key, &options)
+        .unwrap();
+
+    let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, Signed::from(27));
+
+    // Clones share the flag.
+    cancel.clone().cancel();
+
+    assert!(cancel.is_cancelled());
+    assert!(matches!(
+        runtime.run_with_options(program, vec![a], &public_key, &options),
+        Err(RuntimeError::Cancelled)
+    ));
+}
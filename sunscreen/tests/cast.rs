@@ -0,0 +1,62 @@
+#![cfg(feature = "bfv-types")]
+
+use sunscreen::{
+    fhe_program,
+    types::{
+        bfv::{Fixed, Fractional, Rational, Signed},
+        Cipher,
+    },
+    Compiler, FheProgramInput, PlainModulusConstraint, Runtime,
+};
+
+#[fhe_program(scheme = "bfv")]
+fn mixed(
+    count: Cipher<Signed>,
+    price: Cipher<Fixed<8>>,
+    rate: Cipher<Fractional<32>>,
+) -> (Cipher<Fixed<16>>, Cipher<Fractional<64>>, Cipher<Rational>) {
+    let total = count.cast::<Fixed<16>>() * price.cast::<Fixed<16>>();
+    let scaled = price.cast::<Fractional<64>>() * rate.cast::<Fractional<64>>();
+    let share = count.cast::<Rational>() / (count + 1).cast::<Rational>();
+
+    (total, scaled, share)
+}
+
+#[test]
+fn can_mix_types_with_casts() {
+    let app = Compiler::new()
+        .fhe_program(mixed)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let args: Vec<FheProgramInput> = vec![
+        runtime
+            .encrypt(Signed::from(3), &public_key)
+            .unwrap()
+            .into(),
+        runtime
+            .encrypt(Fixed::<8>::from(2.5), &public_key)
+            .unwrap()
+            .into(),
+        runtime
+            .encrypt(Fractional::<32>::from(-0.75), &public_key)
+            .unwrap()
+            .into(),
+    ];
+
+    let result = runtime
+        .run(app.get_fhe_program(mixed).unwrap(), args, &public_key)
+        .unwrap();
+
+    let total: Fixed<16> = runtime.decrypt(&result[0], &private_key).unwrap();
+    let scaled: Fractional<64> = runtime.decrypt(&result[1], &private_key).unwrap();
+    let share: Rational = runtime.decrypt(&result[2], &private_key).unwrap();
+
+    assert_eq!(total, Fixed::from(7.5));
+    assert_eq!(*scaled, -1.875);
+    assert_eq!(share, Rational::try_from(0.75).unwrap());
+}
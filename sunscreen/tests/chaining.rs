@@ -1,3 +1,5 @@
+#![cfg(feature = "bfv-types")]
+
 use sunscreen::{
     types::{bfv::Signed, Cipher},
     *,
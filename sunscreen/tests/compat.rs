@@ -0,0 +1,68 @@
+#![cfg(feature = "bfv-types")]
+
+use sunscreen::{
+    types::{bfv::Signed, Cipher},
+    *,
+};
+
+#[fhe_program(scheme = "bfv")]
+fn add(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+    a + b
+}
+
+#[fhe_program(scheme = "bfv")]
+fn mul(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+    a * b
+}
+
+fn compile(plain_modulus: u64) -> FheApplication {
+    Compiler::new()
+        .fhe_program(add)
+        .fhe_program(mul)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(plain_modulus))
+        .compile()
+        .unwrap()
+}
+
+#[test]
+fn programs_from_one_application_share_params() {
+    let app = compile(64);
+
+    let report = Application::check_compat(
+        app.get_fhe_program(add).unwrap(),
+        app.get_fhe_program(mul).unwrap(),
+    );
+
+    assert!(report.params_compatible());
+    assert!(!report.is_compatible());
+    assert_eq!(
+        report.mismatches,
+        vec![Mismatch::RequiredKeys {
+            only_a: vec![],
+            only_b: vec![RequiredKeys::Relin],
+        }]
+    );
+
+    let report = Application::check_compat(
+        app.get_fhe_program(mul).unwrap(),
+        app.get_fhe_program(mul).unwrap(),
+    );
+
+    assert!(report.is_compatible());
+}
+
+#[test]
+fn reports_params_mismatches_across_applications() {
+    let old = compile(64);
+    let new = compile(4096);
+
+    let report = Application::check_compat(
+        old.get_fhe_program(mul).unwrap(),
+        new.get_fhe_program(mul).unwrap(),
+    );
+
+    assert!(!report.params_compatible());
+    assert!(report
+        .mismatches
+        .contains(&Mismatch::PlainModulus { a: 64, b: 4096 }));
+}
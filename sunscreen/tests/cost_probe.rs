@@ -0,0 +1,69 @@
+#![cfg(feature = "bfv-types")]
+
+use std::time::Duration;
+
+use sunscreen::{
+    types::{
+        bfv::{Batched, Signed},
+        Cipher,
+    },
+    *,
+};
+
+#[fhe_program(scheme = "bfv")]
+fn square_sum(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+    a * a + b
+}
+
+#[test]
+fn can_probe_and_estimate_costs() {
+    let app = Compiler::new()
+        .fhe_program(square_sum)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(64))
+        .compile()
+        .unwrap();
+
+    let costs = app.params().probe().unwrap();
+
+    assert!(costs.multiply > Duration::ZERO);
+    assert!(costs.relinearize > Duration::ZERO);
+    assert!(costs.multiply_plain > Duration::ZERO);
+    assert!(costs.ntt > Duration::ZERO);
+
+    let program = app.get_fhe_program(square_sum).unwrap();
+    let estimate = program.estimate_latency(&costs);
+
+    // One multiplication, relinearization and addition.
+    assert_eq!(estimate, costs.multiply + costs.relinearize + costs.add);
+}
+
+#[fhe_program(scheme = "bfv")]
+fn rotate(x: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+    (x << 1) + (x >> 3)
+}
+
+#[test]
+fn shifts_cost_one_rotation_per_set_bit() {
+    let app = Compiler::new()
+        .fhe_program(rotate)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let costs = OpCosts {
+        add: Duration::from_micros(1),
+        add_plain: Duration::ZERO,
+        negate: Duration::ZERO,
+        multiply: Duration::ZERO,
+        multiply_plain: Duration::ZERO,
+        relinearize: Duration::ZERO,
+        rotate: Duration::from_micros(10),
+        ntt: Duration::ZERO,
+        swap_rows: Duration::ZERO,
+    };
+
+    let program = app.get_fhe_program(rotate).unwrap();
+
+    // Shifting by 1 takes one rotation and by 3 takes two.
+    assert_eq!(program.estimate_latency(&costs), Duration::from_micros(31));
+}
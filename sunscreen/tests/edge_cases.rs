@@ -1,3 +1,5 @@
+#![cfg(feature = "bfv-types")]
+
 use sunscreen::{
     types::{bfv::Signed, Cipher},
     *,
@@ -194,3 +196,140 @@ fn unused_plain_parameter_3() {
 
     assert_eq!(c, 20.into());
 }
+
+#[test]
+fn deny_unused_inputs_rejects_unused_parameter() {
+    #[fhe_program(scheme = "bfv")]
+    fn add(_a: Cipher<Signed>, b: Cipher<Signed>, c: Cipher<Signed>) -> Cipher<Signed> {
+        b + c
+    }
+
+    let result = Compiler::new()
+        .fhe_program(add)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(64))
+        .deny_unused_inputs()
+        .compile();
+
+    assert_eq!(result.err(), Some(Error::unused_inputs("add")));
+}
+
+#[test]
+fn verified_compilation_runs_correctly() {
+    #[fhe_program(scheme = "bfv")]
+    fn square_sum(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+        let c = a * b;
+
+        c * c + a
+    }
+
+    let app = Compiler::new()
+        .fhe_program(square_sum)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(64))
+        .verify_passes()
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = runtime.encrypt(Signed::from(2), &public_key).unwrap();
+    let b = runtime.encrypt(Signed::from(3), &public_key).unwrap();
+
+    let result = runtime
+        .run(
+            app.get_fhe_program(square_sum).unwrap(),
+            vec![a, b],
+            &public_key,
+        )
+        .unwrap();
+
+    let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, 38.into());
+}
+
+#[test]
+fn rejects_program_and_keys_for_other_params() {
+    #[fhe_program(scheme = "bfv")]
+    fn add(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+        a + b
+    }
+
+    let app = Compiler::new()
+        .fhe_program(add)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(64))
+        .compile()
+        .unwrap();
+
+    let other_app = Compiler::new()
+        .fhe_program(add)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(128))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let other_runtime = Runtime::new_fhe(other_app.params()).unwrap();
+
+    let (public_key, _) = runtime.generate_keys().unwrap();
+    let (other_public_key, _) = other_runtime.generate_keys().unwrap();
+
+    let a = runtime.encrypt(Signed::from(15), &public_key).unwrap();
+    let other_a = other_runtime
+        .encrypt(Signed::from(15), &other_public_key)
+        .unwrap();
+
+    let result = runtime.run(
+        other_app.get_fhe_program(add).unwrap(),
+        vec![a.clone(), a.clone()],
+        &public_key,
+    );
+
+    assert_eq!(result.err(), Some(RuntimeError::ProgramParameterMismatch));
+
+    let result = runtime.run(
+        app.get_fhe_program(add).unwrap(),
+        vec![a.clone(), a.clone()],
+        &other_public_key,
+    );
+
+    assert_eq!(result.err(), Some(RuntimeError::KeyParameterMismatch));
+
+    let result = runtime.run(
+        app.get_fhe_program(add).unwrap(),
+        vec![a, other_a],
+        &public_key,
+    );
+
+    assert_eq!(result.err(), Some(RuntimeError::ParameterMismatch));
+}
+
+#[test]
+fn deterministic_params_are_stable_and_conservative() {
+    #[fhe_program(scheme = "bfv")]
+    fn mul(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+        a * b * a
+    }
+
+    let compile = |deterministic: bool| {
+        let compiler = Compiler::new()
+            .fhe_program(mul)
+            .plain_modulus_constraint(PlainModulusConstraint::Raw(64));
+
+        let compiler = if deterministic {
+            compiler.deterministic_params()
+        } else {
+            compiler
+        };
+
+        compiler.compile().unwrap().params().clone()
+    };
+
+    let params = compile(true);
+
+    for _ in 0..3 {
+        assert_eq!(compile(true), params);
+    }
+
+    assert!(params.lattice_dimension >= compile(false).lattice_dimension);
+}
@@ -1,4 +1,6 @@
-use sunscreen::{types::bfv::Signed, types::Cipher, *};
+#![cfg(feature = "bfv-types")]
+
+use sunscreen::{types::bfv::Signed, types::Cipher, types::TryIntoPlaintext, *};
 
 #[test]
 fn can_encrypt_decrypt() {
@@ -29,3 +31,512 @@ fn can_encrypt_decrypt() {
 
     assert_eq!(c, 20.into());
 }
+
+#[test]
+fn can_encrypt_for_named_argument() {
+    use sunscreen::types::bfv::Fractional;
+
+    #[fhe_program(scheme = "bfv")]
+    fn masked(value: Cipher<Signed>, mask: Cipher<Signed>, scale: Signed) -> Cipher<Signed> {
+        value * scale + mask
+    }
+
+    let app = Compiler::new()
+        .fhe_program(masked)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let program = app.get_fhe_program(masked).unwrap();
+
+    assert_eq!(
+        program.metadata.argument_names,
+        vec!["value", "mask", "scale"]
+    );
+
+    let value = runtime.encrypt(Signed::from(7), &public_key).unwrap();
+
+    // The server only needs the public key to contribute its mask.
+    let mask = runtime
+        .encrypt_for(program, "mask", Signed::from(100), &public_key)
+        .unwrap();
+
+    let args: Vec<FheProgramInput> = vec![value.into(), mask.into(), Signed::from(3).into()];
+
+    let result = runtime.run(program, args, &public_key).unwrap();
+
+    let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, 121.into());
+
+    assert!(matches!(
+        runtime.encrypt_for(program, "nonce", Signed::from(1), &public_key),
+        Err(RuntimeError::UnknownArgument(_))
+    ));
+    assert!(matches!(
+        runtime.encrypt_for(program, "mask", Fractional::<64>::from(1.0), &public_key),
+        Err(RuntimeError::TypeMismatch(_))
+    ));
+    assert!(matches!(
+        runtime.encrypt_for(program, "scale", Signed::from(1), &public_key),
+        Err(RuntimeError::TypeMismatch(_))
+    ));
+}
+
+#[test]
+fn can_run_json() {
+    #[fhe_program(scheme = "bfv")]
+    fn foo(a: Cipher<Signed>, b: Signed) -> Cipher<Signed> {
+        a + b
+    }
+
+    let app = Compiler::new()
+        .fhe_program(foo)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let types = JsonTypes::new().register::<Signed>();
+
+    let a = Signed::from(15).try_into_plaintext(app.params()).unwrap();
+    let b = Signed::from(5).try_into_plaintext(app.params()).unwrap();
+
+    // Both arguments are given as plaintexts; the runtime encrypts `a`.
+    let inputs = vec![JsonInput::Plaintext(a), JsonInput::Plaintext(b)];
+    let inputs = serde_json::to_string(&inputs).unwrap();
+
+    let result = runtime
+        .run_json(
+            app.get_fhe_program(foo).unwrap(),
+            &inputs,
+            &types,
+            &public_key,
+        )
+        .unwrap();
+
+    let result: Vec<Ciphertext> = serde_json::from_str(&result).unwrap();
+
+    let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, 20.into());
+
+    // Plain JSON values, encoded as the types the program expects.
+    let inputs = r#"[{"value": 15}, {"value": -5}]"#;
+
+    let result = runtime
+        .run_json(
+            app.get_fhe_program(foo).unwrap(),
+            inputs,
+            &types,
+            &public_key,
+        )
+        .unwrap();
+
+    let result: Vec<Ciphertext> = serde_json::from_str(&result).unwrap();
+
+    let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, 10.into());
+
+    // Too few arguments.
+    let inputs = serde_json::to_string(&vec![JsonInput::Ciphertext(result[0].clone())]).unwrap();
+
+    assert!(matches!(
+        runtime.run_json(
+            app.get_fhe_program(foo).unwrap(),
+            &inputs,
+            &types,
+            &public_key
+        ),
+        Err(RuntimeError::ArgumentMismatch(_))
+    ));
+
+    // A value of the wrong JSON type.
+    let inputs = r#"[{"value": 15}, {"value": "five"}]"#;
+
+    assert!(matches!(
+        runtime.run_json(
+            app.get_fhe_program(foo).unwrap(),
+            inputs,
+            &types,
+            &public_key
+        ),
+        Err(RuntimeError::FheTypeError(_))
+    ));
+
+    // A plaintext encoded under other parameters.
+    let other_app = Compiler::new()
+        .fhe_program(foo)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(1024))
+        .compile()
+        .unwrap();
+
+    let b = Signed::from(5)
+        .try_into_plaintext(other_app.params())
+        .unwrap();
+
+    let inputs = serde_json::to_string(&vec![
+        JsonInput::Value(serde_json::json!(15)),
+        JsonInput::Plaintext(b),
+    ])
+    .unwrap();
+
+    assert!(matches!(
+        runtime.run_json(
+            app.get_fhe_program(foo).unwrap(),
+            &inputs,
+            &types,
+            &public_key
+        ),
+        Err(RuntimeError::ParameterMismatch)
+    ));
+}
+
+#[test]
+fn can_run_with_custom_evaluator() {
+    use seal_fhe::{BFVEvaluator, BfvEncryptionParametersBuilder, Context, Modulus};
+
+    #[fhe_program(scheme = "bfv")]
+    fn foo(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+        a * b
+    }
+
+    let app = Compiler::new()
+        .fhe_program(foo)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let params = app.params();
+    let runtime = Runtime::new_fhe(params).unwrap();
+
+    let encryption_params = BfvEncryptionParametersBuilder::new()
+        .set_poly_modulus_degree(params.lattice_dimension)
+        .set_plain_modulus_u64(params.plain_modulus)
+        .set_coefficient_modulus(
+            params
+                .coeff_modulus
+                .iter()
+                .map(|x| Modulus::new(*x).unwrap())
+                .collect(),
+        )
+        .build()
+        .unwrap();
+
+    let context = Context::new(&encryption_params, true, params.security_level).unwrap();
+    let evaluator = BFVEvaluator::new(&context).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = runtime.encrypt(Signed::from(15), &public_key).unwrap();
+    let b = runtime.encrypt(Signed::from(5), &public_key).unwrap();
+
+    let result = runtime
+        .run_with_evaluator(
+            app.get_fhe_program(foo).unwrap(),
+            vec![a, b],
+            &public_key,
+            &evaluator,
+        )
+        .unwrap();
+
+    let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, 75.into());
+}
+
+#[test]
+fn can_measure_invariant_noise() {
+    #[fhe_program(scheme = "bfv")]
+    fn square(a: Cipher<Signed>) -> Cipher<Signed> {
+        a * a
+    }
+
+    let app = Compiler::new()
+        .fhe_program(square)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = runtime.encrypt(Signed::from(15), &public_key).unwrap();
+
+    let fresh_noise = runtime.measure_invariant_noise(&a, &private_key).unwrap();
+
+    let result = runtime
+        .run(app.get_fhe_program(square).unwrap(), vec![a], &public_key)
+        .unwrap();
+
+    let noise = runtime
+        .measure_invariant_noise(&result[0], &private_key)
+        .unwrap();
+    let budget = runtime
+        .measure_noise_budget(&result[0], &private_key)
+        .unwrap();
+
+    assert!(fresh_noise > 0.0);
+    assert!(noise > fresh_noise);
+    assert!(noise < 0.5);
+
+    // The budget is roughly -log2(2 * noise), up to rounding to whole bits.
+    assert!((budget as f64 + (2.0 * noise).log2()).abs() <= 1.0);
+}
+
+#[test]
+fn can_rerandomize_ciphertexts() {
+    #[fhe_program(scheme = "bfv")]
+    fn square(a: Cipher<Signed>) -> Cipher<Signed> {
+        a * a
+    }
+
+    // Modulus switching requires more than one prime in the coefficient
+    // modulus, so ask for enough noise budget to rule out small lattices.
+    let app = Compiler::new()
+        .fhe_program(square)
+        .additional_noise_budget(40)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = runtime.encrypt(Signed::from(-7), &public_key).unwrap();
+
+    let result = runtime
+        .run(app.get_fhe_program(square).unwrap(), vec![a], &public_key)
+        .unwrap();
+
+    let mut rerandomized = result[0].clone();
+    runtime.rerandomize(&mut rerandomized, &public_key).unwrap();
+
+    assert_ne!(
+        bincode::serialize(&rerandomized).unwrap(),
+        bincode::serialize(&result[0]).unwrap()
+    );
+
+    let c: Signed = runtime.decrypt(&rerandomized, &private_key).unwrap();
+    assert_eq!(c, 49.into());
+
+    let mut switched = result[0].clone();
+    runtime
+        .rerandomize_and_mod_switch(&mut switched, &public_key)
+        .unwrap();
+
+    assert!(
+        bincode::serialize(&switched).unwrap().len()
+            < bincode::serialize(&result[0]).unwrap().len()
+    );
+
+    let c: Signed = runtime.decrypt(&switched, &private_key).unwrap();
+    assert_eq!(c, 49.into());
+
+    // Keys and ciphertexts under other parameters.
+    let other_app = Compiler::new()
+        .fhe_program(square)
+        .additional_noise_budget(40)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(1024))
+        .compile()
+        .unwrap();
+
+    let other_runtime = Runtime::new_fhe(other_app.params()).unwrap();
+    let (other_public_key, _) = other_runtime.generate_keys().unwrap();
+    let mut other = other_runtime
+        .encrypt(Signed::from(-7), &other_public_key)
+        .unwrap();
+
+    let mut unchanged = result[0].clone();
+
+    assert!(matches!(
+        runtime.rerandomize(&mut unchanged, &other_public_key),
+        Err(RuntimeError::KeyParameterMismatch)
+    ));
+    assert_eq!(
+        bincode::serialize(&unchanged).unwrap(),
+        bincode::serialize(&result[0]).unwrap()
+    );
+
+    assert!(matches!(
+        runtime.rerandomize(&mut other, &public_key),
+        Err(RuntimeError::ParameterMismatch)
+    ));
+}
+
+#[test]
+fn can_run_on_mod_switched_inputs() {
+    #[fhe_program(scheme = "bfv")]
+    fn add_mul(a: Cipher<Signed>, b: Cipher<Signed>, c: Signed) -> Cipher<Signed> {
+        (a + b) * c
+    }
+
+    // Modulus switching requires more than one prime in the coefficient
+    // modulus, so ask for enough noise budget to rule out small lattices.
+    let app = Compiler::new()
+        .fhe_program(add_mul)
+        .additional_noise_budget(40)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = runtime.encrypt(Signed::from(3), &public_key).unwrap();
+    let mut b = runtime.encrypt(Signed::from(4), &public_key).unwrap();
+
+    runtime
+        .rerandomize_and_mod_switch(&mut b, &public_key)
+        .unwrap();
+
+    let args: Vec<FheProgramInput> = vec![a.into(), b.into(), Signed::from(5).into()];
+
+    let result = runtime
+        .run(app.get_fhe_program(add_mul).unwrap(), args, &public_key)
+        .unwrap();
+
+    let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, 35.into());
+}
+
+#[test]
+fn deterministic_program_yields_identical_outputs() {
+    #[fhe_program(scheme = "bfv")]
+    fn square(a: Cipher<Signed>) -> Cipher<Signed> {
+        a * a
+    }
+
+    let app = Compiler::new().fhe_program(square).compile().unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let program = app.get_fhe_program(square).unwrap();
+
+    assert!(program.metadata.deterministic);
+
+    let a = runtime.encrypt(Signed::from(6), &public_key).unwrap();
+
+    let first = runtime.run(program, vec![a.clone()], &public_key).unwrap();
+    let second = runtime.run(program, vec![a], &public_key).unwrap();
+
+    assert_eq!(
+        serde_json::to_vec(&first[0]).unwrap(),
+        serde_json::to_vec(&second[0]).unwrap()
+    );
+
+    let c: Signed = runtime.decrypt(&first[0], &private_key).unwrap();
+
+    assert_eq!(c, 36.into());
+}
+
+#[test]
+fn can_decrypt_all_outputs() {
+    use sunscreen::types::bfv::Fractional;
+
+    #[fhe_program(scheme = "bfv")]
+    fn stats(
+        a: Cipher<Signed>,
+        b: Cipher<Fractional<64>>,
+    ) -> (Cipher<Signed>, Cipher<Fractional<64>>) {
+        (a + a, b + b)
+    }
+
+    let app = Compiler::new().fhe_program(stats).compile().unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let program = app.get_fhe_program(stats).unwrap();
+
+    let a = runtime.encrypt(Signed::from(4), &public_key).unwrap();
+    let b = runtime
+        .encrypt(Fractional::<64>::from(1.5), &public_key)
+        .unwrap();
+
+    let outputs = runtime.run(program, vec![a, b], &public_key).unwrap();
+
+    let plaintexts = runtime
+        .decrypt_all(program, &outputs, &private_key)
+        .unwrap();
+
+    assert_eq!(plaintexts.len(), 2);
+
+    let a: Signed = runtime.decode(&plaintexts[0]).unwrap();
+    let b: Fractional<64> = runtime.decode(&plaintexts[1]).unwrap();
+
+    assert_eq!(a, 8.into());
+    assert_eq!(f64::from(b), 3.0);
+
+    assert!(matches!(
+        runtime.decode::<Signed>(&plaintexts[1]),
+        Err(RuntimeError::TypeMismatch(_))
+    ));
+
+    assert!(matches!(
+        runtime.decrypt_all(program, &outputs[..1], &private_key),
+        Err(RuntimeError::IncorrectCiphertextCount)
+    ));
+}
+
+#[test]
+fn can_decrypt_all_mod_switched_outputs_without_mod_chain() {
+    #[fhe_program(scheme = "bfv")]
+    fn pair(a: Cipher<Signed>, b: Cipher<Signed>) -> (Cipher<Signed>, Cipher<Signed>) {
+        (a + b, a - b)
+    }
+
+    // Modulus switching requires more than one prime in the coefficient
+    // modulus, so ask for enough noise budget to rule out small lattices.
+    let app = Compiler::new()
+        .fhe_program(pair)
+        .additional_noise_budget(40)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let program = app.get_fhe_program(pair).unwrap();
+
+    let a = runtime.encrypt(Signed::from(7), &public_key).unwrap();
+    let b = runtime.encrypt(Signed::from(2), &public_key).unwrap();
+
+    let mut outputs = runtime.run(program, vec![a, b], &public_key).unwrap();
+
+    runtime
+        .rerandomize_and_mod_switch(&mut outputs[1], &public_key)
+        .unwrap();
+
+    // Without the chain, each output decrypts at its own level.
+    let runtime = Runtime::new_fhe_with_mod_chain(app.params(), false).unwrap();
+
+    let plaintexts = runtime
+        .decrypt_all(program, &outputs, &private_key)
+        .unwrap();
+
+    let a: Signed = runtime.decode(&plaintexts[0]).unwrap();
+    let b: Signed = runtime.decode(&plaintexts[1]).unwrap();
+
+    assert_eq!(a, 9.into());
+    assert_eq!(b, 5.into());
+}
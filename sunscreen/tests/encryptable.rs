@@ -0,0 +1,40 @@
+#![cfg(feature = "bfv-types")]
+
+use sunscreen::{
+    types::bfv::{Fractional, Signed},
+    Compiler, Encryptable, Runtime,
+};
+
+#[derive(Debug, Clone, PartialEq, Encryptable)]
+struct Account {
+    balance: Fractional<64>,
+    id: Signed,
+    history: Vec<Signed>,
+}
+
+#[sunscreen::fhe_program(scheme = "bfv")]
+fn noop(a: sunscreen::types::Cipher<Signed>) -> sunscreen::types::Cipher<Signed> {
+    a
+}
+
+#[test]
+fn can_roundtrip_encryptable_struct() {
+    let app = Compiler::new().fhe_program(noop).compile().unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let account = Account {
+        balance: Fractional::from(12.5),
+        id: Signed::from(42),
+        history: vec![Signed::from(-3), Signed::from(7), Signed::from(0)],
+    };
+
+    let encrypted: EncryptedAccount = account.encrypt(&runtime, &public_key).unwrap();
+
+    assert_eq!(encrypted.history.len(), 3);
+
+    let decrypted = encrypted.decrypt(&runtime, &private_key).unwrap();
+
+    assert_eq!(decrypted, account);
+}
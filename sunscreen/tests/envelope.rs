@@ -0,0 +1,186 @@
+#![cfg(all(feature = "linkedproofs", feature = "bfv-types"))]
+
+use std::time::Duration;
+
+use sunscreen::{
+    types::{bfv::Signed, zkp::NativeField, Cipher},
+    *,
+};
+use sunscreen_zkp_backend::bulletproofs::BulletproofsBackend;
+
+type BPField = NativeField<<BulletproofsBackend as ZkpBackend>::Field>;
+
+#[fhe_program(scheme = "bfv")]
+fn add(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+    a + b
+}
+
+#[fhe_program(scheme = "bfv")]
+fn sub(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+    a - b
+}
+
+#[zkp_program(backend = "bulletproofs")]
+fn is_42<F: BackendField>(x: NativeField<F>) {
+    x.constrain_eq(NativeField::from(42u32))
+}
+
+fn setup() -> (FheApplication, Envelope) {
+    let app = Compiler::new()
+        .fhe_program(add)
+        .fhe_program(sub)
+        .compile()
+        .unwrap();
+
+    let runtime = FheRuntime::new(app.params()).unwrap();
+    let (public_key, _) = runtime.generate_keys().unwrap();
+
+    let a = runtime.encrypt(Signed::from(3), &public_key).unwrap();
+    let b = runtime.encrypt(Signed::from(4), &public_key).unwrap();
+
+    let envelope = Envelope::new(app.get_fhe_program(add).unwrap(), vec![a, b], vec![]);
+
+    (app, envelope)
+}
+
+fn rejection(result: Result<EnvelopeDigest>) -> EnvelopeError {
+    match result {
+        Err(Error::Envelope(e)) => e,
+        _ => panic!("Expected the envelope to be rejected"),
+    }
+}
+
+#[test]
+fn accepts_fresh_envelope_once() {
+    let (app, envelope) = setup();
+    let add = app.get_fhe_program(add).unwrap();
+
+    let mut guard = ReplayGuard::new(Duration::from_secs(300));
+
+    let digest = guard.check(&envelope, add).unwrap();
+
+    assert_eq!(digest, envelope.digest().unwrap());
+    assert_eq!(
+        rejection(guard.check(&envelope, add)),
+        EnvelopeError::Replayed
+    );
+}
+
+#[test]
+fn rejects_rewrapped_contents() {
+    let (app, envelope) = setup();
+    let add = app.get_fhe_program(add).unwrap();
+
+    let mut guard = ReplayGuard::new(Duration::from_secs(300));
+
+    guard.check(&envelope, add).unwrap();
+
+    // An eavesdropper copies the ciphertexts into a new envelope.
+    let rewrapped = Envelope::new(add, envelope.ciphertexts.clone(), vec![]);
+
+    assert_ne!(rewrapped.digest().unwrap(), envelope.digest().unwrap());
+    assert_eq!(
+        rewrapped.content_digest().unwrap(),
+        envelope.content_digest().unwrap()
+    );
+    assert_eq!(
+        rejection(guard.check(&rewrapped, add)),
+        EnvelopeError::Replayed
+    );
+}
+
+#[test]
+fn rejects_other_programs() {
+    let (app, envelope) = setup();
+
+    let mut guard = ReplayGuard::new(Duration::from_secs(300));
+
+    assert_eq!(
+        rejection(guard.check(&envelope, app.get_fhe_program(sub).unwrap())),
+        EnvelopeError::ProgramMismatch
+    );
+}
+
+#[test]
+fn rejects_stale_and_future_envelopes() {
+    let (app, envelope) = setup();
+    let add = app.get_fhe_program(add).unwrap();
+
+    let mut guard = ReplayGuard::new(Duration::from_secs(300));
+
+    assert_eq!(
+        rejection(guard.check_at(&envelope, add, envelope.timestamp + 301)),
+        EnvelopeError::Expired
+    );
+    assert_eq!(
+        rejection(guard.check_at(&envelope, add, envelope.timestamp - 3600)),
+        EnvelopeError::FromFuture
+    );
+
+    guard
+        .check_at(&envelope, add, envelope.timestamp + 300)
+        .unwrap();
+}
+
+#[test]
+fn forgets_contents_after_max_age() {
+    let (app, envelope) = setup();
+    let add = app.get_fhe_program(add).unwrap();
+
+    let mut guard = ReplayGuard::new(Duration::from_secs(300));
+
+    guard.check_at(&envelope, add, envelope.timestamp).unwrap();
+
+    // Once the original has expired, only proofs bound to it stop the
+    // contents from being rewrapped.
+    let mut rewrapped = Envelope::new(add, envelope.ciphertexts.clone(), vec![]);
+    rewrapped.timestamp = envelope.timestamp + 301;
+
+    guard
+        .check_at(&rewrapped, add, rewrapped.timestamp)
+        .unwrap();
+}
+
+#[test]
+fn proofs_only_verify_in_their_envelope() {
+    let (app, mut envelope) = setup();
+    let add = app.get_fhe_program(add).unwrap();
+
+    let zkp_app = Compiler::new()
+        .zkp_backend::<BulletproofsBackend>()
+        .zkp_program(is_42)
+        .compile()
+        .unwrap();
+
+    let program = zkp_app.get_zkp_program(is_42).unwrap();
+    let runtime = Runtime::new_zkp(&BulletproofsBackend::new()).unwrap();
+
+    let proof = runtime
+        .prove_with_context(
+            program,
+            &envelope.proof_context(),
+            vec![],
+            vec![],
+            vec![BPField::from(42u8)],
+        )
+        .unwrap();
+
+    envelope.proofs.push(proof);
+
+    let verify = |e: &Envelope| {
+        runtime.verify_with_context(
+            program,
+            &e.proofs[0],
+            &e.proof_context(),
+            Vec::<ZkpProgramInput>::new(),
+            vec![],
+        )
+    };
+
+    verify(&envelope).unwrap();
+
+    // An eavesdropper moves the ciphertexts and proofs into a new envelope.
+    let rewrapped = Envelope::new(add, envelope.ciphertexts.clone(), envelope.proofs.clone());
+
+    assert!(verify(&rewrapped).is_err());
+}
@@ -0,0 +1,53 @@
+#![cfg(all(feature = "failure-injection", feature = "bfv-types"))]
+
+use sunscreen::failure_injection::{disarm, fail_nth_call, InjectedFailure};
+use sunscreen::{
+    fhe_program,
+    types::{bfv::Signed, Cipher},
+    Compiler, Runtime,
+};
+
+// Failure injection is global, so everything runs in a single test.
+#[test]
+fn run_reports_injected_failures() {
+    #[fhe_program(scheme = "bfv")]
+    fn add(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+        a + b
+    }
+
+    let app = Compiler::new().fhe_program(add).compile().unwrap();
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let program = app.get_fhe_program(add).unwrap();
+
+    let a = runtime.encrypt(Signed::from(2), &public_key).unwrap();
+    let b = runtime.encrypt(Signed::from(3), &public_key).unwrap();
+
+    // Copying the first input is the first call into SEAL the executor
+    // makes, and fails without panicking.
+    fail_nth_call(1, InjectedFailure::OutOfMemory);
+
+    assert!(runtime
+        .run(program, vec![a.clone(), b.clone()], &public_key)
+        .is_err());
+
+    let result = runtime.run(program, vec![a, b], &public_key).unwrap();
+    let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, 5.into());
+
+    // Key generation reports failures rather than panicking, wherever they
+    // land.
+    fail_nth_call(1, InjectedFailure::OutOfMemory);
+
+    assert!(runtime.generate_keys().is_err());
+
+    for n in 2..=8 {
+        fail_nth_call(n, InjectedFailure::OutOfMemory);
+
+        let _ = runtime.generate_keys();
+    }
+
+    disarm();
+}
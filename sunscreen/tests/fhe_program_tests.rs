@@ -1,3 +1,5 @@
+#![cfg(feature = "bfv-types")]
+
 use sunscreen::{
     fhe::{FheFrontendCompilation, CURRENT_FHE_CTX},
     fhe_program,
@@ -0,0 +1,51 @@
+#![cfg(feature = "bfv-types")]
+
+use sunscreen::{
+    fhe_program,
+    types::{bfv::Fixed, Cipher},
+    Compiler, FheProgramInput, PlainModulusConstraint, Runtime,
+};
+
+type Money = Fixed<16>;
+
+#[test]
+fn can_compute_order_totals() {
+    #[fhe_program(scheme = "bfv")]
+    fn total(price: Cipher<Money>, quantity: Cipher<Money>, fee: Money) -> Cipher<Money> {
+        price * quantity + fee - 0.5
+    }
+
+    let app = Compiler::new()
+        .fhe_program(total)
+        .additional_noise_budget(30)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let do_total = |price: f64, quantity: f64, fee: f64| {
+        let (price, quantity, fee) = (Money::from(price), Money::from(quantity), Money::from(fee));
+
+        let args: Vec<FheProgramInput> = vec![
+            runtime.encrypt(price, &public_key).unwrap().into(),
+            runtime.encrypt(quantity, &public_key).unwrap().into(),
+            fee.into(),
+        ];
+
+        let result = runtime
+            .run(app.get_fhe_program(total).unwrap(), args, &public_key)
+            .unwrap();
+
+        let result: Money = runtime.decrypt(&result[0], &private_key).unwrap();
+
+        assert_eq!(result, price * quantity + fee - Money::from(0.5));
+    };
+
+    do_total(19.99, 3.0, 4.25);
+    do_total(0.01, 1000.0, 0.0);
+    do_total(-12.5, 2.75, 1.125);
+    do_total(1234.5678, -0.3333, -7.0);
+}
@@ -1,3 +1,4 @@
+#![cfg(feature = "bfv-types")]
 #![allow(clippy::approx_constant)]
 
 use float_cmp::ApproxEq;
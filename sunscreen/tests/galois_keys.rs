@@ -0,0 +1,137 @@
+#![cfg(feature = "bfv-types")]
+
+use std::io::Cursor;
+
+use seal_fhe::galois_elt_from_step;
+use sunscreen::{
+    fhe_program,
+    types::{bfv::Batched, Cipher, SwapRows},
+    Compiler, Error, FheProgramInput, GaloisKeyIndex, MinimalGaloisKeys, PlainModulusConstraint,
+    PublicKey, Runtime,
+};
+
+#[fhe_program(scheme = "bfv")]
+fn rotate(x: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+    (x << 1) + (x >> 3)
+}
+
+#[fhe_program(scheme = "bfv")]
+fn swap(x: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+    x.swap_rows()
+}
+
+#[fhe_program(scheme = "bfv")]
+fn double(x: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+    x + x
+}
+
+#[test]
+fn can_generate_minimal_galois_keys() {
+    let app = Compiler::new()
+        .fhe_program(rotate)
+        .fhe_program(swap)
+        .fhe_program(double)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let degree = app.params().lattice_dimension;
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let mut elements = vec![];
+    let mut program_elements = Default::default();
+
+    let (public_key, private_key) = runtime
+        .generate_keys_with_galois(|keygen| {
+            let MinimalGaloisKeys {
+                keys,
+                elements: e,
+                program_elements: p,
+            } = app.generate_minimal_galois_keys(keygen)?;
+
+            elements = e;
+            program_elements = p;
+
+            Ok::<_, Error>(keys)
+        })
+        .unwrap();
+
+    // Rotating by 1 and right by 3 plus swapping rows needs at most 3 keys.
+    assert!(!elements.is_empty() && elements.len() <= 3);
+    assert!(elements.contains(&galois_elt_from_step(0, degree).unwrap()));
+
+    assert_eq!(
+        program_elements["swap"],
+        vec![galois_elt_from_step(0, degree).unwrap()]
+    );
+    assert!(program_elements["double"].is_empty());
+    assert!(!program_elements["rotate"].is_empty());
+    assert!(program_elements["rotate"]
+        .iter()
+        .all(|e| elements.contains(e)));
+
+    let x = Batched::<4>::try_from([vec![1, 2, 3, 4], vec![5, 6, 7, 8]]).unwrap();
+
+    let run = |name: &str| -> Batched<4> {
+        let args: Vec<FheProgramInput> = vec![runtime.encrypt(x, &public_key).unwrap().into()];
+
+        let result = runtime
+            .run(app.get_fhe_program(name).unwrap(), args, &public_key)
+            .unwrap();
+
+        runtime.decrypt(&result[0], &private_key).unwrap()
+    };
+
+    assert_eq!(run("rotate"), (x << 1) + (x >> 3));
+    assert_eq!(run("swap"), x.swap_rows());
+    assert_eq!(run("double"), x + x);
+}
+
+#[test]
+fn can_load_galois_keys_on_demand() {
+    let app = Compiler::new()
+        .fhe_program(rotate)
+        .fhe_program(swap)
+        .fhe_program(double)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let mut archive = vec![];
+
+    GaloisKeyIndex::write(&public_key.galois_key.as_ref().unwrap().data, &mut archive).unwrap();
+
+    // A server only needs the archive's header up front.
+    let mut reader = Cursor::new(archive);
+    let index = GaloisKeyIndex::read(&mut reader).unwrap();
+
+    let x = Batched::<4>::try_from([vec![1, 2, 3, 4], vec![5, 6, 7, 8]]).unwrap();
+
+    let mut run = |name: &str| -> Batched<4> {
+        let program = app.get_fhe_program(name).unwrap();
+
+        let galois_key = runtime
+            .load_galois_keys(program, &index, &mut reader)
+            .unwrap();
+
+        assert_eq!(galois_key.is_none(), name == "double");
+
+        let server_key = PublicKey {
+            public_key: public_key.public_key.clone(),
+            galois_key,
+            relin_key: public_key.relin_key.clone(),
+        };
+
+        let args: Vec<FheProgramInput> = vec![runtime.encrypt(x, &server_key).unwrap().into()];
+        let result = runtime.run(program, args, &server_key).unwrap();
+
+        runtime.decrypt(&result[0], &private_key).unwrap()
+    };
+
+    assert_eq!(run("rotate"), (x << 1) + (x >> 3));
+    assert_eq!(run("swap"), x.swap_rows());
+    assert_eq!(run("double"), x + x);
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "bfv-types")]
+
+use sunscreen::{
+    types::{bfv::Signed, Cipher},
+    *,
+};
+
+#[fhe_program(scheme = "bfv")]
+fn poly(a: Cipher<Signed>, b: Cipher<Signed>, c: Signed) -> (Cipher<Signed>, Cipher<Signed>) {
+    (a * b * b, a + c)
+}
+
+#[test]
+fn can_query_compiled_program() {
+    let app = Compiler::new()
+        .fhe_program(poly)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(64))
+        .compile()
+        .unwrap();
+
+    let graph = app.get_fhe_program(poly).unwrap().graph();
+
+    let muls = graph.find(|op| matches!(op, FheProgramOperation::Multiply));
+
+    assert_eq!(muls.len(), 2);
+    assert_eq!(graph.multiplicative_depth(), 2);
+
+    let outputs = graph.outputs();
+
+    assert_eq!(outputs.len(), 2);
+
+    let path = graph.critical_path();
+
+    assert_eq!(path.last().map(|n| n.id), Some(outputs[0].id));
+    assert!(matches!(
+        path.first().map(|n| n.operation),
+        Some(FheProgramOperation::InputCiphertext(_))
+    ));
+
+    // The second output doesn't depend on any multiplication.
+    let slice = graph.output_slice(1).unwrap();
+
+    assert!(slice
+        .iter()
+        .all(|n| !matches!(n.operation, FheProgramOperation::Multiply)));
+    assert_eq!(slice.last().map(|n| n.id), Some(outputs[1].id));
+
+    for edge in graph.edges() {
+        assert!(graph.node(edge.source).is_some());
+        assert!(graph.users(edge.source).contains(&edge));
+        assert!(graph.operands(edge.target).contains(&edge));
+    }
+}
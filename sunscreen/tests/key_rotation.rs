@@ -0,0 +1,83 @@
+#![cfg(feature = "bfv-types")]
+
+use sunscreen::{
+    fhe_program,
+    types::{bfv::Signed, Cipher},
+    Compiler, KeyRotation, Runtime, RuntimeError,
+};
+
+#[fhe_program(scheme = "bfv")]
+fn square(a: Cipher<Signed>) -> Cipher<Signed> {
+    a * a
+}
+
+#[test]
+fn can_rotate_keys() {
+    let app = Compiler::new().fhe_program(square).compile().unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (old_public_key, old_private_key) = runtime.generate_keys().unwrap();
+    let (new_public_key, new_private_key) = runtime.generate_keys().unwrap();
+
+    let values = (-5..5i64).collect::<Vec<_>>();
+
+    let archive = values
+        .iter()
+        .map(|v| runtime.encrypt(Signed::from(*v), &old_public_key).unwrap())
+        .collect::<Vec<_>>();
+
+    let rotation = KeyRotation::new(&runtime, &old_private_key, &new_public_key).unwrap();
+
+    let rotated = rotation.reencrypt_all(&archive).unwrap();
+
+    for (c, v) in rotated.iter().zip(values.iter()) {
+        let x: Signed = runtime.decrypt(c, &new_private_key).unwrap();
+
+        assert_eq!(x, Signed::from(*v));
+    }
+
+    let streamed = rotation
+        .reencrypt_stream(archive.clone(), 3)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(streamed.len(), archive.len());
+
+    for (c, v) in streamed.iter().zip(values.iter()) {
+        let x: Signed = runtime.decrypt(c, &new_private_key).unwrap();
+
+        assert_eq!(x, Signed::from(*v));
+    }
+
+    // Rotated ciphertexts work with programs run under the new keys.
+    let result = runtime
+        .run(
+            app.get_fhe_program(square).unwrap(),
+            vec![rotated[0].clone()],
+            &new_public_key,
+        )
+        .unwrap();
+
+    let x: Signed = runtime.decrypt(&result[0], &new_private_key).unwrap();
+
+    assert_eq!(x, Signed::from(25));
+}
+
+#[test]
+fn key_rotation_rejects_keys_for_other_params() {
+    let app = Compiler::new().fhe_program(square).compile().unwrap();
+
+    let mut other_params = app.params().clone();
+    other_params.plain_modulus += 1;
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let other_runtime = Runtime::new_fhe(&other_params).unwrap();
+
+    let (_, old_private_key) = runtime.generate_keys().unwrap();
+    let (new_public_key, _) = other_runtime.generate_keys().unwrap();
+
+    assert!(matches!(
+        KeyRotation::new(&runtime, &old_private_key, &new_public_key),
+        Err(RuntimeError::KeyParameterMismatch)
+    ));
+}
@@ -0,0 +1,145 @@
+#![cfg(feature = "bfv-types")]
+
+use sunscreen::{
+    fhe_program,
+    types::{
+        bfv::{Batched, Matrix},
+        Cipher,
+    },
+    Compiler, Error, FheProgramInput, PlainModulusConstraint, Runtime,
+};
+
+const HIDDEN: [[i64; 3]; 4] = [[1, 2, 3], [0, -1, 0], [2, 0, 1], [1, 1, 1]];
+const OUTPUT: [[i64; 4]; 2] = [[1, 0, -1, 2], [3, 1, 0, 0]];
+
+#[fhe_program(scheme = "bfv")]
+fn dense(x: Cipher<Batched<8>>) -> Cipher<Batched<8>> {
+    Matrix::from(OUTPUT) * (Matrix::from(HIDDEN) * x)
+}
+
+#[fhe_program(scheme = "bfv")]
+fn mat_mul(columns: [Cipher<Batched<8>>; 2]) -> [Cipher<Batched<8>>; 2] {
+    Matrix::from(HIDDEN) * columns
+}
+
+#[fhe_program(scheme = "bfv")]
+fn plain_weights(m: Matrix<2, 3>, x: Cipher<Batched<8>>) -> Cipher<Batched<8>> {
+    m * x
+}
+
+#[fhe_program(scheme = "bfv")]
+fn private_weights(m: Cipher<Matrix<2, 3>>, x: Cipher<Batched<8>>) -> Cipher<Batched<8>> {
+    m * x
+}
+
+fn vector(a: [i64; 3], b: [i64; 3]) -> Batched<8> {
+    let pad = |x: [i64; 3]| [x[0], x[1], x[2], 0, 0, 0, 0, 0];
+
+    Batched::from([pad(a), pad(b)])
+}
+
+#[test]
+fn can_run_dense_layers_with_minimal_keys() {
+    let app = Compiler::new()
+        .fhe_program(dense)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime
+        .generate_keys_with_galois(|keygen| {
+            Ok::<_, Error>(app.generate_minimal_galois_keys(keygen)?.keys)
+        })
+        .unwrap();
+
+    let x = vector([1, -2, 3], [4, 0, 5]);
+
+    let args: Vec<FheProgramInput> = vec![runtime.encrypt(x, &public_key).unwrap().into()];
+
+    let result = runtime
+        .run(app.get_fhe_program(dense).unwrap(), args, &public_key)
+        .unwrap();
+
+    let y: Batched<8> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(y, Matrix::from(OUTPUT) * (Matrix::from(HIDDEN) * x));
+}
+
+#[test]
+fn can_multiply_matrix_by_encrypted_columns() {
+    let app = Compiler::new()
+        .fhe_program(mat_mul)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    // The 4 columns of a 3x4 matrix, 2 per ciphertext.
+    let b = Matrix::from([[1, 2, 3, 4], [5, 6, 7, 8], [-1, 0, 1, 0]]);
+    let b_t: [[i64; 3]; 4] = b.transpose().into();
+
+    let columns = [vector(b_t[0], b_t[1]), vector(b_t[2], b_t[3])];
+
+    let args: Vec<FheProgramInput> = vec![runtime.encrypt(columns, &public_key).unwrap().into()];
+
+    let result = runtime
+        .run(app.get_fhe_program(mat_mul).unwrap(), args, &public_key)
+        .unwrap();
+
+    let product: [[i64; 4]; 4] = (Matrix::from(HIDDEN) * b).transpose().into();
+
+    for (i, r) in result.iter().enumerate() {
+        let y: Batched<8> = runtime.decrypt(r, &private_key).unwrap();
+
+        for row in 0..2 {
+            for lane in 0..8 {
+                let expected = if lane < 4 {
+                    product[2 * i + row][lane]
+                } else {
+                    0
+                };
+
+                assert_eq!(y[(row, lane)], expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn can_multiply_plain_and_encrypted_matrix_inputs() {
+    let app = Compiler::new()
+        .fhe_program(plain_weights)
+        .fhe_program(private_weights)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let m = Matrix::from([[1, 2, 3], [-4, 5, 6]]);
+    let x = vector([1, 0, -1], [2, 3, 4]);
+
+    let run = |name: &str, m: FheProgramInput| -> Batched<8> {
+        let args: Vec<FheProgramInput> = vec![m, runtime.encrypt(x, &public_key).unwrap().into()];
+
+        let result = runtime
+            .run(app.get_fhe_program(name).unwrap(), args, &public_key)
+            .unwrap();
+
+        runtime.decrypt(&result[0], &private_key).unwrap()
+    };
+
+    assert_eq!(run("plain_weights", m.into()), m * x);
+    assert_eq!(
+        run(
+            "private_weights",
+            runtime.encrypt(m, &public_key).unwrap().into()
+        ),
+        m * x
+    );
+}
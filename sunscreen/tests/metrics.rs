@@ -0,0 +1,61 @@
+#![cfg(all(feature = "metrics", feature = "bfv-types"))]
+
+use sunscreen::{
+    fhe_program,
+    types::{bfv::Signed, Cipher},
+    Compiler, FheProgramInput, Metrics, RunOptions, Runtime,
+};
+
+#[fhe_program(scheme = "bfv")]
+fn square(a: Cipher<Signed>) -> Cipher<Signed> {
+    a * a
+}
+
+#[test]
+fn metered_runs_record_runs_and_operations() {
+    let app = Compiler::new().fhe_program(square).compile().unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, _) = runtime.generate_keys().unwrap();
+
+    let metrics = Metrics::new();
+    let program = app.get_fhe_program(square).unwrap();
+
+    let options = RunOptions {
+        meter: Some(("square", &metrics)),
+        ..Default::default()
+    };
+
+    for _ in 0..2 {
+        let args: Vec<FheProgramInput> = vec![runtime
+            .encrypt(Signed::from(3), &public_key)
+            .unwrap()
+            .into()];
+
+        runtime
+            .run_with_options(program, args, &public_key, &options)
+            .unwrap();
+    }
+
+    // The wrong number of arguments.
+    assert!(runtime
+        .run_with_options(
+            program,
+            Vec::<FheProgramInput>::new(),
+            &public_key,
+            &options
+        )
+        .is_err());
+
+    let text = metrics.encode();
+
+    for line in [
+        "sunscreen_program_runs_total{program=\"square\",outcome=\"ok\"} 2",
+        "sunscreen_program_runs_total{program=\"square\",outcome=\"error\"} 1",
+        "sunscreen_program_run_duration_seconds_count{program=\"square\"} 3",
+        "sunscreen_evaluator_operations_total{operation=\"multiply\"} 2",
+        "sunscreen_evaluator_operations_total{operation=\"relinearize\"} 2",
+    ] {
+        assert!(text.lines().any(|l| l == line), "missing {}", line);
+    }
+}
@@ -0,0 +1,159 @@
+#![cfg(feature = "bfv-types")]
+
+use sunscreen::{
+    types::{
+        bfv::{Batched, Signed},
+        Cipher,
+    },
+    *,
+};
+
+#[fhe_program(scheme = "bfv")]
+fn add(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+    a + b
+}
+
+mod v1 {
+    use super::*;
+
+    #[fhe_program(scheme = "bfv")]
+    pub fn rotate(x: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+        x << 1
+    }
+}
+
+mod v2 {
+    use super::*;
+
+    #[fhe_program(scheme = "bfv")]
+    pub fn rotate(x: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+        x >> 2
+    }
+}
+
+#[test]
+fn identical_applications_have_empty_diff() {
+    let app = Compiler::new().fhe_program(add).compile().unwrap();
+
+    let diff = app.diff(&app);
+
+    assert!(diff.is_empty());
+    assert!(!diff.params_changed());
+}
+
+#[test]
+fn diff_reports_added_programs() {
+    #[fhe_program(scheme = "bfv")]
+    fn mul(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+        a * b
+    }
+
+    let old = Compiler::new()
+        .fhe_program(add)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let new = Compiler::new()
+        .fhe_program(add)
+        .fhe_program(mul)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let diff = new.diff(&old);
+    let changed = diff
+        .programs
+        .iter()
+        .filter(|p| p.name == "mul")
+        .collect::<Vec<_>>();
+
+    assert_eq!(changed.len(), 1);
+    assert!(changed[0].is_added());
+    assert_eq!(
+        changed[0].new_hash,
+        Some(fhe_program_hash(new.get_fhe_program(mul).unwrap()))
+    );
+    assert!(diff.required_keys.contains(&RequiredKeys::Relin));
+
+    let diff = old.diff(&new);
+
+    assert!(diff
+        .programs
+        .iter()
+        .any(|p| p.name == "mul" && p.is_removed()));
+}
+
+#[test]
+fn diff_reports_changed_rotations() {
+    let old = Compiler::new()
+        .fhe_program(v1::rotate)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let new = Compiler::new()
+        .fhe_program(v2::rotate)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let diff = new.diff(&old);
+
+    assert_eq!(diff.programs.len(), 1);
+
+    let program = &diff.programs[0];
+
+    assert_eq!(program.name, "rotate");
+    assert!(!program.signature_changed);
+    assert_ne!(program.old_hash, program.new_hash);
+    assert_eq!(program.added_rotations, vec![-2]);
+    assert_eq!(program.removed_rotations, vec![1]);
+}
+
+#[test]
+fn can_plan_migration_after_params_change() {
+    let old = Compiler::new()
+        .fhe_program(add)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let new = Compiler::new()
+        .fhe_program(add)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(1000))
+        .compile()
+        .unwrap();
+
+    let diff = new.diff(&old);
+
+    assert!(diff.params_changed());
+    assert_eq!(diff.programs.len(), 1);
+    assert!(!diff.programs[0].signature_changed);
+
+    let old_runtime = Runtime::new_fhe(old.params()).unwrap();
+    let new_runtime = Runtime::new_fhe(new.params()).unwrap();
+
+    let (old_public_key, _) = old_runtime.generate_keys().unwrap();
+    let (new_public_key, _) = new_runtime.generate_keys().unwrap();
+
+    let stale = old_runtime
+        .encrypt(Signed::from(1), &old_public_key)
+        .unwrap();
+    let fresh = new_runtime
+        .encrypt(Signed::from(1), &new_public_key)
+        .unwrap();
+
+    assert_eq!(diff.ciphertext_action(&stale), MigrationAction::Reencrypt);
+    assert_eq!(diff.ciphertext_action(&fresh), MigrationAction::Keep);
+    assert_eq!(diff.incompatible_ciphertexts([&fresh, &stale]), vec![1]);
+
+    assert_eq!(
+        diff.public_key_action(&old_public_key),
+        MigrationAction::RegenerateKeys
+    );
+    assert_eq!(
+        diff.public_key_action(&new_public_key),
+        MigrationAction::Keep
+    );
+}
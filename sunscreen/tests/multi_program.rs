@@ -1,3 +1,5 @@
+#![cfg(feature = "bfv-types")]
+
 use sunscreen::{
     types::{bfv::Signed, Cipher},
     *,
@@ -113,3 +115,75 @@ fn cant_compile_multiple_programs_with_same_name() {
         }
     };
 }
+
+#[test]
+fn groups_get_separate_params() {
+    #[fhe_program(scheme = "bfv")]
+    fn add(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+        a + b
+    }
+
+    #[fhe_program(scheme = "bfv")]
+    fn pow_8(a: Cipher<Signed>) -> Cipher<Signed> {
+        let a = a * a;
+        let a = a * a;
+
+        a * a
+    }
+
+    let app = Compiler::new()
+        .fhe_program(add)
+        .fhe_program_in_group(pow_8, "deep")
+        .compile()
+        .unwrap();
+
+    let add_params = &app.get_fhe_program(add).unwrap().metadata.params;
+    let pow_params = &app.get_fhe_program(pow_8).unwrap().metadata.params;
+
+    assert_eq!(app.params(), add_params);
+    assert!(add_params.lattice_dimension < pow_params.lattice_dimension);
+
+    let runtime = Runtime::new_fhe(pow_params).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = runtime.encrypt(Signed::from(2), &public_key).unwrap();
+
+    let result = runtime
+        .run(app.get_fhe_program(pow_8).unwrap(), vec![a], &public_key)
+        .unwrap();
+
+    let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, 256.into());
+}
+
+#[test]
+fn can_override_program_params() {
+    #[fhe_program(scheme = "bfv")]
+    fn add(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+        a + b
+    }
+
+    #[fhe_program(scheme = "bfv")]
+    fn mul(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+        a * b
+    }
+
+    let mul_app = Compiler::new()
+        .fhe_program(mul)
+        .additional_noise_budget(40)
+        .compile()
+        .unwrap();
+
+    let app = Compiler::new()
+        .fhe_program_with_params(mul, mul_app.params())
+        .fhe_program(add)
+        .compile()
+        .unwrap();
+
+    assert_eq!(
+        app.get_fhe_program(mul).unwrap().metadata.params,
+        *mul_app.params()
+    );
+    assert_ne!(app.params(), mul_app.params());
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "bfv-types")]
+
+use sunscreen::{
+    fhe_program,
+    types::{
+        bfv::{Batched, Signed},
+        Cipher,
+    },
+    Compiler, FheProgramInput, PlainModulusConstraint, Runtime,
+};
+
+#[fhe_program(scheme = "bfv", plain)]
+fn poly(a: Cipher<Signed>, b: Cipher<Signed>, c: Signed) -> (Cipher<Signed>, Cipher<Signed>) {
+    (a * b + c - 7i64, a - b * 2i64)
+}
+
+#[fhe_program(scheme = "bfv", plain)]
+fn rotate_mul(x: Cipher<Batched<4>>, y: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+    (x << 1) * y
+}
+
+#[test]
+fn plain_fn_matches_fhe_program() {
+    let app = Compiler::new().fhe_program(poly).compile().unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let plain = poly.as_plain_fn();
+
+    for (a, b, c) in [(3, 4, 5), (-6, 2, 0), (0, -9, -11)] {
+        let (a, b, c) = (Signed::from(a), Signed::from(b), Signed::from(c));
+
+        let args: Vec<FheProgramInput> = vec![
+            runtime.encrypt(a, &public_key).unwrap().into(),
+            runtime.encrypt(b, &public_key).unwrap().into(),
+            c.into(),
+        ];
+
+        let result = runtime
+            .run(app.get_fhe_program(poly).unwrap(), args, &public_key)
+            .unwrap();
+
+        let x: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+        let y: Signed = runtime.decrypt(&result[1], &private_key).unwrap();
+
+        assert_eq!((x, y), plain(a, b, c));
+    }
+}
+
+#[test]
+fn plain_fn_supports_batched_rotations() {
+    let app = Compiler::new()
+        .fhe_program(rotate_mul)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let x = Batched::<4>::try_from([vec![1, 2, 3, 4], vec![5, 6, 7, 8]]).unwrap();
+    let y = Batched::<4>::try_from([vec![-1, 2, -3, 4], vec![0, 1, 0, 1]]).unwrap();
+
+    let args: Vec<FheProgramInput> = vec![
+        runtime.encrypt(x, &public_key).unwrap().into(),
+        runtime.encrypt(y, &public_key).unwrap().into(),
+    ];
+
+    let result = runtime
+        .run(app.get_fhe_program(rotate_mul).unwrap(), args, &public_key)
+        .unwrap();
+
+    let z: Batched<4> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(z, rotate_mul.as_plain_fn()(x, y));
+}
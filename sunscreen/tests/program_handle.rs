@@ -0,0 +1,61 @@
+#![cfg(feature = "bfv-types")]
+
+use sunscreen::{
+    types::{bfv::Signed, Cipher},
+    *,
+};
+
+#[fhe_program(scheme = "bfv")]
+fn dot(a: [Cipher<Signed>; 3], b: [Signed; 3], c: Cipher<Signed>) -> Cipher<Signed> {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2] + c
+}
+
+#[fhe_program(scheme = "bfv")]
+fn negate(a: Cipher<Signed>) -> Cipher<Signed> {
+    -a
+}
+
+#[test]
+fn can_run_through_handle() {
+    let app = Compiler::new()
+        .fhe_program(dot)
+        .fhe_program(negate)
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = runtime
+        .encrypt(
+            [Signed::from(1), Signed::from(2), Signed::from(3)],
+            &public_key,
+        )
+        .unwrap();
+    let b = [Signed::from(4), Signed::from(5), Signed::from(6)];
+    let c = runtime.encrypt(Signed::from(7), &public_key).unwrap();
+
+    let handle: FheProgramHandle<(Ciphertext, [Signed; 3], Ciphertext)> =
+        app.get_fhe_program_handle(dot).unwrap();
+
+    assert_eq!(handle.program().metadata.signature, dot.signature());
+
+    let result = handle.run(&runtime, (a, b, c), &public_key).unwrap();
+
+    let negated = app
+        .get_fhe_program_handle(negate)
+        .unwrap()
+        .run(&runtime, (result[0].clone(),), &public_key)
+        .unwrap();
+
+    let x: Signed = runtime.decrypt(&negated[0], &private_key).unwrap();
+
+    assert_eq!(x, (-39).into());
+}
+
+#[test]
+fn missing_program_has_no_handle() {
+    let app = Compiler::new().fhe_program(negate).compile().unwrap();
+
+    assert!(app.get_fhe_program_handle(dot).is_none());
+}
@@ -0,0 +1,156 @@
+#![cfg(feature = "bfv-types")]
+
+use std::io::BufReader;
+
+use sunscreen::{
+    fhe_program,
+    types::{bfv::Signed, Cipher},
+    Ciphertext, Compiler, FheApplication, ReEncryptionKey, ReEncryptor, Runtime, RuntimeError,
+};
+
+#[fhe_program(scheme = "bfv")]
+fn square(a: Cipher<Signed>) -> Cipher<Signed> {
+    a * a
+}
+
+fn compile() -> FheApplication {
+    Compiler::new()
+        .fhe_program(square)
+        .additional_noise_budget(64)
+        .compile()
+        .unwrap()
+}
+
+#[test]
+fn auditor_can_decrypt_reencrypted_records() {
+    let app = compile();
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (tenant_public_key, tenant_private_key) = runtime.generate_keys().unwrap();
+    let (auditor_public_key, auditor_private_key) = runtime.generate_keys().unwrap();
+
+    let values = (-5..5i64).collect::<Vec<_>>();
+
+    let records = values
+        .iter()
+        .map(|v| {
+            let x = runtime
+                .encrypt(Signed::from(*v), &tenant_public_key)
+                .unwrap();
+
+            runtime
+                .run(
+                    app.get_fhe_program(square).unwrap(),
+                    vec![x],
+                    &tenant_public_key,
+                )
+                .unwrap()
+                .remove(0)
+        })
+        .collect::<Vec<_>>();
+
+    let key = runtime
+        .generate_reencryption_key(&tenant_private_key, &auditor_public_key)
+        .unwrap();
+
+    // The key travels to the proxy, which never sees a private key.
+    let key: ReEncryptionKey = bincode::deserialize(&bincode::serialize(&key).unwrap()).unwrap();
+    let reencryptor = ReEncryptor::new(&key).unwrap();
+
+    let shared = reencryptor.reencrypt_all(&records).unwrap();
+
+    for (c, v) in shared.iter().zip(values.iter()) {
+        let x: Signed = runtime.decrypt(c, &auditor_private_key).unwrap();
+
+        assert_eq!(x, Signed::from(v * v));
+    }
+
+    // Re-encrypted ciphertexts work with programs run under the new keys.
+    let result = runtime
+        .run(
+            app.get_fhe_program(square).unwrap(),
+            vec![shared[3].clone()],
+            &auditor_public_key,
+        )
+        .unwrap();
+
+    let x: Signed = runtime.decrypt(&result[0], &auditor_private_key).unwrap();
+
+    assert_eq!(x, Signed::from(16));
+}
+
+#[test]
+fn can_reencrypt_stream() {
+    let app = compile();
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (tenant_public_key, tenant_private_key) = runtime.generate_keys().unwrap();
+    let (auditor_public_key, auditor_private_key) = runtime.generate_keys().unwrap();
+
+    let mut archive = vec![];
+
+    for v in 0..4i64 {
+        let c = runtime
+            .encrypt(Signed::from(v), &tenant_public_key)
+            .unwrap();
+
+        bincode::serialize_into(&mut archive, &c).unwrap();
+    }
+
+    let key = runtime
+        .generate_reencryption_key_with_decomposition(&tenant_private_key, &auditor_public_key, 4)
+        .unwrap();
+
+    assert_eq!(key.decomposition_bits(), 4);
+
+    let mut output = vec![];
+    let count = ReEncryptor::new(&key)
+        .unwrap()
+        .reencrypt_stream(BufReader::new(archive.as_slice()), &mut output)
+        .unwrap();
+
+    assert_eq!(count, 4);
+
+    let mut reader = output.as_slice();
+
+    for v in 0..4i64 {
+        let c: Ciphertext = bincode::deserialize_from(&mut reader).unwrap();
+        let x: Signed = runtime.decrypt(&c, &auditor_private_key).unwrap();
+
+        assert_eq!(x, Signed::from(v));
+    }
+
+    assert!(reader.is_empty());
+}
+
+#[test]
+fn rejects_keys_for_other_parameters() {
+    let app = compile();
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let other_app = Compiler::new()
+        .fhe_program(square)
+        .additional_noise_budget(128)
+        .compile()
+        .unwrap();
+    let other_runtime = Runtime::new_fhe(other_app.params()).unwrap();
+    let (other_public_key, _) = other_runtime.generate_keys().unwrap();
+
+    assert!(matches!(
+        runtime.generate_reencryption_key(&private_key, &other_public_key),
+        Err(RuntimeError::KeyParameterMismatch)
+    ));
+
+    let key = runtime
+        .generate_reencryption_key(&private_key, &public_key)
+        .unwrap();
+    let x = other_runtime
+        .encrypt(Signed::from(1), &other_public_key)
+        .unwrap();
+
+    assert!(matches!(
+        ReEncryptor::new(&key).unwrap().reencrypt(&x),
+        Err(RuntimeError::ParameterMismatch)
+    ));
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "fhe")]
+
+use seal_fhe::{
+    BfvEncryptionParametersBuilder, CoefficientModulus, Context, PlainModulus, SecurityLevel,
+};
+use sunscreen::protocols::psi::{PsiReceiver, PsiSender};
+
+fn context() -> Context {
+    let params = BfvEncryptionParametersBuilder::new()
+        .set_poly_modulus_degree(8192)
+        .set_coefficient_modulus(
+            CoefficientModulus::bfv_default(8192, SecurityLevel::TC128).unwrap(),
+        )
+        .set_plain_modulus(PlainModulus::batching(8192, 30).unwrap())
+        .build()
+        .unwrap();
+
+    Context::new(&params, true, SecurityLevel::TC128).unwrap()
+}
+
+#[test]
+fn can_intersect_sets() {
+    let ctx = context();
+
+    let receiver_items = (0..100u32)
+        .map(|i| format!("item-{}", i * 3))
+        .collect::<Vec<_>>();
+    let sender_items = (0..200u32)
+        .map(|i| format!("item-{}", i * 2))
+        .collect::<Vec<_>>();
+
+    let receiver = PsiReceiver::new(&ctx, &receiver_items).unwrap();
+    // Bins hold more than 4 values, so the sender splits them into
+    // partitions.
+    let sender = PsiSender::new(&ctx, &sender_items, 4).unwrap();
+
+    let response = sender.respond(&receiver.query().unwrap()).unwrap();
+
+    // Multiples of 6 below 300 and 400.
+    let expected = (0..100).filter(|i| i % 2 == 0).collect::<Vec<usize>>();
+
+    assert_eq!(receiver.intersection(&response).unwrap(), expected);
+}
+
+#[test]
+fn duplicates_and_disjoint_sets() {
+    let ctx = context();
+
+    let receiver = PsiReceiver::new(&ctx, &["a", "b", "a"]).unwrap();
+
+    let sender = PsiSender::new(&ctx, &["a"], 2).unwrap();
+    let response = sender.respond(&receiver.query().unwrap()).unwrap();
+
+    assert_eq!(receiver.intersection(&response).unwrap(), vec![0, 2]);
+
+    let sender = PsiSender::new(&ctx, &["c", "d"], 2).unwrap();
+    let response = sender.respond(&receiver.query().unwrap()).unwrap();
+
+    assert!(receiver.intersection(&response).unwrap().is_empty());
+}
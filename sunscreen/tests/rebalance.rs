@@ -0,0 +1,54 @@
+#![cfg(feature = "bfv-types")]
+
+use sunscreen::{
+    types::{bfv::Signed, Cipher},
+    *,
+};
+
+#[fhe_program(scheme = "bfv")]
+fn product(
+    a: Cipher<Signed>,
+    b: Cipher<Signed>,
+    c: Cipher<Signed>,
+    d: Cipher<Signed>,
+) -> Cipher<Signed> {
+    a * b * c * d
+}
+
+#[test]
+fn rebalancing_reduces_reported_depth() {
+    let app = Compiler::new()
+        .fhe_program(product)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(64))
+        .compile()
+        .unwrap();
+
+    let program = app.get_fhe_program(product).unwrap();
+
+    assert_eq!(
+        program.metadata.multiplicative_depth,
+        DepthReport {
+            before: 3,
+            after: 2
+        }
+    );
+    assert_eq!(program.graph().multiplicative_depth(), 2);
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let args: Vec<FheProgramInput> = [2, -3, 5, 7]
+        .iter()
+        .map(|x| {
+            runtime
+                .encrypt(Signed::from(*x), &public_key)
+                .unwrap()
+                .into()
+        })
+        .collect();
+
+    let result = runtime.run(program, args, &public_key).unwrap();
+    let c: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, Signed::from(-210));
+}
@@ -0,0 +1,169 @@
+#![cfg(feature = "bfv-types")]
+
+use std::collections::BTreeMap;
+
+use sunscreen::{
+    types::{bfv::Signed, Cipher},
+    *,
+};
+
+#[fhe_program(scheme = "bfv")]
+fn add(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+    a + b
+}
+
+fn compile(plain_modulus: u64) -> FheApplication {
+    Compiler::new()
+        .fhe_program(add)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(plain_modulus))
+        .compile()
+        .unwrap()
+}
+
+#[test]
+fn can_reencrypt_incompatible_ciphertexts_in_batches() {
+    let old = compile(64);
+    let new = compile(4096);
+
+    let old_runtime = Runtime::new_fhe(old.params()).unwrap();
+    let new_runtime = Runtime::new_fhe(new.params()).unwrap();
+
+    let (old_public_key, old_private_key) = old_runtime.generate_keys().unwrap();
+    let (new_public_key, new_private_key) = new_runtime.generate_keys().unwrap();
+
+    let mut store = BTreeMap::new();
+
+    for i in 0..5 {
+        let c = old_runtime
+            .encrypt(Signed::from(i), &old_public_key)
+            .unwrap();
+
+        store.insert(i, c);
+    }
+
+    // Already migrated.
+    store.insert(
+        5,
+        new_runtime
+            .encrypt(Signed::from(5), &new_public_key)
+            .unwrap(),
+    );
+
+    let diff = new.diff(&old);
+
+    let mut planner = ReencryptionPlanner::new(&diff, &mut store)
+        .unwrap()
+        .batch_size(2);
+
+    assert_eq!(planner.pending(), &[0, 1, 2, 3, 4]);
+
+    let mut client = ClientAssisted(|batch: &[Ciphertext]| -> Result<Vec<Ciphertext>> {
+        batch
+            .iter()
+            .map(|c| {
+                let x: Signed = old_runtime.decrypt(c, &old_private_key)?;
+
+                Ok(new_runtime.encrypt(x, &new_public_key)?)
+            })
+            .collect()
+    });
+
+    let mut reports = vec![];
+
+    let progress = planner
+        .run(&mut client, |p| reports.push(p.completed))
+        .unwrap();
+
+    assert_eq!(reports, vec![2, 4, 5]);
+    assert!(progress.is_done());
+    assert_eq!(progress.failed, 0);
+    assert_eq!(planner.completed(), &[0, 1, 2, 3, 4]);
+
+    for (i, c) in &store {
+        assert_eq!(diff.ciphertext_action(c), MigrationAction::Keep);
+
+        let x: Signed = new_runtime.decrypt(c, &new_private_key).unwrap();
+        assert_eq!(x, Signed::from(*i));
+    }
+}
+
+#[test]
+fn records_failures_and_resumes() {
+    let old = compile(64);
+    let new = compile(4096);
+
+    let old_runtime = Runtime::new_fhe(old.params()).unwrap();
+    let (old_public_key, _) = old_runtime.generate_keys().unwrap();
+
+    let mut store = BTreeMap::new();
+
+    for i in 0..3 {
+        store.insert(
+            i,
+            old_runtime
+                .encrypt(Signed::from(i), &old_public_key)
+                .unwrap(),
+        );
+    }
+
+    let diff = new.diff(&old);
+
+    // Returning the ciphertexts unchanged leaves them incompatible.
+    let mut identity =
+        ClientAssisted(|batch: &[Ciphertext]| -> Result<Vec<Ciphertext>> { Ok(batch.to_vec()) });
+
+    let mut planner = ReencryptionPlanner::new(&diff, &mut store)
+        .unwrap()
+        .skip(vec![0]);
+
+    let progress = planner.run(&mut identity, |_| {}).unwrap();
+
+    assert_eq!(
+        progress,
+        ReencryptionProgress {
+            total: 2,
+            completed: 0,
+            failed: 2
+        }
+    );
+    assert_eq!(
+        planner.failures()[0],
+        (1, Error::RuntimeError(RuntimeError::ParameterMismatch))
+    );
+
+    planner.retry_failed();
+
+    assert_eq!(planner.pending(), &[1, 2]);
+}
+
+#[test]
+fn can_rotate_keys_for_whole_store() {
+    let app = compile(64);
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (old_public_key, old_private_key) = runtime.generate_keys().unwrap();
+    let (new_public_key, new_private_key) = runtime.generate_keys().unwrap();
+
+    let mut store = BTreeMap::new();
+
+    for i in 0..3 {
+        store.insert(
+            format!("row-{}", i),
+            runtime.encrypt(Signed::from(i), &old_public_key).unwrap(),
+        );
+    }
+
+    let mut rotation = KeyRotation::new(&runtime, &old_private_key, &new_public_key).unwrap();
+
+    let progress = ReencryptionPlanner::all(&mut store)
+        .unwrap()
+        .run(&mut rotation, |_| {})
+        .unwrap();
+
+    assert_eq!(progress.completed, 3);
+
+    for (i, c) in store.values().enumerate() {
+        let x: Signed = runtime.decrypt(c, &new_private_key).unwrap();
+        assert_eq!(x, Signed::from(i as i64));
+    }
+}
@@ -0,0 +1,89 @@
+#![cfg(feature = "bfv-types")]
+
+use sunscreen::{
+    fhe_program,
+    types::{bfv::Signed, Cipher},
+    Compiler, FheProgramInput, RelinearizationPolicy, RequiredKeys, Runtime,
+};
+
+#[fhe_program(scheme = "bfv")]
+fn dot(
+    a: Cipher<Signed>,
+    b: Cipher<Signed>,
+    c: Cipher<Signed>,
+    d: Cipher<Signed>,
+) -> Cipher<Signed> {
+    a * b + c * d
+}
+
+#[fhe_program(scheme = "bfv")]
+fn cube(a: Cipher<Signed>) -> Cipher<Signed> {
+    a * a * a
+}
+
+fn run(policy: RelinearizationPolicy) -> (i64, i64, Vec<RequiredKeys>) {
+    let app = Compiler::new()
+        .fhe_program(dot)
+        .fhe_program(cube)
+        .relinearization_policy(policy)
+        .verify_passes()
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let encrypt = |x: i64| -> FheProgramInput {
+        runtime
+            .encrypt(Signed::from(x), &public_key)
+            .unwrap()
+            .into()
+    };
+
+    let result = runtime
+        .run(
+            app.get_fhe_program(dot).unwrap(),
+            vec![encrypt(2), encrypt(3), encrypt(-4), encrypt(5)],
+            &public_key,
+        )
+        .unwrap();
+    let dot_result: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    let cube_program = app.get_fhe_program(cube).unwrap();
+
+    let result = runtime
+        .run(cube_program, vec![encrypt(-3)], &public_key)
+        .unwrap();
+    let cube_result: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    (
+        dot_result.into(),
+        cube_result.into(),
+        cube_program.metadata.required_keys.clone(),
+    )
+}
+
+#[test]
+fn policies_compute_the_same_results() {
+    for policy in [
+        RelinearizationPolicy::Always,
+        RelinearizationPolicy::Lazy,
+        RelinearizationPolicy::SizeExceeds(4),
+    ] {
+        let (dot, cube, _) = run(policy);
+
+        assert_eq!(dot, -14);
+        assert_eq!(cube, -27);
+    }
+}
+
+#[test]
+fn size_limit_can_skip_relinearization_keys() {
+    let (_, _, keys) = run(RelinearizationPolicy::Always);
+
+    assert!(keys.contains(&RequiredKeys::Relin));
+
+    let (_, _, keys) = run(RelinearizationPolicy::SizeExceeds(4));
+
+    assert!(!keys.contains(&RequiredKeys::Relin));
+}
@@ -0,0 +1,62 @@
+#![cfg(feature = "bfv-types")]
+
+use sunscreen::{
+    types::{bfv::Signed, Cipher},
+    *,
+};
+
+#[fhe_program(scheme = "bfv")]
+fn long(a: Cipher<Signed>) -> Cipher<Signed> {
+    let mut x = a;
+
+    for _ in 0..16 {
+        x = x + a;
+    }
+
+    x
+}
+
+#[fhe_program(scheme = "bfv")]
+fn short(a: Cipher<Signed>) -> Cipher<Signed> {
+    a * a
+}
+
+#[test]
+fn can_run_concurrent_programs_through_scheduler() {
+    for fairness in [Fairness::Weighted, Fairness::Strict] {
+        let app = Compiler::new()
+            .fhe_program(long)
+            .fhe_program(short)
+            .compile()
+            .unwrap();
+
+        let runtime = Runtime::new_fhe(app.params()).unwrap();
+        let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+        let scheduler = FairScheduler::new(1, fairness);
+        let a = runtime.encrypt(Signed::from(3), &public_key).unwrap();
+
+        let run = |name: &str, priority| -> Signed {
+            let options = RunOptions {
+                schedule: Some((&scheduler, priority)),
+                ..Default::default()
+            };
+
+            let result = runtime
+                .run_with_options(
+                    app.get_fhe_program(name).unwrap(),
+                    vec![a.clone()],
+                    &public_key,
+                    &options,
+                )
+                .unwrap();
+
+            runtime.decrypt(&result[0], &private_key).unwrap()
+        };
+
+        let (l, s) = rayon::join(|| run("long", 1), || run("short", 4));
+
+        assert_eq!(l, Signed::from(51));
+        assert_eq!(s, Signed::from(9));
+    }
+}
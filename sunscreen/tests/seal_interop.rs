@@ -0,0 +1,179 @@
+#![cfg(feature = "bfv-types")]
+
+use seal_fhe::{
+    BFVEncoder, BFVEvaluator, BfvEncryptionParametersBuilder, Ciphertext as SealCiphertext,
+    Context as SealContext, Encryptor, Evaluator, Modulus,
+};
+use sunscreen::{
+    types::{bfv::Batched, Cipher},
+    *,
+};
+
+#[fhe_program(scheme = "bfv")]
+fn add(a: Cipher<Batched<4>>, b: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+    a + b
+}
+
+fn seal_context(params: &Params) -> SealContext {
+    let encryption_params = BfvEncryptionParametersBuilder::new()
+        .set_poly_modulus_degree(params.lattice_dimension)
+        .set_plain_modulus(Modulus::new(params.plain_modulus).unwrap())
+        .set_coefficient_modulus(
+            params
+                .coeff_modulus
+                .iter()
+                .map(|x| Modulus::new(*x).unwrap())
+                .collect(),
+        )
+        .build()
+        .unwrap();
+
+    SealContext::new(&encryption_params, true, params.security_level).unwrap()
+}
+
+#[test]
+fn can_run_program_on_seal_ciphertexts() {
+    let app = Compiler::new()
+        .fhe_program(add)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(20))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    // Encrypt the way an existing SEAL-based service would, repeating each
+    // row to fill the slots as Batched does.
+    let context = seal_context(app.params());
+    let encoder = BFVEncoder::new(&context).unwrap();
+    let encryptor = Encryptor::with_public_key(&context, &public_key.public_key.data).unwrap();
+
+    let reps = app.params().lattice_dimension as usize / 8;
+
+    let encrypt = |rows: [[i64; 4]; 2]| {
+        let data = [rows[0].repeat(reps), rows[1].repeat(reps)].concat();
+
+        encryptor
+            .encrypt(&encoder.encode_signed(&data).unwrap())
+            .unwrap()
+    };
+
+    let a = encrypt([[1, 2, 3, 4], [5, 6, 7, 8]]);
+    let b = encrypt([[10, 20, 30, 40], [50, 60, 70, 80]]);
+
+    let mut result = runtime
+        .run_seal(
+            app.get_fhe_program(add).unwrap(),
+            vec![vec![a.clone()], vec![b]],
+            &public_key,
+        )
+        .unwrap();
+
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].len(), 1);
+
+    let c = runtime
+        .ciphertext_from_seal::<Batched<4>>(result.remove(0))
+        .unwrap();
+    let c: Batched<4> = runtime.decrypt(&c, &private_key).unwrap();
+
+    assert_eq!(c, Batched::from([[11, 22, 33, 44], [55, 66, 77, 88]]));
+
+    // Round trip through the wrapped representation.
+    let wrapped = runtime
+        .ciphertext_from_seal::<Batched<4>>(vec![a.clone()])
+        .unwrap();
+
+    assert!(runtime.ciphertext_to_seal(wrapped).unwrap() == vec![a]);
+}
+
+#[test]
+fn rejects_invalid_seal_ciphertexts() {
+    let app = Compiler::new()
+        .fhe_program(add)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(20))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, _) = runtime.generate_keys().unwrap();
+
+    let empty = SealCiphertext::new().unwrap();
+
+    assert!(matches!(
+        runtime.ciphertext_from_seal::<Batched<4>>(vec![empty.clone()]),
+        Err(RuntimeError::ParameterMismatch)
+    ));
+
+    assert!(matches!(
+        runtime.run_seal(
+            app.get_fhe_program(add).unwrap(),
+            vec![vec![empty]],
+            &public_key
+        ),
+        Err(RuntimeError::IncorrectCiphertextCount)
+    ));
+
+    // Ciphertexts encrypted under other parameters can't be unwrapped.
+    let other_app = Compiler::new()
+        .fhe_program(add)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(24))
+        .compile()
+        .unwrap();
+
+    let other_runtime = Runtime::new_fhe(other_app.params()).unwrap();
+    let (other_public_key, _) = other_runtime.generate_keys().unwrap();
+
+    let c = other_runtime
+        .encrypt(Batched::<4>::from(1), &other_public_key)
+        .unwrap();
+
+    assert!(matches!(
+        runtime.ciphertext_to_seal(c),
+        Err(RuntimeError::ParameterMismatch)
+    ));
+}
+
+#[test]
+fn can_drop_to_seal_and_back() {
+    let app = Compiler::new()
+        .fhe_program(add)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(20))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = runtime
+        .encrypt(
+            Batched::<4>::from([[1, 2, 3, 4], [5, 6, 7, 8]]),
+            &public_key,
+        )
+        .unwrap();
+
+    let mut raw = SealCiphertexts::try_from(a).unwrap();
+
+    assert_eq!(raw.params, *app.params());
+    assert_eq!(raw.ciphertexts.len(), 1);
+
+    // A custom step on the raw ciphertexts.
+    let context = seal_context(app.params());
+    let evaluator = BFVEvaluator::new(&context).unwrap();
+
+    evaluator.negate_inplace(&mut raw.ciphertexts[0]).unwrap();
+
+    let a: Ciphertext = raw.into();
+
+    let result = runtime
+        .run(
+            app.get_fhe_program(add).unwrap(),
+            vec![a.clone(), a],
+            &public_key,
+        )
+        .unwrap();
+
+    let c: Batched<4> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, Batched::from([[-2, -4, -6, -8], [-10, -12, -14, -16]]));
+}
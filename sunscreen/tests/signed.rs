@@ -1,3 +1,5 @@
+#![cfg(feature = "bfv-types")]
+
 use sunscreen::{
     fhe_program,
     types::{bfv::Signed, Cipher},
@@ -517,3 +519,44 @@ fn can_mul_literal_cipher() {
 fn can_create_default() {
     assert_eq!(Into::<i64>::into(Signed::default()), 0);
 }
+
+#[test]
+fn can_select_and_flag_results() {
+    #[fhe_program(scheme = "bfv")]
+    fn guarded(a: Cipher<Signed>, in_range: Cipher<Signed>) -> (Cipher<Signed>, Cipher<Signed>) {
+        let doubled = a + a;
+
+        (in_range.select(doubled, a), in_range)
+    }
+
+    let app = Compiler::new()
+        .fhe_program(guarded)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    for (flag, expected) in [(1, Some(Signed::from(30))), (0, None)] {
+        let a_c = runtime.encrypt(Signed::from(15), &public_key).unwrap();
+        let flag_c = runtime.encrypt(Signed::from(flag), &public_key).unwrap();
+        let args: Vec<FheProgramInput> = vec![a_c.into(), flag_c.into()];
+
+        let result = runtime
+            .run(app.get_fhe_program(guarded).unwrap(), args, &public_key)
+            .unwrap();
+
+        let c: Option<Signed> = runtime
+            .decrypt_flagged::<Signed, Signed>(&result[0], &result[1], &private_key)
+            .unwrap();
+
+        assert_eq!(c, expected);
+
+        let selected: Signed = runtime.decrypt(&result[0], &private_key).unwrap();
+
+        assert_eq!(selected, Signed::from(15 * (1 + flag)));
+    }
+}
@@ -1,7 +1,9 @@
+#![cfg(feature = "bfv-types")]
+
 use sunscreen::{
     fhe_program,
-    types::{bfv::Batched, Cipher, SwapRows},
-    Compiler, FheProgramInput, PlainModulusConstraint, Runtime,
+    types::{bfv::Batched, Cipher, FoldColumns, Rotate, SwapRows},
+    Compiler, FheProgramInput, FheProgramOperation, PlainModulusConstraint, Runtime,
 };
 
 use std::ops::*;
@@ -301,3 +303,284 @@ fn can_neg_cipher_cipher() {
 
     assert_eq!(c, neg_impl(a));
 }
+
+#[test]
+fn can_decrypt_signed_range() {
+    #[fhe_program(scheme = "bfv")]
+    fn add(a: Cipher<Batched<4>>, b: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+        a + b
+    }
+
+    let app = Compiler::new()
+        .fhe_program(add)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = Batched::<4>::try_from([vec![1, 2, 3, 4], vec![5, 6, 7, 8]]).unwrap();
+    let b = Batched::<4>::try_from([vec![5, 6, 7, 8], vec![1, 2, 3, 4]]).unwrap();
+    let a_c = runtime.encrypt(a, &public_key).unwrap();
+    let b_c = runtime.encrypt(b, &public_key).unwrap();
+
+    let args: Vec<FheProgramInput> = vec![a_c.into(), b_c.into()];
+
+    let result = runtime
+        .run(app.get_fhe_program(add).unwrap(), args, &public_key)
+        .unwrap();
+
+    let mut out = [0i64; 2];
+
+    runtime
+        .decrypt_signed_range(&result[0], &private_key, 1..3, &mut out)
+        .unwrap();
+
+    assert_eq!(out, [8, 10]);
+}
+
+#[test]
+fn can_compute_windowed_sum_cipher() {
+    use sunscreen::applications::timeseries::windowed_sum;
+
+    #[fhe_program(scheme = "bfv")]
+    fn window_3(a: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+        windowed_sum(a, 3)
+    }
+
+    let app = Compiler::new()
+        .fhe_program(window_3)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let data = [vec![1, 2, 3, 4], vec![5, 6, 7, 8]];
+
+    let a = Batched::<4>::try_from(data).unwrap();
+    let a_c = runtime.encrypt(a, &public_key).unwrap();
+
+    let args: Vec<FheProgramInput> = vec![a_c.into()];
+
+    let result = runtime
+        .run(app.get_fhe_program(window_3).unwrap(), args, &public_key)
+        .unwrap();
+
+    let c: Batched<4> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    let expected = [vec![8, 7, 6, 9], vec![20, 19, 18, 21]];
+
+    assert_eq!(c, windowed_sum(a, 3));
+    assert_eq!(c, expected.try_into().unwrap());
+}
+
+#[test]
+fn can_subtract_negative_literals() {
+    #[fhe_program(scheme = "bfv")]
+    fn sub(a: Cipher<Batched<4>>) -> (Cipher<Batched<4>>, Cipher<Batched<4>>) {
+        (a - 3i64 - (-5i64) + (-1i64), -7i64 - a)
+    }
+
+    let app = Compiler::new()
+        .fhe_program(sub)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let data = [vec![1, -2, 3, -4], vec![5, -6, 7, -8]];
+
+    let a = Batched::<4>::try_from(data).unwrap();
+    let a_c = runtime.encrypt(a, &public_key).unwrap();
+
+    let args: Vec<FheProgramInput> = vec![a_c.into()];
+
+    let result = runtime
+        .run(app.get_fhe_program(sub).unwrap(), args, &public_key)
+        .unwrap();
+
+    let c: Batched<4> = runtime.decrypt(&result[0], &private_key).unwrap();
+    let d: Batched<4> = runtime.decrypt(&result[1], &private_key).unwrap();
+
+    assert_eq!(c, a - 3 - (-5) + (-1));
+    assert_eq!(
+        c,
+        [vec![2, -1, 4, -3], vec![6, -5, 8, -7]].try_into().unwrap()
+    );
+    assert_eq!(
+        d,
+        [vec![-8, -5, -10, -3], vec![-12, -1, -14, 1]]
+            .try_into()
+            .unwrap()
+    );
+}
+
+#[test]
+fn batched_values_must_fit_plain_modulus() {
+    #[fhe_program(scheme = "bfv")]
+    fn add(a: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+        a + 1i64
+    }
+
+    let app = Compiler::new()
+        .fhe_program(add)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let t = app.params().plain_modulus as i64;
+
+    // t - 1 and -(t - 1) are the largest values in Z_t and map to -1 and
+    // 1 respectively.
+    let a = Batched::<4>::try_from([vec![t - 1, 1 - t, 0, 0], vec![0; 4]]).unwrap();
+    let a_c = runtime.encrypt(a, &public_key).unwrap();
+
+    let result = runtime
+        .run(
+            app.get_fhe_program(add).unwrap(),
+            vec![a_c.into()],
+            &public_key,
+        )
+        .unwrap();
+
+    let c: Batched<4> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    assert_eq!(c, [vec![0, 2, 1, 1], vec![1, 1, 1, 1]].try_into().unwrap());
+
+    for v in [t, -t, i64::MAX, i64::MIN] {
+        let a = Batched::<4>::from(v);
+
+        assert!(runtime.encrypt(a, &public_key).is_err());
+    }
+}
+
+#[test]
+fn can_rotate_with_methods_and_plain_operands() {
+    fn dot_impl<T>(a: T, b: T) -> T
+    where
+        T: Mul<T, Output = T> + Add<T, Output = T> + Rotate<Output = T> + Copy,
+    {
+        let c = a * b;
+        let c = c + c.rotate_left(2);
+
+        c + c.rotate_right(5)
+    }
+
+    #[fhe_program(scheme = "bfv")]
+    fn dot(
+        a: Cipher<Batched<4>>,
+        b: Cipher<Batched<4>>,
+        offset: Batched<4>,
+        bias: Batched<4>,
+    ) -> Cipher<Batched<4>> {
+        dot_impl(a, b) + offset - bias
+    }
+
+    let app = Compiler::new()
+        .fhe_program(dot)
+        .additional_noise_budget(5)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = Batched::<4>::try_from([vec![1, 2, 3, 4], vec![5, 6, 7, 8]]).unwrap();
+    let b = Batched::<4>::try_from([vec![4, 3, 2, 1], vec![1, 1, 1, 1]]).unwrap();
+    let offset = Batched::<4>::try_from([vec![100, 0, 0, 0], vec![0; 4]]).unwrap();
+    let bias = offset.swap_rows();
+
+    let args: Vec<FheProgramInput> = vec![
+        runtime.encrypt(a, &public_key).unwrap().into(),
+        runtime.encrypt(b, &public_key).unwrap().into(),
+        offset.into(),
+        bias.into(),
+    ];
+
+    let result = runtime
+        .run(app.get_fhe_program(dot).unwrap(), args, &public_key)
+        .unwrap();
+
+    let c: Batched<4> = runtime.decrypt(&result[0], &private_key).unwrap();
+
+    // Every lane of each row holds that row's dot product.
+    let expected = [vec![120, 20, 20, 20], vec![-74, 26, 26, 26]];
+
+    assert_eq!(c, dot_impl(a, b) + offset - bias);
+    assert_eq!(c, expected.try_into().unwrap());
+}
+
+#[test]
+fn can_fold_columns_cipher() {
+    #[fhe_program(scheme = "bfv")]
+    fn fold(a: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+        a.fold_columns()
+    }
+
+    #[fhe_program(scheme = "bfv")]
+    fn fold_and_swap(a: Cipher<Batched<4>>) -> Cipher<Batched<4>> {
+        // The fold's rows are equal, so the compiler drops the swap.
+        (a.fold_columns() << 1).swap_rows()
+    }
+
+    let app = Compiler::new()
+        .fhe_program(fold)
+        .fhe_program(fold_and_swap)
+        .plain_modulus_constraint(PlainModulusConstraint::BatchingMinimum(0))
+        .compile()
+        .unwrap();
+
+    let swaps = |name: &str| {
+        app.get_fhe_program(name)
+            .unwrap()
+            .graph()
+            .nodes()
+            .iter()
+            .filter(|n| *n.operation == FheProgramOperation::SwapRows)
+            .count()
+    };
+
+    assert_eq!(swaps("fold"), 1);
+    assert_eq!(swaps("fold_and_swap"), 1);
+    assert_eq!(app.get_fhe_program(fold).unwrap().rotation_steps(), vec![0]);
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let a = Batched::<4>::try_from([vec![1, 2, 3, 4], vec![5, 6, 7, 8]]).unwrap();
+
+    let run = |name: &str| -> Batched<4> {
+        let args: Vec<FheProgramInput> = vec![runtime.encrypt(a, &public_key).unwrap().into()];
+
+        let result = runtime
+            .run(app.get_fhe_program(name).unwrap(), args, &public_key)
+            .unwrap();
+
+        runtime.decrypt(&result[0], &private_key).unwrap()
+    };
+
+    assert_eq!(
+        run("fold"),
+        [vec![6, 8, 10, 12], vec![6, 8, 10, 12]].try_into().unwrap()
+    );
+    assert_eq!(run("fold_and_swap"), (a.fold_columns() << 1).swap_rows());
+}
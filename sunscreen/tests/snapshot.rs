@@ -0,0 +1,54 @@
+#![cfg(feature = "bfv-types")]
+
+use sunscreen::{
+    types::{bfv::Signed, Cipher},
+    *,
+};
+
+#[fhe_program(scheme = "bfv")]
+fn add(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+    a + b
+}
+
+#[fhe_program(scheme = "bfv")]
+fn mul(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+    a * b
+}
+
+#[test]
+fn restoring_state_verifies_programs_and_keys() {
+    let app = Compiler::new()
+        .fhe_program(add)
+        .fhe_program(mul)
+        .compile()
+        .unwrap();
+
+    let add_program = app.get_fhe_program(add).unwrap();
+    let mul_program = app.get_fhe_program(mul).unwrap();
+
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    runtime.register_program("add", add_program).unwrap();
+    runtime.register_keys("alice", &public_key).unwrap();
+
+    let state = runtime.export_state();
+
+    assert!(state.programs.contains_key("add"));
+
+    let restored =
+        Runtime::import_state(&state, &[("add", add_program)], &[("alice", &public_key)]).unwrap();
+
+    let a = restored.encrypt(Signed::from(2), &public_key).unwrap();
+    let b = restored.encrypt(Signed::from(3), &public_key).unwrap();
+    let c = restored.run(add_program, vec![a, b], &public_key).unwrap();
+    let c: Signed = restored.decrypt(&c[0], &private_key).unwrap();
+
+    assert_eq!(c, 5.into());
+
+    // Restoring with a different program under the same name fails.
+    assert!(matches!(
+        Runtime::import_state(&state, &[("add", mul_program)], &[("alice", &public_key)]),
+        Err(RuntimeError::StateMismatch(_))
+    ));
+}
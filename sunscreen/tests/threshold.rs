@@ -0,0 +1,166 @@
+#![cfg(feature = "bfv-types")]
+
+use sunscreen::{
+    protocols::threshold::{combine, deal, DecryptionShare, KeyShare},
+    types::{bfv::Signed, Cipher},
+    *,
+};
+
+#[fhe_program(scheme = "bfv")]
+fn mul(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+    a * b
+}
+
+fn compile() -> FheApplication {
+    Compiler::new()
+        .fhe_program(mul)
+        .additional_noise_budget(64)
+        .compile()
+        .unwrap()
+}
+
+#[test]
+fn any_threshold_of_parties_can_decrypt() {
+    let app = compile();
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, key_shares) = deal(app.params(), 3, 5).unwrap();
+
+    assert_eq!(key_shares.len(), 5);
+
+    let a = runtime.encrypt(Signed::from(-7), &public_key).unwrap();
+    let b = runtime.encrypt(Signed::from(6), &public_key).unwrap();
+
+    let result = runtime
+        .run(app.get_fhe_program(mul).unwrap(), vec![a, b], &public_key)
+        .unwrap();
+
+    for participants in [[1, 2, 3], [2, 4, 5], [1, 3, 5]].iter() {
+        let shares = participants
+            .iter()
+            .map(|p| {
+                key_shares[*p as usize - 1]
+                    .partial_decrypt(&result[0], participants)
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let plaintext = combine(&result[0], &shares).unwrap();
+        let product: Signed = runtime.decode(&plaintext).unwrap();
+
+        assert_eq!(product, (-42).into());
+    }
+}
+
+#[test]
+fn shares_survive_serialization() {
+    let app = compile();
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, key_shares) = deal(app.params(), 2, 2).unwrap();
+
+    let key_shares = key_shares
+        .iter()
+        .map(|k| bincode::deserialize::<KeyShare>(&bincode::serialize(k).unwrap()).unwrap())
+        .collect::<Vec<_>>();
+
+    let x = runtime.encrypt(Signed::from(1234), &public_key).unwrap();
+
+    let shares = key_shares
+        .iter()
+        .map(|k| {
+            let share = k.partial_decrypt(&x, &[2, 1]).unwrap();
+            let json = serde_json::to_string(&share).unwrap();
+
+            serde_json::from_str::<DecryptionShare>(&json).unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let x: Signed = runtime.decode(&combine(&x, &shares).unwrap()).unwrap();
+
+    assert_eq!(x, 1234.into());
+}
+
+#[test]
+fn rejects_too_few_or_mismatched_participants() {
+    let app = compile();
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, key_shares) = deal(app.params(), 2, 3).unwrap();
+    let x = runtime.encrypt(Signed::from(1), &public_key).unwrap();
+
+    assert!(matches!(
+        key_shares[0].partial_decrypt(&x, &[1]),
+        Err(Error::ThresholdDecryption(_))
+    ));
+    assert!(matches!(
+        key_shares[0].partial_decrypt(&x, &[2, 3]),
+        Err(Error::ThresholdDecryption(_))
+    ));
+
+    let a = key_shares[0].partial_decrypt(&x, &[1, 2]).unwrap();
+    let b = key_shares[2].partial_decrypt(&x, &[1, 3]).unwrap();
+
+    assert!(matches!(
+        combine(&x, &[a.clone(), b]),
+        Err(Error::ThresholdDecryption(_))
+    ));
+    assert!(matches!(
+        combine(&x, &[a]),
+        Err(Error::ThresholdDecryption(_))
+    ));
+
+    assert!(matches!(
+        deal(app.params(), 4, 3),
+        Err(Error::ThresholdDecryption(_))
+    ));
+}
+
+#[test]
+fn rejects_malformed_shares() {
+    let app = compile();
+    let runtime = Runtime::new_fhe(app.params()).unwrap();
+
+    let (public_key, key_shares) = deal(app.params(), 2, 2).unwrap();
+    let x = runtime.encrypt(Signed::from(1), &public_key).unwrap();
+
+    assert!(matches!(
+        key_shares[0].partial_decrypt(&x, &[0, 1]),
+        Err(Error::ThresholdDecryption(_))
+    ));
+
+    assert!(matches!(
+        key_shares[0].partial_decrypt_with_smudging(&x, &[1, 2], 63),
+        Err(Error::ThresholdDecryption(_))
+    ));
+
+    let mut truncated = serde_json::to_value(&key_shares[0]).unwrap();
+    truncated["share"].as_array_mut().unwrap().pop();
+    let truncated: KeyShare = serde_json::from_value(truncated).unwrap();
+
+    assert!(matches!(
+        truncated.partial_decrypt(&x, &[1, 2]),
+        Err(Error::ThresholdDecryption(_))
+    ));
+
+    let a = key_shares[0].partial_decrypt(&x, &[1, 2]).unwrap();
+    let b = key_shares[1].partial_decrypt(&x, &[1, 2]).unwrap();
+
+    let mut unreduced = serde_json::to_value(&b).unwrap();
+    unreduced["polynomials"][0][0] = u64::MAX.into();
+    let unreduced: DecryptionShare = serde_json::from_value(unreduced).unwrap();
+
+    assert!(matches!(
+        combine(&x, &[a.clone(), unreduced]),
+        Err(Error::ThresholdDecryption(_))
+    ));
+
+    let mut repeated = serde_json::to_value(&a).unwrap();
+    repeated["participants"] = serde_json::json!([1, 1]);
+    let repeated: DecryptionShare = serde_json::from_value(repeated).unwrap();
+
+    assert!(matches!(
+        combine(&x, &[repeated.clone(), repeated]),
+        Err(Error::ThresholdDecryption(_))
+    ));
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "zkp")]
+
 use sunscreen::{types::zkp::NativeField, zkp_program, Compiler, Runtime};
 use sunscreen_runtime::ZkpProgramInput;
 use sunscreen_zkp_backend::{bulletproofs::BulletproofsBackend, BackendField, ZkpBackend};
@@ -166,3 +168,44 @@ fn can_declare_array_inputs() {
         .verify(program, &proof, Vec::<ZkpProgramInput>::new(), vec![])
         .unwrap();
 }
+
+#[test]
+fn compiler_enforces_circuit_limits() {
+    use sunscreen::{Error, ZkpLimit};
+
+    #[zkp_program(backend = "bulletproofs")]
+    fn power<F: BackendField>(a: NativeField<F>) {
+        let mut x = a;
+
+        for _ in 0..16 {
+            x = x * a;
+        }
+
+        x.constrain_eq(NativeField::from(0u32))
+    }
+
+    let result = Compiler::new()
+        .zkp_backend::<BulletproofsBackend>()
+        .zkp_program(power)
+        .max_constraints(8)
+        .compile();
+
+    match result {
+        Err(Error::ZkpBudgetExceeded(e)) => {
+            assert_eq!(e.program, "power");
+            assert_eq!(e.limit, ZkpLimit::Constraints);
+            assert_eq!(e.actual, 17);
+            assert_eq!(e.max, 8);
+            assert_eq!(e.gadget, None);
+        }
+        _ => panic!("Expected ZkpBudgetExceeded"),
+    }
+
+    Compiler::new()
+        .zkp_backend::<BulletproofsBackend>()
+        .zkp_program(power)
+        .max_constraints(17)
+        .max_witness_size(1)
+        .compile()
+        .unwrap();
+}
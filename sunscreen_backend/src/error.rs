@@ -41,6 +41,17 @@ pub enum Error {
      * [`TargetNoiseLevel::NotApplicable`](crate::noise_model::TargetNoiseLevel::NotApplicable).
      */
     NotApplicable,
+
+    /**
+     * A compiler pass produced a program violating an IR invariant.
+     */
+    VerificationFailed(Box<crate::VerificationFailure>),
+
+    /**
+     * An operation was given the wrong number of operands. The values are
+     * the expected and actual counts.
+     */
+    WrongOperandCount(usize, usize),
 }
 
 impl From<sunscreen_fhe_program::Error> for Error {
@@ -5,6 +5,11 @@
 //! following useful operations:
 //! * [`compile`] takes either an FHE program from the compiler frontend and applies a set
 //! of transformations.
+//! * [`compile_verified`] does the same, checking the program's invariants
+//! after every transformation.
+//!
+//! The `_with_policy` variants place relinearizations according to a
+//! [`RelinearizationPolicy`] rather than after every multiplication.
 
 mod error;
 /**
@@ -12,29 +17,74 @@ mod error;
  */
 pub mod noise_model;
 mod transforms;
+mod verify;
 
 pub use error::*;
+pub use transforms::RelinearizationPolicy;
+pub use verify::{verify, verify_with_policy, VerificationFailure, Violation};
 
 use sunscreen_fhe_program::FheProgram;
 
-use transforms::transform_intermediate_representation;
+use transforms::{transform_and_verify, transform_intermediate_representation};
 
 /**
  * Clones the given [`FheProgram`] and compiles it.
  */
 pub fn compile(ir: &FheProgram) -> FheProgram {
-    let mut clone = ir.clone();
-
-    transform_intermediate_representation(&mut clone);
-
-    clone
+    compile_inplace(ir.clone())
 }
 
 /**
  * Consumes the given [`FheProgram`] and compiles it.
  */
-pub fn compile_inplace(mut ir: FheProgram) -> FheProgram {
-    transform_intermediate_representation(&mut ir);
+pub fn compile_inplace(ir: FheProgram) -> FheProgram {
+    compile_inplace_with_policy(ir, RelinearizationPolicy::default())
+}
+
+/**
+ * Consumes the given [`FheProgram`] and compiles it, placing
+ * relinearizations according to `policy`.
+ */
+pub fn compile_inplace_with_policy(
+    mut ir: FheProgram,
+    policy: RelinearizationPolicy,
+) -> FheProgram {
+    transform_intermediate_representation(&mut ir, policy);
 
     ir
 }
+
+/**
+ * Clones the given [`FheProgram`] and compiles it, checking
+ * [`verify`]'s invariants before and after every pass.
+ *
+ * # Remarks
+ * Returns [`Error::VerificationFailed`] naming the pass that broke an
+ * invariant, rather than a program that fails or decrypts to garbage at
+ * runtime.
+ */
+pub fn compile_verified(ir: &FheProgram) -> Result<FheProgram> {
+    compile_inplace_verified(ir.clone())
+}
+
+/**
+ * Consumes the given [`FheProgram`] and compiles it, checking the program
+ * after every pass as [`compile_verified`] does.
+ */
+pub fn compile_inplace_verified(ir: FheProgram) -> Result<FheProgram> {
+    compile_inplace_verified_with_policy(ir, RelinearizationPolicy::default())
+}
+
+/**
+ * Consumes the given [`FheProgram`] and compiles it as
+ * [`compile_inplace_with_policy`] does, checking the program after every
+ * pass as [`compile_verified`] does.
+ */
+pub fn compile_inplace_verified_with_policy(
+    mut ir: FheProgram,
+    policy: RelinearizationPolicy,
+) -> Result<FheProgram> {
+    transform_and_verify(&mut ir, policy)?;
+
+    Ok(ir)
+}
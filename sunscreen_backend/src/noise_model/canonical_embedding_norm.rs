@@ -210,8 +210,8 @@ mod tests {
                 let (ctx, params) = setup_scheme(d, p);
 
                 let keygen = KeyGenerator::new(&ctx).unwrap();
-                let public_key = keygen.create_public_key();
-                let private_key = keygen.secret_key();
+                let public_key = keygen.create_public_key().unwrap();
+                let private_key = keygen.secret_key().unwrap();
                 let encryptor = Encryptor::with_public_key(&ctx, &public_key).unwrap();
                 let decryptor = Decryptor::new(&ctx, &private_key).unwrap();
 
@@ -244,8 +244,8 @@ mod tests {
                 let (ctx, params) = setup_scheme(d, p);
 
                 let keygen = KeyGenerator::new(&ctx).unwrap();
-                let public_key = keygen.create_public_key();
-                let private_key = keygen.secret_key();
+                let public_key = keygen.create_public_key().unwrap();
+                let private_key = keygen.secret_key().unwrap();
                 let encryptor = Encryptor::with_public_key(&ctx, &public_key).unwrap();
                 let decryptor = Decryptor::new(&ctx, &private_key).unwrap();
                 let evalulator = BFVEvaluator::new(&ctx).unwrap();
@@ -285,8 +285,8 @@ mod tests {
                 let (ctx, params) = setup_scheme(d, p);
 
                 let keygen = KeyGenerator::new(&ctx).unwrap();
-                let public_key = keygen.create_public_key();
-                let private_key = keygen.secret_key();
+                let public_key = keygen.create_public_key().unwrap();
+                let private_key = keygen.secret_key().unwrap();
                 let encryptor = Encryptor::with_public_key(&ctx, &public_key).unwrap();
                 let decryptor = Decryptor::new(&ctx, &private_key).unwrap();
                 let evalulator = BFVEvaluator::new(&ctx).unwrap();
@@ -324,8 +324,8 @@ mod tests {
                 let (ctx, params) = setup_scheme(d, p);
 
                 let keygen = KeyGenerator::new(&ctx).unwrap();
-                let public_key = keygen.create_public_key();
-                let private_key = keygen.secret_key();
+                let public_key = keygen.create_public_key().unwrap();
+                let private_key = keygen.secret_key().unwrap();
                 let encryptor = Encryptor::with_public_key(&ctx, &public_key).unwrap();
                 let decryptor = Decryptor::new(&ctx, &private_key).unwrap();
                 let evalulator = BFVEvaluator::new(&ctx).unwrap();
@@ -380,8 +380,8 @@ mod tests {
                 let (ctx, params) = setup_scheme(d, p);
 
                 let keygen = KeyGenerator::new(&ctx).unwrap();
-                let public_key = keygen.create_public_key();
-                let private_key = keygen.secret_key();
+                let public_key = keygen.create_public_key().unwrap();
+                let private_key = keygen.secret_key().unwrap();
                 let encryptor = Encryptor::with_public_key(&ctx, &public_key).unwrap();
                 let decryptor = Decryptor::new(&ctx, &private_key).unwrap();
                 let evalulator = BFVEvaluator::new(&ctx).unwrap();
@@ -5,7 +5,7 @@ use sunscreen_fhe_program::{
 };
 use sunscreen_runtime::{run_program_unchecked, Params, SealData};
 
-use super::{noise_budget_to_noise, NoiseModel};
+use super::{noise_budget_bits_to_noise, NoiseModel};
 use crate::{Error, Result};
 
 #[derive(Copy, Clone)]
@@ -61,17 +61,27 @@ pub fn create_ciphertext_with_noise_level(
     relin_keys: Option<&RelinearizationKeys>,
     noise_level: TargetNoiseLevel,
 ) -> Result<Ciphertext> {
-    let encoder = BFVScalarEncoder::new();
-
     let encryptor = Encryptor::with_public_and_secret_key(context, public_key, private_key)?;
 
+    create_ciphertext_with_encryptor(context, &encryptor, private_key, relin_keys, noise_level)
+}
+
+fn create_ciphertext_with_encryptor(
+    context: &Context,
+    encryptor: &Encryptor,
+    private_key: &SecretKey,
+    relin_keys: Option<&RelinearizationKeys>,
+    noise_level: TargetNoiseLevel,
+) -> Result<Ciphertext> {
+    let encoder = BFVScalarEncoder::new();
+
     let target_noise_level = match noise_level {
         TargetNoiseLevel::Fresh => {
             let p = encoder.encode_unsigned(1)?;
             return Ok(encryptor.encrypt(&p)?);
         }
         TargetNoiseLevel::InvariantNoiseBudget(target_noise_budget) => {
-            noise_budget_to_noise(target_noise_budget as f64)
+            noise_budget_bits_to_noise(target_noise_budget)
         }
         TargetNoiseLevel::InvariantNoise(target_noise) => target_noise,
         _ => unimplemented!(""),
@@ -229,7 +239,8 @@ pub fn create_ciphertext_with_noise_level(
  * measuring the noise of the output ciphertexts.
  *
  * # Remarks
- * * This model is non-deterministic because we're running on real ciphertexts.
+ * * This model is non-deterministic because we're running on real
+ *   ciphertexts, unless created with [`MeasuredModel::new_seeded`].
  * * All other models should bound its results from above
  * * All operations other than `output` return 0.0 noise.
  */
@@ -262,7 +273,7 @@ fn create_seal_params(params: &Params) -> Result<EncryptionParameters> {
 fn create_inputs_for_program(
     ir: &FheProgram,
     context: &Context,
-    public_key: &PublicKey,
+    encryptor: &Encryptor,
     private_key: &SecretKey,
     relin_keys: Option<&RelinearizationKeys>,
     noise_targets: &[TargetNoiseLevel],
@@ -283,9 +294,9 @@ fn create_inputs_for_program(
         })
         .zip(noise_targets)
         .map(|(n, target)| match n.operation {
-            Operation::InputCiphertext(_) => Ok(create_ciphertext_with_noise_level(
+            Operation::InputCiphertext(_) => Ok(create_ciphertext_with_encryptor(
                 context,
-                public_key,
+                encryptor,
                 private_key,
                 relin_keys,
                 *target,
@@ -328,6 +339,16 @@ fn make_relin_galois_keys(
     Ok((relin_keys, galois_keys))
 }
 
+/**
+ * Returns the seed for encrypting inputs under keys generated from `seed`.
+ */
+fn encryption_seed(seed: &PrngSeed) -> PrngSeed {
+    let mut words = seed.0;
+    words[7] ^= 1;
+
+    PrngSeed(words)
+}
+
 impl MeasuredModel {
     /**
      * Creates a new `MeasuredModel` with the given [`FheProgram`] and [`Params`].
@@ -336,6 +357,32 @@ impl MeasuredModel {
         ir: &FheProgram,
         params: &Params,
         noise_targets: &[TargetNoiseLevel],
+    ) -> Result<Self> {
+        Self::measure(ir, params, noise_targets, None)
+    }
+
+    /**
+     * Creates a new `MeasuredModel` like [`MeasuredModel::new`], but
+     * derives the keys and input ciphertexts it measures from `seed`.
+     *
+     * # Remarks
+     * The same program, parameters, targets and seed always measure the
+     * same noise, as evaluating the program involves no randomness.
+     */
+    pub fn new_seeded(
+        ir: &FheProgram,
+        params: &Params,
+        noise_targets: &[TargetNoiseLevel],
+        seed: &PrngSeed,
+    ) -> Result<Self> {
+        Self::measure(ir, params, noise_targets, Some(seed))
+    }
+
+    fn measure(
+        ir: &FheProgram,
+        params: &Params,
+        noise_targets: &[TargetNoiseLevel],
+        seed: Option<&PrngSeed>,
     ) -> Result<Self> {
         ir.validate()?;
 
@@ -343,9 +390,24 @@ impl MeasuredModel {
 
         let context = Context::new(&seal_params, true, params.security_level)?;
 
-        let keygen = KeyGenerator::new(&context).unwrap();
-        let public_key = keygen.create_public_key();
-        let private_key = keygen.secret_key();
+        let keygen = match seed {
+            Some(seed) => KeyGenerator::new_seeded(&context, seed)?,
+            None => KeyGenerator::new(&context)?,
+        };
+        let public_key = keygen.create_public_key()?;
+        let private_key = keygen.secret_key()?;
+
+        // Seed the encryptor differently from the keys, as reusing the
+        // key generator's randomness would correlate them.
+        let encryptor = match seed {
+            Some(seed) => Encryptor::with_public_and_secret_key_seeded(
+                &context,
+                &public_key,
+                &private_key,
+                &encryption_seed(seed),
+            )?,
+            None => Encryptor::with_public_and_secret_key(&context, &public_key, &private_key)?,
+        };
 
         let decryptor = Decryptor::new(&context, &private_key).unwrap();
 
@@ -358,7 +420,7 @@ impl MeasuredModel {
         let inputs = create_inputs_for_program(
             ir,
             &context,
-            &public_key,
+            &encryptor,
             &private_key,
             relin_keys.as_ref(),
             noise_targets,
@@ -466,8 +528,8 @@ fn can_create_target_noise_budget_ciphertext() {
     let context = Context::new(&params, false, SecurityLevel::TC128).unwrap();
 
     let keygen = KeyGenerator::new(&context).unwrap();
-    let public_key = keygen.create_public_key();
-    let private_key = keygen.secret_key();
+    let public_key = keygen.create_public_key().unwrap();
+    let private_key = keygen.secret_key().unwrap();
     let relin_keys = keygen.create_relinearization_keys().unwrap();
 
     let desired_noise = 42;
@@ -503,8 +565,8 @@ fn can_create_target_noise_ciphertext() {
     let context = Context::new(&params, false, SecurityLevel::TC128).unwrap();
 
     let keygen = KeyGenerator::new(&context).unwrap();
-    let public_key = keygen.create_public_key();
-    let private_key = keygen.secret_key();
+    let public_key = keygen.create_public_key().unwrap();
+    let private_key = keygen.secret_key().unwrap();
     let relin_keys = keygen.create_relinearization_keys().unwrap();
 
     let desired_noise = 0.25f64;
@@ -1,7 +1,9 @@
 use crossbeam::atomic::AtomicCell;
 use sunscreen_compiler_common::GraphQuery;
-use sunscreen_fhe_program::{FheProgram, Literal, Operation::*};
-use sunscreen_runtime::traverse;
+use sunscreen_fhe_program::{FheProgram, Literal, Operation, Operation::*};
+use sunscreen_runtime::{traverse, Params};
+
+use crate::{Error, Result};
 
 use std::collections::HashMap;
 
@@ -169,6 +171,25 @@ pub fn predict_noise(model: &(dyn NoiseModel + Sync), fhe_program: &FheProgram)
         .collect()
 }
 
+/**
+ * Predicts the invariant noise after applying `op` to ciphertexts with the
+ * given invariant noise under `params`, using the
+ * [`CanonicalEmbeddingNormModel`]. See [`NoiseModel::cost_of`] for the
+ * expected operands.
+ *
+ * # Remarks
+ * This lets you budget a pipeline of manual evaluator calls without
+ * trial decryption: feed each operation's result into the next and check
+ * the final noise with [`noise_to_noise_budget_bits`].
+ *
+ * The compiler's parameter search runs whole programs under a
+ * [`MeasuredModel`], which this model bounds from above, so the estimate
+ * is conservative.
+ */
+pub fn cost_of(op: &Operation, operand_noise: &[f64], params: &Params) -> Result<f64> {
+    CanonicalEmbeddingNormModel::new(params)?.cost_of(op, operand_noise)
+}
+
 /**
  * Calculates the invariant noise budget from the given invariant
  * noise.
@@ -194,6 +215,59 @@ pub fn noise_budget_to_noise(invariant_noise_budget: f64) -> f64 {
     f64::powf(2., -invariant_noise_budget) / 2.
 }
 
+/**
+ * Returns the invariant noise $2^{-(b + 1)}$ corresponding to exactly
+ * `invariant_noise_budget` bits of noise budget.
+ *
+ * # Remarks
+ * Unlike [`noise_budget_to_noise`], this builds the result from its
+ * exponent rather than calling `powf`, whose rounding varies between
+ * platforms and math libraries. As such, every platform produces the
+ * same value. Budgets beyond the range of normal `f64` values yield 0.
+ */
+pub fn noise_budget_bits_to_noise(invariant_noise_budget: u32) -> f64 {
+    // 2^-(b + 1) has biased exponent 1023 - (b + 1) and a zero mantissa.
+    match 1022u64.checked_sub(invariant_noise_budget as u64) {
+        Some(0) | None => 0.,
+        Some(exponent) => f64::from_bits(exponent << 52),
+    }
+}
+
+/**
+ * Returns the whole bits of noise budget remaining in a ciphertext with
+ * the given invariant noise, i.e. $\lfloor -log_2(2 * |v|) \rfloor$
+ * clamped to 0.
+ *
+ * # Remarks
+ * This reads the result from the bits of `invariant_noise` rather than
+ * calling `log2`, so every platform produces the same value. A budget is
+ * at least `b` bits exactly when the noise is at most
+ * [`noise_budget_bits_to_noise(b)`](noise_budget_bits_to_noise).
+ *
+ * Zero noise has an unbounded budget and returns `u32::MAX`.
+ */
+pub fn noise_to_noise_budget_bits(invariant_noise: f64) -> u32 {
+    let invariant_noise = invariant_noise.abs();
+
+    if invariant_noise == 0. {
+        return u32::MAX;
+    } else if !invariant_noise.is_normal() {
+        // Subnormal noise leaves more budget than any parameters we
+        // support, while infinite and NaN noise leaves none.
+        return if invariant_noise.is_finite() { 1022 } else { 0 };
+    }
+
+    let bits = invariant_noise.to_bits();
+    let exponent = ((bits >> 52) & 0x7ff) as i64 - 1023;
+    let mantissa = bits & ((1 << 52) - 1);
+
+    // -log2(2 * 2^e * 1.m) = -(e + 1) - log2(1.m), where the last term
+    // lies in [0, 1) and is 0 only for a zero mantissa.
+    let budget = -(exponent + 1) - i64::from(mantissa != 0);
+
+    budget.max(0) as u32
+}
+
 /**
  * A model for predicting noise growth in an FHE program.
  */
@@ -269,6 +343,52 @@ pub trait NoiseModel {
      * Predict the amount of noise after a row swap.
      */
     fn shift_right(&self, a_invariant_noise: f64, places: i32) -> f64;
+
+    /**
+     * Predict the amount of noise after applying `op` to ciphertexts with
+     * the given invariant noise.
+     *
+     * # Remarks
+     * `operand_noise` holds one entry per ciphertext operand: two for
+     * [`Add`](Operation::Add), [`Sub`](Operation::Sub) and
+     * [`Multiply`](Operation::Multiply), none for inputs and literals and
+     * one otherwise. Plaintext operands carry no noise and have no entry.
+     * Rotations are predicted for a single place; their amount doesn't
+     * affect noise.
+     *
+     * Returns [`Error::WrongOperandCount`] if `operand_noise` has the wrong
+     * number of entries for `op`.
+     */
+    fn cost_of(&self, op: &Operation, operand_noise: &[f64]) -> Result<f64> {
+        let expected = match op {
+            InputCiphertext(_) | InputPlaintext(_) | Literal(_) => 0,
+            Add | Sub | Multiply => 2,
+            _ => 1,
+        };
+
+        if operand_noise.len() != expected {
+            return Err(Error::WrongOperandCount(expected, operand_noise.len()));
+        }
+
+        let noise = match op {
+            InputCiphertext(_) => self.encrypt(),
+            InputPlaintext(_) | Literal(_) => 0.,
+            Add => self.add_ct_ct(operand_noise[0], operand_noise[1]),
+            AddPlaintext => self.add_ct_pt(operand_noise[0]),
+            Sub => self.sub_ct_ct(operand_noise[0], operand_noise[1]),
+            SubPlaintext => self.sub_ct_pt(operand_noise[0]),
+            Multiply => self.mul_ct_ct(operand_noise[0], operand_noise[1]),
+            MultiplyPlaintext => self.mul_ct_pt(operand_noise[0]),
+            Relinearize => self.relinearize(operand_noise[0]),
+            Negate => self.neg(operand_noise[0]),
+            ShiftLeft => self.shift_left(operand_noise[0], 1),
+            ShiftRight => self.shift_right(operand_noise[0], 1),
+            SwapRows => self.swap_rows(operand_noise[0]),
+            OutputCiphertext => self.output(0, operand_noise[0]),
+        };
+
+        Ok(noise)
+    }
 }
 
 #[test]
@@ -280,3 +400,63 @@ fn can_roundtrip_noise_to_budget() {
 
     assert_eq!(new_budget, noise_budget);
 }
+
+#[test]
+fn can_convert_noise_budget_bits_exactly() {
+    for bits in [0, 1, 20, 42, 500, 1021] {
+        let noise = noise_budget_bits_to_noise(bits);
+
+        assert_eq!(noise, 2f64.powi(-(bits as i32) - 1));
+        assert_eq!(noise_to_noise_budget_bits(noise), bits);
+
+        // Any more noise costs a bit of budget.
+        let more = f64::from_bits(noise.to_bits() + 1);
+
+        assert_eq!(noise_to_noise_budget_bits(more), bits.saturating_sub(1));
+    }
+
+    assert_eq!(noise_budget_bits_to_noise(1022), 0.);
+    assert_eq!(noise_to_noise_budget_bits(0.), u32::MAX);
+    assert_eq!(noise_to_noise_budget_bits(0.75), 0);
+    assert_eq!(noise_to_noise_budget_bits(f64::NAN), 0);
+}
+
+#[test]
+fn can_cost_single_operations() {
+    use seal_fhe::{CoefficientModulus, SecurityLevel};
+    use sunscreen_fhe_program::SchemeType;
+
+    let params = Params {
+        lattice_dimension: 4096,
+        plain_modulus: 1024,
+        coeff_modulus: CoefficientModulus::bfv_default(4096, SecurityLevel::TC128)
+            .unwrap()
+            .iter()
+            .map(|x| x.value())
+            .collect(),
+        scheme_type: SchemeType::Bfv,
+        security_level: SecurityLevel::TC128,
+    };
+
+    let model = CanonicalEmbeddingNormModel::new(&params).unwrap();
+
+    let fresh = cost_of(&InputCiphertext(0), &[], &params).unwrap();
+    let sum = cost_of(&Add, &[fresh, fresh], &params).unwrap();
+    let product = cost_of(&Multiply, &[fresh, sum], &params).unwrap();
+    let relinearized = cost_of(&Relinearize, &[product], &params).unwrap();
+
+    assert_eq!(fresh, model.encrypt());
+    assert_eq!(sum, model.add_ct_ct(fresh, fresh));
+    assert_eq!(product, model.mul_ct_ct(fresh, sum));
+    assert_eq!(relinearized, model.relinearize(product));
+    assert!(noise_to_noise_budget_bits(relinearized) < noise_to_noise_budget_bits(fresh));
+
+    assert_eq!(
+        cost_of(&Multiply, &[fresh], &params),
+        Err(Error::WrongOperandCount(2, 1))
+    );
+    assert_eq!(
+        cost_of(&Literal(Literal::U64(1)), &[fresh], &params),
+        Err(Error::WrongOperandCount(0, 1))
+    );
+}
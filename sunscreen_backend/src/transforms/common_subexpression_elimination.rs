@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use petgraph::{stable_graph::NodeIndex, visit::EdgeRef, Direction};
+use sunscreen_compiler_common::transforms::common_subexpression_elimination;
+use sunscreen_fhe_program::{FheProgram, Literal, Operation};
+
+use crate::RelinearizationPolicy;
+
+/**
+ * Computes each distinct operation on the same operands once, e.g. the
+ * `c * d` in both `a + c * d` and `c * d + b`.
+ *
+ * # Remarks
+ * The frontend creates a literal node per use, so identical literals
+ * (e.g. rotation amounts) get merged first to let operations taking them
+ * match. Outputs are never merged. This runs before relinearizations get
+ * inserted, so merged multiplications also share a relinearization.
+ */
+pub(crate) fn eliminate_common_subexpressions(ir: &mut FheProgram, _: RelinearizationPolicy) {
+    merge_duplicate_literals(ir);
+    common_subexpression_elimination(&mut ir.graph.0);
+}
+
+fn merge_duplicate_literals(ir: &mut FheProgram) {
+    let literals = ir
+        .graph
+        .node_indices()
+        .filter_map(|n| match &ir.graph[n].operation {
+            Operation::Literal(x) => Some((n, x.clone())),
+            _ => None,
+        })
+        .collect::<Vec<(NodeIndex, Literal)>>();
+
+    let mut kept = HashMap::<Literal, NodeIndex>::new();
+
+    for (node, literal) in literals {
+        let keep = match kept.get(&literal) {
+            Some(keep) => *keep,
+            None => {
+                kept.insert(literal, node);
+                continue;
+            }
+        };
+
+        let edges = ir
+            .graph
+            .edges_directed(node, Direction::Outgoing)
+            .map(|e| (e.target(), *e.weight()))
+            .collect::<Vec<_>>();
+
+        for (target, info) in edges {
+            ir.graph.add_edge(keep, target, info);
+        }
+
+        ir.graph.remove_node(node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compile_inplace_verified, verify};
+    use sunscreen_fhe_program::{FheProgramTrait, Literal as FheProgramLiteral, SchemeType};
+
+    fn count(ir: &FheProgram, f: fn(&Operation) -> bool) -> usize {
+        ir.graph
+            .node_indices()
+            .filter(|n| f(&ir.graph[*n].operation))
+            .count()
+    }
+
+    #[test]
+    fn merges_repeated_products() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        // a + c * d and c * d + b, with the second product commuted.
+        let a = ir.add_input_ciphertext(0);
+        let b = ir.add_input_ciphertext(1);
+        let c = ir.add_input_ciphertext(2);
+        let d = ir.add_input_ciphertext(3);
+
+        let cd = ir.add_multiply(c, d);
+        let left = ir.add_add(a, cd);
+        let dc = ir.add_multiply(d, c);
+        let right = ir.add_add(dc, b);
+
+        ir.add_output_ciphertext(left);
+        ir.add_output_ciphertext(right);
+
+        eliminate_common_subexpressions(&mut ir, RelinearizationPolicy::Always);
+
+        assert_eq!(count(&ir, |o| matches!(o, Operation::Multiply)), 1);
+        assert_eq!(count(&ir, |o| matches!(o, Operation::Add)), 2);
+        assert_eq!(ir.get_outputs().count(), 2);
+
+        let ir = compile_inplace_verified(ir).unwrap();
+
+        assert_eq!(count(&ir, |o| matches!(o, Operation::Relinearize)), 1);
+        assert_eq!(verify(&ir), Ok(()));
+    }
+
+    #[test]
+    fn merges_rotations_by_equal_literals() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let x = ir.add_input_ciphertext(0);
+        let one_a = ir.add_input_literal(FheProgramLiteral::from(1u64));
+        let one_b = ir.add_input_literal(FheProgramLiteral::from(1u64));
+        let two = ir.add_input_literal(FheProgramLiteral::from(2u64));
+
+        let r_a = ir.add_rotate_left(x, one_a);
+        let r_b = ir.add_rotate_left(x, one_b);
+        let r_2 = ir.add_rotate_left(x, two);
+
+        let sum = ir.add_add(r_a, r_b);
+        let sum = ir.add_add(sum, r_2);
+        ir.add_output_ciphertext(sum);
+
+        eliminate_common_subexpressions(&mut ir, RelinearizationPolicy::Always);
+
+        assert_eq!(count(&ir, |o| matches!(o, Operation::Literal(_))), 2);
+        assert_eq!(count(&ir, |o| matches!(o, Operation::ShiftLeft)), 2);
+        assert_eq!(ir.validate(), Ok(()));
+    }
+
+    #[test]
+    fn keeps_outputs_of_the_same_value() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let x = ir.add_input_ciphertext(0);
+        let square = ir.add_multiply(x, x);
+        let square_2 = ir.add_multiply(x, x);
+
+        ir.add_output_ciphertext(square);
+        ir.add_output_ciphertext(square_2);
+        ir.add_output_ciphertext(x);
+
+        eliminate_common_subexpressions(&mut ir, RelinearizationPolicy::Always);
+
+        assert_eq!(count(&ir, |o| matches!(o, Operation::Multiply)), 1);
+        assert_eq!(ir.get_outputs().count(), 3);
+        assert_eq!(ir.validate(), Ok(()));
+    }
+}
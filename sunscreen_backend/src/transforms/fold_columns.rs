@@ -0,0 +1,199 @@
+use std::collections::HashSet;
+
+use petgraph::{algo::toposort, stable_graph::NodeIndex, visit::EdgeRef, Direction};
+use sunscreen_compiler_common::EdgeInfo;
+use sunscreen_fhe_program::{FheProgram, Operation};
+
+use crate::RelinearizationPolicy;
+
+/**
+ * Removes row swaps that don't change their operand, e.g. the swap in
+ * `(x + x.swap_rows()).swap_rows()`, and cancels pairs of swaps.
+ *
+ * # Remarks
+ * A column fold `x + x.swap_rows()` holds the same values in both rows,
+ * as does anything computed from folds with ciphertext operations and row
+ * rotations, so swapping its rows is a no-op. So is swapping rows twice.
+ * Each removed swap saves a column rotation. Programs whose only swaps get
+ * removed no longer require Galois keys, and programs whose only
+ * rotations are folds need just the column rotation's key.
+ *
+ * This runs before rebalancing, which may regroup a fold's addition with
+ * other operands.
+ */
+pub(crate) fn fold_columns(ir: &mut FheProgram, _: RelinearizationPolicy) {
+    // Validation rejects cycles, so the sort can't fail.
+    let order = toposort(&ir.graph.0, None).expect("FHE program graph contains a cycle.");
+
+    // Nodes whose rows are equal.
+    let mut symmetric = HashSet::<NodeIndex>::new();
+
+    for id in order {
+        let is_symmetric = |n: Option<NodeIndex>| n.map_or(false, |n| symmetric.contains(&n));
+
+        let left = operand(ir, id, EdgeInfo::Left);
+        let right = operand(ir, id, EdgeInfo::Right);
+        let unary = operand(ir, id, EdgeInfo::Unary);
+
+        let rows_equal = match ir.graph[id].operation {
+            Operation::SwapRows => {
+                let x = unary.expect("SwapRows has no operand.");
+
+                if symmetric.contains(&x) {
+                    replace_uses(ir, id, x);
+                } else if ir.graph[x].operation == Operation::SwapRows {
+                    let inner = operand(ir, x, EdgeInfo::Unary).expect("SwapRows has no operand.");
+
+                    replace_uses(ir, id, inner);
+
+                    if ir
+                        .graph
+                        .edges_directed(x, Direction::Outgoing)
+                        .next()
+                        .is_none()
+                    {
+                        ir.graph.remove_node(x);
+                    }
+                }
+
+                false
+            }
+            Operation::Add => {
+                is_fold(ir, left, right)
+                    || is_fold(ir, right, left)
+                    || (is_symmetric(left) && is_symmetric(right))
+            }
+            Operation::Sub | Operation::Multiply => is_symmetric(left) && is_symmetric(right),
+            Operation::ShiftLeft | Operation::ShiftRight => is_symmetric(left),
+            Operation::Negate | Operation::Relinearize => is_symmetric(unary),
+            _ => false,
+        };
+
+        if rows_equal {
+            symmetric.insert(id);
+        }
+    }
+}
+
+/**
+ * Whether `b` swaps the rows of `a`, so `a + b` is a fold.
+ */
+fn is_fold(ir: &FheProgram, a: Option<NodeIndex>, b: Option<NodeIndex>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            ir.graph[b].operation == Operation::SwapRows
+                && operand(ir, b, EdgeInfo::Unary) == Some(a)
+        }
+        _ => false,
+    }
+}
+
+fn operand(ir: &FheProgram, id: NodeIndex, info: EdgeInfo) -> Option<NodeIndex> {
+    ir.graph
+        .edges_directed(id, Direction::Incoming)
+        .find(|e| *e.weight() == info)
+        .map(|e| e.source())
+}
+
+/**
+ * Makes every consumer of `old` consume `new` instead and removes `old`.
+ */
+fn replace_uses(ir: &mut FheProgram, old: NodeIndex, new: NodeIndex) {
+    let edges = ir
+        .graph
+        .edges_directed(old, Direction::Outgoing)
+        .map(|e| (e.target(), *e.weight()))
+        .collect::<Vec<_>>();
+
+    for (target, info) in edges {
+        ir.graph.add_edge(new, target, info);
+    }
+
+    ir.graph.remove_node(old);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile_inplace_verified;
+    use sunscreen_fhe_program::{FheProgramTrait, Literal, SchemeType};
+
+    fn count(ir: &FheProgram, operation: Operation) -> usize {
+        ir.graph
+            .node_indices()
+            .filter(|n| ir.graph[*n].operation == operation)
+            .count()
+    }
+
+    #[test]
+    fn removes_swaps_of_folds() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        // fold(x) * y, rotated, then swapped, plus fold(fold(x)).
+        let x = ir.add_input_ciphertext(0);
+        let y = ir.add_input_ciphertext(1);
+        let one = ir.add_input_literal(Literal::from(1u64));
+
+        let swapped = ir.add_swap_rows(x);
+        let fold = ir.add_add(swapped, x);
+        let y_swapped = ir.add_swap_rows(y);
+        let y_fold = ir.add_add(y, y_swapped);
+
+        let product = ir.add_multiply(fold, y_fold);
+        let rotated = ir.add_rotate_left(product, one);
+        let swapped_product = ir.add_swap_rows(rotated);
+
+        let fold_swapped = ir.add_swap_rows(fold);
+        let double_fold = ir.add_add(fold, fold_swapped);
+
+        ir.add_output_ciphertext(swapped_product);
+        ir.add_output_ciphertext(double_fold);
+
+        fold_columns(&mut ir, RelinearizationPolicy::Always);
+
+        // Only the swaps the folds themselves need remain.
+        assert_eq!(count(&ir, Operation::SwapRows), 2);
+        assert_eq!(ir.validate(), Ok(()));
+
+        let ir = compile_inplace_verified(ir).unwrap();
+
+        assert_eq!(count(&ir, Operation::SwapRows), 2);
+        assert!(ir.requires_galois_keys());
+    }
+
+    #[test]
+    fn cancels_double_swaps() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let x = ir.add_input_ciphertext(0);
+        let once = ir.add_swap_rows(x);
+        let twice = ir.add_swap_rows(once);
+
+        ir.add_output_ciphertext(twice);
+
+        fold_columns(&mut ir, RelinearizationPolicy::Always);
+
+        assert_eq!(count(&ir, Operation::SwapRows), 0);
+        assert!(!ir.requires_galois_keys());
+        assert_eq!(ir.validate(), Ok(()));
+    }
+
+    #[test]
+    fn keeps_swaps_of_asymmetric_values() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        // x + swap(y) isn't a fold.
+        let x = ir.add_input_ciphertext(0);
+        let y = ir.add_input_ciphertext(1);
+
+        let y_swapped = ir.add_swap_rows(y);
+        let sum = ir.add_add(x, y_swapped);
+        let swapped = ir.add_swap_rows(sum);
+
+        ir.add_output_ciphertext(swapped);
+
+        fold_columns(&mut ir, RelinearizationPolicy::Always);
+
+        assert_eq!(count(&ir, Operation::SwapRows), 2);
+    }
+}
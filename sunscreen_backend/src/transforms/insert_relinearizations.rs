@@ -1,3 +1,4 @@
+use std::collections::{HashMap, HashSet};
 use std::convert::Infallible;
 
 use sunscreen_compiler_common::{
@@ -6,14 +7,222 @@ use sunscreen_compiler_common::{
     EdgeInfo, GraphQuery, NodeInfo,
 };
 use sunscreen_fhe_program::{
-    FheProgram,
+    FheProgram, FheProgramTrait,
     Operation::{self, *},
+    OutputType, OutputTypeTrait,
 };
 
-use petgraph::{stable_graph::NodeIndex, visit::EdgeRef, Direction};
+use petgraph::{
+    algo::toposort,
+    stable_graph::{EdgeIndex, NodeIndex},
+    visit::EdgeRef,
+    Direction,
+};
 
 type FheGraphQuery<'a> = GraphQuery<'a, NodeInfo<Operation>, EdgeInfo>;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/**
+ * Where the compiler relinearizes the results of ciphertext
+ * multiplications.
+ *
+ * # Remarks
+ * Multiplying ciphertexts with `a` and `b` polynomials yields one with
+ * `a + b - 1`. Relinearization brings a 3-polynomial ciphertext back to
+ * 2, which rotations and row swaps require, at the cost of a key switch
+ * and some noise. Larger ciphertexts make later operations slower and
+ * noisier, and can't be relinearized at all.
+ */
+pub enum RelinearizationPolicy {
+    /**
+     * Relinearize every multiplication's result immediately.
+     */
+    Always,
+
+    /**
+     * Relinearize a multiplication's result only once it reaches an
+     * operation needing 2 polynomials: another multiplication, a rotation,
+     * a row swap or an output. E.g. `a * b + c * d` relinearizes once,
+     * after the addition, rather than once per product.
+     */
+    Lazy,
+
+    /**
+     * Relinearize only where a ciphertext would grow beyond the given
+     * number of polynomials (at least 3), or where a rotation or row swap
+     * needs 2.
+     *
+     * Multiplications whose results never reach another multiplication,
+     * rotation or row swap can produce ciphertexts of up to this size,
+     * which outputs return as is. Programs that never relinearize don't
+     * need relinearization keys. Decrypting a larger ciphertext works, but
+     * leaves less noise budget and feeding it to a program that rotates or
+     * multiplies it fails.
+     */
+    SizeExceeds(usize),
+}
+
+impl Default for RelinearizationPolicy {
+    fn default() -> Self {
+        Self::Always
+    }
+}
+
+impl RelinearizationPolicy {
+    /**
+     * The largest ciphertext this policy lets a multiplication produce.
+     */
+    pub(crate) fn max_size(&self) -> usize {
+        match self {
+            Self::Always | Self::Lazy => 3,
+            Self::SizeExceeds(k) => usize::max(*k, 3),
+        }
+    }
+
+    /**
+     * The largest ciphertext this policy lets a program output.
+     */
+    pub(crate) fn max_output_size(&self) -> usize {
+        match self {
+            Self::Always | Self::Lazy => 2,
+            Self::SizeExceeds(_) => self.max_size(),
+        }
+    }
+}
+
+/**
+ * Inserts relinearizations where `policy` calls for them.
+ */
+pub fn apply_relinearization_policy(ir: &mut FheProgram, policy: RelinearizationPolicy) {
+    match policy {
+        RelinearizationPolicy::Always => apply_insert_relinearizations(ir),
+        _ => insert_relinearizations_on_demand(ir, policy),
+    }
+}
+
+/**
+ * Tracks each ciphertext's size in topological order, relinearizing an
+ * operand just before the first operation exceeding `policy`'s limits.
+ */
+fn insert_relinearizations_on_demand(ir: &mut FheProgram, policy: RelinearizationPolicy) {
+    // Frontend programs are DAGs.
+    let order = toposort(&ir.graph.0, None).expect("FHE program has a cycle.");
+
+    // Whether each node's value reaches an operation needing its operands
+    // relinearizable. Values that don't can grow past 3 polynomials.
+    let mut feeds_relinearization = HashSet::new();
+
+    for id in order.iter().rev() {
+        let feeds = ir
+            .graph
+            .neighbors_directed(*id, Direction::Outgoing)
+            .any(|c| {
+                feeds_relinearization.contains(&c)
+                    || matches!(
+                        ir.graph[c].operation,
+                        Multiply | ShiftLeft | ShiftRight | SwapRows
+                    )
+            });
+
+        if feeds {
+            feeds_relinearization.insert(*id);
+        }
+    }
+
+    let mut sizes = HashMap::<NodeIndex, usize>::new();
+    let mut relinearized = HashMap::<NodeIndex, NodeIndex>::new();
+
+    for id in order {
+        let size = match ir.graph[id].operation {
+            InputPlaintext(_) | Literal(_) => continue,
+            InputCiphertext(_) | Relinearize => 2,
+            Multiply => {
+                let limit = if feeds_relinearization.contains(&id) {
+                    3
+                } else {
+                    policy.max_size()
+                };
+
+                loop {
+                    let operands = ciphertext_operands(ir, id, &sizes);
+                    let product =
+                        operands.iter().map(|(_, s)| s).sum::<usize>() + 1 - operands.len();
+
+                    match operands.iter().find(|(_, s)| *s == 3) {
+                        Some((e, _)) if product > limit => {
+                            relinearize(ir, *e, &mut relinearized, &mut sizes)
+                        }
+                        _ => break product,
+                    }
+                }
+            }
+            ShiftLeft | ShiftRight | SwapRows | OutputCiphertext => {
+                let limit = match ir.graph[id].operation {
+                    OutputCiphertext => policy.max_output_size(),
+                    _ => 2,
+                };
+
+                let mut largest = 2;
+
+                for (e, s) in ciphertext_operands(ir, id, &sizes) {
+                    if s > limit {
+                        relinearize(ir, e, &mut relinearized, &mut sizes);
+                    } else {
+                        largest = usize::max(largest, s);
+                    }
+                }
+
+                largest
+            }
+            Add | Sub | Negate | AddPlaintext | SubPlaintext | MultiplyPlaintext => {
+                ciphertext_operands(ir, id, &sizes)
+                    .iter()
+                    .map(|(_, s)| *s)
+                    .max()
+                    .unwrap_or(2)
+            }
+        };
+
+        sizes.insert(id, size);
+    }
+}
+
+/**
+ * Returns the edges and sizes of `id`'s ciphertext operands.
+ */
+fn ciphertext_operands(
+    ir: &FheProgram,
+    id: NodeIndex,
+    sizes: &HashMap<NodeIndex, usize>,
+) -> Vec<(EdgeIndex, usize)> {
+    ir.graph
+        .edges_directed(id, Direction::Incoming)
+        .filter(|e| ir.graph[e.source()].output_type() == OutputType::Ciphertext)
+        .map(|e| (e.id(), sizes[&e.source()]))
+        .collect()
+}
+
+/**
+ * Routes the operand on edge `e` through a relinearization of its source,
+ * shared by every operation relinearizing that value.
+ */
+fn relinearize(
+    ir: &mut FheProgram,
+    e: EdgeIndex,
+    relinearized: &mut HashMap<NodeIndex, NodeIndex>,
+    sizes: &mut HashMap<NodeIndex, usize>,
+) {
+    let (source, target) = ir.graph.edge_endpoints(e).unwrap();
+    let info = ir.graph.remove_edge(e).unwrap();
+
+    let relin = *relinearized
+        .entry(source)
+        .or_insert_with(|| ir.add_relinearize(source));
+
+    sizes.insert(relin, 2);
+    ir.graph.add_edge(relin, target, info);
+}
+
 pub fn apply_insert_relinearizations(ir: &mut FheProgram) {
     let insert_relin = |id: NodeIndex, query: FheGraphQuery| {
         let mut transforms = GraphTransforms::new();
@@ -143,4 +352,78 @@ mod tests {
             .neighbors_directed(relin_nodes[0], Direction::Outgoing)
             .all(|i| { matches!(query.get_node(i).unwrap().operation, Operation::Add) }),);
     }
+
+    fn count_relinearizations(ir: &FheProgram) -> usize {
+        ir.graph
+            .node_weights()
+            .filter(|n| matches!(n.operation, Operation::Relinearize))
+            .count()
+    }
+
+    /**
+     * Computes `a * b + c * d`, optionally rotating the sum.
+     */
+    fn sum_of_products(rotate: bool) -> FheProgram {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let a = ir.add_input_ciphertext(0);
+        let b = ir.add_input_ciphertext(1);
+        let ab = ir.add_multiply(a, b);
+        let cd = ir.add_multiply(b, a);
+        let sum = ir.add_add(ab, cd);
+
+        let out = if rotate {
+            let amount = ir.add_input_literal(FheProgramLiteral::from(1u64));
+            ir.add_rotate_left(sum, amount)
+        } else {
+            sum
+        };
+
+        ir.add_output_ciphertext(out);
+
+        ir
+    }
+
+    #[test]
+    fn policies_place_relinearizations() {
+        let run = |rotate, policy| {
+            let mut ir = sum_of_products(rotate);
+
+            apply_relinearization_policy(&mut ir, policy);
+            crate::verify_with_policy(&ir, policy).unwrap();
+
+            count_relinearizations(&ir)
+        };
+
+        assert_eq!(run(false, RelinearizationPolicy::Always), 2);
+        assert_eq!(run(false, RelinearizationPolicy::Lazy), 1);
+        assert_eq!(run(false, RelinearizationPolicy::SizeExceeds(3)), 0);
+
+        // Rotations need relinearized operands under every policy.
+        assert_eq!(run(true, RelinearizationPolicy::Lazy), 1);
+        assert_eq!(run(true, RelinearizationPolicy::SizeExceeds(8)), 1);
+    }
+
+    #[test]
+    fn size_limit_bounds_chained_products() {
+        let cube = |policy| {
+            let mut ir = FheProgram::new(SchemeType::Bfv);
+
+            let a = ir.add_input_ciphertext(0);
+            let square = ir.add_multiply(a, a);
+            let cube = ir.add_multiply(square, a);
+            ir.add_output_ciphertext(cube);
+
+            apply_relinearization_policy(&mut ir, policy);
+            crate::verify_with_policy(&ir, policy).unwrap();
+
+            count_relinearizations(&ir)
+        };
+
+        // The square feeds a multiplication, so it's relinearized unless
+        // the cube may have 4 polynomials.
+        assert_eq!(cube(RelinearizationPolicy::Lazy), 2);
+        assert_eq!(cube(RelinearizationPolicy::SizeExceeds(3)), 1);
+        assert_eq!(cube(RelinearizationPolicy::SizeExceeds(4)), 0);
+    }
 }
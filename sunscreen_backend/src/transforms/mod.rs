@@ -1,13 +1,67 @@
+mod common_subexpression_elimination;
+mod fold_columns;
 mod insert_relinearizations;
+mod rebalance;
 
 use petgraph::stable_graph::NodeIndex;
 use sunscreen_fhe_program::{FheProgram, FheProgramTrait};
 
-use insert_relinearizations::apply_insert_relinearizations;
+use common_subexpression_elimination::eliminate_common_subexpressions;
+use fold_columns::fold_columns;
+use insert_relinearizations::apply_relinearization_policy;
+pub use insert_relinearizations::RelinearizationPolicy;
+use rebalance::rebalance;
 
-pub fn transform_intermediate_representation(ir: &mut FheProgram) {
-    apply_insert_relinearizations(ir);
+use crate::verify::{verify_stage, Stage};
+use crate::Result;
 
-    // Dead code elimination.
+type Pass = fn(&mut FheProgram, RelinearizationPolicy);
+
+/**
+ * The backend's passes in the order they run, with the invariants each
+ * leaves the program satisfying.
+ */
+const PASSES: [(&str, Pass, Stage); 5] = [
+    (
+        "common_subexpression_elimination",
+        eliminate_common_subexpressions,
+        Stage::Unrelinearized,
+    ),
+    ("fold_columns", fold_columns, Stage::Unrelinearized),
+    ("rebalance", rebalance, Stage::Unrelinearized),
+    (
+        "insert_relinearizations",
+        apply_relinearization_policy,
+        Stage::Relinearized,
+    ),
+    (
+        "dead_code_elimination",
+        eliminate_dead_code,
+        Stage::Relinearized,
+    ),
+];
+
+fn eliminate_dead_code(ir: &mut FheProgram, _: RelinearizationPolicy) {
     *ir = ir.prune(&ir.get_outputs().collect::<Vec<NodeIndex>>());
 }
+
+pub fn transform_intermediate_representation(ir: &mut FheProgram, policy: RelinearizationPolicy) {
+    for (_, pass, _) in PASSES.iter() {
+        pass(ir, policy);
+    }
+}
+
+/**
+ * Runs the passes as [`transform_intermediate_representation`] does,
+ * verifying the program before the first and after each one.
+ */
+pub fn transform_and_verify(ir: &mut FheProgram, policy: RelinearizationPolicy) -> Result<()> {
+    verify_stage(ir, "input", Stage::Unrelinearized, policy)?;
+
+    for (name, pass, stage) in PASSES.iter() {
+        pass(ir, policy);
+        verify_stage(ir, *name, *stage, policy)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,316 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use petgraph::{algo::toposort, stable_graph::NodeIndex, visit::EdgeRef, Direction};
+use sunscreen_compiler_common::{EdgeInfo, NodeInfo};
+use sunscreen_fhe_program::{FheProgram, Operation};
+
+use crate::RelinearizationPolicy;
+
+/**
+ * How deep a node's value is: its multiplicative depth, then the number
+ * of operations on its longest path from an input.
+ */
+type Depth = (usize, usize);
+
+fn is_associative(operation: &Operation) -> bool {
+    matches!(operation, Operation::Add | Operation::Multiply)
+}
+
+/**
+ * Rebuilds chains of ciphertext additions and multiplications into
+ * balanced trees, e.g. `((a * b) * c) * d` into `(a * b) * (c * d)`.
+ *
+ * # Remarks
+ * Addition and multiplication are associative and commutative in the
+ * plaintext ring, so any tree over the same operands computes the same
+ * value. Each tree pairs its shallowest operands first, which minimizes
+ * its multiplicative depth and hence the noise of its result. Repeated
+ * pairs are only computed once, so `x * x * x * x` takes 2
+ * multiplications rather than 3.
+ *
+ * Intermediate results used anywhere other than the chain keep their
+ * value, as they become operands of the tree rather than part of it.
+ * Trees that can't get shallower are left alone.
+ */
+pub(crate) fn rebalance(ir: &mut FheProgram, _: RelinearizationPolicy) {
+    // Validation rejects cycles, so the sort can't fail.
+    let order = toposort(&ir.graph.0, None).expect("FHE program graph contains a cycle.");
+
+    let mut depths = HashMap::<NodeIndex, Depth>::new();
+
+    for id in order {
+        // Earlier rebuilds may have removed nodes that were in the order.
+        if !ir.graph.contains_node(id) {
+            continue;
+        }
+
+        if is_associative(&ir.graph[id].operation) && !is_absorbed(ir, id) {
+            rebuild_tree(ir, id, &mut depths);
+        }
+
+        let depth = depth_of(ir, id, &depths);
+        depths.insert(id, depth);
+    }
+}
+
+fn depth_of(ir: &FheProgram, id: NodeIndex, depths: &HashMap<NodeIndex, Depth>) -> Depth {
+    let (multiplications, operations) = ir
+        .graph
+        .neighbors_directed(id, Direction::Incoming)
+        .map(|p| depths[&p])
+        .max()
+        .unwrap_or((0, 0));
+
+    match ir.graph[id].operation {
+        Operation::InputCiphertext(_) | Operation::InputPlaintext(_) | Operation::Literal(_) => {
+            (0, 0)
+        }
+        Operation::Multiply => (multiplications + 1, operations + 1),
+        _ => (multiplications, operations + 1),
+    }
+}
+
+/**
+ * Whether `id` is an intermediate result of its only consumer's chain.
+ */
+fn is_absorbed(ir: &FheProgram, id: NodeIndex) -> bool {
+    let mut consumers = ir.graph.edges_directed(id, Direction::Outgoing);
+
+    match (consumers.next(), consumers.next()) {
+        (Some(e), None) => ir.graph[e.target()].operation == ir.graph[id].operation,
+        _ => false,
+    }
+}
+
+/**
+ * Returns the operands of the chain ending at `id` and its intermediate
+ * results.
+ */
+fn collect_tree(ir: &FheProgram, id: NodeIndex) -> (Vec<NodeIndex>, Vec<NodeIndex>) {
+    let mut leaves = vec![];
+    let mut internal = vec![];
+    let mut stack = vec![id];
+
+    while let Some(n) = stack.pop() {
+        for operand in ir.graph.neighbors_directed(n, Direction::Incoming) {
+            if is_absorbed(ir, operand) {
+                internal.push(operand);
+                stack.push(operand);
+            } else {
+                leaves.push(operand);
+            }
+        }
+    }
+
+    (leaves, internal)
+}
+
+fn rebuild_tree(ir: &mut FheProgram, root: NodeIndex, depths: &mut HashMap<NodeIndex, Depth>) {
+    let (leaves, internal) = collect_tree(ir, root);
+
+    if internal.is_empty() {
+        return;
+    }
+
+    let operation = ir.graph[root].operation.clone();
+    let own = usize::from(operation == Operation::Multiply);
+    let combine = |a: Depth, b: Depth| (usize::max(a.0, b.0) + own, usize::max(a.1, b.1) + 1);
+
+    // Find the balanced tree's depth before changing anything. Pairing the
+    // shallowest operands first yields the shallowest tree.
+    let mut heap = leaves
+        .iter()
+        .map(|l| Reverse(depths[l]))
+        .collect::<BinaryHeap<_>>();
+
+    while heap.len() > 1 {
+        let Reverse(a) = heap.pop().unwrap();
+        let Reverse(b) = heap.pop().unwrap();
+
+        heap.push(Reverse(combine(a, b)));
+    }
+
+    let Reverse(balanced) = heap.pop().unwrap();
+
+    if balanced >= depth_of(ir, root, depths) {
+        return;
+    }
+
+    for n in internal {
+        ir.graph.remove_node(n);
+    }
+
+    let incoming = ir
+        .graph
+        .edges_directed(root, Direction::Incoming)
+        .map(|e| e.id())
+        .collect::<Vec<_>>();
+
+    for e in incoming {
+        ir.graph.remove_edge(e);
+    }
+
+    // Ties go to the earliest created node, which keeps the result
+    // deterministic.
+    let mut heap = leaves
+        .iter()
+        .map(|l| Reverse((depths[l], l.index(), *l)))
+        .collect::<BinaryHeap<_>>();
+
+    let mut pairs = HashMap::<(NodeIndex, NodeIndex), NodeIndex>::new();
+
+    while heap.len() > 2 {
+        let Reverse((a_depth, _, a)) = heap.pop().unwrap();
+        let Reverse((b_depth, _, b)) = heap.pop().unwrap();
+
+        let key = (NodeIndex::min(a, b), NodeIndex::max(a, b));
+
+        let n = match pairs.get(&key) {
+            Some(n) => *n,
+            None => {
+                let n = ir.graph.add_node(NodeInfo::new(operation.clone()));
+                ir.graph.add_edge(a, n, EdgeInfo::Left);
+                ir.graph.add_edge(b, n, EdgeInfo::Right);
+
+                depths.insert(n, combine(a_depth, b_depth));
+                pairs.insert(key, n);
+
+                n
+            }
+        };
+
+        heap.push(Reverse((depths[&n], n.index(), n)));
+    }
+
+    let Reverse((_, _, a)) = heap.pop().unwrap();
+    let Reverse((_, _, b)) = heap.pop().unwrap();
+
+    ir.graph.add_edge(a, root, EdgeInfo::Left);
+    ir.graph.add_edge(b, root, EdgeInfo::Right);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile_inplace_verified;
+    use sunscreen_fhe_program::{FheProgramTrait, ProgramGraph, SchemeType};
+
+    fn count(ir: &FheProgram, operation: Operation) -> usize {
+        ir.graph
+            .node_indices()
+            .filter(|n| ir.graph[*n].operation == operation)
+            .count()
+    }
+
+    #[test]
+    fn balances_product_chains() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let inputs = (0..8)
+            .map(|i| ir.add_input_ciphertext(i))
+            .collect::<Vec<_>>();
+
+        let mut product = inputs[0];
+
+        for x in &inputs[1..] {
+            product = ir.add_multiply(product, *x);
+        }
+
+        ir.add_output_ciphertext(product);
+
+        assert_eq!(ProgramGraph::new(&ir).multiplicative_depth(), 7);
+
+        rebalance(&mut ir, RelinearizationPolicy::Always);
+
+        assert_eq!(ir.validate(), Ok(()));
+        assert_eq!(ProgramGraph::new(&ir).multiplicative_depth(), 3);
+        assert_eq!(count(&ir, Operation::Multiply), 7);
+
+        let ir = compile_inplace_verified(ir).unwrap();
+
+        assert_eq!(ProgramGraph::new(&ir).multiplicative_depth(), 3);
+    }
+
+    #[test]
+    fn shares_repeated_pairs() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let x = ir.add_input_ciphertext(0);
+        let mut power = x;
+
+        for _ in 0..3 {
+            power = ir.add_multiply(power, x);
+        }
+
+        ir.add_output_ciphertext(power);
+
+        rebalance(&mut ir, RelinearizationPolicy::Always);
+
+        assert_eq!(ir.validate(), Ok(()));
+        assert_eq!(ProgramGraph::new(&ir).multiplicative_depth(), 2);
+        assert_eq!(count(&ir, Operation::Multiply), 2);
+    }
+
+    #[test]
+    fn keeps_shared_intermediates() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let inputs = (0..4)
+            .map(|i| ir.add_input_ciphertext(i))
+            .collect::<Vec<_>>();
+
+        let ab = ir.add_multiply(inputs[0], inputs[1]);
+        let abc = ir.add_multiply(ab, inputs[2]);
+        let abcd = ir.add_multiply(abc, inputs[3]);
+
+        // abc is also an output, so only abcd's tree can change, and
+        // it can't get shallower.
+        ir.add_output_ciphertext(abc);
+        ir.add_output_ciphertext(abcd);
+
+        let before = ir.clone();
+
+        rebalance(&mut ir, RelinearizationPolicy::Always);
+
+        assert_eq!(ir.graph.node_count(), before.graph.node_count());
+        assert_eq!(ProgramGraph::new(&ir).multiplicative_depth(), 3);
+    }
+
+    #[test]
+    fn balances_sums_without_changing_products() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let inputs = (0..4)
+            .map(|i| ir.add_input_ciphertext(i))
+            .collect::<Vec<_>>();
+
+        let square = ir.add_multiply(inputs[0], inputs[0]);
+        let mut sum = square;
+
+        for x in &inputs[1..] {
+            sum = ir.add_add(sum, *x);
+        }
+
+        ir.add_output_ciphertext(sum);
+
+        rebalance(&mut ir, RelinearizationPolicy::Always);
+
+        assert_eq!(ir.validate(), Ok(()));
+        assert_eq!(count(&ir, Operation::Add), 3);
+        assert_eq!(count(&ir, Operation::Multiply), 1);
+
+        // The square joins the sum last.
+        let output = ir.get_outputs().next().unwrap();
+        let root = ir
+            .graph
+            .neighbors_directed(output, Direction::Incoming)
+            .next()
+            .unwrap();
+
+        assert!(ir
+            .graph
+            .neighbors_directed(root, Direction::Incoming)
+            .any(|n| n == square));
+    }
+}
@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use petgraph::{algo::toposort, stable_graph::NodeIndex, visit::EdgeRef, Direction};
+use sunscreen_fhe_program::{
+    FheProgram, FheProgramTrait, Operation::*, OutputType, OutputTypeTrait,
+};
+
+use crate::{Error, RelinearizationPolicy, Result};
+
+/**
+ * The invariants an [`FheProgram`] must satisfy at a given point in
+ * compilation.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Stage {
+    /**
+     * The program is well typed and acyclic. Multiplications may not yet
+     * be relinearized.
+     */
+    Unrelinearized,
+
+    /**
+     * Additionally, multiplications are relinearized as the
+     * [`RelinearizationPolicy`] requires: always before their results
+     * reach a rotation or row swap, and before multiplications and outputs
+     * would exceed the policy's ciphertext sizes.
+     */
+    Relinearized,
+}
+
+/**
+ * An IR invariant broken by a compiler pass.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /**
+     * The program has a cycle or a node with missing or mistyped
+     * operands.
+     */
+    InvalidIR(sunscreen_fhe_program::Error),
+
+    /**
+     * A node requiring a 2-polynomial ciphertext takes one with `size`
+     * polynomials, i.e. a multiplication result that wasn't relinearized.
+     */
+    UnrelinearizedOperand {
+        /**
+         * The offending node.
+         */
+        node: NodeIndex,
+
+        /**
+         * The node's operation.
+         */
+        operation: String,
+
+        /**
+         * The operand's number of polynomials.
+         */
+        size: usize,
+    },
+
+    /**
+     * A relinearization takes a ciphertext that is already relinearized.
+     */
+    RedundantRelinearization(NodeIndex),
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidIR(e) => write!(f, "{}", e),
+            Self::UnrelinearizedOperand {
+                node,
+                operation,
+                size,
+            } => write!(
+                f,
+                "node {}:{} takes a ciphertext with {} polynomials that was never relinearized",
+                operation,
+                node.index(),
+                size
+            ),
+            Self::RedundantRelinearization(node) => write!(
+                f,
+                "node Relinearize:{} takes a ciphertext that is already relinearized",
+                node.index()
+            ),
+        }
+    }
+}
+
+/**
+ * Describes where and how an [`FheProgram`] failed verification.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationFailure {
+    /**
+     * The pass after which verification failed. `"input"` if the program
+     * was invalid before any pass ran and `"output"` for programs checked
+     * with [`verify`].
+     */
+    pub pass: &'static str,
+
+    /**
+     * The broken invariant.
+     */
+    pub violation: Violation,
+}
+
+impl Display for VerificationFailure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "FHE program failed verification after {}: {}",
+            self.pass, self.violation
+        )
+    }
+}
+
+/**
+ * Checks that a compiled [`FheProgram`] is well typed, acyclic and
+ * relinearizes every multiplication before its result is rotated,
+ * multiplied again or output.
+ *
+ * # Remarks
+ * Use [`verify_with_policy`] for programs compiled with a
+ * [`RelinearizationPolicy`] other than the default.
+ *
+ * Sunscreen programs never switch moduli, so every ciphertext stays at the
+ * top level and its size (number of polynomials) is the only per-value
+ * state to check.
+ *
+ * Returns [`Error::VerificationFailed`] describing the first broken
+ * invariant.
+ */
+pub fn verify(ir: &FheProgram) -> Result<()> {
+    verify_with_policy(ir, RelinearizationPolicy::default())
+}
+
+/**
+ * Checks a compiled [`FheProgram`] as [`verify`] does, allowing the
+ * ciphertext sizes `policy` leaves unrelinearized.
+ */
+pub fn verify_with_policy(ir: &FheProgram, policy: RelinearizationPolicy) -> Result<()> {
+    verify_stage(ir, "output", Stage::Relinearized, policy)
+}
+
+pub(crate) fn verify_stage(
+    ir: &FheProgram,
+    pass: &'static str,
+    stage: Stage,
+    policy: RelinearizationPolicy,
+) -> Result<()> {
+    let fail =
+        |violation| Error::VerificationFailed(Box::new(VerificationFailure { pass, violation }));
+
+    ir.validate().map_err(|e| fail(Violation::InvalidIR(e)))?;
+
+    // Validation rejects cycles, so the sort can't fail.
+    let order = toposort(&ir.graph.0, None).expect("Validated IR has no cycles.");
+    let mut sizes = HashMap::<NodeIndex, usize>::new();
+
+    for id in order {
+        let operands = ir
+            .graph
+            .edges_directed(id, Direction::Incoming)
+            .map(|e| e.source())
+            .filter(|x| ir.graph[*x].output_type() == OutputType::Ciphertext)
+            .map(|x| sizes[&x])
+            .collect::<Vec<usize>>();
+
+        let largest = operands.iter().copied().max().unwrap_or(2);
+        let operation = &ir.graph[id].operation;
+
+        let relinearized = stage == Stage::Relinearized;
+
+        let size = match operation {
+            InputPlaintext(_) | Literal(_) => continue,
+            InputCiphertext(_) => 2,
+            Multiply => {
+                // Each multiplication adds the operands' degrees.
+                let product = operands.iter().sum::<usize>() + 1 - operands.len();
+
+                if relinearized && product > policy.max_size() {
+                    return Err(fail(Violation::UnrelinearizedOperand {
+                        node: id,
+                        operation: operation.to_string(),
+                        size: largest,
+                    }));
+                }
+
+                product
+            }
+            Relinearize => {
+                if largest <= 2 {
+                    return Err(fail(Violation::RedundantRelinearization(id)));
+                }
+
+                2
+            }
+            ShiftLeft | ShiftRight | SwapRows | OutputCiphertext => {
+                let limit = match operation {
+                    OutputCiphertext => policy.max_output_size(),
+                    _ => 2,
+                };
+
+                if relinearized && largest > limit {
+                    return Err(fail(Violation::UnrelinearizedOperand {
+                        node: id,
+                        operation: operation.to_string(),
+                        size: largest,
+                    }));
+                }
+
+                largest
+            }
+            Add | Sub | Negate | AddPlaintext | SubPlaintext | MultiplyPlaintext => largest,
+        };
+
+        sizes.insert(id, size);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile_inplace_verified;
+    use sunscreen_fhe_program::{Literal as FheProgramLiteral, SchemeType};
+
+    fn square_and_rotate() -> (FheProgram, NodeIndex) {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let ct = ir.add_input_ciphertext(0);
+        let mul = ir.add_multiply(ct, ct);
+        let amount = ir.add_input_literal(FheProgramLiteral::from(1u64));
+        let rotate = ir.add_rotate_left(mul, amount);
+        ir.add_output_ciphertext(rotate);
+
+        (ir, rotate)
+    }
+
+    #[test]
+    fn compiled_programs_pass_verification() {
+        let (ir, _) = square_and_rotate();
+
+        let ir = compile_inplace_verified(ir).unwrap();
+
+        assert_eq!(verify(&ir), Ok(()));
+    }
+
+    #[test]
+    fn rejects_unrelinearized_rotation() {
+        let (ir, rotate) = square_and_rotate();
+
+        assert_eq!(
+            verify_stage(
+                &ir,
+                "input",
+                Stage::Unrelinearized,
+                RelinearizationPolicy::Always
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            verify(&ir),
+            Err(Error::VerificationFailed(Box::new(VerificationFailure {
+                pass: "output",
+                violation: Violation::UnrelinearizedOperand {
+                    node: rotate,
+                    operation: "ShiftLeft".to_owned(),
+                    size: 3,
+                },
+            })))
+        );
+    }
+
+    #[test]
+    fn rejects_redundant_relinearization() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let ct = ir.add_input_ciphertext(0);
+        let relin = ir.add_relinearize(ct);
+        ir.add_output_ciphertext(relin);
+
+        assert_eq!(
+            verify(&ir),
+            Err(Error::VerificationFailed(Box::new(VerificationFailure {
+                pass: "output",
+                violation: Violation::RedundantRelinearization(relin),
+            })))
+        );
+    }
+
+    #[test]
+    fn rejects_mistyped_operands() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let ct = ir.add_input_ciphertext(0);
+        let literal = ir.add_input_literal(FheProgramLiteral::from(7u64));
+        let add = ir.add_add(ct, literal);
+        ir.add_output_ciphertext(add);
+
+        assert!(matches!(
+            compile_inplace_verified(ir),
+            Err(Error::VerificationFailed(f))
+                if f.pass == "input" && matches!(f.violation, Violation::InvalidIR(_))
+        ));
+    }
+}
@@ -73,6 +73,17 @@ pub trait Operation: Clone + Debug + Hash + PartialEq + Eq {
      * ordered operands.
      */
     fn is_ordered(&self) -> bool;
+
+    /**
+     * Whether or not
+     * [`common_subexpression_elimination`](transforms::common_subexpression_elimination)
+     * may merge nodes with this operation and the same operands.
+     * Operations whose nodes are distinct even then (e.g. outputs) should
+     * return `false`.
+     */
+    fn is_mergeable(&self) -> bool {
+        true
+    }
 }
 
 /**
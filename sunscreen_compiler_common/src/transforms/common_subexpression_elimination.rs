@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::Infallible;
 
 use petgraph::{
@@ -78,10 +78,18 @@ pub fn common_subexpression_elimination<O: Operation>(
         // the node that matches such a key.
         let mut visited_nodes = HashMap::<(NodeIndex, Option<NodeIndex>, &O), NodeIndex>::new();
 
+        // Children taking this node as both operands (e.g. x * x) appear
+        // twice among its neighbors. Only consider them once.
+        let mut children = HashSet::new();
+
         // Look through out immediate children. If we find any of the
         // type that share an edge with another node, consolidate them into
         // one and fix up their outputs.
         for e in query.neighbors_directed(index, Direction::Outgoing) {
+            if !children.insert(e) {
+                continue;
+            }
+
             // Unwrapping is okay because index e is a node in the graph.
             let child_node = query.get_node(e).unwrap();
 
@@ -104,6 +112,10 @@ pub fn common_subexpression_elimination<O: Operation>(
 
             let child_op = &child_node.operation;
 
+            if !child_op.is_mergeable() {
+                continue;
+            }
+
             if child_op.is_binary() {
                 let (left, right) = get_binary_operands(&query, e);
 
@@ -151,6 +163,7 @@ mod tests {
         Sub,
         Mul,
         Neg,
+        Output,
         PublicInput(NodeIndex),
     }
 
@@ -164,7 +177,7 @@ mod tests {
         }
 
         fn is_unary(&self) -> bool {
-            matches!(self, Operation::Neg)
+            matches!(self, Operation::Neg | Operation::Output)
         }
 
         fn is_unordered(&self) -> bool {
@@ -174,6 +187,10 @@ mod tests {
         fn is_ordered(&self) -> bool {
             false
         }
+
+        fn is_mergeable(&self) -> bool {
+            !matches!(self, Operation::Output)
+        }
     }
 
     fn get_graph() -> CompilationResult<Operation> {
@@ -357,4 +374,48 @@ mod tests {
 
         assert!(equals);
     }
+
+    #[test]
+    fn merges_squares_and_keeps_unmergeable_nodes() {
+        let make_node = |operation| NodeInfo { operation };
+
+        let mut fe = CompilationResult::new();
+
+        let x = fe.add_node(make_node(Operation::PublicInput(NodeIndex::from(0))));
+        let square_1 = fe.add_node(make_node(Operation::Mul));
+        let square_2 = fe.add_node(make_node(Operation::Mul));
+
+        for square in [square_1, square_2] {
+            fe.add_edge(x, square, EdgeInfo::Left);
+            fe.add_edge(x, square, EdgeInfo::Right);
+        }
+
+        let out_1 = fe.add_node(make_node(Operation::Output));
+        let out_2 = fe.add_node(make_node(Operation::Output));
+
+        fe.add_edge(square_1, out_1, EdgeInfo::Unary);
+        fe.add_edge(square_2, out_2, EdgeInfo::Unary);
+
+        common_subexpression_elimination(&mut fe.0);
+
+        let mut expected = CompilationResult::new();
+
+        let x = expected.add_node(make_node(Operation::PublicInput(NodeIndex::from(0))));
+        let square = expected.add_node(make_node(Operation::Mul));
+
+        expected.add_edge(x, square, EdgeInfo::Left);
+        expected.add_edge(x, square, EdgeInfo::Right);
+
+        for _ in 0..2 {
+            let out = expected.add_node(make_node(Operation::Output));
+            expected.add_edge(square, out, EdgeInfo::Unary);
+        }
+
+        assert!(is_isomorphic_matching(
+            &Graph::from(fe.0),
+            &Graph::from(expected.0),
+            |x, y| x == y,
+            |x, y| x == y,
+        ));
+    }
 }
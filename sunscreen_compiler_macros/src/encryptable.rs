@@ -0,0 +1,155 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    parse_macro_input, spanned::Spanned, Data, DeriveInput, Fields, GenericArgument, PathArguments,
+    Type,
+};
+
+use crate::error::{Error, Result};
+
+pub fn derive_encryptable(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match derive_encryptable_inner(input) {
+        Ok(s) => s.into(),
+        Err(Error::CompileError(s, msg)) => quote_spanned! {
+            s => compile_error! { #msg }
+        }
+        .into(),
+    }
+}
+
+/**
+ * Returns whether the given type is a `Vec<T>`.
+ */
+fn is_vec(ty: &Type) -> bool {
+    let path = match ty {
+        Type::Path(p) if p.qself.is_none() => &p.path,
+        _ => return false,
+    };
+
+    match path.segments.last() {
+        Some(segment) if segment.ident == "Vec" => match &segment.arguments {
+            PathArguments::AngleBracketed(args) => {
+                args.args.len() == 1 && matches!(args.args[0], GenericArgument::Type(_))
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn derive_encryptable_inner(input: DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+    let vis = &input.vis;
+
+    if !input.generics.params.is_empty() {
+        return Err(Error::compile_error(
+            input.generics.span(),
+            "Encryptable can't be derived for generic types.",
+        ));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(f) => &f.named,
+            _ => {
+                return Err(Error::compile_error(
+                    input.ident.span(),
+                    "Encryptable can only be derived for structs with named fields.",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::compile_error(
+                input.ident.span(),
+                "Encryptable can only be derived for structs.",
+            ))
+        }
+    };
+
+    let encrypted_name = format_ident!("Encrypted{}", name);
+
+    let mut encrypted_fields = vec![];
+    let mut encrypt_fields = vec![];
+    let mut decrypt_fields = vec![];
+
+    for f in fields.iter() {
+        let field_vis = &f.vis;
+        let field_name = f.ident.as_ref().unwrap();
+        let field_doc = format!(" The encrypted `{field_name}`.");
+
+        if is_vec(&f.ty) {
+            encrypted_fields.push(quote! {
+                #[doc = #field_doc]
+                #field_vis #field_name: Vec<sunscreen::Ciphertext>
+            });
+            encrypt_fields.push(quote! {
+                #field_name: self.#field_name
+                    .iter()
+                    .map(|x| runtime.encrypt(x.clone(), public_key))
+                    .collect::<std::result::Result<Vec<_>, sunscreen::RuntimeError>>()?
+            });
+            decrypt_fields.push(quote! {
+                #field_name: self.#field_name
+                    .iter()
+                    .map(|x| runtime.decrypt(x, private_key))
+                    .collect::<std::result::Result<Vec<_>, sunscreen::RuntimeError>>()?
+            });
+        } else {
+            encrypted_fields.push(quote! {
+                #[doc = #field_doc]
+                #field_vis #field_name: sunscreen::Ciphertext
+            });
+            encrypt_fields.push(quote! {
+                #field_name: runtime.encrypt(self.#field_name.clone(), public_key)?
+            });
+            decrypt_fields.push(quote! {
+                #field_name: runtime.decrypt(&self.#field_name, private_key)?
+            });
+        }
+    }
+
+    let struct_doc = format!(" The encrypted form of [`{name}`], created by `{name}::encrypt`.");
+
+    Ok(quote! {
+        #[derive(Clone)]
+        #[doc = #struct_doc]
+        #vis struct #encrypted_name {
+            #(#encrypted_fields,)*
+        }
+
+        impl #name {
+            /// Encrypts each field under `public_key`. `Vec` fields are
+            /// encrypted one element per ciphertext.
+            #vis fn encrypt<T, B>(
+                &self,
+                runtime: &sunscreen::GenericRuntime<T, B>,
+                public_key: &sunscreen::PublicKey,
+            ) -> sunscreen::Result<#encrypted_name>
+            where
+                T: sunscreen::marker::Fhe,
+            {
+                Ok(#encrypted_name {
+                    #(#encrypt_fields,)*
+                })
+            }
+        }
+
+        impl #encrypted_name {
+            /// Decrypts each field with `private_key`.
+            #vis fn decrypt<T, B>(
+                &self,
+                runtime: &sunscreen::GenericRuntime<T, B>,
+                private_key: &sunscreen::PrivateKey,
+            ) -> sunscreen::Result<#name>
+            where
+                T: sunscreen::marker::Fhe,
+            {
+                Ok(#name {
+                    #(#decrypt_fields,)*
+                })
+            }
+        }
+    })
+}
@@ -30,6 +30,7 @@ pub fn fhe_program_impl(
     };
 
     let chain_count = attr_params.chain_count;
+    let requires = &attr_params.requires;
 
     let unwrapped_inputs = match extract_fn_arguments(inputs) {
         Ok(v) => {
@@ -58,11 +59,21 @@ pub fn fhe_program_impl(
         }
     };
 
+    let argument_names = unwrapped_inputs
+        .iter()
+        .map(|(_, _, name)| name.to_string())
+        .collect::<Vec<String>>();
+
     let argument_types = unwrapped_inputs
         .iter()
         .map(|(_, t, _)| (**t).clone())
         .collect::<Vec<Type>>();
 
+    let input_types = argument_types
+        .iter()
+        .map(map_input_type)
+        .collect::<Vec<Type>>();
+
     let fhe_program_args = unwrapped_inputs
         .iter()
         .map(|i| {
@@ -122,6 +133,66 @@ pub fn fhe_program_impl(
 
     let fhe_program_name_literal = format!("{}", fhe_program_name);
 
+    let plain_fn = if attr_params.plain {
+        let plain_args = match unwrapped_inputs
+            .iter()
+            .map(|(_, ty, name)| {
+                let ty = map_plain_type(ty)?;
+
+                Ok(quote! { #name: #ty })
+            })
+            .collect::<Result<Vec<TokenStream>, MapFheTypeError>>()
+        {
+            Ok(v) => v,
+            Err(MapFheTypeError::IllegalType(s)) => {
+                return proc_macro::TokenStream::from(
+                    quote_spanned! {s => compile_error! {"Plain FHE program arguments must be an array or named struct type."}},
+                );
+            }
+        };
+
+        let plain_arg_types = match argument_types
+            .iter()
+            .map(map_plain_type)
+            .collect::<Result<Vec<Type>, MapFheTypeError>>()
+        {
+            Ok(v) => v,
+            Err(MapFheTypeError::IllegalType(s)) => {
+                return proc_macro::TokenStream::from(
+                    quote_spanned! {s => compile_error! {"Plain FHE program arguments must be an array or named struct type."}},
+                );
+            }
+        };
+
+        let plain_return = match return_types
+            .iter()
+            .map(map_plain_type)
+            .collect::<Result<Vec<Type>, MapFheTypeError>>()
+        {
+            Ok(v) => pack_return_type(&v),
+            Err(MapFheTypeError::IllegalType(s)) => {
+                return proc_macro::TokenStream::from(
+                    quote_spanned! {s => compile_error! {"Each return type for an FHE program must be either an array or named struct type."}},
+                );
+            }
+        };
+
+        quote! {
+            impl #fhe_program_struct_name {
+                /**
+                 * Returns a plain Rust function that computes what this
+                 * program does, with every `Cipher<T>` replaced by `T`.
+                 */
+                #[allow(clippy::type_complexity)]
+                #vis fn as_plain_fn(&self) -> impl Fn(#(#plain_arg_types),*) -> #plain_return {
+                    | #(#plain_args),* | -> #plain_return #body
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
     proc_macro::TokenStream::from(quote! {
         #[allow(non_camel_case_types)]
         #[derive(Clone)]
@@ -199,6 +270,18 @@ pub fn fhe_program_impl(
             fn chain_count(&self) -> usize {
                 self.chain_count
             }
+
+            fn requirements(&self) -> Vec<String> {
+                vec![#(#requires.to_owned()),*]
+            }
+
+            fn argument_names(&self) -> Vec<String> {
+                vec![#(#argument_names.to_owned()),*]
+            }
+        }
+
+        impl sunscreen::TypedFheProgramFn for #fhe_program_struct_name {
+            type Arguments = (#(#input_types,)*);
         }
 
         impl AsRef<str> for #fhe_program_struct_name {
@@ -209,6 +292,8 @@ pub fn fhe_program_impl(
             }
         }
 
+        #plain_fn
+
         #[allow(non_upper_case_globals)]
         #vis const #fhe_program_name: #fhe_program_struct_name = #fhe_program_struct_name {
             chain_count: #chain_count
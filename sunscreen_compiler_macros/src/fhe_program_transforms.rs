@@ -1,6 +1,9 @@
 use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::{format_ident, quote, quote_spanned};
-use syn::{parse_quote, parse_quote_spanned, spanned::Spanned, Index, ReturnType, Type};
+use syn::{
+    parse_quote, parse_quote_spanned, spanned::Spanned, GenericArgument, Index, PathArguments,
+    ReturnType, Type,
+};
 
 #[derive(Debug)]
 pub enum MapFheTypeError {
@@ -31,6 +34,72 @@ pub fn map_fhe_type(arg_type: &Type) -> Result<Type, MapFheTypeError> {
     Ok(transformed_type)
 }
 
+/**
+ * Given an input type T, returns the type the program's plain-Rust
+ * equivalent uses:
+ * * U when T is `Cipher<U>`
+ * * T when T is any other Path
+ * * [map_plain_type(T); N] when T is Array
+ */
+pub fn map_plain_type(arg_type: &Type) -> Result<Type, MapFheTypeError> {
+    let transformed_type = match arg_type {
+        Type::Path(ty) => {
+            let last = ty.path.segments.last();
+
+            match last.map(|s| (&s.ident, &s.arguments)) {
+                Some((ident, PathArguments::AngleBracketed(args)))
+                    if ident == "Cipher" && args.args.len() == 1 =>
+                {
+                    match &args.args[0] {
+                        GenericArgument::Type(inner) => inner.clone(),
+                        _ => return Err(MapFheTypeError::IllegalType(arg_type.span())),
+                    }
+                }
+                _ => arg_type.clone(),
+            }
+        }
+        Type::Array(a) => {
+            let inner_type = map_plain_type(&a.elem)?;
+            let len = &a.len;
+
+            parse_quote_spanned! {a.span() =>
+                [#inner_type; #len]
+            }
+        }
+        _ => {
+            return Err(MapFheTypeError::IllegalType(arg_type.span()));
+        }
+    };
+
+    Ok(transformed_type)
+}
+
+/**
+ * Given an input type T, returns the type of the value a caller passes
+ * to run the program:
+ * * `sunscreen::Ciphertext` when T contains a `Cipher`
+ * * T otherwise
+ */
+pub fn map_input_type(arg_type: &Type) -> Type {
+    if contains_cipher(arg_type) {
+        parse_quote_spanned! {arg_type.span() => sunscreen::Ciphertext }
+    } else {
+        arg_type.clone()
+    }
+}
+
+fn contains_cipher(arg_type: &Type) -> bool {
+    match arg_type {
+        Type::Path(ty) => ty
+            .path
+            .segments
+            .last()
+            .map_or(false, |s| s.ident == "Cipher"),
+        Type::Array(a) => contains_cipher(&a.elem),
+        _ => false,
+    }
+}
+
 /**
  * Emits code to make an FHE program node for the given
  * type T.
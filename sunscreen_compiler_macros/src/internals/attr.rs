@@ -190,13 +190,15 @@ impl TryFrom<&AttrValue> for Scheme {
 pub struct FheProgramAttrs {
     pub scheme: Scheme,
     pub chain_count: usize,
+    pub requires: Vec<String>,
+    pub plain: bool,
 }
 
 impl Parse for FheProgramAttrs {
     fn parse(input: ParseStream) -> SynResult<Self> {
         let attrs = try_parse_dict(input)?;
 
-        const VALUE_KEYS: &[&str] = &["scheme", "chain_count"];
+        const VALUE_KEYS: &[&str] = &["scheme", "chain_count", "requires", "plain"];
 
         for i in attrs.keys() {
             if !VALUE_KEYS.iter().any(|x| x == i) {
@@ -214,9 +216,32 @@ impl Parse for FheProgramAttrs {
             .map(|x| x.as_usize())
             .unwrap_or(Ok(1))?;
 
+        let requires = match attrs.get("requires") {
+            Some(x) => x
+                .as_str()?
+                .split(',')
+                .map(|r| r.trim().to_owned())
+                .filter(|r| !r.is_empty())
+                .collect(),
+            None => vec![],
+        };
+
+        let plain = match attrs.get("plain") {
+            Some(AttrValue::Present(_)) => true,
+            Some(x) => {
+                return Err(SynError::new(
+                    x.span(),
+                    "`plain` doesn't take a value".to_owned(),
+                ))
+            }
+            None => false,
+        };
+
         Ok(Self {
             scheme,
             chain_count,
+            requires,
+            plain,
         })
     }
 }
@@ -6,6 +6,7 @@
 
 extern crate proc_macro;
 
+mod encryptable;
 mod error;
 mod fhe_program;
 mod fhe_program_transforms;
@@ -21,6 +22,21 @@ pub fn derive_typename(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
     type_name::derive_typename(input)
 }
 
+#[proc_macro_derive(Encryptable)]
+/**
+ * Allows you to `#[derive(Encryptable)]` on a struct whose fields are FHE
+ * types (e.g. `Signed`) or `Vec`s of them.
+ *
+ * For a struct `Foo`, this generates a struct `EncryptedFoo` with the same
+ * fields, each holding a `Ciphertext` (or a `Vec<Ciphertext>` with one
+ * ciphertext per element), along with `Foo::encrypt` and
+ * `EncryptedFoo::decrypt` methods that take a runtime and a public or
+ * private key.
+ */
+pub fn derive_encryptable(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    encryptable::derive_encryptable(input)
+}
+
 #[proc_macro_attribute]
 /**
  * Specifies a function to be an [`fhe_program`](macro@fhe_program). An [`fhe_program`](macro@fhe_program) has any number of inputs that impl the
@@ -32,6 +48,14 @@ pub fn derive_typename(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
  *
  * # Parameters
  * * `scheme` (required): Designates the scheme this [`fhe_program`](macro@fhe_program) uses. Today, this must be `"bfv"`.
+ * * `requires` (optional): A comma-separated list of authorization requirements (e.g. `"role:analyst"`)
+ *   recorded in the compiled program's metadata. The runtime refuses to run the program unless a
+ *   policy provider grants every requirement.
+ * * `plain` (optional): Also generates `as_plain_fn()`, which returns a closure running the
+ *   program's body on unencrypted values (i.e. with each `Cipher<T>` replaced by `T`). Use it
+ *   in tests as a reference implementation that can't drift from the circuit. Results agree
+ *   with the FHE program's modulo the plaintext modulus, provided the plain types don't
+ *   overflow. Every operation the body performs must also exist on the plain types.
  *
  * # Examples
  * ```rust,ignore
@@ -7,6 +7,7 @@
 mod error;
 mod literal;
 mod operation;
+mod query;
 
 mod validation;
 
@@ -16,15 +17,18 @@ use petgraph::{
     graph::{Graph, NodeIndex},
     stable_graph::StableGraph,
     visit::IntoNeighbors,
+    Direction,
 };
 use serde::{Deserialize, Serialize};
 
 pub use error::*;
 pub use literal::*;
 pub use operation::*;
+pub use query::*;
 pub use seal_fhe::SecurityLevel;
+pub use sunscreen_compiler_common::EdgeInfo;
 
-use sunscreen_compiler_common::{CompilationResult, Context, EdgeInfo, NodeInfo};
+use sunscreen_compiler_common::{CompilationResult, Context, NodeInfo};
 
 use std::collections::HashSet;
 
@@ -232,6 +236,11 @@ pub trait FheProgramTrait {
      */
     fn append_rotate_right(&mut self, x: NodeIndex, y: NodeIndex) -> NodeIndex;
 
+    /**
+     * Appends an operation that swaps the rows of ciphertext `x`.
+     */
+    fn add_swap_rows(&mut self, x: NodeIndex) -> NodeIndex;
+
     /**
      * Returns the node indices of output ciphertexts
      */
@@ -267,6 +276,16 @@ pub trait FheProgramTrait {
      * operations.
      */
     fn requires_galois_keys(&self) -> bool;
+
+    /**
+     * Returns the input nodes on which no output depends.
+     *
+     * # Remarks
+     * Callers must still supply these inputs when running the program, but
+     * their values are never used. This usually indicates a mistake in the
+     * FHE program.
+     */
+    fn unused_inputs(&self) -> Vec<NodeIndex>;
 }
 
 impl FheProgramTrait for FheProgram {
@@ -318,6 +337,10 @@ impl FheProgramTrait for FheProgram {
         self.add_binary_operation(Operation::ShiftRight, x, y)
     }
 
+    fn add_swap_rows(&mut self, x: NodeIndex) -> NodeIndex {
+        self.add_unary_operation(Operation::SwapRows, x)
+    }
+
     fn get_outputs(&self) -> Box<dyn Iterator<Item = NodeIndex> + '_> {
         Box::new(
             self.graph
@@ -411,6 +434,27 @@ impl FheProgramTrait for FheProgram {
             )
         })
     }
+
+    fn unused_inputs(&self) -> Vec<NodeIndex> {
+        let mut visited = HashSet::new();
+        let mut visit: Vec<NodeIndex> = self.get_outputs().collect();
+
+        while let Some(node) = visit.pop() {
+            if visited.insert(node) {
+                visit.extend(self.graph.neighbors_directed(node, Direction::Incoming));
+            }
+        }
+
+        self.graph
+            .node_indices()
+            .filter(|n| {
+                matches!(
+                    self.graph[*n].operation,
+                    Operation::InputCiphertext(_) | Operation::InputPlaintext(_)
+                ) && !visited.contains(n)
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -448,6 +492,26 @@ mod tests {
         assert!(eq(&pruned, &expected_ir));
     }
 
+    #[test]
+    fn can_find_unused_inputs() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let a = ir.add_input_ciphertext(0);
+        let b = ir.add_input_ciphertext(1);
+        let c = ir.add_input_plaintext(2);
+        let d = ir.add_input_ciphertext(3);
+        let add = ir.add_add(a, a);
+        ir.add_multiply_plaintext(add, c);
+        // b only feeds dead code.
+        ir.add_add(b, a);
+        ir.add_output_ciphertext(add);
+
+        let mut unused = ir.unused_inputs();
+        unused.sort();
+
+        assert_eq!(unused, vec![b, c, d]);
+    }
+
     #[test]
     fn can_prune_graph_with_removed_nodes() {
         let mut ir = FheProgram::new(SchemeType::Bfv);
@@ -133,4 +133,10 @@ impl OperationTrait for Operation {
     fn is_ordered(&self) -> bool {
         false
     }
+
+    fn is_mergeable(&self) -> bool {
+        // Each output node is a distinct program output, even when two
+        // return the same value.
+        !matches!(self, Self::OutputCiphertext)
+    }
 }
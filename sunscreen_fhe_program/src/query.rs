@@ -0,0 +1,357 @@
+use petgraph::{
+    algo::toposort,
+    stable_graph::NodeIndex,
+    visit::{EdgeRef, IntoEdgeReferences},
+    Direction,
+};
+use sunscreen_compiler_common::EdgeInfo;
+
+use crate::{FheProgram, FheProgramTrait, Operation};
+
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/**
+ * A node in a [`ProgramGraph`].
+ */
+pub struct ProgramNode<'a> {
+    /**
+     * The node's id, which is stable for the lifetime of the program.
+     * Ids need not be contiguous.
+     */
+    pub id: usize,
+
+    /**
+     * The operation the node performs.
+     */
+    pub operation: &'a Operation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/**
+ * An edge in a [`ProgramGraph`], indicating that `target` consumes the
+ * value of `source`.
+ */
+pub struct ProgramEdge {
+    /**
+     * The id of the node producing the value.
+     */
+    pub source: usize,
+
+    /**
+     * The id of the node consuming the value.
+     */
+    pub target: usize,
+
+    /**
+     * Which of the target's operands the value is.
+     */
+    pub operand: EdgeInfo,
+}
+
+/**
+ * A read-only view of an [`FheProgram`] for analysis tools such as
+ * visualizers and cost estimators.
+ *
+ * # Remarks
+ * Nodes are identified by plain `usize` ids, so tools needn't depend on
+ * the graph library the compiler uses internally.
+ */
+pub struct ProgramGraph<'a> {
+    program: &'a FheProgram,
+}
+
+impl<'a> ProgramGraph<'a> {
+    /**
+     * Creates a view of the given program.
+     */
+    pub fn new(program: &'a FheProgram) -> Self {
+        Self { program }
+    }
+
+    fn to_node(&self, id: NodeIndex) -> ProgramNode<'a> {
+        ProgramNode {
+            id: id.index(),
+            operation: &self.program.graph[id].operation,
+        }
+    }
+
+    /**
+     * Returns the node with the given id, if any.
+     */
+    pub fn node(&self, id: usize) -> Option<ProgramNode<'a>> {
+        let id = NodeIndex::new(id);
+
+        self.program.graph.node_weight(id).map(|n| ProgramNode {
+            id: id.index(),
+            operation: &n.operation,
+        })
+    }
+
+    /**
+     * Returns every node in the program in id order.
+     */
+    pub fn nodes(&self) -> Vec<ProgramNode<'a>> {
+        self.program
+            .graph
+            .node_indices()
+            .map(|id| self.to_node(id))
+            .collect()
+    }
+
+    /**
+     * Returns every edge in the program.
+     */
+    pub fn edges(&self) -> Vec<ProgramEdge> {
+        self.program
+            .graph
+            .edge_references()
+            .map(|e| ProgramEdge {
+                source: e.source().index(),
+                target: e.target().index(),
+                operand: *e.weight(),
+            })
+            .collect()
+    }
+
+    /**
+     * Returns the edges from the nodes the given node consumes. Returns
+     * an empty list for unknown ids.
+     */
+    pub fn operands(&self, id: usize) -> Vec<ProgramEdge> {
+        self.neighbors(id, Direction::Incoming)
+    }
+
+    /**
+     * Returns the edges to the nodes that consume the given node's value.
+     * Returns an empty list for unknown ids.
+     */
+    pub fn users(&self, id: usize) -> Vec<ProgramEdge> {
+        self.neighbors(id, Direction::Outgoing)
+    }
+
+    fn neighbors(&self, id: usize, direction: Direction) -> Vec<ProgramEdge> {
+        let id = NodeIndex::new(id);
+
+        if self.program.graph.node_weight(id).is_none() {
+            return vec![];
+        }
+
+        self.program
+            .graph
+            .edges_directed(id, direction)
+            .map(|e| ProgramEdge {
+                source: e.source().index(),
+                target: e.target().index(),
+                operand: *e.weight(),
+            })
+            .collect()
+    }
+
+    /**
+     * Returns the nodes whose operation satisfies `predicate`, e.g.
+     * `|op| matches!(op, Operation::Multiply)`, in id order.
+     */
+    pub fn find<F>(&self, predicate: F) -> Vec<ProgramNode<'a>>
+    where
+        F: Fn(&Operation) -> bool,
+    {
+        self.nodes()
+            .into_iter()
+            .filter(|n| predicate(n.operation))
+            .collect()
+    }
+
+    /**
+     * Returns the program's output nodes. The `i`th node is the program's
+     * `i`th output.
+     */
+    pub fn outputs(&self) -> Vec<ProgramNode<'a>> {
+        self.program
+            .get_outputs()
+            .map(|id| self.to_node(id))
+            .collect()
+    }
+
+    /**
+     * Returns the nodes in topological order, i.e. every node appears
+     * after its operands.
+     */
+    pub fn topological_order(&self) -> Vec<ProgramNode<'a>> {
+        toposort(&self.program.graph.0, None)
+            .expect("FHE program graph contains a cycle")
+            .into_iter()
+            .map(|id| self.to_node(id))
+            .collect()
+    }
+
+    /**
+     * Returns the program's multiplicative depth, i.e. the greatest
+     * number of ciphertext-ciphertext multiplications on any path from an
+     * input to an output.
+     */
+    pub fn multiplicative_depth(&self) -> usize {
+        self.critical_path()
+            .iter()
+            .filter(|n| matches!(n.operation, Operation::Multiply))
+            .count()
+    }
+
+    /**
+     * Returns a path from an input to an output containing the most
+     * ciphertext-ciphertext multiplications, in execution order.
+     *
+     * # Remarks
+     * Multiplications dominate noise growth in BFV, so this path usually
+     * determines the program's parameters and is where optimizations pay
+     * off. Returns an empty path for programs without nodes.
+     */
+    pub fn critical_path(&self) -> Vec<ProgramNode<'a>> {
+        let order =
+            toposort(&self.program.graph.0, None).expect("FHE program graph contains a cycle");
+
+        let bound = order.iter().map(|id| id.index() + 1).max().unwrap_or(0);
+        let mut depth = vec![0usize; bound];
+        let mut predecessor: Vec<Option<NodeIndex>> = vec![None; bound];
+
+        for id in order.iter() {
+            let deepest = self
+                .program
+                .graph
+                .neighbors_directed(*id, Direction::Incoming)
+                .max_by_key(|p| (depth[p.index()], std::cmp::Reverse(p.index())));
+
+            let own = usize::from(matches!(
+                self.program.graph[*id].operation,
+                Operation::Multiply
+            ));
+
+            depth[id.index()] = deepest.map_or(0, |p| depth[p.index()]) + own;
+            predecessor[id.index()] = deepest;
+        }
+
+        // Prefer ending at an output, falling back to any node.
+        let mut candidates = self.program.get_outputs().collect::<Vec<_>>();
+
+        if candidates.is_empty() {
+            candidates = order;
+        }
+
+        let end = candidates
+            .into_iter()
+            .max_by_key(|id| (depth[id.index()], std::cmp::Reverse(id.index())));
+
+        let mut path = vec![];
+        let mut cur = end;
+
+        while let Some(id) = cur {
+            path.push(self.to_node(id));
+            cur = predecessor[id.index()];
+        }
+
+        path.reverse();
+
+        path
+    }
+
+    /**
+     * Returns the nodes the given output transitively depends on,
+     * including the output node itself, in topological order. Returns
+     * `None` if the program has no such output.
+     */
+    pub fn output_slice(&self, output: usize) -> Option<Vec<ProgramNode<'a>>> {
+        let output = self.program.get_outputs().nth(output)?;
+
+        let mut visited = HashSet::new();
+        let mut visit = vec![output];
+
+        while let Some(id) = visit.pop() {
+            if visited.insert(id) {
+                visit.extend(
+                    self.program
+                        .graph
+                        .neighbors_directed(id, Direction::Incoming),
+                );
+            }
+        }
+
+        Some(
+            self.topological_order()
+                .into_iter()
+                .filter(|n| visited.contains(&NodeIndex::new(n.id)))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Literal, SchemeType};
+
+    #[test]
+    fn can_query_program_graph() {
+        let mut ir = FheProgram::new(SchemeType::Bfv);
+
+        let a = ir.add_input_ciphertext(0);
+        let b = ir.add_input_ciphertext(1);
+        let l = ir.add_input_literal(Literal::from(3u64));
+        let ab = ir.add_multiply(a, b);
+        let abb = ir.add_multiply(ab, b);
+        let sum = ir.add_add(a, l);
+        let o1 = ir.add_output_ciphertext(abb);
+        let o2 = ir.add_output_ciphertext(sum);
+
+        let graph = ProgramGraph::new(&ir);
+
+        assert_eq!(graph.nodes().len(), 8);
+        assert_eq!(graph.edges().len(), 8);
+
+        let muls = graph
+            .find(|op| matches!(op, Operation::Multiply))
+            .iter()
+            .map(|n| n.id)
+            .collect::<Vec<_>>();
+
+        assert_eq!(muls, vec![ab.index(), abb.index()]);
+
+        let outputs = graph.outputs().iter().map(|n| n.id).collect::<Vec<_>>();
+
+        assert_eq!(outputs, vec![o1.index(), o2.index()]);
+
+        let operands = graph.operands(ab.index());
+
+        assert_eq!(operands.len(), 2);
+        assert!(operands
+            .iter()
+            .any(|e| e.source == a.index() && e.operand == EdgeInfo::Left));
+        assert_eq!(graph.users(b.index()).len(), 2);
+        assert!(graph.users(1000).is_empty());
+
+        assert_eq!(graph.multiplicative_depth(), 2);
+
+        let path = graph
+            .critical_path()
+            .iter()
+            .map(|n| n.id)
+            .collect::<Vec<_>>();
+
+        assert_eq!(path.last(), Some(&o1.index()));
+        assert!(path.contains(&ab.index()) && path.contains(&abb.index()));
+
+        let slice = graph
+            .output_slice(1)
+            .unwrap()
+            .iter()
+            .map(|n| n.id)
+            .collect::<HashSet<_>>();
+
+        assert_eq!(
+            slice,
+            [a.index(), l.index(), sum.index(), o2.index()]
+                .into_iter()
+                .collect()
+        );
+        assert!(graph.output_slice(2).is_none());
+    }
+}
@@ -0,0 +1,292 @@
+use std::sync::Arc;
+
+#[cfg(any(feature = "fhe", feature = "zkp"))]
+use crate::error::*;
+#[cfg(any(feature = "fhe", feature = "zkp"))]
+use crate::marker;
+#[cfg(feature = "fhe")]
+use crate::metadata::*;
+use crate::GenericRuntime;
+#[cfg(feature = "zkp")]
+use crate::ZkpProgramInput;
+#[cfg(feature = "fhe")]
+use crate::{
+    CancellationToken, Ciphertext, FheProgramInput, PrivateKey, PublicKey, RunOptions,
+    TryFromPlaintext, TryIntoPlaintext, TypeName,
+};
+
+#[cfg(feature = "zkp")]
+use sunscreen_zkp_backend::{CompiledZkpProgram, Proof, ZkpBackend};
+
+/**
+ * A wrapper around a [`GenericRuntime`] whose FHE operations return futures
+ * rather than blocking the calling thread.
+ *
+ * # Remarks
+ * Evaluating an FHE program, encrypting, decrypting and generating keys are
+ * CPU-bound calls into SEAL that can take anywhere from milliseconds to
+ * seconds. Running them directly on an async executor's worker threads stalls
+ * every other task scheduled on that worker. Each method on this type instead
+ * runs the underlying operation on tokio's blocking thread pool via
+ * [`tokio::task::spawn_blocking`] and resolves once it completes.
+ *
+ * Since work outlives the calling task, arguments are taken by value or in
+ * an [`Arc`]. Cloning an [`AsyncRuntime`] is cheap and clones share the
+ * same underlying runtime.
+ *
+ * These methods must be called from within a tokio runtime.
+ *
+ * # Cancellation
+ * Dropping the future returned by [`run`](Self::run) or
+ * [`run_cancellable`](Self::run_cancellable) (e.g. when a
+ * `tokio::time::timeout` elapses or a `select!` picks another branch)
+ * cancels the evaluation, which stops before its next operation. Other
+ * operations are short or can't be interrupted midway and run to
+ * completion on the blocking pool, with their results discarded.
+ *
+ * # Panics
+ * If the underlying operation panics, the panic is resumed on the awaiting
+ * task.
+ */
+pub struct AsyncRuntime<T, B> {
+    runtime: Arc<GenericRuntime<T, B>>,
+}
+
+impl<T, B> Clone for AsyncRuntime<T, B> {
+    fn clone(&self) -> Self {
+        Self {
+            runtime: self.runtime.clone(),
+        }
+    }
+}
+
+impl<T, B> From<GenericRuntime<T, B>> for AsyncRuntime<T, B> {
+    fn from(runtime: GenericRuntime<T, B>) -> Self {
+        Self::new(runtime)
+    }
+}
+
+impl<T, B> AsyncRuntime<T, B> {
+    /**
+     * Wraps the given runtime.
+     */
+    pub fn new(runtime: GenericRuntime<T, B>) -> Self {
+        Self {
+            runtime: Arc::new(runtime),
+        }
+    }
+
+    /**
+     * Returns the underlying runtime, which you can use to perform
+     * operations synchronously.
+     */
+    pub fn runtime(&self) -> &GenericRuntime<T, B> {
+        &self.runtime
+    }
+}
+
+/**
+ * Runs `f` on tokio's blocking thread pool and resolves to its result.
+ *
+ * # Remarks
+ * Use this for CPU-bound work [`AsyncRuntime`] doesn't cover. Unlike
+ * [`tokio::task::spawn_blocking`], this resumes panics in `f` on the
+ * awaiting task rather than returning them as errors. Must be called from
+ * within a tokio runtime.
+ */
+pub async fn spawn_blocking<F, R>(f: F) -> R
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(r) => r,
+        Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+        Err(_) => panic!("Blocking FHE task was cancelled."),
+    }
+}
+
+#[cfg(feature = "fhe")]
+/**
+ * Cancels a [`CancellationToken`] when dropped, i.e. when the future
+ * holding it completes or gets dropped.
+ */
+struct CancelOnDrop(CancellationToken);
+
+#[cfg(feature = "fhe")]
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+impl<T, B> AsyncRuntime<T, B>
+where
+    T: Send + Sync + 'static,
+    B: Send + Sync + 'static,
+{
+    async fn spawn_blocking<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&GenericRuntime<T, B>) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let runtime = self.runtime.clone();
+
+        spawn_blocking(move || f(&runtime)).await
+    }
+}
+
+#[cfg(feature = "fhe")]
+impl<T, B> AsyncRuntime<T, B>
+where
+    T: marker::Fhe + Send + Sync + 'static,
+    B: Send + Sync + 'static,
+{
+    /**
+     * Asynchronously runs the given FHE program. See [`GenericRuntime::run`].
+     */
+    pub async fn run<I>(
+        &self,
+        fhe_program: Arc<CompiledFheProgram>,
+        arguments: Vec<I>,
+        public_key: Arc<PublicKey>,
+    ) -> Result<Vec<Ciphertext>>
+    where
+        I: Into<FheProgramInput> + Send + 'static,
+    {
+        self.run_cancellable(fhe_program, arguments, public_key, CancellationToken::new())
+            .await
+    }
+
+    /**
+     * Asynchronously runs the given FHE program, stopping early if `cancel`
+     * gets cancelled or the returned future is dropped. See
+     * [`RunOptions::cancel`].
+     */
+    pub async fn run_cancellable<I>(
+        &self,
+        fhe_program: Arc<CompiledFheProgram>,
+        arguments: Vec<I>,
+        public_key: Arc<PublicKey>,
+        cancel: CancellationToken,
+    ) -> Result<Vec<Ciphertext>>
+    where
+        I: Into<FheProgramInput> + Send + 'static,
+    {
+        // Cancel a token of our own on drop rather than the caller's, which
+        // they may share with other tasks.
+        let dropped = CancellationToken::new();
+        let _guard = CancelOnDrop(dropped.clone());
+
+        self.spawn_blocking(move |runtime| {
+            runtime.run_with_cancellation(
+                &fhe_program,
+                arguments,
+                &public_key,
+                &RunOptions::default(),
+                &|| cancel.is_cancelled() || dropped.is_cancelled(),
+            )
+        })
+        .await
+    }
+
+    /**
+     * Asynchronously encrypts the given value. See [`GenericRuntime::encrypt`].
+     */
+    pub async fn encrypt<P>(&self, val: P, public_key: Arc<PublicKey>) -> Result<Ciphertext>
+    where
+        P: TryIntoPlaintext + TypeName + Send + 'static,
+    {
+        self.spawn_blocking(move |runtime| runtime.encrypt(val, &public_key))
+            .await
+    }
+
+    /**
+     * Asynchronously decrypts the given ciphertext. See
+     * [`GenericRuntime::decrypt`].
+     */
+    pub async fn decrypt<P>(
+        &self,
+        ciphertext: Ciphertext,
+        private_key: Arc<PrivateKey>,
+    ) -> Result<P>
+    where
+        P: TryFromPlaintext + TypeName + Send + 'static,
+    {
+        self.spawn_blocking(move |runtime| runtime.decrypt(&ciphertext, &private_key))
+            .await
+    }
+
+    /**
+     * Asynchronously generates a key pair. See
+     * [`GenericRuntime::generate_keys`].
+     */
+    pub async fn generate_keys(&self) -> Result<(PublicKey, PrivateKey)> {
+        self.spawn_blocking(|runtime| runtime.generate_keys()).await
+    }
+}
+
+#[cfg(feature = "zkp")]
+impl<T, B> AsyncRuntime<T, B>
+where
+    T: marker::Zkp + Send + Sync + 'static,
+    B: ZkpBackend + Send + Sync + 'static,
+{
+    /**
+     * Asynchronously proves the given inputs satisfy `program`. See
+     * [`GenericRuntime::prove`].
+     */
+    pub async fn prove<I>(
+        &self,
+        program: Arc<CompiledZkpProgram>,
+        constant_inputs: Vec<I>,
+        public_inputs: Vec<I>,
+        private_inputs: Vec<I>,
+    ) -> Result<Proof>
+    where
+        I: Into<ZkpProgramInput> + Send + 'static,
+    {
+        self.spawn_blocking(move |runtime| {
+            runtime.prove(&program, constant_inputs, public_inputs, private_inputs)
+        })
+        .await
+    }
+
+    /**
+     * Asynchronously verifies `proof` against `program` and the given
+     * inputs. See [`GenericRuntime::verify`].
+     */
+    pub async fn verify<I>(
+        &self,
+        program: Arc<CompiledZkpProgram>,
+        proof: Arc<Proof>,
+        constant_inputs: Vec<I>,
+        public_inputs: Vec<I>,
+    ) -> Result<()>
+    where
+        I: Into<ZkpProgramInput> + Send + 'static,
+    {
+        self.spawn_blocking(move |runtime| {
+            runtime.verify(&program, &proof, constant_inputs, public_inputs)
+        })
+        .await
+    }
+}
+
+#[cfg(feature = "fhe")]
+/**
+ * An [`AsyncRuntime`] wrapping an [`FheRuntime`](crate::FheRuntime).
+ */
+pub type AsyncFheRuntime = AsyncRuntime<crate::Fhe, ()>;
+
+#[cfg(feature = "zkp")]
+/**
+ * An [`AsyncRuntime`] wrapping a [`ZkpRuntime`](crate::ZkpRuntime).
+ */
+pub type AsyncZkpRuntime<B> = AsyncRuntime<crate::Zkp, B>;
+
+#[cfg(all(feature = "fhe", feature = "zkp"))]
+/**
+ * An [`AsyncRuntime`] wrapping a [`FheZkpRuntime`](crate::FheZkpRuntime).
+ */
+pub type AsyncFheZkpRuntime<B> = AsyncRuntime<crate::FheZkp, B>;
@@ -0,0 +1,40 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Default)]
+/**
+ * A flag for cancelling FHE program evaluation from another thread. See
+ * [`RunOptions::cancel`](crate::RunOptions::cancel).
+ *
+ * # Remarks
+ * Clones share the same flag, so cancelling any clone cancels them all.
+ * Cancellation is cooperative: the runtime checks the flag before each
+ * operation in the program, so an operation already underway finishes
+ * first.
+ */
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /**
+     * Creates a token that isn't cancelled.
+     */
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * Cancels every operation using this token or any of its clones.
+     */
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /**
+     * Returns whether this token has been cancelled.
+     */
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
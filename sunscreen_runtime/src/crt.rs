@@ -0,0 +1,515 @@
+use seal_fhe::BFVEncoder;
+
+use crate::{Error, Params, Result, SealPlaintext};
+
+/**
+ * Recombines results computed under several coprime plaintext moduli into
+ * a single wide integer using the Chinese remainder theorem.
+ *
+ * # Remarks
+ * A computation whose results overflow one plaintext modulus can run
+ * once per modulus, e.g. with one runtime per set of [`Params`] that
+ * differ only in their plain modulus. Each run yields the result modulo
+ * its plain modulus (a residue), and a basis of the moduli recovers the
+ * result modulo their product.
+ *
+ * Values are reconstructed as `u128` or, centered around zero, as `i128`,
+ * so the product of the moduli must fit in 128 bits. Results are only
+ * correct if the true value fits too, i.e. lies in `[0, M)` for unsigned
+ * or `(-M/2, M/2]` for signed reconstruction, where `M` is the product.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrtBasis {
+    moduli: Vec<u64>,
+    product: u128,
+}
+
+impl CrtBasis {
+    /**
+     * Creates a basis of the given moduli.
+     *
+     * Returns [`Error::CrtError`] if there are no moduli, a modulus is
+     * less than 2, two moduli share a factor or their product overflows
+     * 128 bits.
+     */
+    pub fn new(moduli: &[u64]) -> Result<Self> {
+        if moduli.is_empty() {
+            return Err(Error::crt_error("a CRT basis needs at least one modulus"));
+        }
+
+        let mut product: u128 = 1;
+
+        for m in moduli {
+            if *m < 2 {
+                return Err(Error::crt_error(&format!("invalid modulus {}", m)));
+            }
+
+            if gcd((product % *m as u128) as u64, *m) != 1 {
+                return Err(Error::crt_error(&format!(
+                    "modulus {} shares a factor with another modulus",
+                    m
+                )));
+            }
+
+            product = product
+                .checked_mul(*m as u128)
+                .ok_or_else(|| Error::crt_error("the product of the moduli exceeds 128 bits"))?;
+        }
+
+        Ok(Self {
+            moduli: moduli.to_owned(),
+            product,
+        })
+    }
+
+    /**
+     * Creates a basis of the plain moduli of the given parameters, in
+     * order.
+     */
+    pub fn from_params(params: &[Params]) -> Result<Self> {
+        Self::new(&params.iter().map(|p| p.plain_modulus).collect::<Vec<_>>())
+    }
+
+    /**
+     * The moduli in this basis.
+     */
+    pub fn moduli(&self) -> &[u64] {
+        &self.moduli
+    }
+
+    /**
+     * The product of the moduli.
+     */
+    pub fn product(&self) -> u128 {
+        self.product
+    }
+
+    /**
+     * Returns `value` modulo each modulus, the inverse of
+     * [`reconstruct_unsigned`](Self::reconstruct_unsigned).
+     *
+     * Returns [`Error::CrtError`] if `value` isn't less than the product
+     * of the moduli.
+     */
+    pub fn split_unsigned(&self, value: u128) -> Result<Vec<u64>> {
+        if value >= self.product {
+            return Err(Error::crt_error(&format!(
+                "{} doesn't fit in a basis with product {}",
+                value, self.product
+            )));
+        }
+
+        Ok(self
+            .moduli
+            .iter()
+            .map(|m| (value % *m as u128) as u64)
+            .collect())
+    }
+
+    /**
+     * Returns `value` modulo each modulus, the inverse of
+     * [`reconstruct_signed`](Self::reconstruct_signed).
+     *
+     * Returns [`Error::CrtError`] if `value` lies outside `(-M/2, M/2]`.
+     */
+    pub fn split_signed(&self, value: i128) -> Result<Vec<u64>> {
+        let magnitude = value.unsigned_abs();
+        let half = self.product / 2;
+
+        if (value >= 0 && magnitude > half) || (value < 0 && magnitude >= self.product - half) {
+            return Err(Error::crt_error(&format!(
+                "{} doesn't fit in a basis with product {}",
+                value, self.product
+            )));
+        }
+
+        Ok(self
+            .moduli
+            .iter()
+            .map(|m| value.rem_euclid(*m as i128) as u64)
+            .collect())
+    }
+
+    /**
+     * Returns the value in `[0, M)` congruent to each residue modulo the
+     * corresponding modulus.
+     *
+     * Residues may be negative (e.g. the centered lanes of a decrypted
+     * `Batched` value) and are reduced first. Returns [`Error::CrtError`] if the number of
+     * residues doesn't match the number of moduli.
+     */
+    pub fn reconstruct_unsigned(&self, residues: &[i64]) -> Result<u128> {
+        if residues.len() != self.moduli.len() {
+            return Err(Error::crt_error(&format!(
+                "expected {} residues, got {}",
+                self.moduli.len(),
+                residues.len()
+            )));
+        }
+
+        // Garner's algorithm: extend the result one modulus at a time so
+        // intermediate values never exceed the final product.
+        let mut value: u128 = 0;
+        let mut product: u128 = 1;
+
+        for (r, m) in residues.iter().zip(self.moduli.iter()) {
+            let m = *m as u128;
+            let r = (*r as i128).rem_euclid(m as i128) as u128;
+
+            // We checked the moduli are coprime, so the inverse exists.
+            let inverse = mod_inverse(product % m, m);
+            let delta = (r + m - value % m) % m;
+            let t = mul_mod(delta, inverse, m);
+
+            // t < m, so value + t * product < product * m, which we
+            // checked fits when creating the basis.
+            value += t * product;
+            product *= m;
+        }
+
+        Ok(value)
+    }
+
+    /**
+     * Returns the value in `(-M/2, M/2]` congruent to each residue modulo
+     * the corresponding modulus. See
+     * [`reconstruct_unsigned`](Self::reconstruct_unsigned).
+     */
+    pub fn reconstruct_signed(&self, residues: &[i64]) -> Result<i128> {
+        let value = self.reconstruct_unsigned(residues)?;
+
+        if value > self.product / 2 {
+            Ok(-((self.product - value) as i128))
+        } else {
+            Ok(value as i128)
+        }
+    }
+
+    /**
+     * Reconstructs each slot of a batched result, where `residues[i]`
+     * holds the slots computed under the `i`th modulus.
+     *
+     * Returns [`Error::CrtError`] if the number of residue vectors doesn't
+     * match the number of moduli or they have different lengths.
+     */
+    pub fn reconstruct_slots(&self, residues: &[Vec<i64>]) -> Result<Vec<i128>> {
+        let len = residues.first().map(|r| r.len()).unwrap_or(0);
+
+        if residues.iter().any(|r| r.len() != len) {
+            return Err(Error::crt_error("residue vectors have different lengths"));
+        }
+
+        (0..len)
+            .map(|i| {
+                let slot = residues.iter().map(|r| r[i]).collect::<Vec<_>>();
+
+                self.reconstruct_signed(&slot)
+            })
+            .collect()
+    }
+}
+
+/**
+ * Encodes integers wider than a single plain modulus as one batched
+ * plaintext per modulus of a [`CrtBasis`], and reconstructs them on
+ * decode.
+ *
+ * # Remarks
+ * Create a SEAL context, keys and [`BFVEncoder`] for each of several
+ * parameter sets that differ only in their (coprime) plain modulus. Slot
+ * `i` of the `j`th plaintext holds value `i` modulo the `j`th plain
+ * modulus. Encrypt and compute on each plaintext under its own context,
+ * applying the same slot-wise additions and multiplications to each, and
+ * decode the decrypted results together. Results are exact as long as
+ * they fit in the basis, as described in [`CrtBasis`].
+ */
+pub struct CrtEncoder<'a> {
+    basis: CrtBasis,
+    encoders: Vec<&'a BFVEncoder>,
+}
+
+impl<'a> CrtEncoder<'a> {
+    /**
+     * Creates an encoder over the plain moduli of `encoders`, in order.
+     *
+     * Returns [`Error::CrtError`] if the plain moduli don't form a
+     * [`CrtBasis`] or the encoders have different slot counts.
+     */
+    pub fn new(encoders: Vec<&'a BFVEncoder>) -> Result<Self> {
+        let basis = CrtBasis::new(
+            &encoders
+                .iter()
+                .map(|e| e.get_plain_modulus())
+                .collect::<Vec<_>>(),
+        )?;
+
+        let slots = encoders[0].get_slot_count();
+
+        if encoders.iter().any(|e| e.get_slot_count() != slots) {
+            return Err(Error::crt_error("encoders have different slot counts"));
+        }
+
+        Ok(Self { basis, encoders })
+    }
+
+    /**
+     * The basis of the encoders' plain moduli.
+     */
+    pub fn basis(&self) -> &CrtBasis {
+        &self.basis
+    }
+
+    /**
+     * Encodes `data` into one plaintext per modulus, with 0 in any slots
+     * past the end of `data`.
+     *
+     * Returns [`Error::CrtError`] if a value doesn't fit in the basis and
+     * a SEAL error if `data` has more values than there are slots.
+     */
+    pub fn encode_unsigned(&self, data: &[u128]) -> Result<Vec<SealPlaintext>> {
+        self.encode(
+            data.iter()
+                .map(|x| self.basis.split_unsigned(*x))
+                .collect::<Result<Vec<_>>>()?,
+        )
+    }
+
+    /**
+     * Encodes `data` into one plaintext per modulus. See
+     * [`encode_unsigned`](Self::encode_unsigned).
+     */
+    pub fn encode_signed(&self, data: &[i128]) -> Result<Vec<SealPlaintext>> {
+        self.encode(
+            data.iter()
+                .map(|x| self.basis.split_signed(*x))
+                .collect::<Result<Vec<_>>>()?,
+        )
+    }
+
+    /**
+     * Reconstructs every slot of the given plaintexts, one per modulus in
+     * order, as values in `[0, M)`.
+     */
+    pub fn decode_unsigned(&self, plaintexts: &[SealPlaintext]) -> Result<Vec<u128>> {
+        let residues = self.decode(plaintexts)?;
+        let len = residues[0].len();
+
+        (0..len)
+            .map(|i| {
+                let slot = residues.iter().map(|r| r[i] as i64).collect::<Vec<_>>();
+
+                self.basis.reconstruct_unsigned(&slot)
+            })
+            .collect()
+    }
+
+    /**
+     * Reconstructs every slot of the given plaintexts, one per modulus in
+     * order, as values in `(-M/2, M/2]`.
+     */
+    pub fn decode_signed(&self, plaintexts: &[SealPlaintext]) -> Result<Vec<i128>> {
+        let residues = self
+            .decode(plaintexts)?
+            .into_iter()
+            .map(|r| r.into_iter().map(|x| x as i64).collect())
+            .collect::<Vec<Vec<i64>>>();
+
+        self.basis.reconstruct_slots(&residues)
+    }
+
+    /**
+     * Transposes per-value residues into per-modulus slots and encodes
+     * them.
+     */
+    fn encode(&self, residues: Vec<Vec<u64>>) -> Result<Vec<SealPlaintext>> {
+        self.encoders
+            .iter()
+            .enumerate()
+            .map(|(j, e)| {
+                let slots = residues.iter().map(|r| r[j]).collect::<Vec<_>>();
+
+                Ok(e.encode_unsigned(&slots)?)
+            })
+            .collect()
+    }
+
+    fn decode(&self, plaintexts: &[SealPlaintext]) -> Result<Vec<Vec<u64>>> {
+        if plaintexts.len() != self.encoders.len() {
+            return Err(Error::crt_error(&format!(
+                "expected {} plaintexts, got {}",
+                self.encoders.len(),
+                plaintexts.len()
+            )));
+        }
+
+        self.encoders
+            .iter()
+            .zip(plaintexts)
+            .map(|(e, p)| Ok(e.decode_unsigned(p)?))
+            .collect()
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+
+    a
+}
+
+/**
+ * Computes `a * b mod m` for `a, b < m < 2^64` without overflowing.
+ */
+fn mul_mod(a: u128, b: u128, m: u128) -> u128 {
+    // Both operands are below 2^64, so their product fits in 128 bits.
+    (a * b) % m
+}
+
+/**
+ * Computes the inverse of `a` modulo `m`, which must be coprime with `a`.
+ */
+fn mod_inverse(a: u128, m: u128) -> u128 {
+    let (mut old_r, mut r) = (a as i128, m as i128);
+    let (mut old_s, mut s) = (1i128, 0i128);
+
+    while r != 0 {
+        let q = old_r / r;
+
+        let next_r = old_r - q * r;
+        old_r = r;
+        r = next_r;
+
+        let next_s = old_s - q * s;
+        old_s = s;
+        s = next_s;
+    }
+
+    old_s.rem_euclid(m as i128) as u128
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_reconstruct_values() {
+        let moduli = [65537, 786433, 1032193];
+        let basis = CrtBasis::new(&moduli).unwrap();
+
+        assert_eq!(basis.product(), 65537 * 786433 * 1032193);
+
+        for x in [0i128, 1, -1, 123_456_789_012_345, -98_765_432_101_234] {
+            let residues = moduli
+                .iter()
+                .map(|m| x.rem_euclid(*m as i128) as i64)
+                .collect::<Vec<_>>();
+
+            assert_eq!(basis.reconstruct_signed(&residues).unwrap(), x);
+        }
+
+        // Centered residues, as decrypted from batched lanes.
+        assert_eq!(basis.reconstruct_signed(&[-5, -5, -5]).unwrap(), -5);
+        assert_eq!(
+            basis.reconstruct_unsigned(&[-1, -1, -1]).unwrap(),
+            basis.product() - 1
+        );
+
+        assert_eq!(
+            basis
+                .reconstruct_slots(&[vec![1, -2], vec![1, -2], vec![1, -2]])
+                .unwrap(),
+            vec![1, -2]
+        );
+
+        assert!(basis.reconstruct_signed(&[1, 2]).is_err());
+    }
+
+    #[test]
+    fn can_split_values() {
+        let basis = CrtBasis::new(&[7, 11]).unwrap();
+
+        assert_eq!(basis.split_unsigned(76).unwrap(), vec![6, 10]);
+        assert!(basis.split_unsigned(77).is_err());
+
+        assert_eq!(basis.split_signed(-1).unwrap(), vec![6, 10]);
+        assert_eq!(basis.split_signed(38).unwrap(), vec![3, 5]);
+        assert_eq!(basis.reconstruct_signed(&[3, 5]).unwrap(), 38);
+        assert_eq!(basis.reconstruct_signed(&[4, 6]).unwrap(), -38);
+        assert!(basis.split_signed(39).is_err());
+        assert!(basis.split_signed(-38).is_ok());
+        assert!(basis.split_signed(-39).is_err());
+    }
+
+    #[test]
+    fn can_compute_on_wide_values() {
+        use seal_fhe::*;
+
+        let contexts = [65537, 114689, 163841]
+            .iter()
+            .map(|p| {
+                let params = BfvEncryptionParametersBuilder::new()
+                    .set_poly_modulus_degree(4096)
+                    .set_coefficient_modulus(
+                        CoefficientModulus::bfv_default(4096, SecurityLevel::TC128).unwrap(),
+                    )
+                    .set_plain_modulus(Modulus::new(*p).unwrap())
+                    .build()
+                    .unwrap();
+
+                Context::new(&params, false, SecurityLevel::TC128).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let encoders = contexts
+            .iter()
+            .map(|c| BFVEncoder::new(c).unwrap())
+            .collect::<Vec<_>>();
+
+        let crt = CrtEncoder::new(encoders.iter().collect()).unwrap();
+
+        // Each value exceeds every plain modulus, and so does their sum.
+        let data = [200_000_000_000_000i128, -123_456_789_012_345, 1, -1];
+
+        let plaintexts = crt.encode_signed(&data).unwrap();
+
+        let sums = contexts
+            .iter()
+            .zip(plaintexts.iter())
+            .map(|(ctx, p)| {
+                let keygen = KeyGenerator::new(ctx).unwrap();
+                let encryptor =
+                    Encryptor::with_public_key(ctx, &keygen.create_public_key().unwrap()).unwrap();
+                let decryptor = Decryptor::new(ctx, &keygen.secret_key().unwrap()).unwrap();
+                let evaluator = BFVEvaluator::new(ctx).unwrap();
+
+                let c = encryptor.encrypt(p).unwrap();
+                let c = evaluator.add(&c, &c).unwrap();
+
+                decryptor.decrypt(&c).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let decoded = crt.decode_signed(&sums).unwrap();
+
+        assert_eq!(&decoded[..4], &data.map(|x| 2 * x));
+        assert!(decoded[4..].iter().all(|x| *x == 0));
+
+        let unsigned = crt
+            .decode_unsigned(&crt.encode_unsigned(&[crt.basis().product() - 1]).unwrap())
+            .unwrap();
+
+        assert_eq!(unsigned[0], crt.basis().product() - 1);
+    }
+
+    #[test]
+    fn rejects_invalid_bases() {
+        assert!(CrtBasis::new(&[]).is_err());
+        assert!(CrtBasis::new(&[1, 7]).is_err());
+        assert!(CrtBasis::new(&[6, 9]).is_err());
+        assert!(CrtBasis::new(&[u64::MAX, u64::MAX - 1, 11]).is_err());
+        assert!(CrtBasis::new(&[u64::MAX, u64::MAX - 1]).is_ok());
+    }
+}
@@ -1,6 +1,7 @@
 use static_assertions::const_assert;
 
 use crate::Type;
+#[cfg(feature = "zkp")]
 use sunscreen_zkp_backend::Error as ZkpError;
 
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
@@ -11,12 +12,14 @@ pub enum Error {
     /**
      * An issue with an [`FheProgram`](sunscreen_fhe_program::FheProgram).
      */
+    #[cfg(feature = "fhe")]
     #[error("The FHE program is malformed: {0}")]
     IRError(#[from] sunscreen_fhe_program::Error),
 
     /**
      * An error occurred in the SEAL library.
      */
+    #[cfg(feature = "fhe")]
     #[error("SEAL encountered an error {0}")]
     SealError(#[from] seal_fhe::Error),
 
@@ -46,6 +49,54 @@ pub enum Error {
     #[error("The given value is incompatible with the context.")]
     ParameterMismatch,
 
+    /**
+     * The FHE program was compiled for different scheme parameters than the runtime uses.
+     */
+    #[error("The FHE program was compiled for different parameters than the runtime's")]
+    ProgramParameterMismatch,
+
+    /**
+     * A public, relinearization or Galois key was generated for different scheme parameters
+     * than the runtime uses.
+     */
+    #[error("The given keys were generated for different parameters than the runtime's")]
+    KeyParameterMismatch,
+
+    /**
+     * The given Galois keys can't perform a rotation the FHE program requires. Positive
+     * steps rotate left, negative steps rotate right and 0 swaps rows.
+     */
+    #[error("The Galois keys can't rotate by {0} steps as the FHE program requires")]
+    MissingRotationKey(i32),
+
+    /**
+     * The FHE program declares an authorization requirement that no
+     * [`PolicyProvider`](crate::PolicyProvider) granted.
+     */
+    #[error("Running the FHE program requires {0}, which wasn't granted")]
+    Unauthorized(Box<String>),
+
+    /**
+     * A [`DecryptionGuard`](crate::DecryptionGuard) refused to decrypt a
+     * ciphertext it neither produced nor was told to trust.
+     */
+    #[error("Refusing to decrypt a ciphertext that wasn't produced locally")]
+    UntrustedCiphertext,
+
+    /**
+     * A [`DecryptionGuard`](crate::DecryptionGuard) refused to run an FHE
+     * program that wasn't registered with it.
+     */
+    #[error("The FHE program wasn't registered")]
+    UnregisteredProgram,
+
+    /**
+     * The operation was cancelled through a
+     * [`CancellationToken`](crate::CancellationToken) before it completed.
+     */
+    #[error("The operation was cancelled")]
+    Cancelled,
+
     /**
      * The given arguments do not match the call signature of the FHE program.
      */
@@ -72,9 +123,17 @@ pub enum Error {
     #[error("Too much noise")]
     TooMuchNoise,
 
+    /**
+     * A [`ReEncryptor`](crate::ReEncryptor) was given a ciphertext that
+     * isn't relinearized or is in NTT form.
+     */
+    #[error("The ciphertext must be relinearized and not in NTT form")]
+    NotRelinearized,
+
     /**
      * Executing an Fhe Program failed.
      */
+    #[cfg(feature = "fhe")]
     #[error("Running FHE program failed {0}")]
     FheProgramRunError(#[from] crate::run::FheProgramRunFailure),
 
@@ -109,6 +168,12 @@ pub enum Error {
     #[error("Bincode serialization failed: {0}")]
     BincodeError(Box<String>),
 
+    /**
+     * An error occurred when serializing/deserializing JSON.
+     */
+    #[error("JSON serialization failed: {0}")]
+    JsonError(Box<String>),
+
     /**
      * Called [`inner_as_seal_plaintext`](crate::InnerPlaintext.inner_as_seal_plaintext)
      * on non-Seal plaintext.
@@ -116,9 +181,38 @@ pub enum Error {
     #[error("Not a SEAL plaintext")]
     NotASealPlaintext,
 
+    /**
+     * A [`RuntimeState`](crate::RuntimeState) was saved in a format
+     * version this release can't read.
+     */
+    #[error("Unsupported runtime state version {0}")]
+    UnsupportedStateVersion(u32),
+
+    /**
+     * The programs or keys given to restore a
+     * [`RuntimeState`](crate::RuntimeState) don't match those it
+     * recorded.
+     */
+    #[error("Runtime state mismatch: {0}")]
+    StateMismatch(Box<String>),
+
+    /**
+     * The FHE program has no argument with the given name.
+     */
+    #[error("The FHE program has no argument named {0}")]
+    UnknownArgument(Box<String>),
+
+    /**
+     * A [`CrtBasis`](crate::CrtBasis) couldn't be created or couldn't
+     * reconstruct a value.
+     */
+    #[error("CRT error: {0}")]
+    CrtError(Box<String>),
+
     /**
      * An error occurred when creating or verifying a proof.
      */
+    #[cfg(feature = "zkp")]
     #[error("ZKP error: {0}")]
     ZkpError(#[from] ZkpError),
 }
@@ -140,6 +234,34 @@ impl Error {
         Self::TypeMismatch(Box::new((expected.clone(), actual.clone())))
     }
 
+    /**
+     * Create an [`Error::UnknownArgument`].
+     */
+    pub fn unknown_argument(name: &str) -> Self {
+        Self::UnknownArgument(Box::new(name.to_owned()))
+    }
+
+    /**
+     * Create an [`Error::CrtError`].
+     */
+    pub fn crt_error(msg: &str) -> Self {
+        Self::CrtError(Box::new(msg.to_owned()))
+    }
+
+    /**
+     * Create an [`Error::Unauthorized`].
+     */
+    pub fn unauthorized(requirement: &str) -> Self {
+        Self::Unauthorized(Box::new(requirement.to_owned()))
+    }
+
+    /**
+     * Create an [`Error::StateMismatch`].
+     */
+    pub fn state_mismatch(msg: &str) -> Self {
+        Self::StateMismatch(Box::new(msg.to_owned()))
+    }
+
     /**
      * Create an [`Error::FheTypeError`].
      */
@@ -168,6 +290,12 @@ impl From<bincode::Error> for Error {
     }
 }
 
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Self::JsonError(Box::new(format!("{}", err)))
+    }
+}
+
 /**
  * Wrapper around [`Result`](std::result::Result) with this crate's error type.
  */
@@ -0,0 +1,179 @@
+use std::collections::HashSet;
+use std::sync::{Mutex, MutexGuard};
+
+use hmac::{Hmac, Mac};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::Serialize;
+use sha3::Sha3_256;
+use zeroize::Zeroizing;
+
+use crate::{
+    marker, Ciphertext, CompiledFheProgram, Error, FheProgramInput, GenericRuntime, PrivateKey,
+    PublicKey, Result, TryFromPlaintext, TryIntoPlaintext, TypeName,
+};
+
+type Fingerprint = [u8; 32];
+
+#[derive(Default)]
+struct GuardState {
+    programs: HashSet<Fingerprint>,
+    ciphertexts: HashSet<Fingerprint>,
+}
+
+/**
+ * Wraps a runtime to refuse decrypting ciphertexts that were neither
+ * encrypted locally nor produced by running a registered FHE program,
+ * e.g. so a service that returns decrypted results can't be used as a
+ * decryption oracle for arbitrary ciphertexts.
+ *
+ * # Remarks
+ * The guard remembers a fingerprint of each ciphertext it encrypts with
+ * [`encrypt`](Self::encrypt) or returns from [`run`](Self::run), and
+ * [`decrypt`](Self::decrypt) returns [`Error::UntrustedCiphertext`] for
+ * any other ciphertext. Externally supplied ciphertexts may still be
+ * passed to registered programs, whose outputs can then be decrypted.
+ * Call [`trust`](Self::trust) to explicitly allow decrypting a
+ * ciphertext from elsewhere.
+ *
+ * Fingerprints are HMAC-SHA3-256 tags of the serialized ciphertext
+ * (including its type) under a random 256-bit key per guard. Without the
+ * key, forging a ciphertext whose fingerprint collides with a tracked one
+ * is as hard as forging the MAC.
+ * The guard only remembers ciphertexts while it lives and keeps every
+ * fingerprint until dropped, so create one per session or request.
+ */
+pub struct DecryptionGuard<'a, T, B> {
+    runtime: &'a GenericRuntime<T, B>,
+    key: Zeroizing<[u8; 32]>,
+    state: Mutex<GuardState>,
+}
+
+impl<'a, T, B> DecryptionGuard<'a, T, B>
+where
+    T: marker::Fhe,
+{
+    /**
+     * Creates a guard for `runtime` that trusts no ciphertexts and
+     * runs no programs.
+     */
+    pub fn new(runtime: &'a GenericRuntime<T, B>) -> Self {
+        let mut key = Zeroizing::new([0; 32]);
+        OsRng.fill_bytes(&mut *key);
+
+        Self {
+            runtime,
+            key,
+            state: Mutex::new(GuardState::default()),
+        }
+    }
+
+    /**
+     * Allows [`run`](Self::run) to run `fhe_program`.
+     */
+    pub fn register(&self, fhe_program: &CompiledFheProgram) -> Result<()> {
+        let fingerprint = self.fingerprint(fhe_program)?;
+
+        self.lock().programs.insert(fingerprint);
+
+        Ok(())
+    }
+
+    /**
+     * Encrypts `val` as [`GenericRuntime::encrypt`] does and trusts the
+     * result.
+     */
+    pub fn encrypt<P>(&self, val: P, public_key: &PublicKey) -> Result<Ciphertext>
+    where
+        P: TryIntoPlaintext + TypeName,
+    {
+        let ciphertext = self.runtime.encrypt(val, public_key)?;
+
+        self.trust(&ciphertext)?;
+
+        Ok(ciphertext)
+    }
+
+    /**
+     * Runs a registered FHE program as [`GenericRuntime::run`] does and
+     * trusts its outputs. The arguments needn't be trusted.
+     *
+     * Returns [`Error::UnregisteredProgram`] if `fhe_program` wasn't
+     * [`register`](Self::register)ed.
+     */
+    pub fn run<I>(
+        &self,
+        fhe_program: &CompiledFheProgram,
+        arguments: Vec<I>,
+        public_key: &PublicKey,
+    ) -> Result<Vec<Ciphertext>>
+    where
+        I: Into<FheProgramInput>,
+    {
+        let fingerprint = self.fingerprint(fhe_program)?;
+
+        if !self.lock().programs.contains(&fingerprint) {
+            return Err(Error::UnregisteredProgram);
+        }
+
+        let outputs = self.runtime.run(fhe_program, arguments, public_key)?;
+
+        for c in outputs.iter() {
+            self.trust(c)?;
+        }
+
+        Ok(outputs)
+    }
+
+    /**
+     * Explicitly allows decrypting `ciphertext`, e.g. one that was
+     * encrypted locally in an earlier session and stored.
+     */
+    pub fn trust(&self, ciphertext: &Ciphertext) -> Result<()> {
+        let fingerprint = self.fingerprint(ciphertext)?;
+
+        self.lock().ciphertexts.insert(fingerprint);
+
+        Ok(())
+    }
+
+    /**
+     * Returns whether [`decrypt`](Self::decrypt) would accept
+     * `ciphertext`.
+     */
+    pub fn is_trusted(&self, ciphertext: &Ciphertext) -> Result<bool> {
+        let fingerprint = self.fingerprint(ciphertext)?;
+
+        Ok(self.lock().ciphertexts.contains(&fingerprint))
+    }
+
+    /**
+     * Decrypts a trusted ciphertext as [`GenericRuntime::decrypt`] does.
+     *
+     * Returns [`Error::UntrustedCiphertext`] if the guard neither produced
+     * `ciphertext` nor was told to [`trust`](Self::trust) it.
+     */
+    pub fn decrypt<P>(&self, ciphertext: &Ciphertext, private_key: &PrivateKey) -> Result<P>
+    where
+        P: TryFromPlaintext + TypeName,
+    {
+        if !self.is_trusted(ciphertext)? {
+            return Err(Error::UntrustedCiphertext);
+        }
+
+        self.runtime.decrypt(ciphertext, private_key)
+    }
+
+    fn fingerprint<V: Serialize>(&self, value: &V) -> Result<Fingerprint> {
+        let mut mac =
+            Hmac::<Sha3_256>::new_from_slice(&*self.key).expect("HMAC accepts keys of any length");
+
+        mac.update(&bincode::serialize(value)?);
+
+        Ok(mac.finalize().into_bytes().into())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, GuardState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
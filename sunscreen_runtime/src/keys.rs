@@ -1,9 +1,13 @@
 use crate::serialization::WithContext;
+use crate::{Result, Secret};
 
 use seal_fhe::{
-    GaloisKeys, PublicKey as SealPublicKey, RelinearizationKeys, SecretKey as SealSecretKey,
+    CompressionType, GaloisKeys, MemoryUsage, PublicKey as SealPublicKey, RelinearizationKeys,
+    SecretKey as SealSecretKey, ToBytes,
 };
 use serde::{Deserialize, Serialize};
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::Zeroizing;
 
 #[derive(Clone, Deserialize, PartialEq, Serialize)]
 /**
@@ -46,11 +50,78 @@ pub struct PublicKey {
     pub relin_key: Option<WithContext<RelinearizationKeys>>,
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq)]
+impl PublicKey {
+    /**
+     * Returns the approximate number of heap bytes held by the public
+     * key and any Galois and relinearization keys in the bundle. See
+     * [`MemoryUsage`].
+     */
+    pub fn heap_size(&self) -> Result<u64> {
+        let mut size = self.public_key.data.heap_size()?;
+
+        if let Some(k) = &self.galois_key {
+            size += k.data.heap_size()?;
+        }
+
+        if let Some(k) = &self.relin_key {
+            size += k.data.heap_size()?;
+        }
+
+        Ok(size)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
 /**
  * The private key used to decrypt ciphertexts.
+ *
+ * # Remarks
+ * The key's memory is zeroed when it's dropped, as are the intermediate
+ * buffers used to (de)serialize it. However, serializing the key necessarily
+ * copies it into your output buffer, which you're responsible for clearing.
+ *
+ * The key is held in a [`Secret`], so its [`Debug`] output is redacted.
+ * It doesn't implement [`Clone`]; use [`duplicate`](Self::duplicate) if
+ * you really need a second copy.
  */
-pub struct PrivateKey(pub(crate) WithContext<SealSecretKey>);
+pub struct PrivateKey(pub(crate) Secret<WithContext<SealSecretKey>>);
+
+impl ConstantTimeEq for WithContext<SealSecretKey> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        // Compression takes time depending on the data, so compare the
+        // raw serialized keys.
+        let bytes = |k: &Self| {
+            k.data
+                .as_bytes_with_compression(CompressionType::None)
+                .map(Zeroizing::new)
+        };
+
+        match (bytes(self), bytes(other)) {
+            (Ok(a), Ok(b)) => {
+                Choice::from((self.params == other.params) as u8) & a.as_slice().ct_eq(b.as_slice())
+            }
+            _ => Choice::from(0),
+        }
+    }
+}
+
+impl PrivateKey {
+    /**
+     * Creates a second copy of this key, which is zeroed independently
+     * when dropped.
+     */
+    pub fn duplicate(&self) -> Self {
+        Self(self.0.duplicate())
+    }
+
+    /**
+     * Zeroes and frees this key. Unlike dropping it, this returns rather
+     * than panics if the underlying scheme fails to free the key.
+     */
+    pub fn destroy(self) -> Result<()> {
+        Ok(self.0.into_inner().data.destroy()?)
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -186,6 +257,37 @@ mod tests {
             public_key.public_key.data.as_bytes(),
             public_2.public_key.data.as_bytes()
         );
-        assert_eq!(private_key.0.as_bytes(), private_2.0.as_bytes());
+        assert_eq!(
+            private_key.0.expose_secret().as_bytes(),
+            private_2.0.expose_secret().as_bytes()
+        );
+    }
+
+    #[test]
+    fn can_destroy_private_key() {
+        let runtime = Runtime::new_fhe(&Params {
+            lattice_dimension: 8192,
+            security_level: SecurityLevel::TC128,
+            plain_modulus: 1234,
+            scheme_type: SchemeType::Bfv,
+            coeff_modulus: CoefficientModulus::bfv_default(8192, SecurityLevel::TC128)
+                .unwrap()
+                .iter()
+                .map(|x| x.value())
+                .collect(),
+        })
+        .unwrap();
+
+        let (_, private_key) = runtime.generate_keys().unwrap();
+        let private_2 = private_key.duplicate();
+
+        let sk_data = serde_json::to_string(&private_key).unwrap();
+
+        private_key.destroy().unwrap();
+
+        // Duplicates own their own copy of the key.
+        assert_eq!(serde_json::to_string(&private_2).unwrap(), sk_data);
+
+        private_2.destroy().unwrap();
     }
 }
@@ -4,27 +4,89 @@
 //! This crate contains the types and functions for executing a Sunscreen FHE program
 //! (i.e. an [`FheProgram`](sunscreen_fhe_program::FheProgram)).
 
+#[cfg(feature = "fhe")]
 mod array;
+#[cfg(feature = "tokio")]
+mod async_runtime;
+mod cancel;
+#[cfg(feature = "fhe")]
+mod crt;
 mod error;
+#[cfg(feature = "fhe")]
+mod guard;
+#[cfg(feature = "fhe")]
 mod keys;
 mod metadata;
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(feature = "fhe")]
+mod options;
+#[cfg(feature = "fhe")]
+mod policy;
+#[cfg(feature = "fhe")]
+mod probe;
+#[cfg(feature = "fhe")]
+mod proxy;
+#[cfg(feature = "fhe")]
+mod rotation;
+#[cfg(feature = "fhe")]
 mod run;
 mod runtime;
+#[cfg(feature = "fhe")]
+mod scheduler;
+mod secret;
+#[cfg(feature = "fhe")]
 mod serialization;
+#[cfg(feature = "fhe")]
+mod snapshot;
 
+#[cfg(feature = "fhe")]
+use std::collections::HashMap;
+#[cfg(feature = "zkp")]
 use std::sync::Arc;
 
+#[cfg(feature = "tokio")]
+pub use crate::async_runtime::*;
+pub use crate::cancel::CancellationToken;
+#[cfg(feature = "fhe")]
+pub use crate::crt::{CrtBasis, CrtEncoder};
 pub use crate::error::*;
+#[cfg(feature = "fhe")]
+pub use crate::guard::DecryptionGuard;
+#[cfg(feature = "fhe")]
 pub use crate::keys::*;
 pub use crate::metadata::*;
+#[cfg(feature = "metrics")]
+pub use crate::metrics::{Metrics, DEFAULT_DURATION_BUCKETS};
+#[cfg(feature = "fhe")]
+pub use crate::options::RunOptions;
+#[cfg(feature = "fhe")]
+pub use crate::policy::PolicyProvider;
+#[cfg(feature = "fhe")]
+pub use crate::probe::OpCosts;
+#[cfg(feature = "fhe")]
+pub use crate::proxy::{ReEncryptionKey, ReEncryptor, DEFAULT_DECOMPOSITION_BITS};
+#[cfg(feature = "fhe")]
+pub use crate::rotation::KeyRotation;
+#[cfg(feature = "fhe")]
 pub use run::*;
 pub use runtime::*;
+#[cfg(feature = "fhe")]
+pub use scheduler::{FairScheduler, Fairness};
+pub use secret::Secret;
+#[cfg(feature = "fhe")]
 pub use serialization::WithContext;
+#[cfg(feature = "fhe")]
+pub use snapshot::{KeySetFingerprint, RuntimeState};
 
+#[cfg(feature = "fhe")]
 use seal_fhe::{Ciphertext as SealCiphertext, Plaintext as SealPlaintext};
+#[cfg(feature = "fhe")]
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "zkp")]
 use sunscreen_zkp_backend::BigInt;
 
+#[cfg(feature = "fhe")]
 #[derive(Debug, Clone, PartialEq, Hash, Serialize, Deserialize, Eq)]
 /**
  * The underlying backend implementation of a plaintext (e.g. SEAL's [`Plaintext`](seal_fhe::Plaintext)).
@@ -36,6 +98,7 @@ pub enum InnerPlaintext {
     Seal(Vec<WithContext<SealPlaintext>>),
 }
 
+#[cfg(feature = "fhe")]
 impl InnerPlaintext {
     /**
      * Returns how many plaintexts are inside this wrapper.
@@ -95,6 +158,7 @@ impl InnerPlaintext {
     }
 }
 
+#[cfg(feature = "fhe")]
 #[derive(Clone)]
 /**
  * A type that can be either a SEAL plaintext or a ciphertext.
@@ -111,18 +175,35 @@ pub enum SealData {
     Plaintext(SealPlaintext),
 }
 
+#[cfg(feature = "fhe")]
+impl SealData {
+    /**
+     * Returns a deep copy of this data. Unlike [`Clone::clone`], this
+     * returns rather than panics if SEAL fails to allocate the copy.
+     */
+    pub fn try_clone(&self) -> seal_fhe::Result<Self> {
+        Ok(match self {
+            Self::Ciphertext(c) => Self::Ciphertext(c.try_clone()?),
+            Self::Plaintext(p) => Self::Plaintext(p.try_clone()?),
+        })
+    }
+}
+
+#[cfg(feature = "fhe")]
 impl From<SealCiphertext> for SealData {
     fn from(val: SealCiphertext) -> Self {
         Self::Ciphertext(val)
     }
 }
 
+#[cfg(feature = "fhe")]
 impl From<SealPlaintext> for SealData {
     fn from(val: SealPlaintext) -> Self {
         Self::Plaintext(val)
     }
 }
 
+#[cfg(feature = "fhe")]
 #[derive(Clone, Serialize, Deserialize)]
 /**
  * Represents an encoded plaintext suitable for use in the underlying scheme.
@@ -139,6 +220,7 @@ pub struct Plaintext {
     pub inner: InnerPlaintext,
 }
 
+#[cfg(feature = "fhe")]
 impl Plaintext {
     /**
      * Unwraps the inner plaintext as a Seal plaintext variant. Returns an
@@ -147,8 +229,49 @@ impl Plaintext {
     pub fn inner_as_seal_plaintext(&self) -> Result<&[WithContext<SealPlaintext>]> {
         self.inner.as_seal_plaintext()
     }
+
+    /**
+     * Zeroes and frees the encoded data. Plaintexts are zeroed when
+     * dropped as well, but this returns rather than panics if the
+     * underlying scheme fails to free them.
+     */
+    pub fn destroy(self) -> Result<()> {
+        match self.inner {
+            InnerPlaintext::Seal(plaintexts) => {
+                for p in plaintexts {
+                    p.data.destroy()?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "fhe")]
+impl TryIntoPlaintext for Plaintext {
+    fn try_into_plaintext(&self, params: &Params) -> Result<Plaintext> {
+        let inner = self.inner_as_seal_plaintext()?;
+
+        if inner.iter().any(|p| p.params != *params) {
+            return Err(Error::ParameterMismatch);
+        }
+
+        Ok(self.clone())
+    }
 }
 
+#[cfg(feature = "fhe")]
+impl TypeNameInstance for Plaintext {
+    fn type_name_instance(&self) -> Type {
+        self.data_type.clone()
+    }
+}
+
+#[cfg(feature = "fhe")]
+impl FheProgramInputTrait for Plaintext {}
+
+#[cfg(feature = "fhe")]
 #[derive(Clone, Deserialize, Serialize)]
 /**
  * The underlying backend implementation of a ciphertext (e.g SEAL's [`Ciphertext`](seal_fhe::Ciphertext)).
@@ -160,6 +283,7 @@ pub enum InnerCiphertext {
     Seal(Vec<WithContext<SealCiphertext>>),
 }
 
+#[cfg(feature = "fhe")]
 #[derive(Clone, Deserialize, Serialize)]
 /**
  * An encryption of the given data type. Note, the data type is
@@ -178,12 +302,117 @@ pub struct Ciphertext {
     pub inner: InnerCiphertext,
 }
 
+#[cfg(feature = "fhe")]
+impl Ciphertext {
+    /**
+     * Returns the approximate number of heap bytes held by the underlying
+     * ciphertexts. See [`MemoryUsage`](seal_fhe::MemoryUsage).
+     */
+    pub fn heap_size(&self) -> Result<u64> {
+        use seal_fhe::MemoryUsage;
+
+        match &self.inner {
+            InnerCiphertext::Seal(c) => c
+                .iter()
+                .try_fold(0, |size, c| Ok(size + c.data.heap_size()?)),
+        }
+    }
+}
+
+#[cfg(feature = "fhe")]
+/**
+ * The SEAL ciphertexts inside a [`Ciphertext`] along with the metadata
+ * needed to wrap them again, e.g. to apply a custom step with a
+ * [`seal_fhe::Evaluator`] between FHE programs.
+ *
+ * # Remarks
+ * Convert a [`Ciphertext`] with [`TryFrom`] and back with [`From`]. The
+ * conversions don't check the SEAL ciphertexts, so after modifying them,
+ * keep them valid under [`params`](Self::params) and encoded as
+ * [`data_type`](Self::data_type) expects. To wrap ciphertexts that didn't
+ * come from a [`Ciphertext`], use
+ * [`GenericRuntime::ciphertext_from_seal`](crate::GenericRuntime::ciphertext_from_seal),
+ * which validates them.
+ */
+#[derive(Clone)]
+pub struct SealCiphertexts {
+    /**
+     * The type of the encrypted value.
+     */
+    pub data_type: Type,
+
+    /**
+     * The scheme parameters the ciphertexts were encrypted under.
+     */
+    pub params: Params,
+
+    /**
+     * The SEAL ciphertexts, in the order the value's encoding uses.
+     */
+    pub ciphertexts: Vec<SealCiphertext>,
+}
+
+#[cfg(feature = "fhe")]
+impl TryFrom<Ciphertext> for SealCiphertexts {
+    type Error = Error;
+
+    /**
+     * Returns [`Error::IncorrectCiphertextCount`] if the ciphertext holds no
+     * SEAL ciphertexts and [`Error::ParameterMismatch`] if they were
+     * encrypted under different parameters.
+     */
+    fn try_from(ciphertext: Ciphertext) -> Result<Self> {
+        match ciphertext.inner {
+            InnerCiphertext::Seal(c) => {
+                let params = c
+                    .first()
+                    .ok_or(Error::IncorrectCiphertextCount)?
+                    .params
+                    .clone();
+
+                if c.iter().any(|c| c.params != params) {
+                    return Err(Error::ParameterMismatch);
+                }
+
+                Ok(Self {
+                    data_type: ciphertext.data_type,
+                    params,
+                    ciphertexts: c.into_iter().map(|c| c.data).collect(),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(feature = "fhe")]
+impl From<SealCiphertexts> for Ciphertext {
+    fn from(ciphertexts: SealCiphertexts) -> Self {
+        let params = ciphertexts.params;
+
+        Self {
+            data_type: ciphertexts.data_type,
+            inner: InnerCiphertext::Seal(
+                ciphertexts
+                    .ciphertexts
+                    .into_iter()
+                    .map(|data| WithContext {
+                        params: params.clone(),
+                        data,
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "fhe")]
 /**
  * A trait that denotes this type can be used as an
  * argument to an FHE program.
  */
 pub trait FheProgramInputTrait: TryIntoPlaintext + TypeNameInstance {}
 
+#[cfg(feature = "fhe")]
 /**
  * An input argument to an Fhe Program. See [`crate::Runtime::run`].
  */
@@ -199,20 +428,143 @@ pub enum FheProgramInput {
     Plaintext(Box<dyn FheProgramInputTrait>),
 }
 
+#[cfg(feature = "fhe")]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+/**
+ * A JSON-encoded argument to an FHE program. See
+ * [`crate::Runtime::run_json`].
+ */
+pub enum JsonInput {
+    /**
+     * The argument is an already encrypted ciphertext.
+     */
+    Ciphertext(Ciphertext),
+
+    /**
+     * The argument is an encoded plaintext. If the program expects
+     * an encrypted argument, the runtime encrypts it before running.
+     */
+    Plaintext(Plaintext),
+
+    /**
+     * The argument is a plain JSON value, e.g. a number. The runtime
+     * encodes it as the type the program expects using the given
+     * [`JsonTypes`], and encrypts it if the argument is encrypted.
+     */
+    Value(serde_json::Value),
+}
+
+#[cfg(feature = "fhe")]
+impl JsonInput {
+    /**
+     * The type of this argument, or `None` for a [`Value`](Self::Value),
+     * whose type is whatever the program expects.
+     */
+    pub fn data_type(&self) -> Option<&Type> {
+        match self {
+            Self::Ciphertext(c) => Some(&c.data_type),
+            Self::Plaintext(p) => Some(&p.data_type),
+            Self::Value(_) => None,
+        }
+    }
+}
+
+#[cfg(feature = "fhe")]
+/**
+ * A type whose values [`crate::Runtime::run_json`] can read from
+ * [`JsonInput::Value`] arguments.
+ */
+pub trait TryFromJson: Sized {
+    /**
+     * Attempts to read a value of this type from `value`.
+     */
+    fn try_from_json(value: &serde_json::Value) -> Result<Self>;
+}
+
+#[cfg(feature = "fhe")]
+type JsonEncoder = fn(&serde_json::Value, &Params) -> Result<Plaintext>;
+
+#[cfg(feature = "fhe")]
+fn encode_json<T>(value: &serde_json::Value, params: &Params) -> Result<Plaintext>
+where
+    T: TryFromJson + TryIntoPlaintext,
+{
+    T::try_from_json(value)?.try_into_plaintext(params)
+}
+
+#[cfg(feature = "fhe")]
+#[derive(Clone, Default)]
+/**
+ * The types [`crate::Runtime::run_json`] can encode from
+ * [`JsonInput::Value`] arguments, keyed by their [`Type`] name.
+ *
+ * # Remarks
+ * A program's signature only records the names of its argument types,
+ * so each type JSON values are given for must be registered, including
+ * each instantiation of generic types such as `Fractional<64>`.
+ */
+pub struct JsonTypes {
+    encoders: HashMap<String, JsonEncoder>,
+}
+
+#[cfg(feature = "fhe")]
+impl JsonTypes {
+    /**
+     * Creates an empty set of types.
+     */
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * Adds `T` to these types.
+     */
+    pub fn register<T>(mut self) -> Self
+    where
+        T: TryFromJson + TryIntoPlaintext + TypeName,
+    {
+        self.encoders.insert(T::type_name().name, encode_json::<T>);
+        self
+    }
+
+    /**
+     * Encodes `value` as a plaintext of `data_type` under `params`.
+     *
+     * Returns [`Error::FheTypeError`] if `data_type` isn't registered.
+     */
+    pub fn encode(
+        &self,
+        data_type: &Type,
+        value: &serde_json::Value,
+        params: &Params,
+    ) -> Result<Plaintext> {
+        let encode = self.encoders.get(&data_type.name).ok_or_else(|| {
+            Error::fhe_type_error(&format!("No JSON encoding for {}", data_type.name))
+        })?;
+
+        encode(value, params)
+    }
+}
+
+#[cfg(feature = "zkp")]
 /**
  * Denotes this type can be used as an input to a ZKP
  * program.
  */
 pub trait ZkpProgramInputTrait: ToNativeFields + TypeNameInstance {}
 
+#[cfg(feature = "zkp")]
 impl<T, const N: usize> ZkpProgramInputTrait for [T; N] where T: ZkpProgramInputTrait + TypeName {}
 
+#[cfg(feature = "zkp")]
 #[derive(Clone)]
 /**
  * An input argument to a ZKP program.
  */
 pub struct ZkpProgramInput(pub Arc<dyn ZkpProgramInputTrait>);
 
+#[cfg(feature = "zkp")]
 impl<T> From<T> for ZkpProgramInput
 where
     T: ZkpProgramInputTrait + 'static,
@@ -222,6 +574,7 @@ where
     }
 }
 
+#[cfg(feature = "fhe")]
 impl TypeNameInstance for FheProgramInput {
     fn type_name_instance(&self) -> Type {
         match self {
@@ -231,12 +584,14 @@ impl TypeNameInstance for FheProgramInput {
     }
 }
 
+#[cfg(feature = "fhe")]
 impl From<Ciphertext> for FheProgramInput {
     fn from(val: Ciphertext) -> Self {
         Self::Ciphertext(val)
     }
 }
 
+#[cfg(feature = "fhe")]
 impl<T> From<T> for FheProgramInput
 where
     T: FheProgramInputTrait + 'static,
@@ -246,6 +601,7 @@ where
     }
 }
 
+#[cfg(feature = "fhe")]
 /**
  * This trait denotes one may attempt to turn this type into a plaintext.
  */
@@ -256,6 +612,7 @@ pub trait TryIntoPlaintext {
     fn try_into_plaintext(&self, params: &Params) -> Result<Plaintext>;
 }
 
+#[cfg(feature = "zkp")]
 /**
  * A trait for converting values into fields used by ZKPs.
  */
@@ -269,6 +626,7 @@ pub trait ToNativeFields {
     fn to_native_fields(&self) -> Vec<BigInt>;
 }
 
+#[cfg(feature = "zkp")]
 impl<T, const N: usize> ToNativeFields for [T; N]
 where
     T: ToNativeFields,
@@ -278,6 +636,7 @@ where
     }
 }
 
+#[cfg(feature = "fhe")]
 /**
  * This trait specifies one may attempt to convert a plaintext into this type.
  */
@@ -291,6 +650,7 @@ where
     fn try_from_plaintext(plaintext: &Plaintext, params: &Params) -> Result<Self>;
 }
 
+#[cfg(feature = "fhe")]
 /**
  * Declare how many ciphertexts an FheType decomposes into. The runtime needs this
  * to correctly bundle return values from an Fhe Program.
@@ -302,6 +662,7 @@ pub trait NumCiphertexts {
     const NUM_CIPHERTEXTS: usize;
 }
 
+#[cfg(feature = "fhe")]
 /**
  * Denotes the given rust type is an encoding in an FHE scheme
  */
@@ -310,6 +671,7 @@ pub trait FheType:
 {
 }
 
+#[cfg(feature = "fhe")]
 /**
  * Denotes the given type is valid under the BFV scheme.
  */
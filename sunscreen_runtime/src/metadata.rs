@@ -1,10 +1,14 @@
+#[cfg(feature = "fhe")]
 use rlp::encode_list;
+#[cfg(feature = "fhe")]
 use seal_fhe::SecurityLevel;
 pub use semver::Version;
 use serde::{Deserialize, Serialize};
 use sunscreen_compiler_common::Type;
-use sunscreen_fhe_program::{FheProgram, SchemeType};
+#[cfg(feature = "fhe")]
+use sunscreen_fhe_program::{FheProgram, ProgramGraph, SchemeType};
 
+#[cfg(feature = "fhe")]
 use crate::{Error, Result};
 
 /**
@@ -43,6 +47,7 @@ pub struct CallSignature {
     pub num_ciphertexts: Vec<usize>,
 }
 
+#[cfg(feature = "fhe")]
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 /**
  * A key type required for an Fhe Program to function correctly.
@@ -63,6 +68,7 @@ pub enum RequiredKeys {
     PublicKey,
 }
 
+#[cfg(feature = "fhe")]
 #[derive(Debug, Clone, Serialize, Hash, Deserialize, PartialEq, Eq)]
 /**
  * The parameter set required for a given FHE program to run efficiently and correctly.
@@ -94,6 +100,7 @@ pub struct Params {
     pub security_level: SecurityLevel,
 }
 
+#[cfg(feature = "fhe")]
 impl Params {
     /**
      * Serialize the params to a byte array.
@@ -172,6 +179,7 @@ impl Params {
     }
 }
 
+#[cfg(feature = "fhe")]
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 /**
  * A serializable list of requirements for an Fhe Program.
@@ -191,8 +199,71 @@ pub struct FheProgramMetadata {
      * The set of keys required to run the FHE program.
      */
     pub required_keys: Vec<RequiredKeys>,
+
+    /**
+     * The authorization requirements (e.g. `role:analyst`) a
+     * [`PolicyProvider`](crate::PolicyProvider) must grant before the
+     * runtime runs the FHE program.
+     */
+    #[serde(default)]
+    pub requirements: Vec<String>,
+
+    /**
+     * The name of each argument in the FHE program, in the same order as
+     * [`CallSignature::arguments`]. Empty for programs compiled before
+     * argument names were recorded.
+     */
+    #[serde(default)]
+    pub argument_names: Vec<String>,
+
+    /**
+     * Whether running the FHE program is a pure function of its inputs,
+     * i.e. it neither encrypts fresh values nor re-randomizes or floods
+     * the noise of its outputs.
+     *
+     * # Remarks
+     * Running a deterministic program twice with the same ciphertexts and
+     * keys yields identical ciphertexts, so callers may cache results
+     * keyed on their inputs and deduplicate retried requests. Results of
+     * a program that isn't deterministic decrypt to the same values but
+     * differ bitwise, so each run must be treated as distinct.
+     *
+     * Programs compiled before this flag was recorded report `false`.
+     */
+    #[serde(default)]
+    pub deterministic: bool,
+
+    /**
+     * The FHE program's multiplicative depth before and after the
+     * compiler's optimizations.
+     *
+     * # Remarks
+     * Programs compiled before depths were recorded report 0 for both.
+     */
+    #[serde(default)]
+    pub multiplicative_depth: DepthReport,
 }
 
+#[cfg(feature = "fhe")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+/**
+ * The greatest number of ciphertext-ciphertext multiplications on any
+ * path through an FHE program, as written and as compiled.
+ */
+pub struct DepthReport {
+    /**
+     * The depth of the program as written.
+     */
+    pub before: usize,
+
+    /**
+     * The depth of the compiled program, after passes such as rebalancing
+     * chains of multiplications into trees.
+     */
+    pub after: usize,
+}
+
+#[cfg(feature = "fhe")]
 #[derive(Clone, Serialize, Deserialize)]
 /**
  * An FHE program with its associated metadata.
@@ -210,11 +281,32 @@ pub struct CompiledFheProgram {
     pub metadata: FheProgramMetadata,
 }
 
+#[cfg(feature = "fhe")]
+impl CompiledFheProgram {
+    /**
+     * Returns a read-only view of the program's operations for analysis,
+     * e.g. to find its multiplications or its critical path.
+     */
+    pub fn graph(&self) -> ProgramGraph<'_> {
+        ProgramGraph::new(&self.fhe_program_fn)
+    }
+
+    /**
+     * Returns the distinct row rotation steps the program performs,
+     * sorted, where positive steps rotate left, negative steps rotate
+     * right and 0 swaps rows.
+     */
+    pub fn rotation_steps(&self) -> Vec<i32> {
+        crate::runtime::required_rotations(&self.fhe_program_fn)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
+    #[cfg(feature = "fhe")]
     fn can_roundtrip_params() {
         let params = Params {
             lattice_dimension: 4096,
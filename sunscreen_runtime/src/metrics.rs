@@ -0,0 +1,367 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use seal_fhe::{Operation, ProfileReport};
+
+/**
+ * The default upper bounds, in seconds, of the buckets of
+ * [`Metrics`]' run duration histogram.
+ */
+pub const DEFAULT_DURATION_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+];
+
+#[derive(Debug, Clone, PartialEq)]
+struct Histogram {
+    /**
+     * The number of observations in each bucket, not cumulative. The last
+     * entry counts observations above every bound.
+     */
+    counts: Vec<u64>,
+    sum: f64,
+}
+
+impl Histogram {
+    fn new(buckets: usize) -> Self {
+        Self {
+            counts: vec![0; buckets + 1],
+            sum: 0.0,
+        }
+    }
+
+    fn observe(&mut self, bounds: &[f64], value: f64) {
+        let bucket = bounds
+            .iter()
+            .position(|b| value <= *b)
+            .unwrap_or(bounds.len());
+
+        self.counts[bucket] += 1;
+        self.sum += value;
+    }
+}
+
+#[derive(Default)]
+struct State {
+    runs: BTreeMap<(String, bool), u64>,
+    durations: BTreeMap<String, Histogram>,
+    operations: ProfileReport,
+}
+
+/**
+ * Aggregates counters and histograms about FHE program runs and encodes
+ * them in Prometheus' text exposition format, so services can serve them
+ * from whatever HTTP endpoint they already have.
+ *
+ * # Remarks
+ * Record runs with [`RunOptions::meter`](crate::RunOptions::meter), or
+ * with [`record_run`](Self::record_run) and
+ * [`record_profile`](Self::record_profile) for programs run otherwise.
+ * [`encode`](Self::encode) exports:
+ * * `sunscreen_program_runs_total{program, outcome}`: a counter of runs,
+ *   where `outcome` is `ok` or `error`.
+ * * `sunscreen_program_run_duration_seconds{program}`: a histogram of run
+ *   wall times.
+ * * `sunscreen_evaluator_operations_total{operation}` and
+ *   `sunscreen_evaluator_operation_seconds_total{operation}`: counters of
+ *   the evaluator operations runs performed and the time spent in them.
+ *
+ * Counters only ever increase, as Prometheus expects. Share one instance
+ * across threads (e.g. in an [`Arc`](std::sync::Arc)) to aggregate all of
+ * a service's runs.
+ */
+pub struct Metrics {
+    buckets: Vec<f64>,
+    state: Mutex<State>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    /**
+     * Creates an empty set of metrics with the
+     * [`DEFAULT_DURATION_BUCKETS`].
+     */
+    pub fn new() -> Self {
+        Self::with_buckets(DEFAULT_DURATION_BUCKETS)
+    }
+
+    /**
+     * Creates an empty set of metrics whose run duration histogram has the
+     * given bucket upper bounds, in seconds.
+     *
+     * # Panics
+     * If `buckets` isn't strictly increasing or contains a non-finite
+     * bound. Prometheus' `+Inf` bucket is always included.
+     */
+    pub fn with_buckets(buckets: &[f64]) -> Self {
+        assert!(
+            buckets.iter().all(|b| b.is_finite()),
+            "Bucket bounds must be finite."
+        );
+        assert!(
+            buckets.windows(2).all(|w| w[0] < w[1]),
+            "Bucket bounds must be strictly increasing."
+        );
+
+        Self {
+            buckets: buckets.to_owned(),
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // Updates can't panic midway, so the state stays consistent even
+        // if the lock was poisoned.
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /**
+     * Records a run of the program named `program` that took `duration`
+     * and succeeded if `ok`.
+     */
+    pub fn record_run(&self, program: &str, duration: Duration, ok: bool) {
+        let mut state = self.lock();
+
+        *state.runs.entry((program.to_owned(), ok)).or_default() += 1;
+
+        state
+            .durations
+            .entry(program.to_owned())
+            .or_insert_with(|| Histogram::new(self.buckets.len()))
+            .observe(&self.buckets, duration.as_secs_f64());
+    }
+
+    /**
+     * Adds the operations in a
+     * [`ProfilingEvaluator`](seal_fhe::ProfilingEvaluator)'s report to the
+     * evaluator operation counters.
+     *
+     * # Remarks
+     * Reports are cumulative, so either record a fresh evaluator's report
+     * once or [`reset`](seal_fhe::ProfilingEvaluator::reset) it after each
+     * recording.
+     */
+    pub fn record_profile(&self, report: &ProfileReport) {
+        let mut state = self.lock();
+
+        for (operation, profile) in &report.operations {
+            let total = state.operations.operations.entry(*operation).or_default();
+
+            total.count += profile.count;
+            total.total_time += profile.total_time;
+        }
+    }
+
+    /**
+     * Encodes the metrics recorded so far in Prometheus' text exposition
+     * format (version 0.0.4), ready to be served with content type
+     * `text/plain; version=0.0.4`.
+     */
+    pub fn encode(&self) -> String {
+        let state = self.lock();
+        let mut out = String::new();
+
+        header(
+            &mut out,
+            "sunscreen_program_runs_total",
+            "FHE program runs, by outcome.",
+            "counter",
+        );
+
+        for ((program, ok), count) in &state.runs {
+            let outcome = if *ok { "ok" } else { "error" };
+
+            writeln!(
+                out,
+                "sunscreen_program_runs_total{{program=\"{}\",outcome=\"{}\"}} {}",
+                escape(program),
+                outcome,
+                count
+            )
+            .unwrap();
+        }
+
+        header(
+            &mut out,
+            "sunscreen_program_run_duration_seconds",
+            "Wall time of FHE program runs.",
+            "histogram",
+        );
+
+        for (program, histogram) in &state.durations {
+            let program = escape(program);
+            let name = "sunscreen_program_run_duration_seconds";
+            let mut cumulative = 0;
+
+            for (i, count) in histogram.counts.iter().enumerate() {
+                cumulative += count;
+
+                let le = match self.buckets.get(i) {
+                    Some(b) => b.to_string(),
+                    None => "+Inf".to_owned(),
+                };
+
+                writeln!(
+                    out,
+                    "{}_bucket{{program=\"{}\",le=\"{}\"}} {}",
+                    name, program, le, cumulative
+                )
+                .unwrap();
+            }
+
+            writeln!(
+                out,
+                "{}_sum{{program=\"{}\"}} {}",
+                name, program, histogram.sum
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "{}_count{{program=\"{}\"}} {}",
+                name, program, cumulative
+            )
+            .unwrap();
+        }
+
+        header(
+            &mut out,
+            "sunscreen_evaluator_operations_total",
+            "Evaluator operations performed by FHE program runs.",
+            "counter",
+        );
+
+        for (operation, profile) in &state.operations.operations {
+            writeln!(
+                out,
+                "sunscreen_evaluator_operations_total{{operation=\"{}\"}} {}",
+                operation_label(*operation),
+                profile.count
+            )
+            .unwrap();
+        }
+
+        header(
+            &mut out,
+            "sunscreen_evaluator_operation_seconds_total",
+            "Wall time spent in evaluator operations, summed across threads.",
+            "counter",
+        );
+
+        for (operation, profile) in &state.operations.operations {
+            writeln!(
+                out,
+                "sunscreen_evaluator_operation_seconds_total{{operation=\"{}\"}} {}",
+                operation_label(*operation),
+                profile.total_time.as_secs_f64()
+            )
+            .unwrap();
+        }
+
+        out
+    }
+}
+
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    writeln!(out, "# HELP {} {}", name, help).unwrap();
+    writeln!(out, "# TYPE {} {}", name, kind).unwrap();
+}
+
+/**
+ * Escapes a label value as the text format requires.
+ */
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/**
+ * Converts an operation's name to snake case, e.g. `multiply_plain`.
+ */
+fn operation_label(operation: Operation) -> String {
+    let mut label = String::new();
+
+    for (i, c) in format!("{:?}", operation).chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            label.push('_');
+        }
+
+        label.push(c.to_ascii_lowercase());
+    }
+
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use seal_fhe::OperationProfile;
+
+    #[test]
+    fn encodes_runs_and_durations() {
+        let metrics = Metrics::with_buckets(&[0.1, 1.0]);
+
+        metrics.record_run("dot", Duration::from_millis(50), true);
+        metrics.record_run("dot", Duration::from_millis(500), true);
+        metrics.record_run("dot", Duration::from_secs(2), false);
+
+        let text = metrics.encode();
+
+        for line in [
+            "# TYPE sunscreen_program_runs_total counter",
+            "sunscreen_program_runs_total{program=\"dot\",outcome=\"error\"} 1",
+            "sunscreen_program_runs_total{program=\"dot\",outcome=\"ok\"} 2",
+            "# TYPE sunscreen_program_run_duration_seconds histogram",
+            "sunscreen_program_run_duration_seconds_bucket{program=\"dot\",le=\"0.1\"} 1",
+            "sunscreen_program_run_duration_seconds_bucket{program=\"dot\",le=\"1\"} 2",
+            "sunscreen_program_run_duration_seconds_bucket{program=\"dot\",le=\"+Inf\"} 3",
+            "sunscreen_program_run_duration_seconds_sum{program=\"dot\"} 2.55",
+            "sunscreen_program_run_duration_seconds_count{program=\"dot\"} 3",
+        ] {
+            assert!(text.lines().any(|l| l == line), "missing {}", line);
+        }
+    }
+
+    #[test]
+    fn accumulates_profiles() {
+        let metrics = Metrics::new();
+
+        let mut report = ProfileReport::default();
+        report.operations.insert(
+            Operation::MultiplyPlain,
+            OperationProfile {
+                count: 2,
+                total_time: Duration::from_millis(250),
+            },
+        );
+
+        metrics.record_profile(&report);
+        metrics.record_profile(&report);
+
+        let text = metrics.encode();
+
+        assert!(text
+            .lines()
+            .any(|l| l == "sunscreen_evaluator_operations_total{operation=\"multiply_plain\"} 4"));
+        assert!(text.lines().any(|l| l
+            == "sunscreen_evaluator_operation_seconds_total{operation=\"multiply_plain\"} 0.5"));
+    }
+
+    #[test]
+    fn escapes_label_values() {
+        let metrics = Metrics::new();
+
+        metrics.record_run("a\"b\\c\nd", Duration::ZERO, true);
+
+        assert!(metrics
+            .encode()
+            .contains("program=\"a\\\"b\\\\c\\nd\",outcome=\"ok\""));
+    }
+}
@@ -0,0 +1,67 @@
+#[cfg(feature = "metrics")]
+use crate::metrics::Metrics;
+use crate::policy::PolicyProvider;
+use crate::scheduler::FairScheduler;
+use crate::CancellationToken;
+
+#[derive(Clone, Copy, Default)]
+/**
+ * Options for running an FHE program with
+ * [`GenericRuntime::run_with_options`](crate::GenericRuntime::run_with_options).
+ *
+ * # Remarks
+ * Every option defaults to off, so set only the ones you need and fill in
+ * the rest with `..Default::default()`:
+ *
+ * ```ignore
+ * let options = RunOptions {
+ *     policy: Some(&policy),
+ *     cancel: Some(&cancel),
+ *     ..Default::default()
+ * };
+ * ```
+ */
+pub struct RunOptions<'a> {
+    /**
+     * Grants the program's authorization requirements, as declared with
+     * `#[fhe_program(requires = "...")]`. Without a policy, no requirement
+     * is granted, so a program with requirements fails with
+     * [`Error::Unauthorized`](crate::Error::Unauthorized).
+     */
+    pub policy: Option<&'a dyn PolicyProvider>,
+
+    /**
+     * Stops the run early with [`Error::Cancelled`](crate::Error::Cancelled)
+     * once cancelled. The runtime checks the token before each operation in
+     * the program, so cancel it from another thread (e.g. when a client
+     * disconnects or a deadline passes) to free up the CPU sooner than
+     * letting a long program run to completion.
+     */
+    pub cancel: Option<&'a CancellationToken>,
+
+    /**
+     * Takes a slice from the given [`FairScheduler`] for each operation,
+     * with the given priority.
+     *
+     * # Remarks
+     * Running every concurrent program through the same scheduler
+     * interleaves their operations according to its
+     * [`Fairness`](crate::Fairness) policy and each program's priority,
+     * which must be at least 1. Under
+     * [`Fairness::Weighted`](crate::Fairness::Weighted), a program with
+     * priority 2 gets twice the operations of one with priority 1 while both
+     * wait for slices.
+     *
+     * # Panics
+     * Running a program panics if the priority is 0.
+     */
+    pub schedule: Option<(&'a FairScheduler, u32)>,
+
+    /**
+     * Records the run in the given [`Metrics`] under the given program
+     * name: its outcome and wall time, and the evaluator operations it
+     * performed, whether it succeeds or not.
+     */
+    #[cfg(feature = "metrics")]
+    pub meter: Option<(&'a str, &'a Metrics)>,
+}
@@ -0,0 +1,48 @@
+use crate::{CompiledFheProgram, Error, Result};
+
+/**
+ * Decides whether the party on whose behalf a program runs satisfies the
+ * program's authorization requirements (e.g. `role:analyst`), as declared
+ * with `#[fhe_program(requires = "...")]`.
+ *
+ * # Remarks
+ * Pass a provider in [`RunOptions::policy`](crate::RunOptions::policy) for
+ * each request, e.g. one built from the caller's verified credentials.
+ * Requirements are opaque strings to the runtime; their meaning is up to
+ * the provider.
+ *
+ * Any `Fn(&str) -> bool` closure is a provider.
+ */
+pub trait PolicyProvider {
+    /**
+     * Returns whether the given requirement is granted.
+     */
+    fn is_granted(&self, requirement: &str) -> bool;
+}
+
+impl<F> PolicyProvider for F
+where
+    F: Fn(&str) -> bool,
+{
+    fn is_granted(&self, requirement: &str) -> bool {
+        self(requirement)
+    }
+}
+
+/**
+ * Returns [`Error::Unauthorized`] for the first of the program's
+ * requirements `policy` doesn't grant. Without a policy, no requirement is
+ * granted.
+ */
+pub(crate) fn authorize(
+    fhe_program: &CompiledFheProgram,
+    policy: Option<&dyn PolicyProvider>,
+) -> Result<()> {
+    for requirement in fhe_program.metadata.requirements.iter() {
+        if !policy.map_or(false, |p| p.is_granted(requirement)) {
+            return Err(Error::unauthorized(requirement));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,259 @@
+use crate::{CompiledFheProgram, Params, Result};
+
+use seal_fhe::{
+    BFVEvaluator, BfvEncryptionParametersBuilder, Context as SealContext, Encryptor, Evaluator,
+    KeyGenerator, Modulus, Plaintext,
+};
+use serde::{Deserialize, Serialize};
+use sunscreen_fhe_program::{EdgeInfo, Literal, Operation, ProgramGraph, ProgramNode};
+
+use std::time::{Duration, Instant};
+
+/**
+ * The number of times [`Params::probe`] times each operation. It reports
+ * the fastest run, which is the least affected by other load on the
+ * machine.
+ */
+const PROBE_ITERATIONS: u32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/**
+ * The time a single FHE operation takes under a given set of parameters
+ * on the machine that measured it. See [`Params::probe`].
+ */
+pub struct OpCosts {
+    /**
+     * Adding or subtracting two ciphertexts.
+     */
+    pub add: Duration,
+
+    /**
+     * Adding or subtracting a plaintext to a ciphertext.
+     */
+    pub add_plain: Duration,
+
+    /**
+     * Negating a ciphertext.
+     */
+    pub negate: Duration,
+
+    /**
+     * Multiplying two ciphertexts, without relinearization.
+     */
+    pub multiply: Duration,
+
+    /**
+     * Multiplying a ciphertext by a plaintext, dominated by the number
+     * theoretic transforms of both operands.
+     */
+    pub multiply_plain: Duration,
+
+    /**
+     * Relinearizing the product of two ciphertexts. Zero if the
+     * parameters don't support relinearization.
+     */
+    pub relinearize: Duration,
+
+    /**
+     * Rotating the rows of a ciphertext by one place. Rotating by a
+     * number of places that isn't a power of 2 takes several rotations.
+     * Zero if the parameters don't support batching.
+     */
+    pub rotate: Duration,
+
+    /**
+     * Transforming a ciphertext to the number theoretic transform domain,
+     * which multiplications do to each operand. Programs don't perform it
+     * as an operation of its own, so it only informs the other costs.
+     */
+    #[serde(default)]
+    pub ntt: Duration,
+
+    /**
+     * Swapping the rows of a ciphertext. Zero if the parameters don't
+     * support batching.
+     */
+    pub swap_rows: Duration,
+}
+
+impl OpCosts {
+    /**
+     * Returns the cost of the given node of `graph`. Inputs, outputs and
+     * literals cost nothing.
+     *
+     * # Remarks
+     * Rotation keys only cover powers of 2, so shifting by `n` places
+     * costs one rotation per bit set in `n`.
+     */
+    pub fn cost(&self, graph: &ProgramGraph, node: &ProgramNode) -> Duration {
+        match node.operation {
+            Operation::Add | Operation::Sub => self.add,
+            Operation::AddPlaintext | Operation::SubPlaintext => self.add_plain,
+            Operation::Negate => self.negate,
+            Operation::Multiply => self.multiply,
+            Operation::MultiplyPlaintext => self.multiply_plain,
+            Operation::Relinearize => self.relinearize,
+            Operation::ShiftLeft | Operation::ShiftRight => {
+                self.rotate * shift_places(graph, node.id).count_ones()
+            }
+            Operation::SwapRows => self.swap_rows,
+            Operation::InputCiphertext(_)
+            | Operation::InputPlaintext(_)
+            | Operation::Literal(_)
+            | Operation::OutputCiphertext => Duration::ZERO,
+        }
+    }
+}
+
+/**
+ * Returns the number of places the shift with the given id rotates by,
+ * which its right operand holds as a literal.
+ */
+fn shift_places(graph: &ProgramGraph, id: usize) -> u64 {
+    graph
+        .operands(id)
+        .iter()
+        .filter(|e| matches!(e.operand, EdgeInfo::Right))
+        .find_map(|e| match graph.node(e.source)?.operation {
+            Operation::Literal(Literal::U64(v)) => Some(*v),
+            _ => None,
+        })
+        .unwrap_or(1)
+}
+
+/**
+ * Returns the fastest of [`PROBE_ITERATIONS`] runs of `f`.
+ */
+fn fastest<F>(mut f: F) -> Result<Duration>
+where
+    F: FnMut() -> Result<()>,
+{
+    let mut best = Duration::MAX;
+
+    for _ in 0..PROBE_ITERATIONS {
+        let start = Instant::now();
+        f()?;
+        best = best.min(start.elapsed());
+    }
+
+    Ok(best)
+}
+
+impl Params {
+    /**
+     * Measures how long each FHE operation takes with these parameters on
+     * the current machine.
+     *
+     * # Remarks
+     * This generates keys and times a handful of runs of each operation,
+     * which takes from milliseconds to a few seconds depending on the
+     * lattice dimension. Pass the result to
+     * [`CompiledFheProgram::estimate_latency`] for machine-specific
+     * estimates, and cache it, as it only changes with the parameters and
+     * the hardware.
+     */
+    pub fn probe(&self) -> Result<OpCosts> {
+        let params = BfvEncryptionParametersBuilder::new()
+            .set_plain_modulus_u64(self.plain_modulus)
+            .set_poly_modulus_degree(self.lattice_dimension)
+            .set_coefficient_modulus(
+                self.coeff_modulus
+                    .iter()
+                    .map(|v| Modulus::new(*v))
+                    .collect::<seal_fhe::Result<Vec<Modulus>>>()?,
+            )
+            .build()?;
+
+        let context = SealContext::new(&params, true, self.security_level)?;
+        let keygen = KeyGenerator::new(&context)?;
+        let encryptor = Encryptor::with_public_key(&context, &keygen.create_public_key()?)?;
+        let evaluator = BFVEvaluator::new(&context)?;
+
+        // A dense plaintext, as SEAL shortcuts multiplying by constants.
+        let mut plain = Plaintext::new()?;
+        plain.resize(self.lattice_dimension as usize);
+
+        for i in 0..self.lattice_dimension as usize {
+            plain.set_coefficient(i, (i as u64 * 7 + 1) % self.plain_modulus);
+        }
+
+        let a = encryptor.encrypt(&plain)?;
+        let b = encryptor.encrypt(&plain)?;
+        let product = evaluator.multiply(&a, &b)?;
+
+        let mut costs = OpCosts {
+            add: fastest(|| {
+                evaluator.add(&a, &b)?;
+                Ok(())
+            })?,
+            add_plain: fastest(|| {
+                evaluator.add_plain(&a, &plain)?;
+                Ok(())
+            })?,
+            negate: fastest(|| {
+                evaluator.negate(&a)?;
+                Ok(())
+            })?,
+            multiply: fastest(|| {
+                evaluator.multiply(&a, &b)?;
+                Ok(())
+            })?,
+            multiply_plain: fastest(|| {
+                evaluator.multiply_plain(&a, &plain)?;
+                Ok(())
+            })?,
+            relinearize: Duration::ZERO,
+            rotate: Duration::ZERO,
+            ntt: fastest(|| {
+                evaluator.transform_to_ntt(&a)?;
+                Ok(())
+            })?,
+            swap_rows: Duration::ZERO,
+        };
+
+        // Parameters with a single coefficient modulus can't create
+        // relinearization or Galois keys, so programs compiled for them
+        // never use these operations.
+        if let Ok(relin_keys) = keygen.create_relinearization_keys() {
+            costs.relinearize = fastest(|| {
+                evaluator.relinearize(&product, &relin_keys)?;
+                Ok(())
+            })?;
+        }
+
+        if let Ok(galois_keys) = keygen.create_galois_keys() {
+            // Rotations fail without batching, which programs that rotate
+            // always use.
+            if evaluator.rotate_rows(&a, 1, &galois_keys).is_ok() {
+                costs.rotate = fastest(|| {
+                    evaluator.rotate_rows(&a, 1, &galois_keys)?;
+                    Ok(())
+                })?;
+                costs.swap_rows = fastest(|| {
+                    evaluator.rotate_columns(&a, &galois_keys)?;
+                    Ok(())
+                })?;
+            }
+        }
+
+        Ok(costs)
+    }
+}
+
+impl CompiledFheProgram {
+    /**
+     * Estimates the time a single thread takes to run this program on a
+     * machine with the given [`OpCosts`], i.e. the sum of the costs of its
+     * operations.
+     *
+     * # Remarks
+     * The runtime evaluates independent operations in parallel, so the
+     * wall-clock time on a machine with many cores can be lower. The
+     * costs should come from probing the program's own parameters.
+     */
+    pub fn estimate_latency(&self, costs: &OpCosts) -> Duration {
+        let graph = self.graph();
+
+        graph.nodes().iter().map(|n| costs.cost(&graph, n)).sum()
+    }
+}
@@ -0,0 +1,364 @@
+use std::io::{BufRead, Write};
+
+use rayon::prelude::*;
+use seal_fhe::rns::{add_mod, mul_mod, pow_mod, reduce_signed, NttTables};
+use seal_fhe::{
+    Ciphertext as SealCiphertext, Context as SealContext, Encryptor, PublicKey as SealPublicKey,
+    SecretKey as SealSecretKey,
+};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::{Ciphertext, Error, InnerCiphertext, Params, Result, WithContext};
+
+/**
+ * The default number of bits in each digit a [`ReEncryptor`] decomposes
+ * ciphertexts into.
+ */
+pub const DEFAULT_DECOMPOSITION_BITS: u32 = 8;
+
+/**
+ * Lets a [`ReEncryptor`] switch ciphertexts from one key pair to another
+ * without decrypting them. Create one with
+ * [`GenericRuntime::generate_reencryption_key`](crate::GenericRuntime::generate_reencryption_key).
+ *
+ * # Remarks
+ * The key holds encryptions of the delegator's private key under the
+ * delegatee's public key, so anyone holding both it and the delegatee's
+ * private key can recover the delegator's private key. Give it only to
+ * the proxy doing the re-encryption, never to the delegatee.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReEncryptionKey {
+    params: Params,
+    decomposition_bits: u32,
+    moduli: Vec<u64>,
+    // One (c0, c1) pair per prime and digit, prime-major.
+    keys: Vec<(Vec<u64>, Vec<u64>)>,
+}
+
+impl ReEncryptionKey {
+    pub(crate) fn generate(
+        context: &SealContext,
+        params: &Params,
+        from: &SealSecretKey,
+        to: &SealPublicKey,
+        decomposition_bits: u32,
+    ) -> Result<Self> {
+        assert!(
+            (1..=32).contains(&decomposition_bits),
+            "Decomposition bits must be between 1 and 32"
+        );
+
+        let n = params.lattice_dimension as usize;
+        let encryptor = Encryptor::with_public_key(context, to)?;
+
+        // Fresh encryptions sit at the first level of the modulus switching
+        // chain, so their primes are the ones ciphertexts can have.
+        let template = encryptor.encrypt_zero()?;
+        let moduli = params.coeff_modulus[..template.coeff_modulus_size() as usize].to_owned();
+
+        let secret = from.coefficients(context)?;
+        let mut keys = vec![];
+
+        for (j, q) in moduli.iter().enumerate() {
+            let secret_j = Zeroizing::new(
+                secret
+                    .iter()
+                    .map(|s| reduce_signed(*s, *q))
+                    .collect::<Vec<_>>(),
+            );
+
+            for l in 0..digits(*q, decomposition_bits) {
+                // Adding 2^(wl) s to only the j-th block encrypts the value
+                // that is 2^(wl) s modulo q_j and 0 modulo the other primes.
+                let zero = encryptor.encrypt_zero()?;
+                let scale = pow_mod(2, (decomposition_bits * l) as u64, *q);
+                let mut c0 = zero.polynomial(0)?;
+
+                for (c, s) in c0[j * n..(j + 1) * n].iter_mut().zip(secret_j.iter()) {
+                    *c = add_mod(*c, mul_mod(*s, scale, *q), *q);
+                }
+
+                keys.push((c0, zero.polynomial(1)?));
+            }
+        }
+
+        Ok(Self {
+            params: params.clone(),
+            decomposition_bits,
+            moduli,
+            keys,
+        })
+    }
+
+    /**
+     * The parameters of the ciphertexts this key re-encrypts.
+     */
+    pub fn params(&self) -> &Params {
+        &self.params
+    }
+
+    /**
+     * The number of bits in each digit ciphertexts are decomposed into.
+     */
+    pub fn decomposition_bits(&self) -> u32 {
+        self.decomposition_bits
+    }
+}
+
+/**
+ * The number of `bits`-bit digits in a value modulo `q`.
+ */
+fn digits(q: u64, bits: u32) -> u32 {
+    (64 - q.leading_zeros() + bits - 1) / bits
+}
+
+/**
+ * Switches ciphertexts from one key pair to another without decrypting
+ * them, e.g. to share encrypted records with an auditor.
+ *
+ * # Remarks
+ * Unlike [`KeyRotation`](crate::KeyRotation), re-encrypting needs only a
+ * [`ReEncryptionKey`], not the delegator's private key, so it can run on
+ * an untrusted server. The results decrypt under the delegatee's private
+ * key to the same values.
+ *
+ * Re-encryption is key switching: each ciphertext's second polynomial is
+ * split into digits of [`decomposition_bits`](ReEncryptionKey::decomposition_bits)
+ * bits, which multiply the key's encryptions of the delegator's private
+ * key. This costs noise budget growing with the digit size and the
+ * lattice dimension, roughly `bits + log2(n)` bits plus a few more for
+ * each prime, so leave some budget in ciphertexts you intend to share (see
+ * `Compiler::additional_noise_budget`). Smaller digits add less noise but
+ * make the key larger and re-encryption slower.
+ *
+ * The results' noise depends on the original ciphertexts. Delegatees can
+ * hide it from whoever they pass the ciphertexts to with
+ * [`GenericRuntime::rerandomize`](crate::GenericRuntime::rerandomize).
+ *
+ * Ciphertexts must be relinearized and not in NTT form, as FHE program
+ * outputs and fresh encryptions are.
+ */
+pub struct ReEncryptor {
+    params: Params,
+    decomposition_bits: u32,
+    tables: Vec<NttTables>,
+    // The index of each prime's first entry in keys.
+    offsets: Vec<usize>,
+    // The key entries' (c0, c1) blocks, transformed with tables.
+    keys: Vec<(Vec<Vec<u64>>, Vec<Vec<u64>>)>,
+}
+
+impl ReEncryptor {
+    /**
+     * Creates a re-encryptor that uses the given key.
+     *
+     * Returns [`Error::ParameterMismatch`] if the key is malformed, e.g.
+     * because it was deserialized from untrusted data.
+     */
+    pub fn new(key: &ReEncryptionKey) -> Result<Self> {
+        if !(1..=32).contains(&key.decomposition_bits) {
+            return Err(Error::ParameterMismatch);
+        }
+
+        let n = key.params.lattice_dimension as usize;
+        let len = n * key.moduli.len();
+
+        let expected_keys = key
+            .moduli
+            .iter()
+            .map(|q| digits(*q, key.decomposition_bits) as usize)
+            .sum::<usize>();
+
+        if !key.params.coeff_modulus.starts_with(&key.moduli)
+            || key.keys.len() != expected_keys
+            || key
+                .keys
+                .iter()
+                .any(|(c0, c1)| c0.len() != len || c1.len() != len)
+        {
+            return Err(Error::ParameterMismatch);
+        }
+
+        let tables = key
+            .moduli
+            .iter()
+            .map(|q| Ok(NttTables::new(n, *q)?))
+            .collect::<Result<Vec<_>>>()?;
+
+        let transform = |poly: &[u64]| {
+            poly.chunks(n)
+                .zip(tables.iter())
+                .map(|(block, tables)| {
+                    let mut block = block.to_owned();
+                    tables.forward(&mut block);
+                    block
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let keys = key
+            .keys
+            .iter()
+            .map(|(c0, c1)| (transform(c0), transform(c1)))
+            .collect();
+
+        let mut offset = 0;
+        let offsets = key
+            .moduli
+            .iter()
+            .map(|q| {
+                let o = offset;
+                offset += digits(*q, key.decomposition_bits) as usize;
+                o
+            })
+            .collect();
+
+        Ok(Self {
+            params: key.params.clone(),
+            decomposition_bits: key.decomposition_bits,
+            tables,
+            offsets,
+            keys,
+        })
+    }
+
+    /**
+     * Re-encrypts the given ciphertext.
+     *
+     * Returns [`Error::ParameterMismatch`] if the ciphertext wasn't
+     * encrypted under the key's parameters and [`Error::NotRelinearized`]
+     * if it has more than 2 polynomials or is in NTT form.
+     */
+    pub fn reencrypt(&self, ciphertext: &Ciphertext) -> Result<Ciphertext> {
+        let inner = match &ciphertext.inner {
+            InnerCiphertext::Seal(ciphertexts) => ciphertexts
+                .iter()
+                .map(|c| {
+                    if c.params != self.params {
+                        return Err(Error::ParameterMismatch);
+                    }
+
+                    Ok(WithContext {
+                        params: self.params.clone(),
+                        data: self.reencrypt_seal(&c.data)?,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?,
+        };
+
+        Ok(Ciphertext {
+            data_type: ciphertext.data_type.clone(),
+            inner: InnerCiphertext::Seal(inner),
+        })
+    }
+
+    /**
+     * Re-encrypts the given ciphertexts in parallel.
+     */
+    pub fn reencrypt_all(&self, ciphertexts: &[Ciphertext]) -> Result<Vec<Ciphertext>> {
+        ciphertexts.par_iter().map(|c| self.reencrypt(c)).collect()
+    }
+
+    /**
+     * Reads bincode-serialized [`Ciphertext`]s from `reader` until it's
+     * exhausted and writes their re-encryptions to `writer` in the same
+     * format and order. Returns the number of ciphertexts re-encrypted.
+     *
+     * # Remarks
+     * Only one ciphertext is held in memory at a time, so streams needn't
+     * fit in memory.
+     */
+    pub fn reencrypt_stream<R, W>(&self, mut reader: R, mut writer: W) -> Result<u64>
+    where
+        R: BufRead,
+        W: Write,
+    {
+        let mut count = 0;
+
+        while !reader.fill_buf().map_err(bincode::Error::from)?.is_empty() {
+            let ciphertext: Ciphertext = bincode::deserialize_from(&mut reader)?;
+
+            bincode::serialize_into(&mut writer, &self.reencrypt(&ciphertext)?)?;
+            count += 1;
+        }
+
+        writer.flush().map_err(bincode::Error::from)?;
+
+        Ok(count)
+    }
+
+    fn reencrypt_seal(&self, ciphertext: &SealCiphertext) -> Result<SealCiphertext> {
+        if ciphertext.num_polynomials() != 2 || ciphertext.is_ntt_form() {
+            return Err(Error::NotRelinearized);
+        }
+
+        // Modulus switching drops the last primes, so lower levels use a
+        // prefix of the key.
+        let levels = ciphertext.coeff_modulus_size() as usize;
+
+        if levels > self.tables.len() {
+            return Err(Error::ParameterMismatch);
+        }
+
+        let n = self.params.lattice_dimension as usize;
+        let w = self.decomposition_bits;
+        let mask = (1u64 << w) - 1;
+
+        let c0 = ciphertext.polynomial(0)?;
+        let c1 = ciphertext.polynomial(1)?;
+
+        let mut acc0 = vec![vec![0; n]; levels];
+        let mut acc1 = vec![vec![0; n]; levels];
+
+        for (j, tables_j) in self.tables[..levels].iter().enumerate() {
+            let block = &c1[j * n..(j + 1) * n];
+
+            for l in 0..digits(tables_j.modulus(), w) {
+                let (k0, k1) = &self.keys[self.offsets[j] + l as usize];
+
+                for (i, tables) in self.tables[..levels].iter().enumerate() {
+                    let q = tables.modulus();
+
+                    let mut digit = block
+                        .iter()
+                        .map(|c| ((c >> (w * l)) & mask) % q)
+                        .collect::<Vec<_>>();
+
+                    tables.forward(&mut digit);
+
+                    for (x, d) in digit.iter().enumerate() {
+                        acc0[i][x] = add_mod(acc0[i][x], mul_mod(*d, k0[i][x], q), q);
+                        acc1[i][x] = add_mod(acc1[i][x], mul_mod(*d, k1[i][x], q), q);
+                    }
+                }
+            }
+        }
+
+        let mut out0 = Vec::with_capacity(n * levels);
+        let mut out1 = Vec::with_capacity(n * levels);
+
+        for (i, tables) in self.tables[..levels].iter().enumerate() {
+            let q = tables.modulus();
+
+            tables.inverse(&mut acc0[i]);
+            tables.inverse(&mut acc1[i]);
+
+            out0.extend(
+                c0[i * n..(i + 1) * n]
+                    .iter()
+                    .zip(acc0[i].iter())
+                    .map(|(c, a)| add_mod(*c, *a, q)),
+            );
+            out1.extend_from_slice(&acc1[i]);
+        }
+
+        let mut result = ciphertext.clone();
+
+        result.set_polynomial(0, &out0)?;
+        result.set_polynomial(1, &out1)?;
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::VecDeque;
+
+use rayon::prelude::*;
+
+use crate::{marker, Ciphertext, Error, GenericRuntime, PrivateKey, PublicKey, Result};
+
+/**
+ * Moves stored ciphertexts from an old key pair to a new one, e.g. for
+ * scheduled key rotation.
+ *
+ * # Remarks
+ * This decrypts each ciphertext with the old private key and encrypts the
+ * value under the new public key (see [`GenericRuntime::reencrypt`]). It
+ * therefore needs the old private key and must run wherever that key is
+ * held, not on an untrusted server. Intermediate plaintexts are zeroed
+ * once re-encrypted, and the results have fresh noise. To switch keys on
+ * an untrusted server instead, use a [`ReEncryptor`](crate::ReEncryptor).
+ *
+ * Ciphertexts are re-encrypted in parallel.
+ * [`reencrypt_stream`](Self::reencrypt_stream) works through an archive in
+ * batches, so archives needn't fit in memory.
+ */
+pub struct KeyRotation<'a, T, B> {
+    runtime: &'a GenericRuntime<T, B>,
+    old_private_key: &'a PrivateKey,
+    new_public_key: &'a PublicKey,
+}
+
+impl<'a, T, B> Clone for KeyRotation<'a, T, B> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T, B> Copy for KeyRotation<'a, T, B> {}
+
+impl<'a, T, B> KeyRotation<'a, T, B>
+where
+    T: marker::Fhe + Sync,
+    B: Sync,
+{
+    /**
+     * Creates a helper that re-encrypts ciphertexts under `old_private_key`
+     * to `new_public_key`.
+     *
+     * Returns [`Error::KeyParameterMismatch`] if either key wasn't
+     * generated for the runtime's parameters.
+     */
+    pub fn new(
+        runtime: &'a GenericRuntime<T, B>,
+        old_private_key: &'a PrivateKey,
+        new_public_key: &'a PublicKey,
+    ) -> Result<Self> {
+        let params = runtime.params();
+
+        if old_private_key.0.expose_secret().params != *params
+            || new_public_key.public_key.params != *params
+        {
+            return Err(Error::KeyParameterMismatch);
+        }
+
+        Ok(Self {
+            runtime,
+            old_private_key,
+            new_public_key,
+        })
+    }
+
+    /**
+     * Re-encrypts a single ciphertext under the new key pair.
+     */
+    pub fn reencrypt(&self, ciphertext: &Ciphertext) -> Result<Ciphertext> {
+        self.runtime
+            .reencrypt(ciphertext, self.old_private_key, self.new_public_key)
+    }
+
+    /**
+     * Re-encrypts the given ciphertexts in parallel, returning them in the
+     * same order. Fails if any ciphertext fails.
+     */
+    pub fn reencrypt_all(&self, ciphertexts: &[Ciphertext]) -> Result<Vec<Ciphertext>> {
+        ciphertexts.par_iter().map(|c| self.reencrypt(c)).collect()
+    }
+
+    /**
+     * Lazily re-encrypts the given ciphertexts, taking `batch_size` at a
+     * time from the source and re-encrypting each batch in parallel.
+     *
+     * # Remarks
+     * Results are yielded in the source's order, one per ciphertext, so a
+     * caller can write each one back as it arrives. A failure only affects
+     * the corresponding result. At most `batch_size` ciphertexts and their
+     * results are held in memory at once.
+     */
+    pub fn reencrypt_stream<I>(
+        self,
+        ciphertexts: I,
+        batch_size: usize,
+    ) -> impl Iterator<Item = Result<Ciphertext>> + 'a
+    where
+        I: IntoIterator<Item = Ciphertext>,
+        I::IntoIter: 'a,
+        T: 'a,
+        B: 'a,
+    {
+        let mut ciphertexts = ciphertexts.into_iter();
+        let batch_size = batch_size.max(1);
+        let mut ready = VecDeque::with_capacity(batch_size);
+
+        std::iter::from_fn(move || {
+            if ready.is_empty() {
+                let batch = ciphertexts.by_ref().take(batch_size).collect::<Vec<_>>();
+
+                ready.extend(
+                    batch
+                        .par_iter()
+                        .map(|c| self.reencrypt(c))
+                        .collect::<Vec<_>>(),
+                );
+            }
+
+            ready.pop_front()
+        })
+    }
+}
@@ -1,3 +1,4 @@
+use crate::scheduler::Execution;
 use crate::{InnerPlaintext, SealData};
 use static_assertions::const_assert;
 use sunscreen_compiler_common::{GraphQuery, GraphQueryError};
@@ -66,6 +67,12 @@ pub enum FheProgramRunFailure {
     #[error("Internal error: missing data")]
     MissingData,
 
+    /**
+     * Evaluation was cancelled before the program completed.
+     */
+    #[error("Cancelled")]
+    Cancelled,
+
     /**
      * An error occurred when trying to query the graph.
      */
@@ -103,6 +110,35 @@ pub unsafe fn run_program_unchecked<E: Evaluator + Sync + Send>(
     evaluator: &E,
     relin_keys: &Option<&RelinearizationKeys>,
     galois_keys: &Option<&GaloisKeys>,
+) -> Result<Vec<Ciphertext>, FheProgramRunFailure> {
+    run_program_unchecked_cancellable(
+        ir,
+        inputs,
+        evaluator,
+        relin_keys,
+        galois_keys,
+        &|| false,
+        None,
+    )
+}
+
+/**
+ * Runs the given program as [`run_program_unchecked`] does, returning
+ * [`FheProgramRunFailure::Cancelled`] as soon as `is_cancelled` returns
+ * true. It's polled before each operation. If given an `execution`, each
+ * operation runs in one of its scheduler's slices.
+ *
+ * # Safety
+ * See [`run_program_unchecked`].
+ */
+pub(crate) unsafe fn run_program_unchecked_cancellable<E: Evaluator + Sync + Send>(
+    ir: &FheProgram,
+    inputs: &[SealData],
+    evaluator: &E,
+    relin_keys: &Option<&RelinearizationKeys>,
+    galois_keys: &Option<&GaloisKeys>,
+    is_cancelled: &(dyn Fn() -> bool + Sync),
+    execution: Option<&Execution>,
 ) -> Result<Vec<Ciphertext>, FheProgramRunFailure> {
     fn get_data(
         data: &[AtomicCell<Option<Arc<SealData>>>],
@@ -150,8 +186,8 @@ pub unsafe fn run_program_unchecked<E: Evaluator + Sync + Send>(
 
     let inputs = inputs
         .iter()
-        .map(|v| Arc::new(v.clone()))
-        .collect::<Vec<Arc<SealData>>>();
+        .map(|v| v.try_clone().map(Arc::new))
+        .collect::<Result<Vec<Arc<SealData>>, _>>()?;
 
     for _ in 0..ir.graph.node_count() {
         data.push(AtomicCell::new(None));
@@ -160,6 +196,12 @@ pub unsafe fn run_program_unchecked<E: Evaluator + Sync + Send>(
     traverse(
         ir,
         |index| {
+            let _slice = execution.map(|e| e.slice());
+
+            if is_cancelled() {
+                return Err(FheProgramRunFailure::Cancelled);
+            }
+
             let node = &ir.graph[index];
             let query = GraphQuery::new(&ir.graph.0);
 
@@ -315,13 +357,14 @@ pub unsafe fn run_program_unchecked<E: Evaluator + Sync + Send>(
                             .map_err(|_| FheProgramRunFailure::MalformedPlaintext)?;
 
                         match p {
-                            InnerPlaintext::Seal(p) => {
+                            InnerPlaintext::Seal(mut p) => {
                                 // Plaintext literals should always have exactly one plaintext.
                                 if p.len() != 1 {
                                     return Err(FheProgramRunFailure::MalformedPlaintext);
                                 }
 
-                                data[index.index()].store(Some(Arc::new(p[0].data.clone().into())))
+                                data[index.index()]
+                                    .store(Some(Arc::new(p.swap_remove(0).data.into())))
                             }
                         };
                     }
@@ -350,8 +393,8 @@ pub unsafe fn run_program_unchecked<E: Evaluator + Sync + Send>(
         })
         .collect::<Result<Vec<&Ciphertext>, FheProgramRunFailure>>()?
         .drain(0..)
-        .map(|c| c.to_owned())
-        .collect();
+        .map(|c| c.try_clone())
+        .collect::<Result<Vec<Ciphertext>, _>>()?;
 
     Ok(output)
 }
@@ -562,8 +605,8 @@ mod tests {
         let context = Context::new(&params, true, SecurityLevel::default()).unwrap();
 
         let keygen = KeyGenerator::new(&context).unwrap();
-        let public_key = keygen.create_public_key();
-        let private_key = keygen.secret_key();
+        let public_key = keygen.create_public_key().unwrap();
+        let private_key = keygen.secret_key().unwrap();
 
         let encryptor =
             Encryptor::with_public_and_secret_key(&context, &public_key, &private_key).unwrap();
@@ -1,34 +1,170 @@
+#[cfg(feature = "fhe")]
+use std::collections::HashMap;
+#[cfg(feature = "fhe")]
+use std::io::{Read, Seek};
 use std::marker::PhantomData;
+#[cfg(feature = "fhe")]
+use std::ops::Range;
+#[cfg(feature = "fhe")]
+use std::sync::{Mutex, MutexGuard};
+#[cfg(any(feature = "zkp", feature = "metrics"))]
 use std::time::Instant;
 
+#[cfg(any(feature = "fhe", feature = "zkp"))]
 use crate::error::*;
+#[cfg(feature = "fhe")]
 use crate::metadata::*;
+#[cfg(feature = "fhe")]
+use crate::options::RunOptions;
+#[cfg(feature = "fhe")]
+use crate::policy::authorize;
+#[cfg(feature = "fhe")]
+use crate::proxy::{ReEncryptionKey, DEFAULT_DECOMPOSITION_BITS};
+#[cfg(feature = "fhe")]
+use crate::run::run_program_unchecked_cancellable;
+#[cfg(feature = "fhe")]
+use crate::snapshot::{program_hash, KeySetFingerprint, Registry};
+#[cfg(feature = "zkp")]
 use crate::ZkpProgramInput;
+#[cfg(feature = "fhe")]
 use crate::{
-    run_program_unchecked, serialization::WithContext, Ciphertext, FheProgramInput,
-    InnerCiphertext, InnerPlaintext, Plaintext, PrivateKey, PublicKey, SealCiphertext, SealData,
-    SealPlaintext, TryFromPlaintext, TryIntoPlaintext, TypeNameInstance,
+    serialization::WithContext, Ciphertext, FheProgramInput, FheProgramRunFailure, InnerCiphertext,
+    InnerPlaintext, JsonInput, JsonTypes, Plaintext, PrivateKey, PublicKey, SealCiphertext,
+    SealData, SealPlaintext, Secret, TryFromPlaintext, TryIntoPlaintext, TypeNameInstance,
 };
 
+#[cfg(feature = "zkp")]
 use log::trace;
+#[cfg(feature = "fhe")]
+use rayon::prelude::*;
+#[cfg(feature = "fhe")]
+use sunscreen_compiler_common::GraphQuery;
+#[cfg(feature = "fhe")]
 use sunscreen_fhe_program::FheProgramTrait;
+#[cfg(feature = "fhe")]
 use sunscreen_fhe_program::SchemeType;
+#[cfg(feature = "fhe")]
+use sunscreen_fhe_program::{FheProgram, Literal, Operation};
 
+#[cfg(feature = "metrics")]
+use seal_fhe::ProfilingEvaluator;
+#[cfg(feature = "fhe")]
 use seal_fhe::{
-    BFVEvaluator, BfvEncryptionParametersBuilder, Context as SealContext, Decryptor, Encryptor,
-    KeyGenerator, Modulus,
+    BFVEncoder, BFVEvaluator, BfvEncryptionParametersBuilder, Context as SealContext, Decryptor,
+    Encryptor, Evaluator, GaloisKeyIndex, GaloisKeys, KeyGenerator, Modulus,
+    SecretKey as SealSecretKey,
 };
 
 pub use sunscreen_compiler_common::{Type, TypeName};
-use sunscreen_zkp_backend::BigInt;
-use sunscreen_zkp_backend::CompiledZkpProgram;
-use sunscreen_zkp_backend::Proof;
-use sunscreen_zkp_backend::ZkpBackend;
+#[cfg(feature = "zkp")]
+use sunscreen_zkp_backend::{BigInt, CompiledZkpProgram, Proof, ZkpBackend};
 
+#[cfg(feature = "fhe")]
 enum Context {
     Seal(SealContext),
 }
 
+#[cfg(feature = "fhe")]
+/**
+ * Modulus switches every ciphertext in `inputs` down to the lowest level
+ * among them.
+ *
+ * # Remarks
+ * Programs run on whatever level their inputs arrive at, and inputs that
+ * were already modulus switched (e.g. to shrink them in transit) are faster
+ * to compute on. However, SEAL requires both operands of a binary operation
+ * to be at the same level, so mixing levels would otherwise fail partway
+ * through the program.
+ */
+fn align_levels<E>(context: &SealContext, inputs: &mut [SealData], evaluator: &E) -> Result<()>
+where
+    E: Evaluator,
+{
+    let mut min_level = None;
+
+    for i in inputs.iter() {
+        if let SealData::Ciphertext(c) = i {
+            let level = context.get_chain_index(&c.get_parms_id())?;
+
+            min_level = Some(min_level.map_or(level, |m: u64| m.min(level)));
+        }
+    }
+
+    let min_level = match min_level {
+        Some(l) => l,
+        None => return Ok(()),
+    };
+
+    for i in inputs.iter_mut() {
+        if let SealData::Ciphertext(c) = i {
+            while context.get_chain_index(&c.get_parms_id())? > min_level {
+                evaluator.mod_switch_to_next_inplace(c)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "fhe")]
+/**
+ * Creates a decryptor for ciphertexts at the level with the given
+ * parms_id.
+ *
+ * # Remarks
+ * A context created without expanding the modulus switching chain lacks
+ * the levels of modulus switched ciphertexts, so those are decrypted
+ * under an auxiliary context for just their level instead.
+ */
+fn level_decryptor(
+    context: &SealContext,
+    secret_key: &SealSecretKey,
+    parms_id: &[u64; 4],
+) -> Result<Decryptor> {
+    if context.get_chain_index(parms_id).is_ok() {
+        return Ok(Decryptor::new(context, secret_key)?);
+    }
+
+    let level_context = context.at_level(parms_id)?;
+    let level_key = secret_key.at_level(context, &level_context)?;
+
+    Ok(Decryptor::new(&level_context, &level_key)?)
+}
+
+#[cfg(feature = "fhe")]
+/**
+ * Returns the row rotation steps the given program performs, where positive
+ * steps rotate left, negative steps rotate right and 0 swaps rows.
+ */
+pub(crate) fn required_rotations(program: &FheProgram) -> Vec<i32> {
+    let graph = &program.graph.0;
+    let query = GraphQuery::new(graph);
+
+    let mut steps = graph
+        .node_indices()
+        .filter_map(|index| {
+            let sign = match graph[index].operation {
+                Operation::ShiftLeft => 1,
+                Operation::ShiftRight => -1,
+                Operation::SwapRows => return Some(0),
+                _ => return None,
+            };
+
+            let (_, right) = query.get_binary_operands(index).ok()?;
+
+            match graph[right].operation {
+                Operation::Literal(Literal::U64(v)) => Some(sign * v as i32),
+                _ => None,
+            }
+        })
+        .collect::<Vec<i32>>();
+
+    steps.sort_unstable();
+    steps.dedup();
+
+    steps
+}
+
 /**
  * Marker traits.
  */
@@ -65,19 +201,27 @@ pub struct FheZkp {}
 impl marker::Fhe for FheZkp {}
 impl marker::Zkp for FheZkp {}
 
+#[cfg(feature = "fhe")]
 struct FheRuntimeData {
     params: Params,
     context: Context,
+    expand_mod_chain: bool,
+    registry: Mutex<Registry>,
 }
 
+#[cfg(feature = "zkp")]
 struct ZkpRuntimeData;
 
 enum RuntimeData {
+    #[cfg(feature = "fhe")]
     Fhe(FheRuntimeData),
+    #[cfg(feature = "zkp")]
     Zkp(ZkpRuntimeData),
+    #[cfg(all(feature = "fhe", feature = "zkp"))]
     FheZkp(FheRuntimeData, ZkpRuntimeData),
 }
 
+#[cfg(feature = "fhe")]
 impl RuntimeData {
     /**
      * Gets the inner Fhe's runtime data or panics if this value isn't
@@ -89,7 +233,9 @@ impl RuntimeData {
     fn unwrap_fhe(&self) -> &FheRuntimeData {
         match self {
             Self::Fhe(x) => x,
+            #[cfg(feature = "zkp")]
             Self::FheZkp(x, _) => x,
+            #[cfg(feature = "zkp")]
             _ => panic!("Expected RuntimeData::Fhe or RuntimeData::FheZkp."),
         }
     }
@@ -103,11 +249,14 @@ impl RuntimeData {
  *
  */
 pub struct GenericRuntime<T, B> {
+    #[cfg_attr(not(feature = "fhe"), allow(dead_code))]
     runtime_data: RuntimeData,
     _phantom_t: PhantomData<T>,
+    #[cfg_attr(not(feature = "zkp"), allow(dead_code))]
     zkp_backend: B,
 }
 
+#[cfg(feature = "fhe")]
 impl<T, B> GenericRuntime<T, B>
 where
     T: self::marker::Fhe,
@@ -132,7 +281,12 @@ where
 
         let val = match (&fhe_data.context, &ciphertext.inner) {
             (Context::Seal(context), InnerCiphertext::Seal(ciphertexts)) => {
-                let decryptor = Decryptor::new(context, &private_key.0)?;
+                let parms_id = match ciphertexts.first() {
+                    Some(c) => c.data.get_parms_id(),
+                    None => context.get_first_parms_id()?,
+                };
+
+                let decryptor = level_decryptor(context, private_key.0.expose_secret(), &parms_id)?;
 
                 let plaintexts = ciphertexts
                     .iter()
@@ -168,6 +322,218 @@ where
         Ok(val)
     }
 
+    /**
+     * Decrypts an encrypted validity flag and, if it's nonzero, the
+     * value it guards. Returns `None` when the flag encrypts 0.
+     *
+     * # Remarks
+     * FHE programs can't branch on encrypted data, so programs that may
+     * fail (e.g. on out-of-range inputs) instead return an encrypted flag
+     * alongside their result. The program computes both regardless of the
+     * inputs, so the server learns nothing about whether the computation
+     * succeeded. Only the holder of the private key can interpret the flag.
+     */
+    pub fn decrypt_flagged<P, F>(
+        &self,
+        value: &Ciphertext,
+        flag: &Ciphertext,
+        private_key: &PrivateKey,
+    ) -> Result<Option<P>>
+    where
+        P: TryFromPlaintext + TypeName,
+        F: TryFromPlaintext + TypeName + Into<i64>,
+    {
+        let flag: i64 = self.decrypt::<F>(flag, private_key)?.into();
+
+        if flag == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(self.decrypt(value, private_key)?))
+    }
+
+    /**
+     * Decrypts all of an FHE program's outputs at once, returning one
+     * plaintext per output typed as the program declares. Convert each
+     * with [`decode`](Self::decode).
+     *
+     * # Remarks
+     * Unlike calling [`decrypt`](Self::decrypt) per output, this checks
+     * the key, parameters and output types once up front, then decrypts
+     * every underlying ciphertext in parallel, sharing one decryptor per
+     * level of the modulus switching chain.
+     *
+     * Returns [`Error::KeyParameterMismatch`] or
+     * [`Error::ParameterMismatch`] if the key or ciphertexts don't match
+     * the runtime's parameters, [`Error::IncorrectCiphertextCount`] if
+     * `outputs` has a different number of values than the program
+     * returns, and [`Error::TypeMismatch`] for the first output whose
+     * type differs from the declared one.
+     */
+    pub fn decrypt_all(
+        &self,
+        fhe_program: &CompiledFheProgram,
+        outputs: &[Ciphertext],
+        private_key: &PrivateKey,
+    ) -> Result<Vec<Plaintext>> {
+        let fhe_data = self.runtime_data.unwrap_fhe();
+
+        if fhe_program.metadata.params != fhe_data.params {
+            return Err(Error::ParameterMismatch);
+        }
+
+        if private_key.0.expose_secret().params != fhe_data.params {
+            return Err(Error::KeyParameterMismatch);
+        }
+
+        let returns = &fhe_program.metadata.signature.returns;
+
+        if returns.len() != outputs.len() {
+            return Err(Error::IncorrectCiphertextCount);
+        }
+
+        for (expected, c) in returns.iter().zip(outputs) {
+            if *expected != c.data_type {
+                return Err(Error::type_mismatch(expected, &c.data_type));
+            }
+        }
+
+        match &fhe_data.context {
+            Context::Seal(context) => {
+                let ciphertexts = outputs
+                    .iter()
+                    .map(|c| match &c.inner {
+                        InnerCiphertext::Seal(c) => c,
+                    })
+                    .collect::<Vec<_>>();
+
+                if ciphertexts
+                    .iter()
+                    .any(|c| c.iter().any(|c| c.params != fhe_data.params))
+                {
+                    return Err(Error::ParameterMismatch);
+                }
+
+                let mut decryptors = HashMap::new();
+
+                for c in ciphertexts.iter().flat_map(|c| c.iter()) {
+                    let parms_id = c.data.get_parms_id();
+
+                    if !decryptors.contains_key(&parms_id) {
+                        let decryptor =
+                            level_decryptor(context, private_key.0.expose_secret(), &parms_id)?;
+
+                        decryptors.insert(parms_id, decryptor);
+                    }
+                }
+
+                let mut plaintexts = ciphertexts
+                    .iter()
+                    .flat_map(|c| c.iter())
+                    .collect::<Vec<_>>()
+                    .par_iter()
+                    .map(|c| {
+                        let decryptor = &decryptors[&c.data.get_parms_id()];
+
+                        if decryptor.invariant_noise_budget(c)? == 0 {
+                            return Err(Error::TooMuchNoise);
+                        }
+
+                        Ok(WithContext {
+                            params: fhe_data.params.clone(),
+                            data: decryptor.decrypt(c)?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?
+                    .into_iter();
+
+                Ok(outputs
+                    .iter()
+                    .zip(ciphertexts)
+                    .map(|(output, c)| Plaintext {
+                        data_type: Type {
+                            is_encrypted: false,
+                            ..output.data_type.clone()
+                        },
+                        inner: InnerPlaintext::Seal(plaintexts.by_ref().take(c.len()).collect()),
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    /**
+     * Converts a plaintext, e.g. one returned by
+     * [`decrypt_all`](Self::decrypt_all), into the type P.
+     *
+     * Returns [`Error::TypeMismatch`] if the plaintext holds a different
+     * type.
+     */
+    pub fn decode<P>(&self, plaintext: &Plaintext) -> Result<P>
+    where
+        P: TryFromPlaintext + TypeName,
+    {
+        let expected_type = P::type_name();
+
+        if expected_type != plaintext.data_type {
+            return Err(Error::type_mismatch(&expected_type, &plaintext.data_type));
+        }
+
+        P::try_from_plaintext(plaintext, &self.runtime_data.unwrap_fhe().params)
+    }
+
+    /**
+     * Decrypts the given ciphertext and decodes only the batching slots
+     * in `range` into `out`.
+     *
+     * # Remarks
+     * Use this rather than [`decrypt`](Self::decrypt) when a `Batched`
+     * result holds thousands of lanes but you only need a few of them.
+     * Slots are indexed as the underlying SEAL batch encoder lays them out:
+     * the first row occupies slots `0..N/2` and the second row `N/2..N`,
+     * where `N` is the lattice dimension.
+     *
+     * The ciphertext must contain exactly one underlying ciphertext.
+     */
+    pub fn decrypt_signed_range(
+        &self,
+        ciphertext: &Ciphertext,
+        private_key: &PrivateKey,
+        range: Range<usize>,
+        out: &mut [i64],
+    ) -> Result<()> {
+        let fhe_data = self.runtime_data.unwrap_fhe();
+
+        match (&fhe_data.context, &ciphertext.inner) {
+            (Context::Seal(context), InnerCiphertext::Seal(ciphertexts)) => {
+                if ciphertexts.len() != 1 {
+                    return Err(Error::IncorrectCiphertextCount);
+                }
+
+                let c = &ciphertexts[0];
+
+                if c.params != fhe_data.params {
+                    return Err(Error::ParameterMismatch);
+                }
+
+                let decryptor = level_decryptor(
+                    context,
+                    private_key.0.expose_secret(),
+                    &c.data.get_parms_id(),
+                )?;
+                let encoder = BFVEncoder::new(context)?;
+
+                if decryptor.invariant_noise_budget(c)? == 0 {
+                    return Err(Error::TooMuchNoise);
+                }
+
+                decryptor.decrypt_decode_signed_range(&encoder, c, range, out)?;
+            }
+        };
+
+        Ok(())
+    }
+
     /**
      * Returns the amount of noise budget (in bits) remaining in the
      * given ciphertext.
@@ -183,17 +549,50 @@ where
 
         match (&fhe_data.context, &c.inner) {
             (Context::Seal(ctx), InnerCiphertext::Seal(ciphertexts)) => {
-                let decryptor = Decryptor::new(ctx, &private_key.0)?;
+                let private_key = private_key.0.expose_secret();
 
                 Ok(ciphertexts
                     .iter()
                     .fold(Ok(u32::MAX), |min: Result<u32>, c| {
+                        let decryptor = level_decryptor(ctx, private_key, &c.data.get_parms_id())?;
+
                         Ok(u32::min(min?, decryptor.invariant_noise_budget(&c.data)?))
                     })?)
             }
         }
     }
 
+    /**
+     * Returns the invariant noise in the given ciphertext, i.e. the
+     * infinity norm of its invariant noise polynomial. The ciphertext
+     * decrypts correctly while this is less than 1/2.
+     *
+     * # Remarks
+     * Unlike [`measure_noise_budget`](Self::measure_noise_budget), which
+     * rounds down to whole bits, this returns the raw value, which is useful
+     * for collecting fine-grained noise growth statistics.
+     *
+     * Internally, the [`Ciphertext`] object may contain more
+     * than one ciphertext. This function returns the
+     * *maximum* invariant noise of all the enclosed
+     * ciphertexts.
+     */
+    pub fn measure_invariant_noise(&self, c: &Ciphertext, private_key: &PrivateKey) -> Result<f64> {
+        let fhe_data = self.runtime_data.unwrap_fhe();
+
+        match (&fhe_data.context, &c.inner) {
+            (Context::Seal(ctx), InnerCiphertext::Seal(ciphertexts)) => {
+                let private_key = private_key.0.expose_secret();
+
+                Ok(ciphertexts.iter().fold(Ok(0f64), |max: Result<f64>, c| {
+                    let decryptor = level_decryptor(ctx, private_key, &c.data.get_parms_id())?;
+
+                    Ok(f64::max(max?, decryptor.invariant_noise(&c.data)?))
+                })?)
+            }
+        }
+    }
+
     /**
      * Generates a tuple of public/private keys for the encapsulated scheme and parameters.
      *
@@ -206,13 +605,35 @@ where
      * See [`PublicKey`] for more information.
      */
     pub fn generate_keys(&self) -> Result<(PublicKey, PrivateKey)> {
+        self.generate_keys_with_galois(|keygen| Ok(keygen.create_galois_keys().ok()))
+    }
+
+    /**
+     * Like [`generate_keys`](Self::generate_keys), but calls `galois` to
+     * create the Galois keys rather than generating keys for every
+     * power-of-two rotation.
+     *
+     * # Remarks
+     * `galois` receives the generator of the returned private key. Return
+     * keys for just the rotations your programs perform (e.g. those from
+     * `Application::generate_minimal_galois_keys`) to save time and space,
+     * or [`None`] if no program rotates.
+     */
+    pub fn generate_keys_with_galois<F, E>(
+        &self,
+        galois: F,
+    ) -> std::result::Result<(PublicKey, PrivateKey), E>
+    where
+        F: FnOnce(&KeyGenerator) -> std::result::Result<Option<GaloisKeys>, E>,
+        E: From<Error>,
+    {
         let fhe_data = self.runtime_data.unwrap_fhe();
 
         let keys = match &fhe_data.context {
             Context::Seal(context) => {
-                let keygen = KeyGenerator::new(context)?;
+                let keygen = KeyGenerator::new(context).map_err(Error::from)?;
 
-                let galois_keys = keygen.create_galois_keys().ok().map(|v| WithContext {
+                let galois_keys = galois(&keygen)?.map(|v| WithContext {
                     params: fhe_data.params.clone(),
                     data: v,
                 });
@@ -228,15 +649,15 @@ where
                 let public_keys = PublicKey {
                     public_key: WithContext {
                         params: fhe_data.params.clone(),
-                        data: keygen.create_public_key(),
+                        data: keygen.create_public_key().map_err(Error::from)?,
                     },
                     galois_key: galois_keys,
                     relin_key: relin_keys,
                 };
-                let private_key = PrivateKey(WithContext {
+                let private_key = PrivateKey(Secret::new(WithContext {
                     params: fhe_data.params.clone(),
-                    data: keygen.secret_key(),
-                });
+                    data: keygen.secret_key().map_err(Error::from)?,
+                }));
 
                 (public_keys, private_key)
             }
@@ -245,6 +666,59 @@ where
         Ok(keys)
     }
 
+    /**
+     * Loads just the Galois keys `fhe_program` rotates with from an
+     * archive written by [`GaloisKeyIndex::write`], whose header `index`
+     * was read from `reader`. Put the result in a [`PublicKey`]'s
+     * `galois_key` to run the program.
+     *
+     * # Remarks
+     * This avoids holding the full set of Galois keys in memory, which
+     * can run to tens of megabytes, when serving programs that each
+     * rotate by a few steps. Returns [`None`] if the program doesn't
+     * rotate.
+     *
+     * Returns [`Error::ParameterMismatch`] if the program wasn't compiled
+     * for the runtime's parameters and [`Error::MissingRotationKey`] if
+     * the archive can't perform one of its rotations.
+     */
+    pub fn load_galois_keys<R>(
+        &self,
+        fhe_program: &CompiledFheProgram,
+        index: &GaloisKeyIndex,
+        reader: &mut R,
+    ) -> Result<Option<WithContext<GaloisKeys>>>
+    where
+        R: Read + Seek,
+    {
+        let fhe_data = self.runtime_data.unwrap_fhe();
+
+        if fhe_program.metadata.params != fhe_data.params {
+            return Err(Error::ParameterMismatch);
+        }
+
+        let steps = fhe_program.rotation_steps();
+
+        if steps.is_empty() {
+            return Ok(None);
+        }
+
+        let poly_degree = fhe_data.params.lattice_dimension;
+
+        for step in &steps {
+            if !index.can_rotate_rows(*step, poly_degree)? {
+                return Err(Error::MissingRotationKey(*step));
+            }
+        }
+
+        match &fhe_data.context {
+            Context::Seal(context) => Ok(Some(WithContext {
+                params: fhe_data.params.clone(),
+                data: index.load_for_steps(context, reader, &steps, poly_degree)?,
+            })),
+        }
+    }
+
     /**
      * Returns the metadata for this runtime's associated FHE program.
      */
@@ -255,83 +729,297 @@ where
     }
 
     /**
-     * Validates and runs the given FHE program. Unless you can guarantee your FHE program is valid,
-     * you should use this method rather than [`run_program_unchecked`].
+     * Returns the state needed to recreate this runtime with
+     * [`Runtime::import_state`]. See [`RuntimeState`].
      */
-    pub fn run<I>(
-        &self,
-        fhe_program: &CompiledFheProgram,
-        mut arguments: Vec<I>,
-        public_key: &PublicKey,
-    ) -> Result<Vec<Ciphertext>>
-    where
-        I: Into<FheProgramInput>,
-    {
-        // We're going to call run_program_unchecked, which
-        // can result in undefined behavior, non-termination,
-        // or panics on malformed programs. Since this method is safe,
-        // it must guard against calling run_program_unchecked with
-        // inputs that result in undefined behavior.
-        fhe_program.fhe_program_fn.validate()?;
-
-        // Aside from FHE program correctness, check that the required keys are given.
-        if public_key.relin_key.is_none() && fhe_program.fhe_program_fn.requires_relin_keys() {
-            return Err(Error::MissingRelinearizationKeys);
+    pub fn export_state(&self) -> RuntimeState {
+        let data = self.runtime_data.unwrap_fhe();
+        let registry = self.registry();
+
+        RuntimeState {
+            version: RuntimeState::CURRENT_VERSION,
+            params: data.params.clone(),
+            expand_mod_chain: data.expand_mod_chain,
+            programs: registry.programs.clone(),
+            key_sets: registry.key_sets.clone(),
         }
+    }
 
-        if public_key.galois_key.is_none() && fhe_program.fhe_program_fn.requires_galois_keys() {
-            return Err(Error::MissingGaloisKeys);
+    /**
+     * Records a hash of `program` under `name` in this runtime's
+     * [`RuntimeState`], replacing any program registered under the same
+     * name.
+     *
+     * Returns [`Error::ProgramParameterMismatch`] if `program` was
+     * compiled for other parameters than this runtime's.
+     */
+    pub fn register_program(&self, name: &str, program: &CompiledFheProgram) -> Result<()> {
+        if program.metadata.params != self.runtime_data.unwrap_fhe().params {
+            return Err(Error::ProgramParameterMismatch);
         }
 
-        let mut arguments: Vec<FheProgramInput> = arguments.drain(0..).map(|a| a.into()).collect();
+        let hash = program_hash(program)?;
 
-        let expected_args = &fhe_program.metadata.signature.arguments;
+        self.registry().programs.insert(name.to_owned(), hash);
 
-        // Check the arguments match the signature.
-        if expected_args.len() != arguments.len() {
-            return Err(Error::IncorrectCiphertextCount);
-        }
+        Ok(())
+    }
 
-        // Check the passed arguments' types match the signature.
-        if arguments
-            .iter()
-            .enumerate()
-            .any(|(i, a)| a.type_name_instance() != expected_args[i])
-        {
-            return Err(Error::argument_mismatch(
-                expected_args,
-                &arguments
-                    .iter()
-                    .map(|a| a.type_name_instance())
-                    .collect::<Vec<Type>>(),
-            ));
+    /**
+     * Records the fingerprints of `keys` under `id` in this runtime's
+     * [`RuntimeState`], replacing any key set registered under the same
+     * id, and returns them.
+     *
+     * Returns [`Error::KeyParameterMismatch`] if `keys` were generated for
+     * other parameters than this runtime's.
+     */
+    pub fn register_keys(&self, id: &str, keys: &PublicKey) -> Result<KeySetFingerprint> {
+        if keys.public_key.params != self.runtime_data.unwrap_fhe().params {
+            return Err(Error::KeyParameterMismatch);
         }
 
-        if fhe_program.metadata.signature.num_ciphertexts.len()
-            != fhe_program.metadata.signature.returns.len()
-        {
-            return Err(Error::ReturnTypeMetadataError);
-        }
+        let fingerprint = KeySetFingerprint::of(keys)?;
 
-        let fhe_data = self.runtime_data.unwrap_fhe();
+        self.registry().key_sets.insert(id.to_owned(), fingerprint);
 
-        match &fhe_data.context {
-            Context::Seal(context) => {
-                let evaluator = BFVEvaluator::new(context)?;
+        Ok(fingerprint)
+    }
 
-                let mut inputs: Vec<SealData> = vec![];
+    fn registry(&self) -> MutexGuard<'_, Registry> {
+        self.runtime_data
+            .unwrap_fhe()
+            .registry
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
 
-                for i in arguments.drain(0..) {
-                    match i {
-                        FheProgramInput::Ciphertext(c) => match c.inner {
-                            InnerCiphertext::Seal(mut c) => {
-                                for j in c.drain(0..) {
-                                    inputs.push(SealData::Ciphertext(j.data));
-                                }
-                            }
-                        },
-                        FheProgramInput::Plaintext(p) => {
-                            let p = p.try_into_plaintext(&fhe_data.params)?;
+    /**
+     * Validates and runs the given FHE program. Unless you can guarantee your FHE program is valid,
+     * you should use this method rather than [`run_program_unchecked`](crate::run_program_unchecked).
+     *
+     * # Remarks
+     * The program runs at the level of the modulus switching chain its ciphertext arguments
+     * arrive at, so modulus switched arguments take a faster path. If the arguments are at
+     * different levels, they're all switched down to the lowest one first. Each level down
+     * leaves less noise budget for the computation, so results computed on switched arguments
+     * may fail to decrypt where fresh arguments would succeed.
+     */
+    pub fn run<I>(
+        &self,
+        fhe_program: &CompiledFheProgram,
+        arguments: Vec<I>,
+        public_key: &PublicKey,
+    ) -> Result<Vec<Ciphertext>>
+    where
+        I: Into<FheProgramInput>,
+    {
+        self.run_with_options(fhe_program, arguments, public_key, &RunOptions::default())
+    }
+
+    /**
+     * Validates and runs the given FHE program, performing each operation
+     * with the given [`Evaluator`] rather than SEAL's CPU evaluator.
+     *
+     * # Remarks
+     * This allows dropping in alternative evaluator implementations (e.g.
+     * ones that offload operations to an accelerator) without changing how
+     * programs get validated, marshalled and packed. The evaluator operates
+     * on SEAL ciphertexts and plaintexts and must have been created for
+     * this runtime's parameters.
+     *
+     * To use the Intel HEXL-accelerated SEAL build, enable the `hexl` feature
+     * and call [`run`](Self::run) instead.
+     */
+    pub fn run_with_evaluator<I, E>(
+        &self,
+        fhe_program: &CompiledFheProgram,
+        arguments: Vec<I>,
+        public_key: &PublicKey,
+        evaluator: &E,
+    ) -> Result<Vec<Ciphertext>>
+    where
+        I: Into<FheProgramInput>,
+        E: Evaluator + Sync + Send,
+    {
+        self.run_internal(
+            fhe_program,
+            arguments,
+            public_key,
+            evaluator,
+            &RunOptions::default(),
+            &|| false,
+        )
+    }
+
+    /**
+     * Validates and runs the given FHE program as [`run`](Self::run) does,
+     * with the given [`RunOptions`] for authorization, cancellation,
+     * scheduling and metering.
+     *
+     * # Remarks
+     * Programs declare authorization requirements with
+     * `#[fhe_program(requires = "role:analyst")]`, which the compiler records
+     * in [`FheProgramMetadata::requirements`]. Only
+     * [`RunOptions::policy`] can grant them, so every other method that
+     * runs programs fails on a program with requirements. Returns
+     * [`Error::Unauthorized`] naming the first requirement the policy
+     * doesn't grant and [`Error::Cancelled`] if [`RunOptions::cancel`] gets
+     * cancelled before the run completes.
+     */
+    pub fn run_with_options<I>(
+        &self,
+        fhe_program: &CompiledFheProgram,
+        arguments: Vec<I>,
+        public_key: &PublicKey,
+        options: &RunOptions,
+    ) -> Result<Vec<Ciphertext>>
+    where
+        I: Into<FheProgramInput>,
+    {
+        let cancel = options.cancel;
+
+        self.run_with_cancellation(fhe_program, arguments, public_key, options, &|| {
+            cancel.map_or(false, |c| c.is_cancelled())
+        })
+    }
+
+    pub(crate) fn run_with_cancellation<I>(
+        &self,
+        fhe_program: &CompiledFheProgram,
+        arguments: Vec<I>,
+        public_key: &PublicKey,
+        options: &RunOptions,
+        is_cancelled: &(dyn Fn() -> bool + Sync),
+    ) -> Result<Vec<Ciphertext>>
+    where
+        I: Into<FheProgramInput>,
+    {
+        let fhe_data = self.runtime_data.unwrap_fhe();
+
+        match &fhe_data.context {
+            Context::Seal(context) => {
+                let evaluator = BFVEvaluator::new(context)?;
+
+                #[cfg(feature = "metrics")]
+                {
+                    if let Some((program, metrics)) = options.meter {
+                        let evaluator = ProfilingEvaluator::new(evaluator);
+
+                        let start = Instant::now();
+                        let result = self.run_internal(
+                            fhe_program,
+                            arguments,
+                            public_key,
+                            &evaluator,
+                            options,
+                            is_cancelled,
+                        );
+
+                        metrics.record_run(program, start.elapsed(), result.is_ok());
+                        metrics.record_profile(&evaluator.report());
+
+                        return result;
+                    }
+                }
+
+                self.run_internal(
+                    fhe_program,
+                    arguments,
+                    public_key,
+                    &evaluator,
+                    options,
+                    is_cancelled,
+                )
+            }
+        }
+    }
+
+    fn run_internal<I, E>(
+        &self,
+        fhe_program: &CompiledFheProgram,
+        mut arguments: Vec<I>,
+        public_key: &PublicKey,
+        evaluator: &E,
+        options: &RunOptions,
+        is_cancelled: &(dyn Fn() -> bool + Sync),
+    ) -> Result<Vec<Ciphertext>>
+    where
+        I: Into<FheProgramInput>,
+        E: Evaluator + Sync + Send,
+    {
+        authorize(fhe_program, options.policy)?;
+
+        // We're going to call run_program_unchecked, which
+        // can result in undefined behavior, non-termination,
+        // or panics on malformed programs. Since this method is safe,
+        // it must guard against calling run_program_unchecked with
+        // inputs that result in undefined behavior.
+        fhe_program.fhe_program_fn.validate()?;
+
+        // Aside from FHE program correctness, check that the required keys are given.
+        if public_key.relin_key.is_none() && fhe_program.fhe_program_fn.requires_relin_keys() {
+            return Err(Error::MissingRelinearizationKeys);
+        }
+
+        if public_key.galois_key.is_none() && fhe_program.fhe_program_fn.requires_galois_keys() {
+            return Err(Error::MissingGaloisKeys);
+        }
+
+        // Catch mismatched programs and keys here rather than in SEAL
+        // halfway through the graph.
+        self.check_conformance(fhe_program, public_key)?;
+
+        let mut arguments: Vec<FheProgramInput> = arguments.drain(0..).map(|a| a.into()).collect();
+
+        let expected_args = &fhe_program.metadata.signature.arguments;
+
+        // Check the arguments match the signature.
+        if expected_args.len() != arguments.len() {
+            return Err(Error::IncorrectCiphertextCount);
+        }
+
+        // Check the passed arguments' types match the signature.
+        if arguments
+            .iter()
+            .enumerate()
+            .any(|(i, a)| a.type_name_instance() != expected_args[i])
+        {
+            return Err(Error::argument_mismatch(
+                expected_args,
+                &arguments
+                    .iter()
+                    .map(|a| a.type_name_instance())
+                    .collect::<Vec<Type>>(),
+            ));
+        }
+
+        if fhe_program.metadata.signature.num_ciphertexts.len()
+            != fhe_program.metadata.signature.returns.len()
+        {
+            return Err(Error::ReturnTypeMetadataError);
+        }
+
+        let fhe_data = self.runtime_data.unwrap_fhe();
+
+        match &fhe_data.context {
+            Context::Seal(context) => {
+                let mut inputs: Vec<SealData> = vec![];
+
+                for i in arguments.drain(0..) {
+                    match i {
+                        FheProgramInput::Ciphertext(c) => match c.inner {
+                            InnerCiphertext::Seal(mut c) => {
+                                if c.iter().any(|c| c.params != fhe_data.params) {
+                                    return Err(Error::ParameterMismatch);
+                                }
+
+                                for j in c.drain(0..) {
+                                    inputs.push(SealData::Ciphertext(j.data));
+                                }
+                            }
+                        },
+                        FheProgramInput::Plaintext(p) => {
+                            let p = p.try_into_plaintext(&fhe_data.params)?;
 
                             match p.inner {
                                 InnerPlaintext::Seal(mut p) => {
@@ -344,18 +1032,46 @@ where
                     }
                 }
 
+                let max_input_id = fhe_program
+                    .fhe_program_fn
+                    .graph
+                    .node_weights()
+                    .filter_map(|n| match n.operation {
+                        Operation::InputCiphertext(id) | Operation::InputPlaintext(id) => Some(id),
+                        _ => None,
+                    })
+                    .max();
+
+                // Arguments whose types match the signature but that contain
+                // too few ciphertexts would leave inputs unassigned.
+                if matches!(max_input_id, Some(id) if id >= inputs.len()) {
+                    return Err(Error::IncorrectCiphertextCount);
+                }
+
+                align_levels(context, &mut inputs, evaluator)?;
+
                 let relin_key = public_key.relin_key.as_ref().map(|p| &p.data);
                 let galois_key = public_key.galois_key.as_ref().map(|p| &p.data);
 
+                let execution = options
+                    .schedule
+                    .map(|(scheduler, priority)| scheduler.execution(priority));
+
                 let mut raw_ciphertexts = unsafe {
-                    run_program_unchecked(
+                    run_program_unchecked_cancellable(
                         &fhe_program.fhe_program_fn,
                         &inputs,
-                        &evaluator,
+                        evaluator,
                         &relin_key,
                         &galois_key,
+                        is_cancelled,
+                        execution.as_ref(),
                     )
-                }?;
+                }
+                .map_err(|e| match e {
+                    FheProgramRunFailure::Cancelled => Error::Cancelled,
+                    e => e.into(),
+                })?;
 
                 let mut packed_ciphertexts = vec![];
 
@@ -385,6 +1101,46 @@ where
         }
     }
 
+    /**
+     * Checks that the given program and keys were created for this runtime's
+     * parameters and that the Galois keys can perform every rotation in the
+     * program.
+     */
+    fn check_conformance(
+        &self,
+        fhe_program: &CompiledFheProgram,
+        public_key: &PublicKey,
+    ) -> Result<()> {
+        let params = &self.runtime_data.unwrap_fhe().params;
+
+        if fhe_program.metadata.params != *params {
+            return Err(Error::ProgramParameterMismatch);
+        }
+
+        let key_params = [
+            Some(&public_key.public_key.params),
+            public_key.relin_key.as_ref().map(|k| &k.params),
+            public_key.galois_key.as_ref().map(|k| &k.params),
+        ];
+
+        if key_params.iter().flatten().any(|p| *p != params) {
+            return Err(Error::KeyParameterMismatch);
+        }
+
+        if let Some(galois_key) = &public_key.galois_key {
+            for step in required_rotations(&fhe_program.fhe_program_fn) {
+                if !galois_key
+                    .data
+                    .can_rotate_rows(step, params.lattice_dimension)?
+                {
+                    return Err(Error::MissingRotationKey(step));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /**
      * Encrypts the given [`FheType`](crate::FheType) using the given public key.
      *
@@ -399,6 +1155,82 @@ where
 
         let plaintext = val.try_into_plaintext(&fhe_data.params)?;
 
+        self.encrypt_plaintext(
+            Plaintext {
+                data_type: P::type_name(),
+                ..plaintext
+            },
+            public_key,
+        )
+    }
+
+    /**
+     * Encrypts the given value as the named argument of `fhe_program`,
+     * using only the public key.
+     *
+     * # Remarks
+     * This lets a party that doesn't hold the private key, e.g. a server,
+     * contribute its own hidden inputs such as random masks or constants
+     * that other parties mustn't see. The value's type must match the
+     * argument's type in the program's signature, which catches values
+     * passed for the wrong argument before the program runs.
+     *
+     * Returns [`Error::UnknownArgument`] if the program has no argument
+     * named `input_name`, [`Error::TypeMismatch`] if the argument isn't
+     * an encrypted `P`, and [`Error::ProgramParameterMismatch`] or
+     * [`Error::KeyParameterMismatch`] if the program or key weren't
+     * created for this runtime's parameters.
+     */
+    pub fn encrypt_for<P>(
+        &self,
+        fhe_program: &CompiledFheProgram,
+        input_name: &str,
+        val: P,
+        public_key: &PublicKey,
+    ) -> Result<Ciphertext>
+    where
+        P: TryIntoPlaintext + TypeName,
+    {
+        let params = &self.runtime_data.unwrap_fhe().params;
+
+        if fhe_program.metadata.params != *params {
+            return Err(Error::ProgramParameterMismatch);
+        }
+
+        if public_key.public_key.params != *params {
+            return Err(Error::KeyParameterMismatch);
+        }
+
+        let index = fhe_program
+            .metadata
+            .argument_names
+            .iter()
+            .position(|n| n == input_name)
+            .ok_or_else(|| Error::unknown_argument(input_name))?;
+
+        let expected = &fhe_program.metadata.signature.arguments[index];
+        let actual = Type {
+            is_encrypted: true,
+            ..P::type_name()
+        };
+
+        if *expected != actual {
+            return Err(Error::type_mismatch(expected, &actual));
+        }
+
+        self.encrypt(val, public_key)
+    }
+
+    /**
+     * Encrypts an already encoded [`Plaintext`] using the given public key.
+     */
+    fn encrypt_plaintext(
+        &self,
+        plaintext: Plaintext,
+        public_key: &PublicKey,
+    ) -> Result<Ciphertext> {
+        let fhe_data = self.runtime_data.unwrap_fhe();
+
         let ciphertext = match (&fhe_data.context, plaintext.inner) {
             (Context::Seal(context), InnerPlaintext::Seal(inner_plain)) => {
                 let encryptor = Encryptor::with_public_key(context, &public_key.public_key.data)?;
@@ -417,7 +1249,7 @@ where
                 Ciphertext {
                     data_type: Type {
                         is_encrypted: true,
-                        ..P::type_name()
+                        ..plaintext.data_type
                     },
                     inner: InnerCiphertext::Seal(ciphertexts),
                 }
@@ -426,8 +1258,435 @@ where
 
         Ok(ciphertext)
     }
+
+    /**
+     * Re-randomizes the given ciphertext by adding a fresh encryption of zero
+     * under `public_key`. The result decrypts to the same value, but is
+     * unlinkable to the original ciphertext by anyone without the private
+     * key.
+     *
+     * # Remarks
+     * Use this before returning an FHE program's outputs to hide which
+     * program produced them, e.g. to give circuit privacy against the
+     * decryptor. Note this randomizes the ciphertext's mask but not its noise,
+     * whose magnitude may still reveal something about the computation. Use
+     * [`rerandomize_and_mod_switch`](Self::rerandomize_and_mod_switch) to
+     * additionally reduce the noise.
+     *
+     * Each ciphertext is re-randomized at its current level of the modulus
+     * switching chain.
+     *
+     * Returns [`Error::KeyParameterMismatch`] if `public_key` wasn't
+     * generated for the runtime's parameters and
+     * [`Error::ParameterMismatch`] if `c` wasn't encrypted under them. `c`
+     * is left unchanged in either case.
+     */
+    pub fn rerandomize(&self, c: &mut Ciphertext, public_key: &PublicKey) -> Result<()> {
+        self.rerandomize_internal(c, public_key, false)
+    }
+
+    /**
+     * Decrypts the given ciphertext with `old_private_key` and encrypts the
+     * result under `new_public_key`. The result decrypts to the same value
+     * under the new key pair, with fresh noise.
+     *
+     * # Remarks
+     * The intermediate plaintexts are zeroed as soon as they're encrypted
+     * again. To re-encrypt many ciphertexts, use [`KeyRotation`](crate::KeyRotation).
+     */
+    pub fn reencrypt(
+        &self,
+        ciphertext: &Ciphertext,
+        old_private_key: &PrivateKey,
+        new_public_key: &PublicKey,
+    ) -> Result<Ciphertext> {
+        let fhe_data = self.runtime_data.unwrap_fhe();
+
+        match (&fhe_data.context, &ciphertext.inner) {
+            (Context::Seal(context), InnerCiphertext::Seal(ciphertexts)) => {
+                let old_private_key = old_private_key.0.expose_secret();
+                let encryptor =
+                    Encryptor::with_public_key(context, &new_public_key.public_key.data)?;
+
+                let ciphertexts = ciphertexts
+                    .iter()
+                    .map(|c| {
+                        if c.params != fhe_data.params {
+                            return Err(Error::ParameterMismatch);
+                        }
+
+                        let decryptor =
+                            level_decryptor(context, old_private_key, &c.data.get_parms_id())?;
+
+                        if decryptor.invariant_noise_budget(c)? == 0 {
+                            return Err(Error::TooMuchNoise);
+                        }
+
+                        let plaintext = decryptor.decrypt(c)?;
+
+                        Ok(WithContext {
+                            params: fhe_data.params.clone(),
+                            data: encryptor.encrypt(&plaintext)?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                Ok(Ciphertext {
+                    data_type: ciphertext.data_type.clone(),
+                    inner: InnerCiphertext::Seal(ciphertexts),
+                })
+            }
+        }
+    }
+
+    /**
+     * Creates a key that lets a [`ReEncryptor`](crate::ReEncryptor)
+     * switch ciphertexts encrypted under `from`'s key pair to `to`'s without
+     * decrypting them, using digits of
+     * [`DEFAULT_DECOMPOSITION_BITS`](crate::DEFAULT_DECOMPOSITION_BITS) bits.
+     *
+     * Returns [`Error::KeyParameterMismatch`] if either key wasn't
+     * generated for the runtime's parameters.
+     */
+    pub fn generate_reencryption_key(
+        &self,
+        from: &PrivateKey,
+        to: &PublicKey,
+    ) -> Result<ReEncryptionKey> {
+        self.generate_reencryption_key_with_decomposition(from, to, DEFAULT_DECOMPOSITION_BITS)
+    }
+
+    /**
+     * Like [`generate_reencryption_key`](Self::generate_reencryption_key),
+     * but decomposes ciphertexts into digits of `decomposition_bits` bits.
+     * See [`ReEncryptor`](crate::ReEncryptor) for the trade-off.
+     *
+     * # Panics
+     * If `decomposition_bits` isn't between 1 and 32.
+     */
+    pub fn generate_reencryption_key_with_decomposition(
+        &self,
+        from: &PrivateKey,
+        to: &PublicKey,
+        decomposition_bits: u32,
+    ) -> Result<ReEncryptionKey> {
+        let fhe_data = self.runtime_data.unwrap_fhe();
+        let from = from.0.expose_secret();
+
+        if from.params != fhe_data.params || to.public_key.params != fhe_data.params {
+            return Err(Error::KeyParameterMismatch);
+        }
+
+        match &fhe_data.context {
+            Context::Seal(context) => ReEncryptionKey::generate(
+                context,
+                &fhe_data.params,
+                &from.data,
+                &to.public_key.data,
+                decomposition_bits,
+            ),
+        }
+    }
+
+    /**
+     * Re-randomizes the given ciphertext as
+     * [`rerandomize`](Self::rerandomize) does and then switches it to the
+     * next level of the modulus switching chain.
+     *
+     * # Remarks
+     * Modulus switching scales the noise down, which hides most of the noise
+     * growth from the computation and shrinks the ciphertext. However,
+     * it consumes a level, so the result can't be used with FHE programs
+     * compiled for the original parameters.
+     *
+     * Returns an error if the runtime wasn't created with an expanded modulus
+     * switching chain (see
+     * [`Runtime::new_fhe_with_mod_chain`](crate::Runtime::new_fhe_with_mod_chain))
+     * or the ciphertext is already at the last level, and the same
+     * parameter errors as [`rerandomize`](Self::rerandomize).
+     */
+    pub fn rerandomize_and_mod_switch(
+        &self,
+        c: &mut Ciphertext,
+        public_key: &PublicKey,
+    ) -> Result<()> {
+        self.rerandomize_internal(c, public_key, true)
+    }
+
+    fn rerandomize_internal(
+        &self,
+        c: &mut Ciphertext,
+        public_key: &PublicKey,
+        mod_switch: bool,
+    ) -> Result<()> {
+        let fhe_data = self.runtime_data.unwrap_fhe();
+
+        if public_key.public_key.params != fhe_data.params {
+            return Err(Error::KeyParameterMismatch);
+        }
+
+        match (&fhe_data.context, &mut c.inner) {
+            (Context::Seal(context), InnerCiphertext::Seal(ciphertexts)) => {
+                if ciphertexts.iter().any(|c| c.params != fhe_data.params) {
+                    return Err(Error::ParameterMismatch);
+                }
+
+                let encryptor = Encryptor::with_public_key(context, &public_key.public_key.data)?;
+                let evaluator = BFVEvaluator::new(context)?;
+
+                for c in ciphertexts.iter_mut() {
+                    let zero = encryptor.encrypt_zero_with_parms_id(&c.data.get_parms_id())?;
+
+                    evaluator.add_inplace(&mut c.data, &zero)?;
+
+                    if mod_switch {
+                        evaluator.mod_switch_to_next_inplace(&mut c.data)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Wraps SEAL ciphertexts created outside Sunscreen (e.g. by an existing
+     * SEAL-based service) into a [`Ciphertext`] of type `P` that can be
+     * passed to [`run`](Self::run) or [`decrypt`](Self::decrypt).
+     *
+     * # Remarks
+     * `ciphertexts` must hold as many ciphertexts as `P` encodes to, in the
+     * same order and with the same encoding Sunscreen uses for `P` (e.g. a
+     * single batch encoded ciphertext for a `Batched` vector). Sunscreen
+     * can't check the encoding, but returns [`Error::ParameterMismatch`] if
+     * any ciphertext isn't valid under this runtime's parameters.
+     */
+    pub fn ciphertext_from_seal<P>(&self, ciphertexts: Vec<SealCiphertext>) -> Result<Ciphertext>
+    where
+        P: TypeName,
+    {
+        self.wrap_seal_ciphertexts(
+            Type {
+                is_encrypted: true,
+                ..P::type_name()
+            },
+            ciphertexts,
+        )
+    }
+
+    fn wrap_seal_ciphertexts(
+        &self,
+        data_type: Type,
+        ciphertexts: Vec<SealCiphertext>,
+    ) -> Result<Ciphertext> {
+        let fhe_data = self.runtime_data.unwrap_fhe();
+
+        match &fhe_data.context {
+            Context::Seal(context) => {
+                for c in ciphertexts.iter() {
+                    if !c.is_valid_for(context)? {
+                        return Err(Error::ParameterMismatch);
+                    }
+                }
+            }
+        }
+
+        Ok(Ciphertext {
+            data_type,
+            inner: InnerCiphertext::Seal(
+                ciphertexts
+                    .into_iter()
+                    .map(|c| WithContext {
+                        params: fhe_data.params.clone(),
+                        data: c,
+                    })
+                    .collect(),
+            ),
+        })
+    }
+
+    /**
+     * Unwraps the SEAL ciphertexts inside the given [`Ciphertext`], e.g. to
+     * hand an FHE program's output to an existing SEAL-based service.
+     *
+     * Returns [`Error::ParameterMismatch`] if the ciphertext wasn't created
+     * under this runtime's parameters.
+     */
+    pub fn ciphertext_to_seal(&self, ciphertext: Ciphertext) -> Result<Vec<SealCiphertext>> {
+        let fhe_data = self.runtime_data.unwrap_fhe();
+
+        match ciphertext.inner {
+            InnerCiphertext::Seal(c) => {
+                if c.iter().any(|c| c.params != fhe_data.params) {
+                    return Err(Error::ParameterMismatch);
+                }
+
+                Ok(c.into_iter().map(|c| c.data).collect())
+            }
+        }
+    }
+
+    /**
+     * Validates and runs the given FHE program on raw SEAL ciphertexts and
+     * returns the raw SEAL ciphertexts of each output.
+     *
+     * # Remarks
+     * This lets services built directly on SEAL adopt compiled programs
+     * without converting their data model. Every argument of the program
+     * must be encrypted and `arguments` must hold one entry per argument,
+     * each of which is converted as
+     * [`ciphertext_from_seal`](Self::ciphertext_from_seal) does with the
+     * argument's type from the program's signature. For programs that take
+     * plaintext arguments, convert the ciphertexts with
+     * [`ciphertext_from_seal`](Self::ciphertext_from_seal) and call
+     * [`run`](Self::run) instead.
+     *
+     * `public_key` must contain the Galois and relinearization keys the
+     * program requires. Keys generated by SEAL for this runtime's parameters
+     * can be used by wrapping them in [`WithContext`].
+     */
+    pub fn run_seal(
+        &self,
+        fhe_program: &CompiledFheProgram,
+        arguments: Vec<Vec<SealCiphertext>>,
+        public_key: &PublicKey,
+    ) -> Result<Vec<Vec<SealCiphertext>>> {
+        let expected_args = &fhe_program.metadata.signature.arguments;
+
+        if expected_args.len() != arguments.len() {
+            return Err(Error::IncorrectCiphertextCount);
+        }
+
+        if expected_args.iter().any(|a| !a.is_encrypted) {
+            return Err(Error::argument_mismatch(
+                expected_args,
+                &expected_args
+                    .iter()
+                    .map(|a| Type {
+                        is_encrypted: true,
+                        ..a.clone()
+                    })
+                    .collect::<Vec<Type>>(),
+            ));
+        }
+
+        let arguments = expected_args
+            .iter()
+            .zip(arguments)
+            .map(|(t, c)| self.wrap_seal_ciphertexts(t.clone(), c))
+            .collect::<Result<Vec<Ciphertext>>>()?;
+
+        self.run(fhe_program, arguments, public_key)?
+            .into_iter()
+            .map(|c| self.ciphertext_to_seal(c))
+            .collect()
+    }
+
+    /**
+     * Runs the given FHE program on arguments described by a JSON document
+     * and returns the program's outputs as a JSON document.
+     *
+     * # Remarks
+     * `json_inputs` must be a JSON array with one [`JsonInput`] per
+     * argument in the program's signature. Each entry is validated against
+     * the corresponding argument type in the program's metadata. Plain
+     * [`JsonInput::Value`]s are encoded as the argument's type using
+     * `types`, and plaintexts supplied for encrypted arguments are
+     * encrypted under `public_key` before running the program.
+     *
+     * The returned string is a JSON array of the serialized output
+     * [`Ciphertext`]s. This is intended as a building block for generic
+     * gateways that don't know the program's Rust types at compile time.
+     *
+     * Returns [`Error::ArgumentMismatch`] if the inputs don't match the
+     * program's signature, [`Error::ParameterMismatch`] if a plaintext or
+     * ciphertext wasn't created under this runtime's parameters, and
+     * [`Error::FheTypeError`] if a value's type isn't in `types` or the
+     * value isn't valid for it.
+     */
+    pub fn run_json(
+        &self,
+        fhe_program: &CompiledFheProgram,
+        json_inputs: &str,
+        types: &JsonTypes,
+        public_key: &PublicKey,
+    ) -> Result<String> {
+        let json_inputs: Vec<JsonInput> = serde_json::from_str(json_inputs)?;
+
+        let expected_args = &fhe_program.metadata.signature.arguments;
+        let params = &self.runtime_data.unwrap_fhe().params;
+
+        let mismatch = || {
+            let actual_args = json_inputs
+                .iter()
+                .map(|a| {
+                    a.data_type().cloned().unwrap_or_else(|| Type {
+                        name: "serde_json::Value".to_owned(),
+                        version: Version::new(1, 0, 0),
+                        is_encrypted: false,
+                    })
+                })
+                .collect::<Vec<Type>>();
+
+            Error::argument_mismatch(expected_args, &actual_args)
+        };
+
+        if expected_args.len() != json_inputs.len() {
+            return Err(mismatch());
+        }
+
+        let mut arguments: Vec<FheProgramInput> = vec![];
+
+        for (input, expected) in json_inputs.iter().zip(expected_args.iter()) {
+            if let JsonInput::Plaintext(p) = input {
+                if p.inner_as_seal_plaintext()?
+                    .iter()
+                    .any(|p| p.params != *params)
+                {
+                    return Err(Error::ParameterMismatch);
+                }
+            }
+
+            let arg = match input {
+                JsonInput::Ciphertext(c) if c.data_type == *expected => c.clone().into(),
+                JsonInput::Plaintext(p) if p.data_type == *expected => p.clone().into(),
+                // The caller gave us the plaintext for an encrypted argument.
+                JsonInput::Plaintext(p)
+                    if expected.is_encrypted
+                        && Type {
+                            is_encrypted: true,
+                            ..p.data_type.clone()
+                        } == *expected =>
+                {
+                    self.encrypt_plaintext(p.clone(), public_key)?.into()
+                }
+                JsonInput::Value(v) => {
+                    let plain_type = Type {
+                        is_encrypted: false,
+                        ..expected.clone()
+                    };
+
+                    let p = types.encode(&plain_type, v, params)?;
+
+                    if expected.is_encrypted {
+                        self.encrypt_plaintext(p, public_key)?.into()
+                    } else {
+                        p.into()
+                    }
+                }
+                _ => return Err(mismatch()),
+            };
+
+            arguments.push(arg);
+        }
+
+        let outputs = self.run(fhe_program, arguments, public_key)?;
+
+        Ok(serde_json::to_string(&outputs)?)
+    }
 }
 
+#[cfg(feature = "zkp")]
 impl<T, B> GenericRuntime<T, B>
 where
     T: marker::Zkp,
@@ -443,6 +1702,25 @@ where
         public_inputs: Vec<I>,
         private_inputs: Vec<I>,
     ) -> Result<Proof>
+    where
+        I: Into<ZkpProgramInput>,
+    {
+        self.prove_with_context(program, &[], constant_inputs, public_inputs, private_inputs)
+    }
+
+    /**
+     * Like [`prove`](Self::prove), but binds `context` into the proof so
+     * it only verifies with [`verify_with_context`](Self::verify_with_context)
+     * and the same `context`. See [`ZkpBackend::prove_with_context`].
+     */
+    pub fn prove_with_context<I>(
+        &self,
+        program: &CompiledZkpProgram,
+        context: &[u8],
+        constant_inputs: Vec<I>,
+        public_inputs: Vec<I>,
+        private_inputs: Vec<I>,
+    ) -> Result<Proof>
     where
         I: Into<ZkpProgramInput>,
     {
@@ -474,7 +1752,7 @@ where
 
         trace!("Starting backend prove...");
 
-        Ok(backend.prove(&prog, &inputs)?)
+        Ok(backend.prove_with_context(&prog, &inputs, context)?)
     }
 
     /**
@@ -487,6 +1765,25 @@ where
         constant_inputs: Vec<I>,
         public_inputs: Vec<I>,
     ) -> Result<()>
+    where
+        I: Into<ZkpProgramInput>,
+    {
+        self.verify_with_context(program, proof, &[], constant_inputs, public_inputs)
+    }
+
+    /**
+     * Verify that the given `proof`, created with
+     * [`prove_with_context`](Self::prove_with_context) and the given
+     * `context`, satisfies the given `program`.
+     */
+    pub fn verify_with_context<I>(
+        &self,
+        program: &CompiledZkpProgram,
+        proof: &Proof,
+        context: &[u8],
+        constant_inputs: Vec<I>,
+        public_inputs: Vec<I>,
+    ) -> Result<()>
     where
         I: Into<ZkpProgramInput>,
     {
@@ -510,11 +1807,12 @@ where
         trace!("Verifier JIT time {}s", now.elapsed().as_secs_f64());
         trace!("Starting backend verify...");
 
-        Ok(backend.verify(&prog, proof)?)
+        Ok(backend.verify_with_context(&prog, proof, context)?)
     }
 }
 
 impl GenericRuntime<(), ()> {
+    #[cfg(feature = "fhe")]
     #[deprecated]
     /**
      * Create a new Runtime supporting only FHE operations.
@@ -526,7 +1824,8 @@ impl GenericRuntime<(), ()> {
         Self::new_fhe(params)
     }
 
-    fn make_fhe_runtime_data(params: &Params) -> Result<FheRuntimeData> {
+    #[cfg(feature = "fhe")]
+    fn make_fhe_runtime_data(params: &Params, expand_mod_chain: bool) -> Result<FheRuntimeData> {
         match params.scheme_type {
             SchemeType::Bfv => {
                 let bfv_params = BfvEncryptionParametersBuilder::new()
@@ -541,31 +1840,94 @@ impl GenericRuntime<(), ()> {
                     )
                     .build()?;
 
-                let context = SealContext::new(&bfv_params, true, params.security_level)?;
+                let context =
+                    SealContext::new(&bfv_params, expand_mod_chain, params.security_level)?;
 
                 Ok(FheRuntimeData {
                     params: params.clone(),
                     context: Context::Seal(context),
+                    expand_mod_chain,
+                    registry: Mutex::new(Registry::default()),
                 })
             }
         }
     }
 
+    #[cfg(feature = "zkp")]
     fn make_zkp_runtime_data() -> ZkpRuntimeData {
         ZkpRuntimeData
     }
 
+    #[cfg(feature = "fhe")]
+    /**
+     * Creates a Runtime supporting only FHE operations from state saved
+     * with [`export_state`](GenericRuntime::export_state), e.g. on a node
+     * replacing one that failed, registering the given programs and key
+     * sets under their names and ids.
+     *
+     * Returns [`Error::UnsupportedStateVersion`] if the state was saved
+     * in a format this version doesn't read, and
+     * [`Error::StateMismatch`] unless `programs` and `key_sets` are
+     * exactly the ones registered when the state was saved.
+     */
+    pub fn import_state(
+        state: &RuntimeState,
+        programs: &[(&str, &CompiledFheProgram)],
+        key_sets: &[(&str, &PublicKey)],
+    ) -> Result<FheRuntime> {
+        if state.version != RuntimeState::CURRENT_VERSION {
+            return Err(Error::UnsupportedStateVersion(state.version));
+        }
+
+        let runtime = Self::new_fhe_with_mod_chain(&state.params, state.expand_mod_chain)?;
+
+        for (name, program) in programs {
+            runtime.register_program(name, program)?;
+        }
+
+        for (id, keys) in key_sets {
+            runtime.register_keys(id, keys)?;
+        }
+
+        runtime.registry().verify(state)?;
+
+        Ok(runtime)
+    }
+
+    #[cfg(feature = "fhe")]
     /**
      * Create a new Runtime supporting only FHE operations.
      */
     pub fn new_fhe(params: &Params) -> Result<FheRuntime> {
+        Self::new_fhe_with_mod_chain(params, true)
+    }
+
+    #[cfg(feature = "fhe")]
+    /**
+     * Create a new Runtime supporting only FHE operations, optionally
+     * skipping creation of the modulus switching chain.
+     *
+     * # Remarks
+     * When `expand_mod_chain` is false, the runtime doesn't precompute data
+     * for lower levels of the modulus switching chain. This makes creating
+     * the runtime faster and reduces its memory footprint. Ciphertexts that
+     * have been modulus switched (e.g. to reduce their storage size) still
+     * deserialize and [`decrypt`](GenericRuntime::decrypt), using an
+     * auxiliary context for just their level (see
+     * [`Context::at_level`](seal_fhe::Context::at_level)), but running
+     * programs on them requires `expand_mod_chain` to be true. FHE programs
+     * compiled by Sunscreen don't modulus switch, so their outputs work
+     * either way.
+     */
+    pub fn new_fhe_with_mod_chain(params: &Params, expand_mod_chain: bool) -> Result<FheRuntime> {
         Ok(GenericRuntime {
-            runtime_data: RuntimeData::Fhe(Self::make_fhe_runtime_data(params)?),
+            runtime_data: RuntimeData::Fhe(Self::make_fhe_runtime_data(params, expand_mod_chain)?),
             _phantom_t: PhantomData,
             zkp_backend: (),
         })
     }
 
+    #[cfg(feature = "zkp")]
     /**
      * Creates a new Runtime supporting only ZKP operations
      */
@@ -580,6 +1942,7 @@ impl GenericRuntime<(), ()> {
         })
     }
 
+    #[cfg(all(feature = "fhe", feature = "zkp"))]
     /**
      * Creates a new Runtime supporting both ZKP and FHE operations.
      */
@@ -588,7 +1951,7 @@ impl GenericRuntime<(), ()> {
         B: ZkpBackend + Clone + 'static,
     {
         let runtime_data = RuntimeData::FheZkp(
-            Self::make_fhe_runtime_data(params)?,
+            Self::make_fhe_runtime_data(params, true)?,
             Self::make_zkp_runtime_data(),
         );
 
@@ -600,16 +1963,19 @@ impl GenericRuntime<(), ()> {
     }
 }
 
+#[cfg(all(feature = "fhe", feature = "zkp"))]
 /**
  * A runtime capable of both FHE and ZKP operations.
  */
 pub type FheZkpRuntime<B> = GenericRuntime<FheZkp, B>;
 
+#[cfg(feature = "fhe")]
 /**
  * A runtime capable of only FHE operations.
  */
 pub type FheRuntime = GenericRuntime<Fhe, ()>;
 
+#[cfg(feature = "zkp")]
 /**
  * A runtime capable of only ZKP operations.
  */
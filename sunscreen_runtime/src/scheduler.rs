@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+
+/**
+ * The virtual time one slice costs an execution with priority 1. Higher
+ * priorities pay proportionally less.
+ */
+const STRIDE: u64 = 1 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/**
+ * How a [`FairScheduler`] divides slices among executions waiting for
+ * them.
+ */
+pub enum Fairness {
+    /**
+     * Each execution gets a share of slices proportional to its priority,
+     * so an execution with priority 2 runs twice as many operations as one
+     * with priority 1 while both are waiting. Executions that start later
+     * begin at the current virtual time, so they neither starve nor get
+     * starved by those already running.
+     */
+    Weighted,
+
+    /**
+     * Slices always go to the highest priority waiting execution, with ties
+     * going to whichever started first. Lower priorities only run when no
+     * higher priority execution is waiting.
+     */
+    Strict,
+}
+
+/**
+ * Interleaves the operations of FHE programs running concurrently, so long
+ * programs don't monopolize the CPU. See
+ * [`RunOptions::schedule`](crate::RunOptions::schedule).
+ *
+ * # Remarks
+ * The scheduler hands out a fixed number of slices. Each operation
+ * (node) of a scheduled program waits for a slice before it runs and
+ * returns it afterwards, and the [`Fairness`] policy decides which waiting
+ * execution gets the next free slice. A short program arriving while a
+ * long one runs thus gets its operations interleaved with the long one's
+ * rather than queuing behind all of them, which improves tail latency in
+ * mixed workloads.
+ *
+ * Programs run through other methods don't take slices and aren't
+ * throttled. Clones share the same slices.
+ */
+#[derive(Clone)]
+pub struct FairScheduler {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    slice_freed: Condvar,
+}
+
+struct ExecutionState {
+    priority: u32,
+    pass: u64,
+    arrival: u64,
+}
+
+struct State {
+    fairness: Fairness,
+    free_slices: usize,
+    virtual_time: u64,
+    next_id: u64,
+    executions: HashMap<u64, ExecutionState>,
+    waiting: Vec<(u64, u64)>,
+}
+
+impl State {
+    fn new(slices: usize, fairness: Fairness) -> Self {
+        Self {
+            fairness,
+            free_slices: slices,
+            virtual_time: 0,
+            next_id: 0,
+            executions: HashMap::new(),
+            waiting: vec![],
+        }
+    }
+
+    fn next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        id
+    }
+
+    fn register(&mut self, priority: u32) -> u64 {
+        let id = self.next_id();
+
+        self.executions.insert(
+            id,
+            ExecutionState {
+                priority,
+                pass: self.virtual_time,
+                arrival: id,
+            },
+        );
+
+        id
+    }
+
+    /**
+     * Returns the position in `waiting` of the request the next free
+     * slice goes to.
+     */
+    fn best_waiting(&self) -> Option<usize> {
+        let key = |(execution, request): &(u64, u64)| {
+            let e = &self.executions[execution];
+
+            match self.fairness {
+                Fairness::Weighted => (e.pass, 0, *request),
+                Fairness::Strict => (u64::from(u32::MAX - e.priority), e.arrival, *request),
+            }
+        };
+
+        self.waiting
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, w)| key(w))
+            .map(|(i, _)| i)
+    }
+
+    /**
+     * Grants a slice to `request` if it's free and the request is next in
+     * line.
+     */
+    fn try_grant(&mut self, request: (u64, u64)) -> bool {
+        if self.free_slices == 0 {
+            return false;
+        }
+
+        match self.best_waiting() {
+            Some(i) if self.waiting[i] == request => {
+                self.waiting.swap_remove(i);
+                self.free_slices -= 1;
+
+                let e = self.executions.get_mut(&request.0).unwrap();
+
+                self.virtual_time = u64::max(self.virtual_time, e.pass);
+                e.pass += STRIDE / u64::from(e.priority);
+
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FairScheduler {
+    /**
+     * Creates a scheduler running at most `slices` operations at once,
+     * divided according to `fairness`.
+     *
+     * # Remarks
+     * To keep the CPU busy, use at least as many slices as threads
+     * in rayon's pool, which runs program operations.
+     *
+     * # Panics
+     * If `slices` is 0.
+     */
+    pub fn new(slices: usize, fairness: Fairness) -> Self {
+        assert!(slices > 0, "A scheduler needs at least 1 slice.");
+
+        Self {
+            shared: Arc::new(Shared {
+                state: Mutex::new(State::new(slices, fairness)),
+                slice_freed: Condvar::new(),
+            }),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // The lock is never held across code that can panic, so it can't
+        // be poisoned.
+        self.shared.state.lock().unwrap()
+    }
+
+    /**
+     * Registers an execution with the given priority, which deregisters
+     * when dropped.
+     *
+     * # Panics
+     * If `priority` is 0.
+     */
+    pub(crate) fn execution(&self, priority: u32) -> Execution<'_> {
+        assert!(priority > 0, "Priorities must be at least 1.");
+
+        let id = self.lock().register(priority);
+
+        Execution {
+            scheduler: self,
+            id,
+        }
+    }
+}
+
+/**
+ * A program run through a [`FairScheduler`].
+ */
+pub(crate) struct Execution<'a> {
+    scheduler: &'a FairScheduler,
+    id: u64,
+}
+
+impl<'a> Execution<'a> {
+    /**
+     * Waits for a slice to run one operation, which returns the slice when
+     * dropped.
+     */
+    pub(crate) fn slice(&self) -> Slice<'_> {
+        let mut state = self.scheduler.lock();
+
+        let request = (self.id, state.next_id());
+        state.waiting.push(request);
+
+        while !state.try_grant(request) {
+            state = self.scheduler.shared.slice_freed.wait(state).unwrap();
+        }
+
+        // Another waiter may be next in line for a remaining slice.
+        if state.free_slices > 0 && !state.waiting.is_empty() {
+            self.scheduler.shared.slice_freed.notify_all();
+        }
+
+        Slice { execution: self }
+    }
+}
+
+impl<'a> Drop for Execution<'a> {
+    fn drop(&mut self) {
+        self.scheduler.lock().executions.remove(&self.id);
+    }
+}
+
+/**
+ * A held slice, returned to the scheduler when dropped.
+ */
+pub(crate) struct Slice<'a> {
+    execution: &'a Execution<'a>,
+}
+
+impl<'a> Drop for Slice<'a> {
+    fn drop(&mut self) {
+        let scheduler = self.execution.scheduler;
+
+        scheduler.lock().free_slices += 1;
+        scheduler.shared.slice_freed.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Returns the order `state` grants 1 slice at a time to executions
+     * that each keep a request waiting.
+     */
+    fn grant_order(mut state: State, executions: &[u64], grants: usize) -> Vec<u64> {
+        for e in executions {
+            let request = (*e, state.next_id());
+            state.waiting.push(request);
+        }
+
+        let mut order = vec![];
+
+        for _ in 0..grants {
+            let i = state.best_waiting().unwrap();
+            let request = state.waiting[i];
+
+            assert!(state.try_grant(request));
+            order.push(request.0);
+
+            state.free_slices += 1;
+
+            let request = (request.0, state.next_id());
+            state.waiting.push(request);
+        }
+
+        order
+    }
+
+    fn grant_order_in_place(state: &mut State, execution: u64, grants: usize) {
+        for _ in 0..grants {
+            let request = (execution, state.next_id());
+            state.waiting.push(request);
+
+            assert!(state.try_grant(request));
+            state.free_slices += 1;
+        }
+    }
+
+    #[test]
+    fn weighted_shares_follow_priority() {
+        let mut state = State::new(1, Fairness::Weighted);
+
+        let low = state.register(1);
+        let high = state.register(3);
+
+        let order = grant_order(state, &[low, high], 400);
+
+        assert_eq!(order.iter().filter(|e| **e == low).count(), 100);
+        assert_eq!(order.iter().filter(|e| **e == high).count(), 300);
+    }
+
+    #[test]
+    fn late_executions_start_at_virtual_time() {
+        let mut state = State::new(1, Fairness::Weighted);
+
+        let long = state.register(1);
+
+        grant_order_in_place(&mut state, long, 50);
+
+        // A newcomer alternates with the long execution rather than
+        // running 50 operations first.
+        let short = state.register(1);
+
+        let order = grant_order(state, &[long, short], 10);
+
+        assert_eq!(order.iter().filter(|e| **e == short).count(), 5);
+    }
+
+    #[test]
+    fn strict_runs_highest_priority_first() {
+        let mut state = State::new(1, Fairness::Strict);
+
+        let low = state.register(1);
+        let high = state.register(5);
+
+        assert_eq!(grant_order(state, &[low, high], 3), vec![high, high, high]);
+    }
+
+    #[test]
+    fn requests_wait_for_free_slices() {
+        let mut state = State::new(1, Fairness::Weighted);
+
+        let a = state.register(1);
+        let b = state.register(1);
+
+        let first = (a, state.next_id());
+        let second = (b, state.next_id());
+        state.waiting.extend([first, second]);
+
+        assert!(!state.try_grant(second));
+        assert!(state.try_grant(first));
+        assert!(!state.try_grant(second));
+
+        state.free_slices += 1;
+
+        assert!(state.try_grant(second));
+    }
+
+    #[test]
+    fn scheduled_threads_all_complete() {
+        let scheduler = FairScheduler::new(2, Fairness::Weighted);
+
+        crossbeam::scope(|s| {
+            for p in 1..=4 {
+                let scheduler = &scheduler;
+
+                s.spawn(move |_| {
+                    let execution = scheduler.execution(p);
+
+                    for _ in 0..100 {
+                        let _slice = execution.slice();
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        let state = scheduler.lock();
+
+        assert_eq!(state.free_slices, 2);
+        assert!(state.executions.is_empty());
+        assert!(state.waiting.is_empty());
+    }
+}
@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
+
+use std::fmt::{Debug, Formatter};
+use std::mem::ManuallyDrop;
+
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+/**
+ * Holds a value that mustn't leak, such as a private key.
+ *
+ * # Remarks
+ * [`Debug`] prints `Secret([REDACTED])` rather than the value, so secrets
+ * can't end up in logs or panic messages by accident. The wrapper doesn't
+ * implement [`Clone`] either; copy the value explicitly with
+ * [`duplicate`](Self::duplicate) so every copy of a secret is deliberate.
+ *
+ * Serialization passes through to the wrapped value unchanged.
+ *
+ * The wrapped value is zeroed when the wrapper is dropped. Secrets
+ * compare in constant time, so comparing them doesn't leak where they
+ * differ.
+ */
+pub struct Secret<T>(T)
+where
+    T: Zeroize;
+
+impl<T> Secret<T>
+where
+    T: Zeroize,
+{
+    /**
+     * Wraps the given value.
+     */
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /**
+     * Returns a reference to the wrapped value. Keep the reference's
+     * lifetime short and don't copy the value out of it.
+     */
+    pub fn expose_secret(&self) -> &T {
+        &self.0
+    }
+
+    /**
+     * Unwraps the value. It's no longer zeroed on drop unless it zeroes
+     * itself, as SEAL's secret keys do.
+     */
+    pub fn into_inner(self) -> T {
+        let this = ManuallyDrop::new(self);
+
+        // Safe, as `this` is never dropped, so the value is only moved
+        // out once.
+        unsafe { std::ptr::read(&this.0) }
+    }
+}
+
+impl<T> Secret<T>
+where
+    T: Clone + Zeroize,
+{
+    /**
+     * Creates a second copy of the secret.
+     */
+    pub fn duplicate(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Debug for Secret<T>
+where
+    T: Zeroize,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Secret([REDACTED])")
+    }
+}
+
+impl<T> PartialEq for Secret<T>
+where
+    T: ConstantTimeEq + Zeroize,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.0.ct_eq(&other.0).into()
+    }
+}
+
+impl<T> Drop for Secret<T>
+where
+    T: Zeroize,
+{
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T> Zeroize for Secret<T>
+where
+    T: Zeroize,
+{
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_redacts_value() {
+        let secret = Secret::new(vec![1u8, 2, 3]);
+
+        assert_eq!(format!("{:?}", secret), "Secret([REDACTED])");
+        assert_eq!(secret.expose_secret(), &vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn can_duplicate_and_zeroize() {
+        let mut secret = Secret::new(vec![1u8, 2, 3]);
+        let copy = secret.duplicate();
+
+        secret.zeroize();
+
+        assert!(secret.expose_secret().is_empty());
+        assert_eq!(copy.expose_secret(), &vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn can_compare_and_unwrap() {
+        assert!(Secret::new(42u64) == Secret::new(42u64));
+        assert!(Secret::new(42u64) != Secret::new(43u64));
+        assert_eq!(Secret::new(vec![1u8, 2, 3]).into_inner(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn serialization_is_transparent() {
+        let secret = Secret::new(42u64);
+
+        assert_eq!(serde_json::to_string(&secret).unwrap(), "42");
+
+        let secret: Secret<u64> = serde_json::from_str("42").unwrap();
+
+        assert_eq!(*secret.expose_secret(), 42);
+    }
+}
@@ -1,12 +1,15 @@
 use std::hash::Hash;
 
 use crate::Params;
-use seal_fhe::{BfvEncryptionParametersBuilder, Context, FromBytes, Modulus, ToBytes};
+use seal_fhe::{
+    serialized_parms_id, BfvEncryptionParametersBuilder, Context, FromBytes, Modulus, ToBytes,
+};
 use serde::{
     de::{Deserializer, MapAccess, SeqAccess, Visitor},
     ser::{Error, SerializeStruct, Serializer},
     Deserialize, Serialize,
 };
+use zeroize::{Zeroize, Zeroizing};
 
 #[derive(Debug, PartialEq, Hash, Eq, Clone)]
 /**
@@ -39,6 +42,15 @@ where
     }
 }
 
+impl<T> Zeroize for WithContext<T>
+where
+    T: ToBytes + FromBytes + PartialEq + Zeroize,
+{
+    fn zeroize(&mut self) {
+        self.data.zeroize();
+    }
+}
+
 impl<T> Serialize for WithContext<T>
 where
     T: ToBytes + FromBytes + PartialEq,
@@ -49,13 +61,14 @@ where
     {
         let mut state = serializer.serialize_struct("WithContext", 2)?;
         state.serialize_field("params", &self.params)?;
-        state.serialize_field(
-            "data",
-            &self
-                .data
-                .as_bytes()
-                .map_err(|e| S::Error::custom(format!("Failed to serialize key: {}", e)))?,
-        )?;
+        // The data may be a secret key, so clear our copy of its bytes.
+        let data = self
+            .data
+            .as_bytes()
+            .map(Zeroizing::new)
+            .map_err(|e| S::Error::custom(format!("Failed to serialize key: {}", e)))?;
+
+        state.serialize_field("data", &*data)?;
         state.end()
     }
 }
@@ -92,8 +105,9 @@ where
                 let params = seq
                     .next_element()?
                     .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
-                let data: Vec<u8> = seq
+                let data: Zeroizing<Vec<u8>> = seq
                     .next_element()?
+                    .map(Zeroizing::new)
                     .ok_or_else(|| serde::de::Error::invalid_length(1, &self))?;
 
                 let data = deserialize_with_params(&params, &data)
@@ -107,7 +121,7 @@ where
                 A: MapAccess<'de>,
             {
                 let mut params: Option<Params> = None;
-                let mut data: Option<Vec<u8>> = None;
+                let mut data: Option<Zeroizing<Vec<u8>>> = None;
 
                 while let Some(key) = map.next_key()? {
                     match key {
@@ -124,7 +138,7 @@ where
                             let val: Option<Vec<u8>> = map.next_value()?;
 
                             if let Some(val) = val {
-                                data = Some(val);
+                                data = Some(Zeroizing::new(val));
                             } else {
                                 return Err(serde::de::Error::missing_field("data"));
                             }
@@ -177,9 +191,25 @@ where
         .set_poly_modulus_degree(params.lattice_dimension)
         .build()?;
 
+    // Most data lives at the top level of the modulus switching chain, so
+    // don't pay to create the whole chain. Modulus switched data instead
+    // loads under an auxiliary context for just its level.
     let seal_context = Context::new(&encryption_params, false, params.security_level)?;
 
-    let data = T::from_bytes(&seal_context, data)?;
-
-    Ok(data)
+    let err = match T::from_bytes(&seal_context, data) {
+        Ok(data) => return Ok(data),
+        Err(e) => e,
+    };
+
+    // Data at a level the context has didn't fail for want of the chain,
+    // so report why it did.
+    let parms_id = match serialized_parms_id(data) {
+        Ok(parms_id) if seal_context.get_chain_index(&parms_id).is_err() => parms_id,
+        _ => return Err(err),
+    };
+
+    match seal_context.at_level(&parms_id) {
+        Ok(level_context) => T::from_bytes(&level_context, data),
+        Err(_) => Err(err),
+    }
 }
@@ -0,0 +1,231 @@
+use std::collections::BTreeMap;
+
+use seal_fhe::Fingerprint;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+
+use crate::{CompiledFheProgram, Error, Params, PublicKey, Result};
+
+/**
+ * A versioned snapshot of a runtime's state, for recreating the runtime
+ * on another node (e.g. after a failure) with
+ * [`Runtime::import_state`](crate::Runtime::import_state). Create one with
+ * [`GenericRuntime::export_state`](crate::GenericRuntime::export_state) and
+ * serialize it with serde.
+ *
+ * # Remarks
+ * Besides the runtime's scheme parameters, the snapshot records hashes of
+ * the programs and key sets registered with
+ * [`register_program`](crate::GenericRuntime::register_program) and
+ * [`register_keys`](crate::GenericRuntime::register_keys), but not the
+ * programs or keys themselves. A replacement node loads those from
+ * wherever the failed node did, e.g. shared storage, so clients needn't
+ * upload their keys again, and restoring the snapshot checks it loaded
+ * exactly the same ones. The snapshot holds no secrets.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeState {
+    /**
+     * The format version, so newer releases can reject or migrate
+     * snapshots they don't understand.
+     */
+    pub version: u32,
+
+    /**
+     * The runtime's scheme parameters.
+     */
+    pub params: Params,
+
+    /**
+     * Whether the runtime expanded the modulus switching chain. See
+     * [`Runtime::new_fhe_with_mod_chain`](crate::Runtime::new_fhe_with_mod_chain).
+     */
+    pub expand_mod_chain: bool,
+
+    /**
+     * A SHA3-256 hash of each registered program's serialization, by the
+     * name it was registered under.
+     */
+    pub programs: BTreeMap<String, [u8; 32]>,
+
+    /**
+     * The fingerprints of each registered key set, by the id it was
+     * registered under.
+     */
+    pub key_sets: BTreeMap<String, KeySetFingerprint>,
+}
+
+impl RuntimeState {
+    /**
+     * The version [`export_state`](crate::GenericRuntime::export_state)
+     * writes.
+     */
+    pub const CURRENT_VERSION: u32 = 2;
+}
+
+/**
+ * The fingerprints of the keys in a [`PublicKey`]. See [`Fingerprint`].
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeySetFingerprint {
+    /**
+     * The fingerprint of the public key.
+     */
+    pub public_key: Fingerprint,
+
+    /**
+     * The fingerprint of the Galois keys, if any.
+     */
+    pub galois_keys: Option<Fingerprint>,
+
+    /**
+     * The fingerprint of the relinearization keys, if any.
+     */
+    pub relin_keys: Option<Fingerprint>,
+}
+
+impl KeySetFingerprint {
+    /**
+     * Returns the fingerprints of `keys`.
+     */
+    pub fn of(keys: &PublicKey) -> Result<Self> {
+        Ok(Self {
+            public_key: keys.public_key.data.fingerprint()?,
+            galois_keys: keys
+                .galois_key
+                .as_ref()
+                .map(|k| k.data.fingerprint())
+                .transpose()?,
+            relin_keys: keys
+                .relin_key
+                .as_ref()
+                .map(|k| k.data.fingerprint())
+                .transpose()?,
+        })
+    }
+}
+
+/**
+ * The programs and key sets registered with a runtime.
+ */
+#[derive(Default)]
+pub(crate) struct Registry {
+    pub programs: BTreeMap<String, [u8; 32]>,
+    pub key_sets: BTreeMap<String, KeySetFingerprint>,
+}
+
+impl Registry {
+    /**
+     * Returns [`Error::StateMismatch`] unless this registry holds exactly
+     * the programs and key sets `state` recorded.
+     */
+    pub fn verify(&self, state: &RuntimeState) -> Result<()> {
+        verify_entries("program", &state.programs, &self.programs)?;
+        verify_entries("key set", &state.key_sets, &self.key_sets)
+    }
+}
+
+/**
+ * Returns a SHA3-256 hash of `program`'s serialization.
+ */
+pub(crate) fn program_hash(program: &CompiledFheProgram) -> Result<[u8; 32]> {
+    Ok(Sha3_256::digest(&bincode::serialize(program)?).into())
+}
+
+fn verify_entries<T: PartialEq>(
+    kind: &str,
+    recorded: &BTreeMap<String, T>,
+    restored: &BTreeMap<String, T>,
+) -> Result<()> {
+    for (name, hash) in recorded {
+        match restored.get(name) {
+            Some(h) if h == hash => {}
+            Some(_) => {
+                return Err(Error::state_mismatch(&format!(
+                    "{} {} differs from the recorded one",
+                    kind, name
+                )))
+            }
+            None => {
+                return Err(Error::state_mismatch(&format!(
+                    "{} {} is missing",
+                    kind, name
+                )))
+            }
+        }
+    }
+
+    match restored.keys().find(|n| !recorded.contains_key(*n)) {
+        Some(name) => Err(Error::state_mismatch(&format!(
+            "{} {} wasn't recorded",
+            kind, name
+        ))),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use seal_fhe::{CoefficientModulus, PlainModulus, SecurityLevel};
+    use sunscreen_fhe_program::SchemeType;
+
+    #[test]
+    fn can_export_and_import_state() {
+        let params = Params {
+            lattice_dimension: 4096,
+            security_level: SecurityLevel::TC128,
+            plain_modulus: PlainModulus::batching(4096, 16).unwrap().value(),
+            scheme_type: SchemeType::Bfv,
+            coeff_modulus: CoefficientModulus::bfv_default(4096, SecurityLevel::TC128)
+                .unwrap()
+                .iter()
+                .map(|x| x.value())
+                .collect(),
+        };
+
+        let runtime = Runtime::new_fhe_with_mod_chain(&params, false).unwrap();
+        let (public_key, _) = runtime.generate_keys().unwrap();
+        let (other_key, _) = runtime.generate_keys().unwrap();
+
+        let fingerprint = runtime.register_keys("alice", &public_key).unwrap();
+        let state = runtime.export_state();
+
+        assert_eq!(state.version, RuntimeState::CURRENT_VERSION);
+        assert!(!state.expand_mod_chain);
+        assert_eq!(state.key_sets["alice"], fingerprint);
+        assert_eq!(
+            fingerprint.public_key,
+            public_key.public_key.data.fingerprint().unwrap()
+        );
+
+        let state: RuntimeState =
+            bincode::deserialize(&bincode::serialize(&state).unwrap()).unwrap();
+        let restored = Runtime::import_state(&state, &[], &[("alice", &public_key)]).unwrap();
+
+        assert_eq!(restored.params(), &params);
+        assert_eq!(restored.export_state(), state);
+
+        for key_sets in [
+            vec![],
+            vec![("alice", &other_key)],
+            vec![("alice", &public_key), ("bob", &other_key)],
+        ] {
+            assert!(matches!(
+                Runtime::import_state(&state, &[], &key_sets),
+                Err(Error::StateMismatch(_))
+            ));
+        }
+
+        let future = RuntimeState {
+            version: RuntimeState::CURRENT_VERSION + 1,
+            ..state
+        };
+
+        assert!(matches!(
+            Runtime::import_state(&future, &[], &[("alice", &public_key)]),
+            Err(Error::UnsupportedStateVersion(3))
+        ));
+    }
+}
@@ -0,0 +1,55 @@
+#![cfg(all(feature = "tokio", feature = "fhe"))]
+
+use std::sync::Arc;
+
+use sunscreen::{
+    fhe_program,
+    types::{bfv::Signed, Cipher},
+    Compiler, PlainModulusConstraint,
+};
+use sunscreen_runtime::{AsyncFheRuntime, Runtime};
+
+#[test]
+fn can_run_fhe_program_async() {
+    #[fhe_program(scheme = "bfv")]
+    fn mul(a: Cipher<Signed>, b: Cipher<Signed>) -> Cipher<Signed> {
+        a * b
+    }
+
+    let app = Compiler::new()
+        .fhe_program(mul)
+        .plain_modulus_constraint(PlainModulusConstraint::Raw(500))
+        .compile()
+        .unwrap();
+
+    let runtime = AsyncFheRuntime::new(Runtime::new_fhe(app.params()).unwrap());
+    let program = Arc::new(app.get_fhe_program(mul).unwrap().clone());
+
+    let executor = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    executor.block_on(async {
+        let (public_key, private_key) = runtime.generate_keys().await.unwrap();
+        let public_key = Arc::new(public_key);
+        let private_key = Arc::new(private_key);
+
+        let a = runtime
+            .encrypt(Signed::from(6), public_key.clone())
+            .await
+            .unwrap();
+        let b = runtime
+            .encrypt(Signed::from(7), public_key.clone())
+            .await
+            .unwrap();
+
+        let result = runtime.run(program, vec![a, b], public_key).await.unwrap();
+
+        let c: Signed = runtime
+            .decrypt(result[0].clone(), private_key)
+            .await
+            .unwrap();
+
+        assert_eq!(c, 42.into());
+    });
+}
@@ -1,7 +1,12 @@
-use seal_fhe::{CoefficientModulus, SecurityLevel};
+#![cfg(feature = "fhe")]
+
+use seal_fhe::{
+    BFVEvaluator, BfvEncryptionParametersBuilder, CoefficientModulus, Context, Evaluator, Modulus,
+    PlainModulus, SecurityLevel,
+};
 use sunscreen::types::bfv::Signed;
 use sunscreen_fhe_program::SchemeType;
-use sunscreen_runtime::{Ciphertext, Params, Runtime};
+use sunscreen_runtime::{Ciphertext, InnerCiphertext, Params, Runtime};
 
 #[test]
 fn can_roundtrip_ciphertexts_bincode() {
@@ -62,3 +67,111 @@ fn can_roundtrip_ciphertexts_json() {
     let actual: i64 = v.into();
     assert_eq!(actual, expected);
 }
+
+#[test]
+fn can_roundtrip_mod_switched_ciphertexts() {
+    let params = Params {
+        lattice_dimension: 8192,
+        plain_modulus: 1024,
+        coeff_modulus: CoefficientModulus::bfv_default(8192, SecurityLevel::TC128)
+            .unwrap()
+            .iter()
+            .map(|c| c.value())
+            .collect(),
+        security_level: SecurityLevel::TC128,
+        scheme_type: SchemeType::Bfv,
+    };
+
+    let runtime = Runtime::new_fhe(&params).unwrap();
+
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let expected: i64 = 42;
+
+    let mut c = runtime
+        .encrypt(Signed::from(expected), &public_key)
+        .unwrap();
+
+    mod_switch_to_next(&params, &mut c);
+
+    let c: Ciphertext = bincode::deserialize(&bincode::serialize(&c).unwrap()).unwrap();
+
+    let v: Signed = runtime.decrypt(&c, &private_key).unwrap();
+
+    let actual: i64 = v.into();
+    assert_eq!(actual, expected);
+
+    // Without the chain, the runtime decrypts at the ciphertext's level.
+    let runtime = Runtime::new_fhe_with_mod_chain(&params, false).unwrap();
+
+    let v: Signed = runtime.decrypt(&c, &private_key).unwrap();
+
+    let actual: i64 = v.into();
+    assert_eq!(actual, expected);
+}
+
+/**
+ * Switches the ciphertexts in `c` down one level with SEAL directly, as a
+ * program's outputs may be.
+ */
+fn mod_switch_to_next(params: &Params, c: &mut Ciphertext) {
+    let encryption_params = BfvEncryptionParametersBuilder::new()
+        .set_poly_modulus_degree(params.lattice_dimension)
+        .set_plain_modulus_u64(params.plain_modulus)
+        .set_coefficient_modulus(
+            params
+                .coeff_modulus
+                .iter()
+                .map(|x| Modulus::new(*x).unwrap())
+                .collect(),
+        )
+        .build()
+        .unwrap();
+
+    let context = Context::new(&encryption_params, true, params.security_level).unwrap();
+    let evaluator = BFVEvaluator::new(&context).unwrap();
+
+    match &mut c.inner {
+        InnerCiphertext::Seal(c) => {
+            c[0].data = evaluator.mod_switch_to_next(&c[0].data).unwrap();
+        }
+    };
+}
+
+#[test]
+fn can_use_mod_switched_ciphertexts_without_mod_chain() {
+    let params = Params {
+        lattice_dimension: 8192,
+        plain_modulus: PlainModulus::batching(8192, 20).unwrap().value(),
+        coeff_modulus: CoefficientModulus::bfv_default(8192, SecurityLevel::TC128)
+            .unwrap()
+            .iter()
+            .map(|c| c.value())
+            .collect(),
+        security_level: SecurityLevel::TC128,
+        scheme_type: SchemeType::Bfv,
+    };
+
+    let runtime = Runtime::new_fhe_with_mod_chain(&params, false).unwrap();
+    let (public_key, private_key) = runtime.generate_keys().unwrap();
+
+    let mut c = runtime.encrypt(Signed::from(42), &public_key).unwrap();
+    mod_switch_to_next(&params, &mut c);
+
+    assert!(runtime.measure_noise_budget(&c, &private_key).unwrap() > 0);
+    assert!(runtime.measure_invariant_noise(&c, &private_key).unwrap() < 0.5);
+
+    let mut slots = [0; 4];
+
+    runtime
+        .decrypt_signed_range(&c, &private_key, 0..4, &mut slots)
+        .unwrap();
+
+    let (new_public_key, new_private_key) = runtime.generate_keys().unwrap();
+    let c = runtime
+        .reencrypt(&c, &private_key, &new_public_key)
+        .unwrap();
+
+    let v: Signed = runtime.decrypt(&c, &new_private_key).unwrap();
+    assert_eq!(i64::from(v), 42);
+}
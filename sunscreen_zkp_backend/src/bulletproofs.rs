@@ -119,11 +119,16 @@ impl BulletproofsCircuit {
         }
     }
 
-    fn make_transcript(len: usize) -> Transcript {
+    fn make_transcript(len: usize, context: &[u8]) -> Transcript {
         let mut transcript = Transcript::new(b"R1CS");
         transcript.append_message(b"dom-sep", b"R1CS proof");
         transcript.append_u64(b"gen-len", len as u64);
 
+        // Proofs without a context keep the transcript they've always had.
+        if !context.is_empty() {
+            transcript.append_message(b"context", context);
+        }
+
         transcript
     }
 
@@ -371,7 +376,12 @@ fn constraint_count(graph: &ExecutableZkpProgram) -> Result<usize> {
 impl ZkpBackend for BulletproofsBackend {
     type Field = Scalar;
 
-    fn prove(&self, graph: &ExecutableZkpProgram, inputs: &[BigInt]) -> Result<Proof> {
+    fn prove_with_context(
+        &self,
+        graph: &ExecutableZkpProgram,
+        inputs: &[BigInt],
+        context: &[u8],
+    ) -> Result<Proof> {
         let expected_input_count = graph
             .node_weights()
             .filter(|x| matches!(x.operation, Operation::Input(_)))
@@ -393,7 +403,7 @@ impl ZkpBackend for BulletproofsBackend {
             .map(|x| x.try_into())
             .collect::<Result<Vec<Scalar>>>()?;
 
-        let transcript = BulletproofsCircuit::make_transcript(constraint_count);
+        let transcript = BulletproofsCircuit::make_transcript(constraint_count, context);
 
         let (pedersen_gens, bulletproof_gens) =
             BulletproofsCircuit::make_gens(2 * constraint_count);
@@ -418,7 +428,12 @@ impl ZkpBackend for BulletproofsBackend {
         Ok(Proof::Bulletproofs(Box::new(BulletproofsR1CSProof(proof))))
     }
 
-    fn verify(&self, graph: &ExecutableZkpProgram, proof: &Proof) -> Result<()> {
+    fn verify_with_context(
+        &self,
+        graph: &ExecutableZkpProgram,
+        proof: &Proof,
+        context: &[u8],
+    ) -> Result<()> {
         let proof = match proof {
             Proof::Bulletproofs(x) => x,
             _ => {
@@ -430,7 +445,7 @@ impl ZkpBackend for BulletproofsBackend {
 
         let constraint_count = constraint_count(graph)?;
 
-        let transcript = BulletproofsCircuit::make_transcript(constraint_count);
+        let transcript = BulletproofsCircuit::make_transcript(constraint_count, context);
         let (pedersen_gens, bulletproof_gens) =
             BulletproofsCircuit::make_gens(2 * constraint_count);
 
@@ -704,6 +719,27 @@ mod tests {
 
         backend.verify(&graph, &proof).unwrap();
 
+        // A proof bound to a context only verifies with that context.
+        let proof = backend
+            .prove_with_context(
+                &graph,
+                &[
+                    BigInt::from_u32(8),
+                    BigInt::from_u32(5),
+                    BigInt::from_u32(2),
+                ],
+                b"nonce 1",
+            )
+            .unwrap();
+
+        backend
+            .verify_with_context(&graph, &proof, b"nonce 1")
+            .unwrap();
+        assert!(backend
+            .verify_with_context(&graph, &proof, b"nonce 2")
+            .is_err());
+        assert!(backend.verify(&graph, &proof).is_err());
+
         // 8 * 5 + 3 == 42.
         // Verification should fail.
         let proof = backend
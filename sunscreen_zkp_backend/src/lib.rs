@@ -347,13 +347,47 @@ pub trait ZkpBackend {
      * Create a proof for the given executable Sunscreen
      * program with the given inputs.
      */
-    fn prove(&self, graph: &ExecutableZkpProgram, inputs: &[BigInt]) -> Result<Proof>;
+    fn prove(&self, graph: &ExecutableZkpProgram, inputs: &[BigInt]) -> Result<Proof> {
+        self.prove_with_context(graph, inputs, &[])
+    }
 
     /**
      * Verify the given proof for the given executable
      * Sunscreen program.
      */
-    fn verify(&self, graph: &ExecutableZkpProgram, proof: &Proof) -> Result<()>;
+    fn verify(&self, graph: &ExecutableZkpProgram, proof: &Proof) -> Result<()> {
+        self.verify_with_context(graph, proof, &[])
+    }
+
+    /**
+     * Like [`prove`](Self::prove), but binds `context` into the proof's
+     * transcript. The proof only verifies with
+     * [`verify_with_context`](Self::verify_with_context) and the same
+     * `context`.
+     *
+     * # Remarks
+     * Use this to tie a proof to the message it's sent in (e.g. a nonce
+     * and timestamp), so it can't be replayed in another message. An empty
+     * `context` produces the same proof as [`prove`](Self::prove).
+     */
+    fn prove_with_context(
+        &self,
+        graph: &ExecutableZkpProgram,
+        inputs: &[BigInt],
+        context: &[u8],
+    ) -> Result<Proof>;
+
+    /**
+     * Verify the given proof, created with
+     * [`prove_with_context`](Self::prove_with_context) and the given
+     * `context`, for the given executable Sunscreen program.
+     */
+    fn verify_with_context(
+        &self,
+        graph: &ExecutableZkpProgram,
+        proof: &Proof,
+        context: &[u8],
+    ) -> Result<()>;
 
     /**
      * JIT the given frontend-compiled ZKP program